@@ -0,0 +1,112 @@
+// Copyright (c) 2026 - present Nicholas D. Crosbie
+// SPDX-License-Identifier: MIT
+
+//! hindsight-common: Shared helpers for the hindsight-mcp crates
+//!
+//! This library crate holds small utilities used by more than one of the
+//! hindsight crates, so that each crate does not carry its own copy.
+
+#![warn(missing_docs)]
+
+use std::error::Error;
+
+/// Format an error and every underlying cause, separated by `" → "`
+///
+/// thiserror messages usually embed their source's message, so that suffix is
+/// trimmed from each level. A level that adds nothing beyond its source, such
+/// as a transparent wrapper or a variant named like the error it wraps, is
+/// skipped.
+#[must_use]
+pub fn display_chain(error: &dyn Error) -> String {
+    let mut levels: Vec<String> = Vec::new();
+    let mut current = Some(error);
+
+    while let Some(err) = current {
+        let message = err.to_string();
+        let source = err.source();
+        let own = source
+            .and_then(|src| {
+                message
+                    .strip_suffix(&src.to_string())
+                    .map(|m| m.trim_end_matches([':', ' ']).to_string())
+            })
+            .unwrap_or(message);
+        if !own.is_empty() && levels.last() != Some(&own) {
+            levels.push(own);
+        }
+        current = source;
+    }
+
+    levels.join(" → ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use similar_asserts::assert_eq;
+    use std::fmt;
+
+    /// An error with a fixed message and an optional source
+    #[derive(Debug)]
+    struct Level {
+        message: String,
+        source: Option<Box<Level>>,
+    }
+
+    impl Level {
+        fn new(message: &str, source: Option<Level>) -> Self {
+            Self {
+                message: message.to_string(),
+                source: source.map(Box::new),
+            }
+        }
+    }
+
+    impl fmt::Display for Level {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.message)
+        }
+    }
+
+    impl Error for Level {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source.as_deref().map(|s| s as &(dyn Error + 'static))
+        }
+    }
+
+    #[test]
+    fn test_display_chain_single_level() {
+        let err = Level::new("workspace not found", None);
+        assert_eq!(display_chain(&err), "workspace not found");
+    }
+
+    #[test]
+    fn test_display_chain_trims_embedded_source() {
+        let err = Level::new("IO error: disk full", Some(Level::new("disk full", None)));
+        assert_eq!(display_chain(&err), "IO error → disk full");
+    }
+
+    #[test]
+    fn test_display_chain_keeps_unrelated_messages() {
+        let err = Level::new("ingest failed", Some(Level::new("disk full", None)));
+        assert_eq!(display_chain(&err), "ingest failed → disk full");
+    }
+
+    #[test]
+    fn test_display_chain_skips_repeated_levels() {
+        let err = Level::new(
+            "Git error: Git error: object not found",
+            Some(Level::new(
+                "Git error: object not found",
+                Some(Level::new("object not found", None)),
+            )),
+        );
+        assert_eq!(display_chain(&err), "Git error → object not found");
+    }
+
+    #[test]
+    fn test_display_chain_skips_transparent_levels() {
+        let err = Level::new("disk full", Some(Level::new("disk full", None)));
+        assert_eq!(display_chain(&err), "disk full");
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright (c) 2026 - present Nicholas D. Crosbie
+// SPDX-License-Identifier: MIT
+
+//! Aider chat history parsing and discovery
+//!
+//! [Aider](https://aider.chat) records its conversations in a Markdown file named
+//! `.aider.chat.history.md` in the project directory. Each exchange is split into
+//! sections introduced by `#### human` and `#### ai` headers, and every new chat
+//! begins with a `# aider chat started at <timestamp>` line.
+//!
+//! This module provides:
+//! - [`parse_aider_chatlog`] for converting a chat history into a [`ChatSession`]
+//! - [`AiderSessionDiscovery`] for finding chat history files in a workspace
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use tracing::debug;
+
+use crate::error::CopilotError;
+use crate::session::{
+    ChatMessage, ChatSession, MessageRole, Variable, closes_fence, parse_code_blocks,
+    parse_opening_fence,
+};
+
+/// File name Aider uses for its chat history
+pub const AIDER_CHAT_HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Prefix of the line Aider writes at the start of each chat
+const CHAT_STARTED_PREFIX: &str = "# aider chat started at ";
+
+/// Maximum directory depth searched below the workspace root
+const MAX_DISCOVERY_DEPTH: usize = 2;
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse an Aider chat history into a chat session
+///
+/// Each `#### human` section becomes a user message and each `#### ai` section an
+/// assistant message. Code blocks inside a section, found the same way as by
+/// [`ChatMessage::extract_code_blocks`], are attached to its message as
+/// [`Variable`]s of kind `"code_block"`, named after the fence's language hint.
+/// Headers that appear inside a code block are treated as content.
+///
+/// Aider does not record per-message times, so every message is stamped with the
+/// time of the first `# aider chat started at` line (or the current time if absent).
+/// The returned session has an empty `workspace_id`.
+///
+/// # Errors
+///
+/// Returns `CopilotError::InvalidChatLog` if the content is non-empty but contains
+/// no `#### human` or `#### ai` sections.
+pub fn parse_aider_chatlog(content: &str) -> Result<ChatSession, CopilotError> {
+    let started_at = content
+        .lines()
+        .find_map(|line| line.strip_prefix(CHAT_STARTED_PREFIX))
+        .and_then(parse_started_at);
+
+    let timestamp = started_at.unwrap_or_else(Utc::now);
+    let session_id = match started_at {
+        Some(ts) => format!("aider-{}", ts.format("%Y%m%dT%H%M%S")),
+        None => "aider".to_string(),
+    };
+
+    let mut session = ChatSession::new(session_id, String::new(), timestamp);
+    let mut current: Option<(MessageRole, Vec<&str>)> = None;
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in content.lines() {
+        match fence {
+            Some((marker, len)) => {
+                if closes_fence(line, marker, len) {
+                    fence = None;
+                }
+            }
+            None => {
+                if let Some(role) = section_role(line) {
+                    if let Some((role, lines)) = current.take() {
+                        session.add_message(build_message(role, &lines, timestamp));
+                    }
+                    current = Some((role, Vec::new()));
+                    continue;
+                }
+                if let Some(open) = parse_opening_fence(line) {
+                    fence = Some((open.marker, open.len));
+                }
+            }
+        }
+
+        if let Some((_, ref mut lines)) = current {
+            lines.push(line);
+        }
+    }
+
+    if let Some((role, lines)) = current {
+        session.add_message(build_message(role, &lines, timestamp));
+    }
+
+    if session.is_empty() && !content.trim().is_empty() {
+        return Err(CopilotError::InvalidChatLog {
+            message: "no `#### human` or `#### ai` sections found".to_string(),
+        });
+    }
+
+    Ok(session)
+}
+
+/// Parse an Aider chat history file, assigning it to a workspace
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed.
+pub fn parse_aider_file(path: &Path, workspace_id: &str) -> Result<ChatSession, CopilotError> {
+    let content = fs::read_to_string(path)?;
+    let mut session = parse_aider_chatlog(&content)?;
+    session.workspace_id = workspace_id.to_string();
+    Ok(session)
+}
+
+/// Map a section header line to its message role
+fn section_role(line: &str) -> Option<MessageRole> {
+    let header = line.trim().strip_prefix("####")?.trim();
+    if header.eq_ignore_ascii_case("human") {
+        Some(MessageRole::User)
+    } else if header.eq_ignore_ascii_case("ai") {
+        Some(MessageRole::Assistant)
+    } else {
+        None
+    }
+}
+
+/// Parse the timestamp Aider writes after `# aider chat started at`
+fn parse_started_at(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// Build a chat message from the lines of a section
+fn build_message(role: MessageRole, lines: &[&str], timestamp: DateTime<Utc>) -> ChatMessage {
+    let content = lines.join("\n").trim().to_string();
+    let code_blocks = parse_code_blocks(&content)
+        .into_iter()
+        .map(|block| Variable {
+            kind: "code_block".to_string(),
+            name: block.language.unwrap_or_else(|| "text".to_string()),
+            value: Some(block.content),
+        })
+        .collect();
+
+    let message = match role {
+        MessageRole::User => ChatMessage::user(content, timestamp),
+        _ => ChatMessage::assistant(content, timestamp),
+    };
+    message.with_variables(code_blocks)
+}
+
+// ============================================================================
+// Discovery
+// ============================================================================
+
+/// Discovery engine for Aider chat history files within a workspace
+#[derive(Debug)]
+pub struct AiderSessionDiscovery {
+    /// Workspace root to search
+    root: PathBuf,
+}
+
+impl AiderSessionDiscovery {
+    /// Create a discovery rooted at the given workspace directory
+    #[must_use]
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Get the workspace root path
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Find all Aider chat history files
+    ///
+    /// Searches the workspace root and its subdirectories up to two levels deep,
+    /// skipping hidden directories.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CopilotError::WorkspaceStorageNotFound` if the root doesn't exist,
+    /// or an IO error if the root cannot be read.
+    pub fn discover(&self) -> Result<Vec<PathBuf>, CopilotError> {
+        if !self.root.is_dir() {
+            return Err(CopilotError::WorkspaceStorageNotFound {
+                path: self.root.display().to_string(),
+            });
+        }
+
+        let mut found = Vec::new();
+        let mut pending = vec![(self.root.clone(), 0)];
+
+        while let Some((dir, depth)) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if depth == 0 => return Err(e.into()),
+                Err(e) => {
+                    debug!("Failed to read directory {:?}: {}", dir, e);
+                    continue;
+                }
+            };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+
+                if path.is_dir() {
+                    if depth < MAX_DISCOVERY_DEPTH && !name.starts_with('.') {
+                        pending.push((path, depth + 1));
+                    }
+                } else if name == AIDER_CHAT_HISTORY_FILE {
+                    found.push(path);
+                }
+            }
+        }
+
+        found.sort();
+        Ok(found)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use similar_asserts::assert_eq;
+
+    const SAMPLE: &str = "\
+# aider chat started at 2026-01-15 09:30:00
+
+#### human
+Add a function that doubles a number.
+
+#### ai
+Here is the function:
+
+```rust
+fn double(x: i32) -> i32 {
+    x * 2
+}
+```
+
+And a test:
+
+```
+assert_eq!(double(2), 4);
+```
+
+#### human
+Thanks!
+";
+
+    #[test]
+    fn test_parse_aider_chatlog_roles() {
+        let session = parse_aider_chatlog(SAMPLE).expect("parse");
+
+        assert_eq!(session.message_count(), 3);
+        assert_eq!(session.messages[0].role, MessageRole::User);
+        assert_eq!(session.messages[1].role, MessageRole::Assistant);
+        assert_eq!(session.messages[2].role, MessageRole::User);
+        assert_eq!(
+            session.messages[0].content,
+            "Add a function that doubles a number."
+        );
+        assert_eq!(session.messages[2].content, "Thanks!");
+    }
+
+    #[test]
+    fn test_parse_aider_chatlog_started_at() {
+        let session = parse_aider_chatlog(SAMPLE).expect("parse");
+
+        assert_eq!(session.id, "aider-20260115T093000");
+        assert_eq!(session.created_at.to_rfc3339(), "2026-01-15T09:30:00+00:00");
+        assert!(session.workspace_id.is_empty());
+    }
+
+    #[test]
+    fn test_parse_aider_chatlog_code_blocks() {
+        let session = parse_aider_chatlog(SAMPLE).expect("parse");
+        let blocks = &session.messages[1].variables;
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].kind, "code_block");
+        assert_eq!(blocks[0].name, "rust");
+        assert_eq!(
+            blocks[0].value.as_deref(),
+            Some("fn double(x: i32) -> i32 {\n    x * 2\n}")
+        );
+        assert_eq!(blocks[1].name, "text");
+        assert_eq!(
+            blocks[1].value.as_deref(),
+            Some("assert_eq!(double(2), 4);")
+        );
+    }
+
+    #[test]
+    fn test_parse_aider_chatlog_header_inside_code_block() {
+        let content = "#### ai\n```markdown\n#### human\nnot a section\n```\n";
+        let session = parse_aider_chatlog(content).expect("parse");
+
+        assert_eq!(session.message_count(), 1);
+        assert!(session.messages[0].content.contains("#### human"));
+        assert_eq!(
+            session.messages[0].variables[0].value.as_deref(),
+            Some("#### human\nnot a section")
+        );
+    }
+
+    #[test]
+    fn test_parse_aider_chatlog_nested_fence() {
+        let content = "#### ai\n````markdown\n```\n#### human\n````\n#### human\nthanks\n";
+        let session = parse_aider_chatlog(content).expect("parse");
+
+        assert_eq!(session.message_count(), 2);
+        assert_eq!(
+            session.messages[0].variables[0].value.as_deref(),
+            Some("```\n#### human")
+        );
+        assert_eq!(session.messages[1].content, "thanks");
+    }
+
+    #[test]
+    fn test_parse_aider_chatlog_empty() {
+        let session = parse_aider_chatlog("").expect("parse");
+        assert!(session.is_empty());
+    }
+
+    #[test]
+    fn test_parse_aider_chatlog_no_sections() {
+        let result = parse_aider_chatlog("just some notes\n");
+        assert!(matches!(result, Err(CopilotError::InvalidChatLog { .. })));
+    }
+
+    #[test]
+    fn test_section_role() {
+        assert_eq!(section_role("#### human"), Some(MessageRole::User));
+        assert_eq!(section_role("####  AI "), Some(MessageRole::Assistant));
+        assert_eq!(section_role("#### fix the tests"), None);
+        assert_eq!(section_role("### human"), None);
+    }
+
+    #[test]
+    fn test_discover_respects_depth() {
+        let root = std::env::temp_dir().join(format!("hindsight-aider-{}", std::process::id()));
+        let nested = root.join("a/b");
+        let too_deep = root.join("a/b/c");
+        let hidden = root.join(".git");
+        for dir in [&too_deep, &hidden] {
+            fs::create_dir_all(dir).expect("create dirs");
+        }
+        for dir in [&root, &nested, &too_deep, &hidden] {
+            fs::write(dir.join(AIDER_CHAT_HISTORY_FILE), "#### human\nhi\n").expect("write");
+        }
+
+        let found = AiderSessionDiscovery::new(root.clone())
+            .discover()
+            .expect("discover");
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(
+            found,
+            vec![
+                root.join(AIDER_CHAT_HISTORY_FILE),
+                root.join("a/b").join(AIDER_CHAT_HISTORY_FILE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discover_missing_root() {
+        let discovery = AiderSessionDiscovery::new(PathBuf::from("/nonexistent/aider/root"));
+        assert!(matches!(
+            discovery.discover(),
+            Err(CopilotError::WorkspaceStorageNotFound { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod property_tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// Parsing arbitrary text never panics
+        #[test]
+        fn prop_parse_never_panics(content in ".*") {
+            let _ = parse_aider_chatlog(&content);
+        }
+
+        /// Every header produces exactly one message with the right role
+        #[test]
+        fn prop_section_count_matches(roles in prop::collection::vec(any::<bool>(), 1..10)) {
+            let content: String = roles
+                .iter()
+                .map(|human| if *human { "#### human\nq\n" } else { "#### ai\na\n" })
+                .collect();
+            let session = parse_aider_chatlog(&content).expect("parse");
+
+            prop_assert_eq!(session.message_count(), roles.len());
+            for (msg, human) in session.messages.iter().zip(&roles) {
+                let expected = if *human { MessageRole::User } else { MessageRole::Assistant };
+                prop_assert_eq!(msg.role, expected);
+            }
+        }
+    }
+}
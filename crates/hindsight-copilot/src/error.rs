@@ -30,10 +30,75 @@ pub enum CopilotError {
         path: String,
     },
 
+    /// Chat log content could not be interpreted
+    #[error("Invalid chat log: {message}")]
+    InvalidChatLog {
+        /// Description of why the chat log was invalid
+        message: String,
+    },
+
     /// Chat session not found
     #[error("Chat session not found: {session_id}")]
     SessionNotFound {
         /// The session ID that could not be found
         session_id: String,
     },
+
+    /// Chat session file is in a format that cannot be parsed
+    #[error(
+        "Unsupported session format: {format}{}",
+        .version.map(|v| format!(" (version {v})")).unwrap_or_default()
+    )]
+    UnsupportedFormat {
+        /// Description of the detected format
+        format: String,
+        /// The session's `version` field, if present
+        version: Option<u32>,
+    },
+}
+
+impl CopilotError {
+    /// Format this error and every underlying cause, separated by `" → "`
+    #[must_use]
+    pub fn display_chain(&self) -> String {
+        hindsight_common::display_chain(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn test_display_chain_nested() {
+        let err = CopilotError::Io(std::io::Error::other("disk full"));
+        assert_eq!(err.display_chain(), "IO error → disk full");
+    }
+
+    #[test]
+    fn test_display_chain_single_level() {
+        let err = CopilotError::SessionNotFound {
+            session_id: "abc".to_string(),
+        };
+        assert_eq!(err.display_chain(), "Chat session not found: abc");
+    }
+
+    #[test]
+    fn test_unsupported_format_display() {
+        let err = CopilotError::UnsupportedFormat {
+            format: "unknown".to_string(),
+            version: Some(1),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Unsupported session format: unknown (version 1)"
+        );
+
+        let err = CopilotError::UnsupportedFormat {
+            format: "unknown".to_string(),
+            version: None,
+        };
+        assert_eq!(err.to_string(), "Unsupported session format: unknown");
+    }
 }
@@ -19,20 +19,24 @@
 //!
 //! Copilot logs follow JSON Stream / LSP Trace format when log level is set to `Trace`.
 //!
+//! Aider chat histories (`.aider.chat.history.md`) are supported via the [`aider`] module.
+//!
 //! ## Session Discovery
 //!
-//! Use [`SessionDiscovery`] to find chat sessions across all workspaces:
+//! Use [`SessionDiscovery`] to find chat sessions across all workspaces of
+//! VS Code, VS Code Insiders or Cursor:
 //!
 //! ```rust,no_run
-//! use hindsight_copilot::session::{SessionDiscovery, parse_session_file};
+//! use hindsight_copilot::session::{EditionHint, SessionDiscovery, parse_session_file};
 //!
-//! let discovery = SessionDiscovery::new().expect("find storage");
+//! let discovery = SessionDiscovery::new(EditionHint::Any).expect("find storage");
 //! for session in discovery.discover_sessions().expect("enumerate") {
 //!     let parsed = parse_session_file(&session.path, &session.workspace_storage_id);
 //!     println!("{:?}", parsed);
 //! }
 //! ```
 
+pub mod aider;
 pub mod error;
 pub mod lsp;
 pub mod parser;
@@ -42,15 +46,17 @@ pub use error::CopilotError;
 
 // Re-export session discovery types at crate level for convenience
 pub use session::{
-    ChatMessage, ChatSession, DiscoveredSession, MessageRole, SessionDiscovery, Variable,
-    WorkspaceInfo, default_chat_sessions_dir, parse_session_file, parse_session_json,
+    ChatMessage, ChatSession, CodeBlock, DiscoveredSession, EditionHint, MessageRole,
+    SessionDiscovery, SessionFormat, Variable, WorkspaceInfo, chat_sessions_dir,
+    default_chat_sessions_dir, detect_session_format, from_compact_json, parse_session_file,
+    parse_session_json, session_duration_minutes, to_compact_json,
 };
 
 /// Re-export commonly used types
 pub mod prelude {
     pub use crate::error::CopilotError;
     pub use crate::session::{
-        ChatMessage, ChatSession, DiscoveredSession, MessageRole, SessionDiscovery, Variable,
-        WorkspaceInfo,
+        ChatMessage, ChatSession, CodeBlock, DiscoveredSession, EditionHint, MessageRole,
+        SessionDiscovery, Variable, WorkspaceInfo,
     };
 }
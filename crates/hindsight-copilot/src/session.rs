@@ -5,10 +5,15 @@
 //!
 //! This module provides:
 //! - [`ChatSession`] and [`ChatMessage`] types for representing chat data
-//! - [`SessionDiscovery`] for finding VS Code chat session files
+//! - [`SessionDiscovery`] for finding chat session files of VS Code, VS Code
+//!   Insiders and Cursor
 //! - [`parse_session_file`] for parsing session JSON into domain types
+//! - [`detect_session_format`] for identifying the session file version
 //! - [`WorkspaceInfo`] for correlating workspaces with their storage IDs
+//! - [`to_compact_json`] and [`from_compact_json`] for compact export
+//! - [`ChatSession::to_markdown`] for reading sessions outside VS Code
 
+use std::fmt::Write;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -43,6 +48,10 @@ struct RawSession {
     mode: Option<RawMode>,
     #[serde(default)]
     selected_model: Option<RawSelectedModel>,
+    #[serde(default)]
+    system_prompt: Option<String>,
+    #[serde(default)]
+    context: Option<serde_json::Value>,
 }
 
 /// Raw request from the session
@@ -61,6 +70,8 @@ struct RawRequest {
     timestamp: Option<i64>,
     #[serde(default)]
     model_id: Option<String>,
+    #[serde(default)]
+    role: Option<String>,
 }
 
 /// Raw message structure
@@ -142,6 +153,53 @@ struct RawSelectedModel {
     identifier: Option<String>,
 }
 
+/// Raw session file structure for version 2 sessions
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSessionV2 {
+    session_id: String,
+    #[serde(default)]
+    creation_date: Option<i64>,
+    #[serde(default)]
+    last_message_date: Option<i64>,
+    #[serde(default)]
+    requests: Vec<RawRequestV2>,
+}
+
+/// Raw request from a version 2 session
+///
+/// Version 2 requests carry no ID, role, or per-request timestamp, and the
+/// message may be stored as a bare string.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawRequestV2 {
+    #[serde(default)]
+    message: Option<RawMessageV2>,
+    #[serde(default)]
+    variable_data: Option<RawVariableData>,
+    #[serde(default)]
+    response: Vec<RawResponsePart>,
+    #[serde(default)]
+    agent: Option<RawAgent>,
+}
+
+/// Raw version 2 message, either plain text or a structured message
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum RawMessageV2 {
+    Text(String),
+    Structured(RawMessage),
+}
+
+impl RawMessageV2 {
+    fn text(&self) -> &str {
+        match self {
+            Self::Text(text) => text,
+            Self::Structured(message) => &message.text,
+        }
+    }
+}
+
 // ============================================================================
 // Domain Types
 // ============================================================================
@@ -233,11 +291,145 @@ impl ChatSession {
             .collect()
     }
 
+    /// Get all system messages
+    #[must_use]
+    pub fn system_messages(&self) -> Vec<&ChatMessage> {
+        self.messages
+            .iter()
+            .filter(|m| m.role == MessageRole::System)
+            .collect()
+    }
+
     /// Check if session is empty
     #[must_use]
     pub fn is_empty(&self) -> bool {
         self.messages.is_empty()
     }
+
+    /// Estimate the number of LLM tokens in this session
+    ///
+    /// Uses the common approximation of four characters (bytes) per token,
+    /// summed across the content of all messages.
+    #[must_use]
+    pub fn estimated_tokens(&self) -> u64 {
+        let total_len: usize = self.messages.iter().map(ChatMessage::content_len).sum();
+        (total_len / 4) as u64
+    }
+
+    /// Estimate the API cost of this session in US dollars
+    #[must_use]
+    pub fn estimated_cost_usd(&self, cost_per_1k_tokens: f64) -> f64 {
+        self.estimated_tokens() as f64 / 1000.0 * cost_per_1k_tokens
+    }
+
+    /// Render the session as a Markdown document
+    ///
+    /// Session metadata goes in a YAML front-matter block. Each message
+    /// follows as a `## User`, `## Copilot` or `## System` section with its
+    /// content unchanged, except that lines which would read as one of those
+    /// headings are escaped and an unclosed code fence is closed. Variables
+    /// with a value are listed as `[name](value)` links.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "---");
+        let _ = writeln!(out, "id: {}", yaml_string(&self.id));
+        let _ = writeln!(out, "workspace_id: {}", yaml_string(&self.workspace_id));
+        if let Some(model) = &self.model {
+            let _ = writeln!(out, "model: {}", yaml_string(model));
+        }
+        if let Some(mode) = &self.mode {
+            let _ = writeln!(out, "mode: {}", yaml_string(mode));
+        }
+        let _ = writeln!(out, "created_at: {}", self.created_at.to_rfc3339());
+        let _ = writeln!(out, "updated_at: {}", self.updated_at.to_rfc3339());
+        let _ = writeln!(out, "messages: {}", self.messages.len());
+        let _ = writeln!(out, "---");
+
+        for message in &self.messages {
+            let _ = writeln!(out, "\n## {}\n", message.role.display_name());
+            match &message.agent {
+                Some(agent) => {
+                    let _ = writeln!(out, "_{} · {}_\n", message.timestamp.to_rfc3339(), agent);
+                }
+                None => {
+                    let _ = writeln!(out, "_{}_\n", message.timestamp.to_rfc3339());
+                }
+            }
+            write_markdown_content(&mut out, &message.content);
+
+            if !message.variables.is_empty() {
+                out.push('\n');
+                for variable in &message.variables {
+                    match variable.value.as_deref() {
+                        Some(value) if !value.contains('\n') => {
+                            let _ = writeln!(out, "- [{}]({})", variable.name, value);
+                        }
+                        _ => {
+                            let _ = writeln!(out, "- {}", variable.name);
+                        }
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Quote a string for YAML front matter
+///
+/// JSON strings are valid YAML double-quoted scalars.
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Append message content, keeping it from breaking the session's structure
+fn write_markdown_content(out: &mut String, content: &str) {
+    let mut fence: Option<(char, usize)> = None;
+    for line in content.lines() {
+        match fence {
+            Some((marker, len)) => {
+                if closes_fence(line, marker, len) {
+                    fence = None;
+                }
+            }
+            None => {
+                if let Some(open) = parse_opening_fence(line) {
+                    fence = Some((open.marker, open.len));
+                } else if is_role_heading(line) {
+                    out.push('\\');
+                }
+            }
+        }
+        let _ = writeln!(out, "{line}");
+    }
+    if let Some((marker, len)) = fence {
+        let _ = writeln!(out, "{}", marker.to_string().repeat(len));
+    }
+}
+
+/// Check whether a line reads as a `to_markdown` message heading
+fn is_role_heading(line: &str) -> bool {
+    line.trim_start().strip_prefix("## ").is_some_and(|title| {
+        [
+            MessageRole::User,
+            MessageRole::Assistant,
+            MessageRole::System,
+        ]
+        .iter()
+        .any(|role| title.trim() == role.display_name())
+    })
+}
+
+/// Get the time between a session's first and last message, in minutes
+///
+/// Returns `None` if the session has no messages.
+#[must_use]
+pub fn session_duration_minutes(session: &ChatSession) -> Option<f64> {
+    let first = session.messages.first()?;
+    let last = session.messages.last()?;
+    Some((last.timestamp - first.timestamp).num_seconds() as f64 / 60.0)
 }
 
 /// Represents a message in a chat session
@@ -294,6 +486,18 @@ impl ChatMessage {
         }
     }
 
+    /// Create a new system message
+    #[must_use]
+    pub fn system(content: String, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            role: MessageRole::System,
+            content,
+            timestamp,
+            agent: None,
+            variables: Vec::new(),
+        }
+    }
+
     /// Set the agent for this message
     #[must_use]
     pub fn with_agent(mut self, agent: String) -> Self {
@@ -319,6 +523,176 @@ impl ChatMessage {
     pub fn has_agent(&self) -> bool {
         self.agent.is_some()
     }
+
+    /// Extract the Markdown code blocks in this message, in order
+    ///
+    /// Fenced blocks may use three or more backticks or tildes; a longer
+    /// fence can wrap content containing shorter ones. The first word of the
+    /// info string is taken as the language. Blocks indented by four spaces
+    /// are also extracted, unless they continue a list item. A fence left
+    /// open runs to the end of the message.
+    #[must_use]
+    pub fn extract_code_blocks(&self) -> Vec<CodeBlock> {
+        parse_code_blocks(&self.content)
+    }
+}
+
+/// A code block found in a chat message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// Language hint from the fence's info string, e.g. "rust"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Code inside the block, without the fences or indentation
+    pub content: String,
+}
+
+/// An open fenced code block
+pub(crate) struct OpenFence {
+    /// Fence character, '`' or '~'
+    pub(crate) marker: char,
+    /// Number of fence characters in the opening fence
+    pub(crate) len: usize,
+    /// Indentation of the opening fence, removed from content lines
+    indent: usize,
+    language: Option<String>,
+    lines: Vec<String>,
+}
+
+/// Parse an opening code fence of up to three spaces' indentation
+pub(crate) fn parse_opening_fence(line: &str) -> Option<OpenFence> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = rest.len() - rest.trim_start_matches(marker).len();
+    let info = rest[len..].trim();
+    if len < 3 || (marker == '`' && info.contains('`')) {
+        return None;
+    }
+    Some(OpenFence {
+        marker,
+        len,
+        indent,
+        language: info.split_whitespace().next().map(str::to_string),
+        lines: Vec::new(),
+    })
+}
+
+/// Check whether a line closes a fence opened with `len` `marker` characters
+pub(crate) fn closes_fence(line: &str, marker: char, len: usize) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return false;
+    }
+    let rest = trimmed.trim_start_matches(marker);
+    trimmed.len() - rest.len() >= len && rest.trim().is_empty()
+}
+
+/// Strip the four-column indentation of an indented code line
+fn strip_code_indent(line: &str) -> Option<&str> {
+    line.strip_prefix("    ")
+        .or_else(|| line.strip_prefix('\t'))
+}
+
+/// Check whether a line starts a Markdown list item
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = &line[digits..];
+    if digits > 0 {
+        return rest.starts_with(". ") || rest.starts_with(") ");
+    }
+    ["- ", "* ", "+ "]
+        .iter()
+        .any(|bullet| line.starts_with(bullet))
+}
+
+/// Extract fenced and indented code blocks from Markdown text
+pub(crate) fn parse_code_blocks(content: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut fence: Option<OpenFence> = None;
+    let mut indented: Vec<&str> = Vec::new();
+    let mut after_blank = true;
+    let mut in_list = false;
+
+    let flush_indented = |indented: &mut Vec<&str>, blocks: &mut Vec<CodeBlock>| {
+        while indented.last().is_some_and(|l| l.trim().is_empty()) {
+            indented.pop();
+        }
+        if !indented.is_empty() {
+            blocks.push(CodeBlock {
+                language: None,
+                content: indented.join("\n"),
+            });
+            indented.clear();
+        }
+    };
+
+    for line in content.lines() {
+        if let Some(open) = fence.as_mut() {
+            if closes_fence(line, open.marker, open.len) {
+                let open = fence.take().expect("fence is open");
+                blocks.push(CodeBlock {
+                    language: open.language,
+                    content: open.lines.join("\n"),
+                });
+                after_blank = false;
+            } else {
+                let unindent = line.len() - line.trim_start_matches(' ').len();
+                open.lines
+                    .push(line[unindent.min(open.indent)..].to_string());
+            }
+            continue;
+        }
+
+        if !indented.is_empty() {
+            if line.trim().is_empty() {
+                indented.push("");
+                continue;
+            }
+            if let Some(code) = strip_code_indent(line) {
+                indented.push(code);
+                continue;
+            }
+            flush_indented(&mut indented, &mut blocks);
+        }
+
+        if line.trim().is_empty() {
+            after_blank = true;
+            continue;
+        }
+
+        // Indented code cannot interrupt a paragraph or continue a list item
+        if after_blank
+            && !in_list
+            && let Some(code) = strip_code_indent(line)
+        {
+            indented.push(code);
+            continue;
+        }
+
+        if let Some(open) = parse_opening_fence(line) {
+            fence = Some(open);
+        } else if is_list_item(line) {
+            in_list = true;
+        } else if line.starts_with(|c: char| !c.is_whitespace()) {
+            in_list = false;
+        }
+        after_blank = false;
+    }
+
+    if let Some(open) = fence {
+        blocks.push(CodeBlock {
+            language: open.language,
+            content: open.lines.join("\n"),
+        });
+    }
+    flush_indented(&mut indented, &mut blocks);
+
+    blocks
 }
 
 /// Message role in a chat conversation
@@ -345,20 +719,69 @@ impl MessageRole {
     }
 }
 
-/// Get the default Copilot chat sessions directory for the current OS
-#[must_use]
-pub fn default_chat_sessions_dir() -> Option<std::path::PathBuf> {
-    #[cfg(target_os = "macos")]
-    {
-        dirs::home_dir().map(|h| h.join("Library/Application Support/Code/User/workspaceStorage"))
+/// Editor whose workspace storage holds the chat sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EditionHint {
+    /// Stable VS Code
+    #[default]
+    VSCode,
+    /// VS Code Insiders
+    VSCodeInsiders,
+    /// Cursor IDE
+    Cursor,
+    /// Every supported editor that is installed
+    Any,
+}
+
+impl EditionHint {
+    /// Get the display name for this editor
+    #[must_use]
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::VSCode => "VS Code",
+            Self::VSCodeInsiders => "VS Code Insiders",
+            Self::Cursor => "Cursor",
+            Self::Any => "Any",
+        }
+    }
+
+    /// The editor's directory under the user config directory
+    ///
+    /// Returns `None` for [`EditionHint::Any`].
+    #[must_use]
+    pub fn config_dir_name(&self) -> Option<&'static str> {
+        match self {
+            Self::VSCode => Some("Code"),
+            Self::VSCodeInsiders => Some("Code - Insiders"),
+            Self::Cursor => Some("Cursor"),
+            Self::Any => None,
+        }
+    }
+
+    /// The editors this hint covers
+    fn editions(self) -> &'static [Self] {
+        match self {
+            Self::VSCode => &[Self::VSCode],
+            Self::VSCodeInsiders => &[Self::VSCodeInsiders],
+            Self::Cursor => &[Self::Cursor],
+            Self::Any => &[Self::VSCode, Self::VSCodeInsiders, Self::Cursor],
+        }
     }
-    #[cfg(target_os = "windows")]
+}
+
+/// Get the directory holding per-editor settings for the current OS
+///
+/// This is `~/Library/Application Support` on macOS, `%APPDATA%` on Windows
+/// and `~/.config` on Linux. Every supported editor keeps its workspace
+/// storage in `<editor>/User/workspaceStorage` beneath it.
+fn editor_config_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
     {
-        dirs::config_dir().map(|c| c.join("Code/User/workspaceStorage"))
+        dirs::home_dir().map(|h| h.join("Library/Application Support"))
     }
-    #[cfg(target_os = "linux")]
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
     {
-        dirs::config_dir().map(|c| c.join("Code/User/workspaceStorage"))
+        dirs::config_dir()
     }
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
@@ -366,6 +789,30 @@ pub fn default_chat_sessions_dir() -> Option<std::path::PathBuf> {
     }
 }
 
+/// The workspace storage directory of an editor under a config directory
+fn workspace_storage_dir(config_dir: &Path, edition: EditionHint) -> Option<PathBuf> {
+    edition
+        .config_dir_name()
+        .map(|name| config_dir.join(name).join("User").join("workspaceStorage"))
+}
+
+/// Get the chat sessions directory of an editor for the current OS
+///
+/// Returns `None` for [`EditionHint::Any`] or on unsupported platforms.
+#[must_use]
+pub fn chat_sessions_dir(edition: EditionHint) -> Option<PathBuf> {
+    workspace_storage_dir(&editor_config_dir()?, edition)
+}
+
+/// Get the default Copilot chat sessions directory for the current OS
+///
+/// This is the stable VS Code location; see [`chat_sessions_dir`] for
+/// other editors.
+#[must_use]
+pub fn default_chat_sessions_dir() -> Option<std::path::PathBuf> {
+    chat_sessions_dir(EditionHint::VSCode)
+}
+
 // ============================================================================
 // Session Discovery
 // ============================================================================
@@ -480,56 +927,151 @@ pub struct DiscoveredSession {
     pub session_id: String,
     /// Workspace storage ID
     pub workspace_storage_id: String,
+    /// Size of the session file on disk
+    pub file_size_bytes: u64,
+    /// Editor whose storage the session was found in
+    pub editor: EditionHint,
 }
 
 /// Session discovery engine for finding VS Code chat sessions
+///
+/// VS Code Insiders and Cursor store sessions in the same layout, so one
+/// discovery can search several editors' workspace storage at once.
 #[derive(Debug)]
 pub struct SessionDiscovery {
-    /// Root directory for workspace storage
-    storage_root: PathBuf,
+    /// Workspace storage root of each editor searched
+    storage_roots: Vec<(EditionHint, PathBuf)>,
+    /// Skip session files larger than this many bytes
+    max_file_size: Option<u64>,
+    /// Only include workspaces whose path is under this directory
+    path_prefix: Option<PathBuf>,
 }
 
 impl SessionDiscovery {
-    /// Create a new session discovery using the default storage location
+    /// Create a new session discovery using an editor's default storage location
+    ///
+    /// With [`EditionHint::Any`], every installed editor is searched.
     ///
     /// # Errors
     ///
     /// Returns an error if the default storage directory cannot be determined.
-    pub fn new() -> Result<Self, CopilotError> {
-        let storage_root =
-            default_chat_sessions_dir().ok_or_else(|| CopilotError::WorkspaceStorageNotFound {
+    pub fn new(edition: EditionHint) -> Result<Self, CopilotError> {
+        let config_dir =
+            editor_config_dir().ok_or_else(|| CopilotError::WorkspaceStorageNotFound {
                 path: "default location not available".to_string(),
             })?;
-        Ok(Self { storage_root })
+        Ok(Self::in_config_dir(&config_dir, edition))
+    }
+
+    /// Create a session discovery for editors installed under `config_dir`
+    ///
+    /// `config_dir` is the directory holding per-editor settings, such as
+    /// `~/.config` on Linux. With [`EditionHint::Any`], only editors whose
+    /// workspace storage exists are searched; if there are none, the stable
+    /// VS Code location is used so discovery reports it as missing.
+    #[must_use]
+    pub fn in_config_dir(config_dir: &Path, edition: EditionHint) -> Self {
+        let mut storage_roots: Vec<(EditionHint, PathBuf)> = edition
+            .editions()
+            .iter()
+            .filter_map(|&e| workspace_storage_dir(config_dir, e).map(|dir| (e, dir)))
+            .collect();
+        if edition == EditionHint::Any {
+            storage_roots.retain(|(_, dir)| dir.exists());
+            if storage_roots.is_empty() {
+                storage_roots.extend(
+                    workspace_storage_dir(config_dir, EditionHint::VSCode)
+                        .map(|dir| (EditionHint::VSCode, dir)),
+                );
+            }
+        }
+        Self {
+            storage_roots,
+            max_file_size: None,
+            path_prefix: None,
+        }
     }
 
     /// Create a session discovery with a custom storage root
+    ///
+    /// Sessions found under it are attributed to [`EditionHint::VSCode`].
     #[must_use]
     pub fn with_root(storage_root: PathBuf) -> Self {
-        Self { storage_root }
+        Self {
+            storage_roots: vec![(EditionHint::VSCode, storage_root)],
+            max_file_size: None,
+            path_prefix: None,
+        }
+    }
+
+    /// Skip session files larger than `bytes` during discovery
+    #[must_use]
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Only discover sessions from workspaces located under `prefix`
+    ///
+    /// Matching is done per path component, so `/projects` matches
+    /// `/projects/a` but not `/projects-old`. Workspaces without a
+    /// recorded folder are skipped.
+    #[must_use]
+    pub fn with_path_prefix_filter(mut self, prefix: &Path) -> Self {
+        self.path_prefix = Some(prefix.to_path_buf());
+        self
     }
 
     /// Get the storage root path
+    ///
+    /// When several editors are searched, this is the first one's root.
     #[must_use]
     pub fn storage_root(&self) -> &Path {
-        &self.storage_root
+        &self.storage_roots[0].1
     }
 
-    /// Discover all workspace storage directories
+    /// Get the storage root path of every editor searched
+    pub fn storage_roots(&self) -> impl Iterator<Item = (EditionHint, &Path)> {
+        self.storage_roots
+            .iter()
+            .map(|(edition, root)| (*edition, root.as_path()))
+    }
+
+    /// Get the storage roots that exist on disk
     ///
     /// # Errors
     ///
-    /// Returns an error if the storage root cannot be read.
-    pub fn discover_workspaces(&self) -> Result<Vec<WorkspaceInfo>, CopilotError> {
-        if !self.storage_root.exists() {
+    /// Returns `CopilotError::WorkspaceStorageNotFound` if none of them exist.
+    fn existing_roots(&self) -> Result<Vec<(EditionHint, &Path)>, CopilotError> {
+        let roots: Vec<_> = self.storage_roots().filter(|(_, r)| r.exists()).collect();
+        if roots.is_empty() {
             return Err(CopilotError::WorkspaceStorageNotFound {
-                path: self.storage_root.display().to_string(),
+                path: self.storage_root().display().to_string(),
             });
         }
+        Ok(roots)
+    }
 
+    /// Discover all workspace storage directories
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage root cannot be read.
+    pub fn discover_workspaces(&self) -> Result<Vec<WorkspaceInfo>, CopilotError> {
         let mut workspaces = Vec::new();
+        for (_, root) in self.existing_roots()? {
+            self.discover_workspaces_in(root, &mut workspaces)?;
+        }
+        Ok(workspaces)
+    }
 
-        for entry in fs::read_dir(&self.storage_root)? {
+    /// Collect the workspace storage directories under one storage root
+    fn discover_workspaces_in(
+        &self,
+        storage_root: &Path,
+        workspaces: &mut Vec<WorkspaceInfo>,
+    ) -> Result<(), CopilotError> {
+        for entry in fs::read_dir(storage_root)? {
             let entry = entry?;
             let path = entry.path();
 
@@ -552,24 +1094,33 @@ impl SessionDiscovery {
             }
         }
 
-        Ok(workspaces)
+        Ok(())
     }
 
     /// Discover all chat session files
     ///
+    /// Sessions from every searched editor are merged, each tagged with the
+    /// editor it was found in.
+    ///
     /// # Errors
     ///
     /// Returns an error if the storage directories cannot be read.
     pub fn discover_sessions(&self) -> Result<Vec<DiscoveredSession>, CopilotError> {
-        if !self.storage_root.exists() {
-            return Err(CopilotError::WorkspaceStorageNotFound {
-                path: self.storage_root.display().to_string(),
-            });
-        }
-
         let mut sessions = Vec::new();
+        for (editor, root) in self.existing_roots()? {
+            self.discover_sessions_in(editor, root, &mut sessions)?;
+        }
+        Ok(sessions)
+    }
 
-        for entry in fs::read_dir(&self.storage_root)? {
+    /// Collect the chat session files under one editor's storage root
+    fn discover_sessions_in(
+        &self,
+        editor: EditionHint,
+        storage_root: &Path,
+        sessions: &mut Vec<DiscoveredSession>,
+    ) -> Result<(), CopilotError> {
+        for entry in fs::read_dir(storage_root)? {
             let entry = entry?;
             let workspace_dir = entry.path();
 
@@ -588,6 +1139,15 @@ impl SessionDiscovery {
                 continue;
             }
 
+            if let Some(prefix) = &self.path_prefix {
+                let in_scope = WorkspaceInfo::from_storage_dir(&workspace_dir)
+                    .ok()
+                    .is_some_and(|info| info.path().is_some_and(|p| p.starts_with(prefix)));
+                if !in_scope {
+                    continue;
+                }
+            }
+
             let chat_sessions_dir = workspace_dir.join("chatSessions");
             if !chat_sessions_dir.exists() {
                 continue;
@@ -603,11 +1163,24 @@ impl SessionDiscovery {
                                 .and_then(|n| n.to_str())
                                 .unwrap_or("")
                                 .to_string();
+                            let file_size_bytes = session_path.metadata()?.len();
+
+                            if let Some(max) = self.max_file_size
+                                && file_size_bytes > max
+                            {
+                                debug!(
+                                    "Skipping session {:?}: {} bytes exceeds limit of {}",
+                                    session_path, file_size_bytes, max
+                                );
+                                continue;
+                            }
 
                             sessions.push(DiscoveredSession {
                                 path: session_path,
                                 session_id,
                                 workspace_storage_id: workspace_storage_id.clone(),
+                                file_size_bytes,
+                                editor,
                             });
                         }
                     }
@@ -621,7 +1194,7 @@ impl SessionDiscovery {
             }
         }
 
-        Ok(sessions)
+        Ok(())
     }
 
     /// Discover sessions for a specific workspace folder path
@@ -666,14 +1239,71 @@ pub fn parse_session_file(path: &Path, workspace_id: &str) -> Result<ChatSession
     parse_session_json(&content, workspace_id)
 }
 
+/// Known VS Code chat session file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionFormat {
+    /// Version 3 sessions with request IDs and per-request timestamps
+    VsCodeV3,
+    /// Version 2 sessions, whose requests lack IDs and timestamps
+    VsCodeV2,
+    /// Any other or missing version
+    Unknown,
+}
+
+impl SessionFormat {
+    /// Map a session's `version` field to its format
+    #[must_use]
+    pub fn from_version(version: Option<u32>) -> Self {
+        match version {
+            Some(3) => Self::VsCodeV3,
+            Some(2) => Self::VsCodeV2,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Read the `version` field of a session JSON object
+fn session_version(value: &serde_json::Value) -> Option<u32> {
+    value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|v| u32::try_from(v).ok())
+}
+
+/// Detect the format of a chat session from its JSON content
+///
+/// # Errors
+///
+/// Returns an error if the content is not valid JSON.
+pub fn detect_session_format(json: &str) -> Result<SessionFormat, CopilotError> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    Ok(SessionFormat::from_version(session_version(&value)))
+}
+
 /// Parse a chat session from JSON content
 ///
+/// Dispatches on the session's `version` field to the matching parser.
+///
 /// # Errors
 ///
-/// Returns an error if the JSON is invalid or doesn't match the expected format.
+/// Returns `CopilotError::UnsupportedFormat` if the version is not recognised,
+/// or a JSON error if the content doesn't match the expected format.
 pub fn parse_session_json(json: &str, workspace_id: &str) -> Result<ChatSession, CopilotError> {
-    let raw: RawSession = serde_json::from_str(json)?;
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let version = session_version(&value);
+
+    match SessionFormat::from_version(version) {
+        SessionFormat::VsCodeV3 => parse_session_v3(serde_json::from_value(value)?, workspace_id),
+        SessionFormat::VsCodeV2 => parse_session_v2(serde_json::from_value(value)?, workspace_id),
+        SessionFormat::Unknown => Err(CopilotError::UnsupportedFormat {
+            format: "unknown".to_string(),
+            version,
+        }),
+    }
+}
 
+/// Build a chat session from a version 3 session file
+fn parse_session_v3(raw: RawSession, workspace_id: &str) -> Result<ChatSession, CopilotError> {
     let created_at = raw
         .creation_date
         .and_then(DateTime::from_timestamp_millis)
@@ -687,6 +1317,13 @@ pub fn parse_session_json(json: &str, workspace_id: &str) -> Result<ChatSession,
     let model = raw.selected_model.and_then(|m| m.identifier);
     let mode = raw.mode.and_then(|m| m.id);
 
+    // A session-level system prompt (or context) becomes the first message
+    let system_prompt = raw.system_prompt.or_else(|| match raw.context {
+        Some(serde_json::Value::String(s)) => Some(s),
+        Some(serde_json::Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    });
+
     let mut session = ChatSession::with_metadata(
         raw.session_id,
         workspace_id.to_string(),
@@ -696,60 +1333,115 @@ pub fn parse_session_json(json: &str, workspace_id: &str) -> Result<ChatSession,
         mode,
     );
 
+    if let Some(prompt) = system_prompt.filter(|p| !p.is_empty()) {
+        session.add_message(ChatMessage::system(prompt, created_at));
+    }
+
     // Parse each request/response pair
     for request in raw.requests {
-        // Extract user message
-        if let Some(msg) = &request.message {
-            let timestamp = request
-                .timestamp
-                .and_then(DateTime::from_timestamp_millis)
-                .unwrap_or(created_at);
-
-            let variables = extract_variables(&request.variable_data);
+        let timestamp = request
+            .timestamp
+            .and_then(DateTime::from_timestamp_millis)
+            .unwrap_or(created_at);
+
+        // Requests explicitly marked as system prompts
+        if request.role.as_deref() == Some("system") {
+            if let Some(msg) = &request.message {
+                session.add_message(ChatMessage::system(msg.text.clone(), timestamp));
+            }
+            continue;
+        }
 
-            let agent_name = request
-                .agent
-                .as_ref()
-                .and_then(|a| a.name.clone().or(a.full_name.clone()));
+        add_exchange(
+            &mut session,
+            request.message.as_ref().map(|m| m.text.as_str()),
+            &request.variable_data,
+            request.agent.as_ref(),
+            &request.response,
+            timestamp,
+        );
+    }
 
-            let user_msg = ChatMessage::user(msg.text.clone(), timestamp).with_variables(variables);
+    Ok(session)
+}
 
-            let user_msg = if let Some(agent) = agent_name.clone() {
-                user_msg.with_agent(agent)
-            } else {
-                user_msg
-            };
+/// Build a chat session from a version 2 session file
+///
+/// Requests have no timestamps of their own, so every message is stamped
+/// with the session's creation date.
+fn parse_session_v2(raw: RawSessionV2, workspace_id: &str) -> Result<ChatSession, CopilotError> {
+    let created_at = raw
+        .creation_date
+        .and_then(DateTime::from_timestamp_millis)
+        .unwrap_or_else(Utc::now);
 
-            session.add_message(user_msg);
-        }
+    let updated_at = raw
+        .last_message_date
+        .and_then(DateTime::from_timestamp_millis)
+        .unwrap_or(created_at);
 
-        // Extract assistant response
-        let response_text = extract_response_text(&request.response);
-        if !response_text.is_empty() {
-            let timestamp = request
-                .timestamp
-                .and_then(DateTime::from_timestamp_millis)
-                .unwrap_or(created_at);
-
-            let agent_name = request
-                .agent
-                .as_ref()
-                .and_then(|a| a.name.clone().or(a.full_name.clone()));
-
-            let assistant_msg = ChatMessage::assistant(response_text, timestamp);
-            let assistant_msg = if let Some(agent) = agent_name {
-                assistant_msg.with_agent(agent)
-            } else {
-                assistant_msg
-            };
+    let mut session = ChatSession::with_metadata(
+        raw.session_id,
+        workspace_id.to_string(),
+        created_at,
+        updated_at,
+        None,
+        None,
+    );
 
-            session.add_message(assistant_msg);
-        }
+    for request in raw.requests {
+        add_exchange(
+            &mut session,
+            request.message.as_ref().map(RawMessageV2::text),
+            &request.variable_data,
+            request.agent.as_ref(),
+            &request.response,
+            created_at,
+        );
     }
 
     Ok(session)
 }
 
+/// Add a request's user message and assistant response to a session
+fn add_exchange(
+    session: &mut ChatSession,
+    message: Option<&str>,
+    variable_data: &Option<RawVariableData>,
+    agent: Option<&RawAgent>,
+    response: &[RawResponsePart],
+    timestamp: DateTime<Utc>,
+) {
+    let agent_name = agent.and_then(|a| a.name.clone().or(a.full_name.clone()));
+
+    // Extract user message
+    if let Some(text) = message {
+        let variables = extract_variables(variable_data);
+        let user_msg = ChatMessage::user(text.to_string(), timestamp).with_variables(variables);
+
+        let user_msg = if let Some(agent) = agent_name.clone() {
+            user_msg.with_agent(agent)
+        } else {
+            user_msg
+        };
+
+        session.add_message(user_msg);
+    }
+
+    // Extract assistant response
+    let response_text = extract_response_text(response);
+    if !response_text.is_empty() {
+        let assistant_msg = ChatMessage::assistant(response_text, timestamp);
+        let assistant_msg = if let Some(agent) = agent_name {
+            assistant_msg.with_agent(agent)
+        } else {
+            assistant_msg
+        };
+
+        session.add_message(assistant_msg);
+    }
+}
+
 /// Extract variables from raw variable data
 fn extract_variables(variable_data: &Option<RawVariableData>) -> Vec<Variable> {
     let Some(data) = variable_data else {
@@ -824,6 +1516,153 @@ fn extract_response_text(response_parts: &[RawResponsePart]) -> String {
     text_parts.join("")
 }
 
+// ============================================================================
+// Compact Serialization
+// ============================================================================
+
+/// Compact session layout used by [`to_compact_json`]
+#[derive(Debug, Serialize, Deserialize)]
+struct CompactSession {
+    id: String,
+    #[serde(rename = "w")]
+    workspace_id: String,
+    /// Creation time in Unix milliseconds
+    #[serde(rename = "c")]
+    created_at: i64,
+    /// Last update time in Unix milliseconds
+    #[serde(rename = "u")]
+    updated_at: i64,
+    #[serde(rename = "m")]
+    messages: Vec<CompactMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+}
+
+/// A message as a `[role, timestamp_ms, content, agent?, variables?]` array
+///
+/// Trailing fields are dropped when empty.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum CompactMessage {
+    Full(char, i64, String, Option<String>, Vec<Variable>),
+    WithAgent(char, i64, String, Option<String>),
+    Plain(char, i64, String),
+}
+
+impl From<&ChatMessage> for CompactMessage {
+    fn from(message: &ChatMessage) -> Self {
+        let role = match message.role {
+            MessageRole::User => 'u',
+            MessageRole::Assistant => 'a',
+            MessageRole::System => 's',
+        };
+        let timestamp = message.timestamp.timestamp_millis();
+        let content = message.content.clone();
+
+        if !message.variables.is_empty() {
+            Self::Full(
+                role,
+                timestamp,
+                content,
+                message.agent.clone(),
+                message.variables.clone(),
+            )
+        } else if message.agent.is_some() {
+            Self::WithAgent(role, timestamp, content, message.agent.clone())
+        } else {
+            Self::Plain(role, timestamp, content)
+        }
+    }
+}
+
+impl TryFrom<CompactMessage> for ChatMessage {
+    type Error = CopilotError;
+
+    fn try_from(message: CompactMessage) -> Result<Self, Self::Error> {
+        let (role, timestamp, content, agent, variables) = match message {
+            CompactMessage::Full(r, t, c, a, v) => (r, t, c, a, v),
+            CompactMessage::WithAgent(r, t, c, a) => (r, t, c, a, Vec::new()),
+            CompactMessage::Plain(r, t, c) => (r, t, c, None, Vec::new()),
+        };
+
+        let role = match role {
+            'u' => MessageRole::User,
+            'a' => MessageRole::Assistant,
+            's' => MessageRole::System,
+            other => {
+                return Err(CopilotError::InvalidChatLog {
+                    message: format!("unknown compact message role '{other}'"),
+                });
+            }
+        };
+
+        Ok(Self {
+            role,
+            content,
+            timestamp: compact_timestamp(timestamp)?,
+            agent,
+            variables,
+        })
+    }
+}
+
+/// Convert Unix milliseconds back into a timestamp
+fn compact_timestamp(millis: i64) -> Result<DateTime<Utc>, CopilotError> {
+    DateTime::from_timestamp_millis(millis).ok_or_else(|| CopilotError::InvalidChatLog {
+        message: format!("timestamp out of range: {millis}"),
+    })
+}
+
+/// Serialize a session to a compact JSON form for export
+///
+/// Timestamps are stored as Unix milliseconds, roles as single characters
+/// (`u`/`a`/`s`), and each message as an array with empty trailing fields
+/// omitted. Sub-millisecond precision is not preserved.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+pub fn to_compact_json(session: &ChatSession) -> Result<String, serde_json::Error> {
+    let compact = CompactSession {
+        id: session.id.clone(),
+        workspace_id: session.workspace_id.clone(),
+        created_at: session.created_at.timestamp_millis(),
+        updated_at: session.updated_at.timestamp_millis(),
+        messages: session.messages.iter().map(CompactMessage::from).collect(),
+        model: session.model.clone(),
+        mode: session.mode.clone(),
+    };
+    serde_json::to_string(&compact)
+}
+
+/// Parse a session produced by [`to_compact_json`]
+///
+/// # Errors
+///
+/// Returns an error if the JSON is invalid, a role is unknown, or a
+/// timestamp is out of range.
+pub fn from_compact_json(json: &str) -> Result<ChatSession, CopilotError> {
+    let compact: CompactSession = serde_json::from_str(json)?;
+
+    let messages = compact
+        .messages
+        .into_iter()
+        .map(ChatMessage::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ChatSession {
+        id: compact.id,
+        workspace_id: compact.workspace_id,
+        created_at: compact_timestamp(compact.created_at)?,
+        updated_at: compact_timestamp(compact.updated_at)?,
+        messages,
+        model: compact.model,
+        mode: compact.mode,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -843,6 +1682,108 @@ mod tests {
         session
     }
 
+    #[test]
+    fn test_session_duration_minutes() {
+        let start = Utc::now();
+        let mut session = ChatSession::new("s-1".to_string(), "ws-1".to_string(), start);
+        assert_eq!(session_duration_minutes(&session), None);
+
+        session.add_message(ChatMessage::user("Start".to_string(), start));
+        assert_eq!(session_duration_minutes(&session), Some(0.0));
+
+        session.add_message(ChatMessage::assistant(
+            "Reply".to_string(),
+            start + chrono::Duration::minutes(30),
+        ));
+        session.add_message(ChatMessage::user(
+            "Done".to_string(),
+            start + chrono::Duration::minutes(90),
+        ));
+        assert_eq!(session_duration_minutes(&session), Some(90.0));
+    }
+
+    #[test]
+    fn test_compact_json_roundtrip() {
+        let ts = sample_timestamp();
+        let mut session = ChatSession::with_metadata(
+            "session-123".to_string(),
+            "workspace-456".to_string(),
+            ts,
+            ts,
+            Some("copilot/gpt-4o".to_string()),
+            Some("agent".to_string()),
+        );
+        session.add_message(ChatMessage::system("Be brief".to_string(), ts));
+        session.add_message(
+            ChatMessage::user(
+                "Explain this".to_string(),
+                ts + chrono::Duration::seconds(1),
+            )
+            .with_agent("@workspace".to_string())
+            .with_variables(vec![Variable {
+                kind: "file".to_string(),
+                name: "main.rs".to_string(),
+                value: Some("/src/main.rs".to_string()),
+            }]),
+        );
+        session.add_message(ChatMessage::assistant(
+            "Done".to_string(),
+            ts + chrono::Duration::milliseconds(2500),
+        ));
+
+        let json = to_compact_json(&session).expect("serialize");
+        let restored = from_compact_json(&json).expect("deserialize");
+        assert_eq!(restored, session);
+    }
+
+    #[test]
+    fn test_compact_json_size() {
+        let ts = sample_timestamp();
+        let mut session = ChatSession::new("session-123".to_string(), "ws-1".to_string(), ts);
+        for i in 0..10 {
+            let at = ts + chrono::Duration::seconds(i);
+            if i % 2 == 0 {
+                session.add_message(ChatMessage::user(format!("Question {i}"), at));
+            } else {
+                session.add_message(ChatMessage::assistant(format!("Answer {i}"), at));
+            }
+        }
+
+        let full = serde_json::to_string(&session).expect("full json");
+        let compact = to_compact_json(&session).expect("compact json");
+        assert!(
+            compact.len() * 2 <= full.len(),
+            "compact {} bytes vs full {} bytes",
+            compact.len(),
+            full.len()
+        );
+    }
+
+    #[test]
+    fn test_compact_json_unicode_roundtrip() {
+        let ts = sample_timestamp();
+        let mut session = ChatSession::new("séance-🦀".to_string(), "ws-日本".to_string(), ts);
+        session.add_message(ChatMessage::user(
+            "Ünïcödé \"quotes\" \\ tab\t 中文 👩‍💻".to_string(),
+            ts,
+        ));
+        session.add_message(ChatMessage::assistant(
+            "\u{0}null and \u{200b}zwsp".to_string(),
+            ts,
+        ));
+
+        let json = to_compact_json(&session).expect("serialize");
+        let restored = from_compact_json(&json).expect("deserialize");
+        assert_eq!(restored, session);
+    }
+
+    #[test]
+    fn test_from_compact_json_rejects_unknown_role() {
+        let json = r#"{"id":"s","w":"ws","c":0,"u":0,"m":[["x",0,"hi"]]}"#;
+        let result = from_compact_json(json);
+        assert!(matches!(result, Err(CopilotError::InvalidChatLog { .. })));
+    }
+
     #[test]
     fn test_session_serialization_roundtrip() {
         let session = sample_session();
@@ -889,6 +1830,123 @@ mod tests {
         assert_eq!(assistant_msgs[0].content, "Hi there!");
     }
 
+    #[test]
+    fn test_session_estimated_tokens() {
+        let mut session = ChatSession::new("s".to_string(), "w".to_string(), Utc::now());
+        assert_eq!(session.estimated_tokens(), 0);
+
+        // 16 + 6 = 22 bytes -> 5 tokens
+        session.add_message(ChatMessage::user(
+            "How do I do this".to_string(),
+            Utc::now(),
+        ));
+        session.add_message(ChatMessage::assistant("Use it".to_string(), Utc::now()));
+        assert_eq!(session.estimated_tokens(), 5);
+    }
+
+    #[test]
+    fn test_session_to_markdown() {
+        let ts = sample_timestamp();
+        let mut session = ChatSession::with_metadata(
+            "session-123".to_string(),
+            "workspace-456".to_string(),
+            ts,
+            ts,
+            Some("copilot/gpt-4o".to_string()),
+            Some("agent".to_string()),
+        );
+        session.add_message(
+            ChatMessage::user("Why does this fail?".to_string(), ts)
+                .with_agent("@workspace".to_string())
+                .with_variables(vec![
+                    Variable {
+                        kind: "file".to_string(),
+                        name: "lib.rs".to_string(),
+                        value: Some("/src/lib.rs".to_string()),
+                    },
+                    Variable {
+                        kind: "workspace".to_string(),
+                        name: "workspace".to_string(),
+                        value: None,
+                    },
+                ]),
+        );
+        session.add_message(ChatMessage::assistant(
+            "Try this:\n```rust\n## User\nlet x = 1;\n```".to_string(),
+            ts,
+        ));
+
+        let expected = "\
+---
+id: \"session-123\"
+workspace_id: \"workspace-456\"
+model: \"copilot/gpt-4o\"
+mode: \"agent\"
+created_at: 2026-01-17T02:33:06+00:00
+updated_at: 2026-01-17T02:33:06+00:00
+messages: 2
+---
+
+## User
+
+_2026-01-17T02:33:06+00:00 · @workspace_
+
+Why does this fail?
+
+- [lib.rs](/src/lib.rs)
+- workspace
+
+## Copilot
+
+_2026-01-17T02:33:06+00:00_
+
+Try this:
+```rust
+## User
+let x = 1;
+```
+";
+        assert_eq!(session.to_markdown(), expected);
+    }
+
+    #[test]
+    fn test_session_to_markdown_escapes_role_headings() {
+        let ts = sample_timestamp();
+        let mut session = ChatSession::new("s".to_string(), "w".to_string(), ts);
+        session.add_message(ChatMessage::user(
+            "## Copilot\n```\nunclosed".to_string(),
+            ts,
+        ));
+
+        let markdown = session.to_markdown();
+        assert!(markdown.contains("\\## Copilot\n"));
+        assert!(markdown.ends_with("unclosed\n```\n"));
+    }
+
+    #[test]
+    fn test_session_to_markdown_nested_fence() {
+        let ts = sample_timestamp();
+        let mut session = ChatSession::new("s".to_string(), "w".to_string(), ts);
+        session.add_message(ChatMessage::user(
+            "````markdown\n```\n## User\n````\nafter".to_string(),
+            ts,
+        ));
+
+        let markdown = session.to_markdown();
+        assert!(markdown.contains("\n```\n## User\n````\nafter\n"));
+        assert!(!markdown.contains("\\## User"));
+        assert!(markdown.ends_with("after\n"));
+    }
+
+    #[test]
+    fn test_session_estimated_cost_usd() {
+        let mut session = ChatSession::new("s".to_string(), "w".to_string(), Utc::now());
+        session.add_message(ChatMessage::user("a".repeat(8000), Utc::now()));
+
+        // 8000 bytes -> 2000 tokens -> 2 * $0.01
+        assert!((session.estimated_cost_usd(0.01) - 0.02).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn test_message_serialization_roundtrip() {
         let msg = ChatMessage::user("Test message".to_string(), sample_timestamp());
@@ -934,6 +1992,76 @@ mod tests {
         }
     }
 
+    fn code_blocks(content: &str) -> Vec<CodeBlock> {
+        ChatMessage::assistant(content.to_string(), Utc::now()).extract_code_blocks()
+    }
+
+    fn block(language: Option<&str>, content: &str) -> CodeBlock {
+        CodeBlock {
+            language: language.map(str::to_string),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_extract_code_blocks_fenced() {
+        let content = "Try this:\n\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\nThen run:\n```\ncargo run\n```\n";
+        assert_eq!(
+            code_blocks(content),
+            vec![
+                block(Some("rust"), "fn main() {\n    println!(\"hi\");\n}"),
+                block(None, "cargo run"),
+            ]
+        );
+
+        // Info strings beyond the first word are ignored
+        assert_eq!(
+            code_blocks("~~~python title=\"x.py\"\nprint(1)\n~~~"),
+            vec![block(Some("python"), "print(1)")]
+        );
+        assert!(code_blocks("No code here, just `inline` spans.").is_empty());
+    }
+
+    #[test]
+    fn test_extract_code_blocks_nested_fences() {
+        let content = "````markdown\nUse a fence:\n```rust\nlet x = 1;\n```\n````\nafter";
+        assert_eq!(
+            code_blocks(content),
+            vec![block(
+                Some("markdown"),
+                "Use a fence:\n```rust\nlet x = 1;\n```"
+            )]
+        );
+
+        // A shorter fence does not close a longer one, an unclosed fence runs to the end
+        assert_eq!(
+            code_blocks("````\na\n```\nb"),
+            vec![block(None, "a\n```\nb")]
+        );
+
+        // Fence indentation is removed from content lines
+        assert_eq!(
+            code_blocks("  ```sh\n  ls -la\n    cd src\n  ```"),
+            vec![block(Some("sh"), "ls -la\n  cd src")]
+        );
+    }
+
+    #[test]
+    fn test_extract_code_blocks_indented() {
+        let content = "Run it like so:\n\n    cargo build\n\n    cargo test\n\nDone.";
+        assert_eq!(
+            code_blocks(content),
+            vec![block(None, "cargo build\n\ncargo test")]
+        );
+
+        // Indented lines continuing a paragraph are not code
+        assert!(code_blocks("A paragraph\n    still the paragraph").is_empty());
+
+        // Indented lines under a list item continue the item
+        let list = "1. First step\n\n    More about the first step\n2. Second step";
+        assert!(code_blocks(list).is_empty());
+    }
+
     #[test]
     fn test_message_role_display_name() {
         assert_eq!(MessageRole::User.display_name(), "User");
@@ -1039,6 +2167,60 @@ mod tests {
         assert_eq!(session.mode, Some("agent".to_string()));
     }
 
+    #[test]
+    fn test_parse_session_json_with_system_prompt() {
+        let json = r#"{
+            "version": 3,
+            "sessionId": "session-with-system",
+            "creationDate": 1705500000000,
+            "systemPrompt": "You are a helpful coding assistant.",
+            "requests": [
+                {
+                    "requestId": "req-1",
+                    "message": {"text": "Hello"},
+                    "response": [{"value": "Hi there"}],
+                    "timestamp": 1705500001000
+                }
+            ]
+        }"#;
+
+        let session = parse_session_json(json, "ws").expect("parse");
+        let system = session.system_messages();
+        assert_eq!(system.len(), 1);
+        assert_eq!(system[0].content, "You are a helpful coding assistant.");
+        assert_eq!(system[0].timestamp, session.created_at);
+        assert_eq!(session.messages[0].role, MessageRole::System);
+        assert_eq!(session.user_messages().len(), 1);
+        assert_eq!(session.assistant_messages().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_session_json_with_system_role_request() {
+        let json = r#"{
+            "version": 3,
+            "sessionId": "session-with-system-request",
+            "context": {"repository": "hindsight"},
+            "requests": [
+                {
+                    "requestId": "req-0",
+                    "role": "system",
+                    "message": {"text": "Follow the repository conventions."}
+                },
+                {
+                    "requestId": "req-1",
+                    "message": {"text": "Hello"}
+                }
+            ]
+        }"#;
+
+        let session = parse_session_json(json, "ws").expect("parse");
+        let system = session.system_messages();
+        assert_eq!(system.len(), 2);
+        assert_eq!(system[0].content, r#"{"repository":"hindsight"}"#);
+        assert_eq!(system[1].content, "Follow the repository conventions.");
+        assert_eq!(session.user_messages().len(), 1);
+    }
+
     #[test]
     fn test_parse_session_json_with_variables() {
         let json = r#"{
@@ -1089,6 +2271,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_detect_session_format() {
+        let detect = |json: &str| detect_session_format(json).expect("detect");
+        assert_eq!(detect(r#"{"version": 3}"#), SessionFormat::VsCodeV3);
+        assert_eq!(detect(r#"{"version": 2}"#), SessionFormat::VsCodeV2);
+        assert_eq!(detect(r#"{"version": 1}"#), SessionFormat::Unknown);
+        assert_eq!(detect(r#"{"sessionId": "x"}"#), SessionFormat::Unknown);
+        assert_eq!(detect("[]"), SessionFormat::Unknown);
+        assert!(matches!(
+            detect_session_format("not json"),
+            Err(CopilotError::JsonParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_session_json_v2() {
+        let json = r#"{
+            "version": 2,
+            "sessionId": "v2-session",
+            "creationDate": 1705500000000,
+            "lastMessageDate": 1705500005000,
+            "requests": [
+                {
+                    "message": "How do I read a file?",
+                    "response": [{"value": "Use std::fs::read_to_string.", "isTrusted": false}],
+                    "agent": {"id": "github.copilot.default", "fullName": "GitHub Copilot"}
+                },
+                {
+                    "message": {"text": "And write one?", "parts": []},
+                    "response": [{"value": "Use std::fs::write."}]
+                }
+            ]
+        }"#;
+
+        let session = parse_session_json(json, "ws").expect("parse");
+        assert_eq!(session.id, "v2-session");
+        assert_eq!(session.message_count(), 4);
+
+        let user_msgs = session.user_messages();
+        assert_eq!(user_msgs[0].content, "How do I read a file?");
+        assert_eq!(user_msgs[0].agent, Some("GitHub Copilot".to_string()));
+        assert_eq!(user_msgs[1].content, "And write one?");
+        assert_eq!(user_msgs[1].timestamp, session.created_at);
+
+        let assistant_msgs = session.assistant_messages();
+        assert_eq!(assistant_msgs[1].content, "Use std::fs::write.");
+    }
+
+    #[test]
+    fn test_parse_session_json_unsupported_version() {
+        let json = r#"{"version": 1, "sessionId": "old", "requests": []}"#;
+        let err = parse_session_json(json, "ws").expect_err("version 1 is unsupported");
+        assert!(matches!(
+            err,
+            CopilotError::UnsupportedFormat {
+                version: Some(1),
+                ..
+            }
+        ));
+
+        let err = parse_session_json(r#"{"sessionId": "x"}"#, "ws").expect_err("no version");
+        assert!(matches!(
+            err,
+            CopilotError::UnsupportedFormat { version: None, .. }
+        ));
+    }
+
     #[test]
     fn test_parse_file_uri() {
         assert_eq!(
@@ -1178,6 +2427,200 @@ mod tests {
         };
         assert!(info3.path().is_none());
     }
+
+    #[test]
+    fn test_discover_sessions_max_file_size() {
+        let root = std::env::temp_dir().join(format!("hindsight-sessions-{}", std::process::id()));
+        let chat_dir = root.join("workspace1").join("chatSessions");
+        fs::create_dir_all(&chat_dir).expect("create dirs");
+        fs::write(chat_dir.join("small.json"), "{}").expect("write");
+        fs::write(chat_dir.join("large.json"), vec![b' '; 4096]).expect("write");
+
+        let unlimited = SessionDiscovery::with_root(root.clone())
+            .discover_sessions()
+            .expect("discover");
+        let limited = SessionDiscovery::with_root(root.clone())
+            .with_max_file_size(1024)
+            .discover_sessions()
+            .expect("discover");
+        let _ = fs::remove_dir_all(&root);
+
+        let mut all: Vec<_> = unlimited
+            .iter()
+            .map(|s| (s.session_id.as_str(), s.file_size_bytes))
+            .collect();
+        all.sort_unstable();
+        assert_eq!(all, vec![("large", 4096), ("small", 2)]);
+
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].session_id, "small");
+        assert_eq!(limited[0].workspace_storage_id, "workspace1");
+    }
+
+    #[test]
+    fn test_discover_sessions_path_prefix_filter() {
+        let root =
+            std::env::temp_dir().join(format!("hindsight-sessions-prefix-{}", std::process::id()));
+        for (storage_id, folder) in [
+            ("ws-a", "/projects/a"),
+            ("ws-b", "/projects/b"),
+            ("ws-c", "/other/c"),
+            ("ws-d", "/projects-old/d"),
+        ] {
+            let storage_dir = root.join(storage_id);
+            fs::create_dir_all(storage_dir.join("chatSessions")).expect("create dirs");
+            fs::write(
+                storage_dir.join("workspace.json"),
+                format!(r#"{{"folder": "file://{folder}"}}"#),
+            )
+            .expect("write");
+            fs::write(
+                storage_dir
+                    .join("chatSessions")
+                    .join(format!("{storage_id}-session.json")),
+                "{}",
+            )
+            .expect("write");
+        }
+        // Storage without workspace.json can't be placed under any prefix
+        fs::create_dir_all(root.join("ws-e").join("chatSessions")).expect("create dirs");
+        fs::write(root.join("ws-e").join("chatSessions").join("e.json"), "{}").expect("write");
+
+        let discovered = SessionDiscovery::with_root(root.clone())
+            .with_path_prefix_filter(Path::new("/projects"))
+            .discover_sessions()
+            .expect("discover");
+        let _ = fs::remove_dir_all(&root);
+
+        let mut storage_ids: Vec<_> = discovered
+            .iter()
+            .map(|s| s.workspace_storage_id.as_str())
+            .collect();
+        storage_ids.sort_unstable();
+        assert_eq!(storage_ids, vec!["ws-a", "ws-b"]);
+    }
+
+    /// Create `<config>/<editor>/User/workspaceStorage/<storage_id>/chatSessions/<session>.json`
+    fn write_editor_session(config_dir: &Path, editor_dir: &str, storage_id: &str, session: &str) {
+        let chat_dir = config_dir
+            .join(editor_dir)
+            .join("User/workspaceStorage")
+            .join(storage_id)
+            .join("chatSessions");
+        fs::create_dir_all(&chat_dir).expect("create dirs");
+        fs::write(chat_dir.join(format!("{session}.json")), "{}").expect("write");
+    }
+
+    #[test]
+    fn test_edition_hint_config_dir_names() {
+        assert_eq!(EditionHint::default(), EditionHint::VSCode);
+        assert_eq!(EditionHint::VSCode.config_dir_name(), Some("Code"));
+        assert_eq!(
+            EditionHint::VSCodeInsiders.config_dir_name(),
+            Some("Code - Insiders")
+        );
+        assert_eq!(EditionHint::Cursor.config_dir_name(), Some("Cursor"));
+        assert_eq!(EditionHint::Any.config_dir_name(), None);
+        assert_eq!(
+            EditionHint::VSCodeInsiders.display_name(),
+            "VS Code Insiders"
+        );
+
+        assert!(chat_sessions_dir(EditionHint::Any).is_none());
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        {
+            let cursor = chat_sessions_dir(EditionHint::Cursor).expect("cursor dir");
+            assert!(cursor.ends_with("Cursor/User/workspaceStorage"));
+            assert_eq!(
+                default_chat_sessions_dir(),
+                chat_sessions_dir(EditionHint::VSCode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_sessions_per_editor() {
+        let config_dir =
+            std::env::temp_dir().join(format!("hindsight-editors-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&config_dir);
+        write_editor_session(&config_dir, "Code", "ws-stable", "stable");
+        write_editor_session(&config_dir, "Code - Insiders", "ws-insiders", "insiders");
+        write_editor_session(&config_dir, "Cursor", "ws-cursor-1", "cursor-1");
+        write_editor_session(&config_dir, "Cursor", "ws-cursor-2", "cursor-2");
+
+        let sessions_of = |edition| {
+            let mut found: Vec<(String, EditionHint)> =
+                SessionDiscovery::in_config_dir(&config_dir, edition)
+                    .discover_sessions()
+                    .expect("discover")
+                    .into_iter()
+                    .map(|s| (s.session_id, s.editor))
+                    .collect();
+            found.sort_by(|a, b| a.0.cmp(&b.0));
+            found
+        };
+
+        let insiders = sessions_of(EditionHint::VSCodeInsiders);
+        let cursor = sessions_of(EditionHint::Cursor);
+        let any = sessions_of(EditionHint::Any);
+        let workspaces = SessionDiscovery::in_config_dir(&config_dir, EditionHint::Any)
+            .discover_workspaces()
+            .expect("workspaces");
+        let _ = fs::remove_dir_all(&config_dir);
+
+        assert_eq!(
+            insiders,
+            vec![("insiders".to_string(), EditionHint::VSCodeInsiders)]
+        );
+        assert_eq!(
+            cursor,
+            vec![
+                ("cursor-1".to_string(), EditionHint::Cursor),
+                ("cursor-2".to_string(), EditionHint::Cursor),
+            ]
+        );
+        assert_eq!(
+            any,
+            vec![
+                ("cursor-1".to_string(), EditionHint::Cursor),
+                ("cursor-2".to_string(), EditionHint::Cursor),
+                ("insiders".to_string(), EditionHint::VSCodeInsiders),
+                ("stable".to_string(), EditionHint::VSCode),
+            ]
+        );
+        assert_eq!(workspaces.len(), 4);
+    }
+
+    #[test]
+    fn test_discover_sessions_any_skips_missing_editors() {
+        let config_dir =
+            std::env::temp_dir().join(format!("hindsight-editors-any-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&config_dir);
+        write_editor_session(&config_dir, "Cursor", "ws-cursor", "only-cursor");
+
+        let any = SessionDiscovery::in_config_dir(&config_dir, EditionHint::Any);
+        let roots: Vec<EditionHint> = any.storage_roots().map(|(e, _)| e).collect();
+        let sessions = any.discover_sessions().expect("discover");
+        let stable =
+            SessionDiscovery::in_config_dir(&config_dir, EditionHint::VSCode).discover_sessions();
+        let _ = fs::remove_dir_all(&config_dir);
+
+        assert_eq!(roots, vec![EditionHint::Cursor]);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].editor, EditionHint::Cursor);
+        assert!(matches!(
+            stable,
+            Err(CopilotError::WorkspaceStorageNotFound { .. })
+        ));
+
+        // With no editor installed, the stable VS Code location is reported missing
+        let none = SessionDiscovery::in_config_dir(&config_dir, EditionHint::Any);
+        assert!(none.storage_root().ends_with("Code/User/workspaceStorage"));
+        assert!(matches!(
+            none.discover_sessions(),
+            Err(CopilotError::WorkspaceStorageNotFound { .. })
+        ));
+    }
 }
 
 #[cfg(test)]
@@ -1252,6 +2695,29 @@ mod property_tests {
             })
     }
 
+    /// Count lines outside code fences that are exactly `heading`
+    fn count_headings(markdown: &str, heading: &str) -> usize {
+        let mut fence: Option<(char, usize)> = None;
+        let mut count = 0;
+        for line in markdown.lines() {
+            match fence {
+                Some((marker, len)) => {
+                    if closes_fence(line, marker, len) {
+                        fence = None;
+                    }
+                }
+                None => {
+                    if let Some(open) = parse_opening_fence(line) {
+                        fence = Some((open.marker, open.len));
+                    } else if line == heading {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
     proptest! {
         /// Property: Round-trip JSON serialization preserves ChatMessage
         #[test]
@@ -1269,6 +2735,22 @@ mod property_tests {
             prop_assert_eq!(session, deserialized);
         }
 
+        /// Property: to_markdown keeps one heading per user and assistant message
+        #[test]
+        fn prop_to_markdown_preserves_message_headings(session in session_strategy()) {
+            prop_assume!(!session.is_empty());
+            let markdown = session.to_markdown();
+            prop_assert!(!markdown.is_empty());
+            prop_assert_eq!(
+                count_headings(&markdown, "## User"),
+                session.user_messages().len()
+            );
+            prop_assert_eq!(
+                count_headings(&markdown, "## Copilot"),
+                session.assistant_messages().len()
+            );
+        }
+
         /// Property: content_len equals content.len()
         #[test]
         fn prop_content_len_matches(msg in message_strategy()) {
@@ -1293,6 +2775,13 @@ mod property_tests {
             prop_assert_eq!(session.is_empty(), session.messages.is_empty());
         }
 
+        /// Property: estimated_tokens is a quarter of the total content length
+        #[test]
+        fn prop_estimated_tokens_quarter_of_content(session in session_strategy()) {
+            let total: usize = session.messages.iter().map(|m| m.content.len()).sum();
+            prop_assert_eq!(session.estimated_tokens(), (total / 4) as u64);
+        }
+
         /// Property: user_messages returns only User role messages
         #[test]
         fn prop_user_messages_role(session in session_strategy()) {
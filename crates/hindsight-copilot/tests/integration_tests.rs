@@ -7,7 +7,8 @@
 
 use chrono::Utc;
 use hindsight_copilot::session::{
-    ChatMessage, ChatSession, MessageRole, default_chat_sessions_dir,
+    ChatMessage, ChatSession, MessageRole, SessionFormat, default_chat_sessions_dir,
+    detect_session_format, parse_session_file,
 };
 use std::path::Path;
 
@@ -50,6 +51,47 @@ fn test_parse_sample_chat_session() {
     println!("Parsed {} requests from chat session", requests.len());
 }
 
+#[test]
+fn test_detect_and_parse_v3_session() {
+    let fixture_path = fixtures_dir().join("chat-session-sample.json");
+    let content = std::fs::read_to_string(&fixture_path).expect("read fixture");
+    assert_eq!(
+        detect_session_format(&content).expect("detect"),
+        SessionFormat::VsCodeV3
+    );
+
+    let session = parse_session_file(&fixture_path, "ws").expect("parse v3 session");
+    assert_eq!(session.id, "8f1d6c2a-3b4e-4f5a-9c7d-0e1f2a3b4c5d");
+    assert_eq!(session.user_messages().len(), 2);
+    assert_eq!(session.assistant_messages().len(), 2);
+    assert_eq!(
+        session.messages[1].agent,
+        Some("GitHub Copilot".to_string())
+    );
+}
+
+#[test]
+fn test_detect_and_parse_v2_session() {
+    let fixture_path = fixtures_dir().join("chat-session-v2-sample.json");
+    let content = std::fs::read_to_string(&fixture_path).expect("read fixture");
+    assert_eq!(
+        detect_session_format(&content).expect("detect"),
+        SessionFormat::VsCodeV2
+    );
+
+    let session = parse_session_file(&fixture_path, "ws").expect("parse v2 session");
+    assert_eq!(session.id, "2c9e7b1a-5d3f-4e8a-b6c4-1f0e9d8c7b6a");
+
+    let user_msgs = session.user_messages();
+    assert_eq!(user_msgs.len(), 2);
+    assert_eq!(user_msgs[0].content, "How do I return an error from main?");
+    assert_eq!(user_msgs[1].content, "What does the ? operator do?");
+
+    let assistant_msgs = session.assistant_messages();
+    assert_eq!(assistant_msgs.len(), 2);
+    assert!(assistant_msgs[0].content.contains("Result"));
+}
+
 #[test]
 fn test_extract_messages_from_chat_session() {
     let fixture_path = fixtures_dir().join("chat-session-sample.json");
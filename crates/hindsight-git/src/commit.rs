@@ -4,10 +4,12 @@
 //! Git commit types and operations
 
 use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Represents a parsed git commit
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Commit {
     /// The commit SHA (40 hex characters)
     pub sha: String,
@@ -53,6 +55,44 @@ impl Commit {
     pub fn subject(&self) -> &str {
         self.message.lines().next().unwrap_or("")
     }
+
+    /// Check if this commit was created by `git revert`
+    #[must_use]
+    pub fn is_revert(&self) -> bool {
+        is_revert(&self.message)
+    }
+
+    /// Get the SHA of the commit this one reverts, if recorded
+    #[must_use]
+    pub fn reverted_sha(&self) -> Option<&str> {
+        reverted_sha(&self.message)
+    }
+}
+
+/// Marker line `git revert` adds to the message body
+const REVERT_MARKER: &str = "This reverts commit ";
+
+/// Check if a commit message looks like one written by `git revert`
+///
+/// Matches a `Revert "..."` subject or a `This reverts commit <sha>` line.
+#[must_use]
+pub fn is_revert(message: &str) -> bool {
+    message.starts_with("Revert \"") || reverted_sha(message).is_some()
+}
+
+/// Extract the reverted commit SHA from a `git revert` message
+///
+/// Looks for the `This reverts commit <sha>.` line and returns the SHA when
+/// it is at least 7 hex characters long.
+#[must_use]
+pub fn reverted_sha(message: &str) -> Option<&str> {
+    message.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(REVERT_MARKER)?;
+        let end = rest
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(rest.len());
+        (end >= 7).then(|| &rest[..end])
+    })
 }
 
 #[cfg(test)]
@@ -74,6 +114,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_commit_serialization_roundtrip() {
         let commit = sample_commit();
         let json = serde_json::to_string(&commit).expect("serialize");
@@ -82,6 +123,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_commit_json_format() {
         let commit = sample_commit();
         let json = serde_json::to_string_pretty(&commit).expect("serialize");
@@ -185,6 +227,39 @@ mod tests {
     }
 
     #[test]
+    fn test_revert_message_from_git() {
+        let mut commit = sample_commit();
+        commit.message = "Revert \"feat(skills): add milestone-creator\"\n\n\
+                          This reverts commit 1945ab9c752534e733c38ba0109dc3b741f0a6eb.\n"
+            .to_string();
+
+        assert!(commit.is_revert());
+        assert_eq!(
+            commit.reverted_sha(),
+            Some("1945ab9c752534e733c38ba0109dc3b741f0a6eb")
+        );
+    }
+
+    #[test]
+    fn test_revert_detection_edge_cases() {
+        assert!(!is_revert("feat: add revert support"));
+        assert_eq!(reverted_sha("feat: add revert support"), None);
+
+        // Reworded subject, marker still present
+        let reworded = "Back out the parser change\n\nThis reverts commit c460aeb7fb2d.";
+        assert!(is_revert(reworded));
+        assert_eq!(reverted_sha(reworded), Some("c460aeb7fb2d"));
+
+        // Subject only, e.g. when the body was edited away
+        assert!(is_revert("Revert \"fix: typo\""));
+        assert_eq!(reverted_sha("Revert \"fix: typo\""), None);
+
+        // Too short to be a SHA
+        assert_eq!(reverted_sha("This reverts commit abc."), None);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
     fn test_timestamp_iso8601_serialization() {
         let commit = sample_commit();
         let json = serde_json::to_string(&commit).expect("serialize");
@@ -241,6 +316,7 @@ mod property_tests {
 
         /// Property: Round-trip JSON serialization preserves all fields
         #[test]
+        #[cfg(feature = "serde")]
         fn prop_commit_roundtrip_serialization(commit in commit_strategy()) {
             let json = serde_json::to_string(&commit).expect("serialize");
             let deserialized: Commit = serde_json::from_str(&json).expect("deserialize");
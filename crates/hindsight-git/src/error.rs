@@ -3,6 +3,8 @@
 
 //! Error types for hindsight-git
 
+use std::path::PathBuf;
+
 use thiserror::Error;
 
 /// Errors that can occur during git operations
@@ -19,6 +21,13 @@ pub enum GitError {
         path: String,
     },
 
+    /// The path exists but is not inside a git repository
+    #[error("Not a git repository: {}", path.display())]
+    NotARepository {
+        /// The path that was opened
+        path: PathBuf,
+    },
+
     /// Invalid commit reference (branch, tag, or SHA)
     #[error("Invalid commit reference: {reference}")]
     InvalidReference {
@@ -25,14 +25,21 @@
 pub mod commit;
 pub mod error;
 pub mod parser;
+pub mod tags;
 
 pub use commit::Commit;
 pub use error::GitError;
-pub use parser::{CommitWithDiff, DiffSummary, FileDiff, GitRepo, WalkOptions};
+pub use parser::{
+    CommitStats, CommitWalk, CommitWithDiff, DiffSummary, FileDiff, FileDiffStats, FilePatch,
+    GitRepo, WalkOptions, dominant_language, file_extension, file_extension_stats,
+    language_for_extension,
+};
+pub use tags::TagRecord;
 
 /// Re-export commonly used types
 pub mod prelude {
     pub use crate::commit::Commit;
     pub use crate::error::GitError;
     pub use crate::parser::{CommitWithDiff, DiffSummary, GitRepo, WalkOptions};
+    pub use crate::tags::TagRecord;
 }
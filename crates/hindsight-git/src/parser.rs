@@ -8,9 +8,12 @@
 
 use crate::commit::Commit;
 use crate::error::GitError;
+use crate::tags::TagRecord;
 use chrono::{DateTime, TimeZone, Utc};
-use git2::{DiffOptions, Repository, Sort};
+use git2::{DiffFindOptions, DiffOptions, Repository, Sort};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 /// Configuration for walking commits
@@ -26,6 +29,18 @@ pub struct WalkOptions {
     pub until: Option<DateTime<Utc>>,
     /// Include diff information for each commit
     pub include_diff: bool,
+    /// Include line statistics for each commit, even without the full diff
+    pub include_stats: bool,
+    /// Walk from the root commit towards HEAD instead of newest-first
+    pub oldest_first: bool,
+    /// Leave out this commit and every commit reachable from it
+    pub exclude_ref: Option<String>,
+    /// Stop walking at the first commit older than this date
+    pub stop_before: Option<DateTime<Utc>>,
+    /// Stop walking when this commit SHA is reached, leaving it out
+    pub stop_at_sha: Option<String>,
+    /// Only include commits whose author name contains this (case-insensitive)
+    pub author_filter: Option<String>,
 }
 
 impl WalkOptions {
@@ -38,6 +53,56 @@ impl WalkOptions {
         }
     }
 
+    /// Create options that stop at the first commit older than a date
+    ///
+    /// Unlike [`WalkOptions::since`], which filters every commit, the walk
+    /// ends as soon as an older commit is reached, so history before `date`
+    /// is never read. In an [`oldest_first`](WalkOptions::oldest_first) walk
+    /// older commits are skipped instead.
+    #[must_use]
+    pub fn until_date(date: DateTime<Utc>) -> Self {
+        Self::default().since_date(date)
+    }
+
+    /// Stop at the first commit older than a date
+    ///
+    /// Same as [`WalkOptions::until_date`], for use in a builder chain.
+    #[must_use]
+    pub fn since_date(mut self, date: DateTime<Utc>) -> Self {
+        self.stop_before = Some(date);
+        self
+    }
+
+    /// Stop walking when the commit with this full SHA is reached
+    ///
+    /// The commit itself is left out, so the walk yields only commits made
+    /// since it, without reading older history. In an
+    /// [`oldest_first`](WalkOptions::oldest_first) walk the commits up to and
+    /// including it are skipped instead.
+    #[must_use]
+    pub fn since_sha(mut self, sha: &str) -> Self {
+        self.stop_at_sha = Some(sha.to_string());
+        self
+    }
+
+    /// Only include commits whose author name contains `name`
+    ///
+    /// Matching is case-insensitive, so `"ada"` matches `"Ada Lovelace"`.
+    #[must_use]
+    pub fn by_author(mut self, name: &str) -> Self {
+        self.author_filter = Some(name.to_string());
+        self
+    }
+
+    /// Limit the walk to at most N commits
+    ///
+    /// Same as [`WalkOptions::latest`], for use in a builder chain.
+    #[must_use]
+    pub fn with_max_count(mut self, n: usize) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
     /// Create options with diff extraction enabled
     #[must_use]
     pub fn with_diff(mut self) -> Self {
@@ -45,6 +110,16 @@ impl WalkOptions {
         self
     }
 
+    /// Create options with line statistics enabled
+    ///
+    /// Statistics are cheap to store and are derived from the diff when
+    /// [`WalkOptions::with_diff`] is also set.
+    #[must_use]
+    pub fn with_stats(mut self) -> Self {
+        self.include_stats = true;
+        self
+    }
+
     /// Set the starting reference
     #[must_use]
     pub fn from(mut self, reference: &str) -> Self {
@@ -52,6 +127,15 @@ impl WalkOptions {
         self
     }
 
+    /// Leave out commits reachable from a reference
+    ///
+    /// Equivalent to `git log <reference>..HEAD`.
+    #[must_use]
+    pub fn excluding(mut self, reference: &str) -> Self {
+        self.exclude_ref = Some(reference.to_string());
+        self
+    }
+
     /// Filter commits since a date
     #[must_use]
     pub fn since(mut self, date: DateTime<Utc>) -> Self {
@@ -65,10 +149,20 @@ impl WalkOptions {
         self.until = Some(date);
         self
     }
+
+    /// Walk oldest-first, so parents are always visited before their children
+    ///
+    /// Combined with a limit, this yields the N oldest commits.
+    #[must_use]
+    pub fn oldest_first(mut self) -> Self {
+        self.oldest_first = true;
+        self
+    }
 }
 
 /// Represents file changes in a commit
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FileDiff {
     /// Path to the file
     pub path: String,
@@ -80,8 +174,27 @@ pub struct FileDiff {
     pub deletions: usize,
 }
 
+/// Full patch for one file changed by a commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FilePatch {
+    /// Path to the file after the change
+    pub path: String,
+    /// Previous path, set for renamed and copied files
+    pub old_path: Option<String>,
+    /// Change status: "added", "modified", "deleted", "renamed"
+    pub status: String,
+    /// Number of lines added
+    pub insertions: usize,
+    /// Number of lines deleted
+    pub deletions: usize,
+    /// Unified diff text, empty for binary files
+    pub patch: String,
+}
+
 /// Summary of all changes in a commit
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DiffSummary {
     /// Number of files changed
     pub files_changed: usize,
@@ -104,16 +217,293 @@ impl DiffSummary {
             files: Vec::new(),
         }
     }
+
+    /// Serialize the summary to a JSON string
+    ///
+    /// # Errors
+    ///
+    /// Returns `serde_json::Error` if serialization fails.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserialize a summary from a JSON string
+    ///
+    /// # Errors
+    ///
+    /// Returns `serde_json::Error` if the JSON is not a valid summary.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Line-level statistics for a commit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CommitStats {
+    /// Number of files changed
+    pub files_changed: u32,
+    /// Total lines added
+    pub insertions: u64,
+    /// Total lines deleted
+    pub deletions: u64,
+}
+
+impl From<&DiffSummary> for CommitStats {
+    fn from(diff: &DiffSummary) -> Self {
+        Self {
+            files_changed: diff.files_changed as u32,
+            insertions: diff.insertions as u64,
+            deletions: diff.deletions as u64,
+        }
+    }
+}
+
+/// Aggregated line changes for a group of files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FileDiffStats {
+    /// Number of files in the group
+    pub files: usize,
+    /// Total lines added
+    pub insertions: usize,
+    /// Total lines deleted
+    pub deletions: usize,
+}
+
+/// Get the extension used to group a path in [`file_extension_stats`]
+///
+/// Extensions are lowercased. Dot-files such as `.gitignore` use the name
+/// after the leading dot, and files without an extension map to `""`.
+#[must_use]
+pub fn file_extension(path: &str) -> String {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    if let Some(rest) = file_name.strip_prefix('.')
+        && !rest.contains('.')
+    {
+        return rest.to_ascii_lowercase();
+    }
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => ext.to_ascii_lowercase(),
+        _ => String::new(),
+    }
+}
+
+/// Aggregate a diff's insertions and deletions by file extension
+#[must_use]
+pub fn file_extension_stats(diff: &DiffSummary) -> HashMap<String, FileDiffStats> {
+    let mut stats: HashMap<String, FileDiffStats> = HashMap::new();
+    for file in &diff.files {
+        let entry = stats.entry(file_extension(&file.path)).or_default();
+        entry.files += 1;
+        entry.insertions += file.insertions;
+        entry.deletions += file.deletions;
+    }
+    stats
+}
+
+/// Map a file extension (as returned by [`file_extension`]) to a language name
+#[must_use]
+pub fn language_for_extension(extension: &str) -> Option<&'static str> {
+    let language = match extension {
+        "rs" => "Rust",
+        "py" | "pyi" => "Python",
+        "js" | "mjs" | "cjs" | "jsx" => "JavaScript",
+        "ts" | "mts" | "cts" | "tsx" => "TypeScript",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => "C++",
+        "cs" => "C#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "swift" => "Swift",
+        "scala" => "Scala",
+        "sh" | "bash" | "zsh" => "Shell",
+        "sql" => "SQL",
+        "html" | "htm" => "HTML",
+        "css" | "scss" | "sass" => "CSS",
+        "lua" => "Lua",
+        "zig" => "Zig",
+        "hs" => "Haskell",
+        "ex" | "exs" => "Elixir",
+        "dart" => "Dart",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Get the language with the most added lines in a diff
+///
+/// Files whose extension doesn't map to a known language (documentation,
+/// config, lock files) are ignored. Returns `None` if no recognised source
+/// lines were added. Ties are broken alphabetically.
+#[must_use]
+pub fn dominant_language(diff: &DiffSummary) -> Option<&'static str> {
+    let mut insertions: HashMap<&'static str, usize> = HashMap::new();
+    for file in &diff.files {
+        if let Some(language) = language_for_extension(&file_extension(&file.path)) {
+            *insertions.entry(language).or_default() += file.insertions;
+        }
+    }
+
+    insertions
+        .into_iter()
+        .filter(|&(_, added)| added > 0)
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(language, _)| language)
+}
+
+/// Lazy iterator over the commits selected by [`WalkOptions`]
+///
+/// Created by [`GitRepo::commits`].
+pub struct CommitWalk<'repo> {
+    repo: &'repo GitRepo,
+    revwalk: git2::Revwalk<'repo>,
+    options: WalkOptions,
+    author_filter: Option<String>,
+    skipping_to_sha: bool,
+    yielded: usize,
+    done: bool,
+}
+
+impl CommitWalk<'_> {
+    /// Read the next commit that passes the walk's filters
+    fn next_commit(&mut self) -> Result<Option<CommitWithDiff>, GitError> {
+        if self.yielded >= self.options.limit.unwrap_or(usize::MAX) {
+            return Ok(None);
+        }
+
+        let options = &self.options;
+        for oid_result in self.revwalk.by_ref() {
+            let oid = oid_result?;
+            if let Some(ref stop_at_sha) = options.stop_at_sha
+                && oid.to_string() == *stop_at_sha
+            {
+                if options.oldest_first {
+                    self.skipping_to_sha = false;
+                    continue;
+                }
+                return Ok(None);
+            }
+            if self.skipping_to_sha {
+                continue;
+            }
+            let git_commit = self.repo.repo.find_commit(oid)?;
+            let timestamp = git_time(git_commit.time());
+
+            if let Some(stop_before) = options.stop_before
+                && timestamp < stop_before
+            {
+                if options.oldest_first {
+                    continue;
+                }
+                return Ok(None);
+            }
+
+            // Apply date filters
+            if let Some(since) = options.since
+                && timestamp < since
+            {
+                continue;
+            }
+            if let Some(until) = options.until
+                && timestamp > until
+            {
+                continue;
+            }
+            if let Some(ref author_filter) = self.author_filter {
+                let author = git_commit.author().name().unwrap_or("").to_lowercase();
+                if !author.contains(author_filter.as_str()) {
+                    continue;
+                }
+            }
+
+            let commit = self.repo.extract_commit(&git_commit, timestamp)?;
+            let diff = if options.include_diff {
+                Some(self.repo.extract_diff(&git_commit)?)
+            } else {
+                None
+            };
+            let stats = match &diff {
+                Some(diff) => Some(CommitStats::from(diff)),
+                None if options.include_stats => Some(self.repo.extract_stats(&git_commit)?),
+                None => None,
+            };
+
+            return Ok(Some(CommitWithDiff {
+                commit,
+                diff,
+                stats,
+            }));
+        }
+
+        Ok(None)
+    }
+}
+
+impl Iterator for CommitWalk<'_> {
+    type Item = Result<CommitWithDiff, GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_commit().transpose();
+        match next {
+            Some(Ok(_)) => self.yielded += 1,
+            _ => self.done = true,
+        }
+        next
+    }
+}
+
+/// Map a failure to open the repository at `path` to a [`GitError`]
+fn open_error(path: &Path, error: &git2::Error) -> GitError {
+    if error.code() == git2::ErrorCode::NotFound {
+        GitError::NotARepository {
+            path: path.to_path_buf(),
+        }
+    } else {
+        GitError::RepositoryNotFound {
+            path: path.display().to_string(),
+        }
+    }
+}
+
+/// Convert a git timestamp to UTC
+fn git_time(time: git2::Time) -> DateTime<Utc> {
+    Utc.timestamp_opt(time.seconds(), 0)
+        .single()
+        .unwrap_or_else(Utc::now)
+}
+
+/// Name a diff delta status the way [`FileDiff::status`] reports it
+fn delta_status(status: git2::Delta) -> &'static str {
+    match status {
+        git2::Delta::Added => "added",
+        git2::Delta::Deleted => "deleted",
+        git2::Delta::Modified => "modified",
+        git2::Delta::Renamed => "renamed",
+        git2::Delta::Copied => "copied",
+        _ => "unknown",
+    }
 }
 
 /// A commit with optional diff information
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CommitWithDiff {
     /// The commit data
-    #[serde(flatten)]
+    #[cfg_attr(feature = "serde", serde(flatten))]
     pub commit: Commit,
     /// Diff summary (if requested)
     pub diff: Option<DiffSummary>,
+    /// Line statistics (if requested, or derived from the diff)
+    pub stats: Option<CommitStats>,
 }
 
 /// A git repository wrapper for parsing commits
@@ -126,12 +516,12 @@ impl GitRepo {
     ///
     /// # Errors
     ///
-    /// Returns `GitError::RepositoryNotFound` if the path is not a git repository.
+    /// Returns `GitError::NotARepository` if the path is not a git repository,
+    /// or `GitError::RepositoryNotFound` if it cannot be opened for another
+    /// reason.
     pub fn open(path: impl AsRef<Path>) -> Result<Self, GitError> {
         let path = path.as_ref();
-        let repo = Repository::open(path).map_err(|_| GitError::RepositoryNotFound {
-            path: path.display().to_string(),
-        })?;
+        let repo = Repository::open(path).map_err(|e| open_error(path, &e))?;
         Ok(Self { repo })
     }
 
@@ -141,12 +531,11 @@ impl GitRepo {
     ///
     /// # Errors
     ///
-    /// Returns `GitError::RepositoryNotFound` if no repository is found.
+    /// Returns `GitError::NotARepository` if no repository is found, or
+    /// `GitError::RepositoryNotFound` if it cannot be opened for another reason.
     pub fn discover(path: impl AsRef<Path>) -> Result<Self, GitError> {
         let path = path.as_ref();
-        let repo = Repository::discover(path).map_err(|_| GitError::RepositoryNotFound {
-            path: path.display().to_string(),
-        })?;
+        let repo = Repository::discover(path).map_err(|e| open_error(path, &e))?;
         Ok(Self { repo })
     }
 
@@ -170,12 +559,33 @@ impl GitRepo {
 
     /// Walk commits according to the given options
     ///
+    /// Collects [`GitRepo::commits`] into a vector, so every commit (and
+    /// diff) is held in memory at once. Prefer [`GitRepo::commits`] for
+    /// large histories.
+    ///
     /// # Errors
     ///
     /// Returns `GitError` if the repository cannot be walked.
     pub fn walk_commits(&self, options: &WalkOptions) -> Result<Vec<CommitWithDiff>, GitError> {
+        self.commits(options)?.collect()
+    }
+
+    /// Walk commits lazily according to the given options
+    ///
+    /// Each commit, with its diff or statistics, is read only when the
+    /// iterator yields it. The iterator ends after the first error.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError` if the walk cannot be set up, e.g. because a
+    /// reference in `options` does not resolve.
+    pub fn commits(&self, options: &WalkOptions) -> Result<CommitWalk<'_>, GitError> {
         let mut revwalk = self.repo.revwalk()?;
-        revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL)?;
+        if options.oldest_first {
+            revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL | Sort::REVERSE)?;
+        } else {
+            revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL)?;
+        }
 
         // Start from specified ref or HEAD
         if let Some(ref from_ref) = options.from_ref {
@@ -185,47 +595,209 @@ impl GitRepo {
             revwalk.push_head()?;
         }
 
-        let mut commits = Vec::new();
-        let limit = options.limit.unwrap_or(usize::MAX);
+        if let Some(ref exclude_ref) = options.exclude_ref {
+            let oid = self
+                .repo
+                .revparse_single(exclude_ref)?
+                .peel_to_commit()?
+                .id();
+            revwalk.hide(oid)?;
+        }
 
-        for oid_result in revwalk {
-            if commits.len() >= limit {
-                break;
-            }
+        Ok(CommitWalk {
+            repo: self,
+            revwalk,
+            options: options.clone(),
+            author_filter: options.author_filter.as_deref().map(str::to_lowercase),
+            skipping_to_sha: options.oldest_first && options.stop_at_sha.is_some(),
+            yielded: 0,
+            done: false,
+        })
+    }
 
-            let oid = oid_result?;
-            let git_commit = self.repo.find_commit(oid)?;
+    /// Walk commits made since a tag, newest first
+    ///
+    /// Returns commits reachable from HEAD but not from the commit the tag
+    /// points to, like `git log <tag>..HEAD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::InvalidReference` if the tag does not exist, or
+    /// `GitError` if the repository cannot be walked.
+    pub fn walk_commits_since_tag(
+        &self,
+        tag_name: &str,
+        include_diff: bool,
+    ) -> Result<Vec<CommitWithDiff>, GitError> {
+        let target = self.tag_target(tag_name)?;
+        let options = WalkOptions::default().excluding(&target);
+        let options = if include_diff {
+            options.with_diff()
+        } else {
+            options
+        };
+        self.walk_commits(&options)
+    }
 
-            // Convert timestamp
-            let time = git_commit.time();
-            let timestamp = Utc
-                .timestamp_opt(time.seconds(), 0)
-                .single()
-                .unwrap_or_else(Utc::now);
+    /// Resolve a tag to the SHA of the commit it points to
+    ///
+    /// Both lightweight and annotated tags are supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::InvalidReference` if the tag does not exist or does
+    /// not point to a commit.
+    pub fn tag_target(&self, tag_name: &str) -> Result<String, GitError> {
+        let invalid = || GitError::InvalidReference {
+            reference: tag_name.to_string(),
+        };
+        let reference = self
+            .repo
+            .find_reference(&format!("refs/tags/{tag_name}"))
+            .map_err(|_| invalid())?;
+        let commit = reference.peel_to_commit().map_err(|_| invalid())?;
+        Ok(commit.id().to_string())
+    }
 
-            // Apply date filters
-            if let Some(since) = options.since
-                && timestamp < since
-            {
+    /// Get the SHAs of every commit reachable from a tag
+    ///
+    /// Includes the tagged commit itself, like `git log <tag>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::InvalidReference` if the tag does not exist, or
+    /// `GitError` if the repository cannot be walked.
+    pub fn commits_reachable_from_tag(&self, tag_name: &str) -> Result<HashSet<String>, GitError> {
+        let target = git2::Oid::from_str(&self.tag_target(tag_name)?)?;
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(target)?;
+
+        let mut shas = HashSet::new();
+        for oid in revwalk {
+            shas.insert(oid?.to_string());
+        }
+        Ok(shas)
+    }
+
+    /// List every tag that points at a commit, oldest first
+    ///
+    /// Annotated tags report their tagger, message, and tagging time;
+    /// lightweight tags use the time of the tagged commit. Tags on trees or
+    /// blobs are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError` if the tag names or references cannot be read.
+    pub fn get_tags(&self) -> Result<Vec<TagRecord>, GitError> {
+        let names = self.repo.tag_names(None)?;
+
+        let mut tags = Vec::new();
+        for name in names.iter().flatten() {
+            let reference = self.repo.find_reference(&format!("refs/tags/{name}"))?;
+            let Ok(commit) = reference.peel_to_commit() else {
                 continue;
-            }
-            if let Some(until) = options.until
-                && timestamp > until
-            {
+            };
+            let Some(target) = reference.target() else {
                 continue;
+            };
+
+            let record = match reference.peel_to_tag() {
+                Ok(tag) => {
+                    let tagger = tag.tagger();
+                    let time = tagger.as_ref().map_or(commit.time(), |t| t.when());
+                    TagRecord {
+                        name: name.to_string(),
+                        sha: target.to_string(),
+                        tagged_sha: commit.id().to_string(),
+                        tagger: tagger.as_ref().and_then(|t| t.name()).map(String::from),
+                        message: tag.message().map(|m| m.trim_end().to_string()),
+                        is_annotated: true,
+                        timestamp: git_time(time),
+                    }
+                }
+                Err(_) => TagRecord {
+                    name: name.to_string(),
+                    sha: target.to_string(),
+                    tagged_sha: commit.id().to_string(),
+                    tagger: None,
+                    message: None,
+                    is_annotated: false,
+                    timestamp: git_time(commit.time()),
+                },
+            };
+            tags.push(record);
+        }
+
+        tags.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(tags)
+    }
+
+    /// Find commits that changed a file, newest first
+    ///
+    /// `path` is relative to the repository root and is matched as a git
+    /// pathspec, so a directory matches every file beneath it. As with
+    /// `git log <path>`, a merge commit is only included when the file
+    /// differs from every parent.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError` if the repository cannot be walked or diffed.
+    pub fn find_commits_touching_file(
+        &self,
+        path: &str,
+        limit: usize,
+    ) -> Result<Vec<Commit>, GitError> {
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL)?;
+        revwalk.push_head()?;
+
+        let mut opts = DiffOptions::new();
+        opts.pathspec(path);
+
+        let mut commits = Vec::new();
+        for oid_result in revwalk {
+            if commits.len() >= limit {
+                break;
             }
 
-            // Extract commit data
-            let commit = self.extract_commit(&git_commit, timestamp)?;
+            let git_commit = self.repo.find_commit(oid_result?)?;
+            let tree = git_commit.tree()?;
 
-            // Extract diff if requested
-            let diff = if options.include_diff {
-                Some(self.extract_diff(&git_commit)?)
+            let touched = if git_commit.parent_count() == 0 {
+                let diff = self
+                    .repo
+                    .diff_tree_to_tree(None, Some(&tree), Some(&mut opts))?;
+                diff.deltas().len() > 0
             } else {
-                None
+                let mut differs_from_all = true;
+                for parent in git_commit.parents() {
+                    let parent_tree = parent.tree()?;
+                    let diff = self.repo.diff_tree_to_tree(
+                        Some(&parent_tree),
+                        Some(&tree),
+                        Some(&mut opts),
+                    )?;
+                    if diff.deltas().len() == 0 {
+                        differs_from_all = false;
+                        break;
+                    }
+                }
+                differs_from_all
             };
 
-            commits.push(CommitWithDiff { commit, diff });
+            if touched {
+                let time = git_commit.time();
+                let timestamp = Utc
+                    .timestamp_opt(time.seconds(), 0)
+                    .single()
+                    .unwrap_or_else(Utc::now);
+                commits.push(self.extract_commit(&git_commit, timestamp)?);
+            }
         }
 
         Ok(commits)
@@ -254,6 +826,27 @@ impl GitRepo {
         })
     }
 
+    /// Compute line statistics against the first parent without keeping the diff
+    fn extract_stats(&self, git_commit: &git2::Commit<'_>) -> Result<CommitStats, GitError> {
+        let tree = git_commit.tree()?;
+        let parent_tree = if git_commit.parent_count() > 0 {
+            Some(git_commit.parent(0)?.tree()?)
+        } else {
+            None
+        };
+
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let stats = diff.stats()?;
+
+        Ok(CommitStats {
+            files_changed: stats.files_changed() as u32,
+            insertions: stats.insertions() as u64,
+            deletions: stats.deletions() as u64,
+        })
+    }
+
     /// Extract diff summary for a commit
     fn extract_diff(&self, git_commit: &git2::Commit<'_>) -> Result<DiffSummary, GitError> {
         let tree = git_commit.tree()?;
@@ -275,7 +868,7 @@ impl GitRepo {
         let stats = diff.stats()?;
         let mut files = Vec::new();
 
-        for delta in diff.deltas() {
+        for (idx, delta) in diff.deltas().enumerate() {
             let path = delta
                 .new_file()
                 .path()
@@ -283,23 +876,22 @@ impl GitRepo {
                 .map(|p| p.display().to_string())
                 .unwrap_or_else(|| "<unknown>".to_string());
 
-            let status = match delta.status() {
-                git2::Delta::Added => "added",
-                git2::Delta::Deleted => "deleted",
-                git2::Delta::Modified => "modified",
-                git2::Delta::Renamed => "renamed",
-                git2::Delta::Copied => "copied",
-                _ => "unknown",
-            }
-            .to_string();
+            let status = delta_status(delta.status()).to_string();
+
+            // Binary files have no patch, so their line counts stay at 0
+            let (insertions, deletions) = match git2::Patch::from_diff(&diff, idx)? {
+                Some(patch) => {
+                    let (_context, additions, deletions) = patch.line_stats()?;
+                    (additions, deletions)
+                }
+                None => (0, 0),
+            };
 
-            // Note: per-file stats require iterating hunks, which is expensive
-            // We'll use 0 for per-file and rely on total stats
             files.push(FileDiff {
                 path,
                 status,
-                insertions: 0,
-                deletions: 0,
+                insertions,
+                deletions,
             });
         }
 
@@ -337,9 +929,80 @@ impl GitRepo {
             .unwrap_or_else(Utc::now);
 
         let commit = self.extract_commit(&git_commit, timestamp)?;
-        let diff = Some(self.extract_diff(&git_commit)?);
+        let diff = self.extract_diff(&git_commit)?;
+        let stats = Some(CommitStats::from(&diff));
 
-        Ok(CommitWithDiff { commit, diff })
+        Ok(CommitWithDiff {
+            commit,
+            diff: Some(diff),
+            stats,
+        })
+    }
+
+    /// Get the full patch of every file changed by a commit
+    ///
+    /// The commit is diffed against its first parent, or against the empty
+    /// tree for root commits. Renames are detected so moved files report
+    /// their previous path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::InvalidReference` if the reference cannot be resolved,
+    /// or `GitError` if the diff cannot be computed.
+    pub fn commit_patches(&self, reference: &str) -> Result<Vec<FilePatch>, GitError> {
+        let git_commit = self
+            .repo
+            .revparse_single(reference)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|_| GitError::InvalidReference {
+                reference: reference.to_string(),
+            })?;
+
+        let tree = git_commit.tree()?;
+        let parent_tree = if git_commit.parent_count() > 0 {
+            Some(git_commit.parent(0)?.tree()?)
+        } else {
+            None
+        };
+
+        let mut diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+        let mut patches = Vec::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            let new_path = delta.new_file().path().map(|p| p.display().to_string());
+            let old_path = delta.old_file().path().map(|p| p.display().to_string());
+            let path = new_path
+                .clone()
+                .or_else(|| old_path.clone())
+                .unwrap_or_else(|| "<unknown>".to_string());
+            let old_path = match delta.status() {
+                git2::Delta::Renamed | git2::Delta::Copied => old_path,
+                _ => None,
+            };
+
+            let (insertions, deletions, patch) = match git2::Patch::from_diff(&diff, idx)? {
+                Some(mut patch) => {
+                    let (_context, additions, deletions) = patch.line_stats()?;
+                    let text = patch.to_buf()?.as_str().unwrap_or_default().to_string();
+                    (additions, deletions, text)
+                }
+                None => (0, 0, String::new()),
+            };
+
+            patches.push(FilePatch {
+                path,
+                old_path,
+                status: delta_status(delta.status()).to_string(),
+                insertions,
+                deletions,
+                patch,
+            });
+        }
+
+        Ok(patches)
     }
 
     /// Get the HEAD commit SHA
@@ -365,6 +1028,69 @@ impl GitRepo {
         revwalk.push_head()?;
         Ok(revwalk.count())
     }
+
+    /// Compute a fingerprint identifying the repository across clones
+    ///
+    /// The fingerprint is a hash of the root commit SHA reachable from HEAD,
+    /// so separate checkouts of the same history share it. When history has
+    /// several roots the lexicographically smallest SHA is used. Returns
+    /// `None` for a repository without commits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError` if the repository cannot be walked.
+    pub fn repo_fingerprint(&self) -> Result<Option<String>, GitError> {
+        if self.repo.head().is_err() {
+            return Ok(None);
+        }
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+
+        let mut root: Option<git2::Oid> = None;
+        for oid in revwalk {
+            let oid = oid?;
+            let commit = self.repo.find_commit(oid)?;
+            if commit.parent_count() == 0 && root.is_none_or(|r| oid < r) {
+                root = Some(oid);
+            }
+        }
+
+        root.map(|oid| {
+            git2::Oid::hash_object(git2::ObjectType::Blob, oid.to_string().as_bytes())
+                .map(|hash| hash.to_string())
+                .map_err(GitError::from)
+        })
+        .transpose()
+    }
+
+    /// List git notes as `(commit_sha, note_body)` pairs
+    ///
+    /// `namespace` is a notes reference such as `refs/notes/ci`; `None` uses
+    /// the default `refs/notes/commits`. A namespace with no notes yields an
+    /// empty list.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError` if the notes reference cannot be read.
+    pub fn list_notes(&self, namespace: Option<&str>) -> Result<Vec<(String, String)>, GitError> {
+        let notes = match self.repo.notes(namespace) {
+            Ok(notes) => notes,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut result = Vec::new();
+        for entry in notes {
+            let (_note_id, commit_id) = entry?;
+            let note = self.repo.find_note(namespace, commit_id)?;
+            let body = note.message().unwrap_or("").to_string();
+            result.push((commit_id.to_string(), body));
+        }
+
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(result)
+    }
 }
 
 #[cfg(test)]
@@ -399,13 +1125,27 @@ mod tests {
         let result = GitRepo::open("/nonexistent/path");
         assert!(result.is_err());
         match result {
-            Err(GitError::RepositoryNotFound { path }) => {
-                assert!(path.contains("nonexistent"));
+            Err(GitError::NotARepository { path }) => {
+                assert!(path.to_string_lossy().contains("nonexistent"));
             }
-            _ => panic!("Expected RepositoryNotFound error"),
+            _ => panic!("Expected NotARepository error"),
         }
     }
 
+    #[test]
+    fn test_open_non_git_directory() {
+        let temp_dir = env::temp_dir();
+        match GitRepo::open(&temp_dir) {
+            Err(GitError::NotARepository { path }) => assert_eq!(path, temp_dir),
+            Err(e) => panic!("Expected NotARepository error, got {e}"),
+            Ok(_) => panic!("Expected {} not to be a repository", temp_dir.display()),
+        }
+
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+        let workspace_root = Path::new(&manifest_dir).join("../..");
+        assert!(GitRepo::open(workspace_root).is_ok());
+    }
+
     #[test]
     fn test_head_sha() {
         let repo = get_repo();
@@ -422,6 +1162,27 @@ mod tests {
         assert!(!commits.is_empty());
     }
 
+    #[test]
+    fn test_commits_iterator_matches_walk() {
+        let now = Utc::now();
+        let path = dated_repo("commits_iterator", now, &daily_ages());
+        let repo = GitRepo::open(&path).expect("open repo");
+        let options = WalkOptions::latest(5).with_diff();
+
+        let walked = repo.walk_commits(&options).expect("walk");
+        let mut lazy = repo.commits(&options).expect("iterator");
+        let first = lazy.next().expect("first commit").expect("readable");
+        assert_eq!(first, walked[0]);
+        assert_eq!(lazy.count(), 4);
+
+        assert!(
+            repo.commits(&WalkOptions::latest(1).from("no-such-ref"))
+                .is_err()
+        );
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
     #[test]
     fn test_walk_commits_with_diff() {
         let repo = get_repo();
@@ -434,6 +1195,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_walk_commits_with_stats_only() {
+        let repo = get_repo();
+        let with_stats = repo
+            .walk_commits(&WalkOptions::latest(3).with_stats())
+            .expect("Should walk commits");
+        let with_diff = repo
+            .walk_commits(&WalkOptions::latest(3).with_diff())
+            .expect("Should walk commits");
+
+        for (stats_only, full) in with_stats.iter().zip(&with_diff) {
+            assert!(stats_only.diff.is_none());
+            let diff = full.diff.as_ref().expect("diff requested");
+            assert_eq!(stats_only.stats, Some(CommitStats::from(diff)));
+            assert_eq!(full.stats, stats_only.stats);
+        }
+
+        let plain = repo
+            .walk_commits(&WalkOptions::latest(1))
+            .expect("Should walk commits");
+        assert!(plain[0].stats.is_none());
+    }
+
+    #[test]
+    fn test_walk_commits_oldest_first() {
+        let repo = get_repo();
+        let commits = repo
+            .walk_commits(&WalkOptions::default().oldest_first())
+            .expect("Should walk commits");
+
+        assert!(!commits.is_empty());
+        assert!(
+            commits[0].commit.parents.is_empty(),
+            "First commit should be the root"
+        );
+
+        // Every parent appears before its child
+        let mut seen = std::collections::HashSet::new();
+        for cwc in &commits {
+            for parent in &cwc.commit.parents {
+                assert!(seen.contains(parent), "parent {parent} not yet visited");
+            }
+            seen.insert(cwc.commit.sha.clone());
+        }
+    }
+
     #[test]
     fn test_commit_extraction_fields() {
         let repo = get_repo();
@@ -479,6 +1286,511 @@ mod tests {
         assert!(count > 0, "Repository should have commits");
     }
 
+    #[test]
+    fn test_walk_commits_since_missing_tag() {
+        let repo = get_repo();
+        match repo.walk_commits_since_tag("no-such-tag-12345", false) {
+            Err(GitError::InvalidReference { reference }) => {
+                assert_eq!(reference, "no-such-tag-12345");
+            }
+            other => panic!("Expected InvalidReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_walk_commits_excluding_head() {
+        let repo = get_repo();
+        let options = WalkOptions::default().excluding("HEAD");
+        let commits = repo.walk_commits(&options).expect("Should walk");
+        assert!(commits.is_empty());
+    }
+
+    #[test]
+    fn test_repo_fingerprint_stable() {
+        let first = get_repo().repo_fingerprint().expect("Should fingerprint");
+        let second = get_repo().repo_fingerprint().expect("Should fingerprint");
+        let fingerprint = first.expect("Repository should have a root commit");
+        assert_eq!(fingerprint.len(), 40);
+        assert_eq!(Some(fingerprint), second);
+    }
+
+    #[test]
+    fn test_file_extension() {
+        assert_eq!(file_extension("src/main.rs"), "rs");
+        assert_eq!(file_extension("Cargo.toml"), "toml");
+        assert_eq!(file_extension("docs/README.MD"), "md");
+        assert_eq!(file_extension("archive.tar.gz"), "gz");
+        assert_eq!(file_extension(".gitignore"), "gitignore");
+        assert_eq!(file_extension("crates/x/.gitignore"), "gitignore");
+        assert_eq!(file_extension(".github/workflows/ci.yml"), "yml");
+        assert_eq!(file_extension(".env.local"), "local");
+        assert_eq!(file_extension("Makefile"), "");
+    }
+
+    #[test]
+    fn test_dominant_language() {
+        let file = |path: &str, insertions| FileDiff {
+            path: path.to_string(),
+            status: "modified".to_string(),
+            insertions,
+            deletions: 0,
+        };
+        let diff = |files: Vec<FileDiff>| DiffSummary {
+            files_changed: files.len(),
+            insertions: files.iter().map(|f| f.insertions).sum(),
+            deletions: 0,
+            files,
+        };
+
+        let mixed = diff(vec![
+            file("src/lib.rs", 60),
+            file("src/main.rs", 40),
+            file("scripts/gen.py", 10),
+            file("README.md", 500),
+        ]);
+        assert_eq!(dominant_language(&mixed), Some("Rust"));
+
+        let python = diff(vec![file("a.py", 10), file("b.rs", 3)]);
+        assert_eq!(dominant_language(&python), Some("Python"));
+
+        let tie = diff(vec![file("a.ts", 5), file("b.go", 5)]);
+        assert_eq!(dominant_language(&tie), Some("Go"));
+
+        let docs = diff(vec![file("README.md", 10), file("Cargo.lock", 100)]);
+        assert_eq!(dominant_language(&docs), None);
+        assert_eq!(dominant_language(&DiffSummary::empty()), None);
+    }
+
+    #[test]
+    fn test_file_extension_stats() {
+        let file = |path: &str, insertions, deletions| FileDiff {
+            path: path.to_string(),
+            status: "modified".to_string(),
+            insertions,
+            deletions,
+        };
+        let diff = DiffSummary {
+            files_changed: 4,
+            insertions: 18,
+            deletions: 3,
+            files: vec![
+                file("src/lib.rs", 10, 2),
+                file("src/main.rs", 5, 1),
+                file("Cargo.toml", 2, 0),
+                file(".gitignore", 1, 0),
+            ],
+        };
+
+        let stats = file_extension_stats(&diff);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(
+            stats["rs"],
+            FileDiffStats {
+                files: 2,
+                insertions: 15,
+                deletions: 3
+            }
+        );
+        assert_eq!(stats["toml"].insertions, 2);
+        assert_eq!(stats["gitignore"].files, 1);
+    }
+
+    #[test]
+    fn test_walk_commits_per_file_stats() {
+        let repo = get_repo();
+        let commits = repo
+            .walk_commits(&WalkOptions::latest(5).with_diff())
+            .expect("walk");
+
+        for c in commits {
+            let diff = c.diff.expect("diff requested");
+            let file_insertions: usize = diff.files.iter().map(|f| f.insertions).sum();
+            let file_deletions: usize = diff.files.iter().map(|f| f.deletions).sum();
+            assert_eq!(file_insertions, diff.insertions);
+            assert_eq!(file_deletions, diff.deletions);
+        }
+    }
+
+    #[test]
+    fn test_find_commits_touching_file() {
+        let repo = get_repo();
+        let commits = repo
+            .find_commits_touching_file("Cargo.toml", 5)
+            .expect("find commits");
+        assert!(!commits.is_empty());
+        assert!(commits.len() <= 5);
+
+        // Every returned commit should list Cargo.toml in its diff
+        for commit in &commits {
+            let with_diff = repo.get_commit(&commit.sha).expect("get commit");
+            let diff = with_diff.diff.expect("diff");
+            if commit.parents.len() <= 1 {
+                assert!(
+                    diff.files.iter().any(|f| f.path == "Cargo.toml"),
+                    "{} should touch Cargo.toml",
+                    commit.short_sha()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_commits_touching_missing_file() {
+        let repo = get_repo();
+        let commits = repo
+            .find_commits_touching_file("no/such/file-hindsight.txt", 5)
+            .expect("find commits");
+        assert!(commits.is_empty());
+    }
+
+    #[test]
+    fn test_list_notes_missing_namespace() {
+        let repo = get_repo();
+        let notes = repo
+            .list_notes(Some("refs/notes/hindsight-nonexistent"))
+            .expect("list notes");
+        assert!(notes.is_empty());
+    }
+
+    /// Create a throwaway repository with one commit per entry in `ages`,
+    /// oldest first, each dated that far before `now`
+    fn dated_repo(name: &str, now: DateTime<Utc>, ages: &[chrono::Duration]) -> std::path::PathBuf {
+        let commits: Vec<(&str, chrono::Duration)> =
+            ages.iter().map(|age| ("Test", *age)).collect();
+        authored_repo(name, now, &commits)
+    }
+
+    /// Build a linear repository of commits by the given authors, oldest first
+    fn authored_repo(
+        name: &str,
+        now: DateTime<Utc>,
+        commits: &[(&str, chrono::Duration)],
+    ) -> std::path::PathBuf {
+        let path = env::temp_dir().join(format!("hindsight_git_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        let repo = Repository::init(&path).expect("init repo");
+        let tree_id = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+
+        let mut parent: Option<git2::Oid> = None;
+        for (i, (author, age)) in commits.iter().enumerate() {
+            let time = git2::Time::new((now - *age).timestamp(), 0);
+            let signature = git2::Signature::new(author, "test@example.com", &time).unwrap();
+            let parent_commit = parent.map(|oid| repo.find_commit(oid).unwrap());
+            let parents: Vec<&git2::Commit<'_>> = parent_commit.iter().collect();
+            let oid = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    &format!("Commit {i}"),
+                    &tree,
+                    &parents,
+                )
+                .unwrap();
+            parent = Some(oid);
+        }
+        path
+    }
+
+    #[test]
+    fn test_commit_patches() {
+        let path = env::temp_dir().join(format!("hindsight_git_patches_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        let repo = Repository::init(&path).expect("init repo");
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+
+        let commit_files = |files: &[(&str, &str)], parent: Option<git2::Oid>| {
+            let mut builder = repo.treebuilder(None).unwrap();
+            for (name, content) in files {
+                let blob = repo.blob(content.as_bytes()).unwrap();
+                builder.insert(name, blob, 0o100_644).unwrap();
+            }
+            let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+            let parent_commit = parent.map(|oid| repo.find_commit(oid).unwrap());
+            let parents: Vec<&git2::Commit<'_>> = parent_commit.iter().collect();
+            repo.commit(Some("HEAD"), &signature, &signature, "msg", &tree, &parents)
+                .unwrap()
+        };
+
+        let moved = "line 1\nline 2\nline 3\nline 4\n";
+        let root = commit_files(&[("a.txt", "one\n"), ("old.txt", moved)], None);
+        let head = commit_files(&[("a.txt", "one\ntwo\n"), ("new.txt", moved)], Some(root));
+
+        let git_repo = GitRepo::open(&path).expect("open repo");
+
+        let root_patches = git_repo.commit_patches(&root.to_string()).expect("root");
+        assert_eq!(root_patches.len(), 2);
+        assert!(root_patches.iter().all(|p| p.status == "added"));
+
+        let patches = git_repo.commit_patches(&head.to_string()).expect("head");
+        assert_eq!(patches.len(), 2);
+
+        let modified = patches.iter().find(|p| p.path == "a.txt").unwrap();
+        assert_eq!(modified.status, "modified");
+        assert_eq!(modified.old_path, None);
+        assert_eq!((modified.insertions, modified.deletions), (1, 0));
+        assert!(modified.patch.contains("+two"));
+
+        let renamed = patches.iter().find(|p| p.path == "new.txt").unwrap();
+        assert_eq!(renamed.status, "renamed");
+        assert_eq!(renamed.old_path.as_deref(), Some("old.txt"));
+
+        assert!(git_repo.commit_patches("nonexistent-ref-12345").is_err());
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    #[test]
+    fn test_get_tags() {
+        let now = Utc::now();
+        let day = chrono::Duration::days;
+        let path = dated_repo("get_tags", now, &[day(3), day(1)]);
+        let repo = Repository::open(&path).expect("open repo");
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let first = head.parent(0).unwrap();
+
+        let tagger = git2::Signature::new(
+            "Releaser",
+            "release@example.com",
+            &git2::Time::new((now - day(2)).timestamp(), 0),
+        )
+        .unwrap();
+        repo.tag(
+            "v1.0.0",
+            first.as_object(),
+            &tagger,
+            "Release 1.0.0\n",
+            false,
+        )
+        .unwrap();
+        repo.tag_lightweight("v1.1.0", head.as_object(), false)
+            .unwrap();
+        let tree = head.tree().unwrap();
+        repo.tag_lightweight("tree-tag", tree.as_object(), false)
+            .unwrap();
+
+        let tags = GitRepo::open(&path).unwrap().get_tags().expect("tags");
+        assert_eq!(tags.len(), 2, "tags on trees are skipped");
+
+        let annotated = &tags[0];
+        assert_eq!(annotated.name, "v1.0.0");
+        assert!(annotated.is_annotated);
+        assert_ne!(annotated.sha, annotated.tagged_sha);
+        assert_eq!(annotated.tagged_sha, first.id().to_string());
+        assert_eq!(annotated.tagger.as_deref(), Some("Releaser"));
+        assert_eq!(annotated.message.as_deref(), Some("Release 1.0.0"));
+        assert_eq!(annotated.timestamp.timestamp(), (now - day(2)).timestamp());
+
+        let lightweight = &tags[1];
+        assert_eq!(lightweight.name, "v1.1.0");
+        assert!(!lightweight.is_annotated);
+        assert_eq!(lightweight.sha, head.id().to_string());
+        assert_eq!(lightweight.tagged_sha, head.id().to_string());
+        assert_eq!(lightweight.tagger, None);
+        assert_eq!(
+            lightweight.timestamp.timestamp(),
+            (now - day(1)).timestamp()
+        );
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    /// Twenty daily commits, the newest an hour old
+    fn daily_ages() -> Vec<chrono::Duration> {
+        (0..20)
+            .rev()
+            .map(|day| chrono::Duration::days(day) + chrono::Duration::hours(1))
+            .collect()
+    }
+
+    #[test]
+    fn test_walk_until_date() {
+        let now = Utc::now();
+        let path = dated_repo("until_date", now, &daily_ages());
+        let repo = GitRepo::open(&path).expect("open repo");
+
+        let week_ago = now - chrono::Duration::weeks(1);
+        let commits = repo
+            .walk_commits(&WalkOptions::until_date(week_ago))
+            .expect("walk");
+        assert_eq!(commits.len(), 7);
+        assert!(commits.iter().all(|c| c.commit.timestamp >= week_ago));
+
+        let commits = repo
+            .walk_commits(&WalkOptions::until_date(week_ago).oldest_first())
+            .expect("walk oldest first");
+        assert_eq!(commits.len(), 7);
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    #[test]
+    fn test_walk_by_author() {
+        let now = Utc::now();
+        let day = chrono::Duration::days;
+        let path = authored_repo(
+            "by_author",
+            now,
+            &[
+                ("Ada Lovelace", day(10)),
+                ("Grace Hopper", day(8)),
+                ("ada lovelace", day(3)),
+                ("Grace Hopper", day(2)),
+                ("ADA LOVELACE", day(1)),
+            ],
+        );
+        let repo = GitRepo::open(&path).expect("open repo");
+
+        let commits = repo
+            .walk_commits(&WalkOptions::default().by_author("Ada"))
+            .expect("walk");
+        let messages: Vec<&str> = commits.iter().map(|c| c.commit.message.as_str()).collect();
+        assert_eq!(messages, vec!["Commit 4", "Commit 2", "Commit 0"]);
+
+        // Combined with a date cut-off, older matching commits are not reached
+        let commits = repo
+            .walk_commits(
+                &WalkOptions::default()
+                    .since_date(now - day(5))
+                    .by_author("lovelace"),
+            )
+            .expect("walk");
+        let messages: Vec<&str> = commits.iter().map(|c| c.commit.message.as_str()).collect();
+        assert_eq!(messages, vec!["Commit 4", "Commit 2"]);
+
+        let commits = repo
+            .walk_commits(&WalkOptions::latest(10).by_author("Turing"))
+            .expect("walk");
+        assert!(commits.is_empty());
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    #[test]
+    fn test_walk_since_date_builder() {
+        let now = Utc::now();
+        let path = dated_repo("since_date", now, &daily_ages());
+        let repo = GitRepo::open(&path).expect("open repo");
+        let week_ago = now - chrono::Duration::weeks(1);
+
+        let commits = repo
+            .walk_commits(&WalkOptions::latest(100).since_date(week_ago))
+            .expect("walk");
+        assert_eq!(commits.len(), 7);
+        assert!(commits.iter().all(|c| c.commit.timestamp >= week_ago));
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    #[test]
+    fn test_walk_since_date_with_max_count() {
+        let now = Utc::now();
+        let path = dated_repo("since_date_max_count", now, &daily_ages());
+        let repo = GitRepo::open(&path).expect("open repo");
+        let week_ago = now - chrono::Duration::weeks(1);
+
+        // The count limit is hit first
+        let commits = repo
+            .walk_commits(&WalkOptions::latest(3).since_date(week_ago))
+            .expect("walk");
+        assert_eq!(commits.len(), 3);
+
+        // The date limit is hit first
+        let commits = repo
+            .walk_commits(&WalkOptions::latest(15).since_date(week_ago))
+            .expect("walk");
+        assert_eq!(commits.len(), 7);
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    #[test]
+    fn test_walk_since_sha() {
+        let now = Utc::now();
+        let path = dated_repo("since_sha", now, &daily_ages());
+        let repo = GitRepo::open(&path).expect("open repo");
+
+        let all = repo.walk_commits(&WalkOptions::default()).expect("walk");
+        let stop = all[5].commit.sha.clone();
+
+        let newer = repo
+            .walk_commits(&WalkOptions::default().since_sha(&stop))
+            .expect("walk");
+        let newer_shas: Vec<&str> = newer.iter().map(|c| c.commit.sha.as_str()).collect();
+        let expected: Vec<&str> = all[..5].iter().map(|c| c.commit.sha.as_str()).collect();
+        assert_eq!(newer_shas, expected);
+
+        // Oldest-first, the commits up to and including the SHA are skipped
+        let newer = repo
+            .walk_commits(&WalkOptions::default().oldest_first().since_sha(&stop))
+            .expect("walk");
+        assert_eq!(newer.len(), 5);
+        assert_eq!(newer[0].commit.sha, all[4].commit.sha);
+
+        // An unknown SHA never stops the walk
+        let unknown = repo
+            .walk_commits(&WalkOptions::default().since_sha("0000000"))
+            .expect("walk");
+        assert_eq!(unknown.len(), all.len());
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    #[test]
+    fn test_walk_until_date_with_max_count() {
+        let now = Utc::now();
+        let path = dated_repo("until_date_max_count", now, &daily_ages());
+        let repo = GitRepo::open(&path).expect("open repo");
+        let week_ago = now - chrono::Duration::weeks(1);
+
+        // The count limit is hit first
+        let commits = repo
+            .walk_commits(&WalkOptions::until_date(week_ago).with_max_count(3))
+            .expect("walk");
+        assert_eq!(commits.len(), 3);
+
+        // The date limit is hit first
+        let commits = repo
+            .walk_commits(&WalkOptions::until_date(week_ago).with_max_count(15))
+            .expect("walk");
+        assert_eq!(commits.len(), 7);
+
+        let commits = repo
+            .walk_commits(&WalkOptions::default().with_max_count(15))
+            .expect("walk");
+        assert_eq!(commits.len(), 15);
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
+    #[test]
+    fn test_commits_reachable_from_tag() {
+        let now = Utc::now();
+        let path = dated_repo("reachable_from_tag", now, &daily_ages()[..4]);
+        let git = Repository::open(&path).expect("open repo");
+        let repo = GitRepo::open(&path).expect("open repo");
+
+        let commits = repo.walk_commits(&WalkOptions::default()).expect("walk");
+        let tagged = git
+            .find_object(git2::Oid::from_str(&commits[2].commit.sha).unwrap(), None)
+            .unwrap();
+        git.tag_lightweight("v1.0.0", &tagged, false).unwrap();
+
+        let reachable = repo
+            .commits_reachable_from_tag("v1.0.0")
+            .expect("reachable");
+        let expected: HashSet<String> = commits[2..].iter().map(|c| c.commit.sha.clone()).collect();
+        assert_eq!(reachable, expected);
+
+        assert!(matches!(
+            repo.commits_reachable_from_tag("v9.9.9"),
+            Err(GitError::InvalidReference { .. })
+        ));
+
+        std::fs::remove_dir_all(&path).ok();
+    }
+
     #[test]
     fn test_walk_options_builder() {
         let options = WalkOptions::latest(10).with_diff().from("main");
@@ -489,6 +1801,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_diff_summary_serialization() {
         let diff = DiffSummary {
             files_changed: 3,
@@ -502,15 +1815,17 @@ mod tests {
             }],
         };
 
-        let json = serde_json::to_string(&diff).expect("Should serialize");
+        let json = diff.to_json().expect("Should serialize");
         assert!(json.contains("files_changed"));
         assert!(json.contains("insertions"));
 
-        let deserialized: DiffSummary = serde_json::from_str(&json).expect("Should deserialize");
+        let deserialized = DiffSummary::from_json(&json).expect("Should deserialize");
         assert_eq!(diff, deserialized);
+        assert!(DiffSummary::from_json("{}").is_err());
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_commit_with_diff_serialization() {
         let cwc = CommitWithDiff {
             commit: Commit {
@@ -522,6 +1837,7 @@ mod tests {
                 parents: vec![],
             },
             diff: Some(DiffSummary::empty()),
+            stats: Some(CommitStats::default()),
         };
 
         let json = serde_json::to_string(&cwc).expect("Should serialize");
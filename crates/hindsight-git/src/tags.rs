@@ -0,0 +1,28 @@
+// Copyright (c) 2026 - present Nicholas D. Crosbie
+// SPDX-License-Identifier: MIT
+
+//! Git tag types
+
+use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A git tag and the commit it points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TagRecord {
+    /// Tag name without the `refs/tags/` prefix
+    pub name: String,
+    /// SHA of the tag object, or of the commit for lightweight tags
+    pub sha: String,
+    /// SHA of the tagged commit
+    pub tagged_sha: String,
+    /// Tagger name, for annotated tags
+    pub tagger: Option<String>,
+    /// Tag message, for annotated tags
+    pub message: Option<String>,
+    /// Whether this is an annotated tag
+    pub is_annotated: bool,
+    /// When the tag was made, or the commit time for lightweight tags
+    pub timestamp: DateTime<Utc>,
+}
@@ -113,6 +113,7 @@ fn test_commit_chain_traversal() {
 }
 
 #[test]
+#[cfg(feature = "serde")]
 fn test_commit_serialization_from_real_data() {
     let repo_path = workspace_root();
     let repo = Repository::open(&repo_path).expect("Failed to open git repository");
@@ -197,6 +198,7 @@ fn test_find_merge_commits() {
 }
 
 #[test]
+#[cfg(feature = "serde")]
 fn test_commit_timestamps_are_iso8601() {
     let repo_path = workspace_root();
     let repo = Repository::open(&repo_path).expect("Failed to open git repository");
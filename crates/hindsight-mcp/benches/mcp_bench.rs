@@ -89,6 +89,67 @@ fn database_benchmarks(c: &mut Criterion) {
     group.finish();
 }
 
+/// Build commit records for the batch insert benchmarks
+fn bench_commits(workspace_id: &str, count: usize) -> Vec<hindsight_mcp::db::CommitRecord> {
+    (0..count)
+        .map(|i| {
+            hindsight_mcp::db::CommitRecord::new(
+                workspace_id.to_string(),
+                format!("{:040x}", i),
+                "Bench Author".to_string(),
+                None,
+                format!("Batch commit {}", i),
+                chrono::Utc::now(),
+            )
+        })
+        .collect()
+}
+
+fn write_profile_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write_profile");
+    group.sample_size(20);
+
+    // Compare batch inserts into a file database with and without the write profile
+    for (name, use_write_profile) in [("default", false), ("optimize_for_write", true)] {
+        group.bench_function(BenchmarkId::new("insert_commits_batch_500", name), |b| {
+            let path = std::env::temp_dir().join(format!(
+                "hindsight-bench-{}-{}.db",
+                name,
+                std::process::id()
+            ));
+            b.iter_batched(
+                || {
+                    let _ = std::fs::remove_file(&path);
+                    let db = Database::open(&path).expect("Failed to open database");
+                    db.initialize().expect("Failed to initialize");
+                    let workspace = db
+                        .get_or_create_workspace("bench", "/bench")
+                        .expect("Failed to create workspace");
+                    let commits = bench_commits(&workspace, 500);
+                    (db, commits)
+                },
+                |(mut db, commits)| {
+                    if use_write_profile {
+                        db.optimize_for_write().expect("write profile failed");
+                    }
+                    db.insert_commits_batch(&commits).expect("insert failed");
+                    if use_write_profile {
+                        db.optimize_for_read().expect("read profile failed");
+                    }
+                },
+                criterion::BatchSize::PerIteration,
+            )
+        });
+        let _ = std::fs::remove_file(std::env::temp_dir().join(format!(
+            "hindsight-bench-{}-{}.db",
+            name,
+            std::process::id()
+        )));
+    }
+
+    group.finish();
+}
+
 fn scaling_benchmarks(c: &mut Criterion) {
     let mut group = c.benchmark_group("scaling");
 
@@ -112,6 +173,7 @@ criterion_group!(
     benches,
     query_benchmarks,
     database_benchmarks,
+    write_profile_benchmarks,
     scaling_benchmarks
 );
 criterion_main!(benches);
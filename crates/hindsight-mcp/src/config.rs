@@ -8,7 +8,7 @@
 
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// Hindsight MCP Server - AI-assisted coding with development history
 #[derive(Parser, Debug, Clone, Default)]
@@ -60,19 +60,44 @@ pub struct Config {
 pub enum Command {
     /// Ingest data from various sources
     ///
-    /// Use this command to ingest test results from nextest output.
-    /// Test output should be piped from stdin.
+    /// Use this command to ingest git commits from the workspace, or test
+    /// results from nextest output piped to stdin.
     ///
-    /// Example:
+    /// Examples:
+    ///   hindsight-mcp ingest --git --since-tag v1.2.3
+    ///   hindsight-mcp ingest --all-workspaces
     ///   NEXTEST_EXPERIMENTAL_LIBTEST_JSON=1 cargo nextest run --message-format libtest-json | hindsight-mcp ingest --tests
     Ingest {
         /// Ingest test results from stdin (nextest JSON format)
         #[arg(long)]
         tests: bool,
 
+        /// Ingest git commits from the workspace repository
+        #[arg(long)]
+        git: bool,
+
+        /// Only ingest commits made since this tag (requires --git)
+        #[arg(long, value_name = "TAG", requires = "git")]
+        since_tag: Option<String>,
+
         /// Git commit SHA to associate with test results
         #[arg(long)]
         commit: Option<String>,
+
+        /// Ingest git commits and Copilot sessions for every workspace in the database
+        #[arg(long, conflicts_with_all = ["tests", "since_tag"])]
+        all_workspaces: bool,
+
+        /// Report ingestion progress as JSON lines on stderr
+        #[arg(long)]
+        progress_json: bool,
+
+        /// Output format for the result summary
+        ///
+        /// With `json`, a single JSON object is written to stdout; logs stay
+        /// on stderr.
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
 
     /// Run tests and ingest results in one command
@@ -139,12 +164,99 @@ pub enum Command {
         #[arg(long)]
         show_output: bool,
 
+        /// Report ingestion progress as JSON lines on stderr
+        ///
+        /// Each event is a JSON object with a `type` field, for example
+        /// `{"type":"completed","source":"tests","stats":{...}}`.
+        #[arg(long)]
+        progress_json: bool,
+
+        /// Output format for the result summary
+        ///
+        /// With `json`, a single JSON object such as
+        /// `{"status":"success","passed":5,"failed":0,...}` is written to
+        /// stdout; logs and warnings stay on stderr.
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
         /// Additional arguments passed to cargo nextest
         ///
         /// Everything after `--` is passed through to nextest.
         #[arg(last = true)]
         nextest_args: Vec<String>,
     },
+
+    /// Refresh query planner statistics for the database
+    ///
+    /// Runs `PRAGMA optimize` and `ANALYZE`, reporting the free page count
+    /// before and after.
+    OptimizeDb,
+
+    /// Check the database for corrupt or dangling data
+    ///
+    /// Validates stored JSON, test result references and full-text indexes.
+    /// Exits with status 1 if any errors are found.
+    Verify,
+
+    /// Rebuild the database to reclaim space left by deleted rows
+    ///
+    /// Runs `VACUUM` and prints the number of bytes freed. With `--into`,
+    /// writes a compacted copy instead and leaves the database untouched.
+    Compact {
+        /// Write the compacted database to this new file
+        #[arg(long)]
+        into: Option<PathBuf>,
+    },
+
+    /// Show when each workspace was last ingested
+    ///
+    /// Lists the last git, Copilot and test ingestion time for every
+    /// workspace and flags those not ingested recently.
+    Status {
+        /// Flag workspaces with no ingestion in this many hours
+        #[arg(long, default_value_t = 24)]
+        stale_hours: u32,
+    },
+
+    /// Generate a development summary report
+    ///
+    /// Summarizes commits, test pass rates, active files, Copilot usage and
+    /// common topics over a recent period.
+    ///
+    /// Example:
+    ///   hindsight-mcp report --days 30 --output report.md
+    Report {
+        /// Number of days to cover (default: 7)
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+        format: ReportFormat,
+    },
+}
+
+/// Output format for the `ingest` and `test` subcommands
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// A single JSON object
+    Json,
+}
+
+/// Output format for the `report` subcommand
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Markdown document
+    Markdown,
+    /// Pretty-printed JSON
+    Json,
 }
 
 impl Config {
@@ -343,6 +455,152 @@ mod tests {
     // Test subcommand CLI parsing tests
     // ========================================================================
 
+    #[test]
+    fn test_parse_optimize_db_command() {
+        let config = Config::try_parse_from(["hindsight-mcp", "optimize-db"]).expect("parse");
+        assert!(matches!(config.command, Some(Command::OptimizeDb)));
+    }
+
+    #[test]
+    fn test_parse_ingest_git_since_tag() {
+        let config =
+            Config::try_parse_from(["hindsight-mcp", "ingest", "--git", "--since-tag", "v1.2.3"])
+                .expect("parse");
+        match config.command {
+            Some(Command::Ingest { git, since_tag, .. }) => {
+                assert!(git);
+                assert_eq!(since_tag.as_deref(), Some("v1.2.3"));
+            }
+            other => panic!("expected ingest command, got {other:?}"),
+        }
+
+        let result = Config::try_parse_from(["hindsight-mcp", "ingest", "--since-tag", "v1.2.3"]);
+        assert!(result.is_err(), "--since-tag should require --git");
+    }
+
+    #[test]
+    fn test_parse_ingest_all_workspaces() {
+        let config =
+            Config::try_parse_from(["hindsight-mcp", "ingest", "--all-workspaces"]).expect("parse");
+        assert!(matches!(
+            config.command,
+            Some(Command::Ingest {
+                all_workspaces: true,
+                ..
+            })
+        ));
+
+        let result =
+            Config::try_parse_from(["hindsight-mcp", "ingest", "--all-workspaces", "--tests"]);
+        assert!(result.is_err(), "--all-workspaces conflicts with --tests");
+    }
+
+    #[test]
+    fn test_parse_output_format() {
+        let config =
+            Config::try_parse_from(["hindsight-mcp", "ingest", "--git", "--format", "json"])
+                .expect("parse");
+        assert!(matches!(
+            config.command,
+            Some(Command::Ingest {
+                format: OutputFormat::Json,
+                ..
+            })
+        ));
+
+        let config =
+            Config::try_parse_from(["hindsight-mcp", "test", "-f", "json"]).expect("parse");
+        assert!(matches!(
+            config.command,
+            Some(Command::Test {
+                format: OutputFormat::Json,
+                ..
+            })
+        ));
+
+        let result = Config::try_parse_from(["hindsight-mcp", "test", "--format", "yaml"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_compact_command() {
+        let config = Config::try_parse_from(["hindsight-mcp", "compact"]).expect("parse");
+        assert!(matches!(
+            config.command,
+            Some(Command::Compact { into: None })
+        ));
+
+        let config = Config::try_parse_from(["hindsight-mcp", "compact", "--into", "copy.db"])
+            .expect("parse");
+        match config.command {
+            Some(Command::Compact { into }) => assert_eq!(into, Some(PathBuf::from("copy.db"))),
+            other => panic!("expected compact command, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_status_command() {
+        let config = Config::try_parse_from(["hindsight-mcp", "status"]).expect("parse");
+        assert!(matches!(
+            config.command,
+            Some(Command::Status { stale_hours: 24 })
+        ));
+
+        let config = Config::try_parse_from(["hindsight-mcp", "status", "--stale-hours", "2"])
+            .expect("parse");
+        assert!(matches!(
+            config.command,
+            Some(Command::Status { stale_hours: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_parse_verify_command() {
+        let config = Config::try_parse_from(["hindsight-mcp", "verify"]).expect("parse");
+        assert!(matches!(config.command, Some(Command::Verify)));
+    }
+
+    #[test]
+    fn test_parse_report_command() {
+        let config = Config::try_parse_from(["hindsight-mcp", "report"]).expect("parse");
+        match config.command {
+            Some(Command::Report {
+                days,
+                output,
+                format,
+            }) => {
+                assert!(days.is_none());
+                assert!(output.is_none());
+                assert_eq!(format, ReportFormat::Markdown);
+            }
+            _ => panic!("Expected Report command"),
+        }
+
+        let config = Config::try_parse_from([
+            "hindsight-mcp",
+            "report",
+            "--days",
+            "30",
+            "--format",
+            "json",
+            "-o",
+            "report.json",
+        ])
+        .expect("parse");
+        match config.command {
+            Some(Command::Report {
+                days,
+                output,
+                format,
+            }) => {
+                assert_eq!(days, Some(30));
+                assert_eq!(output, Some(PathBuf::from("report.json")));
+                assert_eq!(format, ReportFormat::Json);
+            }
+            _ => panic!("Expected Report command"),
+        }
+    }
+
     #[test]
     fn test_parse_test_command_minimal() {
         let config = Config::try_parse_from(["hindsight-mcp", "test"]).expect("parse");
@@ -356,6 +614,8 @@ mod tests {
                 no_commit,
                 commit,
                 show_output,
+                progress_json,
+                format,
                 nextest_args,
             }) => {
                 assert!(package.is_empty());
@@ -366,6 +626,8 @@ mod tests {
                 assert!(!no_commit);
                 assert!(commit.is_none());
                 assert!(!show_output);
+                assert!(!progress_json);
+                assert_eq!(format, OutputFormat::Text);
                 assert!(nextest_args.is_empty());
             }
             _ => panic!("Expected Test command"),
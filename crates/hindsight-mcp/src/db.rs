@@ -15,8 +15,13 @@
 
 use crate::migrations;
 use chrono::{DateTime, Utc};
-use rusqlite::{Connection, Transaction, params};
+use hindsight_git::{CommitStats, FileDiffStats, TagRecord};
+use hindsight_tests::{CiAnnotation, TestList, TestSuiteSummary};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -47,8 +52,59 @@ pub enum DbError {
     /// JSON serialization error
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+
+    /// Pragma name or value rejected
+    #[error("Invalid pragma {pragma}: {message}")]
+    InvalidPragma {
+        /// The pragma that was being read or set
+        pragma: String,
+        /// Why the pragma was rejected
+        message: String,
+    },
+
+    /// Ad-hoc query attempted to modify the database
+    #[error("Read-only violation: {0}")]
+    ReadOnlyViolation(String),
+
+    /// Invalid parameter
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    /// Stored timestamp is not valid RFC 3339
+    #[error("Invalid timestamp {value:?}: {source}")]
+    InvalidTimestamp {
+        /// The value read from the database
+        value: String,
+        /// Why it failed to parse
+        source: chrono::ParseError,
+    },
 }
 
+/// Shortest SHA prefix accepted by [`Database::get_commit_count_by_sha_prefix`]
+pub const MIN_SHA_PREFIX_LEN: usize = 4;
+
+/// Maximum number of rows returned by [`Database::execute_readonly_query`]
+pub const MAX_QUERY_ROWS: usize = 1000;
+
+/// Time limit for [`Database::execute_readonly_query`]
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Freelist ratio above which [`Database::delete_workspace`] vacuums
+pub const AUTO_VACUUM_FREELIST_RATIO: f32 = 0.2;
+
+/// Chunks written by [`Database::stream_commits`] before committing its transaction
+const STREAM_CHUNKS_PER_COMMIT: usize = 10;
+
+/// Pragmas applied by [`Database::optimize_for_write`]
+const WRITE_PROFILE: &[(&str, &str)] = &[("synchronous", "OFF"), ("journal_mode", "DELETE")];
+
+/// Pragmas applied by [`Database::optimize_for_read`]
+const READ_PROFILE: &[(&str, &str)] = &[
+    ("synchronous", "FULL"),
+    ("cache_size", "10000"),
+    ("mmap_size", "268435456"),
+];
+
 // ============================================================================
 // Workspace Types
 // ============================================================================
@@ -66,6 +122,14 @@ pub struct WorkspaceRecord {
     pub created_at: DateTime<Utc>,
     /// Last update timestamp
     pub updated_at: DateTime<Utc>,
+    /// Hash of the repository's root commit, shared by clones of the same repo
+    pub repo_fingerprint: Option<String>,
+    /// When git commits were last ingested
+    pub last_git_ingest_at: Option<DateTime<Utc>>,
+    /// When Copilot sessions were last ingested
+    pub last_copilot_ingest_at: Option<DateTime<Utc>>,
+    /// When test results were last ingested
+    pub last_test_ingest_at: Option<DateTime<Utc>>,
 }
 
 impl WorkspaceRecord {
@@ -79,10 +143,191 @@ impl WorkspaceRecord {
             path,
             created_at: now,
             updated_at: now,
+            repo_fingerprint: None,
+            last_git_ingest_at: None,
+            last_copilot_ingest_at: None,
+            last_test_ingest_at: None,
+        }
+    }
+
+    /// Create a workspace record for a directory, named after its basename
+    ///
+    /// The path is canonicalised, so relative paths and `..` components are
+    /// resolved before the name is taken. Returns `None` if the path cannot
+    /// be canonicalised, e.g. because it does not exist.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let canonical = std::fs::canonicalize(path).ok()?;
+        let name = canonical
+            .file_name()
+            .unwrap_or(canonical.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        Some(Self::new(name, canonical.display().to_string()))
+    }
+
+    /// Most recent ingestion of any source, or `None` if never ingested
+    #[must_use]
+    pub fn last_ingested_at(&self) -> Option<DateTime<Utc>> {
+        [
+            self.last_git_ingest_at,
+            self.last_copilot_ingest_at,
+            self.last_test_ingest_at,
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+
+    /// Creation time relative to now, e.g. "3 days ago"
+    #[must_use]
+    pub fn created_relative(&self) -> String {
+        format_relative(self.created_at, Utc::now())
+    }
+
+    /// Last update time relative to now, e.g. "5 minutes ago"
+    #[must_use]
+    pub fn updated_relative(&self) -> String {
+        format_relative(self.updated_at, Utc::now())
+    }
+
+    /// Last ingestion of `source` relative to now, or "never"
+    #[must_use]
+    pub fn ingested_relative(&self, source: IngestSource) -> String {
+        let ingested_at = match source {
+            IngestSource::Git => self.last_git_ingest_at,
+            IngestSource::Copilot => self.last_copilot_ingest_at,
+            IngestSource::Tests => self.last_test_ingest_at,
+        };
+        ingested_at.map_or_else(
+            || "never".to_string(),
+            |time| format_relative(time, Utc::now()),
+        )
+    }
+}
+
+/// A data source whose last ingestion time is tracked per workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestSource {
+    /// Git commits
+    Git,
+    /// Copilot chat sessions
+    Copilot,
+    /// Test results
+    Tests,
+}
+
+impl IngestSource {
+    /// The `workspaces` column holding this source's last ingestion time
+    fn column(self) -> &'static str {
+        match self {
+            Self::Git => "last_git_ingest_at",
+            Self::Copilot => "last_copilot_ingest_at",
+            Self::Tests => "last_test_ingest_at",
+        }
+    }
+
+    /// The `ingest_checkpoints.source` value for this source
+    fn key(self) -> &'static str {
+        match self {
+            Self::Git => "git",
+            Self::Copilot => "copilot",
+            Self::Tests => "tests",
         }
     }
 }
 
+/// Statistics from merging one workspace into another
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeStats {
+    /// Number of commits re-pointed to the target workspace
+    pub commits_moved: usize,
+    /// Number of source commits dropped because the target already had the SHA
+    pub commits_skipped: usize,
+    /// Number of test runs re-pointed to the target workspace
+    pub runs_moved: usize,
+    /// Number of Copilot sessions re-pointed to the target workspace
+    pub sessions_moved: usize,
+}
+
+/// Statistics from a batch commit insert
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchInsertStats {
+    /// Number of commits written as new rows
+    pub inserted: usize,
+    /// Number of commits ignored because their SHA was already stored
+    pub skipped: usize,
+}
+
+/// Limits on how much history of one source a workspace keeps
+///
+/// A policy with neither limit set keeps everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Delete records older than this many days
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many of the most recent records
+    pub max_records: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// Delete records older than `days` days
+    #[must_use]
+    pub fn with_max_age_days(mut self, days: u32) -> Self {
+        self.max_age_days = Some(days);
+        self
+    }
+
+    /// Keep at most `count` of the most recent records
+    #[must_use]
+    pub fn with_max_records(mut self, count: usize) -> Self {
+        self.max_records = Some(count);
+        self
+    }
+
+    /// `datetime()` modifier for the age limit, e.g. `-30 days`
+    fn age_modifier(&self) -> Option<String> {
+        self.max_age_days.map(|days| format!("-{days} days"))
+    }
+
+    /// Record limit for SQL, where `-1` means no limit
+    fn record_limit(&self) -> i64 {
+        self.max_records
+            .map_or(-1, |count| i64::try_from(count).unwrap_or(i64::MAX))
+    }
+}
+
+/// Statistics from applying a workspace's retention policies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionStats {
+    /// Number of commits deleted
+    pub commits_deleted: usize,
+    /// Number of test results deleted along with their runs
+    pub test_results_deleted: usize,
+    /// Number of Copilot messages deleted
+    pub messages_deleted: usize,
+}
+
+/// Number of records attached to a workspace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceCounts {
+    /// Number of ingested commits
+    pub commit_count: u64,
+    /// Number of ingested Copilot sessions
+    pub session_count: u64,
+}
+
+/// Time span covered by a workspace's commits, test runs, and Copilot sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineBounds {
+    /// Timestamp of the earliest event
+    pub earliest: DateTime<Utc>,
+    /// Timestamp of the latest event
+    pub latest: DateTime<Utc>,
+    /// Number of commits, test runs, and sessions in the workspace
+    pub total_events: u64,
+}
+
 // ============================================================================
 // Commit Types
 // ============================================================================
@@ -108,6 +353,16 @@ pub struct CommitRecord {
     pub parents_json: Option<String>,
     /// Diff summary as JSON
     pub diff_json: Option<String>,
+    /// Whether the diff was dropped because every changed file was excluded
+    pub diff_excluded: bool,
+    /// Language with the most added lines (e.g., "Rust")
+    pub language_hint: Option<String>,
+    /// Whether the commit was created by `git revert`
+    pub is_revert: bool,
+    /// SHA of the reverted commit, if the message records it
+    pub reverted_sha: Option<String>,
+    /// Line statistics as JSON, kept even when the diff is not stored
+    pub stats_json: Option<String>,
     /// Record creation time
     pub created_at: DateTime<Utc>,
 }
@@ -133,6 +388,11 @@ impl CommitRecord {
             timestamp,
             parents_json: None,
             diff_json: None,
+            diff_excluded: false,
+            language_hint: None,
+            is_revert: false,
+            reverted_sha: None,
+            stats_json: None,
             created_at: Utc::now(),
         }
     }
@@ -150,6 +410,70 @@ impl CommitRecord {
         self.diff_json = Some(diff_json);
         self
     }
+
+    /// Store per-extension change stats under `extension_stats` in the diff JSON
+    ///
+    /// Has no effect when the diff was excluded.
+    #[must_use]
+    pub fn with_extension_stats(mut self, stats: HashMap<String, FileDiffStats>) -> Self {
+        if self.diff_excluded {
+            return self;
+        }
+
+        let mut diff = self
+            .diff_json
+            .as_deref()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+            .filter(serde_json::Value::is_object)
+            .unwrap_or_else(|| serde_json::json!({}));
+        diff["extension_stats"] = serde_json::to_value(stats).unwrap_or_default();
+        self.diff_json = Some(diff.to_string());
+        self
+    }
+
+    /// Set the dominant language of the commit's changes
+    #[must_use]
+    pub fn with_language_hint(mut self, language: &str) -> Self {
+        self.language_hint = Some(language.to_string());
+        self
+    }
+
+    /// Mark the commit as a revert of `reverted_sha`
+    #[must_use]
+    pub fn with_revert(mut self, reverted_sha: Option<&str>) -> Self {
+        self.is_revert = true;
+        self.reverted_sha = reverted_sha.map(str::to_string);
+        self
+    }
+
+    /// Store line statistics for the commit
+    #[must_use]
+    pub fn with_stats(mut self, stats: CommitStats) -> Self {
+        self.stats_json = serde_json::to_string(&stats).ok();
+        self
+    }
+
+    /// Parse the stored line statistics
+    #[must_use]
+    pub fn stats(&self) -> Option<CommitStats> {
+        self.stats_json
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+    }
+
+    /// Mark the diff as excluded, clearing any diff JSON
+    #[must_use]
+    pub fn with_diff_excluded(mut self) -> Self {
+        self.diff_json = None;
+        self.diff_excluded = true;
+        self
+    }
+
+    /// Commit time relative to now, e.g. "2 hours ago"
+    #[must_use]
+    pub fn timestamp_relative(&self) -> String {
+        format_relative(self.timestamp, Utc::now())
+    }
 }
 
 // ============================================================================
@@ -177,6 +501,8 @@ pub struct TestRunRecord {
     pub ignored_count: i32,
     /// Build metadata as JSON
     pub metadata_json: Option<String>,
+    /// Environment tags as a JSON object (e.g., `{"env": "ci"}`)
+    pub tags_json: Option<String>,
 }
 
 impl TestRunRecord {
@@ -193,6 +519,7 @@ impl TestRunRecord {
             failed_count: 0,
             ignored_count: 0,
             metadata_json: None,
+            tags_json: None,
         }
     }
 
@@ -212,6 +539,68 @@ impl TestRunRecord {
         self.ignored_count = ignored;
         self
     }
+
+    /// Set the start and finish timestamps
+    #[must_use]
+    pub fn with_timing(mut self, started_at: DateTime<Utc>, finished_at: DateTime<Utc>) -> Self {
+        self.started_at = started_at;
+        self.finished_at = Some(finished_at);
+        self
+    }
+
+    /// Elapsed time between start and finish in seconds, if finished
+    #[must_use]
+    pub fn duration_secs(&self) -> Option<f64> {
+        self.finished_at
+            .map(|finished| (finished - self.started_at).num_milliseconds() as f64 / 1000.0)
+    }
+
+    /// Add an environment tag, merging it into any existing tags
+    #[must_use]
+    pub fn with_environment_tag(mut self, key: &str, value: &str) -> Self {
+        let mut tags: serde_json::Map<String, serde_json::Value> = self
+            .tags_json
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        tags.insert(
+            key.to_string(),
+            serde_json::Value::String(value.to_string()),
+        );
+        self.tags_json = Some(serde_json::Value::Object(tags).to_string());
+        self
+    }
+
+    /// Record per-suite totals as `{"suites": [...]}` in the metadata JSON
+    #[must_use]
+    pub fn with_suites(mut self, suites: &[TestSuiteSummary]) -> Self {
+        self.metadata_json = Some(serde_json::json!({ "suites": suites }).to_string());
+        self
+    }
+}
+
+/// Format of a test's captured output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Plain text
+    Raw,
+    /// A JSON document
+    Json,
+    /// An XML document
+    Xml,
+}
+
+impl OutputFormat {
+    /// Get the format name stored in `output_json`
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Json => "json",
+            Self::Xml => "xml",
+        }
+    }
 }
 
 /// A test result record for insertion
@@ -231,6 +620,10 @@ pub struct TestResultRecord {
     pub duration_ms: Option<i64>,
     /// Output as JSON
     pub output_json: Option<String>,
+    /// Position of the test's `started` event in the run output
+    pub started_seq: Option<i64>,
+    /// Position of the test's result event in the run output
+    pub finished_seq: Option<i64>,
     /// Record creation time
     pub created_at: DateTime<Utc>,
 }
@@ -253,10 +646,20 @@ impl TestResultRecord {
             outcome,
             duration_ms,
             output_json: None,
+            started_seq: None,
+            finished_seq: None,
             created_at: Utc::now(),
         }
     }
 
+    /// Set the test's event window within the run output
+    #[must_use]
+    pub fn with_event_window(mut self, started: usize, finished: usize) -> Self {
+        self.started_seq = Some(started as i64);
+        self.finished_seq = Some(finished as i64);
+        self
+    }
+
     /// Set output JSON
     #[must_use]
     pub fn with_output(mut self, stdout: Option<&str>, stderr: Option<&str>) -> Self {
@@ -269,6 +672,35 @@ impl TestResultRecord {
         }
         self
     }
+
+    /// Set output JSON from stdout in a known format
+    ///
+    /// The content is kept under `stdout` as with [`Self::with_output`] and
+    /// the format is recorded under `format`. JSON content is also stored
+    /// parsed under `json`; content that fails to parse is recorded as
+    /// [`OutputFormat::Raw`].
+    #[must_use]
+    pub fn with_structured_output(mut self, format: OutputFormat, content: &str) -> Self {
+        let mut output = serde_json::json!({
+            "stdout": content,
+            "stderr": null,
+        });
+
+        let format = match format {
+            OutputFormat::Json => match serde_json::from_str::<serde_json::Value>(content) {
+                Ok(parsed) => {
+                    output["json"] = parsed;
+                    OutputFormat::Json
+                }
+                Err(_) => OutputFormat::Raw,
+            },
+            other => other,
+        };
+        output["format"] = format.as_str().into();
+
+        self.output_json = Some(output.to_string());
+        self
+    }
 }
 
 // ============================================================================
@@ -290,6 +722,14 @@ pub struct CopilotSessionRecord {
     pub updated_at: DateTime<Utc>,
     /// Metadata as JSON
     pub metadata_json: Option<String>,
+    /// Estimated LLM token count for the session
+    pub token_estimate: Option<i64>,
+    /// Timestamp of the first message
+    pub first_message_at: Option<DateTime<Utc>>,
+    /// Timestamp of the last message
+    pub last_message_at: Option<DateTime<Utc>>,
+    /// Topic keywords as a JSON array of strings
+    pub topics_json: Option<String>,
 }
 
 impl CopilotSessionRecord {
@@ -304,6 +744,10 @@ impl CopilotSessionRecord {
             created_at: now,
             updated_at: now,
             metadata_json: None,
+            token_estimate: None,
+            first_message_at: None,
+            last_message_at: None,
+            topics_json: None,
         }
     }
 
@@ -319,8 +763,47 @@ impl CopilotSessionRecord {
         }
         self
     }
+
+    /// Set the estimated token count
+    #[must_use]
+    pub fn with_token_estimate(mut self, tokens: u64) -> Self {
+        self.token_estimate = Some(tokens as i64);
+        self
+    }
+
+    /// Set the timestamps of the first and last messages
+    #[must_use]
+    pub fn with_message_span(mut self, first: DateTime<Utc>, last: DateTime<Utc>) -> Self {
+        self.first_message_at = Some(first);
+        self.last_message_at = Some(last);
+        self
+    }
+
+    /// Set the session's topic keywords
+    ///
+    /// An empty list leaves `topics_json` unset.
+    #[must_use]
+    pub fn with_topics(mut self, topics: &[String]) -> Self {
+        if !topics.is_empty() {
+            self.topics_json = Some(serde_json::json!(topics).to_string());
+        }
+        self
+    }
+
+    /// Get the time between the first and last messages, in minutes
+    ///
+    /// Matches the `duration_minutes` column computed by the database.
+    #[must_use]
+    pub fn session_duration_minutes(&self) -> Option<f64> {
+        let first = self.first_message_at?;
+        let last = self.last_message_at?;
+        Some((last - first).num_seconds() as f64 / 60.0)
+    }
 }
 
+/// Suffix appended to Copilot message content truncated during ingestion
+pub const TRUNCATION_MARKER: &str = "\n[content truncated]";
+
 /// A Copilot message record for insertion
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CopilotMessageRecord {
@@ -340,6 +823,10 @@ pub struct CopilotMessageRecord {
     pub timestamp: DateTime<Utc>,
     /// Record creation time
     pub created_at: DateTime<Utc>,
+    /// Chat participant the message was addressed to, without the `@`
+    pub agent: Option<String>,
+    /// Code blocks extracted from the content, as JSON
+    pub code_blocks_json: Option<String>,
 }
 
 impl CopilotMessageRecord {
@@ -360,6 +847,8 @@ impl CopilotMessageRecord {
             variables_json: None,
             timestamp,
             created_at: Utc::now(),
+            agent: None,
+            code_blocks_json: None,
         }
     }
 
@@ -376,6 +865,61 @@ impl CopilotMessageRecord {
         self.variables_json = Some(json);
         self
     }
+
+    /// Set the chat participant, e.g. `@workspace`
+    ///
+    /// A leading `@` is dropped so `@workspace` and `workspace` are stored alike.
+    #[must_use]
+    pub fn with_agent(mut self, agent: &str) -> Self {
+        self.agent = Some(agent.trim_start_matches('@').to_string());
+        self
+    }
+
+    /// Set the code blocks JSON
+    #[must_use]
+    pub fn with_code_blocks_json(mut self, json: String) -> Self {
+        self.code_blocks_json = Some(json);
+        self
+    }
+
+    /// Whether the content was cut short during ingestion
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.content.ends_with(TRUNCATION_MARKER)
+    }
+}
+
+// ============================================================================
+// Note Types
+// ============================================================================
+
+/// A git note record for insertion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRecord {
+    /// Note ID (UUID)
+    pub id: String,
+    /// Commit ID (FK)
+    pub commit_id: String,
+    /// Notes reference (e.g., `refs/notes/ci`)
+    pub namespace: String,
+    /// Note body
+    pub content: String,
+    /// When the note was ingested
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl NoteRecord {
+    /// Create a note record with auto-generated ID
+    #[must_use]
+    pub fn new(commit_id: String, namespace: String, content: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            commit_id,
+            namespace,
+            content,
+            recorded_at: Utc::now(),
+        }
+    }
 }
 
 /// Database connection wrapper
@@ -445,775 +989,3750 @@ impl Database {
         Ok(count)
     }
 
-    // ========================================================================
-    // Workspace Management
-    // ========================================================================
-
-    /// Insert a new workspace
+    /// Run an ad-hoc `SELECT` statement and return its rows as JSON objects
+    ///
+    /// The connection is switched to `query_only` mode for the duration of the
+    /// query, so nothing can be written even if a statement slips past the
+    /// `SELECT` check. At most [`MAX_QUERY_ROWS`] rows are returned, and the
+    /// query is interrupted once it runs longer than [`QUERY_TIMEOUT`].
     ///
     /// # Errors
     ///
-    /// Returns an error if the insert fails (e.g., duplicate path).
-    pub fn insert_workspace(&self, record: &WorkspaceRecord) -> Result<(), DbError> {
-        self.conn.execute(
-            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![
-                record.id,
-                record.name,
-                record.path,
-                record.created_at.to_rfc3339(),
-                record.updated_at.to_rfc3339(),
-            ],
-        )?;
-        Ok(())
+    /// Returns `DbError::ReadOnlyViolation` if the SQL is not a single
+    /// read-only `SELECT` statement, or an error if the query fails or times out.
+    pub fn execute_readonly_query(
+        &self,
+        sql: &str,
+        params: &[&str],
+    ) -> Result<Vec<HashMap<String, serde_json::Value>>, DbError> {
+        let keyword = sql
+            .trim_start()
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or("");
+        if !keyword.eq_ignore_ascii_case("select") && !keyword.eq_ignore_ascii_case("with") {
+            return Err(DbError::ReadOnlyViolation(
+                "only SELECT statements are allowed".to_string(),
+            ));
+        }
+
+        self.conn.pragma_update(None, "query_only", true)?;
+        let started = Instant::now();
+        self.conn
+            .progress_handler(1000, Some(move || started.elapsed() > QUERY_TIMEOUT))?;
+
+        let result = self.run_readonly_query(sql, params);
+
+        self.conn.progress_handler(0, None::<fn() -> bool>)?;
+        self.conn.pragma_update(None, "query_only", false)?;
+        result
     }
 
-    /// Get or create a workspace by path
+    fn run_readonly_query(
+        &self,
+        sql: &str,
+        params: &[&str],
+    ) -> Result<Vec<HashMap<String, serde_json::Value>>, DbError> {
+        let mut stmt = self.conn.prepare(sql)?;
+        if !stmt.readonly() {
+            return Err(DbError::ReadOnlyViolation(
+                "statement would modify the database".to_string(),
+            ));
+        }
+
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+
+        let mut results = Vec::new();
+        while results.len() < MAX_QUERY_ROWS
+            && let Some(row) = rows.next()?
+        {
+            let mut record = HashMap::with_capacity(columns.len());
+            for (idx, name) in columns.iter().enumerate() {
+                record.insert(name.clone(), value_ref_to_json(row.get_ref(idx)?));
+            }
+            results.push(record);
+        }
+
+        Ok(results)
+    }
+
+    // ========================================================================
+    // Runtime Tuning
+    // ========================================================================
+
+    /// Set a pragma, e.g. `pragma_set("cache_size", "-8000")`
     ///
-    /// Returns the workspace ID. If the workspace already exists, returns its ID.
-    /// Otherwise, creates a new workspace and returns the new ID.
+    /// Pragmas that echo their new value (such as `journal_mode`) are checked
+    /// against the requested value, so a setting SQLite silently ignored is
+    /// reported as an error.
     ///
     /// # Errors
     ///
-    /// Returns an error if the operation fails.
-    pub fn get_or_create_workspace(&self, name: &str, path: &str) -> Result<String, DbError> {
-        // Try to find existing workspace
-        let existing: Result<String, _> =
-            self.conn
-                .query_row("SELECT id FROM workspaces WHERE path = ?1", [path], |row| {
-                    row.get(0)
-                });
+    /// Returns `DbError::InvalidPragma` if the name or value contains anything
+    /// other than identifier characters, or if SQLite applied a different value.
+    pub fn pragma_set(&self, pragma: &str, value: &str) -> Result<(), DbError> {
+        validate_pragma_name(pragma)?;
+        if value.is_empty()
+            || !value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(DbError::InvalidPragma {
+                pragma: pragma.to_string(),
+                message: format!("invalid value '{value}'"),
+            });
+        }
 
-        match existing {
-            Ok(id) => Ok(id),
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                // Create new workspace
-                let record = WorkspaceRecord::new(name.to_string(), path.to_string());
-                self.insert_workspace(&record)?;
-                Ok(record.id)
+        let mut stmt = self.conn.prepare(&format!("PRAGMA {pragma} = {value}"))?;
+        let mut rows = stmt.query([])?;
+        if let Some(row) = rows.next()? {
+            let actual = value_ref_to_string(row.get_ref(0)?);
+            if !actual.eq_ignore_ascii_case(value) {
+                return Err(DbError::InvalidPragma {
+                    pragma: pragma.to_string(),
+                    message: format!("requested '{value}' but SQLite reported '{actual}'"),
+                });
             }
-            Err(e) => Err(DbError::Sqlite(e)),
         }
+
+        Ok(())
     }
 
-    /// List all workspaces
+    /// Read a pragma's current value as a string
     ///
     /// # Errors
     ///
-    /// Returns an error if the query fails.
-    pub fn list_workspaces(&self) -> Result<Vec<WorkspaceRecord>, DbError> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, path, created_at, updated_at FROM workspaces ORDER BY name",
-        )?;
-
-        let rows = stmt.query_map([], |row| {
-            Ok(WorkspaceRecord {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                path: row.get(2)?,
-                created_at: parse_timestamp(row.get::<_, String>(3)?),
-                updated_at: parse_timestamp(row.get::<_, String>(4)?),
-            })
-        })?;
-
-        let mut workspaces = Vec::new();
-        for row in rows {
-            workspaces.push(row?);
-        }
-        Ok(workspaces)
+    /// Returns `DbError::InvalidPragma` for a malformed name, or an error if
+    /// the pragma returns no value.
+    pub fn pragma_get(&self, pragma: &str) -> Result<String, DbError> {
+        validate_pragma_name(pragma)?;
+        let value = self
+            .conn
+            .query_row(&format!("PRAGMA {pragma}"), [], |row| {
+                Ok(value_ref_to_string(row.get_ref(0)?))
+            })?;
+        Ok(value)
     }
 
-    // ========================================================================
-    // Commit Insertion
-    // ========================================================================
+    /// Tune the connection for bulk inserts
+    ///
+    /// Turns off `synchronous` and uses a rollback journal. In-memory
+    /// databases keep their memory journal. Call [`Self::optimize_for_read`]
+    /// once the inserts are done to restore durable writes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any pragma cannot be applied, after restoring the
+    /// pragmas already changed.
+    pub fn optimize_for_write(&self) -> Result<(), DbError> {
+        self.apply_pragma_profile(WRITE_PROFILE)
+    }
 
-    /// Insert a single commit
+    /// Tune the connection for queries
+    ///
+    /// Restores `synchronous = FULL`, enlarges the page cache to 10,000 pages
+    /// and enables 256 MiB of memory-mapped I/O. Memory mapping has no effect
+    /// on in-memory databases.
     ///
     /// # Errors
     ///
-    /// Returns an error if the insert fails.
-    pub fn insert_commit(&self, record: &CommitRecord) -> Result<(), DbError> {
-        self.conn.execute(
-            "INSERT INTO commits (id, workspace_id, sha, author, author_email, message, timestamp, parents_json, diff_json, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            params![
-                record.id,
-                record.workspace_id,
-                record.sha,
-                record.author,
-                record.author_email,
-                record.message,
-                record.timestamp.to_rfc3339(),
-                record.parents_json,
-                record.diff_json,
-                record.created_at.to_rfc3339(),
-            ],
-        )?;
+    /// Returns an error if any pragma cannot be applied, after restoring the
+    /// pragmas already changed.
+    pub fn optimize_for_read(&self) -> Result<(), DbError> {
+        self.apply_pragma_profile(READ_PROFILE)
+    }
+
+    /// Apply a set of pragmas, rolling all of them back if one fails
+    fn apply_pragma_profile(&self, profile: &[(&str, &str)]) -> Result<(), DbError> {
+        let in_memory = self.pragma_get("journal_mode")? == "memory";
+        let mut applied: Vec<(&str, String)> = Vec::with_capacity(profile.len());
+
+        for &(pragma, value) in profile {
+            if in_memory && matches!(pragma, "journal_mode" | "mmap_size") {
+                continue;
+            }
+            let previous = self.pragma_get(pragma)?;
+            if let Err(e) = self.pragma_set(pragma, value) {
+                for (pragma, previous) in applied.into_iter().rev() {
+                    let _ = self.pragma_set(pragma, &previous);
+                }
+                return Err(e);
+            }
+            applied.push((pragma, previous));
+        }
+
         Ok(())
     }
 
-    /// Insert multiple commits in a transaction
+    /// Refresh query planner statistics
+    ///
+    /// Runs `PRAGMA optimize` followed by `ANALYZE`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the insert fails. All inserts are rolled back on error.
-    pub fn insert_commits_batch(&mut self, records: &[CommitRecord]) -> Result<usize, DbError> {
-        let tx = self.conn.transaction()?;
-        let count = Self::insert_commits_in_tx(&tx, records)?;
-        tx.commit()?;
-        Ok(count)
+    /// Returns an error if either statement fails.
+    pub fn optimize(&self) -> Result<(), DbError> {
+        self.conn.execute_batch("PRAGMA optimize; ANALYZE;")?;
+        Ok(())
     }
 
-    fn insert_commits_in_tx(
-        tx: &Transaction<'_>,
-        records: &[CommitRecord],
-    ) -> Result<usize, DbError> {
-        let mut count = 0;
-        for record in records {
-            tx.execute(
-                "INSERT OR IGNORE INTO commits (id, workspace_id, sha, author, author_email, message, timestamp, parents_json, diff_json, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-                params![
-                    record.id,
-                    record.workspace_id,
-                    record.sha,
-                    record.author,
-                    record.author_email,
-                    record.message,
-                    record.timestamp.to_rfc3339(),
-                    record.parents_json,
-                    record.diff_json,
-                    record.created_at.to_rfc3339(),
-                ],
-            )?;
-            count += 1;
-        }
-        Ok(count)
+    /// Rebuild the database file to reclaim space left by deleted rows
+    ///
+    /// Runs `VACUUM` and returns the number of bytes freed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vacuum fails, e.g. inside an open transaction.
+    pub fn compact(&mut self) -> Result<u64, DbError> {
+        let before = database_size(&self.conn)?;
+        self.vacuum()?;
+        let after = database_size(&self.conn)?;
+        Ok(before.saturating_sub(after))
     }
 
-    /// Get a commit by SHA within a workspace
+    /// Checkpoint the WAL and rebuild the database file
+    ///
+    /// Runs `PRAGMA wal_checkpoint(TRUNCATE)` so the write-ahead log is
+    /// emptied, then `VACUUM` to drop free pages from the main file.
     ///
     /// # Errors
     ///
-    /// Returns `DbError::NotFound` if the commit doesn't exist.
-    pub fn get_commit_by_sha(
-        &self,
-        workspace_id: &str,
-        sha: &str,
-    ) -> Result<CommitRecord, DbError> {
+    /// Returns an error if the vacuum fails, e.g. inside an open transaction.
+    pub fn vacuum(&mut self) -> Result<(), DbError> {
+        // The checkpoint pragma reports its result as a row
         self.conn
-            .query_row(
-                "SELECT id, workspace_id, sha, author, author_email, message, timestamp, parents_json, diff_json, created_at
-                 FROM commits WHERE workspace_id = ?1 AND sha = ?2",
-                [workspace_id, sha],
-                |row| {
-                    Ok(CommitRecord {
-                        id: row.get(0)?,
-                        workspace_id: row.get(1)?,
-                        sha: row.get(2)?,
-                        author: row.get(3)?,
-                        author_email: row.get(4)?,
-                        message: row.get(5)?,
-                        timestamp: parse_timestamp(row.get::<_, String>(6)?),
-                        parents_json: row.get(7)?,
-                        diff_json: row.get(8)?,
-                        created_at: parse_timestamp(row.get::<_, String>(9)?),
-                    })
-                },
-            )
-            .map_err(|e| match e {
-                rusqlite::Error::QueryReturnedNoRows => DbError::NotFound {
-                    table: "commits".to_string(),
-                    id: sha.to_string(),
-                },
-                _ => DbError::Sqlite(e),
-            })
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        self.conn.execute_batch("VACUUM")?;
+        Ok(())
     }
 
-    // ========================================================================
-    // Test Result Insertion
-    // ========================================================================
-
-    /// Insert a test run
+    /// Fraction of database pages that are on the freelist
+    ///
+    /// Returns `0.0` for an empty database.
     ///
     /// # Errors
     ///
-    /// Returns an error if the insert fails.
-    pub fn insert_test_run(&self, record: &TestRunRecord) -> Result<String, DbError> {
-        self.conn.execute(
-            "INSERT INTO test_runs (id, workspace_id, commit_sha, started_at, finished_at, passed_count, failed_count, ignored_count, metadata_json)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            params![
-                record.id,
-                record.workspace_id,
-                record.commit_sha,
-                record.started_at.to_rfc3339(),
-                record.finished_at.map(|t| t.to_rfc3339()),
-                record.passed_count,
-                record.failed_count,
-                record.ignored_count,
-                record.metadata_json,
-            ],
+    /// Returns an error if the pragmas cannot be read.
+    pub fn freelist_ratio(&self) -> Result<f32, DbError> {
+        let (free, total): (i64, i64) = self.conn.query_row(
+            "SELECT freelist_count, page_count FROM pragma_freelist_count(), pragma_page_count()",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )?;
-        Ok(record.id.clone())
+        if total == 0 {
+            return Ok(0.0);
+        }
+        Ok(free as f32 / total as f32)
     }
 
-    /// Insert multiple test results in a transaction
+    /// Write a compacted copy of the database to `dest`
+    ///
+    /// Uses `VACUUM INTO`, so the open database is left untouched and stays
+    /// usable while the copy is written. Returns how many bytes smaller the
+    /// copy is than the original.
     ///
     /// # Errors
     ///
-    /// Returns an error if the insert fails.
-    pub fn insert_test_results_batch(
-        &mut self,
-        records: &[TestResultRecord],
-    ) -> Result<usize, DbError> {
-        let tx = self.conn.transaction()?;
-        let count = Self::insert_test_results_in_tx(&tx, records)?;
-        tx.commit()?;
-        Ok(count)
+    /// Returns an error if `dest` already exists or cannot be written.
+    pub fn compact_into(&self, dest: &Path) -> Result<u64, DbError> {
+        let before = database_size(&self.conn)?;
+        self.conn
+            .execute("VACUUM INTO ?1", params![dest.to_string_lossy()])?;
+        let after = database_size(&Connection::open(dest)?)?;
+        Ok(before.saturating_sub(after))
     }
 
-    fn insert_test_results_in_tx(
-        tx: &Transaction<'_>,
-        records: &[TestResultRecord],
-    ) -> Result<usize, DbError> {
-        let mut count = 0;
-        for record in records {
-            tx.execute(
-                "INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, duration_ms, output_json, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-                params![
-                    record.id,
-                    record.run_id,
-                    record.suite_name,
-                    record.test_name,
-                    record.outcome,
-                    record.duration_ms,
-                    record.output_json,
-                    record.created_at.to_rfc3339(),
-                ],
-            )?;
-            count += 1;
-        }
-        Ok(count)
-    }
+    // ========================================================================
+    // Workspace Management
+    // ========================================================================
 
-    /// Link a test run to a commit SHA
+    /// Insert a new workspace
     ///
     /// # Errors
     ///
-    /// Returns an error if the update fails.
-    pub fn link_test_run_to_commit(&self, run_id: &str, commit_sha: &str) -> Result<(), DbError> {
+    /// Returns an error if the insert fails (e.g., duplicate path).
+    pub fn insert_workspace(&self, record: &WorkspaceRecord) -> Result<(), DbError> {
         self.conn.execute(
-            "UPDATE test_runs SET commit_sha = ?1 WHERE id = ?2",
-            [commit_sha, run_id],
+            &format!(
+                "INSERT INTO workspaces ({WORKSPACE_COLUMNS})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            ),
+            params![
+                record.id,
+                record.name,
+                record.path,
+                record.created_at.to_rfc3339(),
+                record.updated_at.to_rfc3339(),
+                record.repo_fingerprint,
+                record.last_git_ingest_at.map(|t| t.to_rfc3339()),
+                record.last_copilot_ingest_at.map(|t| t.to_rfc3339()),
+                record.last_test_ingest_at.map(|t| t.to_rfc3339()),
+            ],
         )?;
         Ok(())
     }
 
-    // ========================================================================
-    // Copilot Insertion
-    // ========================================================================
-
-    /// Insert a Copilot session
+    /// Get or create a workspace by path
     ///
-    /// Returns the session ID. Uses INSERT OR IGNORE to handle duplicates.
+    /// Returns the workspace ID. If the workspace already exists, returns its ID.
+    /// Otherwise, creates a new workspace and returns the new ID.
     ///
     /// # Errors
     ///
-    /// Returns an error if the insert fails.
-    pub fn insert_copilot_session(&self, record: &CopilotSessionRecord) -> Result<String, DbError> {
-        // Check if session already exists
-        let existing: Result<String, _> = self.conn.query_row(
-            "SELECT id FROM copilot_sessions WHERE workspace_id = ?1 AND vscode_session_id = ?2",
-            [&record.workspace_id, &record.vscode_session_id],
-            |row| row.get(0),
-        );
+    /// Returns an error if the operation fails.
+    pub fn get_or_create_workspace(&self, name: &str, path: &str) -> Result<String, DbError> {
+        // Try to find existing workspace
+        let existing: Result<String, _> =
+            self.conn
+                .query_row("SELECT id FROM workspaces WHERE path = ?1", [path], |row| {
+                    row.get(0)
+                });
 
         match existing {
             Ok(id) => Ok(id),
             Err(rusqlite::Error::QueryReturnedNoRows) => {
-                self.conn.execute(
-                    "INSERT INTO copilot_sessions (id, workspace_id, vscode_session_id, created_at, updated_at, metadata_json)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                    params![
-                        record.id,
-                        record.workspace_id,
-                        record.vscode_session_id,
-                        record.created_at.to_rfc3339(),
-                        record.updated_at.to_rfc3339(),
-                        record.metadata_json,
-                    ],
-                )?;
-                Ok(record.id.clone())
+                // Create new workspace
+                let record = WorkspaceRecord::new(name.to_string(), path.to_string());
+                self.insert_workspace(&record)?;
+                Ok(record.id)
             }
             Err(e) => Err(DbError::Sqlite(e)),
         }
     }
 
-    /// Insert multiple Copilot messages in a transaction
+    /// Record a custom event, such as a deploy or incident, on a workspace
+    ///
+    /// The event appears in the timeline with `event_type` as its type and
+    /// `title` as its summary. Returns the new event ID.
     ///
     /// # Errors
     ///
-    /// Returns an error if the insert fails.
-    pub fn insert_copilot_messages_batch(
-        &mut self,
-        records: &[CopilotMessageRecord],
-    ) -> Result<usize, DbError> {
-        let tx = self.conn.transaction()?;
-        let count = Self::insert_copilot_messages_in_tx(&tx, records)?;
-        tx.commit()?;
-        Ok(count)
+    /// Returns `DbError::InvalidParameter` if `event_type` or `title` is
+    /// empty, or an error if the insert fails.
+    pub fn insert_workspace_event(
+        &self,
+        workspace_id: &str,
+        event_type: &str,
+        title: &str,
+        description: &str,
+        event_at: DateTime<Utc>,
+    ) -> Result<String, DbError> {
+        if event_type.trim().is_empty() {
+            return Err(DbError::InvalidParameter(
+                "event_type must not be empty".to_string(),
+            ));
+        }
+        if title.trim().is_empty() {
+            return Err(DbError::InvalidParameter(
+                "title must not be empty".to_string(),
+            ));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        self.conn.execute(
+            "INSERT INTO workspace_events (id, workspace_id, event_type, title, description, event_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                workspace_id,
+                event_type.trim(),
+                title,
+                description,
+                event_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(id)
     }
 
-    fn insert_copilot_messages_in_tx(
-        tx: &Transaction<'_>,
-        records: &[CopilotMessageRecord],
-    ) -> Result<usize, DbError> {
-        let mut count = 0;
-        for record in records {
-            tx.execute(
-                "INSERT INTO copilot_messages (id, session_id, request_id, role, content, variables_json, timestamp, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-                params![
-                    record.id,
-                    record.session_id,
-                    record.request_id,
-                    record.role,
-                    record.content,
-                    record.variables_json,
-                    record.timestamp.to_rfc3339(),
-                    record.created_at.to_rfc3339(),
-                ],
-            )?;
-            count += 1;
+    /// Look up the ID of the workspace at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_workspace_id(&self, path: &str) -> Result<Option<String>, DbError> {
+        match self
+            .conn
+            .query_row("SELECT id FROM workspaces WHERE path = ?1", [path], |row| {
+                row.get(0)
+            }) {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::Sqlite(e)),
         }
-        Ok(count)
     }
 
-    /// Get message count for a session
+    /// List all workspaces
     ///
     /// # Errors
     ///
     /// Returns an error if the query fails.
-    pub fn get_session_message_count(&self, session_id: &str) -> Result<i64, DbError> {
-        let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM copilot_messages WHERE session_id = ?1",
-            [session_id],
-            |row| row.get(0),
-        )?;
-        Ok(count)
-    }
-}
+    pub fn list_workspaces(&self) -> Result<Vec<WorkspaceRecord>, DbError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {WORKSPACE_COLUMNS} FROM workspaces ORDER BY name"
+        ))?;
 
-/// Parse an ISO 8601 timestamp string
-fn parse_timestamp(s: String) -> DateTime<Utc> {
-    DateTime::parse_from_rfc3339(&s)
-        .map(|dt| dt.with_timezone(&Utc))
-        .unwrap_or_else(|_| Utc::now())
-}
+        let rows = stmt.query_map([], workspace_from_row)?;
 
-#[cfg(test)]
-mod tests {
+        let mut workspaces = Vec::new();
+        for row in rows {
+            workspaces.push(row?);
+        }
+        Ok(workspaces)
+    }
+
+    /// Get the stored repository fingerprint of a workspace
+    ///
+    /// Returns `None` if the workspace does not exist or has no fingerprint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_workspace_fingerprint(&self, workspace_id: &str) -> Result<Option<String>, DbError> {
+        let fingerprint = self
+            .conn
+            .query_row(
+                "SELECT repo_fingerprint FROM workspaces WHERE id = ?1",
+                [workspace_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(fingerprint.flatten())
+    }
+
+    /// Record the repository fingerprint for a workspace
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub fn set_workspace_fingerprint(
+        &self,
+        workspace_id: &str,
+        fingerprint: &str,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE workspaces SET repo_fingerprint = ?2 WHERE id = ?1",
+            params![workspace_id, fingerprint],
+        )?;
+        Ok(())
+    }
+
+    /// Record that `source` was just ingested for a workspace
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub fn record_ingest(&self, workspace_id: &str, source: IngestSource) -> Result<(), DbError> {
+        self.conn.execute(
+            &format!(
+                "UPDATE workspaces SET {} = ?2 WHERE id = ?1",
+                source.column()
+            ),
+            params![workspace_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Get the checkpoint left by an interrupted ingestion, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_checkpoint(
+        &self,
+        workspace_id: &str,
+        source: IngestSource,
+    ) -> Result<Option<String>, DbError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT last_sha FROM ingest_checkpoints WHERE workspace_id = ?1 AND source = ?2",
+                params![workspace_id, source.key()],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Record an ingestion checkpoint using an open transaction
+    ///
+    /// Replaces any earlier checkpoint for the workspace and source, so it is
+    /// committed together with the rows it describes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the upsert fails.
+    pub fn set_checkpoint_in_tx(
+        tx: &Transaction<'_>,
+        workspace_id: &str,
+        source: IngestSource,
+        last_sha: &str,
+    ) -> Result<(), DbError> {
+        tx.execute(
+            "INSERT INTO ingest_checkpoints (workspace_id, source, last_sha, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(workspace_id, source)
+             DO UPDATE SET last_sha = excluded.last_sha, updated_at = excluded.updated_at",
+            params![
+                workspace_id,
+                source.key(),
+                last_sha,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Remove the ingestion checkpoint for a workspace and source
+    ///
+    /// Called once an ingestion completes. Resetting it by hand makes the next
+    /// incremental ingestion ignore where an interrupted one stopped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delete fails.
+    pub fn reset_checkpoint(
+        &self,
+        workspace_id: &str,
+        source: IngestSource,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM ingest_checkpoints WHERE workspace_id = ?1 AND source = ?2",
+            params![workspace_id, source.key()],
+        )?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // Retention
+    // ========================================================================
+
+    /// Set the retention policy for one source of a workspace
+    ///
+    /// Replaces any earlier policy for the workspace and source. The policy
+    /// takes effect the next time [`Self::apply_retention_policy`] runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the upsert fails.
+    pub fn set_retention_policy(
+        &self,
+        workspace_id: &str,
+        source: IngestSource,
+        policy: RetentionPolicy,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO retention_policies (workspace_id, source, max_age_days, max_records, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(workspace_id, source)
+             DO UPDATE SET max_age_days = excluded.max_age_days,
+                           max_records = excluded.max_records,
+                           updated_at = excluded.updated_at",
+            params![
+                workspace_id,
+                source.key(),
+                policy.max_age_days,
+                policy.max_records.map(|n| n as i64),
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the retention policy for one source of a workspace
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_retention_policy(
+        &self,
+        workspace_id: &str,
+        source: IngestSource,
+    ) -> Result<Option<RetentionPolicy>, DbError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT max_age_days, max_records FROM retention_policies
+                 WHERE workspace_id = ?1 AND source = ?2",
+                params![workspace_id, source.key()],
+                |row| {
+                    Ok(RetentionPolicy {
+                        max_age_days: row.get(0)?,
+                        max_records: row.get::<_, Option<i64>>(1)?.map(|n| n.max(0) as usize),
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Delete the history a workspace's retention policies no longer keep
+    ///
+    /// For each source with a policy, deletes records older than
+    /// `max_age_days` and then everything beyond the `max_records` most
+    /// recent. Records are commits for git, test runs for tests, and messages
+    /// for Copilot. Notes, test results and CI annotations go with their
+    /// commit or run. All deletes happen in one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any delete fails; nothing is deleted in that case.
+    pub fn apply_retention_policy(
+        &mut self,
+        workspace_id: &str,
+    ) -> Result<RetentionStats, DbError> {
+        let git = self.get_retention_policy(workspace_id, IngestSource::Git)?;
+        let tests = self.get_retention_policy(workspace_id, IngestSource::Tests)?;
+        let copilot = self.get_retention_policy(workspace_id, IngestSource::Copilot)?;
+
+        let mut stats = RetentionStats::default();
+        let tx = self.conn.transaction()?;
+
+        if let Some(policy) = git {
+            let expired = "SELECT id FROM commits
+                WHERE workspace_id = ?1
+                  AND (datetime(timestamp) < datetime('now', ?2)
+                       OR id NOT IN (SELECT id FROM commits WHERE workspace_id = ?1
+                                     ORDER BY timestamp DESC, rowid DESC LIMIT ?3))";
+            let args = params![workspace_id, policy.age_modifier(), policy.record_limit()];
+            tx.execute(
+                &format!("DELETE FROM notes WHERE commit_id IN ({expired})"),
+                args,
+            )?;
+            tx.execute(
+                &format!(
+                    "DELETE FROM tags WHERE workspace_id = ?1
+                       AND tagged_sha IN (SELECT sha FROM commits WHERE id IN ({expired}))"
+                ),
+                args,
+            )?;
+            stats.commits_deleted = tx.execute(
+                &format!("DELETE FROM commits WHERE id IN ({expired})"),
+                args,
+            )?;
+        }
+
+        if let Some(policy) = tests {
+            let expired = "SELECT id FROM test_runs
+                WHERE workspace_id = ?1
+                  AND (datetime(started_at) < datetime('now', ?2)
+                       OR id NOT IN (SELECT id FROM test_runs WHERE workspace_id = ?1
+                                     ORDER BY started_at DESC, rowid DESC LIMIT ?3))";
+            let args = params![workspace_id, policy.age_modifier(), policy.record_limit()];
+            tx.execute(
+                &format!("DELETE FROM ci_annotations WHERE run_id IN ({expired})"),
+                args,
+            )?;
+            stats.test_results_deleted = tx.execute(
+                &format!("DELETE FROM test_results WHERE run_id IN ({expired})"),
+                args,
+            )?;
+            tx.execute(
+                &format!("DELETE FROM test_runs WHERE id IN ({expired})"),
+                args,
+            )?;
+        }
+
+        if let Some(policy) = copilot {
+            stats.messages_deleted = tx.execute(
+                "DELETE FROM copilot_messages WHERE id IN (
+                    SELECT m.id FROM copilot_messages m
+                    JOIN copilot_sessions s ON s.id = m.session_id
+                    WHERE s.workspace_id = ?1
+                      AND (datetime(m.timestamp) < datetime('now', ?2)
+                           OR m.id NOT IN (
+                               SELECT m2.id FROM copilot_messages m2
+                               JOIN copilot_sessions s2 ON s2.id = m2.session_id
+                               WHERE s2.workspace_id = ?1
+                               ORDER BY m2.timestamp DESC, m2.rowid DESC LIMIT ?3)))",
+                params![workspace_id, policy.age_modifier(), policy.record_limit()],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(stats)
+    }
+
+    /// List workspaces with no ingestion of any source in the last `hours_threshold` hours
+    ///
+    /// Workspaces that have never been ingested are included.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_stale_workspaces(
+        &self,
+        hours_threshold: u32,
+    ) -> Result<Vec<WorkspaceRecord>, DbError> {
+        let cutoff =
+            (Utc::now() - chrono::Duration::hours(i64::from(hours_threshold))).to_rfc3339();
+
+        // Multi-argument MAX() is NULL if any argument is, so map NULL to ''
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {WORKSPACE_COLUMNS} FROM workspaces
+             WHERE MAX(COALESCE(last_git_ingest_at, ''),
+                       COALESCE(last_copilot_ingest_at, ''),
+                       COALESCE(last_test_ingest_at, '')) < ?1
+             ORDER BY name"
+        ))?;
+
+        let rows = stmt.query_map([cutoff], workspace_from_row)?;
+
+        let mut workspaces = Vec::new();
+        for row in rows {
+            workspaces.push(row?);
+        }
+        Ok(workspaces)
+    }
+
+    /// List workspaces whose repository has the given fingerprint
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_workspaces_by_fingerprint(
+        &self,
+        fingerprint: &str,
+    ) -> Result<Vec<WorkspaceRecord>, DbError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {WORKSPACE_COLUMNS} FROM workspaces
+             WHERE repo_fingerprint = ?1 ORDER BY created_at"
+        ))?;
+
+        let rows = stmt.query_map([fingerprint], workspace_from_row)?;
+
+        let mut workspaces = Vec::new();
+        for row in rows {
+            workspaces.push(row?);
+        }
+        Ok(workspaces)
+    }
+
+    /// Count commits and Copilot sessions for every workspace, keyed by workspace ID
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn workspace_counts(&self) -> Result<HashMap<String, WorkspaceCounts>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT w.id,
+                    (SELECT COUNT(*) FROM commits c WHERE c.workspace_id = w.id),
+                    (SELECT COUNT(*) FROM copilot_sessions s WHERE s.workspace_id = w.id)
+             FROM workspaces w",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                WorkspaceCounts {
+                    commit_count: row.get::<_, i64>(1)? as u64,
+                    session_count: row.get::<_, i64>(2)? as u64,
+                },
+            ))
+        })?;
+
+        let mut counts = HashMap::new();
+        for row in rows {
+            let (id, workspace_counts) = row?;
+            counts.insert(id, workspace_counts);
+        }
+        Ok(counts)
+    }
+
+    /// Merge one workspace into another
+    ///
+    /// Re-points all commits, test runs, Copilot sessions, and timeline events
+    /// from `source_id` to `target_id`, then deletes the source workspace. Where both workspaces
+    /// contain the same commit SHA (or Copilot session), the target's copy is kept.
+    /// Merging a workspace into itself is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DbError::NotFound` if either workspace doesn't exist. All changes
+    /// are rolled back on error.
+    pub fn merge_workspace(
+        &mut self,
+        source_id: &str,
+        target_id: &str,
+    ) -> Result<MergeStats, DbError> {
+        for id in [source_id, target_id] {
+            let exists: i64 = self.conn.query_row(
+                "SELECT COUNT(*) FROM workspaces WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )?;
+            if exists == 0 {
+                return Err(DbError::NotFound {
+                    table: "workspaces".to_string(),
+                    id: id.to_string(),
+                });
+            }
+        }
+
+        if source_id == target_id {
+            return Ok(MergeStats::default());
+        }
+
+        let tx = self.conn.transaction()?;
+
+        // Tags are read again from the repository on the next git ingest
+        tx.execute("DELETE FROM tags WHERE workspace_id = ?1", [source_id])?;
+
+        // UPDATE OR IGNORE leaves rows that would violate UNIQUE(workspace_id, sha)
+        let commits_moved = tx.execute(
+            "UPDATE OR IGNORE commits SET workspace_id = ?2 WHERE workspace_id = ?1",
+            [source_id, target_id],
+        )?;
+        tx.execute(
+            "DELETE FROM notes WHERE commit_id IN
+                (SELECT id FROM commits WHERE workspace_id = ?1)",
+            [source_id],
+        )?;
+        let commits_skipped =
+            tx.execute("DELETE FROM commits WHERE workspace_id = ?1", [source_id])?;
+
+        let runs_moved = tx.execute(
+            "UPDATE test_runs SET workspace_id = ?2 WHERE workspace_id = ?1",
+            [source_id, target_id],
+        )?;
+        // Tests already listed for the target keep the target's timestamps
+        tx.execute(
+            "UPDATE OR IGNORE test_inventory SET workspace_id = ?2 WHERE workspace_id = ?1",
+            [source_id, target_id],
+        )?;
+        tx.execute(
+            "DELETE FROM test_inventory WHERE workspace_id = ?1",
+            [source_id],
+        )?;
+
+        let sessions_moved = tx.execute(
+            "UPDATE OR IGNORE copilot_sessions SET workspace_id = ?2 WHERE workspace_id = ?1",
+            [source_id, target_id],
+        )?;
+        // Sessions left behind are duplicates of target sessions
+        tx.execute(
+            "DELETE FROM copilot_messages WHERE session_id IN
+                (SELECT id FROM copilot_sessions WHERE workspace_id = ?1)",
+            [source_id],
+        )?;
+        tx.execute(
+            "DELETE FROM copilot_sessions WHERE workspace_id = ?1",
+            [source_id],
+        )?;
+
+        // Events cascade with the source workspace, so move them first
+        tx.execute(
+            "UPDATE workspace_events SET workspace_id = ?2 WHERE workspace_id = ?1",
+            [source_id, target_id],
+        )?;
+
+        tx.execute(
+            "DELETE FROM ingest_checkpoints WHERE workspace_id = ?1",
+            [source_id],
+        )?;
+        tx.execute(
+            "DELETE FROM retention_policies WHERE workspace_id = ?1",
+            [source_id],
+        )?;
+        tx.execute("DELETE FROM workspaces WHERE id = ?1", [source_id])?;
+        tx.commit()?;
+
+        Ok(MergeStats {
+            commits_moved,
+            commits_skipped,
+            runs_moved,
+            sessions_moved,
+        })
+    }
+
+    /// Rename a workspace or point it at a new path
+    ///
+    /// Also refreshes `updated_at`. Use this when a project has moved, so
+    /// that later ingests find the existing workspace instead of creating a
+    /// duplicate for the new path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DbError::NotFound` if the workspace does not exist, or an
+    /// error if the update fails (e.g. another workspace already has `path`).
+    pub fn update_workspace(&self, id: &str, name: &str, path: &str) -> Result<(), DbError> {
+        let updated = self.conn.execute(
+            "UPDATE workspaces SET name = ?2, path = ?3, updated_at = ?4 WHERE id = ?1",
+            params![id, name, path, Utc::now().to_rfc3339()],
+        )?;
+        if updated == 0 {
+            return Err(DbError::NotFound {
+                table: "workspaces".to_string(),
+                id: id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Delete a workspace and everything recorded for it
+    ///
+    /// Removes the workspace's commits with their notes and tags, test runs with their
+    /// results and CI annotations, the test inventory, Copilot sessions with
+    /// their messages, and timeline events. Children are deleted explicitly, in a single
+    /// transaction, so this works whether or not foreign keys are enforced.
+    ///
+    /// If more than [`AUTO_VACUUM_FREELIST_RATIO`] of the file is left free
+    /// afterwards, the database is vacuumed to give the space back.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DbError::NotFound` if the workspace does not exist, or an
+    /// error if any delete fails.
+    pub fn delete_workspace(&mut self, id: &str) -> Result<(), DbError> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM notes WHERE commit_id IN
+                (SELECT id FROM commits WHERE workspace_id = ?1)",
+            [id],
+        )?;
+        tx.execute("DELETE FROM tags WHERE workspace_id = ?1", [id])?;
+        tx.execute("DELETE FROM commits WHERE workspace_id = ?1", [id])?;
+
+        tx.execute(
+            "DELETE FROM ci_annotations WHERE run_id IN
+                (SELECT id FROM test_runs WHERE workspace_id = ?1)",
+            [id],
+        )?;
+        tx.execute(
+            "DELETE FROM test_results WHERE run_id IN
+                (SELECT id FROM test_runs WHERE workspace_id = ?1)",
+            [id],
+        )?;
+        tx.execute("DELETE FROM test_runs WHERE workspace_id = ?1", [id])?;
+        tx.execute("DELETE FROM test_inventory WHERE workspace_id = ?1", [id])?;
+
+        tx.execute(
+            "DELETE FROM copilot_messages WHERE session_id IN
+                (SELECT id FROM copilot_sessions WHERE workspace_id = ?1)",
+            [id],
+        )?;
+        tx.execute("DELETE FROM copilot_sessions WHERE workspace_id = ?1", [id])?;
+
+        tx.execute("DELETE FROM workspace_events WHERE workspace_id = ?1", [id])?;
+        tx.execute(
+            "DELETE FROM ingest_checkpoints WHERE workspace_id = ?1",
+            [id],
+        )?;
+        tx.execute(
+            "DELETE FROM retention_policies WHERE workspace_id = ?1",
+            [id],
+        )?;
+
+        // Dropping the transaction rolls back the child deletes
+        if tx.execute("DELETE FROM workspaces WHERE id = ?1", [id])? == 0 {
+            return Err(DbError::NotFound {
+                table: "workspaces".to_string(),
+                id: id.to_string(),
+            });
+        }
+        tx.commit()?;
+
+        if self.freelist_ratio()? > AUTO_VACUUM_FREELIST_RATIO {
+            self.vacuum()?;
+        }
+        Ok(())
+    }
+
+    /// Get the earliest and latest event timestamps in a workspace
+    ///
+    /// Considers commit timestamps, test run start times, and Copilot session
+    /// creation and update times. Returns `None` if the workspace has no events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_workspace_timeline_bounds(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Option<TimelineBounds>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT MIN(timestamp), MAX(timestamp), COUNT(*) FROM commits WHERE workspace_id = ?1
+             UNION ALL
+             SELECT MIN(started_at), MAX(started_at), COUNT(*) FROM test_runs WHERE workspace_id = ?1
+             UNION ALL
+             SELECT MIN(created_at), MAX(updated_at), COUNT(*) FROM copilot_sessions WHERE workspace_id = ?1",
+        )?;
+        let rows = stmt.query_map([workspace_id], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut bounds: Option<TimelineBounds> = None;
+        for row in rows {
+            let (Some(min), Some(max), count) = row? else {
+                continue;
+            };
+            let (earliest, latest) = (try_parse_timestamp(&min)?, try_parse_timestamp(&max)?);
+            bounds = Some(match bounds {
+                Some(b) => TimelineBounds {
+                    earliest: b.earliest.min(earliest),
+                    latest: b.latest.max(latest),
+                    total_events: b.total_events + count as u64,
+                },
+                None => TimelineBounds {
+                    earliest,
+                    latest,
+                    total_events: count as u64,
+                },
+            });
+        }
+        Ok(bounds)
+    }
+
+    /// Check whether a workspace has no commits, test runs, or Copilot sessions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn is_workspace_empty(&self, workspace_id: &str) -> Result<bool, DbError> {
+        let has_events: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM commits WHERE workspace_id = ?1)
+                 OR EXISTS(SELECT 1 FROM test_runs WHERE workspace_id = ?1)
+                 OR EXISTS(SELECT 1 FROM copilot_sessions WHERE workspace_id = ?1)",
+            [workspace_id],
+            |row| row.get(0),
+        )?;
+        Ok(!has_events)
+    }
+
+    // ========================================================================
+    // Commit Insertion
+    // ========================================================================
+
+    /// Insert a single commit
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails.
+    pub fn insert_commit(&self, record: &CommitRecord) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO commits (id, workspace_id, sha, author, author_email, message, timestamp, parents_json, diff_json, diff_excluded, language_hint, created_at, is_revert, reverted_sha, stats_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                record.id,
+                record.workspace_id,
+                record.sha,
+                record.author,
+                record.author_email,
+                record.message,
+                record.timestamp.to_rfc3339(),
+                record.parents_json,
+                record.diff_json,
+                record.diff_excluded,
+                record.language_hint,
+                record.created_at.to_rfc3339(),
+                record.is_revert,
+                record.reverted_sha,
+                record.stats_json,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Insert multiple commits in a transaction
+    ///
+    /// Commits whose SHA is already stored for the workspace are counted as
+    /// skipped rather than inserted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails. All inserts are rolled back on error.
+    pub fn insert_commits_batch(
+        &mut self,
+        records: &[CommitRecord],
+    ) -> Result<BatchInsertStats, DbError> {
+        let tx = self.conn.transaction()?;
+        let inserted = Self::insert_commits_in_tx(&tx, records)?;
+        tx.commit()?;
+        Ok(BatchInsertStats {
+            inserted,
+            skipped: records.len() - inserted,
+        })
+    }
+
+    /// Feed commits to `f` in chunks inside a long-running transaction
+    ///
+    /// Only one chunk of `chunk_size` records is held at a time, so `commits`
+    /// can be a lazy iterator over a large history. Each record's
+    /// `workspace_id` is set to `workspace_id`. The transaction is committed
+    /// every few chunks and once more at the end. Returns the sum of the counts
+    /// returned by `f`; pass [`Database::insert_commits_in_tx`] to insert.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from `f` or the transaction. Chunks from
+    /// earlier commits stay in the database; the failing batch is rolled back.
+    pub fn stream_commits<I, F>(
+        &mut self,
+        workspace_id: &str,
+        commits: I,
+        chunk_size: usize,
+        mut f: F,
+    ) -> Result<usize, DbError>
+    where
+        I: IntoIterator<Item = CommitRecord>,
+        F: FnMut(&Transaction<'_>, &[CommitRecord]) -> Result<usize, DbError>,
+    {
+        let chunk_size = chunk_size.max(1);
+        let mut chunk = Vec::with_capacity(chunk_size);
+        let mut chunks_in_tx = 0;
+        let mut total = 0;
+
+        let mut tx = self.conn.transaction()?;
+        for mut record in commits {
+            record.workspace_id = workspace_id.to_string();
+            chunk.push(record);
+            if chunk.len() < chunk_size {
+                continue;
+            }
+
+            total += f(&tx, &chunk)?;
+            chunk.clear();
+            chunks_in_tx += 1;
+            if chunks_in_tx == STREAM_CHUNKS_PER_COMMIT {
+                tx.commit()?;
+                tx = self.conn.transaction()?;
+                chunks_in_tx = 0;
+            }
+        }
+        if !chunk.is_empty() {
+            total += f(&tx, &chunk)?;
+        }
+        tx.commit()?;
+
+        Ok(total)
+    }
+
+    /// Insert commits using an open transaction, skipping duplicate SHAs
+    ///
+    /// Returns the number of rows actually inserted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an insert fails.
+    pub fn insert_commits_in_tx(
+        tx: &Transaction<'_>,
+        records: &[CommitRecord],
+    ) -> Result<usize, DbError> {
+        let mut count = 0;
+        for record in records {
+            let rows_affected = tx.execute(
+                "INSERT OR IGNORE INTO commits (id, workspace_id, sha, author, author_email, message, timestamp, parents_json, diff_json, diff_excluded, language_hint, created_at, is_revert, reverted_sha, stats_json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                params![
+                    record.id,
+                    record.workspace_id,
+                    record.sha,
+                    record.author,
+                    record.author_email,
+                    record.message,
+                    record.timestamp.to_rfc3339(),
+                    record.parents_json,
+                    record.diff_json,
+                    record.diff_excluded,
+                    record.language_hint,
+                    record.created_at.to_rfc3339(),
+                    record.is_revert,
+                    record.reverted_sha,
+                    record.stats_json,
+                ],
+            )?;
+            if rows_affected > 0 {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Check whether any of the given workspaces holds a commit, using an open transaction
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lookup fails.
+    pub fn commit_exists_in_tx(
+        tx: &Transaction<'_>,
+        workspace_ids: &[String],
+        sha: &str,
+    ) -> Result<bool, DbError> {
+        let mut stmt =
+            tx.prepare_cached("SELECT 1 FROM commits WHERE workspace_id = ?1 AND sha = ?2")?;
+        for workspace_id in workspace_ids {
+            if stmt.exists(params![workspace_id, sha])? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Get a commit by SHA within a workspace
+    ///
+    /// # Errors
+    ///
+    /// Returns `DbError::NotFound` if the commit doesn't exist.
+    pub fn get_commit_by_sha(
+        &self,
+        workspace_id: &str,
+        sha: &str,
+    ) -> Result<CommitRecord, DbError> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {COMMIT_COLUMNS} FROM commits WHERE workspace_id = ?1 AND sha = ?2"
+                ),
+                [workspace_id, sha],
+                commit_from_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => DbError::NotFound {
+                    table: "commits".to_string(),
+                    id: sha.to_string(),
+                },
+                _ => DbError::Sqlite(e),
+            })
+    }
+
+    /// Count distinct commits whose SHA starts with `prefix`
+    ///
+    /// A prefix of 40 or more characters is matched exactly. Use this before
+    /// resolving a short SHA to detect ambiguous prefixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DbError::InvalidParameter` if the prefix is shorter than 4
+    /// characters or is not hexadecimal, or an error if the query fails.
+    pub fn get_commit_count_by_sha_prefix(&self, prefix: &str) -> Result<u64, DbError> {
+        if prefix.len() < MIN_SHA_PREFIX_LEN {
+            return Err(DbError::InvalidParameter(
+                "SHA prefix too short".to_string(),
+            ));
+        }
+        // `%` and `_` would act as LIKE wildcards
+        if !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(DbError::InvalidParameter(
+                "SHA prefix must be hexadecimal".to_string(),
+            ));
+        }
+
+        let sql = if prefix.len() >= 40 {
+            "SELECT COUNT(DISTINCT sha) FROM commits WHERE sha = ?1"
+        } else {
+            "SELECT COUNT(DISTINCT sha) FROM commits WHERE sha LIKE ?1 || '%'"
+        };
+        let count: i64 = self.conn.query_row(sql, [prefix], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+
+    // ========================================================================
+    // Note Insertion
+    // ========================================================================
+
+    /// Insert or update a git note
+    ///
+    /// A commit has at most one note per namespace, so re-ingesting a note
+    /// replaces its content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails (e.g., unknown commit ID).
+    pub fn insert_note(&self, record: &NoteRecord) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO notes (id, commit_id, namespace, content, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(commit_id, namespace) DO UPDATE SET
+                content = excluded.content,
+                recorded_at = excluded.recorded_at",
+            params![
+                record.id,
+                record.commit_id,
+                record.namespace,
+                record.content,
+                record.recorded_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // Tag Insertion
+    // ========================================================================
+
+    /// Store the full set of tags of a workspace
+    ///
+    /// Tags are matched by name, so a tag that was moved is updated in place,
+    /// and stored tags missing from `tags` are deleted. Tags on commits that
+    /// have not been ingested for the workspace are skipped. Because commits
+    /// already stored under a sibling clone are not ingested again, a second
+    /// checkout of a repository keeps no tags of its own; they are found
+    /// under the workspace that stored the commits. Returns the number of
+    /// tags stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any statement fails; the workspace's tags are left
+    /// unchanged in that case.
+    pub fn insert_tags_batch(
+        &mut self,
+        workspace_id: &str,
+        tags: &[TagRecord],
+    ) -> Result<usize, DbError> {
+        let tx = self.conn.transaction()?;
+
+        // Drop tags that were deleted from the repository
+        let current: HashSet<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+        let stored = {
+            let mut stmt = tx.prepare("SELECT name FROM tags WHERE workspace_id = ?1")?;
+            let rows = stmt.query_map([workspace_id], |row| row.get::<_, String>(0))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        for name in stored
+            .iter()
+            .filter(|name| !current.contains(name.as_str()))
+        {
+            tx.execute(
+                "DELETE FROM tags WHERE workspace_id = ?1 AND name = ?2",
+                params![workspace_id, name],
+            )?;
+        }
+
+        let mut count = 0;
+        for tag in tags {
+            count += tx.execute(
+                "INSERT INTO tags (id, workspace_id, name, sha, tagged_sha, tagger, message, is_annotated, timestamp, created_at)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
+                 WHERE EXISTS (SELECT 1 FROM commits WHERE workspace_id = ?2 AND sha = ?5)
+                 ON CONFLICT(workspace_id, name) DO UPDATE SET
+                    sha = excluded.sha,
+                    tagged_sha = excluded.tagged_sha,
+                    tagger = excluded.tagger,
+                    message = excluded.message,
+                    is_annotated = excluded.is_annotated,
+                    timestamp = excluded.timestamp",
+                params![
+                    Uuid::new_v4().to_string(),
+                    workspace_id,
+                    tag.name,
+                    tag.sha,
+                    tag.tagged_sha,
+                    tag.tagger,
+                    tag.message,
+                    tag.is_annotated,
+                    tag.timestamp.to_rfc3339(),
+                    Utc::now().to_rfc3339(),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    // ========================================================================
+    // Test Inventory
+    // ========================================================================
+
+    /// Record the tests listed by `cargo nextest list` for a workspace
+    ///
+    /// Tests are keyed by suite (the binary ID) and name. Every listed test
+    /// gets the same `last_seen_at`, and tests seen before keep their
+    /// `first_seen_at`, so a test whose `last_seen_at` is older than the
+    /// latest listing has disappeared. Returns the number of tests recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any insert fails; nothing is recorded in that case.
+    pub fn insert_test_inventory(
+        &mut self,
+        workspace_id: &str,
+        list: &TestList,
+    ) -> Result<usize, DbError> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO test_inventory (workspace_id, suite_name, test_name, ignored, first_seen_at, last_seen_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+                 ON CONFLICT(workspace_id, suite_name, test_name) DO UPDATE SET
+                    ignored = excluded.ignored,
+                    last_seen_at = excluded.last_seen_at",
+            )?;
+            for (suite_name, suite) in &list.rust_suites {
+                for (test_name, case) in &suite.testcases {
+                    count += stmt.execute(params![
+                        workspace_id,
+                        suite_name,
+                        test_name,
+                        case.ignored,
+                        now
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    // ========================================================================
+    // Test Result Insertion
+    // ========================================================================
+
+    /// Insert a test run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails.
+    pub fn insert_test_run(&self, record: &TestRunRecord) -> Result<String, DbError> {
+        self.conn.execute(
+            "INSERT INTO test_runs (id, workspace_id, commit_sha, started_at, finished_at, passed_count, failed_count, ignored_count, metadata_json, tags_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                record.id,
+                record.workspace_id,
+                record.commit_sha,
+                record.started_at.to_rfc3339(),
+                record.finished_at.map(|t| t.to_rfc3339()),
+                record.passed_count,
+                record.failed_count,
+                record.ignored_count,
+                record.metadata_json,
+                record.tags_json,
+            ],
+        )?;
+        Ok(record.id.clone())
+    }
+
+    /// List test runs, most recent first
+    ///
+    /// `commit_sha` matches full SHAs or prefixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn list_test_runs(
+        &self,
+        workspace_id: Option<&str>,
+        commit_sha: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<TestRunRecord>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, workspace_id, commit_sha, started_at, finished_at, passed_count, failed_count, ignored_count, metadata_json, tags_json
+             FROM test_runs
+             WHERE (?1 IS NULL OR workspace_id = ?1)
+               AND (?2 IS NULL OR commit_sha LIKE ?2 || '%')
+             ORDER BY started_at DESC
+             LIMIT ?3",
+        )?;
+
+        let rows = stmt.query_map(
+            params![workspace_id, commit_sha, limit as i64],
+            test_run_from_row,
+        )?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            runs.push(row?);
+        }
+        Ok(runs)
+    }
+
+    /// Get a test run by ID
+    ///
+    /// # Errors
+    ///
+    /// Returns `DbError::NotFound` if no run has this ID.
+    pub fn get_test_run_by_id(&self, run_id: &str) -> Result<TestRunRecord, DbError> {
+        self.conn
+            .query_row(
+                "SELECT id, workspace_id, commit_sha, started_at, finished_at, passed_count, failed_count, ignored_count, metadata_json, tags_json
+                 FROM test_runs WHERE id = ?1",
+                [run_id],
+                test_run_from_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => DbError::NotFound {
+                    table: "test_runs".to_string(),
+                    id: run_id.to_string(),
+                },
+                _ => DbError::Sqlite(e),
+            })
+    }
+
+    /// Insert multiple test results in a transaction
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails.
+    pub fn insert_test_results_batch(
+        &mut self,
+        records: &[TestResultRecord],
+    ) -> Result<usize, DbError> {
+        let tx = self.conn.transaction()?;
+        let count = Self::insert_test_results_in_tx(&tx, records)?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    fn insert_test_results_in_tx(
+        tx: &Transaction<'_>,
+        records: &[TestResultRecord],
+    ) -> Result<usize, DbError> {
+        let mut count = 0;
+        for record in records {
+            tx.execute(
+                "INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, duration_ms, output_json, started_seq, finished_seq, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    record.id,
+                    record.run_id,
+                    record.suite_name,
+                    record.test_name,
+                    record.outcome,
+                    record.duration_ms,
+                    record.output_json,
+                    record.started_seq,
+                    record.finished_seq,
+                    record.created_at.to_rfc3339(),
+                ],
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Store CI annotations for a test run in a transaction
+    ///
+    /// Returns the number of annotations inserted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails, e.g. when the run does not exist
+    /// and foreign keys are enforced.
+    pub fn insert_ci_annotations(
+        &mut self,
+        run_id: &str,
+        annotations: &[CiAnnotation],
+    ) -> Result<usize, DbError> {
+        let tx = self.conn.transaction()?;
+        let created_at = Utc::now().to_rfc3339();
+        for annotation in annotations {
+            tx.execute(
+                "INSERT INTO ci_annotations (id, run_id, level, file, line, message, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    Uuid::new_v4().to_string(),
+                    run_id,
+                    annotation.level.as_str(),
+                    annotation.file,
+                    annotation.line,
+                    annotation.message,
+                    created_at,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(annotations.len())
+    }
+
+    /// Link a test run to a commit SHA
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub fn link_test_run_to_commit(&self, run_id: &str, commit_sha: &str) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE test_runs SET commit_sha = ?1 WHERE id = ?2",
+            [commit_sha, run_id],
+        )?;
+        Ok(())
+    }
+
+    /// Count failing tests in each workspace's most recent test run
+    ///
+    /// Returns `(workspace_id, workspace_name, fail_count)` for workspaces
+    /// whose latest run has at least one failure, most failures first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_failing_test_counts_by_workspace(
+        &self,
+    ) -> Result<Vec<(String, String, u64)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT w.id, w.name, COUNT(*) AS fail_count
+             FROM failing_tests ft
+             JOIN test_runs r ON r.id = ft.run_id
+             JOIN workspaces w ON w.id = r.workspace_id
+             WHERE r.id = (
+                 SELECT latest.id FROM test_runs latest
+                 WHERE latest.workspace_id = w.id
+                 ORDER BY latest.started_at DESC
+                 LIMIT 1
+             )
+             GROUP BY w.id, w.name
+             ORDER BY fail_count DESC, w.name",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            let count: i64 = row.get(2)?;
+            Ok((row.get(0)?, row.get(1)?, count as u64))
+        })?;
+
+        let mut counts = Vec::new();
+        for row in rows {
+            counts.push(row?);
+        }
+        Ok(counts)
+    }
+
+    /// Total failing tests across all workspaces' most recent test runs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn total_failing_tests_count(&self) -> Result<u64, DbError> {
+        Ok(self
+            .get_failing_test_counts_by_workspace()?
+            .iter()
+            .map(|(_, _, count)| count)
+            .sum())
+    }
+
+    // ========================================================================
+    // Copilot Insertion
+    // ========================================================================
+
+    /// Insert a Copilot session
+    ///
+    /// Returns the session ID. Uses INSERT OR IGNORE to handle duplicates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails.
+    pub fn insert_copilot_session(&self, record: &CopilotSessionRecord) -> Result<String, DbError> {
+        // Check if session already exists
+        let existing: Result<String, _> = self.conn.query_row(
+            "SELECT id FROM copilot_sessions WHERE workspace_id = ?1 AND vscode_session_id = ?2",
+            [&record.workspace_id, &record.vscode_session_id],
+            |row| row.get(0),
+        );
+
+        match existing {
+            Ok(id) => Ok(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                self.conn.execute(
+                    "INSERT INTO copilot_sessions (id, workspace_id, vscode_session_id, created_at, updated_at, metadata_json, token_estimate, first_message_at, last_message_at, topics_json)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        record.id,
+                        record.workspace_id,
+                        record.vscode_session_id,
+                        record.created_at.to_rfc3339(),
+                        record.updated_at.to_rfc3339(),
+                        record.metadata_json,
+                        record.token_estimate,
+                        record.first_message_at.map(|t| t.to_rfc3339()),
+                        record.last_message_at.map(|t| t.to_rfc3339()),
+                        record.topics_json,
+                    ],
+                )?;
+                Ok(record.id.clone())
+            }
+            Err(e) => Err(DbError::Sqlite(e)),
+        }
+    }
+
+    /// List Copilot sessions, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn list_copilot_sessions(
+        &self,
+        workspace_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CopilotSessionRecord>, DbError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SESSION_COLUMNS}
+             FROM copilot_sessions
+             WHERE (?1 IS NULL OR workspace_id = ?1)
+             ORDER BY created_at DESC
+             LIMIT ?2"
+        ))?;
+
+        let rows = stmt.query_map(params![workspace_id, limit as i64], session_from_row)?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(row?);
+        }
+        Ok(sessions)
+    }
+
+    /// List Copilot sessions with their message counts, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn list_copilot_sessions_with_counts(
+        &self,
+        workspace_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<(CopilotSessionRecord, u64)>, DbError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SESSION_COLUMNS},
+                    (SELECT COUNT(*) FROM copilot_messages m WHERE m.session_id = copilot_sessions.id)
+             FROM copilot_sessions
+             WHERE (?1 IS NULL OR workspace_id = ?1)
+             ORDER BY created_at DESC
+             LIMIT ?2"
+        ))?;
+
+        let rows = stmt.query_map(params![workspace_id, limit as i64], |row| {
+            Ok((session_from_row(row)?, row.get::<_, i64>(10)? as u64))
+        })?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(row?);
+        }
+        Ok(sessions)
+    }
+
+    /// Get a Copilot session and its messages, oldest message first
+    ///
+    /// `session_id` may be the session ID or the original VS Code session ID.
+    /// Both reads happen in one transaction so the messages match the
+    /// session. Returns `None` if the session does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_copilot_session_with_messages(
+        &self,
+        session_id: &str,
+    ) -> Result<Option<(CopilotSessionRecord, Vec<CopilotMessageRecord>)>, DbError> {
+        Ok(copilot_session_with_messages(&self.conn, session_id)?)
+    }
+
+    /// Get the most recently created Copilot session in a workspace, with
+    /// its messages
+    ///
+    /// Returns `None` if the workspace has no sessions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_latest_copilot_session_with_messages(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Option<(CopilotSessionRecord, Vec<CopilotMessageRecord>)>, DbError> {
+        Ok(session_with_messages(
+            &self.conn,
+            "WHERE workspace_id = ?1 ORDER BY created_at DESC, rowid DESC",
+            workspace_id,
+        )?)
+    }
+
+    /// Insert multiple Copilot messages in a transaction
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails.
+    pub fn insert_copilot_messages_batch(
+        &mut self,
+        records: &[CopilotMessageRecord],
+    ) -> Result<usize, DbError> {
+        let tx = self.conn.transaction()?;
+        let count = Self::insert_copilot_messages_in_tx(&tx, records)?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    fn insert_copilot_messages_in_tx(
+        tx: &Transaction<'_>,
+        records: &[CopilotMessageRecord],
+    ) -> Result<usize, DbError> {
+        let mut count = 0;
+        for record in records {
+            tx.execute(
+                "INSERT INTO copilot_messages (id, session_id, request_id, role, content, variables_json, timestamp, created_at, truncated, agent, code_blocks_json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    record.id,
+                    record.session_id,
+                    record.request_id,
+                    record.role,
+                    record.content,
+                    record.variables_json,
+                    record.timestamp.to_rfc3339(),
+                    record.created_at.to_rfc3339(),
+                    record.is_truncated(),
+                    record.agent,
+                    record.code_blocks_json,
+                ],
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Get message count for a session
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_session_message_count(&self, session_id: &str) -> Result<i64, DbError> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM copilot_messages WHERE session_id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+}
+
+/// Parse an RFC 3339 timestamp string
+///
+/// # Errors
+///
+/// Returns [`DbError::InvalidTimestamp`] if `s` is not valid RFC 3339.
+pub(crate) fn try_parse_timestamp(s: &str) -> Result<DateTime<Utc>, DbError> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|source| DbError::InvalidTimestamp {
+            value: s.to_string(),
+            source,
+        })
+}
+
+/// Read a timestamp column, failing the row if the stored value is malformed
+pub(crate) fn timestamp_at(row: &rusqlite::Row<'_>, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: String = row.get(idx)?;
+    try_parse_timestamp(&value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// Read a nullable timestamp column, failing the row if the stored value is malformed
+pub(crate) fn optional_timestamp_at(
+    row: &rusqlite::Row<'_>,
+    idx: usize,
+) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    match row.get_ref(idx)? {
+        rusqlite::types::ValueRef::Null => Ok(None),
+        _ => timestamp_at(row, idx).map(Some),
+    }
+}
+
+/// Describe how long before `now` a timestamp was, e.g. "3 days ago"
+///
+/// Months are 30 days and years are 365 days. Timestamps in the future
+/// are treated as "just now".
+pub(crate) fn format_relative(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - then).num_seconds();
+    let (count, unit) = match secs {
+        ..60 => return "just now".to_string(),
+        60..3_600 => (secs / 60, "minute"),
+        3_600..86_400 => (secs / 3_600, "hour"),
+        86_400..2_592_000 => (secs / 86_400, "day"),
+        2_592_000..31_536_000 => (secs / 2_592_000, "month"),
+        _ => (secs / 31_536_000, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+/// Columns read by [`commit_from_row`], in order
+pub(crate) const COMMIT_COLUMNS: &str = "id, workspace_id, sha, author, author_email, message, timestamp, parents_json, diff_json, diff_excluded, language_hint, created_at, is_revert, reverted_sha, stats_json";
+
+/// Build a commit record from a row selecting [`COMMIT_COLUMNS`]
+pub(crate) fn commit_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CommitRecord> {
+    Ok(CommitRecord {
+        id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        sha: row.get(2)?,
+        author: row.get(3)?,
+        author_email: row.get(4)?,
+        message: row.get(5)?,
+        timestamp: timestamp_at(row, 6)?,
+        parents_json: row.get(7)?,
+        diff_json: row.get(8)?,
+        diff_excluded: row.get(9)?,
+        language_hint: row.get(10)?,
+        created_at: timestamp_at(row, 11)?,
+        is_revert: row.get::<_, Option<bool>>(12)?.unwrap_or(false),
+        reverted_sha: row.get(13)?,
+        stats_json: row.get(14)?,
+    })
+}
+
+/// Columns read by [`session_from_row`], in order
+pub(crate) const SESSION_COLUMNS: &str = "id, workspace_id, vscode_session_id, created_at, updated_at, metadata_json, token_estimate, first_message_at, last_message_at, topics_json";
+
+/// Build a Copilot session record from a row selecting [`SESSION_COLUMNS`]
+pub(crate) fn session_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CopilotSessionRecord> {
+    Ok(CopilotSessionRecord {
+        id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        vscode_session_id: row.get(2)?,
+        created_at: timestamp_at(row, 3)?,
+        updated_at: timestamp_at(row, 4)?,
+        metadata_json: row.get(5)?,
+        token_estimate: row.get(6)?,
+        first_message_at: optional_timestamp_at(row, 7)?,
+        last_message_at: optional_timestamp_at(row, 8)?,
+        topics_json: row.get(9)?,
+    })
+}
+
+/// Columns of `copilot_messages` read by [`message_from_row`], in order
+pub(crate) const MESSAGE_COLUMNS: &str = "m.id, m.session_id, m.request_id, m.role, m.content, m.variables_json, m.timestamp, m.created_at, m.agent, m.code_blocks_json";
+
+/// Build a Copilot message record from a row selecting [`MESSAGE_COLUMNS`]
+pub(crate) fn message_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CopilotMessageRecord> {
+    Ok(CopilotMessageRecord {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        request_id: row.get(2)?,
+        role: row.get(3)?,
+        content: row.get(4)?,
+        variables_json: row.get(5)?,
+        timestamp: timestamp_at(row, 6)?,
+        created_at: timestamp_at(row, 7)?,
+        agent: row.get(8)?,
+        code_blocks_json: row.get(9)?,
+    })
+}
+
+/// Load a Copilot session by session ID or VS Code session ID, with its
+/// messages oldest first
+///
+/// If several workspaces share a VS Code session ID, the newest session is
+/// used.
+pub(crate) fn copilot_session_with_messages(
+    conn: &Connection,
+    session_id: &str,
+) -> rusqlite::Result<Option<(CopilotSessionRecord, Vec<CopilotMessageRecord>)>> {
+    session_with_messages(
+        conn,
+        "WHERE id = ?1 OR vscode_session_id = ?1 ORDER BY created_at DESC",
+        session_id,
+    )
+}
+
+/// Load the first session matching `filter` and its messages in one
+/// transaction
+fn session_with_messages(
+    conn: &Connection,
+    filter: &str,
+    value: &str,
+) -> rusqlite::Result<Option<(CopilotSessionRecord, Vec<CopilotMessageRecord>)>> {
+    let tx = conn.unchecked_transaction()?;
+
+    let session = tx
+        .query_row(
+            &format!("SELECT {SESSION_COLUMNS} FROM copilot_sessions {filter} LIMIT 1"),
+            [value],
+            session_from_row,
+        )
+        .optional()?;
+    let Some(session) = session else {
+        return Ok(None);
+    };
+
+    let messages = {
+        let mut stmt = tx.prepare(&format!(
+            "SELECT {MESSAGE_COLUMNS}
+             FROM copilot_messages m
+             WHERE m.session_id = ?1
+             ORDER BY m.timestamp, m.rowid"
+        ))?;
+        let rows = stmt.query_map([&session.id], message_from_row)?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    tx.commit()?;
+
+    Ok(Some((session, messages)))
+}
+
+/// Columns read by [`workspace_from_row`], in order
+pub(crate) const WORKSPACE_COLUMNS: &str = "id, name, path, created_at, updated_at, repo_fingerprint, last_git_ingest_at, last_copilot_ingest_at, last_test_ingest_at";
+
+/// Build a workspace record from a row selecting [`WORKSPACE_COLUMNS`]
+pub(crate) fn workspace_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<WorkspaceRecord> {
+    Ok(WorkspaceRecord {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        path: row.get(2)?,
+        created_at: timestamp_at(row, 3)?,
+        updated_at: timestamp_at(row, 4)?,
+        repo_fingerprint: row.get(5)?,
+        last_git_ingest_at: optional_timestamp_at(row, 6)?,
+        last_copilot_ingest_at: optional_timestamp_at(row, 7)?,
+        last_test_ingest_at: optional_timestamp_at(row, 8)?,
+    })
+}
+
+/// Build a test run record from a row of `test_runs` columns in table order
+fn test_run_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TestRunRecord> {
+    Ok(TestRunRecord {
+        id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        commit_sha: row.get(2)?,
+        started_at: timestamp_at(row, 3)?,
+        finished_at: optional_timestamp_at(row, 4)?,
+        passed_count: row.get(5)?,
+        failed_count: row.get(6)?,
+        ignored_count: row.get(7)?,
+        metadata_json: row.get(8)?,
+        tags_json: row.get(9)?,
+    })
+}
+
+/// Reject pragma names that aren't plain identifiers
+fn validate_pragma_name(pragma: &str) -> Result<(), DbError> {
+    if pragma.is_empty()
+        || !pragma
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(DbError::InvalidPragma {
+            pragma: pragma.to_string(),
+            message: "pragma names may only contain letters, digits and underscores".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Size of a database in bytes (`page_count * page_size`)
+fn database_size(conn: &Connection) -> Result<u64, DbError> {
+    let size: i64 = conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(u64::try_from(size).unwrap_or(0))
+}
+
+/// Render a pragma result column as text
+fn value_ref_to_string(value: rusqlite::types::ValueRef<'_>) -> String {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) | ValueRef::Blob(t) => String::from_utf8_lossy(t).into_owned(),
+    }
+}
+
+/// Convert a result column to JSON, rendering blobs as lossy UTF-8
+fn value_ref_to_json(value: rusqlite::types::ValueRef<'_>) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f)
+            .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        ValueRef::Text(t) | ValueRef::Blob(t) => String::from_utf8_lossy(t).into_owned().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
     use super::*;
     use similar_asserts::assert_eq;
 
     #[test]
-    fn test_database_in_memory() {
-        let db = Database::in_memory().expect("should create in-memory db");
-        assert!(!db.is_initialized());
+    fn test_database_in_memory() {
+        let db = Database::in_memory().expect("should create in-memory db");
+        assert!(!db.is_initialized());
+    }
+
+    #[test]
+    fn test_database_initialize() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        assert!(db.is_initialized());
+    }
+
+    #[test]
+    fn test_database_initialize_idempotent() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("first init");
+        db.initialize().expect("second init should succeed");
+        assert!(db.is_initialized());
+    }
+
+    #[test]
+    fn test_database_tables_created() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let tables = vec![
+            "workspaces",
+            "commits",
+            "test_runs",
+            "test_results",
+            "copilot_sessions",
+            "copilot_messages",
+        ];
+
+        for table in tables {
+            let exists: i32 = db
+                .connection()
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?",
+                    [table],
+                    |row| row.get(0),
+                )
+                .expect("query should succeed");
+            assert_eq!(exists, 1, "Table {table} should exist");
+        }
+    }
+
+    #[test]
+    fn test_database_count_empty() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        assert_eq!(db.count("workspaces").expect("count"), 0);
+        assert_eq!(db.count("commits").expect("count"), 0);
+    }
+
+    #[test]
+    fn test_database_insert_workspace() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        db.connection()
+            .execute(
+                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+                [
+                    "550e8400-e29b-41d4-a716-446655440000",
+                    "test-workspace",
+                    "/path/to/workspace",
+                    "2026-01-17T02:33:06Z",
+                    "2026-01-17T02:33:06Z",
+                ],
+            )
+            .expect("insert should succeed");
+
+        assert_eq!(db.count("workspaces").expect("count"), 1);
+    }
+
+    #[test]
+    fn test_database_foreign_key_references() {
+        let db = Database::in_memory().expect("should create db");
+        // Enable foreign key enforcement
+        db.connection()
+            .execute("PRAGMA foreign_keys = ON", [])
+            .expect("pragma");
+        db.initialize().expect("should initialize");
+
+        // Insert a workspace first
+        db.connection()
+            .execute(
+                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+                ["ws-1", "test", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
+            )
+            .expect("insert workspace");
+
+        // Insert a commit referencing the workspace
+        db.connection()
+            .execute(
+                "INSERT INTO commits (id, workspace_id, sha, author, message, timestamp, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                ["c-1", "ws-1", "abc123", "Test Author", "Test commit", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
+            )
+            .expect("insert commit");
+
+        assert_eq!(db.count("commits").expect("count"), 1);
+    }
+
+    #[test]
+    fn test_database_json_column() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        // Insert workspace
+        db.connection()
+            .execute(
+                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+                ["ws-1", "test", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
+            )
+            .expect("insert workspace");
+
+        // Insert commit with JSON data
+        let parents_json = r#"["parent1", "parent2"]"#;
+        let diff_json = r#"{"files": [{"path": "test.rs", "added": 10, "deleted": 5}]}"#;
+
+        db.connection()
+            .execute(
+                "INSERT INTO commits (id, workspace_id, sha, author, message, timestamp, parents_json, diff_json, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                ["c-1", "ws-1", "abc123", "Author", "Message", "2026-01-17T00:00:00Z", parents_json, diff_json, "2026-01-17T00:00:00Z"],
+            )
+            .expect("insert commit");
+
+        // Query and verify JSON is stored correctly
+        let stored_json: String = db
+            .connection()
+            .query_row(
+                "SELECT parents_json FROM commits WHERE id = ?",
+                ["c-1"],
+                |row| row.get(0),
+            )
+            .expect("query");
+
+        assert_eq!(stored_json, parents_json);
+    }
+
+    #[test]
+    fn test_database_unique_constraint() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        // Insert workspace
+        db.connection()
+            .execute(
+                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+                ["ws-1", "test", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
+            )
+            .expect("insert workspace");
+
+        // Try to insert duplicate path
+        let result = db.connection().execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+            ["ws-2", "test2", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
+        );
+
+        assert!(result.is_err(), "Duplicate path should fail");
+    }
+
+    #[test]
+    fn test_database_indexes_created() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let indexes = vec![
+            "idx_commits_workspace",
+            "idx_commits_timestamp",
+            "idx_commits_sha",
+            "idx_test_runs_workspace",
+            "idx_test_results_run",
+            "idx_copilot_sessions_workspace",
+            "idx_copilot_messages_session",
+        ];
+
+        for index in indexes {
+            let exists: i32 = db
+                .connection()
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='index' AND name=?",
+                    [index],
+                    |row| row.get(0),
+                )
+                .expect("query should succeed");
+            assert_eq!(exists, 1, "Index {index} should exist");
+        }
+    }
+
+    #[test]
+    fn test_database_schema_version() {
+        let db = Database::in_memory().expect("should create db");
+
+        // Before initialization, version should be 0
+        assert_eq!(db.schema_version().expect("version"), 0);
+
+        db.initialize().expect("should initialize");
+
+        // After initialization, version should match CURRENT_VERSION
+        assert_eq!(
+            db.schema_version().expect("version"),
+            crate::migrations::CURRENT_VERSION
+        );
+    }
+
+    #[test]
+    fn test_pragma_set_get_cache_size() {
+        let db = Database::in_memory().expect("should create db");
+
+        db.pragma_set("cache_size", "-4000")
+            .expect("set cache_size");
+        assert_eq!(db.pragma_get("cache_size").expect("get"), "-4000");
+    }
+
+    #[test]
+    fn test_pragma_set_rejects_ignored_value() {
+        let db = Database::in_memory().expect("should create db");
+
+        // In-memory databases always report journal_mode = memory
+        let result = db.pragma_set("journal_mode", "wal");
+        assert!(matches!(result, Err(DbError::InvalidPragma { .. })));
+    }
+
+    #[test]
+    fn test_optimize_profiles_in_memory() {
+        let db = Database::in_memory().expect("should create db");
+
+        db.optimize_for_write().expect("write profile");
+        assert_eq!(db.pragma_get("synchronous").expect("get"), "0");
+        assert_eq!(db.pragma_get("journal_mode").expect("get"), "memory");
+
+        db.optimize_for_read().expect("read profile");
+        assert_eq!(db.pragma_get("synchronous").expect("get"), "2");
+        assert_eq!(db.pragma_get("cache_size").expect("get"), "10000");
+    }
+
+    #[test]
+    fn test_optimize_profiles_file() {
+        let path = std::env::temp_dir().join(format!(
+            "hindsight-optimize-profiles-{}.db",
+            std::process::id()
+        ));
+        let db = Database::open(&path).expect("should open db");
+
+        db.optimize_for_write().expect("write profile");
+        assert_eq!(db.pragma_get("synchronous").expect("get"), "0");
+        assert_eq!(db.pragma_get("journal_mode").expect("get"), "delete");
+
+        db.optimize_for_read().expect("read profile");
+        assert_eq!(db.pragma_get("synchronous").expect("get"), "2");
+        assert_eq!(db.pragma_get("cache_size").expect("get"), "10000");
+        assert_eq!(db.pragma_get("mmap_size").expect("get"), "268435456");
+
+        drop(db);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_pragma_rejects_injection() {
+        let db = Database::in_memory().expect("should create db");
+
+        assert!(matches!(
+            db.pragma_get("cache_size; DROP TABLE workspaces"),
+            Err(DbError::InvalidPragma { .. })
+        ));
+        assert!(matches!(
+            db.pragma_set("cache_size", "1; DROP TABLE workspaces"),
+            Err(DbError::InvalidPragma { .. })
+        ));
+    }
+
+    #[test]
+    fn test_execute_readonly_query_select() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        db.get_or_create_workspace("test", "/test")
+            .expect("create workspace");
+
+        let rows = db
+            .execute_readonly_query(
+                "SELECT name, path, 1.5 AS ratio FROM workspaces WHERE path = ?1",
+                &["/test"],
+            )
+            .expect("query");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["name"], serde_json::json!("test"));
+        assert_eq!(rows[0]["ratio"], serde_json::json!(1.5));
+
+        let empty = db
+            .execute_readonly_query("SELECT * FROM workspaces WHERE path = ?1", &["/none"])
+            .expect("query");
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_execute_readonly_query_rejects_writes() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let insert = db.execute_readonly_query(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('a', 'b', 'c', 'd', 'e')",
+            &[],
+        );
+        assert!(matches!(insert, Err(DbError::ReadOnlyViolation(_))));
+
+        let cte_delete = db.execute_readonly_query(
+            "WITH doomed AS (SELECT id FROM workspaces) DELETE FROM workspaces WHERE id IN doomed",
+            &[],
+        );
+        assert!(matches!(cte_delete, Err(DbError::ReadOnlyViolation(_))));
+
+        let chained = db.execute_readonly_query("SELECT 1; DELETE FROM workspaces", &[]);
+        assert!(chained.is_err());
+
+        // Normal writes still work once the query has finished
+        db.get_or_create_workspace("test", "/test")
+            .expect("create workspace");
+        assert_eq!(db.count("workspaces").expect("count"), 1);
+    }
+
+    #[test]
+    fn test_execute_readonly_query_row_limit() {
+        let db = Database::in_memory().expect("should create db");
+
+        let rows = db
+            .execute_readonly_query(
+                "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 5000) SELECT x FROM n",
+                &[],
+            )
+            .expect("query");
+        assert_eq!(rows.len(), MAX_QUERY_ROWS);
+    }
+
+    #[test]
+    fn test_optimize() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        db.optimize().expect("optimize");
+    }
+
+    /// Open a file database holding a few thousand since-deleted workspaces
+    fn database_with_deleted_rows(name: &str) -> (Database, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("hindsight-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut db = Database::open(&path).expect("should open db");
+        db.initialize().expect("should initialize");
+
+        let tx = db.conn.transaction().expect("transaction");
+        for i in 0..2000 {
+            tx.execute(
+                "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, '2026-01-01', '2026-01-01')",
+                params![
+                    format!("ws-{i}"),
+                    "x".repeat(200),
+                    format!("/workspace/{i}")
+                ],
+            )
+            .expect("insert");
+        }
+        tx.commit().expect("commit");
+        db.conn
+            .execute("DELETE FROM workspaces", [])
+            .expect("delete");
+
+        (db, path)
+    }
+
+    fn integrity_check(conn: &Connection) -> String {
+        conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .expect("integrity check")
+    }
+
+    #[test]
+    fn test_compact_reclaims_space() {
+        let (mut db, path) = database_with_deleted_rows("compact");
+        let before = database_size(&db.conn).expect("size");
+
+        let freed = db.compact().expect("compact");
+
+        assert!(freed > 0, "deleted rows should leave space to reclaim");
+        assert_eq!(database_size(&db.conn).expect("size"), before - freed);
+        assert_eq!(integrity_check(&db.conn), "ok");
+        assert_eq!(db.count("workspaces").expect("count"), 0);
+
+        drop(db);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Insert 1000 commits with long messages into a new workspace
+    fn insert_bulk_commits(db: &mut Database) -> String {
+        let ws_id = db
+            .get_or_create_workspace("bulk", "/bulk")
+            .expect("workspace");
+        let commits: Vec<CommitRecord> = (0..1000)
+            .map(|i| {
+                CommitRecord::new(
+                    ws_id.clone(),
+                    format!("{i:040x}"),
+                    "Test Author".to_string(),
+                    None,
+                    format!("Commit {i}: {}", "x".repeat(200)),
+                    Utc::now(),
+                )
+            })
+            .collect();
+        db.insert_commits_batch(&commits).expect("insert commits");
+        ws_id
+    }
+
+    #[test]
+    fn test_vacuum_clears_freelist() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        assert_eq!(db.freelist_ratio().expect("ratio"), 0.0);
+
+        insert_bulk_commits(&mut db);
+        db.conn.execute("DELETE FROM commits", []).expect("delete");
+
+        assert!(db.freelist_ratio().expect("ratio") > 0.0);
+        db.vacuum().expect("vacuum");
+        assert!(db.freelist_ratio().expect("ratio") < 0.01);
+        assert_eq!(integrity_check(&db.conn), "ok");
+    }
+
+    #[test]
+    fn test_compact_into_writes_smaller_copy() {
+        let (db, path) = database_with_deleted_rows("compact-into");
+        let dest = path.with_extension("compacted.db");
+        let _ = std::fs::remove_file(&dest);
+        let before = database_size(&db.conn).expect("size");
+
+        let freed = db.compact_into(&dest).expect("compact into");
+
+        assert!(freed > 0);
+        assert_eq!(database_size(&db.conn).expect("size"), before);
+        let copy = Database::open(&dest).expect("open copy");
+        assert_eq!(integrity_check(&copy.conn), "ok");
+        assert!(copy.is_initialized());
+
+        // The destination must not already exist
+        assert!(db.compact_into(&dest).is_err());
+
+        drop((db, copy));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&dest);
+    }
+
+    #[test]
+    fn test_database_fts_tables_created() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let fts_tables = vec!["commits_fts", "copilot_messages_fts"];
+
+        for table in fts_tables {
+            let exists: i32 = db
+                .connection()
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?",
+                    [table],
+                    |row| row.get(0),
+                )
+                .expect("query should succeed");
+            assert_eq!(exists, 1, "FTS table {table} should exist");
+        }
+    }
+
+    #[test]
+    fn test_database_views_created() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let views = vec!["timeline", "failing_tests", "recent_activity"];
+
+        for view in views {
+            let exists: i32 = db
+                .connection()
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='view' AND name=?",
+                    [view],
+                    |row| row.get(0),
+                )
+                .expect("query should succeed");
+            assert_eq!(exists, 1, "View {view} should exist");
+        }
+    }
+
+    // ========================================================================
+    // Record Type Tests
+    // ========================================================================
+
+    #[test]
+    fn test_workspace_record_new() {
+        let record = WorkspaceRecord::new("test".to_string(), "/path".to_string());
+        assert!(!record.id.is_empty());
+        assert_eq!(record.name, "test");
+        assert_eq!(record.path, "/path");
+    }
+
+    #[test]
+    fn test_workspace_record_from_path() {
+        let root = std::env::temp_dir().join(format!("hindsight-ws-{}", std::process::id()));
+        let project = root.join("my-project");
+        std::fs::create_dir_all(&project).expect("create dir");
+
+        let record = WorkspaceRecord::from_path(&project).expect("absolute path");
+        assert_eq!(record.name, "my-project");
+        assert_eq!(
+            Path::new(&record.path),
+            project.canonicalize().expect("canonical")
+        );
+
+        let parent = WorkspaceRecord::from_path(&project.join("..")).expect("parent path");
+        assert_eq!(
+            Some(parent.name.as_str()),
+            root.file_name().and_then(|n| n.to_str())
+        );
+
+        // Relative paths resolve against the crate directory under cargo test
+        let src = WorkspaceRecord::from_path(Path::new("src")).expect("relative path");
+        assert_eq!(src.name, "src");
+        assert!(Path::new(&src.path).is_absolute());
+        let cwd = std::env::current_dir().expect("cwd");
+        let here = WorkspaceRecord::from_path(Path::new(".")).expect("current dir");
+        assert_eq!(
+            Some(here.name.as_str()),
+            cwd.file_name().and_then(|n| n.to_str())
+        );
+
+        assert!(WorkspaceRecord::from_path(&root.join("missing")).is_none());
+        std::fs::remove_dir_all(&root).expect("clean up");
+    }
+
+    #[test]
+    fn test_commit_record_new() {
+        let record = CommitRecord::new(
+            "ws-1".to_string(),
+            "abc123".to_string(),
+            "Author".to_string(),
+            Some("author@example.com".to_string()),
+            "Test message".to_string(),
+            Utc::now(),
+        );
+        assert!(!record.id.is_empty());
+        assert_eq!(record.workspace_id, "ws-1");
+        assert_eq!(record.sha, "abc123");
+        assert!(record.parents_json.is_none());
+        assert!(record.diff_json.is_none());
+    }
+
+    #[test]
+    fn test_commit_record_with_parents() {
+        let record = CommitRecord::new(
+            "ws-1".to_string(),
+            "abc123".to_string(),
+            "Author".to_string(),
+            Some("author@example.com".to_string()),
+            "Test".to_string(),
+            Utc::now(),
+        )
+        .with_parents(vec!["parent1".to_string(), "parent2".to_string()]);
+
+        assert!(record.parents_json.is_some());
+        let json = record.parents_json.unwrap();
+        assert!(json.contains("parent1"));
+        assert!(json.contains("parent2"));
+    }
+
+    #[test]
+    fn test_commit_record_with_stats() {
+        let stats = CommitStats {
+            files_changed: 3,
+            insertions: 120,
+            deletions: 7,
+        };
+        let record = CommitRecord::new(
+            "ws-1".to_string(),
+            "abc123".to_string(),
+            "Author".to_string(),
+            None,
+            "Test".to_string(),
+            Utc::now(),
+        )
+        .with_stats(stats);
+
+        assert!(record.diff_json.is_none());
+        assert_eq!(record.stats(), Some(stats));
+        assert!(
+            record
+                .stats_json
+                .expect("stats json")
+                .contains("\"insertions\":120")
+        );
+    }
+
+    #[test]
+    fn test_test_run_record_new() {
+        let record = TestRunRecord::new("ws-1".to_string());
+        assert!(!record.id.is_empty());
+        assert_eq!(record.workspace_id, "ws-1");
+        assert!(record.commit_sha.is_none());
+        assert!(record.finished_at.is_none());
+        assert_eq!(record.passed_count, 0);
     }
 
     #[test]
-    fn test_database_initialize() {
+    fn test_test_run_record_finished() {
+        let record = TestRunRecord::new("ws-1".to_string()).finished(10, 2, 1);
+        assert!(record.finished_at.is_some());
+        assert_eq!(record.passed_count, 10);
+        assert_eq!(record.failed_count, 2);
+        assert_eq!(record.ignored_count, 1);
+    }
+
+    #[test]
+    fn test_test_run_record_with_timing() {
+        let started: DateTime<Utc> = "2026-04-01T10:00:00Z".parse().expect("start");
+        let finished = started + chrono::Duration::seconds(4);
+
+        let record = TestRunRecord::new("ws-1".to_string()).with_timing(started, finished);
+        assert_eq!(record.started_at, started);
+        assert_eq!(record.finished_at, Some(finished));
+        assert_eq!(record.duration_secs(), Some(4.0));
+
+        assert_eq!(TestRunRecord::new("ws-1".to_string()).duration_secs(), None);
+    }
+
+    #[test]
+    fn test_test_run_record_with_environment_tag() {
+        let record = TestRunRecord::new("ws-1".to_string());
+        assert!(record.tags_json.is_none());
+
+        let record = record
+            .with_environment_tag("env", "local")
+            .with_environment_tag("os", "linux")
+            .with_environment_tag("env", "ci");
+
+        let tags: serde_json::Value =
+            serde_json::from_str(record.tags_json.as_deref().expect("tags")).expect("json");
+        assert_eq!(tags, serde_json::json!({"env": "ci", "os": "linux"}));
+    }
+
+    #[test]
+    fn test_copilot_session_record_new() {
+        let record = CopilotSessionRecord::new("ws-1".to_string(), "vscode-123".to_string());
+        assert!(!record.id.is_empty());
+        assert_eq!(record.workspace_id, "ws-1");
+        assert_eq!(record.vscode_session_id, "vscode-123");
+    }
+
+    #[test]
+    fn test_copilot_message_record_new() {
+        let record = CopilotMessageRecord::new(
+            "session-1".to_string(),
+            "user".to_string(),
+            "Hello".to_string(),
+            Utc::now(),
+        );
+        assert!(!record.id.is_empty());
+        assert_eq!(record.session_id, "session-1");
+        assert_eq!(record.role, "user");
+        assert_eq!(record.content, "Hello");
+        assert!(record.request_id.is_none());
+    }
+
+    #[test]
+    fn test_test_result_record_with_structured_output() {
+        let record = |format, content| {
+            let record = TestResultRecord::new(
+                "run-1".to_string(),
+                "suite".to_string(),
+                "test".to_string(),
+                "passed".to_string(),
+                None,
+            )
+            .with_structured_output(format, content);
+            serde_json::from_str::<serde_json::Value>(&record.output_json.expect("output"))
+                .expect("valid json")
+        };
+
+        let json = record(OutputFormat::Json, r#"{"count": 3}"#);
+        assert_eq!(json["format"], "json");
+        assert_eq!(json["json"]["count"], 3);
+        assert_eq!(json["stdout"], r#"{"count": 3}"#);
+
+        let malformed = record(OutputFormat::Json, "{\"count\": ");
+        assert_eq!(malformed["format"], "raw");
+        assert!(malformed.get("json").is_none());
+        assert_eq!(malformed["stdout"], "{\"count\": ");
+
+        let xml = record(OutputFormat::Xml, "<testsuite/>");
+        assert_eq!(xml["format"], "xml");
+        assert_eq!(xml["stdout"], "<testsuite/>");
+    }
+
+    #[test]
+    fn test_commit_record_with_extension_stats() {
+        let mut stats = HashMap::new();
+        stats.insert(
+            "rs".to_string(),
+            FileDiffStats {
+                files: 2,
+                insertions: 15,
+                deletions: 3,
+            },
+        );
+
+        let record = CommitRecord::new(
+            "ws".to_string(),
+            "abc".to_string(),
+            "Author".to_string(),
+            None,
+            "msg".to_string(),
+            Utc::now(),
+        )
+        .with_diff_json(r#"{"files_changed":2,"files":[]}"#.to_string())
+        .with_extension_stats(stats.clone());
+
+        let diff: serde_json::Value =
+            serde_json::from_str(record.diff_json.as_deref().expect("diff json")).expect("json");
+        assert_eq!(diff["files_changed"], 2);
+        assert_eq!(diff["extension_stats"]["rs"]["insertions"], 15);
+
+        let excluded = CommitRecord::new(
+            "ws".to_string(),
+            "def".to_string(),
+            "Author".to_string(),
+            None,
+            "msg".to_string(),
+            Utc::now(),
+        )
+        .with_diff_excluded()
+        .with_extension_stats(stats);
+        assert!(excluded.diff_json.is_none());
+    }
+
+    // ========================================================================
+    // Workspace Insertion Tests
+    // ========================================================================
+
+    #[test]
+    fn test_insert_workspace_via_method() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
-        assert!(db.is_initialized());
+
+        let record =
+            WorkspaceRecord::new("my-project".to_string(), "/home/user/project".to_string());
+        db.insert_workspace(&record).expect("insert should succeed");
+
+        assert_eq!(db.count("workspaces").expect("count"), 1);
     }
 
     #[test]
-    fn test_database_initialize_idempotent() {
+    fn test_get_or_create_workspace_creates() {
         let db = Database::in_memory().expect("should create db");
-        db.initialize().expect("first init");
-        db.initialize().expect("second init should succeed");
-        assert!(db.is_initialized());
+        db.initialize().expect("should initialize");
+
+        let id = db
+            .get_or_create_workspace("new-project", "/new/path")
+            .expect("should create");
+        assert!(!id.is_empty());
+        assert_eq!(db.count("workspaces").expect("count"), 1);
     }
 
     #[test]
-    fn test_database_tables_created() {
+    fn test_get_or_create_workspace_returns_existing() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        let tables = vec![
-            "workspaces",
-            "commits",
-            "test_runs",
-            "test_results",
-            "copilot_sessions",
-            "copilot_messages",
-        ];
+        let id1 = db
+            .get_or_create_workspace("project", "/path")
+            .expect("first call");
+        let id2 = db
+            .get_or_create_workspace("project", "/path")
+            .expect("second call");
 
-        for table in tables {
-            let exists: i32 = db
-                .connection()
-                .query_row(
-                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?",
-                    [table],
-                    |row| row.get(0),
+        assert_eq!(id1, id2);
+        assert_eq!(db.count("workspaces").expect("count"), 1);
+    }
+
+    #[test]
+    fn test_insert_workspace_event_appears_in_timeline() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        let ws_id = db
+            .get_or_create_workspace("project", "/path")
+            .expect("workspace");
+
+        let event_at: DateTime<Utc> = "2026-04-01T10:00:00Z".parse().expect("timestamp");
+        let id = db
+            .insert_workspace_event(
+                &ws_id,
+                "deploy",
+                "Shipped v2",
+                "Production rollout",
+                event_at,
+            )
+            .expect("insert event");
+
+        let timeline = crate::queries::get_timeline(
+            db.connection(),
+            10,
+            Some(&crate::queries::TimelineFilter::new().with_workspace("/path")),
+        )
+        .expect("timeline");
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline[0].event_id, id);
+        assert_eq!(timeline[0].event_type, "deploy");
+        assert_eq!(timeline[0].summary, "Shipped v2");
+        assert!(
+            timeline[0]
+                .details_json
+                .as_deref()
+                .is_some_and(|d| d.contains("Production rollout"))
+        );
+
+        assert!(matches!(
+            db.insert_workspace_event(&ws_id, " ", "title", "", event_at),
+            Err(DbError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            db.insert_workspace_event(&ws_id, "deploy", "", "", event_at),
+            Err(DbError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_list_workspaces() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        db.get_or_create_workspace("alpha", "/alpha")
+            .expect("create");
+        db.get_or_create_workspace("beta", "/beta").expect("create");
+
+        let workspaces = db.list_workspaces().expect("list");
+        assert_eq!(workspaces.len(), 2);
+        assert_eq!(workspaces[0].name, "alpha"); // Sorted by name
+        assert_eq!(workspaces[1].name, "beta");
+    }
+
+    #[test]
+    fn test_get_workspaces_by_fingerprint() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let a = db.get_or_create_workspace("a", "/a").expect("create");
+        let b = db.get_or_create_workspace("b", "/b").expect("create");
+        db.get_or_create_workspace("c", "/c").expect("create");
+        assert_eq!(db.get_workspace_fingerprint(&a).expect("get"), None);
+        db.set_workspace_fingerprint(&a, "abc123").expect("set");
+        db.set_workspace_fingerprint(&b, "abc123").expect("set");
+        assert_eq!(
+            db.get_workspace_fingerprint(&a).expect("get").as_deref(),
+            Some("abc123")
+        );
+
+        let matches = db.get_workspaces_by_fingerprint("abc123").expect("query");
+        let mut ids: Vec<_> = matches.iter().map(|w| w.id.clone()).collect();
+        ids.sort();
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert!(
+            matches
+                .iter()
+                .all(|w| w.repo_fingerprint.as_deref() == Some("abc123"))
+        );
+
+        assert!(
+            db.get_workspaces_by_fingerprint("missing")
+                .expect("query")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_set_get_reset() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        assert_eq!(
+            db.get_checkpoint(&ws_id, IngestSource::Git).expect("get"),
+            None
+        );
+
+        for sha in ["aaa111", "bbb222"] {
+            let tx = db.conn.transaction().expect("tx");
+            Database::set_checkpoint_in_tx(&tx, &ws_id, IngestSource::Git, sha).expect("set");
+            tx.commit().expect("commit");
+        }
+        assert_eq!(
+            db.get_checkpoint(&ws_id, IngestSource::Git)
+                .expect("get")
+                .as_deref(),
+            Some("bbb222")
+        );
+        assert_eq!(
+            db.get_checkpoint(&ws_id, IngestSource::Copilot)
+                .expect("get"),
+            None
+        );
+
+        db.reset_checkpoint(&ws_id, IngestSource::Git)
+            .expect("reset");
+        assert_eq!(
+            db.get_checkpoint(&ws_id, IngestSource::Git).expect("get"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_apply_retention_policy() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let other_id = db
+            .get_or_create_workspace("other", "/other")
+            .expect("workspace");
+        let days_ago = |days: i64| Utc::now() - chrono::Duration::days(days);
+
+        let commits: Vec<CommitRecord> = [(&ws_id, 1), (&ws_id, 2), (&ws_id, 3), (&other_id, 3)]
+            .iter()
+            .enumerate()
+            .map(|(i, (ws, age))| {
+                CommitRecord::new(
+                    ws.to_string(),
+                    format!("sha{i}"),
+                    "Test Author".to_string(),
+                    None,
+                    format!("Commit {i}"),
+                    days_ago(*age),
                 )
-                .expect("query should succeed");
-            assert_eq!(exists, 1, "Table {table} should exist");
+            })
+            .collect();
+        db.insert_commits_batch(&commits).expect("commits");
+        db.insert_tags_batch(
+            &ws_id,
+            &[sample_tag("v0.1.0", "sha2"), sample_tag("v0.2.0", "sha0")],
+        )
+        .expect("tags");
+
+        for age in [1, 100] {
+            let mut run = TestRunRecord::new(ws_id.clone());
+            run.started_at = days_ago(age);
+            let run_id = db.insert_test_run(&run).expect("run");
+            db.insert_test_results_batch(&[
+                TestResultRecord::new(
+                    run_id.clone(),
+                    "core".to_string(),
+                    "test_a".to_string(),
+                    "passed".to_string(),
+                    None,
+                ),
+                TestResultRecord::new(
+                    run_id,
+                    "core".to_string(),
+                    "test_b".to_string(),
+                    "passed".to_string(),
+                    None,
+                ),
+            ])
+            .expect("results");
         }
+
+        let session_id = db
+            .insert_copilot_session(&CopilotSessionRecord::new(
+                ws_id.clone(),
+                "chat".to_string(),
+            ))
+            .expect("session");
+        let messages: Vec<CopilotMessageRecord> = (1..=3)
+            .map(|age| {
+                CopilotMessageRecord::new(
+                    session_id.clone(),
+                    "user".to_string(),
+                    format!("message {age}"),
+                    days_ago(age),
+                )
+            })
+            .collect();
+        db.insert_copilot_messages_batch(&messages)
+            .expect("messages");
+
+        // Without policies nothing is deleted
+        assert_eq!(
+            db.apply_retention_policy(&ws_id).expect("apply"),
+            RetentionStats::default()
+        );
+
+        db.set_retention_policy(
+            &ws_id,
+            IngestSource::Git,
+            RetentionPolicy::default().with_max_records(5),
+        )
+        .expect("set");
+        db.set_retention_policy(
+            &ws_id,
+            IngestSource::Git,
+            RetentionPolicy::default().with_max_records(2),
+        )
+        .expect("replace");
+        db.set_retention_policy(
+            &ws_id,
+            IngestSource::Tests,
+            RetentionPolicy::default().with_max_age_days(30),
+        )
+        .expect("set");
+        db.set_retention_policy(
+            &ws_id,
+            IngestSource::Copilot,
+            RetentionPolicy::default()
+                .with_max_age_days(30)
+                .with_max_records(1),
+        )
+        .expect("set");
+        assert_eq!(
+            db.get_retention_policy(&ws_id, IngestSource::Git)
+                .expect("get"),
+            Some(RetentionPolicy {
+                max_age_days: None,
+                max_records: Some(2),
+            })
+        );
+
+        let stats = db.apply_retention_policy(&ws_id).expect("apply");
+        assert_eq!(
+            stats,
+            RetentionStats {
+                commits_deleted: 1,
+                test_results_deleted: 2,
+                messages_deleted: 2,
+            }
+        );
+
+        let remaining: Vec<String> = db
+            .conn
+            .prepare("SELECT sha FROM commits ORDER BY sha")
+            .expect("prepare")
+            .query_map([], |row| row.get(0))
+            .expect("query")
+            .collect::<Result<_, _>>()
+            .expect("rows");
+        assert_eq!(remaining, vec!["sha0", "sha1", "sha3"]);
+        let tags: String = db
+            .conn
+            .query_row("SELECT group_concat(name) FROM tags", [], |row| row.get(0))
+            .expect("tags");
+        assert_eq!(tags, "v0.2.0");
+        assert_eq!(db.count("test_runs").expect("count"), 1);
+        assert_eq!(db.count("test_results").expect("count"), 2);
+        assert_eq!(db.count("copilot_messages").expect("count"), 1);
+
+        // Applying again finds nothing more to delete
+        assert_eq!(
+            db.apply_retention_policy(&ws_id).expect("apply"),
+            RetentionStats::default()
+        );
     }
 
     #[test]
-    fn test_database_count_empty() {
+    fn test_record_ingest_and_stale_workspaces() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        assert_eq!(db.count("workspaces").expect("count"), 0);
-        assert_eq!(db.count("commits").expect("count"), 0);
+        let fresh = db
+            .get_or_create_workspace("fresh", "/fresh")
+            .expect("create");
+        let old = db.get_or_create_workspace("old", "/old").expect("create");
+        db.get_or_create_workspace("never", "/never")
+            .expect("create");
+
+        db.record_ingest(&fresh, IngestSource::Copilot)
+            .expect("record");
+        let two_days_ago = (Utc::now() - chrono::Duration::days(2)).to_rfc3339();
+        db.connection()
+            .execute(
+                "UPDATE workspaces SET last_git_ingest_at = ?2 WHERE id = ?1",
+                params![old, two_days_ago],
+            )
+            .expect("backdate");
+
+        let workspaces = db.list_workspaces().expect("list");
+        let fresh_record = workspaces.iter().find(|w| w.id == fresh).expect("fresh");
+        assert!(fresh_record.last_copilot_ingest_at.is_some());
+        assert!(fresh_record.last_git_ingest_at.is_none());
+        assert_eq!(
+            fresh_record.last_ingested_at(),
+            fresh_record.last_copilot_ingest_at
+        );
+
+        let stale: Vec<String> = db
+            .get_stale_workspaces(24)
+            .expect("stale")
+            .into_iter()
+            .map(|w| w.name)
+            .collect();
+        assert_eq!(stale, vec!["never".to_string(), "old".to_string()]);
+
+        let stale = db.get_stale_workspaces(72).expect("stale");
+        assert_eq!(stale.len(), 1, "only the never-ingested workspace");
+    }
+
+    fn insert_test_commit(db: &Database, ws_id: &str, sha: &str, message: &str) {
+        let record = CommitRecord::new(
+            ws_id.to_string(),
+            sha.to_string(),
+            "Test Author".to_string(),
+            None,
+            message.to_string(),
+            Utc::now(),
+        );
+        db.insert_commit(&record).expect("insert commit");
+    }
+
+    fn workspace_shas(db: &Database, ws_id: &str) -> Vec<String> {
+        let mut stmt = db
+            .connection()
+            .prepare("SELECT sha FROM commits WHERE workspace_id = ?1 ORDER BY sha")
+            .expect("prepare");
+        stmt.query_map([ws_id], |row| row.get(0))
+            .expect("query")
+            .collect::<Result<Vec<String>, _>>()
+            .expect("collect")
     }
 
     #[test]
-    fn test_database_insert_workspace() {
-        let db = Database::in_memory().expect("should create db");
+    fn test_merge_workspace_unions_commits() {
+        let mut db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        db.connection()
-            .execute(
-                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
-                [
-                    "550e8400-e29b-41d4-a716-446655440000",
-                    "test-workspace",
-                    "/path/to/workspace",
-                    "2026-01-17T02:33:06Z",
-                    "2026-01-17T02:33:06Z",
-                ],
-            )
-            .expect("insert should succeed");
+        let source = db
+            .get_or_create_workspace("old-name", "/old/path")
+            .expect("source");
+        let target = db
+            .get_or_create_workspace("new-name", "/new/path")
+            .expect("target");
 
-        assert_eq!(db.count("workspaces").expect("count"), 1);
+        insert_test_commit(&db, &source, "aaa111", "Only in source");
+        insert_test_commit(&db, &source, "bbb222", "Shared (source copy)");
+        insert_test_commit(&db, &target, "bbb222", "Shared (target copy)");
+        insert_test_commit(&db, &target, "ccc333", "Only in target");
+
+        let run = TestRunRecord::new(source.clone());
+        db.insert_test_run(&run).expect("insert run");
+        let session = CopilotSessionRecord::new(source.clone(), "vscode-1".to_string());
+        db.insert_copilot_session(&session).expect("insert session");
+
+        let stats = db.merge_workspace(&source, &target).expect("merge");
+
+        assert_eq!(
+            stats,
+            MergeStats {
+                commits_moved: 1,
+                commits_skipped: 1,
+                runs_moved: 1,
+                sessions_moved: 1,
+            }
+        );
+        assert_eq!(
+            workspace_shas(&db, &target),
+            vec!["aaa111", "bbb222", "ccc333"]
+        );
+
+        // Target's copy of the shared commit wins
+        let shared = db.get_commit_by_sha(&target, "bbb222").expect("shared");
+        assert_eq!(shared.message, "Shared (target copy)");
+
+        // Source workspace is gone
+        let workspaces = db.list_workspaces().expect("list");
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0].id, target);
+        assert!(workspace_shas(&db, &source).is_empty());
     }
 
     #[test]
-    fn test_database_foreign_key_references() {
-        let db = Database::in_memory().expect("should create db");
-        // Enable foreign key enforcement
-        db.connection()
-            .execute("PRAGMA foreign_keys = ON", [])
-            .expect("pragma");
+    fn test_merge_workspace_drops_duplicate_sessions() {
+        let mut db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        // Insert a workspace first
-        db.connection()
-            .execute(
-                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
-                ["ws-1", "test", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
-            )
-            .expect("insert workspace");
+        let source = db.get_or_create_workspace("a", "/a").expect("source");
+        let target = db.get_or_create_workspace("b", "/b").expect("target");
 
-        // Insert a commit referencing the workspace
-        db.connection()
-            .execute(
-                "INSERT INTO commits (id, workspace_id, sha, author, message, timestamp, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
-                ["c-1", "ws-1", "abc123", "Test Author", "Test commit", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
-            )
-            .expect("insert commit");
+        let src_session = CopilotSessionRecord::new(source.clone(), "shared".to_string());
+        let src_session_id = db.insert_copilot_session(&src_session).expect("session");
+        let msg = CopilotMessageRecord::new(
+            src_session_id,
+            "user".to_string(),
+            "hello".to_string(),
+            Utc::now(),
+        );
+        db.insert_copilot_messages_batch(&[msg]).expect("messages");
+        db.insert_copilot_session(&CopilotSessionRecord::new(
+            target.clone(),
+            "shared".to_string(),
+        ))
+        .expect("session");
 
-        assert_eq!(db.count("commits").expect("count"), 1);
+        let stats = db.merge_workspace(&source, &target).expect("merge");
+
+        assert_eq!(stats.sessions_moved, 0);
+        assert_eq!(db.count("copilot_sessions").expect("count"), 1);
+        assert_eq!(db.count("copilot_messages").expect("count"), 0);
     }
 
     #[test]
-    fn test_database_json_column() {
-        let db = Database::in_memory().expect("should create db");
+    fn test_merge_workspace_keeps_source_events() {
+        let mut db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        // Insert workspace
-        db.connection()
-            .execute(
-                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
-                ["ws-1", "test", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
-            )
-            .expect("insert workspace");
+        let source = db.get_or_create_workspace("a", "/a").expect("source");
+        let target = db.get_or_create_workspace("b", "/b").expect("target");
 
-        // Insert commit with JSON data
-        let parents_json = r#"["parent1", "parent2"]"#;
-        let diff_json = r#"{"files": [{"path": "test.rs", "added": 10, "deleted": 5}]}"#;
+        let event_id = db
+            .insert_workspace_event(&source, "deploy", "Release 1.2", "", Utc::now())
+            .expect("event");
 
-        db.connection()
-            .execute(
-                "INSERT INTO commits (id, workspace_id, sha, author, message, timestamp, parents_json, diff_json, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                ["c-1", "ws-1", "abc123", "Author", "Message", "2026-01-17T00:00:00Z", parents_json, diff_json, "2026-01-17T00:00:00Z"],
-            )
-            .expect("insert commit");
+        db.merge_workspace(&source, &target).expect("merge");
 
-        // Query and verify JSON is stored correctly
-        let stored_json: String = db
+        let workspace_id: String = db
             .connection()
             .query_row(
-                "SELECT parents_json FROM commits WHERE id = ?",
-                ["c-1"],
+                "SELECT workspace_id FROM workspace_events WHERE id = ?1",
+                [&event_id],
                 |row| row.get(0),
             )
-            .expect("query");
+            .expect("event survives the merge");
+        assert_eq!(workspace_id, target);
+    }
 
-        assert_eq!(stored_json, parents_json);
+    #[test]
+    fn test_merge_workspace_not_found() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let target = db.get_or_create_workspace("b", "/b").expect("target");
+        let result = db.merge_workspace("missing", &target);
+
+        assert!(matches!(result, Err(DbError::NotFound { .. })));
+        assert_eq!(db.count("workspaces").expect("count"), 1);
     }
 
     #[test]
-    fn test_database_unique_constraint() {
-        let db = Database::in_memory().expect("should create db");
+    fn test_merge_workspace_into_itself_is_noop() {
+        let mut db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        // Insert workspace
-        db.connection()
-            .execute(
-                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
-                ["ws-1", "test", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
-            )
-            .expect("insert workspace");
+        let ws = db.get_or_create_workspace("a", "/a").expect("workspace");
+        insert_test_commit(&db, &ws, "aaa111", "Commit");
 
-        // Try to insert duplicate path
-        let result = db.connection().execute(
-            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
-            ["ws-2", "test2", "/test", "2026-01-17T00:00:00Z", "2026-01-17T00:00:00Z"],
-        );
+        let stats = db.merge_workspace(&ws, &ws).expect("merge");
 
-        assert!(result.is_err(), "Duplicate path should fail");
+        assert_eq!(stats, MergeStats::default());
+        assert_eq!(workspace_shas(&db, &ws), vec!["aaa111"]);
     }
 
     #[test]
-    fn test_database_indexes_created() {
+    fn test_update_workspace() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        let indexes = vec![
-            "idx_commits_workspace",
-            "idx_commits_timestamp",
-            "idx_commits_sha",
-            "idx_test_runs_workspace",
-            "idx_test_results_run",
-            "idx_copilot_sessions_workspace",
-            "idx_copilot_messages_session",
-        ];
+        let ws = db.get_or_create_workspace("old", "/old/path").expect("ws");
+        let before = db.list_workspaces().expect("list")[0].updated_at;
 
-        for index in indexes {
-            let exists: i32 = db
-                .connection()
-                .query_row(
-                    "SELECT COUNT(*) FROM sqlite_master WHERE type='index' AND name=?",
-                    [index],
-                    |row| row.get(0),
-                )
-                .expect("query should succeed");
-            assert_eq!(exists, 1, "Index {index} should exist");
+        db.update_workspace(&ws, "new", "/new/path")
+            .expect("update");
+
+        let workspaces = db.list_workspaces().expect("list");
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0].id, ws);
+        assert_eq!(workspaces[0].name, "new");
+        assert_eq!(workspaces[0].path, "/new/path");
+        assert!(workspaces[0].updated_at >= before);
+
+        // The new path resolves to the same workspace instead of a duplicate
+        let again = db.get_or_create_workspace("new", "/new/path").expect("ws");
+        assert_eq!(again, ws);
+        assert_eq!(db.get_workspace_id("/old/path").expect("lookup"), None);
+        assert_eq!(db.count("workspaces").expect("count"), 1);
+    }
+
+    fn sample_tag(name: &str, tagged_sha: &str) -> TagRecord {
+        TagRecord {
+            name: name.to_string(),
+            sha: tagged_sha.to_string(),
+            tagged_sha: tagged_sha.to_string(),
+            tagger: None,
+            message: None,
+            is_annotated: false,
+            timestamp: Utc::now(),
         }
     }
 
     #[test]
-    fn test_database_schema_version() {
-        let db = Database::in_memory().expect("should create db");
-
-        // Before initialization, version should be 0
-        assert_eq!(db.schema_version().expect("version"), 0);
-
+    fn test_insert_tags_batch() {
+        let mut db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
+        db.connection()
+            .execute_batch("PRAGMA foreign_keys = ON")
+            .expect("enable foreign keys");
+        let ws = db.get_or_create_workspace("a", "/a").expect("ws");
+        insert_test_commit(&db, &ws, "aaa111", "First");
+        insert_test_commit(&db, &ws, "bbb222", "Second");
+
+        let tags = [
+            sample_tag("v1.0.0", "aaa111"),
+            sample_tag("v2.0.0", "not-ingested"),
+        ];
+        assert_eq!(db.insert_tags_batch(&ws, &tags).expect("insert"), 1);
 
-        // After initialization, version should match CURRENT_VERSION
-        assert_eq!(
-            db.schema_version().expect("version"),
-            crate::migrations::CURRENT_VERSION
-        );
+        // A moved tag is updated rather than duplicated
+        let moved = [sample_tag("v1.0.0", "bbb222")];
+        assert_eq!(db.insert_tags_batch(&ws, &moved).expect("update"), 1);
+        assert_eq!(db.count("tags").expect("count"), 1);
+        let tagged: String = db
+            .connection()
+            .query_row("SELECT tagged_sha FROM tags", [], |row| row.get(0))
+            .expect("tag");
+        assert_eq!(tagged, "bbb222");
+
+        // Tags deleted from the repository are removed, in this workspace only
+        let other = db.get_or_create_workspace("b", "/b").expect("ws");
+        insert_test_commit(&db, &other, "aaa111", "First");
+        db.insert_tags_batch(&other, &[sample_tag("v1.0.0", "aaa111")])
+            .expect("other");
+        assert_eq!(db.insert_tags_batch(&ws, &[]).expect("delete"), 0);
+        let remaining: String = db
+            .connection()
+            .query_row("SELECT workspace_id FROM tags", [], |row| row.get(0))
+            .expect("tag");
+        assert_eq!(remaining, other);
     }
 
     #[test]
-    fn test_database_fts_tables_created() {
-        let db = Database::in_memory().expect("should create db");
+    fn test_insert_test_inventory() {
+        let mut db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
+        let ws = db.get_or_create_workspace("a", "/a").expect("ws");
+
+        let list = |tests: &[&str]| {
+            let cases: Vec<String> = tests
+                .iter()
+                .map(|t| format!(r#""{t}": {{"kind": "test", "ignored": false}}"#))
+                .collect();
+            hindsight_tests::parse_list_output(&format!(
+                r#"{{"test-count": {}, "rust-suites": {{"my-crate": {{
+                    "package-name": "my-crate", "binary-id": "my-crate",
+                    "binary-name": "my_crate", "kind": "lib",
+                    "testcases": {{{}}}}}}}}}"#,
+                tests.len(),
+                cases.join(",")
+            ))
+            .expect("list")
+        };
+
+        let first = list(&["tests::kept", "tests::removed"]);
+        assert_eq!(db.insert_test_inventory(&ws, &first).expect("insert"), 2);
+        db.connection()
+            .execute(
+                "UPDATE test_inventory SET first_seen_at = '2026-01-01T00:00:00+00:00',
+                     last_seen_at = '2026-01-01T00:00:00+00:00'",
+                [],
+            )
+            .expect("age inventory");
 
-        let fts_tables = vec!["commits_fts", "copilot_messages_fts"];
+        let second = list(&["tests::kept", "tests::added"]);
+        assert_eq!(db.insert_test_inventory(&ws, &second).expect("insert"), 2);
+        assert_eq!(db.count("test_inventory").expect("count"), 3);
 
-        for table in fts_tables {
-            let exists: i32 = db
-                .connection()
-                .query_row(
-                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?",
-                    [table],
-                    |row| row.get(0),
-                )
-                .expect("query should succeed");
-            assert_eq!(exists, 1, "FTS table {table} should exist");
-        }
+        let stale: Vec<String> = db
+            .connection()
+            .prepare(
+                "SELECT test_name FROM test_inventory
+                 WHERE last_seen_at < (SELECT MAX(last_seen_at) FROM test_inventory)",
+            )
+            .expect("prepare")
+            .query_map([], |row| row.get(0))
+            .expect("query")
+            .collect::<Result<_, _>>()
+            .expect("rows");
+        assert_eq!(stale, vec!["tests::removed".to_string()]);
+
+        let first_seen: String = db
+            .connection()
+            .query_row(
+                "SELECT first_seen_at FROM test_inventory WHERE test_name = 'tests::kept'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("kept");
+        assert_eq!(first_seen, "2026-01-01T00:00:00+00:00");
     }
 
     #[test]
-    fn test_database_views_created() {
+    fn test_update_workspace_not_found() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        let views = vec!["timeline", "failing_tests", "recent_activity"];
+        let result = db.update_workspace("missing", "name", "/path");
+        assert!(matches!(result, Err(DbError::NotFound { .. })));
+    }
 
-        for view in views {
-            let exists: i32 = db
-                .connection()
-                .query_row(
-                    "SELECT COUNT(*) FROM sqlite_master WHERE type='view' AND name=?",
-                    [view],
-                    |row| row.get(0),
-                )
-                .expect("query should succeed");
-            assert_eq!(exists, 1, "View {view} should exist");
+    #[test]
+    fn test_delete_workspace_removes_children() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        db.connection()
+            .execute_batch("PRAGMA foreign_keys = ON")
+            .expect("enable foreign keys");
+
+        let doomed = db.get_or_create_workspace("a", "/a").expect("ws");
+        let kept = db.get_or_create_workspace("b", "/b").expect("ws");
+
+        for ws in [&doomed, &kept] {
+            insert_test_commit(&db, ws, "aaa111", "Commit");
+            let commit = db.get_commit_by_sha(ws, "aaa111").expect("commit");
+            db.insert_note(&NoteRecord::new(
+                commit.id,
+                "review".to_string(),
+                "LGTM".to_string(),
+            ))
+            .expect("note");
+            db.insert_tags_batch(ws, &[sample_tag("v1.0.0", "aaa111")])
+                .expect("tags");
+
+            let run_id = db
+                .insert_test_run(&TestRunRecord::new(ws.to_string()))
+                .expect("run");
+            db.insert_test_results_batch(&[TestResultRecord::new(
+                run_id.clone(),
+                "suite".to_string(),
+                "test_a".to_string(),
+                "failed".to_string(),
+                Some(5),
+            )])
+            .expect("results");
+            db.insert_ci_annotations(
+                &run_id,
+                &[CiAnnotation {
+                    level: hindsight_tests::AnnotationLevel::Error,
+                    file: None,
+                    line: None,
+                    message: "boom".to_string(),
+                }],
+            )
+            .expect("annotations");
+
+            let session_id = db
+                .insert_copilot_session(&CopilotSessionRecord::new(
+                    ws.to_string(),
+                    "vscode-1".to_string(),
+                ))
+                .expect("session");
+            db.insert_copilot_messages_batch(&[CopilotMessageRecord::new(
+                session_id,
+                "user".to_string(),
+                "hello".to_string(),
+                Utc::now(),
+            )])
+            .expect("messages");
+
+            db.insert_workspace_event(ws, "deploy", "v1", "", Utc::now())
+                .expect("event");
         }
-    }
+        assert_eq!(db.count("workspaces").expect("count"), 2);
 
-    // ========================================================================
-    // Record Type Tests
-    // ========================================================================
+        db.delete_workspace(&doomed).expect("delete");
 
-    #[test]
-    fn test_workspace_record_new() {
-        let record = WorkspaceRecord::new("test".to_string(), "/path".to_string());
-        assert!(!record.id.is_empty());
-        assert_eq!(record.name, "test");
-        assert_eq!(record.path, "/path");
-    }
+        assert_eq!(db.count("workspaces").expect("count"), 1);
+        for table in [
+            "commits",
+            "notes",
+            "tags",
+            "test_runs",
+            "test_results",
+            "ci_annotations",
+            "copilot_sessions",
+            "copilot_messages",
+            "workspace_events",
+        ] {
+            assert_eq!(db.count(table).expect("count"), 1, "{table}");
+        }
+        assert_eq!(workspace_shas(&db, &kept), vec!["aaa111"]);
+        assert!(workspace_shas(&db, &doomed).is_empty());
 
-    #[test]
-    fn test_commit_record_new() {
-        let record = CommitRecord::new(
-            "ws-1".to_string(),
-            "abc123".to_string(),
-            "Author".to_string(),
-            Some("author@example.com".to_string()),
-            "Test message".to_string(),
-            Utc::now(),
-        );
-        assert!(!record.id.is_empty());
-        assert_eq!(record.workspace_id, "ws-1");
-        assert_eq!(record.sha, "abc123");
-        assert!(record.parents_json.is_none());
-        assert!(record.diff_json.is_none());
+        let violations: i64 = db
+            .connection()
+            .query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| {
+                row.get(0)
+            })
+            .expect("foreign key check");
+        assert_eq!(violations, 0);
     }
 
     #[test]
-    fn test_commit_record_with_parents() {
-        let record = CommitRecord::new(
-            "ws-1".to_string(),
-            "abc123".to_string(),
-            "Author".to_string(),
-            Some("author@example.com".to_string()),
-            "Test".to_string(),
-            Utc::now(),
-        )
-        .with_parents(vec!["parent1".to_string(), "parent2".to_string()]);
+    fn test_delete_workspace_vacuums_large_deletions() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+        let ws_id = insert_bulk_commits(&mut db);
 
-        assert!(record.parents_json.is_some());
-        let json = record.parents_json.unwrap();
-        assert!(json.contains("parent1"));
-        assert!(json.contains("parent2"));
+        db.delete_workspace(&ws_id).expect("delete");
+
+        assert!(db.freelist_ratio().expect("ratio") <= AUTO_VACUUM_FREELIST_RATIO);
+        assert_eq!(db.count("commits").expect("count"), 0);
     }
 
     #[test]
-    fn test_test_run_record_new() {
-        let record = TestRunRecord::new("ws-1".to_string());
-        assert!(!record.id.is_empty());
-        assert_eq!(record.workspace_id, "ws-1");
-        assert!(record.commit_sha.is_none());
-        assert!(record.finished_at.is_none());
-        assert_eq!(record.passed_count, 0);
+    fn test_delete_workspace_not_found() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws = db.get_or_create_workspace("a", "/a").expect("ws");
+        let result = db.delete_workspace("missing");
+
+        assert!(matches!(result, Err(DbError::NotFound { .. })));
+        assert_eq!(db.list_workspaces().expect("list")[0].id, ws);
     }
 
     #[test]
-    fn test_test_run_record_finished() {
-        let record = TestRunRecord::new("ws-1".to_string()).finished(10, 2, 1);
-        assert!(record.finished_at.is_some());
-        assert_eq!(record.passed_count, 10);
-        assert_eq!(record.failed_count, 2);
-        assert_eq!(record.ignored_count, 1);
+    fn test_format_relative_buckets() {
+        let now = Utc::now();
+        let ago = |secs: i64| format_relative(now - chrono::Duration::seconds(secs), now);
+
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(-30), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(45 * 60), "45 minutes ago");
+        assert_eq!(ago(3_600), "1 hour ago");
+        assert_eq!(ago(23 * 3_600), "23 hours ago");
+        assert_eq!(ago(86_400), "1 day ago");
+        assert_eq!(ago(3 * 86_400), "3 days ago");
+        assert_eq!(ago(30 * 86_400), "1 month ago");
+        assert_eq!(ago(200 * 86_400), "6 months ago");
+        assert_eq!(ago(365 * 86_400), "1 year ago");
+        assert_eq!(ago(3 * 365 * 86_400), "3 years ago");
     }
 
     #[test]
-    fn test_copilot_session_record_new() {
-        let record = CopilotSessionRecord::new("ws-1".to_string(), "vscode-123".to_string());
-        assert!(!record.id.is_empty());
-        assert_eq!(record.workspace_id, "ws-1");
-        assert_eq!(record.vscode_session_id, "vscode-123");
-    }
+    fn test_record_relative_times() {
+        let mut workspace = WorkspaceRecord::new("a".to_string(), "/a".to_string());
+        workspace.created_at = Utc::now() - chrono::Duration::days(3);
+        assert_eq!(workspace.created_relative(), "3 days ago");
+        assert_eq!(workspace.updated_relative(), "just now");
 
-    #[test]
-    fn test_copilot_message_record_new() {
-        let record = CopilotMessageRecord::new(
-            "session-1".to_string(),
-            "user".to_string(),
-            "Hello".to_string(),
-            Utc::now(),
+        workspace.last_git_ingest_at = Some(Utc::now() - chrono::Duration::hours(2));
+        assert_eq!(
+            workspace.ingested_relative(IngestSource::Git),
+            "2 hours ago"
         );
-        assert!(!record.id.is_empty());
-        assert_eq!(record.session_id, "session-1");
-        assert_eq!(record.role, "user");
-        assert_eq!(record.content, "Hello");
-        assert!(record.request_id.is_none());
+        assert_eq!(workspace.ingested_relative(IngestSource::Tests), "never");
+
+        let commit = CommitRecord::new(
+            "ws".to_string(),
+            "aaa111".to_string(),
+            "Test Author".to_string(),
+            None,
+            "Commit".to_string(),
+            Utc::now() - chrono::Duration::hours(2),
+        );
+        assert_eq!(commit.timestamp_relative(), "2 hours ago");
     }
 
-    // ========================================================================
-    // Workspace Insertion Tests
-    // ========================================================================
+    fn ts(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s)
+            .expect("valid timestamp")
+            .with_timezone(&Utc)
+    }
 
     #[test]
-    fn test_insert_workspace_via_method() {
+    fn test_timeline_bounds_empty_workspace() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        let record =
-            WorkspaceRecord::new("my-project".to_string(), "/home/user/project".to_string());
-        db.insert_workspace(&record).expect("insert should succeed");
+        let ws = db.get_or_create_workspace("a", "/a").expect("workspace");
 
-        assert_eq!(db.count("workspaces").expect("count"), 1);
+        assert!(db.is_workspace_empty(&ws).expect("empty"));
+        assert_eq!(db.get_workspace_timeline_bounds(&ws).expect("bounds"), None);
     }
 
     #[test]
-    fn test_get_or_create_workspace_creates() {
+    fn test_timeline_bounds_commits_only() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        let id = db
-            .get_or_create_workspace("new-project", "/new/path")
-            .expect("should create");
-        assert!(!id.is_empty());
-        assert_eq!(db.count("workspaces").expect("count"), 1);
+        let ws = db.get_or_create_workspace("a", "/a").expect("workspace");
+        for (sha, timestamp) in [
+            ("aaa111", "2026-01-10T09:00:00Z"),
+            ("bbb222", "2026-01-05T09:00:00Z"),
+            ("ccc333", "2026-01-20T09:00:00Z"),
+        ] {
+            let record = CommitRecord::new(
+                ws.clone(),
+                sha.to_string(),
+                "Test Author".to_string(),
+                None,
+                "Commit".to_string(),
+                ts(timestamp),
+            );
+            db.insert_commit(&record).expect("insert commit");
+        }
+
+        assert!(!db.is_workspace_empty(&ws).expect("empty"));
+        assert_eq!(
+            db.get_workspace_timeline_bounds(&ws).expect("bounds"),
+            Some(TimelineBounds {
+                earliest: ts("2026-01-05T09:00:00Z"),
+                latest: ts("2026-01-20T09:00:00Z"),
+                total_events: 3,
+            })
+        );
     }
 
     #[test]
-    fn test_get_or_create_workspace_returns_existing() {
+    fn test_timeline_bounds_sessions_only() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        let id1 = db
-            .get_or_create_workspace("project", "/path")
-            .expect("first call");
-        let id2 = db
-            .get_or_create_workspace("project", "/path")
-            .expect("second call");
+        let ws = db.get_or_create_workspace("a", "/a").expect("workspace");
+        let mut session = CopilotSessionRecord::new(ws.clone(), "s1".to_string());
+        session.created_at = ts("2026-02-01T10:00:00Z");
+        session.updated_at = ts("2026-02-03T18:30:00Z");
+        db.insert_copilot_session(&session).expect("insert session");
 
-        assert_eq!(id1, id2);
-        assert_eq!(db.count("workspaces").expect("count"), 1);
+        assert!(!db.is_workspace_empty(&ws).expect("empty"));
+        assert_eq!(
+            db.get_workspace_timeline_bounds(&ws).expect("bounds"),
+            Some(TimelineBounds {
+                earliest: ts("2026-02-01T10:00:00Z"),
+                latest: ts("2026-02-03T18:30:00Z"),
+                total_events: 1,
+            })
+        );
     }
 
     #[test]
-    fn test_list_workspaces() {
+    fn test_timeline_bounds_mixed_sources() {
         let db = Database::in_memory().expect("should create db");
         db.initialize().expect("should initialize");
 
-        db.get_or_create_workspace("alpha", "/alpha")
-            .expect("create");
-        db.get_or_create_workspace("beta", "/beta").expect("create");
+        let ws = db.get_or_create_workspace("a", "/a").expect("workspace");
+        let other = db.get_or_create_workspace("b", "/b").expect("workspace");
 
-        let workspaces = db.list_workspaces().expect("list");
-        assert_eq!(workspaces.len(), 2);
-        assert_eq!(workspaces[0].name, "alpha"); // Sorted by name
-        assert_eq!(workspaces[1].name, "beta");
+        let commit = CommitRecord::new(
+            ws.clone(),
+            "aaa111".to_string(),
+            "Test Author".to_string(),
+            None,
+            "Commit".to_string(),
+            ts("2026-03-05T12:00:00Z"),
+        );
+        db.insert_commit(&commit).expect("insert commit");
+
+        let mut run = TestRunRecord::new(ws.clone());
+        run.started_at = ts("2026-03-01T08:00:00Z");
+        db.insert_test_run(&run).expect("insert run");
+
+        let mut session = CopilotSessionRecord::new(ws.clone(), "s1".to_string());
+        session.created_at = ts("2026-03-02T10:00:00Z");
+        session.updated_at = ts("2026-03-09T16:00:00Z");
+        db.insert_copilot_session(&session).expect("insert session");
+
+        // Events in other workspaces are ignored
+        let mut other_run = TestRunRecord::new(other.clone());
+        other_run.started_at = ts("2025-12-01T00:00:00Z");
+        db.insert_test_run(&other_run).expect("insert run");
+
+        assert_eq!(
+            db.get_workspace_timeline_bounds(&ws).expect("bounds"),
+            Some(TimelineBounds {
+                earliest: ts("2026-03-01T08:00:00Z"),
+                latest: ts("2026-03-09T16:00:00Z"),
+                total_events: 3,
+            })
+        );
     }
 
     // ========================================================================
@@ -1262,11 +4781,121 @@ mod tests {
             })
             .collect();
 
-        let count = db.insert_commits_batch(&records).expect("batch insert");
-        assert_eq!(count, 5);
+        let stats = db.insert_commits_batch(&records).expect("batch insert");
+        assert_eq!(stats.inserted, 5);
+        assert_eq!(stats.skipped, 0);
         assert_eq!(db.count("commits").expect("count"), 5);
     }
 
+    #[test]
+    fn test_insert_commits_batch_twice_counts_skipped() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let records: Vec<CommitRecord> = (0..3)
+            .map(|i| {
+                CommitRecord::new(
+                    ws_id.clone(),
+                    format!("sha{i}"),
+                    "Author".to_string(),
+                    None,
+                    format!("Commit {i}"),
+                    Utc::now(),
+                )
+            })
+            .collect();
+
+        db.insert_commits_batch(&records).expect("first insert");
+        let stats = db.insert_commits_batch(&records).expect("second insert");
+
+        assert_eq!(
+            stats,
+            BatchInsertStats {
+                inserted: 0,
+                skipped: 3
+            }
+        );
+        assert_eq!(db.count("commits").expect("count"), 3);
+    }
+
+    #[test]
+    fn test_stream_commits_inserts_all_in_bounded_chunks() {
+        use std::cell::Cell;
+
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+
+        // Records are generated lazily; count how many exist at each chunk
+        let produced = Cell::new(0usize);
+        let records = (0..2_345).map(|i| {
+            produced.set(produced.get() + 1);
+            CommitRecord::new(
+                String::new(),
+                format!("sha{i}"),
+                "Author".to_string(),
+                None,
+                format!("Commit {i}"),
+                Utc::now(),
+            )
+        });
+
+        let mut consumed = 0;
+        let mut max_buffered = 0;
+        let count = db
+            .stream_commits(&ws_id, records, 100, |tx, chunk| {
+                assert!(chunk.len() <= 100);
+                consumed += chunk.len();
+                max_buffered = max_buffered.max(produced.get() - consumed + chunk.len());
+                Database::insert_commits_in_tx(tx, chunk)
+            })
+            .expect("stream");
+
+        assert_eq!(count, 2_345);
+        assert_eq!(db.count("commits").expect("count"), 2_345);
+        assert_eq!(max_buffered, 100, "only one chunk should be held at a time");
+        assert_eq!(workspace_shas(&db, &ws_id).len(), 2_345);
+    }
+
+    #[test]
+    fn test_stream_commits_error_keeps_committed_chunks() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let records = (0..25).map(|i| {
+            CommitRecord::new(
+                String::new(),
+                format!("sha{i}"),
+                "Author".to_string(),
+                None,
+                format!("Commit {i}"),
+                Utc::now(),
+            )
+        });
+
+        // One-record chunks: the first ten are committed, the failing one is not
+        let mut calls = 0;
+        let result = db.stream_commits(&ws_id, records, 1, |tx, chunk| {
+            calls += 1;
+            if calls > 12 {
+                return Err(DbError::NotInitialized);
+            }
+            Database::insert_commits_in_tx(tx, chunk)
+        });
+
+        assert!(matches!(result, Err(DbError::NotInitialized)));
+        assert_eq!(db.count("commits").expect("count"), 10);
+    }
+
     #[test]
     fn test_get_commit_by_sha() {
         let db = Database::in_memory().expect("should create db");
@@ -1291,6 +4920,63 @@ mod tests {
         assert_eq!(found.message, "Find this commit");
     }
 
+    #[test]
+    fn test_get_commit_by_sha_malformed_timestamp() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        insert_test_commit(&db, &ws_id, "badtime1", "Corrupted");
+        db.conn
+            .execute(
+                "UPDATE commits SET timestamp = 'not a timestamp' WHERE sha = 'badtime1'",
+                [],
+            )
+            .expect("corrupt timestamp");
+
+        let result = db.get_commit_by_sha(&ws_id, "badtime1");
+        assert!(matches!(result, Err(DbError::Sqlite(_))));
+        assert!(matches!(
+            try_parse_timestamp(""),
+            Err(DbError::InvalidTimestamp { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_commit_count_by_sha_prefix() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let full = "abcd1234ef567890abcd1234ef567890abcd1234";
+        insert_test_commit(&db, &ws_id, full, "First");
+        insert_test_commit(&db, &ws_id, "abcd1299", "Second");
+        insert_test_commit(&db, &ws_id, "ffff0000", "Third");
+
+        let count = |prefix| db.get_commit_count_by_sha_prefix(prefix).expect("count");
+        assert_eq!(count("abcd"), 2);
+        assert_eq!(count("abcd12"), 2);
+        assert_eq!(count("abcd123"), 1);
+        assert_eq!(count("ffff"), 1);
+        assert_eq!(count("0000"), 0);
+        assert_eq!(count(full), 1);
+
+        assert!(matches!(
+            db.get_commit_count_by_sha_prefix("abc"),
+            Err(DbError::InvalidParameter(_))
+        ));
+        for wildcard in ["%%%%", "abc_", "ab%d"] {
+            assert!(matches!(
+                db.get_commit_count_by_sha_prefix(wildcard),
+                Err(DbError::InvalidParameter(_))
+            ));
+        }
+    }
+
     #[test]
     fn test_get_commit_by_sha_not_found() {
         let db = Database::in_memory().expect("should create db");
@@ -1304,6 +4990,35 @@ mod tests {
         assert!(matches!(result, Err(DbError::NotFound { .. })));
     }
 
+    #[test]
+    fn test_insert_note_replaces_existing() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        insert_test_commit(&db, &ws_id, "noted123", "Noted commit");
+        let commit = db.get_commit_by_sha(&ws_id, "noted123").expect("commit");
+
+        let ns = "refs/notes/ci".to_string();
+        db.insert_note(&NoteRecord::new(
+            commit.id.clone(),
+            ns.clone(),
+            "pending".to_string(),
+        ))
+        .expect("insert note");
+        db.insert_note(&NoteRecord::new(commit.id, ns, "passed".to_string()))
+            .expect("update note");
+
+        assert_eq!(db.count("notes").expect("count"), 1);
+        let content: String = db
+            .connection()
+            .query_row("SELECT content FROM notes", [], |row| row.get(0))
+            .expect("query");
+        assert_eq!(content, "passed");
+    }
+
     // ========================================================================
     // Test Result Insertion Tests
     // ========================================================================
@@ -1323,6 +5038,80 @@ mod tests {
         assert_eq!(db.count("test_runs").expect("count"), 1);
     }
 
+    #[test]
+    fn test_list_test_runs_filters() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_a = db.get_or_create_workspace("a", "/a").expect("workspace");
+        let ws_b = db.get_or_create_workspace("b", "/b").expect("workspace");
+        let runs = [
+            (&ws_a, "aaaa1111", "2026-01-01T00:00:00Z", (5, 0, 0)),
+            (&ws_a, "bbbb2222", "2026-01-02T00:00:00Z", (4, 1, 0)),
+            (&ws_b, "aaaa1111", "2026-01-03T00:00:00Z", (3, 0, 2)),
+        ];
+        for (ws, sha, started_at, (passed, failed, ignored)) in runs {
+            let mut record = TestRunRecord::new(ws.clone())
+                .with_commit(sha)
+                .finished(passed, failed, ignored);
+            record.started_at = try_parse_timestamp(started_at).expect("valid timestamp");
+            db.insert_test_run(&record).expect("insert run");
+        }
+
+        let all = db.list_test_runs(None, None, 10).expect("list");
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].workspace_id, ws_b);
+        assert_eq!(
+            (
+                all[0].passed_count,
+                all[0].failed_count,
+                all[0].ignored_count
+            ),
+            (3, 0, 2)
+        );
+
+        let by_workspace = db.list_test_runs(Some(&ws_a), None, 10).expect("list");
+        assert_eq!(by_workspace.len(), 2);
+        assert_eq!(by_workspace[0].commit_sha.as_deref(), Some("bbbb2222"));
+
+        let by_commit = db.list_test_runs(None, Some("aaaa"), 10).expect("list");
+        assert_eq!(by_commit.len(), 2);
+        assert!(
+            by_commit
+                .iter()
+                .all(|r| r.commit_sha.as_deref() == Some("aaaa1111"))
+        );
+
+        let both = db
+            .list_test_runs(Some(&ws_b), Some("aaaa1111"), 10)
+            .expect("list");
+        assert_eq!(both.len(), 1);
+
+        assert_eq!(db.list_test_runs(None, None, 1).expect("list").len(), 1);
+    }
+
+    #[test]
+    fn test_get_test_run_by_id() {
+        let db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let record = TestRunRecord::new(ws_id).finished(10, 2, 1);
+        let run_id = db.insert_test_run(&record).expect("insert");
+
+        let fetched = db.get_test_run_by_id(&run_id).expect("get");
+        assert_eq!(fetched.failed_count, 2);
+        assert!(fetched.finished_at.is_some());
+        assert!(fetched.commit_sha.is_none());
+
+        assert!(matches!(
+            db.get_test_run_by_id("missing"),
+            Err(DbError::NotFound { .. })
+        ));
+    }
+
     #[test]
     fn test_insert_test_results_batch() {
         let mut db = Database::in_memory().expect("should create db");
@@ -1393,6 +5182,60 @@ mod tests {
         assert_eq!(linked_sha, "commitsha");
     }
 
+    #[test]
+    fn test_failing_test_counts_by_workspace() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("proj", "/proj")
+            .expect("workspace");
+        db.get_or_create_workspace("clean", "/clean")
+            .expect("workspace");
+
+        // An older run whose failures are no longer current
+        let old_run = TestRunRecord {
+            started_at: Utc::now() - chrono::Duration::hours(1),
+            ..TestRunRecord::new(ws_id.clone())
+        };
+        let old_run_id = db.insert_test_run(&old_run).expect("insert run");
+        let old_results: Vec<TestResultRecord> = (0..5)
+            .map(|i| {
+                TestResultRecord::new(
+                    old_run_id.clone(),
+                    "suite".to_string(),
+                    format!("test_{i}"),
+                    "failed".to_string(),
+                    None,
+                )
+            })
+            .collect();
+        db.insert_test_results_batch(&old_results)
+            .expect("batch insert");
+
+        let run_id = db
+            .insert_test_run(&TestRunRecord::new(ws_id.clone()))
+            .expect("insert run");
+        let results: Vec<TestResultRecord> = (0..10)
+            .map(|i| {
+                let outcome = if i < 3 { "failed" } else { "passed" };
+                TestResultRecord::new(
+                    run_id.clone(),
+                    "suite".to_string(),
+                    format!("test_{i}"),
+                    outcome.to_string(),
+                    Some(10),
+                )
+            })
+            .collect();
+        db.insert_test_results_batch(&results)
+            .expect("batch insert");
+
+        let counts = db.get_failing_test_counts_by_workspace().expect("counts");
+        assert_eq!(counts, vec![(ws_id, "proj".to_string(), 3)]);
+        assert_eq!(db.total_failing_tests_count().expect("total"), 3);
+    }
+
     // ========================================================================
     // Copilot Insertion Tests
     // ========================================================================
@@ -1473,6 +5316,125 @@ mod tests {
         assert_eq!(db.count("copilot_messages").expect("count"), 3);
     }
 
+    #[test]
+    fn test_list_copilot_sessions_with_counts() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let other_ws = db
+            .get_or_create_workspace("other", "/other")
+            .expect("workspace");
+
+        let base = Utc::now() - chrono::Duration::hours(3);
+        for (offset, name, messages) in [(0, "oldest", 2), (1, "middle", 0), (2, "newest", 5)] {
+            let mut record = CopilotSessionRecord::new(ws_id.clone(), name.to_string());
+            record.created_at = base + chrono::Duration::hours(offset);
+            let session_id = db.insert_copilot_session(&record).expect("insert session");
+            let batch: Vec<CopilotMessageRecord> = (0..messages)
+                .map(|i| {
+                    CopilotMessageRecord::new(
+                        session_id.clone(),
+                        "user".to_string(),
+                        format!("message {i}"),
+                        Utc::now(),
+                    )
+                })
+                .collect();
+            db.insert_copilot_messages_batch(&batch)
+                .expect("insert messages");
+        }
+        db.insert_copilot_session(&CopilotSessionRecord::new(
+            other_ws,
+            "elsewhere".to_string(),
+        ))
+        .expect("insert session");
+
+        let listed = db
+            .list_copilot_sessions_with_counts(Some(&ws_id), 10)
+            .expect("list");
+        let summary: Vec<(&str, u64)> = listed
+            .iter()
+            .map(|(s, count)| (s.vscode_session_id.as_str(), *count))
+            .collect();
+        assert_eq!(summary, vec![("newest", 5), ("middle", 0), ("oldest", 2)]);
+
+        let plain = db.list_copilot_sessions(None, 2).expect("list");
+        assert_eq!(plain.len(), 2);
+        assert_eq!(plain[0].vscode_session_id, "elsewhere");
+        assert_eq!(plain[1].vscode_session_id, "newest");
+    }
+
+    #[test]
+    fn test_get_copilot_session_with_messages() {
+        let mut db = Database::in_memory().expect("should create db");
+        db.initialize().expect("should initialize");
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let base = Utc::now() - chrono::Duration::hours(3);
+
+        let mut older = CopilotSessionRecord::new(ws_id.clone(), "older".to_string());
+        older.created_at = base;
+        db.insert_copilot_session(&older).expect("insert session");
+
+        let mut newer = CopilotSessionRecord::new(ws_id.clone(), "newer".to_string());
+        newer.created_at = base + chrono::Duration::hours(1);
+        let session_id = db.insert_copilot_session(&newer).expect("insert session");
+
+        // Inserted out of order to check the ordering
+        let message = |content: &str, minutes: i64| {
+            CopilotMessageRecord::new(
+                session_id.clone(),
+                "user".to_string(),
+                content.to_string(),
+                base + chrono::Duration::minutes(minutes),
+            )
+        };
+        db.insert_copilot_messages_batch(&[
+            message("third", 30),
+            message("first", 10),
+            message("second", 20),
+        ])
+        .expect("insert messages");
+
+        let (session, messages) = db
+            .get_copilot_session_with_messages(&session_id)
+            .expect("query")
+            .expect("session exists");
+        assert_eq!(session.vscode_session_id, "newer");
+        let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["first", "second", "third"]);
+
+        // The VS Code session ID also works
+        let (by_vscode_id, _) = db
+            .get_copilot_session_with_messages("newer")
+            .expect("query")
+            .expect("session exists");
+        assert_eq!(by_vscode_id.id, session_id);
+
+        let (latest, latest_messages) = db
+            .get_latest_copilot_session_with_messages(&ws_id)
+            .expect("query")
+            .expect("workspace has sessions");
+        assert_eq!(latest.id, session_id);
+        assert_eq!(latest_messages.len(), 3);
+
+        assert!(
+            db.get_copilot_session_with_messages("missing")
+                .expect("query")
+                .is_none()
+        );
+        assert!(
+            db.get_latest_copilot_session_with_messages("no-such-workspace")
+                .expect("query")
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_get_session_message_count() {
         let mut db = Database::in_memory().expect("should create db");
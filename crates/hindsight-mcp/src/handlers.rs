@@ -6,16 +6,22 @@
 //! This module implements the handlers for each MCP tool, bridging
 //! MCP requests to database queries and returning formatted responses.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use thiserror::Error;
 
-use crate::db::Database;
-use crate::ingest::{IngestError, IngestOptions, IngestStats, Ingestor};
+use crate::db::{Database, DbError, MIN_SHA_PREFIX_LEN, WorkspaceCounts, WorkspaceRecord};
+use crate::ingest::{IngestError, IngestOptions, IngestStats, Ingestor, ProgressCallback};
 use crate::queries::{
-    self, ActivitySummary, CommitWithTests, FailingTest, QueryError, SearchResult, TimelineEvent,
+    self, ActivitySummary, CommitDiff, CommitWithTests, ContributorStat, DailyCommitVolume,
+    FailingTest, FailureStreak, FlakyTestStat, GitTag, HeatmapCell, ParallelGroup, QAPair,
+    QueryError, Regression, SearchResult, SessionDetail, TestDurationStat, TestRegression,
+    TimelineEvent, TimelineFilter, TokenUsageStats, TopicTrend, VariableKindStats,
+    WorkspaceActivity,
 };
 
 // ============================================================================
@@ -33,6 +39,10 @@ pub enum HandlerError {
     #[error("Data ingestion failed: {0}")]
     Ingest(#[from] IngestError),
 
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] DbError),
+
     /// Invalid input - missing required field
     #[error("Invalid input: {0}. Check the tool's required parameters.")]
     InvalidInput(String),
@@ -48,6 +58,93 @@ pub enum HandlerError {
     /// Workspace not found
     #[error("Workspace not found: {0}. Ensure the path exists and is accessible.")]
     WorkspaceNotFound(String),
+
+    /// Unexpected internal failure, such as a panicked worker thread
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+/// Error code for a workspace path that does not exist or is not a repository
+pub const ERR_WORKSPACE_NOT_FOUND: &str = "WORKSPACE_NOT_FOUND";
+
+/// Error code for a failed database query or update
+pub const ERR_DATABASE_ERROR: &str = "DATABASE_ERROR";
+
+/// Error code for a missing or malformed tool argument
+pub const ERR_INVALID_PARAMETER: &str = "INVALID_PARAMETER";
+
+/// Error code for a lookup that matched nothing
+pub const ERR_EMPTY_RESULT: &str = "EMPTY_RESULT";
+
+/// Error code for an ingestion failure outside the database
+pub const ERR_INGEST_FAILED: &str = "INGEST_FAILED";
+
+/// Error code for an unexpected internal failure
+pub const ERR_INTERNAL_ERROR: &str = "INTERNAL_ERROR";
+
+impl HandlerError {
+    /// Get the MCP error code for this error
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) | Self::Query(QueryError::InvalidParameter(_)) => {
+                ERR_INVALID_PARAMETER
+            }
+            Self::Query(QueryError::Sqlite(_)) => ERR_DATABASE_ERROR,
+            Self::Database(err) | Self::Ingest(IngestError::Database(err)) => match err {
+                DbError::InvalidParameter(_) | DbError::ReadOnlyViolation(_) => {
+                    ERR_INVALID_PARAMETER
+                }
+                DbError::NotFound { .. } => ERR_EMPTY_RESULT,
+                _ => ERR_DATABASE_ERROR,
+            },
+            Self::NotFound(_) => ERR_EMPTY_RESULT,
+            Self::WorkspaceNotFound(_)
+            | Self::Ingest(IngestError::WorkspaceNotFound { .. })
+            | Self::Ingest(IngestError::NotARepository { .. }) => ERR_WORKSPACE_NOT_FOUND,
+            Self::Ingest(IngestError::InvalidPattern(_)) => ERR_INVALID_PARAMETER,
+            Self::Ingest(_) => ERR_INGEST_FAILED,
+            Self::Json(_) | Self::Internal(_) => ERR_INTERNAL_ERROR,
+        }
+    }
+
+    /// Format this error as an MCP tool error response
+    ///
+    /// `details` carries the underlying error when this one wraps another.
+    #[must_use]
+    pub fn to_mcp_error(&self) -> Value {
+        let message = match self {
+            Self::Ingest(err @ IngestError::NotARepository { .. }) => err.to_string(),
+            _ => self.to_string(),
+        };
+        let details = match self {
+            Self::Query(err) => Some(err.to_string()),
+            Self::Database(err) => Some(err.to_string()),
+            Self::Ingest(IngestError::NotARepository { .. }) => None,
+            Self::Ingest(err) => Some(err.display_chain()),
+            Self::Json(err) => Some(err.to_string()),
+            Self::InvalidInput(_)
+            | Self::NotFound(_)
+            | Self::WorkspaceNotFound(_)
+            | Self::Internal(_) => None,
+        };
+        format_mcp_error(self.code(), &message, details.as_deref())
+    }
+}
+
+/// Build an MCP tool error response
+///
+/// Produces `{"error": {"code": ..., "message": ..., "details": ...}}`, with
+/// `details` set to `null` when absent.
+#[must_use]
+pub fn format_mcp_error(code: &str, message: &str, details: Option<&str>) -> Value {
+    serde_json::json!({
+        "error": {
+            "code": code,
+            "message": message,
+            "details": details,
+        }
+    })
 }
 
 // ============================================================================
@@ -62,6 +159,8 @@ pub struct TimelineInput {
     pub limit: usize,
     /// Filter by workspace path
     pub workspace: Option<String>,
+    /// Only include events on this day (`YYYY-MM-DD`)
+    pub date: Option<String>,
 }
 
 fn default_timeline_limit() -> usize {
@@ -105,6 +204,159 @@ fn default_failing_tests_limit() -> usize {
     50
 }
 
+/// Input for the regressions tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RegressionsInput {
+    /// Number of recent runs per test to inspect
+    #[serde(default = "default_lookback_runs")]
+    pub lookback_runs: u32,
+    /// Compare the two halves of this many days instead of recent runs
+    pub window_days: Option<u32>,
+    /// Maximum number of regressions to return when `window_days` is set
+    #[serde(default = "default_regressions_limit")]
+    pub limit: usize,
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+}
+
+fn default_lookback_runs() -> u32 {
+    5
+}
+
+fn default_regressions_limit() -> usize {
+    20
+}
+
+/// Input for the failure_streaks tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FailureStreaksInput {
+    /// Minimum number of consecutive failures to report
+    #[serde(default = "default_min_streak")]
+    pub min_streak: u32,
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+}
+
+fn default_min_streak() -> u32 {
+    3
+}
+
+/// Input for the flaky_tests tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FlakyTestsInput {
+    /// Number of days to look back
+    #[serde(default = "default_flaky_tests_days")]
+    pub days: u32,
+    /// Maximum number of tests to return
+    #[serde(default = "default_flaky_tests_limit")]
+    pub limit: usize,
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+}
+
+fn default_flaky_tests_days() -> u32 {
+    30
+}
+
+fn default_flaky_tests_limit() -> usize {
+    20
+}
+
+/// Input for the slow_tests tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SlowTestsInput {
+    /// Only include tests from this suite
+    pub suite: Option<String>,
+    /// Maximum number of tests to return
+    #[serde(default = "default_slow_tests_top_n")]
+    pub top_n: usize,
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+}
+
+fn default_slow_tests_top_n() -> usize {
+    20
+}
+
+/// Input for the suite_parallelism tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SuiteParallelismInput {
+    /// Test run ID
+    pub run_id: String,
+}
+
+/// Input for the commit_volume tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CommitVolumeInput {
+    /// Number of days to include
+    #[serde(default = "default_commit_volume_days")]
+    pub days: u32,
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+}
+
+fn default_commit_volume_days() -> u32 {
+    30
+}
+
+/// Input for the authors tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuthorsInput {
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+    /// Maximum number of authors to return
+    #[serde(default = "default_authors_limit")]
+    pub limit: usize,
+}
+
+fn default_authors_limit() -> usize {
+    20
+}
+
+/// Input for the heatmap tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HeatmapInput {
+    /// Author name or email; all authors if omitted
+    pub author: Option<String>,
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+    /// Number of days to look back
+    #[serde(default = "default_heatmap_days")]
+    pub days: u32,
+}
+
+fn default_heatmap_days() -> u32 {
+    90
+}
+
+/// Input for the tags tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TagsInput {
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+    /// Maximum number of tags to return
+    #[serde(default = "default_tags_limit")]
+    pub limit: usize,
+}
+
+fn default_tags_limit() -> usize {
+    20
+}
+
+/// Input for the context_usage tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContextUsageInput {
+    /// Number of days to look back
+    #[serde(default = "default_context_usage_days")]
+    pub days: u32,
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+}
+
+fn default_context_usage_days() -> u32 {
+    30
+}
+
 /// Input for the activity_summary tool
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct ActivitySummaryInput {
@@ -117,6 +369,93 @@ fn default_activity_days() -> u32 {
     7
 }
 
+/// Input for the copilot_stats tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CopilotStatsInput {
+    /// Number of days to summarize
+    #[serde(default = "default_activity_days")]
+    pub days: u32,
+    /// Cost per 1,000 tokens in US dollars (optional)
+    pub cost_per_1k_tokens: Option<f64>,
+}
+
+/// Input for the trending_topics tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TrendingTopicsInput {
+    /// Length of each comparison period in days
+    #[serde(default = "default_activity_days")]
+    pub days: u32,
+    /// Minimum sessions mentioning a topic across both periods
+    #[serde(default = "default_min_topic_sessions")]
+    pub min_sessions: u32,
+}
+
+fn default_min_topic_sessions() -> u32 {
+    2
+}
+
+/// Input for the session_detail tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionDetailInput {
+    /// Copilot session ID or original VS Code session ID
+    pub session_id: String,
+    /// Return every message in order instead of question-answer turns
+    #[serde(default)]
+    pub messages: bool,
+}
+
+/// Input for the annotate tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnnotateInput {
+    /// Kind of event, e.g. "deploy" or "incident"
+    pub event_type: String,
+    /// Short title shown in the timeline
+    pub title: String,
+    /// Longer description
+    #[serde(default)]
+    pub description: String,
+    /// When the event happened (RFC 3339), defaults to now
+    pub event_at: Option<String>,
+    /// Workspace path
+    pub workspace: Option<String>,
+}
+
+/// Input for the workspaces tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WorkspacesInput {
+    /// Number of days to look back
+    #[serde(default = "default_workspaces_days")]
+    pub days: u32,
+    /// Maximum number of workspaces to return
+    #[serde(default = "default_workspaces_limit")]
+    pub limit: usize,
+}
+
+fn default_workspaces_days() -> u32 {
+    7
+}
+
+fn default_workspaces_limit() -> usize {
+    20
+}
+
+/// Input for the suggest_queries tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SuggestQueriesInput {
+    /// Filter by workspace path
+    pub workspace: Option<String>,
+}
+
+/// Input for the query tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct QueryInput {
+    /// SELECT statement to run
+    pub sql: String,
+    /// Positional parameters bound to `?1`, `?2`, ...
+    #[serde(default)]
+    pub params: Vec<String>,
+}
+
 /// Input for the commit_details tool
 #[derive(Debug, Clone, Deserialize)]
 pub struct CommitDetailsInput {
@@ -124,6 +463,13 @@ pub struct CommitDetailsInput {
     pub sha: String,
 }
 
+/// Input for the diff tool
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiffInput {
+    /// Full or partial commit SHA
+    pub sha: String,
+}
+
 /// Input for the ingest tool
 #[derive(Debug, Clone, Deserialize)]
 pub struct IngestInput {
@@ -137,6 +483,11 @@ pub struct IngestInput {
     pub incremental: bool,
     /// Max items to ingest (optional)
     pub limit: Option<usize>,
+    /// Store per-file diff statistics with each commit
+    #[serde(default)]
+    pub include_diffs: bool,
+    /// Nextest libtest-json output to ingest for the "tests" source
+    pub nextest_json: Option<String>,
 }
 
 fn default_ingest_source() -> String {
@@ -195,6 +546,70 @@ impl From<IngestStats> for IngestStatsResponse {
     }
 }
 
+/// Response from the commit_volume tool
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitVolumeResponse {
+    /// Number of days covered
+    pub days: u32,
+    /// One block character per day, oldest first
+    pub sparkline: String,
+    /// Commit counts per day, oldest first
+    pub volume: Vec<DailyCommitVolume>,
+}
+
+/// Response from the regressions tool
+///
+/// Serialized as a plain array of whichever kind of regression was asked for.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum RegressionsResponse {
+    /// Pass-to-failure transitions within each test's recent runs
+    Transitions(Vec<Regression>),
+    /// Tests that passed early in a time window and failed later in it
+    Window(Vec<TestRegression>),
+}
+
+impl RegressionsResponse {
+    /// Number of regressions found
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Transitions(regressions) => regressions.len(),
+            Self::Window(regressions) => regressions.len(),
+        }
+    }
+
+    /// Whether no regressions were found
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Response from the session_detail tool
+///
+/// Serialized as an array of turns, or as the session object when the full
+/// message history was asked for.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum SessionDetailResponse {
+    /// Question-answer turns
+    Turns(Vec<QAPair>),
+    /// The session with every message in order
+    Session(SessionDetail),
+}
+
+/// Response from the annotate tool
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotateResponse {
+    /// ID of the new event
+    pub event_id: String,
+    /// Workspace the event was recorded on
+    pub workspace_id: String,
+    /// When the event happened (RFC 3339)
+    pub event_at: String,
+}
+
 // ============================================================================
 // Handler Functions
 // ============================================================================
@@ -225,7 +640,18 @@ pub fn handle_timeline(
         .as_deref()
         .or_else(|| default_workspace.and_then(|p| p.to_str()));
 
-    let events = queries::get_timeline(db.connection(), input.limit, workspace_filter)?;
+    let events = match input.date.as_deref() {
+        Some(date) => {
+            queries::get_timeline_on_date(db.connection(), date, input.limit, workspace_filter)?
+        }
+        None => {
+            let filter = TimelineFilter {
+                workspace_filter: workspace_filter.map(str::to_string),
+                ..TimelineFilter::default()
+            };
+            queries::get_timeline(db.connection(), input.limit, Some(&filter))?
+        }
+    };
 
     Ok(events)
 }
@@ -281,96 +707,726 @@ pub fn handle_failing_tests(
     Ok(tests)
 }
 
-/// Handle the hindsight_activity_summary tool
+/// Handle the hindsight_regressions tool
 ///
-/// Returns aggregate activity statistics for a time period.
-pub fn handle_activity_summary(
+/// Returns tests that recently started failing after previously passing,
+/// either within each test's recent runs or across a window of days.
+pub fn handle_regressions(
     db: &Database,
     args: Option<Map<String, Value>>,
-) -> Result<ActivitySummary, HandlerError> {
-    let input: ActivitySummaryInput = parse_input(args)?;
+    default_workspace: Option<&PathBuf>,
+) -> Result<RegressionsResponse, HandlerError> {
+    let input: RegressionsInput = parse_input(args)?;
 
-    let summary = queries::get_activity_summary(db.connection(), input.days)?;
+    if input.lookback_runs == 0 {
+        return Err(HandlerError::InvalidInput(
+            "lookback_runs must be at least 1".to_string(),
+        ));
+    }
+    if input.window_days == Some(0) {
+        return Err(HandlerError::InvalidInput(
+            "window_days must be at least 1".to_string(),
+        ));
+    }
 
-    Ok(summary)
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let response = match input.window_days {
+        Some(window_days) => RegressionsResponse::Window(queries::get_regression_detection(
+            db.connection(),
+            window_days,
+            workspace_filter,
+            input.limit,
+        )?),
+        None => RegressionsResponse::Transitions(queries::get_test_regressions(
+            db.connection(),
+            input.lookback_runs,
+            workspace_filter,
+        )?),
+    };
+
+    Ok(response)
 }
 
-/// Handle the hindsight_commit_details tool
+/// Handle the hindsight_failure_streaks tool
 ///
-/// Returns detailed information about a specific commit including linked test runs.
-pub fn handle_commit_details(
+/// Returns tests that have kept failing across their most recent runs.
+pub fn handle_failure_streaks(
     db: &Database,
     args: Option<Map<String, Value>>,
-) -> Result<CommitWithTests, HandlerError> {
-    let input: CommitDetailsInput = parse_input(args)?;
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<FailureStreak>, HandlerError> {
+    let input: FailureStreaksInput = parse_input(args)?;
 
-    if input.sha.is_empty() {
+    if input.min_streak == 0 {
         return Err(HandlerError::InvalidInput(
-            "Commit SHA is required. Provide a full or partial SHA like 'abc123' or 'abc123def456789'.".to_string(),
+            "min_streak must be at least 1".to_string(),
         ));
     }
 
-    let commit = queries::get_commit_with_tests(db.connection(), &input.sha)?;
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
 
-    commit.ok_or_else(|| HandlerError::NotFound(format!("Commit not found: {}", input.sha)))
+    let streaks =
+        queries::get_failure_streaks(db.connection(), input.min_streak, workspace_filter)?;
+
+    Ok(streaks)
 }
 
-/// Handle the hindsight_ingest tool
+/// Handle the hindsight_flaky_tests tool
 ///
-/// Triggers data ingestion from sources.
-pub fn handle_ingest(
-    db: Database,
+/// Returns the tests that failed most often over the requested period.
+pub fn handle_flaky_tests(
+    db: &Database,
     args: Option<Map<String, Value>>,
-) -> Result<IngestResponse, HandlerError> {
-    let input: IngestInput = parse_input(args)?;
-
-    let workspace_path = PathBuf::from(&input.workspace);
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<FlakyTestStat>, HandlerError> {
+    let input: FlakyTestsInput = parse_input(args)?;
 
-    if !workspace_path.exists() {
-        return Err(HandlerError::WorkspaceNotFound(input.workspace.clone()));
+    if input.days == 0 {
+        return Err(HandlerError::InvalidInput(
+            "days must be at least 1".to_string(),
+        ));
     }
 
-    if !workspace_path.is_dir() {
-        return Err(HandlerError::InvalidInput(format!(
-            "Workspace path is not a directory: {}",
-            input.workspace
-        )));
-    }
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
 
-    // Build ingest options
-    let options = if input.incremental {
-        IngestOptions::incremental()
-    } else {
-        IngestOptions::full()
-    };
+    let stats = queries::get_most_failing_tests(
+        db.connection(),
+        input.days,
+        input.limit,
+        workspace_filter,
+    )?;
 
-    let options = if let Some(limit) = input.limit {
-        options.with_limit(limit)
-    } else {
-        options
-    };
+    Ok(stats)
+}
 
-    let mut ingestor = Ingestor::new(db);
-    let mut total_stats = IngestStats::default();
+/// Handle the hindsight_suite_parallelism tool
+///
+/// Returns groups of test suites that ran concurrently within a test run.
+pub fn handle_suite_parallelism(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<Vec<ParallelGroup>, HandlerError> {
+    let input: SuiteParallelismInput = parse_input(args)?;
 
-    // Ingest based on source
-    match input.source.as_str() {
-        "git" => {
-            let stats = ingestor.ingest_git(&workspace_path, &options)?;
-            total_stats.merge(&stats);
-        }
-        "copilot" => {
-            let stats = ingestor.ingest_copilot(&workspace_path)?;
+    let groups = queries::get_inferred_suite_parallelism(db.connection(), &input.run_id)?;
+
+    Ok(groups)
+}
+
+/// Handle the hindsight_commit_volume tool
+///
+/// Returns daily commit counts with a sparkline of the volume over time.
+pub fn handle_commit_volume(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+) -> Result<CommitVolumeResponse, HandlerError> {
+    let input: CommitVolumeInput = parse_input(args)?;
+
+    if input.days == 0 {
+        return Err(HandlerError::InvalidInput(
+            "days must be at least 1".to_string(),
+        ));
+    }
+
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let volume = queries::get_daily_commit_volume(db.connection(), input.days, workspace_filter)?;
+    let counts: Vec<u64> = volume.iter().map(|d| d.commit_count).collect();
+
+    Ok(CommitVolumeResponse {
+        days: input.days,
+        sparkline: sparkline(&counts),
+        volume,
+    })
+}
+
+/// Handle the hindsight_authors tool
+///
+/// Returns the contributors with the most commits.
+pub fn handle_authors(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<ContributorStat>, HandlerError> {
+    let input: AuthorsInput = parse_input(args)?;
+
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let contributors = queries::get_contributors(db.connection(), workspace_filter, input.limit)?;
+
+    Ok(contributors)
+}
+
+/// Handle the hindsight_heatmap tool
+///
+/// Returns commit counts for each hour of the week that had commits.
+pub fn handle_heatmap(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<HeatmapCell>, HandlerError> {
+    let input: HeatmapInput = parse_input(args)?;
+
+    if input.days == 0 {
+        return Err(HandlerError::InvalidInput(
+            "days must be at least 1".to_string(),
+        ));
+    }
+
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let heatmap = queries::get_author_activity_heatmap(
+        db.connection(),
+        input.author.as_deref(),
+        workspace_filter,
+        input.days,
+    )?;
+
+    Ok(heatmap.cells())
+}
+
+/// Handle the hindsight_tags tool
+///
+/// Returns git tags, most recent first.
+pub fn handle_tags(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<GitTag>, HandlerError> {
+    let input: TagsInput = parse_input(args)?;
+
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let tags = queries::get_tags(db.connection(), workspace_filter, input.limit)?;
+
+    Ok(tags)
+}
+
+/// Handle the hindsight_slow_tests tool
+///
+/// Returns duration percentiles for the slowest tests, ranked by p90.
+pub fn handle_slow_tests(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<TestDurationStat>, HandlerError> {
+    let input: SlowTestsInput = parse_input(args)?;
+
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let mut stats = queries::get_test_duration_percentiles(
+        db.connection(),
+        input.suite.as_deref(),
+        workspace_filter,
+    )?;
+    stats.truncate(input.top_n);
+
+    Ok(stats)
+}
+
+/// Handle the hindsight_context_usage tool
+///
+/// Returns how often each kind of context (files, workspace, prompt files)
+/// is attached to Copilot messages.
+pub fn handle_context_usage(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<VariableKindStats>, HandlerError> {
+    let input: ContextUsageInput = parse_input(args)?;
+
+    if input.days == 0 {
+        return Err(HandlerError::InvalidInput(
+            "days must be at least 1".to_string(),
+        ));
+    }
+
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let stats = queries::get_variable_kind_stats(db.connection(), input.days, workspace_filter)?;
+
+    Ok(stats)
+}
+
+/// Render values as a string of block characters scaled to the maximum
+fn sparkline(values: &[u64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| {
+            if max == 0 {
+                BLOCKS[0]
+            } else {
+                BLOCKS[(v * (BLOCKS.len() as u64 - 1)).div_ceil(max) as usize]
+            }
+        })
+        .collect()
+}
+
+/// Handle the hindsight_suggest_queries tool
+///
+/// Returns the most common topics in commit messages as search terms.
+pub fn handle_suggest_queries(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+) -> Result<Vec<String>, HandlerError> {
+    let input: SuggestQueriesInput = parse_input(args)?;
+
+    let workspace_filter = input
+        .workspace
+        .as_deref()
+        .or_else(|| default_workspace.and_then(|p| p.to_str()));
+
+    let suggestions = queries::suggest_search_queries(db.connection(), workspace_filter)?;
+
+    Ok(suggestions)
+}
+
+/// Handle the hindsight_query tool
+///
+/// Runs a read-only SQL query against the hindsight database.
+pub fn handle_query(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<Vec<HashMap<String, Value>>, HandlerError> {
+    let input: QueryInput = parse_input(args)?;
+
+    if input.sql.trim().is_empty() {
+        return Err(HandlerError::InvalidInput(
+            "sql is required. Provide a SELECT statement.".to_string(),
+        ));
+    }
+
+    let params: Vec<&str> = input.params.iter().map(String::as_str).collect();
+    let rows = db.execute_readonly_query(&input.sql, &params)?;
+
+    Ok(rows)
+}
+
+/// Handle the hindsight_activity_summary tool
+///
+/// Returns aggregate activity statistics for a time period.
+pub fn handle_activity_summary(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<ActivitySummary, HandlerError> {
+    let input: ActivitySummaryInput = parse_input(args)?;
+
+    let summary = queries::get_activity_summary(db.connection(), input.days)?;
+
+    Ok(summary)
+}
+
+/// Handle the hindsight_copilot_stats tool
+///
+/// Returns estimated Copilot token usage and cost for a time period.
+pub fn handle_copilot_stats(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<TokenUsageStats, HandlerError> {
+    let input: CopilotStatsInput = parse_input(args)?;
+
+    let stats = queries::get_total_token_usage(db.connection(), input.days)?;
+
+    match input.cost_per_1k_tokens {
+        Some(rate) if !rate.is_finite() || rate < 0.0 => Err(HandlerError::InvalidInput(
+            "cost_per_1k_tokens must be a non-negative number".to_string(),
+        )),
+        Some(rate) => Ok(stats.with_cost_rate(rate)),
+        None => Ok(stats),
+    }
+}
+
+/// Handle the hindsight_trending_topics tool
+///
+/// Returns Copilot topics that are coming up more or less often than before.
+pub fn handle_trending_topics(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<Vec<TopicTrend>, HandlerError> {
+    let input: TrendingTopicsInput = parse_input(args)?;
+
+    if input.days == 0 {
+        return Err(HandlerError::InvalidInput(
+            "days must be at least 1".to_string(),
+        ));
+    }
+
+    let trends = queries::get_trending_topics(db.connection(), input.days, input.min_sessions)?;
+
+    Ok(trends)
+}
+
+/// Handle the hindsight_session_detail tool
+///
+/// Returns a Copilot session as question-answer turns, or with its full
+/// message history when `messages` is set.
+pub fn handle_session_detail(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<SessionDetailResponse, HandlerError> {
+    let input: SessionDetailInput = parse_input(args)?;
+
+    if input.session_id.is_empty() {
+        return Err(HandlerError::InvalidInput(
+            "session_id is required. Use hindsight_search or hindsight_timeline to find session IDs."
+                .to_string(),
+        ));
+    }
+
+    if input.messages {
+        return queries::get_copilot_session_with_messages(db.connection(), &input.session_id)?
+            .map(SessionDetailResponse::Session)
+            .ok_or_else(|| {
+                HandlerError::NotFound(format!("Copilot session not found: {}", input.session_id))
+            });
+    }
+
+    let Some((_, messages)) = db.get_copilot_session_with_messages(&input.session_id)? else {
+        return Err(HandlerError::NotFound(format!(
+            "Copilot session not found: {}",
+            input.session_id
+        )));
+    };
+
+    let pairs = queries::pair_messages(messages);
+    if pairs.is_empty() {
+        return Err(HandlerError::NotFound(format!(
+            "No questions found for Copilot session: {}",
+            input.session_id
+        )));
+    }
+
+    Ok(SessionDetailResponse::Turns(pairs))
+}
+
+/// Handle the hindsight_annotate tool
+///
+/// Records a custom event, such as a deploy or incident, on a workspace's
+/// timeline. The workspace path is turned into an ID by
+/// `resolve_workspace_id`, which the server backs with its workspace cache.
+pub fn handle_annotate(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+    default_workspace: Option<&PathBuf>,
+    resolve_workspace_id: impl Fn(&str) -> Result<Option<String>, DbError>,
+) -> Result<AnnotateResponse, HandlerError> {
+    let input: AnnotateInput = parse_input(args)?;
+
+    if input.event_type.trim().is_empty() || input.title.trim().is_empty() {
+        return Err(HandlerError::InvalidInput(
+            "event_type and title are required".to_string(),
+        ));
+    }
+
+    let event_at = match input.event_at.as_deref() {
+        Some(value) => DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|_| {
+                HandlerError::InvalidInput(format!(
+                    "Invalid event_at '{value}', expected an RFC 3339 timestamp"
+                ))
+            })?,
+        None => Utc::now(),
+    };
+
+    let workspace = input
+        .workspace
+        .clone()
+        .or_else(|| default_workspace.map(|p| p.display().to_string()))
+        .ok_or_else(|| HandlerError::InvalidInput("workspace is required".to_string()))?;
+    let workspace_id =
+        resolve_workspace_id(&workspace)?.ok_or(HandlerError::WorkspaceNotFound(workspace))?;
+
+    let event_id = db.insert_workspace_event(
+        &workspace_id,
+        &input.event_type,
+        &input.title,
+        &input.description,
+        event_at,
+    )?;
+
+    Ok(AnnotateResponse {
+        event_id,
+        workspace_id,
+        event_at: event_at.to_rfc3339(),
+    })
+}
+
+/// Format workspaces as a JSON array for the workspace_list tool
+///
+/// Each entry has `id`, `name`, `path`, `created_at`, `commit_count`, and
+/// `session_count`. Workspaces missing from `counts` report zero for both.
+#[must_use]
+pub fn format_workspace_list(
+    workspaces: &[WorkspaceRecord],
+    counts: &HashMap<String, WorkspaceCounts>,
+) -> Value {
+    let entries = workspaces
+        .iter()
+        .map(|w| {
+            let c = counts.get(&w.id).copied().unwrap_or_default();
+            serde_json::json!({
+                "id": w.id,
+                "name": w.name,
+                "path": w.path,
+                "created_at": w.created_at.to_rfc3339(),
+                "commit_count": c.commit_count,
+                "session_count": c.session_count,
+            })
+        })
+        .collect();
+    Value::Array(entries)
+}
+
+/// Handle the hindsight_workspace_list tool
+///
+/// Returns every known workspace with its commit and session counts.
+pub fn handle_workspace_list(db: &Database) -> Result<Value, HandlerError> {
+    let workspaces = db.list_workspaces()?;
+    let counts = db.workspace_counts()?;
+
+    Ok(format_workspace_list(&workspaces, &counts))
+}
+
+/// Handle the hindsight_workspaces tool
+///
+/// Returns the workspaces with recent activity, most recently active first.
+pub fn handle_workspaces(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<Vec<WorkspaceActivity>, HandlerError> {
+    let input: WorkspacesInput = parse_input(args)?;
+
+    if input.days == 0 {
+        return Err(HandlerError::InvalidInput(
+            "days must be at least 1".to_string(),
+        ));
+    }
+
+    let workspaces = queries::get_active_workspaces(db.connection(), input.days, input.limit)?;
+
+    Ok(workspaces)
+}
+
+/// Handle the hindsight_commit_details tool
+///
+/// Returns detailed information about a specific commit including linked test runs.
+pub fn handle_commit_details(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<CommitWithTests, HandlerError> {
+    let input: CommitDetailsInput = parse_input(args)?;
+    check_sha_prefix(db, &input.sha)?;
+
+    let commit = queries::get_commit_with_tests(db.connection(), &input.sha)?;
+
+    commit.ok_or_else(|| HandlerError::NotFound(format!("Commit not found: {}", input.sha)))
+}
+
+/// Reject SHA prefixes that are empty, not hexadecimal, too short, or match
+/// several commits
+fn check_sha_prefix(db: &Database, sha: &str) -> Result<(), HandlerError> {
+    if sha.is_empty() {
+        return Err(HandlerError::InvalidInput(
+            "Commit SHA is required. Provide a full or partial SHA like 'abc123' or 'abc123def456789'.".to_string(),
+        ));
+    }
+    if !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(HandlerError::InvalidInput(format!(
+            "Invalid SHA prefix: '{}'. Use hexadecimal characters only",
+            sha
+        )));
+    }
+
+    let matches = db
+        .get_commit_count_by_sha_prefix(sha)
+        .map_err(|e| match e {
+            DbError::InvalidParameter(_) => HandlerError::InvalidInput(format!(
+                "SHA prefix too short: '{}'. Provide at least {} characters",
+                sha, MIN_SHA_PREFIX_LEN
+            )),
+            other => HandlerError::Database(other),
+        })?;
+    if matches > 1 {
+        return Err(HandlerError::InvalidInput(format!(
+            "Ambiguous SHA prefix: {} commits match",
+            matches
+        )));
+    }
+
+    Ok(())
+}
+
+/// Handle the hindsight_diff tool
+///
+/// Returns the full patch of every file the commit changed, read from the
+/// workspace repository. When the repository is no longer available, the
+/// per-file line counts stored at ingest time are returned without patches,
+/// and `NotFound` when the commit was ingested without a diff.
+pub fn handle_diff(
+    db: &Database,
+    args: Option<Map<String, Value>>,
+) -> Result<CommitDiff, HandlerError> {
+    let input: DiffInput = parse_input(args)?;
+    check_sha_prefix(db, &input.sha)?;
+
+    let mut diff = queries::get_commit_diff(db.connection(), &input.sha)?
+        .ok_or_else(|| HandlerError::NotFound(format!("Commit not found: {}", input.sha)))?;
+
+    match hindsight_git::GitRepo::open(&diff.workspace_path)
+        .and_then(|repo| repo.commit_patches(&diff.sha))
+    {
+        Ok(patches) => {
+            diff.files = patches
+                .into_iter()
+                .map(|file| queries::FileDiffEntry {
+                    path: file.path,
+                    old_path: file.old_path,
+                    additions: file.insertions,
+                    deletions: file.deletions,
+                    patch: Some(file.patch),
+                })
+                .collect();
+        }
+        Err(e) if diff.diff_stored => {
+            tracing::debug!(sha = %diff.sha, error = %e, "Falling back to stored diff");
+        }
+        Err(e) => {
+            return Err(HandlerError::NotFound(format!(
+                "No diff available for commit {}: it was ingested without a diff and the \
+                 repository at {} could not be read ({e})",
+                diff.sha, diff.workspace_path
+            )));
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Handle the hindsight_ingest tool
+///
+/// Triggers data ingestion from sources.
+pub fn handle_ingest(
+    db: Database,
+    args: Option<Map<String, Value>>,
+) -> Result<IngestResponse, HandlerError> {
+    handle_ingest_with_progress(db, args, None)
+}
+
+/// Handle the hindsight_ingest tool, reporting progress through `on_progress`
+///
+/// The "tests" source ingests the `nextest_json` argument, which it requires.
+/// "all" ingests git and Copilot sessions, plus `nextest_json` when given;
+/// git and Copilot failures are skipped in that mode, but a supplied
+/// `nextest_json` that fails to ingest is still an error.
+pub fn handle_ingest_with_progress(
+    db: Database,
+    args: Option<Map<String, Value>>,
+    on_progress: Option<ProgressCallback>,
+) -> Result<IngestResponse, HandlerError> {
+    let input: IngestInput = parse_input(args)?;
+
+    let workspace_path = PathBuf::from(&input.workspace);
+
+    if !workspace_path.exists() {
+        return Err(HandlerError::WorkspaceNotFound(input.workspace.clone()));
+    }
+
+    if !workspace_path.is_dir() {
+        return Err(HandlerError::InvalidInput(format!(
+            "Workspace path is not a directory: {}",
+            input.workspace
+        )));
+    }
+
+    if input.source == "tests" && input.nextest_json.is_none() {
+        return Err(HandlerError::InvalidInput(
+            "nextest_json is required for the 'tests' source. Provide the output of \
+             'cargo nextest run --message-format libtest-json'"
+                .to_string(),
+        ));
+    }
+
+    // Build ingest options
+    let mut options = if input.incremental {
+        IngestOptions::incremental()
+    } else {
+        IngestOptions::full()
+    };
+
+    if let Some(limit) = input.limit {
+        options = options.with_limit(limit);
+    }
+    if input.include_diffs {
+        options = options.with_diffs();
+    }
+
+    let mut ingestor = Ingestor::new(db);
+    if let Some(callback) = on_progress {
+        ingestor = ingestor.with_progress(callback);
+    }
+    let mut total_stats = IngestStats::default();
+
+    // Ingest based on source
+    match input.source.as_str() {
+        "git" => {
+            let stats = ingestor.ingest_git(&workspace_path, &options)?;
+            total_stats.merge(&stats);
+        }
+        "copilot" => {
+            let stats = ingestor.ingest_copilot(&workspace_path, &options, None)?;
+            total_stats.merge(&stats);
+        }
+        "tests" => {
+            let output = input.nextest_json.as_deref().unwrap_or_default();
+            let stats =
+                ingestor.ingest_tests_with_options(&workspace_path, output, None, &options)?;
             total_stats.merge(&stats);
         }
         _ => {
             // Ingest all sources (default), collecting stats
-            // Note: test ingestion requires nextest output, which we don't have here
-            // So we only ingest git and copilot in "all" mode
             if let Ok(stats) = ingestor.ingest_git(&workspace_path, &options) {
                 total_stats.merge(&stats);
             }
-            if let Ok(stats) = ingestor.ingest_copilot(&workspace_path) {
+            if let Ok(stats) = ingestor.ingest_copilot(&workspace_path, &options, None) {
+                total_stats.merge(&stats);
+            }
+            if let Some(output) = input.nextest_json.as_deref() {
+                let stats =
+                    ingestor.ingest_tests_with_options(&workspace_path, output, None, &options)?;
                 total_stats.merge(&stats);
             }
         }
@@ -406,6 +1462,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_handler_error_codes() {
+        let cases = [
+            (
+                HandlerError::InvalidInput("x".into()),
+                ERR_INVALID_PARAMETER,
+            ),
+            (
+                HandlerError::Query(QueryError::InvalidParameter("x".into())),
+                ERR_INVALID_PARAMETER,
+            ),
+            (
+                HandlerError::Database(DbError::ReadOnlyViolation("x".into())),
+                ERR_INVALID_PARAMETER,
+            ),
+            (
+                HandlerError::Database(DbError::NotInitialized),
+                ERR_DATABASE_ERROR,
+            ),
+            (HandlerError::NotFound("x".into()), ERR_EMPTY_RESULT),
+            (
+                HandlerError::WorkspaceNotFound("x".into()),
+                ERR_WORKSPACE_NOT_FOUND,
+            ),
+            (
+                HandlerError::Ingest(IngestError::NotARepository {
+                    path: "/tmp".into(),
+                }),
+                ERR_WORKSPACE_NOT_FOUND,
+            ),
+        ];
+
+        for (err, code) in cases {
+            assert_eq!(err.code(), code, "{err:?}");
+        }
+    }
+
+    #[test]
+    fn test_to_mcp_error_includes_wrapped_details() {
+        let err = HandlerError::Database(DbError::NotInitialized);
+        let response = err.to_mcp_error();
+
+        assert_eq!(response["error"]["code"], ERR_DATABASE_ERROR);
+        assert_eq!(response["error"]["message"], err.to_string());
+        assert_eq!(response["error"]["details"], "Database not initialized");
+
+        let response = HandlerError::NotFound("Commit not found: abc".into()).to_mcp_error();
+        assert!(response["error"]["details"].is_null());
+    }
+
     #[test]
     fn test_parse_timeline_input_defaults() {
         let input: TimelineInput = parse_input(None).expect("parse");
@@ -496,6 +1602,31 @@ mod tests {
         assert!(events.is_empty());
     }
 
+    #[test]
+    fn test_handle_timeline_date() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        db.connection()
+            .execute_batch(
+                r#"
+                INSERT INTO workspaces (id, name, path, created_at, updated_at)
+                VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+                VALUES ('c-1', 'ws-1', 'c-1', 'msg', 'Author', '2026-04-01T10:00:00+00:00', datetime('now')),
+                       ('c-2', 'ws-1', 'c-2', 'msg', 'Author', '2026-04-02T10:00:00+00:00', datetime('now'));
+                "#,
+            )
+            .expect("insert commits");
+
+        let args = to_map(json!({ "date": "2026-04-02" }));
+        let events = handle_timeline(&db, Some(args), None).expect("handle");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_id, "c-2");
+
+        let args = to_map(json!({ "date": "yesterday" }));
+        assert!(handle_timeline(&db, Some(args), None).is_err());
+    }
+
     #[test]
     fn test_handle_search_empty_query() {
         let db = Database::in_memory().expect("create db");
@@ -515,11 +1646,534 @@ mod tests {
         assert_eq!(summary.copilot_sessions, 0);
     }
 
+    #[test]
+    fn test_handle_regressions_empty_db() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let regressions = handle_regressions(&db, None, None).expect("regressions");
+        assert!(regressions.is_empty());
+    }
+
+    #[test]
+    fn test_handle_failure_streaks_zero_min_streak() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+
+        let args = to_map(json!({"min_streak": 0}));
+        let result = handle_failure_streaks(&db, Some(args), None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let streaks = handle_failure_streaks(&db, None, None).expect("streaks");
+        assert!(streaks.is_empty());
+    }
+
+    #[test]
+    fn test_handle_flaky_tests_zero_days() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+
+        let args = to_map(json!({"days": 0}));
+        let result = handle_flaky_tests(&db, Some(args), None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let stats = handle_flaky_tests(&db, None, None).expect("stats");
+        assert!(stats.is_empty());
+    }
+
+    #[test]
+    fn test_handle_context_usage_zero_days() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+
+        let args = to_map(json!({"days": 0}));
+        let result = handle_context_usage(&db, Some(args), None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let stats = handle_context_usage(&db, None, None).expect("stats");
+        assert!(stats.is_empty());
+    }
+
+    #[test]
+    fn test_handle_regressions_zero_lookback() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"lookback_runs": 0}));
+        let result = handle_regressions(&db, Some(args), None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let args = to_map(json!({"window_days": 0}));
+        let result = handle_regressions(&db, Some(args), None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_handle_regressions_window() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+        db.connection()
+            .execute_batch(
+                r#"
+                INSERT INTO workspaces (id, name, path, created_at, updated_at)
+                VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+                INSERT INTO test_runs (id, workspace_id, started_at)
+                VALUES ('run-1', 'ws-1', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-10 days')),
+                       ('run-2', 'ws-1', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-1 days'));
+                INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, created_at)
+                VALUES ('r-1', 'run-1', 'core', 'test_a', 'passed', datetime('now')),
+                       ('r-2', 'run-2', 'core', 'test_a', 'failed', datetime('now'));
+                "#,
+            )
+            .expect("insert results");
+
+        let args = to_map(json!({"window_days": 14}));
+        let response = handle_regressions(&db, Some(args), None).expect("regressions");
+        let RegressionsResponse::Window(regressions) = &response else {
+            panic!("expected window regressions");
+        };
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].failure_run_id, "run-2");
+
+        // The two runs are adjacent, so the default mode reports it too
+        let response = handle_regressions(&db, None, None).expect("regressions");
+        assert!(matches!(response, RegressionsResponse::Transitions(_)));
+        assert_eq!(response.len(), 1);
+
+        let value = serde_json::to_value(&response).expect("serialize");
+        assert!(value.is_array());
+    }
+
+    #[test]
+    fn test_handle_suite_parallelism_missing_run_id() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let result = handle_suite_parallelism(&db, None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 0, 0]), "▁▁▁");
+        assert_eq!(sparkline(&[0, 1, 7, 14]), "▁▂▅█");
+    }
+
+    #[test]
+    fn test_handle_commit_volume_empty_db() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"days": 10}));
+        let response = handle_commit_volume(&db, Some(args), None).expect("volume");
+        assert_eq!(response.volume.len(), 10);
+        assert_eq!(response.sparkline.chars().count(), 10);
+        assert!(response.volume.iter().all(|d| d.commit_count == 0));
+    }
+
+    #[test]
+    fn test_handle_commit_volume_zero_days() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"days": 0}));
+        let result = handle_commit_volume(&db, Some(args), None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_handle_authors() {
+        let mut db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let ws_id = db.get_or_create_workspace("test", "/test").expect("ws");
+        let commits: Vec<crate::db::CommitRecord> = ["Alice", "Bob", "Alice"]
+            .iter()
+            .enumerate()
+            .map(|(i, author)| {
+                crate::db::CommitRecord::new(
+                    ws_id.clone(),
+                    format!("sha{i}"),
+                    author.to_string(),
+                    None,
+                    format!("Commit {i}"),
+                    Utc::now(),
+                )
+            })
+            .collect();
+        db.insert_commits_batch(&commits).expect("commits");
+
+        let contributors = handle_authors(&db, None, None).expect("authors");
+        let summary: Vec<(&str, u64)> = contributors
+            .iter()
+            .map(|c| (c.author.as_str(), c.commit_count))
+            .collect();
+        assert_eq!(summary, vec![("Alice", 2), ("Bob", 1)]);
+
+        let args = to_map(json!({"limit": 1, "workspace": "/test"}));
+        let contributors = handle_authors(&db, Some(args), None).expect("authors");
+        assert_eq!(contributors.len(), 1);
+    }
+
+    #[test]
+    fn test_handle_heatmap() {
+        let mut db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let ws_id = db.get_or_create_workspace("test", "/test").expect("ws");
+        let commits: Vec<crate::db::CommitRecord> = ["Alice", "Bob", "Alice"]
+            .iter()
+            .enumerate()
+            .map(|(i, author)| {
+                crate::db::CommitRecord::new(
+                    ws_id.clone(),
+                    format!("sha{i}"),
+                    author.to_string(),
+                    None,
+                    format!("Commit {i}"),
+                    Utc::now(),
+                )
+            })
+            .collect();
+        db.insert_commits_batch(&commits).expect("commits");
+
+        let cells = handle_heatmap(&db, None, None).expect("heatmap");
+        assert_eq!(cells.iter().map(|c| c.count).sum::<u32>(), 3);
+
+        let args = to_map(json!({"author": "Bob", "workspace": "/test"}));
+        let cells = handle_heatmap(&db, Some(args), None).expect("heatmap");
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].count, 1);
+
+        let args = to_map(json!({"days": 0}));
+        let result = handle_heatmap(&db, Some(args), None);
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_handle_slow_tests() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+        db.connection()
+            .execute_batch(
+                r#"
+                INSERT INTO workspaces (id, name, path, created_at, updated_at)
+                VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+                INSERT INTO test_runs (id, workspace_id, started_at)
+                VALUES ('run-1', 'ws-1', datetime('now'));
+                INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, duration_ms, created_at)
+                VALUES ('r-1', 'run-1', 'core', 'test_quick', 'passed', 3, datetime('now')),
+                       ('r-2', 'run-1', 'core', 'test_slow', 'passed', 800, datetime('now')),
+                       ('r-3', 'run-1', 'util', 'test_medium', 'passed', 50, datetime('now'));
+                "#,
+            )
+            .expect("insert results");
+
+        let stats = handle_slow_tests(&db, None, None).expect("slow tests");
+        let names: Vec<&str> = stats.iter().map(|s| s.test_name.as_str()).collect();
+        assert_eq!(names, vec!["test_slow", "test_medium", "test_quick"]);
+
+        let args = to_map(json!({"suite": "core", "top_n": 1, "workspace": "/test"}));
+        let stats = handle_slow_tests(&db, Some(args), None).expect("slow tests");
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].test_name, "test_slow");
+    }
+
+    #[test]
+    fn test_handle_tags() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+        db.connection()
+            .execute_batch(
+                r#"
+                INSERT INTO workspaces (id, name, path, created_at, updated_at)
+                VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+                VALUES ('c-1', 'ws-1', 'aaa111', 'First', 'Dev', '2026-01-10T10:00:00Z', datetime('now')),
+                       ('c-2', 'ws-1', 'bbb222', 'Second', 'Dev', '2026-01-17T10:00:00Z', datetime('now'));
+                INSERT INTO tags (id, workspace_id, name, sha, tagged_sha, is_annotated, timestamp, created_at)
+                VALUES ('t-1', 'ws-1', 'v1.0.0', 'aaa111', 'aaa111', 0, '2026-01-10T10:00:00Z', datetime('now')),
+                       ('t-2', 'ws-1', 'v1.1.0', 'bbb222', 'bbb222', 0, '2026-01-17T10:00:00Z', datetime('now'));
+                "#,
+            )
+            .expect("insert tags");
+
+        let tags = handle_tags(&db, None, None).expect("tags");
+        let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["v1.1.0", "v1.0.0"]);
+
+        let args = to_map(json!({"workspace": "/test", "limit": 1}));
+        let tags = handle_tags(&db, Some(args), None).expect("tags");
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].commit_subject, "Second");
+    }
+
+    #[test]
+    fn test_handle_suggest_queries_empty_db() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let suggestions = handle_suggest_queries(&db, None, None).expect("suggestions");
+        assert!(suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_handle_query() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"sql": "SELECT ?1 AS value", "params": ["hello"]}));
+        let rows = handle_query(&db, Some(args)).expect("query");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["value"], json!("hello"));
+
+        let args = to_map(json!({"sql": "SELECT * FROM commits"}));
+        let rows = handle_query(&db, Some(args)).expect("query");
+        assert!(rows.is_empty());
+    }
+
+    #[test]
+    fn test_handle_query_rejects_writes() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"sql": "DELETE FROM commits"}));
+        let result = handle_query(&db, Some(args));
+        assert!(matches!(
+            result,
+            Err(HandlerError::Database(DbError::ReadOnlyViolation(_)))
+        ));
+
+        let result = handle_query(&db, Some(to_map(json!({"sql": "  "}))));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_parse_copilot_stats_input_defaults() {
+        let input: CopilotStatsInput = parse_input(None).expect("parse");
+        assert_eq!(input.days, 7);
+        assert!(input.cost_per_1k_tokens.is_none());
+    }
+
+    #[test]
+    fn test_handle_copilot_stats_empty_db() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"days": 30, "cost_per_1k_tokens": 0.5}));
+        let stats = handle_copilot_stats(&db, Some(args)).expect("stats");
+        assert_eq!(stats.days, 30);
+        assert_eq!(stats.total_tokens, 0);
+    }
+
+    #[test]
+    fn test_handle_copilot_stats_negative_cost() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"cost_per_1k_tokens": -1.0}));
+        let result = handle_copilot_stats(&db, Some(args));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_handle_trending_topics() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let args = to_map(json!({"days": 14, "min_sessions": 1}));
+        let trends = handle_trending_topics(&db, Some(args)).expect("trends");
+        assert!(trends.is_empty());
+
+        let args = to_map(json!({"days": 0}));
+        let result = handle_trending_topics(&db, Some(args));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_handle_session_detail() {
+        let mut db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let ws_id = db.get_or_create_workspace("test", "/test").expect("ws");
+        let session_id = db
+            .insert_copilot_session(&crate::db::CopilotSessionRecord::new(
+                ws_id,
+                "chat-1".to_string(),
+            ))
+            .expect("session");
+        let now = chrono::Utc::now();
+        db.insert_copilot_messages_batch(&[
+            crate::db::CopilotMessageRecord::new(
+                session_id.clone(),
+                "user".to_string(),
+                "How do I parse JSON?".to_string(),
+                now,
+            ),
+            crate::db::CopilotMessageRecord::new(
+                session_id,
+                "assistant".to_string(),
+                "Use serde_json.".to_string(),
+                now + chrono::Duration::seconds(1),
+            ),
+        ])
+        .expect("messages");
+
+        let args = to_map(json!({"session_id": "chat-1"}));
+        let Ok(SessionDetailResponse::Turns(pairs)) = handle_session_detail(&db, Some(args)) else {
+            panic!("expected question-answer turns");
+        };
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(
+            pairs[0].answer.as_ref().map(|a| a.content.as_str()),
+            Some("Use serde_json.")
+        );
+
+        let args = to_map(json!({"session_id": "chat-1", "messages": true}));
+        let Ok(SessionDetailResponse::Session(session)) = handle_session_detail(&db, Some(args))
+        else {
+            panic!("expected the full session");
+        };
+        assert_eq!(session.vscode_session_id, "chat-1");
+        assert_eq!(session.messages.len(), 2);
+
+        for messages in [false, true] {
+            let args = to_map(json!({"session_id": "missing", "messages": messages}));
+            let result = handle_session_detail(&db, Some(args));
+            assert!(matches!(result, Err(HandlerError::NotFound(_))));
+        }
+
+        let args = to_map(json!({"session_id": ""}));
+        let result = handle_session_detail(&db, Some(args));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_handle_annotate() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+        let ws_id = db.get_or_create_workspace("test", "/test").expect("ws");
+
+        let args = to_map(json!({
+            "workspace": "/test",
+            "event_type": "deploy",
+            "title": "Shipped v2",
+            "event_at": "2026-04-01T10:00:00Z"
+        }));
+        let response = handle_annotate(&db, Some(args), None, |path| db.get_workspace_id(path))
+            .expect("annotate");
+        assert_eq!(response.workspace_id, ws_id);
+        assert_eq!(response.event_at, "2026-04-01T10:00:00+00:00");
+
+        let timeline = queries::get_timeline(db.connection(), 10, None).expect("timeline");
+        assert_eq!(timeline[0].event_type, "deploy");
+        assert_eq!(timeline[0].event_id, response.event_id);
+
+        let args = to_map(json!({"workspace": "/test", "event_type": "deploy", "title": ""}));
+        let result = handle_annotate(&db, Some(args), None, |path| db.get_workspace_id(path));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let args = to_map(json!({
+            "workspace": "/test",
+            "event_type": "deploy",
+            "title": "x",
+            "event_at": "yesterday"
+        }));
+        let result = handle_annotate(&db, Some(args), None, |path| db.get_workspace_id(path));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let args = to_map(json!({"workspace": "/nowhere", "event_type": "deploy", "title": "x"}));
+        let result = handle_annotate(&db, Some(args), None, |path| db.get_workspace_id(path));
+        assert!(matches!(result, Err(HandlerError::WorkspaceNotFound(_))));
+
+        // The resolver decides which workspace a path maps to
+        let args = to_map(json!({"workspace": "/cached", "event_type": "deploy", "title": "x"}));
+        let response = handle_annotate(&db, Some(args), None, |path| {
+            Ok((path == "/cached").then(|| ws_id.clone()))
+        })
+        .expect("annotate cached");
+        assert_eq!(response.workspace_id, ws_id);
+    }
+
+    #[test]
+    fn test_handle_workspaces() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let active = db.get_or_create_workspace("active", "/active").expect("ws");
+        db.get_or_create_workspace("idle", "/idle").expect("ws");
+        let record = crate::db::CommitRecord::new(
+            active,
+            "abc123".to_string(),
+            "Dev".to_string(),
+            None,
+            "Commit".to_string(),
+            chrono::Utc::now() - chrono::Duration::hours(1),
+        );
+        db.insert_commit(&record).expect("commit");
+
+        let workspaces = handle_workspaces(&db, None).expect("workspaces");
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0].workspace.path, "/active");
+        assert_eq!(workspaces[0].total_events_in_window, 1);
+
+        let value = serde_json::to_value(&workspaces).expect("json");
+        assert_eq!(value[0]["name"], "active");
+        assert!(value[0]["last_commit_at"].is_string());
+
+        let result = handle_workspaces(&db, Some(to_map(json!({"days": 0}))));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_handle_workspace_list() {
+        let mut db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+
+        let list = handle_workspace_list(&db).expect("list");
+        assert_eq!(list, json!([]));
+
+        let busy = db.get_or_create_workspace("busy", "/busy").expect("ws");
+        db.get_or_create_workspace("idle", "/idle").expect("ws");
+        let commits: Vec<_> = (0..3)
+            .map(|i| {
+                crate::db::CommitRecord::new(
+                    busy.clone(),
+                    format!("sha{i}"),
+                    "Dev".to_string(),
+                    None,
+                    format!("Commit {i}"),
+                    chrono::Utc::now(),
+                )
+            })
+            .collect();
+        db.insert_commits_batch(&commits).expect("commits");
+        db.insert_copilot_session(&crate::db::CopilotSessionRecord::new(
+            busy.clone(),
+            "chat-1".to_string(),
+        ))
+        .expect("session");
+
+        let list = handle_workspace_list(&db).expect("list");
+        let entries = list.as_array().expect("array");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["name"], "busy");
+        assert_eq!(entries[0]["id"], busy);
+        assert_eq!(entries[0]["path"], "/busy");
+        assert_eq!(entries[0]["commit_count"], 3);
+        assert_eq!(entries[0]["session_count"], 1);
+        assert!(entries[0]["created_at"].is_string());
+        assert_eq!(entries[1]["name"], "idle");
+        assert_eq!(entries[1]["commit_count"], 0);
+        assert_eq!(entries[1]["session_count"], 0);
+    }
+
     #[test]
     fn test_handle_commit_details_not_found() {
         let db = Database::in_memory().expect("create db");
         db.initialize().expect("init db");
-        let args = to_map(json!({ "sha": "nonexistent" }));
+        let args = to_map(json!({ "sha": "deadbeef" }));
         let result = handle_commit_details(&db, Some(args));
         assert!(matches!(result, Err(HandlerError::NotFound(_))));
     }
@@ -533,6 +2187,109 @@ mod tests {
         assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
     }
 
+    #[test]
+    fn test_handle_diff_validation() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+
+        let result = handle_diff(&db, Some(to_map(json!({ "sha": "" }))));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let result = handle_diff(&db, Some(to_map(json!({ "sha": "deadbeef" }))));
+        assert!(matches!(result, Err(HandlerError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_handle_diff_without_repository() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("ws", "/nonexistent/hindsight-diff-ws")
+            .expect("workspace");
+        let diff = hindsight_git::DiffSummary {
+            files_changed: 1,
+            insertions: 2,
+            deletions: 0,
+            files: vec![hindsight_git::FileDiff {
+                path: "src/main.rs".to_string(),
+                status: "modified".to_string(),
+                insertions: 2,
+                deletions: 0,
+            }],
+        };
+        let record = crate::db::CommitRecord::new(
+            ws_id.clone(),
+            "feed00112233".to_string(),
+            "Author".to_string(),
+            None,
+            "Commit".to_string(),
+            chrono::Utc::now(),
+        )
+        .with_diff_json(diff.to_json().expect("diff json"));
+        db.insert_commit(&record).expect("insert commit");
+
+        let result = handle_diff(&db, Some(to_map(json!({ "sha": "feed" })))).expect("diff");
+        assert_eq!(result.sha, "feed00112233");
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.files[0].path, "src/main.rs");
+        assert_eq!(result.files[0].additions, 2);
+        assert!(result.files[0].patch.is_none());
+
+        // Without a stored diff there is nothing to fall back to
+        let record = crate::db::CommitRecord::new(
+            ws_id,
+            "beef00112233".to_string(),
+            "Author".to_string(),
+            None,
+            "No diff".to_string(),
+            chrono::Utc::now(),
+        );
+        db.insert_commit(&record).expect("insert commit");
+
+        let result = handle_diff(&db, Some(to_map(json!({ "sha": "beef" }))));
+        assert!(matches!(result, Err(HandlerError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_handle_commit_details_ambiguous_prefix() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db.get_or_create_workspace("ws", "/ws").expect("workspace");
+        for sha in ["cafe0001", "cafe0002"] {
+            let record = crate::db::CommitRecord::new(
+                ws_id.clone(),
+                sha.to_string(),
+                "Author".to_string(),
+                None,
+                "Commit".to_string(),
+                chrono::Utc::now(),
+            );
+            db.insert_commit(&record).expect("insert commit");
+        }
+
+        let args = to_map(json!({ "sha": "cafe" }));
+        match handle_commit_details(&db, Some(args)) {
+            Err(HandlerError::InvalidInput(msg)) => {
+                assert_eq!(msg, "Ambiguous SHA prefix: 2 commits match");
+            }
+            other => panic!("expected ambiguity error, got {other:?}"),
+        }
+
+        let args = to_map(json!({ "sha": "cafe0002" }));
+        let commit = handle_commit_details(&db, Some(args)).expect("unique prefix");
+        assert_eq!(commit.sha, "cafe0002");
+
+        let args = to_map(json!({ "sha": "caf" }));
+        let result = handle_commit_details(&db, Some(args));
+        assert!(matches!(result, Err(HandlerError::InvalidInput(_))));
+
+        let args = to_map(json!({ "sha": "%%%%" }));
+        match handle_commit_details(&db, Some(args)) {
+            Err(HandlerError::InvalidInput(msg)) => assert!(msg.contains("hexadecimal")),
+            other => panic!("expected hex error, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_ingest_stats_response_conversion() {
         let stats = IngestStats {
@@ -542,6 +2299,9 @@ mod tests {
             test_results_inserted: 30,
             sessions_inserted: 2,
             messages_inserted: 20,
+            notes_inserted: 0,
+            tags_inserted: 0,
+            tests_listed: 0,
             warnings: 0,
         };
 
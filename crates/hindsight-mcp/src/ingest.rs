@@ -22,16 +22,29 @@
 //! println!("Ingested {} commits", stats.commits_inserted);
 //! ```
 
-use std::path::Path;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 
+use serde::Serialize;
 use thiserror::Error;
 use tracing::{debug, info, warn};
 
 use crate::db::{
-    CommitRecord, CopilotMessageRecord, CopilotSessionRecord, Database, DbError, TestResultRecord,
-    TestRunRecord,
+    BatchInsertStats, CommitRecord, CopilotMessageRecord, CopilotSessionRecord, Database, DbError,
+    IngestSource, NoteRecord, RetentionStats, TRUNCATION_MARKER, TestResultRecord, TestRunRecord,
+    WorkspaceRecord,
 };
-use hindsight_tests::TestOutcome;
+use crate::queries::{SESSION_TOPIC_LIMIT, extract_keywords};
+use hindsight_tests::{OutputFormat, TestOutcome};
+
+/// Commits per chunk when streaming git ingestion
+const STREAM_CHUNK_SIZE: usize = 500;
+
+/// One commit read from a lazy repository walk
+type GitWalkItem = Result<hindsight_git::CommitWithDiff, hindsight_git::GitError>;
+
+/// Copilot session files larger than this are skipped during ingestion
+const DEFAULT_MAX_SESSION_FILE_SIZE: u64 = 10 * 1024 * 1024;
 
 // ============================================================================
 // Error Types
@@ -66,6 +79,38 @@ pub enum IngestError {
         /// The workspace path that could not be found
         path: String,
     },
+
+    /// Invalid exclude path pattern
+    #[error("Invalid exclude pattern: {0}")]
+    InvalidPattern(#[from] glob::PatternError),
+
+    /// The workspace is not a git repository
+    #[error(
+        "The path `{path}` is not a git repository. Initialize it with `git init` or provide the correct workspace path."
+    )]
+    NotARepository {
+        /// The workspace path
+        path: String,
+    },
+
+    /// A git bundle could not be unpacked
+    #[error("Failed to unpack git bundle `{path}`: {message}")]
+    Bundle {
+        /// The bundle file path
+        path: String,
+        /// Why unpacking failed
+        message: String,
+    },
+}
+
+impl IngestError {
+    /// Format this error and every underlying cause, separated by `" → "`
+    ///
+    /// For example, a libgit2 failure renders as `Git error → object not found`.
+    #[must_use]
+    pub fn display_chain(&self) -> String {
+        hindsight_common::display_chain(self)
+    }
 }
 
 // ============================================================================
@@ -76,7 +121,11 @@ pub enum IngestError {
 pub type ProgressCallback = Box<dyn Fn(&ProgressEvent) + Send + Sync>;
 
 /// Progress event during ingestion
-#[derive(Debug, Clone)]
+///
+/// Serializes as a JSON object tagged by `type`, e.g.
+/// `{"type":"progress","source":"git","processed":50,"total":100}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ProgressEvent {
     /// Starting ingestion of a source
     Started {
@@ -123,6 +172,20 @@ pub struct IngestOptions {
     pub include_diffs: bool,
     /// Skip commits already in database (incremental sync)
     pub incremental: bool,
+    /// Glob patterns for file paths to leave out of stored diffs
+    pub exclude_paths: Vec<String>,
+    /// Extra tags attached to ingested test runs (overrides the detected `env` tag)
+    pub environment_tags: HashMap<String, String>,
+    /// Walk commits oldest-first, so parents are inserted before children
+    pub oldest_first: bool,
+    /// Truncate Copilot message content longer than this many bytes
+    pub max_copilot_message_bytes: Option<usize>,
+    /// Only ingest commits made since this tag
+    pub since_tag: Option<String>,
+    /// Only ingest commits reachable from at least one of these tags
+    pub tags_allowlist: Vec<String>,
+    /// Format of the test output passed to test ingestion
+    pub output_format: OutputFormat,
 }
 
 impl IngestOptions {
@@ -133,6 +196,13 @@ impl IngestOptions {
             commit_limit: None,
             include_diffs: true,
             incremental: false,
+            exclude_paths: Vec::new(),
+            environment_tags: HashMap::new(),
+            oldest_first: false,
+            max_copilot_message_bytes: None,
+            since_tag: None,
+            tags_allowlist: Vec::new(),
+            output_format: OutputFormat::Nextest,
         }
     }
 
@@ -143,6 +213,13 @@ impl IngestOptions {
             commit_limit: None,
             include_diffs: true,
             incremental: true,
+            exclude_paths: Vec::new(),
+            environment_tags: HashMap::new(),
+            oldest_first: false,
+            max_copilot_message_bytes: None,
+            since_tag: None,
+            tags_allowlist: Vec::new(),
+            output_format: OutputFormat::Nextest,
         }
     }
 
@@ -159,10 +236,76 @@ impl IngestOptions {
         self.include_diffs = true;
         self
     }
+
+    /// Exclude file paths matching any of the given glob patterns from diffs
+    ///
+    /// Useful for lock files, vendored dependencies, and generated code whose
+    /// changes would otherwise dominate the stored diff summaries.
+    #[must_use]
+    pub fn with_exclude_paths(mut self, patterns: Vec<String>) -> Self {
+        self.exclude_paths = patterns;
+        self
+    }
+
+    /// Ingest commits oldest-first
+    ///
+    /// Useful when seeding a new database. With a limit, the N oldest
+    /// commits are ingested instead of the N most recent.
+    #[must_use]
+    pub fn oldest_first(mut self) -> Self {
+        self.oldest_first = true;
+        self
+    }
+
+    /// Only ingest commits reachable from HEAD but not from `tag`
+    ///
+    /// Useful for ingesting the commits of a release in progress.
+    #[must_use]
+    pub fn with_since_tag(mut self, tag: &str) -> Self {
+        self.since_tag = Some(tag.to_string());
+        self
+    }
+
+    /// Only ingest commits that are ancestors of (or are) one of `tags`
+    ///
+    /// Like `git log v1.0.0 v1.1.0`, keeping commits from released versions
+    /// only. Commits are still walked from HEAD, and the limit applies before
+    /// filtering.
+    #[must_use]
+    pub fn with_tags_allowlist(mut self, tags: Vec<String>) -> Self {
+        self.tags_allowlist = tags;
+        self
+    }
+
+    /// Read test output written by the given test runner
+    ///
+    /// Defaults to [`OutputFormat::Nextest`].
+    #[must_use]
+    pub fn with_output_format(mut self, format: OutputFormat) -> Self {
+        self.output_format = format;
+        self
+    }
+
+    /// Attach an environment tag to ingested test runs
+    #[must_use]
+    pub fn with_environment_tag(mut self, key: &str, value: &str) -> Self {
+        self.environment_tags
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Truncate stored Copilot messages to at most `max_bytes` of content
+    ///
+    /// Truncated messages end with [`TRUNCATION_MARKER`].
+    #[must_use]
+    pub fn with_max_copilot_message_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_copilot_message_bytes = Some(max_bytes);
+        self
+    }
 }
 
 /// Statistics from an ingestion operation
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct IngestStats {
     /// Number of commits inserted
     pub commits_inserted: usize,
@@ -176,6 +319,12 @@ pub struct IngestStats {
     pub sessions_inserted: usize,
     /// Number of Copilot messages inserted
     pub messages_inserted: usize,
+    /// Number of git notes inserted or updated
+    pub notes_inserted: usize,
+    /// Number of git tags inserted or updated
+    pub tags_inserted: usize,
+    /// Number of tests recorded in the test inventory
+    pub tests_listed: usize,
     /// Number of warnings/errors encountered
     pub warnings: usize,
 }
@@ -189,6 +338,9 @@ impl IngestStats {
             + self.test_results_inserted
             + self.sessions_inserted
             + self.messages_inserted
+            + self.notes_inserted
+            + self.tags_inserted
+            + self.tests_listed
     }
 
     /// Merge stats from another operation
@@ -199,8 +351,67 @@ impl IngestStats {
         self.test_results_inserted += other.test_results_inserted;
         self.sessions_inserted += other.sessions_inserted;
         self.messages_inserted += other.messages_inserted;
+        self.notes_inserted += other.notes_inserted;
+        self.tags_inserted += other.tags_inserted;
+        self.tests_listed += other.tests_listed;
         self.warnings += other.warnings;
     }
+
+    /// Format the stats as a multi-line human-readable summary
+    ///
+    /// The notes, tags and test inventory lines are only included when
+    /// those were ingested.
+    #[must_use]
+    pub fn format_summary(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "Commits: +{} (skipped {})",
+                self.commits_inserted, self.commits_skipped
+            ),
+            format!(
+                "Test results: +{} in {} runs",
+                self.test_results_inserted, self.test_runs_inserted
+            ),
+            format!(
+                "Copilot: {} sessions, {} messages",
+                self.sessions_inserted, self.messages_inserted
+            ),
+        ];
+        if self.notes_inserted > 0 {
+            lines.push(format!("Notes: +{}", self.notes_inserted));
+        }
+        if self.tags_inserted > 0 {
+            lines.push(format!("Tags: +{}", self.tags_inserted));
+        }
+        if self.tests_listed > 0 {
+            lines.push(format!("Test inventory: {} tests", self.tests_listed));
+        }
+        lines.push(format!("Warnings: {}", self.warnings));
+        lines.join("\n")
+    }
+
+    /// Format the stats as pretty-printed JSON
+    #[must_use]
+    pub fn format_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Problems found by [`Ingestor::verify_database_consistency`]
+#[derive(Debug, Clone, Default)]
+pub struct ConsistencyReport {
+    /// Corrupt or dangling data
+    pub errors: Vec<String>,
+    /// Recoverable issues, such as a stale full-text index
+    pub warnings: Vec<String>,
+}
+
+impl ConsistencyReport {
+    /// Check whether no errors were found (warnings are allowed)
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.errors.is_empty()
+    }
 }
 
 // ============================================================================
@@ -243,6 +454,23 @@ impl Ingestor {
         &self.db
     }
 
+    /// Switch the connection to the bulk insert pragma profile
+    ///
+    /// Tuning is best-effort: a failure is logged and ingestion continues
+    /// with the current settings.
+    fn tune_for_write(&self) {
+        if let Err(e) = self.db.optimize_for_write() {
+            warn!(error = %e, "Failed to apply write pragma profile");
+        }
+    }
+
+    /// Switch the connection back to the query pragma profile
+    fn tune_for_read(&self) {
+        if let Err(e) = self.db.optimize_for_read() {
+            warn!(error = %e, "Failed to apply read pragma profile");
+        }
+    }
+
     /// Consume the ingestor and return the owned database
     ///
     /// This is useful when you need to transfer ownership of the database
@@ -270,117 +498,485 @@ impl Ingestor {
 
         info!(path = %repo_path_str, "Starting git ingestion");
 
-        // Get or create workspace
-        let workspace_name = repo_path
+        // Open repository before creating a workspace for it
+        let git_repo = hindsight_git::GitRepo::open(repo_path).map_err(|e| match e {
+            hindsight_git::GitError::NotARepository { .. } => IngestError::NotARepository {
+                path: repo_path_str.clone(),
+            },
+            other => IngestError::Git(other),
+        })?;
+
+        let workspace = WorkspaceRecord::from_path(repo_path);
+        let workspace_name = workspace.as_ref().map_or("unknown", |w| w.name.as_str());
+        self.ingest_git_repo(&git_repo, workspace_name, &repo_path_str, options)
+    }
+
+    /// Ingest git commits from a `git bundle` file
+    ///
+    /// Bundles are what CI systems produce with `git bundle create` for
+    /// offline transfer. libgit2 cannot read them, so the bundle is cloned
+    /// into a temporary bare repository with the `git` command, which must be
+    /// on `PATH`. Commits are then walked exactly as by [`Self::ingest_git`]
+    /// and stored under the workspace `workspace_path`. The temporary
+    /// repository is removed afterwards, whether or not ingestion succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IngestError::Bundle` if the bundle cannot be unpacked, or an
+    /// error if commits cannot be read or inserted.
+    pub fn ingest_git_bundle(
+        &mut self,
+        bundle_path: &Path,
+        workspace_path: &str,
+        options: &IngestOptions,
+    ) -> Result<IngestStats, IngestError> {
+        let bundle_path_str = bundle_path.display().to_string();
+        let bundle_error = |message: String| IngestError::Bundle {
+            path: bundle_path_str.clone(),
+            message,
+        };
+
+        info!(bundle = %bundle_path_str, workspace = %workspace_path, "Starting git bundle ingestion");
+
+        if !bundle_path.is_file() {
+            return Err(bundle_error("no such file".to_string()));
+        }
+
+        let clone = TempDir::new("bundle");
+        let output = std::process::Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg("--quiet")
+            // Keep a bundle path starting with `-` from being read as an option
+            .arg("--")
+            .arg(bundle_path)
+            .arg(clone.path())
+            .output()
+            .map_err(|e| bundle_error(format!("could not run git: {e}")))?;
+        if !output.status.success() {
+            return Err(bundle_error(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+
+        let git_repo = hindsight_git::GitRepo::open(clone.path())?;
+        let workspace_name = Path::new(workspace_path)
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown");
+        self.ingest_git_repo(&git_repo, workspace_name, workspace_path, options)
+    }
+
+    /// Walk an open repository and store its commits under a workspace
+    fn ingest_git_repo(
+        &mut self,
+        git_repo: &hindsight_git::GitRepo,
+        workspace_name: &str,
+        workspace_path: &str,
+        options: &IngestOptions,
+    ) -> Result<IngestStats, IngestError> {
+        // Get or create workspace
         let workspace_id = self
             .db
-            .get_or_create_workspace(workspace_name, &repo_path_str)?;
+            .get_or_create_workspace(workspace_name, workspace_path)?;
+
+        // Other workspaces checked out from the same repository already hold
+        // any commits they share with this one
+        let sibling_workspaces = self.sibling_workspaces(git_repo, &workspace_id)?;
 
-        // Open repository
-        let git_repo = hindsight_git::GitRepo::open(repo_path)?;
+        // Compile exclude patterns up front so a bad pattern fails fast
+        let exclude_patterns = options
+            .exclude_paths
+            .iter()
+            .map(|p| glob::Pattern::new(p))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Build walk options
+        // Build walk options. Line statistics are always kept, even without diffs.
         let mut walk_opts = if let Some(limit) = options.commit_limit {
             hindsight_git::WalkOptions::latest(limit)
         } else {
             hindsight_git::WalkOptions::default()
-        };
+        }
+        .with_stats();
 
         if options.include_diffs {
             walk_opts = walk_opts.with_diff();
         }
+        if options.oldest_first {
+            walk_opts = walk_opts.oldest_first();
+        }
+        if let Some(ref tag) = options.since_tag {
+            walk_opts = walk_opts.excluding(&git_repo.tag_target(tag)?);
+        }
+
+        // Resolve the allowlist before walking so a missing tag fails fast
+        let allowed_shas = if options.tags_allowlist.is_empty() {
+            None
+        } else {
+            let mut shas = HashSet::new();
+            for tag in &options.tags_allowlist {
+                shas.extend(git_repo.commits_reachable_from_tag(tag)?);
+            }
+            Some(shas)
+        };
 
-        // Get last ingested SHA for incremental sync
-        let last_sha = if options.incremental {
-            self.get_last_ingested_sha(&workspace_id)?
+        // An interrupted newest-first walk stored everything from its start
+        // down to the checkpoint, so older history is resumed from there
+        let checkpoint = if options.incremental && !options.oldest_first {
+            self.db.get_checkpoint(&workspace_id, IngestSource::Git)?
         } else {
             None
         };
+        let resume_opts = checkpoint.as_deref().map(|sha| walk_opts.clone().from(sha));
+
+        // Stop at last ingested SHA for incremental sync. Walking
+        // oldest-first, older commits come first and are skipped below.
+        if options.incremental
+            && !options.oldest_first
+            && let Some(last) = self.get_last_ingested_sha(&workspace_id)?
+        {
+            walk_opts = walk_opts.since_sha(&last);
+        }
 
-        // Walk commits
-        let commits = git_repo.walk_commits(&walk_opts)?;
-        let total = commits.len();
+        // Walk commits lazily; they are stored a chunk at a time as they are read
+        let mut commits: Box<dyn Iterator<Item = GitWalkItem> + '_> =
+            Box::new(git_repo.commits(&walk_opts)?);
+        if let Some(resume_opts) = resume_opts {
+            match git_repo.commits(&resume_opts) {
+                Ok(resumed) => {
+                    info!(checkpoint = ?checkpoint, "Resuming interrupted git ingestion");
+                    commits = Box::new(commits.chain(resumed));
+                }
+                Err(e) => warn!(error = %e, "Ignoring unreadable ingest checkpoint"),
+            }
+        }
+        let commits = commits.filter(|item| match (&allowed_shas, item) {
+            (Some(allowed), Ok(c)) => allowed.contains(&c.commit.sha),
+            _ => true,
+        });
 
         self.report(ProgressEvent::Started {
             source: "git".to_string(),
-            total_items: Some(total),
+            total_items: None,
         });
 
-        let mut stats = IngestStats::default();
-        let mut records = Vec::new();
-
-        for (idx, commit_with_diff) in commits.into_iter().enumerate() {
-            let commit = &commit_with_diff.commit;
+        self.tune_for_write();
+        let inserted = self.stream_git_commits(
+            &workspace_id,
+            commits,
+            &sibling_workspaces,
+            &exclude_patterns,
+        );
+        self.tune_for_read();
+        let batch = inserted?;
+        let mut stats = IngestStats {
+            commits_inserted: batch.inserted,
+            commits_skipped: batch.skipped,
+            ..Default::default()
+        };
 
-            // Stop at last ingested SHA for incremental sync
-            if let Some(ref last) = last_sha
-                && &commit.sha == last
-            {
-                debug!(sha = %commit.sha, "Reached last ingested commit, stopping");
-                break;
+        // Tags are secondary to the history, so failing to read them is only a warning
+        match self.store_tags(git_repo, &workspace_id) {
+            Ok(count) => stats.tags_inserted = count,
+            Err(e) => {
+                warn!(error = %e, "Failed to ingest git tags");
+                stats.warnings += 1;
             }
+        }
 
-            // Check if commit already exists
-            if options.incremental
-                && self
-                    .db
-                    .get_commit_by_sha(&workspace_id, &commit.sha)
-                    .is_ok()
-            {
-                stats.commits_skipped += 1;
-                continue;
-            }
+        self.db.record_ingest(&workspace_id, IngestSource::Git)?;
+        self.db.reset_checkpoint(&workspace_id, IngestSource::Git)?;
 
-            // Convert to record
-            let mut record = CommitRecord::new(
-                workspace_id.clone(),
-                commit.sha.clone(),
-                commit.author.clone(),
-                Some(commit.author_email.clone()),
-                commit.message.clone(),
-                commit.timestamp,
-            )
-            .with_parents(commit.parents.clone());
+        info!(
+            inserted = batch.inserted,
+            skipped = stats.commits_skipped,
+            "Git ingestion complete"
+        );
 
-            // Add diff if available
-            if let Some(ref diff) = commit_with_diff.diff {
-                let diff_json = serde_json::to_string(diff)?;
-                record = record.with_diff_json(diff_json);
-            }
+        self.report(ProgressEvent::Completed {
+            source: "git".to_string(),
+            stats: stats.clone(),
+        });
 
-            records.push(record);
+        Ok(stats)
+    }
 
-            // Report progress every 10 items
-            if (idx + 1) % 10 == 0 {
-                self.report(ProgressEvent::Progress {
-                    source: "git".to_string(),
-                    processed: idx + 1,
-                    total: Some(total),
-                });
-            }
+    /// Return the IDs of other workspaces sharing this workspace's repository
+    /// fingerprint
+    ///
+    /// The fingerprint walks the full history, so it is computed and recorded
+    /// only on a workspace's first ingestion and read back afterwards. A
+    /// repository whose fingerprint cannot be computed is treated as having
+    /// no siblings.
+    fn sibling_workspaces(
+        &self,
+        git_repo: &hindsight_git::GitRepo,
+        workspace_id: &str,
+    ) -> Result<Vec<String>, IngestError> {
+        let fingerprint = match self.db.get_workspace_fingerprint(workspace_id)? {
+            Some(fingerprint) => fingerprint,
+            None => match git_repo.repo_fingerprint() {
+                Ok(Some(fingerprint)) => {
+                    self.db
+                        .set_workspace_fingerprint(workspace_id, &fingerprint)?;
+                    fingerprint
+                }
+                Ok(None) => return Ok(Vec::new()),
+                Err(e) => {
+                    warn!(error = %e, "Failed to fingerprint repository");
+                    return Ok(Vec::new());
+                }
+            },
+        };
+
+        Ok(self
+            .db
+            .get_workspaces_by_fingerprint(&fingerprint)?
+            .into_iter()
+            .map(|w| w.id)
+            .filter(|id| id != workspace_id)
+            .collect())
+    }
+
+    /// Convert and insert commits a chunk at a time, reporting progress per chunk
+    ///
+    /// `commits` is consumed lazily, so only one chunk of commits (and their
+    /// diff JSON) sits in memory however long the history is. Commits already
+    /// stored, here or under a sibling workspace, are counted as skipped.
+    /// Each chunk also checkpoints the last SHA it read, the oldest when
+    /// walking newest-first, so an interrupted ingestion can resume.
+    fn stream_git_commits(
+        &mut self,
+        workspace_id: &str,
+        commits: impl Iterator<Item = GitWalkItem>,
+        sibling_workspaces: &[String],
+        exclude_patterns: &[glob::Pattern],
+    ) -> Result<BatchInsertStats, IngestError> {
+        let mut walk_error = None;
+        let records = commits.map_while(|item| {
+            item.map_err(IngestError::from)
+                .and_then(|c| commit_record(workspace_id, &c, exclude_patterns))
+                .map_err(|e| walk_error = Some(e))
+                .ok()
+        });
+
+        let progress = &self.progress;
+        let mut processed = 0;
+        let inserted =
+            self.db
+                .stream_commits(workspace_id, records, STREAM_CHUNK_SIZE, |tx, chunk| {
+                    let mut count = 0;
+                    for record in chunk {
+                        // Skip commits already stored under a clone of the same repository
+                        if !Database::commit_exists_in_tx(tx, sibling_workspaces, &record.sha)? {
+                            count +=
+                                Database::insert_commits_in_tx(tx, std::slice::from_ref(record))?;
+                        }
+                    }
+                    if let Some(oldest) = chunk.last() {
+                        Database::set_checkpoint_in_tx(
+                            tx,
+                            workspace_id,
+                            IngestSource::Git,
+                            &oldest.sha,
+                        )?;
+                    }
+                    processed += chunk.len();
+                    if let Some(callback) = progress {
+                        callback(&ProgressEvent::Progress {
+                            source: "git".to_string(),
+                            processed,
+                            total: None,
+                        });
+                    }
+                    Ok(count)
+                })?;
+
+        match walk_error {
+            Some(e) => Err(e),
+            None => Ok(BatchInsertStats {
+                inserted,
+                skipped: processed - inserted,
+            }),
         }
+    }
+
+    /// Ingest git notes attached to already-ingested commits
+    ///
+    /// `namespace` is a notes reference such as `refs/notes/ci`; `None` uses
+    /// the default `refs/notes/commits`. Notes on commits that have not been
+    /// ingested yet are skipped, so run [`Ingestor::ingest_git`] first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be opened or database operations fail.
+    pub fn ingest_git_notes(
+        &mut self,
+        workspace_path: impl AsRef<Path>,
+        namespace: Option<&str>,
+    ) -> Result<IngestStats, IngestError> {
+        let workspace_path = workspace_path.as_ref();
+        let workspace_path_str = workspace_path.display().to_string();
+        let namespace_ref = namespace.unwrap_or("refs/notes/commits");
 
-        // Batch insert
-        let inserted = self.db.insert_commits_batch(&records)?;
-        stats.commits_inserted = inserted;
+        info!(path = %workspace_path_str, namespace = %namespace_ref, "Starting git notes ingestion");
+
+        let workspace_name = workspace_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        let workspace_id = self
+            .db
+            .get_or_create_workspace(workspace_name, &workspace_path_str)?;
+
+        let git_repo = hindsight_git::GitRepo::open(workspace_path)?;
+        let notes = git_repo.list_notes(namespace)?;
+
+        self.report(ProgressEvent::Started {
+            source: "git_notes".to_string(),
+            total_items: Some(notes.len()),
+        });
+
+        let mut stats = IngestStats::default();
+
+        for (sha, content) in notes {
+            let commit = match self.db.get_commit_by_sha(&workspace_id, &sha) {
+                Ok(commit) => commit,
+                Err(DbError::NotFound { .. }) => {
+                    debug!(sha = %sha, "Skipping note for commit that has not been ingested");
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let record = NoteRecord::new(commit.id, namespace_ref.to_string(), content);
+            self.db.insert_note(&record)?;
+            stats.notes_inserted += 1;
+        }
 
         info!(
-            inserted = inserted,
-            skipped = stats.commits_skipped,
-            "Git ingestion complete"
+            inserted = stats.notes_inserted,
+            "Git notes ingestion complete"
         );
 
         self.report(ProgressEvent::Completed {
-            source: "git".to_string(),
+            source: "git_notes".to_string(),
             stats: stats.clone(),
         });
 
         Ok(stats)
     }
 
+    /// Ingest the tags of a repository
+    ///
+    /// Runs as part of [`Ingestor::ingest_git`]; call this directly to pick
+    /// up new tags without walking the history again. Tags on commits that
+    /// have not been ingested are skipped, and tags deleted from the
+    /// repository are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be opened or database operations fail.
+    pub fn ingest_tags(
+        &mut self,
+        workspace_path: impl AsRef<Path>,
+    ) -> Result<IngestStats, IngestError> {
+        let workspace_path = workspace_path.as_ref();
+        let workspace_path_str = workspace_path.display().to_string();
+
+        info!(path = %workspace_path_str, "Starting git tag ingestion");
+
+        let workspace_name = workspace_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        let workspace_id = self
+            .db
+            .get_or_create_workspace(workspace_name, &workspace_path_str)?;
+
+        let git_repo = hindsight_git::GitRepo::open(workspace_path)?;
+        let stats = IngestStats {
+            tags_inserted: self.store_tags(&git_repo, &workspace_id)?,
+            ..Default::default()
+        };
+
+        info!(inserted = stats.tags_inserted, "Git tag ingestion complete");
+
+        Ok(stats)
+    }
+
+    /// Read a repository's tags and store those on ingested commits
+    fn store_tags(
+        &mut self,
+        git_repo: &hindsight_git::GitRepo,
+        workspace_id: &str,
+    ) -> Result<usize, IngestError> {
+        let tags = git_repo.get_tags()?;
+        Ok(self.db.insert_tags_batch(workspace_id, &tags)?)
+    }
+
+    /// Find the commits that changed a file by querying git directly
+    ///
+    /// Works even when commits were ingested without diffs. Commits already
+    /// in the database are returned as stored; commits that have not been
+    /// ingested are built from git and carry the workspace ID if the
+    /// workspace is known (an empty string otherwise). Nothing is inserted.
+    ///
+    /// `file_path` is relative to the repository root.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be opened or walked, or a
+    /// database lookup fails.
+    pub fn query_file_history_live(
+        &self,
+        workspace_path: &Path,
+        file_path: &str,
+        limit: usize,
+    ) -> Result<Vec<CommitRecord>, IngestError> {
+        let workspace_path_str = workspace_path.display().to_string();
+
+        let git_repo = hindsight_git::GitRepo::open(workspace_path).map_err(|e| match e {
+            hindsight_git::GitError::NotARepository { .. } => IngestError::NotARepository {
+                path: workspace_path_str.clone(),
+            },
+            other => IngestError::Git(other),
+        })?;
+        let workspace_id = self.db.get_workspace_id(&workspace_path_str)?;
+
+        let commits = git_repo.find_commits_touching_file(file_path, limit)?;
+
+        let mut records = Vec::with_capacity(commits.len());
+        for commit in commits {
+            if let Some(ref id) = workspace_id {
+                match self.db.get_commit_by_sha(id, &commit.sha) {
+                    Ok(record) => {
+                        records.push(record);
+                        continue;
+                    }
+                    Err(DbError::NotFound { .. }) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            let mut record = CommitRecord::new(
+                workspace_id.clone().unwrap_or_default(),
+                commit.sha.clone(),
+                commit.author.clone(),
+                Some(commit.author_email.clone()),
+                commit.message.clone(),
+                commit.timestamp,
+            )
+            .with_parents(commit.parents.clone());
+            if commit.is_revert() {
+                record = record.with_revert(commit.reverted_sha());
+            }
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
     /// Get the SHA of the most recently ingested commit for a workspace
     fn get_last_ingested_sha(&self, workspace_id: &str) -> Result<Option<String>, IngestError> {
         let result: Result<String, _> = self.db.connection().query_row(
@@ -400,22 +996,103 @@ impl Ingestor {
     // Test Result Ingestion
     // ========================================================================
 
-    /// Ingest test results from nextest output
+    /// Ingest the test inventory from `cargo nextest list --message-format json`
+    ///
+    /// Records every test the workspace defines, including ignored tests and
+    /// tests that have never run, so tests that disappear between listings
+    /// can be detected. See [`Database::insert_test_inventory`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON cannot be parsed or the inventory cannot be stored.
+    pub fn ingest_test_list(
+        &mut self,
+        workspace_path: impl AsRef<Path>,
+        list_json: &str,
+    ) -> Result<IngestStats, IngestError> {
+        let workspace_path = workspace_path.as_ref();
+        let workspace_path_str = workspace_path.display().to_string();
+
+        info!(path = %workspace_path_str, "Starting test inventory ingestion");
+
+        let list = hindsight_tests::parse_list_output(list_json)?;
+
+        let workspace_name = workspace_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        let workspace_id = self
+            .db
+            .get_or_create_workspace(workspace_name, &workspace_path_str)?;
+
+        let stats = IngestStats {
+            tests_listed: self.db.insert_test_inventory(&workspace_id, &list)?,
+            ..Default::default()
+        };
+
+        info!(
+            listed = stats.tests_listed,
+            "Test inventory ingestion complete"
+        );
+
+        Ok(stats)
+    }
+
+    /// Ingest test results from nextest or `cargo test` JSON output
+    ///
+    /// `format` selects the parser for `test_output`. The test run is tagged
+    /// with `{"env": "ci"}` or `{"env": "local"}` based on the current
+    /// environment (see [`detect_environment`]).
     ///
     /// # Errors
     ///
     /// Returns an error if the output cannot be parsed or results cannot be inserted.
     pub fn ingest_tests(
+        &mut self,
+        workspace_path: impl AsRef<Path>,
+        test_output: &str,
+        format: OutputFormat,
+        commit_sha: Option<&str>,
+    ) -> Result<IngestStats, IngestError> {
+        self.ingest_tests_with_options(
+            workspace_path,
+            test_output,
+            commit_sha,
+            &IngestOptions::default().with_output_format(format),
+        )
+    }
+
+    /// Ingest test results from nextest output with custom options
+    ///
+    /// The output is parsed according to `options.output_format`. Tags from
+    /// `options.environment_tags` are applied on top of the detected `env`
+    /// tag, so an explicit `env` entry overrides auto-detection. Empty or
+    /// whitespace-only output is logged and skipped without creating a run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be parsed or results cannot be inserted.
+    pub fn ingest_tests_with_options(
         &mut self,
         workspace_path: impl AsRef<Path>,
         nextest_output: &str,
         commit_sha: Option<&str>,
+        options: &IngestOptions,
     ) -> Result<IngestStats, IngestError> {
         let workspace_path = workspace_path.as_ref();
         let workspace_path_str = workspace_path.display().to_string();
 
         info!(path = %workspace_path_str, "Starting test ingestion");
 
+        let summary = match options.output_format.parse(nextest_output) {
+            Ok(summary) => summary,
+            Err(hindsight_tests::TestsError::EmptyInput) => {
+                warn!(path = %workspace_path_str, "Test output is empty; nothing to ingest");
+                return Ok(IngestStats::default());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
         // Get or create workspace
         let workspace_name = workspace_path
             .file_name()
@@ -425,8 +1102,19 @@ impl Ingestor {
             .db
             .get_or_create_workspace(workspace_name, &workspace_path_str)?;
 
-        // Parse nextest output
-        let summary = hindsight_tests::parse_run_output(nextest_output)?;
+        // Windows are matched to results by normalized test name; a name
+        // repeated across test binaries takes its windows in output order.
+        // `cargo test` runs one binary at a time, so its output has no
+        // cross-suite parallelism to record.
+        let mut windows: HashMap<String, VecDeque<hindsight_tests::EventWindow>> = HashMap::new();
+        if options.output_format == OutputFormat::Nextest {
+            for window in hindsight_tests::parse_event_windows(nextest_output)? {
+                windows
+                    .entry(window.name.clone())
+                    .or_default()
+                    .push_back(window);
+            }
+        }
 
         self.report(ProgressEvent::Started {
             source: "tests".to_string(),
@@ -442,12 +1130,35 @@ impl Ingestor {
             summary.ignored as i32,
         );
 
+        // Nextest reports elapsed time rather than timestamps, so count back
+        // from when the run finished
+        let run_record = match (run_record.finished_at, summary.exec_time_secs) {
+            (Some(finished), secs) if secs > 0.0 => {
+                let elapsed = chrono::Duration::milliseconds((secs * 1000.0).round() as i64);
+                run_record.with_timing(finished - elapsed, finished)
+            }
+            _ => run_record,
+        };
+
         let run_record = if let Some(sha) = commit_sha {
             run_record.with_commit(sha)
         } else {
             run_record
         };
 
+        // Tag the run with its environment; sort explicit tags for stable JSON
+        let mut run_record = run_record.with_environment_tag("env", detect_environment());
+        let mut tags: Vec<_> = options.environment_tags.iter().collect();
+        tags.sort();
+        for (key, value) in tags {
+            run_record = run_record.with_environment_tag(key, value);
+        }
+
+        // Per-suite totals, sorted by name for stable JSON
+        let mut suites: Vec<_> = summary.suites().into_values().collect();
+        suites.sort_by(|a, b| a.name.cmp(&b.name));
+        let run_record = run_record.with_suites(&suites);
+
         let run_id = self.db.insert_test_run(&run_record)?;
         stats.test_runs_inserted = 1;
 
@@ -472,13 +1183,21 @@ impl Ingestor {
                     record = record.with_output(Some(output.as_str()), None);
                 }
 
+                if let Some(window) = windows.get_mut(&r.name).and_then(VecDeque::pop_front) {
+                    record = record.with_event_window(window.started, window.finished);
+                }
+
                 record
             })
             .collect();
 
         // Batch insert results
-        let inserted = self.db.insert_test_results_batch(&result_records)?;
+        self.tune_for_write();
+        let inserted = self.db.insert_test_results_batch(&result_records);
+        self.tune_for_read();
+        let inserted = inserted?;
         stats.test_results_inserted = inserted;
+        self.db.record_ingest(&workspace_id, IngestSource::Tests)?;
 
         info!(
             run_id = %run_id,
@@ -494,18 +1213,70 @@ impl Ingestor {
         Ok(stats)
     }
 
+    /// Store the GitHub Actions annotations in a CI log against a test run
+    ///
+    /// `::error` and `::warning` commands are extracted with
+    /// [`hindsight_tests::parse_github_annotations`]. Returns the number of
+    /// annotations stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IngestError::WorkspaceNotFound` if the workspace has never been
+    /// ingested, and a `DbError::NotFound` if the run does not belong to it.
+    pub fn ingest_ci_annotations(
+        &mut self,
+        workspace_path: impl AsRef<Path>,
+        log_content: &str,
+        run_id: &str,
+    ) -> Result<usize, IngestError> {
+        let workspace_path_str = workspace_path.as_ref().display().to_string();
+
+        let workspace_id = self
+            .db
+            .get_workspace_id(&workspace_path_str)?
+            .ok_or_else(|| IngestError::WorkspaceNotFound {
+                path: workspace_path_str.clone(),
+            })?;
+        let run = self.db.get_test_run_by_id(run_id)?;
+        if run.workspace_id != workspace_id {
+            return Err(DbError::NotFound {
+                table: "test_runs".to_string(),
+                id: run_id.to_string(),
+            }
+            .into());
+        }
+
+        let annotations = hindsight_tests::parse_github_annotations(log_content);
+        let inserted = self.db.insert_ci_annotations(run_id, &annotations)?;
+
+        info!(
+            path = %workspace_path_str,
+            run_id = %run_id,
+            annotations = inserted,
+            "CI annotation ingestion complete"
+        );
+
+        Ok(inserted)
+    }
+
     // ========================================================================
     // Copilot Ingestion
     // ========================================================================
 
     /// Ingest Copilot sessions from VS Code storage
     ///
+    /// By default only sessions recorded for exactly `workspace_path` are
+    /// ingested. With `scope_prefix`, sessions from every VS Code workspace
+    /// under that directory are ingested into the workspace instead.
+    ///
     /// # Errors
     ///
     /// Returns an error if sessions cannot be discovered or inserted.
     pub fn ingest_copilot(
         &mut self,
         workspace_path: impl AsRef<Path>,
+        options: &IngestOptions,
+        scope_prefix: Option<&Path>,
     ) -> Result<IngestStats, IngestError> {
         let workspace_path = workspace_path.as_ref();
         let workspace_path_str = workspace_path.display().to_string();
@@ -521,9 +1292,16 @@ impl Ingestor {
             .db
             .get_or_create_workspace(workspace_name, &workspace_path_str)?;
 
-        // Discover sessions
-        let discovery = hindsight_copilot::SessionDiscovery::new()?;
-        let sessions = discovery.discover_sessions_for_workspace(workspace_path)?;
+        // Discover sessions from every installed editor
+        let discovery =
+            hindsight_copilot::SessionDiscovery::new(hindsight_copilot::EditionHint::Any)?
+                .with_max_file_size(DEFAULT_MAX_SESSION_FILE_SIZE);
+        let sessions = match scope_prefix {
+            Some(prefix) => discovery
+                .with_path_prefix_filter(prefix)
+                .discover_sessions()?,
+            None => discovery.discover_sessions_for_workspace(workspace_path)?,
+        };
 
         self.report(ProgressEvent::Started {
             source: "copilot".to_string(),
@@ -533,7 +1311,7 @@ impl Ingestor {
         let mut stats = IngestStats::default();
 
         for (idx, discovered) in sessions.iter().enumerate() {
-            match self.ingest_single_session(&workspace_id, discovered) {
+            match self.ingest_single_session(&workspace_id, discovered, options) {
                 Ok(session_stats) => {
                     stats.merge(&session_stats);
                 }
@@ -566,6 +1344,8 @@ impl Ingestor {
             messages = stats.messages_inserted,
             "Copilot ingestion complete"
         );
+        self.db
+            .record_ingest(&workspace_id, IngestSource::Copilot)?;
 
         self.report(ProgressEvent::Completed {
             source: "copilot".to_string(),
@@ -580,6 +1360,7 @@ impl Ingestor {
         &mut self,
         workspace_id: &str,
         discovered: &hindsight_copilot::DiscoveredSession,
+        options: &IngestOptions,
     ) -> Result<IngestStats, IngestError> {
         let mut stats = IngestStats::default();
 
@@ -590,9 +1371,17 @@ impl Ingestor {
         )?;
 
         // Create session record
-        let session_record =
+        let mut session_record =
             CopilotSessionRecord::new(workspace_id.to_string(), session.id.clone())
-                .with_metadata(session.model.as_deref(), session.mode.as_deref());
+                .with_metadata(session.model.as_deref(), session.mode.as_deref())
+                .with_token_estimate(session.estimated_tokens())
+                .with_topics(&extract_keywords(
+                    session.user_messages().iter().map(|m| m.content.as_str()),
+                    SESSION_TOPIC_LIMIT,
+                ));
+        if let (Some(first), Some(last)) = (session.messages.first(), session.messages.last()) {
+            session_record = session_record.with_message_span(first.timestamp, last.timestamp);
+        }
 
         let db_session_id = self.db.insert_copilot_session(&session_record)?;
 
@@ -605,37 +1394,14 @@ impl Ingestor {
 
         stats.sessions_inserted = 1;
 
-        // Convert messages to records
-        let message_records: Vec<CopilotMessageRecord> = session
-            .messages
-            .iter()
-            .map(|m| {
-                let role = match m.role {
-                    hindsight_copilot::MessageRole::User => "user",
-                    hindsight_copilot::MessageRole::Assistant => "assistant",
-                    hindsight_copilot::MessageRole::System => "system",
-                };
-
-                let mut record = CopilotMessageRecord::new(
-                    db_session_id.clone(),
-                    role.to_string(),
-                    m.content.clone(),
-                    m.timestamp,
-                );
-
-                // Add variables if present
-                if !m.variables.is_empty()
-                    && let Ok(json) = serde_json::to_string(&m.variables)
-                {
-                    record = record.with_variables_json(json);
-                }
-
-                record
-            })
-            .collect();
+        let message_records =
+            message_records(&db_session_id, &session, options.max_copilot_message_bytes);
 
         // Batch insert messages
-        let inserted = self.db.insert_copilot_messages_batch(&message_records)?;
+        self.tune_for_write();
+        let inserted = self.db.insert_copilot_messages_batch(&message_records);
+        self.tune_for_read();
+        let inserted = inserted?;
         stats.messages_inserted = inserted;
 
         Ok(stats)
@@ -647,7 +1413,8 @@ impl Ingestor {
 
     /// Ingest all available data sources for a workspace
     ///
-    /// This combines git, tests (if available), and Copilot sessions.
+    /// This combines git, tests (if available), and Copilot sessions. The
+    /// workspace's retention policies are applied afterwards.
     ///
     /// # Errors
     ///
@@ -673,7 +1440,7 @@ impl Ingestor {
         }
 
         // Ingest Copilot sessions
-        match self.ingest_copilot(workspace_path) {
+        match self.ingest_copilot(workspace_path, options, None) {
             Ok(stats) => total_stats.merge(&stats),
             Err(e) => {
                 warn!(error = %e, "Copilot ingestion failed");
@@ -681,6 +1448,13 @@ impl Ingestor {
             }
         }
 
+        if let Some(workspace_id) = self
+            .db
+            .get_workspace_id(&workspace_path.display().to_string())?
+        {
+            self.enforce_retention(&workspace_id, &mut total_stats);
+        }
+
         info!(
             commits = total_stats.commits_inserted,
             sessions = total_stats.sessions_inserted,
@@ -691,6 +1465,173 @@ impl Ingestor {
 
         Ok(total_stats)
     }
+
+    /// Ingest git commits and Copilot sessions for every known workspace
+    ///
+    /// Workspaces whose path no longer exists are skipped. A source that
+    /// fails for one workspace is logged and counted in that workspace's
+    /// [`IngestStats::warnings`] without stopping the others. Each workspace's
+    /// retention policies are applied after its sources are ingested.
+    ///
+    /// Returns the stats of each ingested workspace, keyed by workspace ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the workspaces cannot be listed.
+    pub fn ingest_all_workspaces(
+        &mut self,
+        options: &IngestOptions,
+    ) -> Result<HashMap<String, IngestStats>, IngestError> {
+        let workspaces = self.db.list_workspaces()?;
+        let mut all_stats = HashMap::new();
+
+        info!(
+            workspaces = workspaces.len(),
+            "Starting ingestion of all workspaces"
+        );
+
+        for workspace in workspaces {
+            let path = Path::new(&workspace.path);
+            if !path.exists() {
+                warn!(
+                    workspace = %workspace.id,
+                    path = %workspace.path,
+                    "Workspace path no longer exists; skipping"
+                );
+                continue;
+            }
+
+            let mut stats = IngestStats::default();
+            match self.ingest_git(path, options) {
+                Ok(git_stats) => stats.merge(&git_stats),
+                Err(e) => {
+                    warn!(path = %workspace.path, error = %e, "Git ingestion failed");
+                    stats.warnings += 1;
+                }
+            }
+            match self.ingest_copilot(path, options, None) {
+                Ok(copilot_stats) => stats.merge(&copilot_stats),
+                Err(e) => {
+                    warn!(path = %workspace.path, error = %e, "Copilot ingestion failed");
+                    stats.warnings += 1;
+                }
+            }
+            self.enforce_retention(&workspace.id, &mut stats);
+
+            all_stats.insert(workspace.id, stats);
+        }
+
+        Ok(all_stats)
+    }
+
+    /// Apply a workspace's retention policies after an ingestion run
+    ///
+    /// A failure is logged and counted as a warning so it never fails the
+    /// ingestion itself.
+    fn enforce_retention(&mut self, workspace_id: &str, stats: &mut IngestStats) {
+        match self.db.apply_retention_policy(workspace_id) {
+            Ok(retention) => {
+                if retention != RetentionStats::default() {
+                    info!(
+                        workspace = %workspace_id,
+                        commits = retention.commits_deleted,
+                        test_results = retention.test_results_deleted,
+                        messages = retention.messages_deleted,
+                        "Retention policy applied"
+                    );
+                }
+            }
+            Err(e) => {
+                warn!(workspace = %workspace_id, error = %e, "Retention policy failed");
+                stats.warnings += 1;
+            }
+        }
+    }
+
+    /// Check stored data for corruption after ingestion
+    ///
+    /// Reports commits whose `parents_json` is not a JSON array of strings or
+    /// whose `diff_json` does not parse as a diff summary, and test results
+    /// whose run no longer exists. Full-text indexes that are out of sync with
+    /// their base tables are reported as warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be queried.
+    pub fn verify_database_consistency(&self) -> Result<ConsistencyReport, IngestError> {
+        let conn = self.db.connection();
+        let mut report = ConsistencyReport::default();
+
+        let mut stmt = conn
+            .prepare("SELECT sha, parents_json, diff_json FROM commits")
+            .map_err(DbError::from)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .map_err(DbError::from)?;
+
+        for row in rows {
+            let (sha, parents_json, diff_json) = row.map_err(DbError::from)?;
+            if let Some(json) = parents_json
+                && serde_json::from_str::<Vec<String>>(&json).is_err()
+            {
+                report.errors.push(format!(
+                    "commit {sha}: parents_json is not an array of strings"
+                ));
+            }
+            if let Some(json) = diff_json
+                && let Err(e) = serde_json::from_str::<hindsight_git::DiffSummary>(&json)
+            {
+                report.errors.push(format!(
+                    "commit {sha}: diff_json is not a valid diff summary: {e}"
+                ));
+            }
+        }
+
+        let orphaned: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM test_results r
+                 LEFT JOIN test_runs tr ON tr.id = r.run_id
+                 WHERE tr.id IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(DbError::from)?;
+        if orphaned > 0 {
+            report.errors.push(format!(
+                "{orphaned} test result(s) reference a missing test run"
+            ));
+        }
+
+        // External-content FTS tables keep one docsize row per indexed row
+        for (fts, table) in [
+            ("commits_fts", "commits"),
+            ("copilot_messages_fts", "copilot_messages"),
+            ("notes_fts", "notes"),
+        ] {
+            let (indexed, total): (i64, i64) = conn
+                .query_row(
+                    &format!(
+                        "SELECT (SELECT COUNT(*) FROM {fts}_docsize), (SELECT COUNT(*) FROM {table})"
+                    ),
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .map_err(DbError::from)?;
+            if indexed != total {
+                report.warnings.push(format!(
+                    "{fts} indexes {indexed} row(s) but {table} has {total}"
+                ));
+            }
+        }
+
+        Ok(report)
+    }
 }
 
 // ============================================================================
@@ -720,6 +1661,189 @@ fn split_test_name(name: &str) -> (String, String) {
     }
 }
 
+/// Detect whether ingestion is running in CI or locally
+///
+/// Returns `"ci"` when `CI=true`, `GITHUB_ACTIONS`, or `GITLAB_CI` is set,
+/// otherwise `"local"`.
+#[must_use]
+pub fn detect_environment() -> &'static str {
+    environment_from(|key| std::env::var(key).ok())
+}
+
+/// Classify the environment using the given variable lookup
+fn environment_from(lookup: impl Fn(&str) -> Option<String>) -> &'static str {
+    let ci_flag = lookup("CI").is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1");
+    if ci_flag || lookup("GITHUB_ACTIONS").is_some() || lookup("GITLAB_CI").is_some() {
+        "ci"
+    } else {
+        "local"
+    }
+}
+
+/// Convert a parsed session's messages to records for `db_session_id`
+///
+/// Content longer than `max_bytes` is cut at a character boundary and ends
+/// with [`TRUNCATION_MARKER`].
+fn message_records(
+    db_session_id: &str,
+    session: &hindsight_copilot::ChatSession,
+    max_bytes: Option<usize>,
+) -> Vec<CopilotMessageRecord> {
+    session
+        .messages
+        .iter()
+        .map(|m| {
+            let role = match m.role {
+                hindsight_copilot::MessageRole::User => "user",
+                hindsight_copilot::MessageRole::Assistant => "assistant",
+                hindsight_copilot::MessageRole::System => "system",
+            };
+
+            let content = match max_bytes {
+                Some(max) => truncate_content(&m.content, max),
+                None => m.content.clone(),
+            };
+
+            let mut record = CopilotMessageRecord::new(
+                db_session_id.to_string(),
+                role.to_string(),
+                content,
+                m.timestamp,
+            );
+
+            // Add variables if present
+            if !m.variables.is_empty()
+                && let Ok(json) = serde_json::to_string(&m.variables)
+            {
+                record = record.with_variables_json(json);
+            }
+
+            if let Some(agent) = &m.agent {
+                record = record.with_agent(agent);
+            }
+
+            // Extracted from the full content, so truncation cannot cut a block
+            let code_blocks = m.extract_code_blocks();
+            if !code_blocks.is_empty()
+                && let Ok(json) = serde_json::to_string(&code_blocks)
+            {
+                record = record.with_code_blocks_json(json);
+            }
+
+            record
+        })
+        .collect()
+}
+
+/// Cut `content` to at most `max_bytes` and append the truncation marker
+fn truncate_content(content: &str, max_bytes: usize) -> String {
+    if content.len() <= max_bytes {
+        return content.to_string();
+    }
+
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &content[..end], TRUNCATION_MARKER)
+}
+
+/// Build the database record for a walked commit
+fn commit_record(
+    workspace_id: &str,
+    commit_with_diff: &hindsight_git::CommitWithDiff,
+    exclude_patterns: &[glob::Pattern],
+) -> Result<CommitRecord, IngestError> {
+    let commit = &commit_with_diff.commit;
+    let mut record = CommitRecord::new(
+        workspace_id.to_string(),
+        commit.sha.clone(),
+        commit.author.clone(),
+        Some(commit.author_email.clone()),
+        commit.message.clone(),
+        commit.timestamp,
+    )
+    .with_parents(commit.parents.clone());
+    if commit.is_revert() {
+        record = record.with_revert(commit.reverted_sha());
+    }
+    if let Some(stats) = commit_with_diff.stats {
+        record = record.with_stats(stats);
+    }
+
+    // Add diff if available
+    if let Some(ref diff) = commit_with_diff.diff {
+        match filter_excluded_paths(diff, exclude_patterns) {
+            Some(filtered) => {
+                let diff_json = serde_json::to_string(&filtered)?;
+                record = record
+                    .with_diff_json(diff_json)
+                    .with_extension_stats(hindsight_git::file_extension_stats(&filtered));
+                if let Some(language) = hindsight_git::dominant_language(&filtered) {
+                    record = record.with_language_hint(language);
+                }
+            }
+            None => record = record.with_diff_excluded(),
+        }
+    }
+
+    Ok(record)
+}
+
+/// Remove files matching any exclude pattern from a diff summary
+///
+/// Returns `None` if the diff touched files and every one of them was excluded.
+fn filter_excluded_paths(
+    diff: &hindsight_git::DiffSummary,
+    patterns: &[glob::Pattern],
+) -> Option<hindsight_git::DiffSummary> {
+    if patterns.is_empty() || diff.files.is_empty() {
+        return Some(diff.clone());
+    }
+
+    let (excluded, kept): (Vec<_>, Vec<_>) = diff
+        .files
+        .iter()
+        .cloned()
+        .partition(|f| patterns.iter().any(|p| p.matches(&f.path)));
+
+    if kept.is_empty() {
+        return None;
+    }
+
+    let excluded_insertions: usize = excluded.iter().map(|f| f.insertions).sum();
+    let excluded_deletions: usize = excluded.iter().map(|f| f.deletions).sum();
+
+    Some(hindsight_git::DiffSummary {
+        files_changed: kept.len(),
+        insertions: diff.insertions.saturating_sub(excluded_insertions),
+        deletions: diff.deletions.saturating_sub(excluded_deletions),
+        files: kept,
+    })
+}
+
+/// A scratch path under the system temp directory, removed when dropped
+///
+/// The directory itself is not created, so it can be handed to tools that
+/// insist on creating their destination.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(purpose: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("hindsight-{purpose}-{}", uuid::Uuid::new_v4())))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -742,6 +1866,68 @@ mod tests {
         assert_eq!(test, "test_simple");
     }
 
+    #[test]
+    fn test_verify_database_consistency_clean() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let commit = CommitRecord::new(
+            ws_id,
+            "a".repeat(40),
+            "Author".to_string(),
+            None,
+            "Initial commit".to_string(),
+            chrono::Utc::now(),
+        )
+        .with_parents(vec!["b".repeat(40)])
+        .with_diff_json(serde_json::to_string(&hindsight_git::DiffSummary::empty()).unwrap());
+        db.insert_commit(&commit).expect("insert commit");
+
+        let report = Ingestor::new(db)
+            .verify_database_consistency()
+            .expect("verify");
+        assert!(report.is_consistent(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn test_verify_database_consistency_detects_bad_json() {
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let sha = "c".repeat(40);
+        let commit = CommitRecord::new(
+            ws_id,
+            sha.clone(),
+            "Author".to_string(),
+            None,
+            "Broken commit".to_string(),
+            chrono::Utc::now(),
+        )
+        .with_diff_json(r#"{"files": "not a list"}"#.to_string());
+        db.insert_commit(&commit).expect("insert commit");
+        db.connection()
+            .execute("UPDATE commits SET parents_json = '[1, 2]'", [])
+            .expect("corrupt parents");
+
+        let report = Ingestor::new(db)
+            .verify_database_consistency()
+            .expect("verify");
+        assert!(!report.is_consistent());
+        assert_eq!(report.errors.len(), 2);
+        assert!(
+            report
+                .errors
+                .iter()
+                .any(|e| e.contains("diff_json") && e.contains(&sha))
+        );
+        assert!(report.errors.iter().any(|e| e.contains("parents_json")));
+    }
+
     #[test]
     fn test_ingest_options_default() {
         let opts = IngestOptions::default();
@@ -772,6 +1958,89 @@ mod tests {
         assert_eq!(opts.commit_limit, Some(50));
     }
 
+    #[test]
+    fn test_ingest_options_with_exclude_paths() {
+        let opts = IngestOptions::full()
+            .with_exclude_paths(vec!["Cargo.lock".to_string(), "vendor/**".to_string()]);
+        assert_eq!(opts.exclude_paths, vec!["Cargo.lock", "vendor/**"]);
+    }
+
+    #[test]
+    fn test_ingest_options_with_max_copilot_message_bytes() {
+        let opts = IngestOptions::default().with_max_copilot_message_bytes(4096);
+        assert_eq!(opts.max_copilot_message_bytes, Some(4096));
+    }
+
+    #[test]
+    fn test_ingest_options_with_environment_tag() {
+        let opts = IngestOptions::default().with_environment_tag("runner", "self-hosted");
+        assert_eq!(
+            opts.environment_tags.get("runner").map(String::as_str),
+            Some("self-hosted")
+        );
+    }
+
+    #[test]
+    fn test_environment_from_ci_variables() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            environment_from(move |key| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| (*v).to_string())
+            })
+        };
+
+        assert_eq!(env(&[]), "local");
+        assert_eq!(env(&[("CI", "true")]), "ci");
+        assert_eq!(env(&[("CI", "false")]), "local");
+        assert_eq!(env(&[("GITHUB_ACTIONS", "true")]), "ci");
+        assert_eq!(env(&[("GITLAB_CI", "true")]), "ci");
+    }
+
+    fn sample_diff(paths: &[&str]) -> hindsight_git::DiffSummary {
+        hindsight_git::DiffSummary {
+            files_changed: paths.len(),
+            insertions: 10,
+            deletions: 2,
+            files: paths
+                .iter()
+                .map(|p| hindsight_git::FileDiff {
+                    path: (*p).to_string(),
+                    status: "modified".to_string(),
+                    insertions: 0,
+                    deletions: 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_filter_excluded_paths_no_patterns() {
+        let diff = sample_diff(&["Cargo.lock"]);
+        assert_eq!(filter_excluded_paths(&diff, &[]), Some(diff));
+    }
+
+    #[test]
+    fn test_filter_excluded_paths_partial() {
+        let diff = sample_diff(&["Cargo.lock", "src/lib.rs", "vendor/dep/mod.rs"]);
+        let patterns = vec![
+            glob::Pattern::new("Cargo.lock").expect("pattern"),
+            glob::Pattern::new("vendor/**").expect("pattern"),
+        ];
+
+        let filtered = filter_excluded_paths(&diff, &patterns).expect("some files kept");
+        assert_eq!(filtered.files_changed, 1);
+        assert_eq!(filtered.files.len(), 1);
+        assert_eq!(filtered.files[0].path, "src/lib.rs");
+    }
+
+    #[test]
+    fn test_filter_excluded_paths_all_excluded() {
+        let diff = sample_diff(&["Cargo.lock"]);
+        let patterns = vec![glob::Pattern::new("Cargo.lock").expect("pattern")];
+        assert!(filter_excluded_paths(&diff, &patterns).is_none());
+    }
+
     #[test]
     fn test_ingest_stats_merge() {
         let mut stats1 = IngestStats {
@@ -808,6 +2077,43 @@ mod tests {
         assert_eq!(stats.total_items(), 94);
     }
 
+    #[test]
+    fn test_ingest_stats_format_summary() {
+        let stats = IngestStats {
+            commits_inserted: 42,
+            commits_skipped: 3,
+            test_runs_inserted: 3,
+            test_results_inserted: 150,
+            sessions_inserted: 5,
+            messages_inserted: 87,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            stats.format_summary(),
+            "Commits: +42 (skipped 3)\nTest results: +150 in 3 runs\nCopilot: 5 sessions, 87 messages\nWarnings: 0"
+        );
+    }
+
+    #[test]
+    fn test_ingest_stats_format_json() {
+        let stats = IngestStats {
+            test_runs_inserted: 1,
+            test_results_inserted: 12,
+            notes_inserted: 2,
+            tags_inserted: 1,
+            ..Default::default()
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&stats.format_json()).expect("valid JSON");
+        assert_eq!(json["test_results_inserted"], 12);
+        assert_eq!(json["notes_inserted"], 2);
+        assert!(stats.format_summary().contains("Notes: +2"));
+        assert!(stats.format_summary().contains("Tags: +1"));
+        assert!(!stats.format_summary().contains("Test inventory"));
+    }
+
     #[test]
     fn test_ingestor_new() {
         let db = Database::in_memory().expect("create db");
@@ -849,4 +2155,126 @@ mod tests {
         };
         assert!(matches!(completed, ProgressEvent::Completed { .. }));
     }
+
+    #[test]
+    fn test_truncate_content() {
+        assert_eq!(truncate_content("short", 10), "short");
+        assert_eq!(
+            truncate_content("abcdefghij", 4),
+            format!("abcd{}", TRUNCATION_MARKER)
+        );
+        // Never split a multi-byte character
+        assert_eq!(
+            truncate_content("ééé", 3),
+            format!("é{}", TRUNCATION_MARKER)
+        );
+    }
+
+    #[test]
+    fn test_message_records_keep_agent() {
+        let now = chrono::Utc::now();
+        let mut session =
+            hindsight_copilot::ChatSession::new("s1".to_string(), "ws".to_string(), now);
+        session.add_message(
+            hindsight_copilot::ChatMessage::user("Where is main?".to_string(), now)
+                .with_agent("workspace".to_string()),
+        );
+        session.add_message(hindsight_copilot::ChatMessage::assistant(
+            "In src/main.rs".to_string(),
+            now,
+        ));
+
+        let records = message_records("session", &session, None);
+        assert_eq!(records[0].agent.as_deref(), Some("workspace"));
+        assert_eq!(records[1].agent, None);
+    }
+
+    #[test]
+    fn test_message_records_keep_code_blocks() {
+        let now = chrono::Utc::now();
+        let mut session =
+            hindsight_copilot::ChatSession::new("s1".to_string(), "ws".to_string(), now);
+        session.add_message(hindsight_copilot::ChatMessage::user(
+            "How do I print?".to_string(),
+            now,
+        ));
+        session.add_message(hindsight_copilot::ChatMessage::assistant(
+            "Use:\n```rust\nprintln!(\"hi\");\n```".to_string(),
+            now,
+        ));
+
+        let records = message_records("session", &session, None);
+        assert_eq!(records[0].code_blocks_json, None);
+        assert_eq!(
+            records[1].code_blocks_json.as_deref(),
+            Some(r#"[{"language":"rust","content":"println!(\"hi\");"}]"#)
+        );
+    }
+
+    #[test]
+    fn test_message_records_truncates_long_content() {
+        let now = chrono::Utc::now();
+        let mut session =
+            hindsight_copilot::ChatSession::new("s1".to_string(), "ws".to_string(), now);
+        session.add_message(hindsight_copilot::ChatMessage::user(
+            "Generate a parser".to_string(),
+            now,
+        ));
+        session.add_message(hindsight_copilot::ChatMessage::assistant(
+            "x".repeat(5000),
+            now,
+        ));
+
+        let mut db = Database::in_memory().expect("create db");
+        db.initialize().expect("init");
+        let ws_id = db.get_or_create_workspace("ws", "/ws").expect("workspace");
+        let session_id = db
+            .insert_copilot_session(&CopilotSessionRecord::new(ws_id, "s1".to_string()))
+            .expect("session");
+
+        let records = message_records(&session_id, &session, Some(100));
+        assert!(!records[0].is_truncated());
+        assert!(records[1].is_truncated());
+        assert_eq!(records[1].content.len(), 100 + TRUNCATION_MARKER.len());
+
+        db.insert_copilot_messages_batch(&records).expect("insert");
+        let (content, truncated): (String, bool) = db
+            .connection()
+            .query_row(
+                "SELECT content, truncated FROM copilot_messages WHERE role = 'assistant'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("query");
+        assert!(content.ends_with(TRUNCATION_MARKER));
+        assert!(truncated);
+
+        let unlimited = message_records(&session_id, &session, None);
+        assert_eq!(unlimited[1].content.len(), 5000);
+        assert!(!unlimited[1].is_truncated());
+    }
+
+    #[test]
+    fn test_progress_event_json() {
+        let progress = ProgressEvent::Progress {
+            source: "git".to_string(),
+            processed: 50,
+            total: Some(100),
+        };
+        assert_eq!(
+            serde_json::to_value(&progress).expect("serialize"),
+            serde_json::json!({"type": "progress", "source": "git", "processed": 50, "total": 100})
+        );
+
+        let completed = ProgressEvent::Completed {
+            source: "tests".to_string(),
+            stats: IngestStats {
+                test_results_inserted: 3,
+                ..IngestStats::default()
+            },
+        };
+        let value = serde_json::to_value(&completed).expect("serialize");
+        assert_eq!(value["type"], "completed");
+        assert_eq!(value["stats"]["test_results_inserted"], 3);
+    }
 }
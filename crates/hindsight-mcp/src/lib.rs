@@ -15,4 +15,5 @@ pub mod db;
 pub mod handlers;
 pub mod ingest;
 pub mod queries;
+pub mod report;
 pub mod server;
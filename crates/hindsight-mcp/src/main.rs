@@ -14,9 +14,11 @@
 //!
 //! The server communicates over stdio using the MCP (Model Context Protocol).
 
+use std::collections::{HashMap, HashSet};
 use std::io::{self, BufRead};
 use std::path::Path;
 use std::process::{Command as ProcessCommand, Stdio};
+use std::time::Instant;
 
 use clap::Parser;
 use rust_mcp_sdk::mcp_server::{McpServerOptions, ToMcpServerHandler, server_runtime};
@@ -27,9 +29,10 @@ use rust_mcp_sdk::{McpServer, StdioTransport, TransportOptions};
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
-use hindsight_mcp::config::{Command, Config};
-use hindsight_mcp::db::Database;
-use hindsight_mcp::ingest::Ingestor;
+use hindsight_mcp::config::{Command, Config, OutputFormat, ReportFormat};
+use hindsight_mcp::db::{Database, IngestSource};
+use hindsight_mcp::ingest::{IngestOptions, IngestStats, Ingestor, ProgressEvent};
+use hindsight_mcp::report;
 use hindsight_mcp::server::HindsightServer;
 
 /// Initialize the tracing/logging subsystem
@@ -83,7 +86,24 @@ fn init_database(config: &Config) -> anyhow::Result<Database> {
 }
 
 /// Build the MCP server details and capabilities
-fn build_server_details() -> InitializeResult {
+///
+/// `failing_tests` holds `(workspace_id, workspace_name, fail_count)` for each
+/// workspace with failures; the counts are attached as `_meta.failingTests` so
+/// an assistant can see them when the session starts.
+fn build_server_details(failing_tests: &[(String, String, u64)]) -> InitializeResult {
+    let total: u64 = failing_tests.iter().map(|(_, _, count)| count).sum();
+    let workspaces: Vec<serde_json::Value> = failing_tests
+        .iter()
+        .map(|(id, name, count)| {
+            serde_json::json!({ "workspaceId": id, "workspaceName": name, "failCount": count })
+        })
+        .collect();
+    let mut meta = serde_json::Map::new();
+    meta.insert(
+        "failingTests".to_string(),
+        serde_json::json!({ "total": total, "workspaces": workspaces }),
+    );
+
     InitializeResult {
         server_info: Implementation {
             name: "hindsight-mcp".into(),
@@ -111,11 +131,30 @@ fn build_server_details() -> InitializeResult {
              - hindsight_failing_tests: Get currently failing tests\n\
              - hindsight_activity_summary: Aggregate activity statistics\n\
              - hindsight_commit_details: Detailed commit information\n\
-             - hindsight_ingest: Trigger data ingestion from sources\n\n\
+             - hindsight_diff: Full patch of a commit\n\
+             - hindsight_ingest: Trigger data ingestion from sources\n\
+             - hindsight_copilot_stats: Estimated Copilot token usage and cost\n\
+             - hindsight_trending_topics: Copilot topics rising or falling over time\n\
+             - hindsight_session_detail: A Copilot session as question-answer turns\n\
+             - hindsight_regressions: Tests that started failing after passing\n\
+             - hindsight_failure_streaks: Tests failing in several consecutive runs\n\
+             - hindsight_flaky_tests: Tests ranked by how often they fail\n\
+             - hindsight_slow_tests: Slowest tests by p90 duration over recent runs\n\
+             - hindsight_suite_parallelism: Test suites that ran concurrently in a run\n\
+             - hindsight_commit_volume: Daily commit counts with a sparkline\n\
+             - hindsight_authors: Contributors ranked by commit count\n\
+             - hindsight_heatmap: Commit counts by day of week and hour\n\
+             - hindsight_tags: Git tags and the commits they mark, newest first\n\
+             - hindsight_context_usage: Kinds of context attached to Copilot chats\n\
+             - hindsight_annotate: Record a deploy, incident or other event on the timeline\n\
+             - hindsight_query: Read-only SQL SELECT against the database\n\
+             - hindsight_suggest_queries: Most common topics in the development history\n\
+             - hindsight_workspace_list: Known workspaces with commit and session counts\n\
+             - hindsight_workspaces: Workspaces with recent activity, most recent first\n\n\
              All tools support optional workspace filtering."
                 .into(),
         ),
-        meta: None,
+        meta: Some(meta),
     }
 }
 
@@ -126,8 +165,24 @@ async fn main() -> anyhow::Result<()> {
 
     // Handle subcommands
     match &config.command {
-        Some(Command::Ingest { tests, commit }) => {
-            run_ingest(&config, *tests, commit.clone()).await
+        Some(Command::Ingest {
+            tests,
+            git,
+            since_tag,
+            commit,
+            all_workspaces,
+            progress_json,
+            format,
+        }) => {
+            let sources = IngestSources {
+                tests: *tests,
+                git: *git,
+                since_tag: since_tag.clone(),
+                all_workspaces: *all_workspaces,
+            };
+            let result =
+                run_ingest(&config, sources, commit.clone(), *progress_json, *format).await;
+            json_errors(*format, result)
         }
         Some(Command::Test {
             package,
@@ -138,9 +193,11 @@ async fn main() -> anyhow::Result<()> {
             no_commit,
             commit,
             show_output,
+            progress_json,
+            format,
             nextest_args,
         }) => {
-            run_test(
+            let result = run_test(
                 &config,
                 package.clone(),
                 bin.clone(),
@@ -150,10 +207,22 @@ async fn main() -> anyhow::Result<()> {
                 *no_commit,
                 commit.clone(),
                 *show_output,
+                *progress_json,
+                *format,
                 nextest_args.clone(),
             )
-            .await
+            .await;
+            json_errors(*format, result)
         }
+        Some(Command::OptimizeDb) => run_optimize_db(&config),
+        Some(Command::Verify) => run_verify(&config),
+        Some(Command::Compact { into }) => run_compact(&config, into.as_deref()),
+        Some(Command::Status { stale_hours }) => run_status(&config, *stale_hours),
+        Some(Command::Report {
+            days,
+            output,
+            format,
+        }) => run_report(&config, days.unwrap_or(7), output.as_deref(), *format),
         None => {
             // Default: run MCP server
             run_server(config).await
@@ -161,67 +230,339 @@ async fn main() -> anyhow::Result<()> {
     }
 }
 
-/// Run the test ingestion command
-async fn run_ingest(config: &Config, tests: bool, commit: Option<String>) -> anyhow::Result<()> {
-    if !tests {
-        eprintln!("Error: No ingestion source specified. Use --tests to ingest test results.");
-        std::process::exit(1);
+/// Run the ingestion command
+async fn run_ingest(
+    config: &Config,
+    sources: IngestSources,
+    commit: Option<String>,
+    progress_json: bool,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if !sources.tests && !sources.git && !sources.all_workspaces {
+        exit_with_error(
+            format,
+            "No ingestion source specified. Use --git to ingest commits, --tests to ingest test results, or --all-workspaces.",
+        );
     }
+    let started = Instant::now();
+
+    init_cli_logging(config, progress_json);
+
+    if sources.all_workspaces {
+        let db = init_database(config)?;
+        let mut ingestor = cli_ingestor(db, progress_json);
+        let all_stats = ingestor.ingest_all_workspaces(&IngestOptions::incremental())?;
+
+        let paths: HashMap<String, String> = ingestor
+            .database()
+            .list_workspaces()?
+            .into_iter()
+            .map(|w| (w.id, w.path))
+            .collect();
+        let mut ids: Vec<&String> = all_stats.keys().collect();
+        ids.sort_by_key(|id| paths.get(*id));
+
+        if format == OutputFormat::Json {
+            let workspaces: serde_json::Map<String, serde_json::Value> = ids
+                .into_iter()
+                .map(|id| {
+                    let path = paths.get(id).unwrap_or(id).clone();
+                    (
+                        path,
+                        serde_json::to_value(&all_stats[id]).unwrap_or_default(),
+                    )
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::json!({
+                    "status": "success",
+                    "duration_ms": elapsed_ms(started),
+                    "workspaces": workspaces,
+                })
+            );
+            return Ok(());
+        }
 
-    // Initialize simple logging for CLI mode
-    let filter = EnvFilter::from_default_env().add_directive(config.log_level().into());
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_writer(std::io::stderr)
-        .with_ansi(true)
-        .init();
+        for id in ids {
+            println!("{}:", paths.get(id).map_or(id.as_str(), String::as_str));
+            print_ingest_summary(&all_stats[id]);
+        }
+        return Ok(());
+    }
 
     // Get workspace path
     let workspace = config.workspace_path().ok_or_else(|| {
         anyhow::anyhow!("Workspace path is required. Use --workspace or set HINDSIGHT_WORKSPACE")
     })?;
 
-    info!(workspace = %workspace.display(), "Starting test ingestion");
+    // Read stdin before touching the database, so a missing pipe fails fast
+    let input = if sources.tests {
+        let stdin = io::stdin();
+        let mut input = String::new();
+        for line in stdin.lock().lines() {
+            let line = line?;
+            input.push_str(&line);
+            input.push('\n');
+        }
+
+        if input.trim().is_empty() {
+            exit_with_error(
+                format,
+                "No input received from stdin. Pipe nextest JSON output.\n\
+                 Example: NEXTEST_EXPERIMENTAL_LIBTEST_JSON=1 cargo nextest run --message-format libtest-json | hindsight-mcp ingest --tests",
+            );
+        }
+        Some(input)
+    } else {
+        None
+    };
+
+    // Initialize database
+    let db = init_database(config)?;
+    let mut ingestor = cli_ingestor(db, progress_json);
+    let mut git_stats = None;
+    let mut test_stats = None;
 
-    // Read stdin
-    let stdin = io::stdin();
-    let mut input = String::new();
-    for line in stdin.lock().lines() {
-        let line = line?;
-        input.push_str(&line);
-        input.push('\n');
+    if sources.git {
+        info!(workspace = %workspace.display(), "Starting git ingestion");
+
+        let mut options = IngestOptions::incremental();
+        if let Some(tag) = &sources.since_tag {
+            options = options.with_since_tag(tag);
+        }
+        let stats = match ingestor.ingest_git(&workspace, &options) {
+            Ok(stats) => stats,
+            Err(e) => exit_with_error(format, &e.display_chain()),
+        };
+
+        info!(
+            commits_inserted = stats.commits_inserted,
+            commits_skipped = stats.commits_skipped,
+            "Git ingestion complete"
+        );
+
+        if format == OutputFormat::Text {
+            print_ingest_summary(&stats);
+        }
+        git_stats = Some(stats);
     }
 
-    if input.trim().is_empty() {
-        eprintln!("Error: No input received from stdin. Pipe nextest JSON output.");
-        eprintln!(
-            "Example: NEXTEST_EXPERIMENTAL_LIBTEST_JSON=1 cargo nextest run --message-format libtest-json | hindsight-mcp ingest --tests"
+    if let Some(input) = input {
+        info!(workspace = %workspace.display(), "Starting test ingestion");
+
+        let stats = match ingestor.ingest_tests(
+            &workspace,
+            &input,
+            hindsight_tests::OutputFormat::Nextest,
+            commit.as_deref(),
+        ) {
+            Ok(stats) => stats,
+            Err(e) => exit_with_error(format, &e.display_chain()),
+        };
+
+        info!(
+            tests_inserted = stats.test_results_inserted,
+            runs_inserted = stats.test_runs_inserted,
+            "Test ingestion complete"
         );
-        std::process::exit(1);
+
+        if format == OutputFormat::Text {
+            print_ingest_summary(&stats);
+        }
+        test_stats = Some(stats);
     }
 
-    // Initialize database
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "success",
+                "commit": commit,
+                "duration_ms": elapsed_ms(started),
+                "git": git_stats,
+                "tests": test_stats,
+            })
+        );
+    }
+
+    Ok(())
+}
+
+/// Sources selected for the `ingest` command
+struct IngestSources {
+    /// Ingest test results from stdin
+    tests: bool,
+    /// Ingest git commits from the workspace
+    git: bool,
+    /// Only ingest commits made since this tag
+    since_tag: Option<String>,
+    /// Ingest git and Copilot data for every workspace in the database
+    all_workspaces: bool,
+}
+
+/// Run the database optimize command
+fn run_optimize_db(config: &Config) -> anyhow::Result<()> {
+    init_cli_logging(config, false);
+
     let db = init_database(config)?;
 
-    // Run ingestion
-    let mut ingestor = Ingestor::new(db);
-    let stats = ingestor.ingest_tests(&workspace, &input, commit.as_deref())?;
+    let before = db.pragma_get("freelist_count")?;
+    db.optimize()?;
+    let after = db.pragma_get("freelist_count")?;
 
-    info!(
-        tests_inserted = stats.test_results_inserted,
-        runs_inserted = stats.test_runs_inserted,
-        "Test ingestion complete"
+    info!(before = %before, after = %after, "Database optimized");
+
+    println!(
+        "Optimized {} (free pages: {} -> {})",
+        config.database_path().display(),
+        before,
+        after
     );
 
+    Ok(())
+}
+
+/// Run the database compact command
+fn run_compact(config: &Config, into: Option<&Path>) -> anyhow::Result<()> {
+    init_cli_logging(config, false);
+
+    let mut db = init_database(config)?;
+
+    match into {
+        Some(dest) => {
+            let freed = db.compact_into(dest)?;
+            info!(freed = freed, dest = %dest.display(), "Database copied");
+            println!(
+                "Compacted {} into {} ({} bytes freed)",
+                config.database_path().display(),
+                dest.display(),
+                freed
+            );
+        }
+        None => {
+            let freed = db.compact()?;
+            info!(freed = freed, "Database compacted");
+            println!(
+                "Compacted {} ({} bytes freed)",
+                config.database_path().display(),
+                freed
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the database verify command
+fn run_verify(config: &Config) -> anyhow::Result<()> {
+    init_cli_logging(config, false);
+
+    let db = init_database(config)?;
+    let report = Ingestor::new(db).verify_database_consistency()?;
+
+    for warning in &report.warnings {
+        println!("warning: {warning}");
+    }
+    for error in &report.errors {
+        println!("error: {error}");
+    }
+
+    if !report.is_consistent() {
+        eprintln!(
+            "Found {} error(s) in {}",
+            report.errors.len(),
+            config.database_path().display()
+        );
+        std::process::exit(1);
+    }
+
     println!(
-        "Ingested {} test results in {} test run(s)",
-        stats.test_results_inserted, stats.test_runs_inserted
+        "{} is consistent ({} warning(s))",
+        config.database_path().display(),
+        report.warnings.len()
     );
 
     Ok(())
 }
 
+/// Run the status command
+fn run_status(config: &Config, stale_hours: u32) -> anyhow::Result<()> {
+    init_cli_logging(config, false);
+
+    let db = init_database(config)?;
+    let workspaces = db.list_workspaces()?;
+    let stale: HashSet<String> = db
+        .get_stale_workspaces(stale_hours)?
+        .into_iter()
+        .map(|w| w.id)
+        .collect();
+
+    if workspaces.is_empty() {
+        println!("No workspaces in {}", config.database_path().display());
+        return Ok(());
+    }
+
+    for workspace in &workspaces {
+        let marker = if stale.contains(&workspace.id) {
+            " (stale)"
+        } else {
+            ""
+        };
+        println!("{} {}{marker}", workspace.name, workspace.path);
+        println!(
+            "  git:     {}",
+            workspace.ingested_relative(IngestSource::Git)
+        );
+        println!(
+            "  copilot: {}",
+            workspace.ingested_relative(IngestSource::Copilot)
+        );
+        println!(
+            "  tests:   {}",
+            workspace.ingested_relative(IngestSource::Tests)
+        );
+    }
+
+    println!(
+        "\n{} of {} workspace(s) not ingested in the last {stale_hours} hour(s)",
+        stale.len(),
+        workspaces.len()
+    );
+
+    Ok(())
+}
+
+/// Run the report command
+fn run_report(
+    config: &Config,
+    days: u32,
+    output: Option<&Path>,
+    format: ReportFormat,
+) -> anyhow::Result<()> {
+    init_cli_logging(config, false);
+
+    let db = init_database(config)?;
+
+    let content = match format {
+        ReportFormat::Markdown => report::generate_report(db.connection(), days)?,
+        ReportFormat::Json => {
+            let report = report::build_report(db.connection(), days)?;
+            serde_json::to_string_pretty(&report)?
+        }
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, &content)?;
+            info!(path = %path.display(), "Report written");
+        }
+        None => println!("{content}"),
+    }
+
+    Ok(())
+}
+
 /// Check if cargo-nextest is installed
 ///
 /// Returns Ok(()) if nextest is available, or an error with install instructions.
@@ -396,16 +737,11 @@ async fn run_test(
     no_commit: bool,
     commit: Option<String>,
     show_output: bool,
+    progress_json: bool,
+    format: OutputFormat,
     nextest_args: Vec<String>,
 ) -> anyhow::Result<()> {
-    // Initialize logging for CLI mode
-    let filter_directive = EnvFilter::from_default_env().add_directive(config.log_level().into());
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter_directive)
-        .with_writer(std::io::stderr)
-        .with_ansi(true)
-        .init();
+    init_cli_logging(config, progress_json);
 
     info!("hindsight-mcp test subcommand");
 
@@ -494,10 +830,21 @@ async fn run_test(
     };
 
     // Parse and display results
-    let summary = hindsight_tests::parse_run_output(&json_output)?;
+    let summary = match hindsight_tests::parse_run_output(&json_output) {
+        Err(hindsight_tests::TestsError::EmptyInput) => hindsight_tests::TestRunSummary::empty(),
+        result => result?,
+    };
 
     // Warn if no tests were found
-    if summary.results.is_empty() {
+    if summary.results.is_empty() && progress_json {
+        let warning = ProgressEvent::Warning {
+            source: "tests".to_string(),
+            message: "No test results found in nextest output".to_string(),
+        };
+        if let Ok(json) = serde_json::to_string(&warning) {
+            eprintln!("{json}");
+        }
+    } else if summary.results.is_empty() {
         warn!("No test results found in nextest output");
         eprintln!(
             "Warning: No tests were found.\n\
@@ -508,6 +855,11 @@ async fn run_test(
         );
     }
 
+    if dry_run && format == OutputFormat::Json {
+        println!("{}", test_run_json(&summary, commit_sha.as_deref(), None));
+        return Ok(());
+    }
+
     if dry_run {
         // Dry-run mode: display what would be ingested
         println!("Dry-run mode - no data will be written to database\n");
@@ -531,8 +883,13 @@ async fn run_test(
 
     // Run ingestion
     info!("Ingesting test results to database");
-    let mut ingestor = Ingestor::new(db);
-    let stats = ingestor.ingest_tests(&workspace, &json_output, commit_sha.as_deref())?;
+    let mut ingestor = cli_ingestor(db, progress_json);
+    let stats = ingestor.ingest_tests(
+        &workspace,
+        &json_output,
+        hindsight_tests::OutputFormat::Nextest,
+        commit_sha.as_deref(),
+    )?;
 
     // Report results
     info!(
@@ -541,6 +898,14 @@ async fn run_test(
         "Test ingestion complete"
     );
 
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            test_run_json(&summary, commit_sha.as_deref(), Some(&stats))
+        );
+        return Ok(());
+    }
+
     println!("Test run completed:");
     println!("  Total:   {}", summary.results.len());
     println!("  Passed:  {}", summary.passed);
@@ -550,14 +915,104 @@ async fn run_test(
         println!("  Commit:  {}", sha);
     }
     println!();
-    println!(
-        "Ingested {} test results in {} test run(s)",
-        stats.test_results_inserted, stats.test_runs_inserted
-    );
+    print_ingest_summary(&stats);
 
     Ok(())
 }
 
+/// Build the `--format json` result of the `test` command
+///
+/// `stats` is `None` for a dry run, which ingests nothing.
+fn test_run_json(
+    summary: &hindsight_tests::TestRunSummary,
+    commit_sha: Option<&str>,
+    stats: Option<&IngestStats>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "status": if summary.failed == 0 { "success" } else { "failure" },
+        "passed": summary.passed,
+        "failed": summary.failed,
+        "ignored": summary.ignored,
+        "total": summary.results.len(),
+        "commit": commit_sha,
+        "duration_ms": (summary.exec_time_secs * 1000.0).round() as u64,
+        "dry_run": stats.is_none(),
+        "ingested": stats,
+    })
+}
+
+/// Milliseconds elapsed since `started`
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Report a CLI error in the requested format and exit with status 1
+///
+/// Text errors go to stderr; JSON errors are written to stdout as
+/// `{"status":"error","error":...}` so scripts always get one object.
+fn exit_with_error(format: OutputFormat, message: &str) -> ! {
+    match format {
+        OutputFormat::Text => eprintln!("Error: {message}"),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::json!({ "status": "error", "error": message })
+        ),
+    }
+    std::process::exit(1);
+}
+
+/// Report a failed CLI command as a JSON object when `--format json` is set
+///
+/// Text-format results are returned unchanged.
+fn json_errors(format: OutputFormat, result: anyhow::Result<()>) -> anyhow::Result<()> {
+    match result {
+        Err(e) if format == OutputFormat::Json => exit_with_error(format, &format!("{e:#}")),
+        other => other,
+    }
+}
+
+/// Print ingestion stats for CLI commands
+fn print_ingest_summary(stats: &IngestStats) {
+    println!("Ingested:");
+    for line in stats.format_summary().lines() {
+        println!("  {line}");
+    }
+}
+
+/// Initialize logging for the CLI subcommands
+///
+/// With `progress_json`, stderr is reserved for progress events, so no log
+/// output is installed and every stderr line stays parseable as JSON.
+fn init_cli_logging(config: &Config, progress_json: bool) {
+    if progress_json {
+        return;
+    }
+
+    let filter = EnvFilter::from_default_env().add_directive(config.log_level().into());
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(true)
+        .init();
+}
+
+/// Create an ingestor for CLI commands
+///
+/// With `progress_json`, each progress event is written to stderr as a single
+/// JSON line so wrapping tools can follow ingestion without parsing logs.
+fn cli_ingestor(db: Database, progress_json: bool) -> Ingestor {
+    let ingestor = Ingestor::new(db);
+    if !progress_json {
+        return ingestor;
+    }
+    ingestor.with_progress(Box::new(|event| {
+        if let Ok(json) = serde_json::to_string(event) {
+            eprintln!("{json}");
+        }
+    }))
+}
+
 /// Run the MCP server
 async fn run_server(config: Config) -> anyhow::Result<()> {
     // Initialize logging - must write to stderr to not interfere with MCP stdio
@@ -592,11 +1047,25 @@ async fn run_server(config: Config) -> anyhow::Result<()> {
         "Database ready"
     );
 
+    let failing_tests = db
+        .get_failing_test_counts_by_workspace()
+        .unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to count failing tests");
+            Vec::new()
+        });
+
     // Create handler instance with db path for ingestion support
-    let handler = HindsightServer::new(db, workspace).with_db_path(db_path);
+    let handler = HindsightServer::new(db, workspace.clone()).with_db_path(db_path);
+    if let Some(workspace) = &workspace
+        && let Err(e) = handler
+            .prefetch_workspace(&workspace.to_string_lossy())
+            .await
+    {
+        warn!(error = %e, "Failed to prefetch workspace");
+    }
 
     // Build server details and capabilities
-    let server_details = build_server_details();
+    let server_details = build_server_details(&failing_tests);
 
     // Create stdio transport
     let transport = StdioTransport::new(TransportOptions::default())
@@ -685,10 +1154,18 @@ mod tests {
 
     #[test]
     fn test_build_server_details() {
-        let details = build_server_details();
+        let failing = vec![
+            ("ws1".to_string(), "proj".to_string(), 3),
+            ("ws2".to_string(), "other".to_string(), 1),
+        ];
+        let details = build_server_details(&failing);
         assert_eq!(details.server_info.name, "hindsight-mcp");
         assert!(details.capabilities.tools.is_some());
         assert!(details.instructions.is_some());
+
+        let meta = details.meta.expect("meta should be set");
+        assert_eq!(meta["failingTests"]["total"], 4);
+        assert_eq!(meta["failingTests"]["workspaces"][0]["failCount"], 3);
     }
 
     #[test]
@@ -734,4 +1211,35 @@ mod tests {
         let commit = get_current_commit(&nonexistent);
         assert!(commit.is_none(), "Expected None for nonexistent path");
     }
+
+    #[test]
+    fn test_test_run_json_output() {
+        let mut summary = hindsight_tests::TestRunSummary::empty();
+        summary.passed = 5;
+        summary.exec_time_secs = 4.2;
+        let stats = IngestStats {
+            test_runs_inserted: 1,
+            test_results_inserted: 5,
+            ..Default::default()
+        };
+
+        let output = test_run_json(&summary, Some("abc123"), Some(&stats)).to_string();
+        let parsed: serde_json::Value = serde_json::from_str(&output).expect("valid JSON");
+        assert_eq!(parsed["status"], "success");
+        assert_eq!(parsed["passed"], 5);
+        assert_eq!(parsed["failed"], 0);
+        assert_eq!(parsed["commit"], "abc123");
+        assert_eq!(parsed["duration_ms"], 4200);
+        assert_eq!(parsed["dry_run"], false);
+        assert_eq!(parsed["ingested"]["test_results_inserted"], 5);
+
+        summary.failed = 2;
+        let parsed: serde_json::Value =
+            serde_json::from_str(&test_run_json(&summary, None, None).to_string())
+                .expect("valid JSON");
+        assert_eq!(parsed["status"], "failure");
+        assert!(parsed["commit"].is_null());
+        assert_eq!(parsed["dry_run"], true);
+        assert!(parsed["ingested"].is_null());
+    }
 }
@@ -26,7 +26,7 @@ pub enum MigrationError {
 }
 
 /// Current schema version
-pub const CURRENT_VERSION: i32 = 1;
+pub const CURRENT_VERSION: i32 = 24;
 
 /// A database migration
 #[allow(dead_code)]
@@ -42,12 +42,13 @@ pub struct Migration {
 }
 
 /// All available migrations in order
-pub static MIGRATIONS: &[Migration] = &[Migration {
-    version: 1,
-    name: "initial_schema",
-    up: include_str!("schema.sql"),
-    down: Some(
-        r#"
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        up: include_str!("schema.sql"),
+        down: Some(
+            r#"
         DROP VIEW IF EXISTS recent_activity;
         DROP VIEW IF EXISTS failing_tests;
         DROP VIEW IF EXISTS timeline;
@@ -67,8 +68,726 @@ pub static MIGRATIONS: &[Migration] = &[Migration {
         DROP TABLE IF EXISTS workspaces;
         DROP TABLE IF EXISTS schema_migrations;
     "#,
-    ),
-}];
+        ),
+    },
+    Migration {
+        version: 2,
+        name: "commit_diff_excluded",
+        up: r#"
+        ALTER TABLE commits ADD COLUMN diff_excluded INTEGER NOT NULL DEFAULT 0;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (2, 'commit_diff_excluded', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE commits DROP COLUMN diff_excluded;
+        DELETE FROM schema_migrations WHERE version = 2;
+    "#,
+        ),
+    },
+    Migration {
+        version: 3,
+        name: "test_run_tags",
+        up: r#"
+        ALTER TABLE test_runs ADD COLUMN tags_json TEXT;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (3, 'test_run_tags', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE test_runs DROP COLUMN tags_json;
+        DELETE FROM schema_migrations WHERE version = 3;
+    "#,
+        ),
+    },
+    Migration {
+        version: 4,
+        name: "copilot_session_token_estimate",
+        up: r#"
+        ALTER TABLE copilot_sessions ADD COLUMN token_estimate INTEGER;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (4, 'copilot_session_token_estimate', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE copilot_sessions DROP COLUMN token_estimate;
+        DELETE FROM schema_migrations WHERE version = 4;
+    "#,
+        ),
+    },
+    Migration {
+        version: 5,
+        name: "git_notes",
+        up: r#"
+        CREATE TABLE IF NOT EXISTS notes (
+            id TEXT PRIMARY KEY,
+            commit_id TEXT NOT NULL REFERENCES commits(id),
+            namespace TEXT NOT NULL,
+            content TEXT NOT NULL,
+            recorded_at TEXT NOT NULL,
+            UNIQUE(commit_id, namespace)
+        );
+        CREATE INDEX IF NOT EXISTS idx_notes_commit ON notes(commit_id);
+        CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+            content,
+            content = 'notes',
+            content_rowid = 'rowid'
+        );
+        CREATE TRIGGER IF NOT EXISTS notes_ai
+        AFTER INSERT ON notes BEGIN
+            INSERT INTO notes_fts(rowid, content) VALUES (new.rowid, new.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS notes_ad
+        AFTER DELETE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, content)
+            VALUES('delete', old.rowid, old.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS notes_au
+        AFTER UPDATE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, content)
+            VALUES('delete', old.rowid, old.content);
+            INSERT INTO notes_fts(rowid, content) VALUES (new.rowid, new.content);
+        END;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (5, 'git_notes', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP TRIGGER IF EXISTS notes_au;
+        DROP TRIGGER IF EXISTS notes_ad;
+        DROP TRIGGER IF EXISTS notes_ai;
+        DROP TABLE IF EXISTS notes_fts;
+        DROP TABLE IF EXISTS notes;
+        DELETE FROM schema_migrations WHERE version = 5;
+    "#,
+        ),
+    },
+    Migration {
+        version: 6,
+        name: "test_result_event_window",
+        up: r#"
+        ALTER TABLE test_results ADD COLUMN started_seq INTEGER;
+        ALTER TABLE test_results ADD COLUMN finished_seq INTEGER;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (6, 'test_result_event_window', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE test_results DROP COLUMN finished_seq;
+        ALTER TABLE test_results DROP COLUMN started_seq;
+        DELETE FROM schema_migrations WHERE version = 6;
+    "#,
+        ),
+    },
+    Migration {
+        version: 7,
+        name: "commit_language_hint",
+        up: r#"
+        ALTER TABLE commits ADD COLUMN language_hint TEXT;
+        CREATE INDEX IF NOT EXISTS idx_commits_language_hint ON commits(language_hint);
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (7, 'commit_language_hint', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP INDEX IF EXISTS idx_commits_language_hint;
+        ALTER TABLE commits DROP COLUMN language_hint;
+        DELETE FROM schema_migrations WHERE version = 7;
+    "#,
+        ),
+    },
+    Migration {
+        version: 8,
+        name: "copilot_session_duration",
+        up: r#"
+        ALTER TABLE copilot_sessions ADD COLUMN first_message_at TEXT;
+        ALTER TABLE copilot_sessions ADD COLUMN last_message_at TEXT;
+        ALTER TABLE copilot_sessions ADD COLUMN duration_minutes REAL
+            GENERATED ALWAYS AS ((julianday(last_message_at) - julianday(first_message_at)) * 1440.0) VIRTUAL;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (8, 'copilot_session_duration', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE copilot_sessions DROP COLUMN duration_minutes;
+        ALTER TABLE copilot_sessions DROP COLUMN last_message_at;
+        ALTER TABLE copilot_sessions DROP COLUMN first_message_at;
+        DELETE FROM schema_migrations WHERE version = 8;
+    "#,
+        ),
+    },
+    Migration {
+        version: 9,
+        name: "copilot_session_topics",
+        up: r#"
+        ALTER TABLE copilot_sessions ADD COLUMN topics_json TEXT;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (9, 'copilot_session_topics', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE copilot_sessions DROP COLUMN topics_json;
+        DELETE FROM schema_migrations WHERE version = 9;
+    "#,
+        ),
+    },
+    Migration {
+        version: 10,
+        name: "commit_reverts",
+        up: r#"
+        ALTER TABLE commits ADD COLUMN is_revert INTEGER DEFAULT 0;
+        ALTER TABLE commits ADD COLUMN reverted_sha TEXT;
+        DROP VIEW IF EXISTS timeline;
+        CREATE VIEW timeline AS
+        SELECT 'commit' AS event_type,
+            c.id AS event_id,
+            c.workspace_id,
+            c.timestamp AS event_timestamp,
+            c.message AS summary,
+            CASE WHEN c.is_revert
+                THEN json_object('sha', c.sha, 'author', c.author,
+                                 'is_revert', json('true'), 'reverted_sha', c.reverted_sha)
+                ELSE json_object('sha', c.sha, 'author', c.author)
+            END AS details_json
+        FROM commits c
+        UNION ALL
+        SELECT 'test_run' AS event_type,
+            tr.id AS event_id,
+            tr.workspace_id,
+            tr.started_at AS event_timestamp,
+            printf('Tests: %d passed, %d failed, %d ignored',
+                   tr.passed_count, tr.failed_count, tr.ignored_count) AS summary,
+            json_object('commit_sha', tr.commit_sha, 'passed', tr.passed_count,
+                        'failed', tr.failed_count) AS details_json
+        FROM test_runs tr
+        UNION ALL
+        SELECT 'copilot_message' AS event_type,
+            cm.id AS event_id,
+            cs.workspace_id,
+            cm.timestamp AS event_timestamp,
+            substr(cm.content, 1, 100) AS summary,
+            json_object('role', cm.role, 'session_id', cm.session_id) AS details_json
+        FROM copilot_messages cm
+            JOIN copilot_sessions cs ON cm.session_id = cs.id;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (10, 'commit_reverts', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP VIEW IF EXISTS timeline;
+        CREATE VIEW timeline AS
+        SELECT 'commit' AS event_type,
+            c.id AS event_id,
+            c.workspace_id,
+            c.timestamp AS event_timestamp,
+            c.message AS summary,
+            json_object('sha', c.sha, 'author', c.author) AS details_json
+        FROM commits c
+        UNION ALL
+        SELECT 'test_run' AS event_type,
+            tr.id AS event_id,
+            tr.workspace_id,
+            tr.started_at AS event_timestamp,
+            printf('Tests: %d passed, %d failed, %d ignored',
+                   tr.passed_count, tr.failed_count, tr.ignored_count) AS summary,
+            json_object('commit_sha', tr.commit_sha, 'passed', tr.passed_count,
+                        'failed', tr.failed_count) AS details_json
+        FROM test_runs tr
+        UNION ALL
+        SELECT 'copilot_message' AS event_type,
+            cm.id AS event_id,
+            cs.workspace_id,
+            cm.timestamp AS event_timestamp,
+            substr(cm.content, 1, 100) AS summary,
+            json_object('role', cm.role, 'session_id', cm.session_id) AS details_json
+        FROM copilot_messages cm
+            JOIN copilot_sessions cs ON cm.session_id = cs.id;
+        ALTER TABLE commits DROP COLUMN reverted_sha;
+        ALTER TABLE commits DROP COLUMN is_revert;
+        DELETE FROM schema_migrations WHERE version = 10;
+    "#,
+        ),
+    },
+    Migration {
+        version: 11,
+        name: "workspace_repo_fingerprint",
+        up: r#"
+        ALTER TABLE workspaces ADD COLUMN repo_fingerprint TEXT;
+        CREATE INDEX IF NOT EXISTS idx_workspaces_repo_fingerprint ON workspaces(repo_fingerprint);
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (11, 'workspace_repo_fingerprint', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP INDEX IF EXISTS idx_workspaces_repo_fingerprint;
+        ALTER TABLE workspaces DROP COLUMN repo_fingerprint;
+        DELETE FROM schema_migrations WHERE version = 11;
+    "#,
+        ),
+    },
+    Migration {
+        version: 12,
+        name: "copilot_message_truncated",
+        up: r#"
+        ALTER TABLE copilot_messages ADD COLUMN truncated INTEGER DEFAULT 0;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (12, 'copilot_message_truncated', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE copilot_messages DROP COLUMN truncated;
+        DELETE FROM schema_migrations WHERE version = 12;
+    "#,
+        ),
+    },
+    Migration {
+        version: 13,
+        name: "timeline_copilot_sessions",
+        up: r#"
+        DROP VIEW IF EXISTS timeline;
+        CREATE VIEW timeline AS
+        SELECT 'commit' AS event_type,
+            c.id AS event_id,
+            c.workspace_id,
+            c.timestamp AS event_timestamp,
+            c.message AS summary,
+            CASE WHEN c.is_revert
+                THEN json_object('sha', c.sha, 'author', c.author,
+                                 'is_revert', json('true'), 'reverted_sha', c.reverted_sha)
+                ELSE json_object('sha', c.sha, 'author', c.author)
+            END AS details_json
+        FROM commits c
+        UNION ALL
+        SELECT 'test_run' AS event_type,
+            tr.id AS event_id,
+            tr.workspace_id,
+            tr.started_at AS event_timestamp,
+            printf('Tests: %d passed, %d failed, %d ignored',
+                   tr.passed_count, tr.failed_count, tr.ignored_count) AS summary,
+            json_object('commit_sha', tr.commit_sha, 'passed', tr.passed_count,
+                        'failed', tr.failed_count) AS details_json
+        FROM test_runs tr
+        UNION ALL
+        SELECT 'copilot_session' AS event_type,
+            cs.id AS event_id,
+            cs.workspace_id,
+            MIN(cm.timestamp) AS event_timestamp,
+            substr(COALESCE((SELECT f.content FROM copilot_messages f
+                             WHERE f.session_id = cs.id AND f.role = 'user'
+                             ORDER BY f.timestamp LIMIT 1), ''), 1, 100) AS summary,
+            json_object('session_id', cs.id, 'message_count', COUNT(cm.id),
+                        'model', json_extract(cs.metadata_json, '$.model'),
+                        'last_message', MAX(cm.timestamp)) AS details_json
+        FROM copilot_sessions cs
+            JOIN copilot_messages cm ON cm.session_id = cs.id
+        GROUP BY cs.id;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (13, 'timeline_copilot_sessions', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP VIEW IF EXISTS timeline;
+        CREATE VIEW timeline AS
+        SELECT 'commit' AS event_type,
+            c.id AS event_id,
+            c.workspace_id,
+            c.timestamp AS event_timestamp,
+            c.message AS summary,
+            CASE WHEN c.is_revert
+                THEN json_object('sha', c.sha, 'author', c.author,
+                                 'is_revert', json('true'), 'reverted_sha', c.reverted_sha)
+                ELSE json_object('sha', c.sha, 'author', c.author)
+            END AS details_json
+        FROM commits c
+        UNION ALL
+        SELECT 'test_run' AS event_type,
+            tr.id AS event_id,
+            tr.workspace_id,
+            tr.started_at AS event_timestamp,
+            printf('Tests: %d passed, %d failed, %d ignored',
+                   tr.passed_count, tr.failed_count, tr.ignored_count) AS summary,
+            json_object('commit_sha', tr.commit_sha, 'passed', tr.passed_count,
+                        'failed', tr.failed_count) AS details_json
+        FROM test_runs tr
+        UNION ALL
+        SELECT 'copilot_message' AS event_type,
+            cm.id AS event_id,
+            cs.workspace_id,
+            cm.timestamp AS event_timestamp,
+            substr(cm.content, 1, 100) AS summary,
+            json_object('role', cm.role, 'session_id', cm.session_id) AS details_json
+        FROM copilot_messages cm
+            JOIN copilot_sessions cs ON cm.session_id = cs.id;
+        DELETE FROM schema_migrations WHERE version = 13;
+    "#,
+        ),
+    },
+    Migration {
+        version: 14,
+        name: "commit_stats",
+        up: r#"
+        ALTER TABLE commits ADD COLUMN stats_json TEXT;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (14, 'commit_stats', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE commits DROP COLUMN stats_json;
+        DELETE FROM schema_migrations WHERE version = 14;
+    "#,
+        ),
+    },
+    Migration {
+        version: 15,
+        name: "workspace_last_ingest",
+        up: r#"
+        ALTER TABLE workspaces ADD COLUMN last_git_ingest_at TEXT;
+        ALTER TABLE workspaces ADD COLUMN last_copilot_ingest_at TEXT;
+        ALTER TABLE workspaces ADD COLUMN last_test_ingest_at TEXT;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (15, 'workspace_last_ingest', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE workspaces DROP COLUMN last_test_ingest_at;
+        ALTER TABLE workspaces DROP COLUMN last_copilot_ingest_at;
+        ALTER TABLE workspaces DROP COLUMN last_git_ingest_at;
+        DELETE FROM schema_migrations WHERE version = 15;
+    "#,
+        ),
+    },
+    Migration {
+        version: 16,
+        name: "copilot_message_agent",
+        up: r#"
+        ALTER TABLE copilot_messages ADD COLUMN agent TEXT;
+        CREATE INDEX IF NOT EXISTS idx_copilot_messages_agent ON copilot_messages(agent);
+        DROP TRIGGER IF EXISTS copilot_messages_au;
+        DROP TRIGGER IF EXISTS copilot_messages_ad;
+        DROP TRIGGER IF EXISTS copilot_messages_ai;
+        DROP TABLE IF EXISTS copilot_messages_fts;
+        CREATE VIRTUAL TABLE copilot_messages_fts USING fts5(
+            content,
+            agent,
+            content = 'copilot_messages',
+            content_rowid = 'rowid'
+        );
+        CREATE TRIGGER copilot_messages_ai
+        AFTER INSERT ON copilot_messages BEGIN
+            INSERT INTO copilot_messages_fts(rowid, content, agent)
+            VALUES (new.rowid, new.content, new.agent);
+        END;
+        CREATE TRIGGER copilot_messages_ad
+        AFTER DELETE ON copilot_messages BEGIN
+            INSERT INTO copilot_messages_fts(copilot_messages_fts, rowid, content, agent)
+            VALUES('delete', old.rowid, old.content, old.agent);
+        END;
+        CREATE TRIGGER copilot_messages_au
+        AFTER UPDATE ON copilot_messages BEGIN
+            INSERT INTO copilot_messages_fts(copilot_messages_fts, rowid, content, agent)
+            VALUES('delete', old.rowid, old.content, old.agent);
+            INSERT INTO copilot_messages_fts(rowid, content, agent)
+            VALUES (new.rowid, new.content, new.agent);
+        END;
+        INSERT INTO copilot_messages_fts(copilot_messages_fts) VALUES('rebuild');
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (16, 'copilot_message_agent', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP TRIGGER IF EXISTS copilot_messages_au;
+        DROP TRIGGER IF EXISTS copilot_messages_ad;
+        DROP TRIGGER IF EXISTS copilot_messages_ai;
+        DROP TABLE IF EXISTS copilot_messages_fts;
+        DROP INDEX IF EXISTS idx_copilot_messages_agent;
+        ALTER TABLE copilot_messages DROP COLUMN agent;
+        CREATE VIRTUAL TABLE copilot_messages_fts USING fts5(
+            content,
+            content = 'copilot_messages',
+            content_rowid = 'rowid'
+        );
+        CREATE TRIGGER copilot_messages_ai
+        AFTER INSERT ON copilot_messages BEGIN
+            INSERT INTO copilot_messages_fts(rowid, content) VALUES (new.rowid, new.content);
+        END;
+        CREATE TRIGGER copilot_messages_ad
+        AFTER DELETE ON copilot_messages BEGIN
+            INSERT INTO copilot_messages_fts(copilot_messages_fts, rowid, content)
+            VALUES('delete', old.rowid, old.content);
+        END;
+        CREATE TRIGGER copilot_messages_au
+        AFTER UPDATE ON copilot_messages BEGIN
+            INSERT INTO copilot_messages_fts(copilot_messages_fts, rowid, content)
+            VALUES('delete', old.rowid, old.content);
+            INSERT INTO copilot_messages_fts(rowid, content) VALUES (new.rowid, new.content);
+        END;
+        INSERT INTO copilot_messages_fts(copilot_messages_fts) VALUES('rebuild');
+        DELETE FROM schema_migrations WHERE version = 16;
+    "#,
+        ),
+    },
+    Migration {
+        version: 17,
+        name: "ci_annotations",
+        up: r#"
+        CREATE TABLE IF NOT EXISTS ci_annotations (
+            id TEXT PRIMARY KEY,
+            run_id TEXT NOT NULL REFERENCES test_runs(id) ON DELETE CASCADE,
+            level TEXT NOT NULL CHECK (level IN ('error', 'warning')),
+            file TEXT,
+            line INTEGER,
+            message TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_ci_annotations_run ON ci_annotations(run_id);
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (17, 'ci_annotations', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP INDEX IF EXISTS idx_ci_annotations_run;
+        DROP TABLE IF EXISTS ci_annotations;
+        DELETE FROM schema_migrations WHERE version = 17;
+    "#,
+        ),
+    },
+    Migration {
+        version: 18,
+        name: "workspace_events",
+        up: r#"
+        CREATE TABLE IF NOT EXISTS workspace_events (
+            id TEXT PRIMARY KEY,
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+            event_type TEXT NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            event_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_workspace_events_workspace
+            ON workspace_events(workspace_id, event_at);
+        DROP VIEW IF EXISTS timeline;
+        CREATE VIEW timeline AS
+        SELECT 'commit' AS event_type,
+            c.id AS event_id,
+            c.workspace_id,
+            c.timestamp AS event_timestamp,
+            c.message AS summary,
+            CASE WHEN c.is_revert
+                THEN json_object('sha', c.sha, 'author', c.author,
+                                 'is_revert', json('true'), 'reverted_sha', c.reverted_sha)
+                ELSE json_object('sha', c.sha, 'author', c.author)
+            END AS details_json
+        FROM commits c
+        UNION ALL
+        SELECT 'test_run' AS event_type,
+            tr.id AS event_id,
+            tr.workspace_id,
+            tr.started_at AS event_timestamp,
+            printf('Tests: %d passed, %d failed, %d ignored',
+                   tr.passed_count, tr.failed_count, tr.ignored_count) AS summary,
+            json_object('commit_sha', tr.commit_sha, 'passed', tr.passed_count,
+                        'failed', tr.failed_count) AS details_json
+        FROM test_runs tr
+        UNION ALL
+        SELECT 'copilot_session' AS event_type,
+            cs.id AS event_id,
+            cs.workspace_id,
+            MIN(cm.timestamp) AS event_timestamp,
+            substr(COALESCE((SELECT f.content FROM copilot_messages f
+                             WHERE f.session_id = cs.id AND f.role = 'user'
+                             ORDER BY f.timestamp LIMIT 1), ''), 1, 100) AS summary,
+            json_object('session_id', cs.id, 'message_count', COUNT(cm.id),
+                        'model', json_extract(cs.metadata_json, '$.model'),
+                        'last_message', MAX(cm.timestamp)) AS details_json
+        FROM copilot_sessions cs
+            JOIN copilot_messages cm ON cm.session_id = cs.id
+        GROUP BY cs.id
+        UNION ALL
+        SELECT we.event_type,
+            we.id AS event_id,
+            we.workspace_id,
+            we.event_at AS event_timestamp,
+            we.title AS summary,
+            json_object('description', we.description) AS details_json
+        FROM workspace_events we;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (18, 'workspace_events', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP VIEW IF EXISTS timeline;
+        CREATE VIEW timeline AS
+        SELECT 'commit' AS event_type,
+            c.id AS event_id,
+            c.workspace_id,
+            c.timestamp AS event_timestamp,
+            c.message AS summary,
+            CASE WHEN c.is_revert
+                THEN json_object('sha', c.sha, 'author', c.author,
+                                 'is_revert', json('true'), 'reverted_sha', c.reverted_sha)
+                ELSE json_object('sha', c.sha, 'author', c.author)
+            END AS details_json
+        FROM commits c
+        UNION ALL
+        SELECT 'test_run' AS event_type,
+            tr.id AS event_id,
+            tr.workspace_id,
+            tr.started_at AS event_timestamp,
+            printf('Tests: %d passed, %d failed, %d ignored',
+                   tr.passed_count, tr.failed_count, tr.ignored_count) AS summary,
+            json_object('commit_sha', tr.commit_sha, 'passed', tr.passed_count,
+                        'failed', tr.failed_count) AS details_json
+        FROM test_runs tr
+        UNION ALL
+        SELECT 'copilot_session' AS event_type,
+            cs.id AS event_id,
+            cs.workspace_id,
+            MIN(cm.timestamp) AS event_timestamp,
+            substr(COALESCE((SELECT f.content FROM copilot_messages f
+                             WHERE f.session_id = cs.id AND f.role = 'user'
+                             ORDER BY f.timestamp LIMIT 1), ''), 1, 100) AS summary,
+            json_object('session_id', cs.id, 'message_count', COUNT(cm.id),
+                        'model', json_extract(cs.metadata_json, '$.model'),
+                        'last_message', MAX(cm.timestamp)) AS details_json
+        FROM copilot_sessions cs
+            JOIN copilot_messages cm ON cm.session_id = cs.id
+        GROUP BY cs.id;
+        DROP INDEX IF EXISTS idx_workspace_events_workspace;
+        DROP TABLE IF EXISTS workspace_events;
+        DELETE FROM schema_migrations WHERE version = 18;
+    "#,
+        ),
+    },
+    Migration {
+        version: 19,
+        name: "test_run_wall_time",
+        up: r#"
+        ALTER TABLE test_runs ADD COLUMN wall_time_secs REAL
+            GENERATED ALWAYS AS (
+                CAST((julianday(finished_at) - julianday(started_at)) * 86400 AS REAL)
+            ) VIRTUAL;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (19, 'test_run_wall_time', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE test_runs DROP COLUMN wall_time_secs;
+        DELETE FROM schema_migrations WHERE version = 19;
+    "#,
+        ),
+    },
+    Migration {
+        version: 20,
+        name: "ingest_checkpoints",
+        up: r#"
+        CREATE TABLE IF NOT EXISTS ingest_checkpoints (
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+            source TEXT NOT NULL,
+            last_sha TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (workspace_id, source)
+        );
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (20, 'ingest_checkpoints', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP TABLE IF EXISTS ingest_checkpoints;
+        DELETE FROM schema_migrations WHERE version = 20;
+    "#,
+        ),
+    },
+    Migration {
+        version: 21,
+        name: "copilot_message_code_blocks",
+        up: r#"
+        ALTER TABLE copilot_messages ADD COLUMN code_blocks_json TEXT;
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (21, 'copilot_message_code_blocks', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        ALTER TABLE copilot_messages DROP COLUMN code_blocks_json;
+        DELETE FROM schema_migrations WHERE version = 21;
+    "#,
+        ),
+    },
+    Migration {
+        version: 22,
+        name: "retention_policies",
+        up: r#"
+        CREATE TABLE IF NOT EXISTS retention_policies (
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+            source TEXT NOT NULL,
+            max_age_days INTEGER,
+            max_records INTEGER,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (workspace_id, source)
+        );
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (22, 'retention_policies', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP TABLE IF EXISTS retention_policies;
+        DELETE FROM schema_migrations WHERE version = 22;
+    "#,
+        ),
+    },
+    Migration {
+        version: 23,
+        name: "git_tags",
+        up: r#"
+        CREATE TABLE IF NOT EXISTS tags (
+            id TEXT PRIMARY KEY,
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id),
+            name TEXT NOT NULL,
+            sha TEXT NOT NULL,
+            tagged_sha TEXT NOT NULL,
+            tagger TEXT,
+            message TEXT,
+            is_annotated INTEGER NOT NULL DEFAULT 0,
+            timestamp TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            UNIQUE(workspace_id, name),
+            FOREIGN KEY (workspace_id, tagged_sha) REFERENCES commits(workspace_id, sha)
+        );
+        CREATE INDEX IF NOT EXISTS idx_tags_timestamp ON tags(timestamp);
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (23, 'git_tags', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP INDEX IF EXISTS idx_tags_timestamp;
+        DROP TABLE IF EXISTS tags;
+        DELETE FROM schema_migrations WHERE version = 23;
+    "#,
+        ),
+    },
+    Migration {
+        version: 24,
+        name: "test_inventory",
+        up: r#"
+        CREATE TABLE IF NOT EXISTS test_inventory (
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id),
+            suite_name TEXT NOT NULL,
+            test_name TEXT NOT NULL,
+            ignored INTEGER NOT NULL DEFAULT 0,
+            first_seen_at TEXT NOT NULL,
+            last_seen_at TEXT NOT NULL,
+            PRIMARY KEY (workspace_id, suite_name, test_name)
+        );
+        INSERT OR IGNORE INTO schema_migrations (version, name, applied_at)
+        VALUES (24, 'test_inventory', datetime('now'));
+    "#,
+        down: Some(
+            r#"
+        DROP TABLE IF EXISTS test_inventory;
+        DELETE FROM schema_migrations WHERE version = 24;
+    "#,
+        ),
+    },
+];
 
 /// Get the current schema version from the database
 ///
@@ -275,6 +994,507 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_migration_adds_diff_excluded_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('commits') WHERE name = 'diff_excluded'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "commits.diff_excluded should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_test_run_tags_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('test_runs') WHERE name = 'tags_json'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "test_runs.tags_json should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_token_estimate_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('copilot_sessions') WHERE name = 'token_estimate'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "copilot_sessions.token_estimate should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_event_window_columns() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let count: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('test_results') WHERE name IN ('started_seq', 'finished_seq')",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(count, 2, "test_results event window columns should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_language_hint_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('commits') WHERE name = 'language_hint'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "commits.language_hint should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_session_duration_columns() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        // Generated columns are only listed by table_xinfo
+        let count: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_xinfo('copilot_sessions')
+                 WHERE name IN ('first_message_at', 'last_message_at', 'duration_minutes')",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(count, 3, "copilot_sessions duration columns should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_session_topics_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('copilot_sessions') WHERE name = 'topics_json'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "copilot_sessions.topics_json should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_revert_columns() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        for column in ["is_revert", "reverted_sha"] {
+            let exists: i32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('commits') WHERE name = ?1",
+                    [column],
+                    |row| row.get(0),
+                )
+                .expect("query");
+            assert_eq!(exists, 1, "commits.{} should exist", column);
+        }
+    }
+
+    #[test]
+    fn test_migration_adds_repo_fingerprint_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('workspaces') WHERE name = 'repo_fingerprint'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "workspaces.repo_fingerprint should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_message_truncated_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('copilot_messages') WHERE name = 'truncated'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "copilot_messages.truncated should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_commit_stats_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('commits') WHERE name = 'stats_json'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "commits.stats_json should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_last_ingest_columns() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        for column in [
+            "last_git_ingest_at",
+            "last_copilot_ingest_at",
+            "last_test_ingest_at",
+        ] {
+            let exists: i32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('workspaces') WHERE name = ?1",
+                    [column],
+                    |row| row.get(0),
+                )
+                .expect("query");
+            assert_eq!(exists, 1, "workspaces.{} should exist", column);
+        }
+    }
+
+    #[test]
+    fn test_migration_adds_message_agent_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('copilot_messages') WHERE name = 'agent'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "copilot_messages.agent should exist");
+    }
+
+    #[test]
+    fn test_rollback_restores_message_fts() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        rollback_to(&conn, 15).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 15);
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('copilot_messages') WHERE name = 'agent'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 0, "copilot_messages.agent should be dropped");
+
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM copilot_messages_fts WHERE copilot_messages_fts MATCH 'x'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("copilot_messages_fts should still be queryable");
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_migration_creates_ci_annotations_table() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        let exists: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'ci_annotations'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(exists, 1, "ci_annotations table should exist");
+    }
+
+    #[test]
+    fn test_migration_adds_wall_time_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+             INSERT INTO test_runs (id, workspace_id, started_at, finished_at)
+             VALUES ('run-1', 'ws-1', '2026-04-01T10:00:00+00:00', '2026-04-01T10:00:04.5+00:00'),
+                    ('run-2', 'ws-1', '2026-04-01T11:00:00+00:00', NULL);",
+        )
+        .expect("insert runs");
+        let wall_time: f64 = conn
+            .query_row(
+                "SELECT wall_time_secs FROM test_runs WHERE id = 'run-1'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert!((wall_time - 4.5).abs() < 0.01, "got {wall_time}");
+        let unfinished: Option<f64> = conn
+            .query_row(
+                "SELECT wall_time_secs FROM test_runs WHERE id = 'run-2'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(unfinished, None);
+
+        rollback_to(&conn, 18).expect("rollback");
+        assert!(
+            conn.prepare("SELECT wall_time_secs FROM test_runs")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_migration_adds_ingest_checkpoints() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+             INSERT INTO ingest_checkpoints (workspace_id, source, last_sha, updated_at)
+             VALUES ('ws-1', 'git', 'abc123', datetime('now'));",
+        )
+        .expect("insert checkpoint");
+        let duplicate = conn.execute(
+            "INSERT INTO ingest_checkpoints (workspace_id, source, last_sha, updated_at)
+             VALUES ('ws-1', 'git', 'def456', datetime('now'))",
+            [],
+        );
+        assert!(
+            duplicate.is_err(),
+            "one checkpoint per workspace and source"
+        );
+
+        rollback_to(&conn, 19).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 19);
+        assert!(
+            conn.prepare("SELECT last_sha FROM ingest_checkpoints")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_migration_adds_test_inventory() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+             INSERT INTO test_inventory (workspace_id, suite_name, test_name, first_seen_at, last_seen_at)
+             VALUES ('ws-1', 'my-crate', 'tests::test_one', datetime('now'), datetime('now'));",
+        )
+        .expect("insert inventory");
+
+        // A test is listed once per workspace and suite
+        let duplicate = conn.execute(
+            "INSERT INTO test_inventory (workspace_id, suite_name, test_name, first_seen_at, last_seen_at)
+             VALUES ('ws-1', 'my-crate', 'tests::test_one', datetime('now'), datetime('now'))",
+            [],
+        );
+        assert!(duplicate.is_err());
+
+        rollback_to(&conn, 23).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 23);
+        assert!(
+            conn.prepare("SELECT test_name FROM test_inventory")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_migration_adds_tags() {
+        let conn = Connection::open_in_memory().expect("create db");
+        conn.execute_batch("PRAGMA foreign_keys = ON")
+            .expect("pragma");
+        migrate(&conn).expect("migrate");
+
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+             INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+             VALUES ('c-1', 'ws-1', 'abc123', 'Release', 'Dev', datetime('now'), datetime('now'));
+             INSERT INTO tags (id, workspace_id, name, sha, tagged_sha, timestamp, created_at)
+             VALUES ('t-1', 'ws-1', 'v1.0.0', 'abc123', 'abc123', datetime('now'), datetime('now'));",
+        )
+        .expect("insert tag");
+
+        // Tags must point at an ingested commit of the same workspace
+        let orphan = conn.execute(
+            "INSERT INTO tags (id, workspace_id, name, sha, tagged_sha, timestamp, created_at)
+             VALUES ('t-2', 'ws-1', 'v2.0.0', 'fff999', 'fff999', datetime('now'), datetime('now'))",
+            [],
+        );
+        assert!(orphan.is_err());
+
+        rollback_to(&conn, 22).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 22);
+        assert!(conn.prepare("SELECT name FROM tags").is_err());
+    }
+
+    #[test]
+    fn test_migration_adds_retention_policies() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+             INSERT INTO retention_policies (workspace_id, source, max_age_days, updated_at)
+             VALUES ('ws-1', 'git', 365, datetime('now'));",
+        )
+        .expect("insert policy");
+
+        rollback_to(&conn, 21).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 21);
+        assert!(
+            conn.prepare("SELECT max_age_days FROM retention_policies")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_migration_adds_code_blocks_column() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        assert!(
+            conn.prepare("SELECT code_blocks_json FROM copilot_messages")
+                .is_ok()
+        );
+
+        rollback_to(&conn, 20).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 20);
+        assert!(
+            conn.prepare("SELECT code_blocks_json FROM copilot_messages")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_migration_adds_workspace_events_to_timeline() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+             INSERT INTO workspace_events (id, workspace_id, event_type, title, event_at)
+             VALUES ('ev-1', 'ws-1', 'deploy', 'Shipped v2', '2026-04-01T10:00:00+00:00');",
+        )
+        .expect("insert event");
+        let event_type: String = conn
+            .query_row(
+                "SELECT event_type FROM timeline WHERE event_id = 'ev-1'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("event should be in the timeline");
+        assert_eq!(event_type, "deploy");
+
+        rollback_to(&conn, 17).expect("rollback");
+        let sql: String = conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE type = 'view' AND name = 'timeline'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("timeline view should exist");
+        assert!(!sql.contains("workspace_events"));
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'workspace_events'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(tables, 0);
+    }
+
+    #[test]
+    fn test_rollback_restores_timeline_view() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        rollback_to(&conn, 9).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 9);
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM timeline", [], |row| row.get(0))
+            .expect("timeline should still be queryable");
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_rollback_restores_per_message_timeline() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        rollback_to(&conn, 12).expect("rollback");
+        assert_eq!(get_version(&conn).expect("version"), 12);
+
+        let sql: String = conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE type = 'view' AND name = 'timeline'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("timeline view should exist");
+        assert!(sql.contains("'copilot_message'"));
+    }
+
+    #[test]
+    fn test_migration_creates_notes_tables() {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrate(&conn).expect("migrate");
+
+        for table in ["notes", "notes_fts"] {
+            let exists: i32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?",
+                    [table],
+                    |row| row.get(0),
+                )
+                .expect("query");
+            assert_eq!(exists, 1, "Table {} should exist", table);
+        }
+    }
+
     #[test]
     fn test_rollback() {
         let conn = Connection::open_in_memory().expect("create db");
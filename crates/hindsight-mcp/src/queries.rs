@@ -6,10 +6,21 @@
 //! This module provides high-level query functions for searching and
 //! retrieving development history data from the SQLite database.
 
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use hindsight_tests::{AnnotationLevel, CiAnnotation};
 use rusqlite::{Connection, OptionalExtension, params};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::db::{
+    COMMIT_COLUMNS, CommitRecord, CopilotMessageRecord, CopilotSessionRecord, MESSAGE_COLUMNS,
+    SESSION_COLUMNS, TestResultRecord, WORKSPACE_COLUMNS, WorkspaceRecord, commit_from_row,
+    copilot_session_with_messages, message_from_row, session_from_row, timestamp_at,
+    workspace_from_row,
+};
+
 /// Query errors
 #[derive(Debug, Error)]
 pub enum QueryError {
@@ -25,7 +36,8 @@ pub enum QueryError {
 /// A timeline event representing activity in the workspace
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimelineEvent {
-    /// Event type: 'commit', 'test_run', or 'copilot_message'
+    /// Event type: 'commit', 'test_run', 'copilot_session', or the type of a
+    /// custom workspace event such as 'deploy'
     pub event_type: String,
     /// Unique identifier for the event (UUID as string)
     pub event_id: String,
@@ -39,10 +51,77 @@ pub struct TimelineEvent {
     pub details_json: Option<String>,
 }
 
+/// A time window with optional inclusive bounds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    /// Earliest time to include, or unbounded if `None`
+    pub start: Option<DateTime<Utc>>,
+    /// Latest time to include, or unbounded if `None`
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    /// Create a range between two optional bounds
+    #[must_use]
+    pub fn new(start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> Self {
+        Self { start, end }
+    }
+
+    /// Create a range covering everything from `start` onwards
+    #[must_use]
+    pub fn since(start: DateTime<Utc>) -> Self {
+        Self::new(Some(start), None)
+    }
+}
+
+/// Restrictions applied by [`get_timeline`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimelineFilter {
+    /// Workspace path or ID to filter by
+    pub workspace_filter: Option<String>,
+    /// Time window the events must fall in
+    pub time_range: TimeRange,
+    /// Event types to include, e.g. `commit`; empty means all types
+    pub event_types: Vec<String>,
+}
+
+impl TimelineFilter {
+    /// Create a filter that matches every event
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only include events from a workspace path or ID
+    #[must_use]
+    pub fn with_workspace(mut self, workspace: impl Into<String>) -> Self {
+        self.workspace_filter = Some(workspace.into());
+        self
+    }
+
+    /// Only include events within a time range
+    #[must_use]
+    pub fn with_time_range(mut self, time_range: TimeRange) -> Self {
+        self.time_range = time_range;
+        self
+    }
+
+    /// Only include events of the given types
+    #[must_use]
+    pub fn with_event_types<I, S>(mut self, event_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.event_types = event_types.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
 /// A search result from full-text search
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
-    /// Type of result: 'commit' or 'copilot_message'
+    /// Type of result: 'commit', 'copilot_message' or 'note'
     pub result_type: String,
     /// Unique identifier (UUID as string)
     pub id: String,
@@ -52,6 +131,10 @@ pub struct SearchResult {
     pub rank: f64,
     /// ISO 8601 timestamp
     pub timestamp: String,
+    /// Workspace the matching entity belongs to
+    pub workspace_id: Option<String>,
+    /// Link back to the source entity (commit SHA or Copilot session ID)
+    pub source_info: Option<String>,
 }
 
 /// A failing test result
@@ -75,6 +158,359 @@ pub struct FailingTest {
     pub started_at: String,
 }
 
+/// A test that started failing after previously passing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Regression {
+    /// Suite name
+    pub suite_name: String,
+    /// Full test name
+    pub test_name: String,
+    /// ISO 8601 timestamp of the last passing run before the failure
+    pub last_pass_at: String,
+    /// ISO 8601 timestamp of the first failing run after that pass
+    pub first_failure_at: String,
+    /// Commit SHA of the first failing run
+    pub commit_sha: Option<String>,
+}
+
+/// A test that passed throughout the first half of a time window and then
+/// failed during the second half
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestRegression {
+    /// Suite name
+    pub suite_name: String,
+    /// Full test name
+    pub test_name: String,
+    /// ISO 8601 timestamp of the first failing run in the second half
+    pub first_failure_at: String,
+    /// ISO 8601 timestamp of the latest passing run before that failure
+    pub last_pass_before_failure: String,
+    /// ID of the first failing run
+    pub failure_run_id: String,
+}
+
+/// A test that has failed in every one of its most recent runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureStreak {
+    /// Suite name
+    pub suite_name: String,
+    /// Full test name
+    pub test_name: String,
+    /// Number of consecutive failing runs, ending with the latest
+    pub streak_length: u32,
+    /// ISO 8601 timestamp of the latest failing run
+    pub last_failed_at: String,
+    /// Commit SHA of the first run in the streak
+    pub first_failure_commit: Option<String>,
+}
+
+/// How often a test failed over a time period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlakyTestStat {
+    /// Suite name
+    pub suite_name: String,
+    /// Full test name
+    pub test_name: String,
+    /// Number of runs in which the test failed or timed out
+    pub failure_count: u64,
+    /// Number of runs in which the test was not ignored
+    pub total_runs: u64,
+    /// `failure_count` as a percentage of `total_runs`
+    pub failure_rate_pct: f64,
+    /// ISO 8601 timestamp of the latest failing run
+    pub last_failed_at: String,
+}
+
+/// Wall-clock duration of a test run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestRunDuration {
+    /// Test run ID
+    pub run_id: String,
+    /// Commit SHA the run was linked to
+    pub commit_sha: Option<String>,
+    /// ISO 8601 timestamp when the run started
+    pub started_at: String,
+    /// Seconds between the start and finish of the run
+    pub wall_time_secs: f64,
+}
+
+/// Outcome of one test in a single run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestHistoryEntry {
+    /// Test run ID
+    pub run_id: String,
+    /// Outcome: passed/failed/ignored/timedout
+    pub outcome: String,
+    /// Duration in milliseconds
+    pub duration_ms: Option<i64>,
+    /// ISO 8601 timestamp when the run started
+    pub started_at: String,
+    /// Commit SHA the run was linked to
+    pub commit_sha: Option<String>,
+}
+
+/// Duration percentiles for a single test over its recent runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestDurationStat {
+    /// Suite name
+    pub suite_name: String,
+    /// Full test name
+    pub test_name: String,
+    /// Median duration in milliseconds
+    pub p50_ms: i64,
+    /// 90th percentile duration in milliseconds
+    pub p90_ms: i64,
+    /// 99th percentile duration in milliseconds
+    pub p99_ms: i64,
+    /// Longest duration in milliseconds
+    pub max_ms: i64,
+    /// Number of durations the percentiles were computed from
+    pub sample_count: u64,
+}
+
+/// A workspace with its activity over a recent time window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceActivity {
+    /// The workspace
+    #[serde(flatten)]
+    pub workspace: WorkspaceRecord,
+    /// Timestamp of the latest commit in the window (ISO 8601)
+    pub last_commit_at: Option<String>,
+    /// Start of the latest test run in the window (ISO 8601)
+    pub last_test_run_at: Option<String>,
+    /// Last update of the latest Copilot session in the window (ISO 8601)
+    pub last_copilot_session_at: Option<String>,
+    /// Commits, test runs and Copilot sessions in the window
+    pub total_events_in_window: u64,
+}
+
+/// A git tag on an ingested commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitTag {
+    /// Tag name
+    pub name: String,
+    /// SHA of the tag object, or of the commit for lightweight tags
+    pub sha: String,
+    /// SHA of the tagged commit
+    pub tagged_sha: String,
+    /// Tagger name, for annotated tags
+    pub tagger: Option<String>,
+    /// Tag message, for annotated tags
+    pub message: Option<String>,
+    /// Whether this is an annotated tag
+    pub is_annotated: bool,
+    /// When the tag was made (ISO 8601)
+    pub timestamp: String,
+    /// First line of the tagged commit's message
+    pub commit_subject: String,
+}
+
+/// Test suites that ran concurrently within a test run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParallelGroup {
+    /// Suites with at least one test in the group (sorted)
+    pub suite_names: Vec<String>,
+    /// Largest number of tests running at the same time
+    pub max_parallelism: usize,
+}
+
+/// Commit activity for a single calendar day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyCommitVolume {
+    /// Date in `YYYY-MM-DD` format (UTC)
+    pub date: String,
+    /// Number of commits on this day
+    pub commit_count: u64,
+    /// Number of distinct commit authors on this day
+    pub distinct_authors: u64,
+}
+
+/// Change activity of a single file path across commits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveFileStat {
+    /// Path of the file
+    pub path: String,
+    /// Extension as returned by [`hindsight_git::file_extension`]
+    pub extension: String,
+    /// Number of commits that touched the file
+    pub commit_count: u64,
+    /// Total lines added
+    pub additions: u64,
+    /// Total lines deleted
+    pub deletions: u64,
+}
+
+/// Day names indexed by SQLite's `strftime('%w')` weekday number
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Commit counts by day of week and hour of day (UTC)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityHeatmap {
+    /// Commit counts indexed by `[weekday][hour]`, where weekday 0 is Sunday
+    pub counts: [[u32; 24]; 7],
+}
+
+impl ActivityHeatmap {
+    /// Total number of commits counted
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.counts.iter().flatten().map(|&c| u64::from(c)).sum()
+    }
+
+    /// Flatten the hours with at least one commit, Sunday midnight first
+    #[must_use]
+    pub fn cells(&self) -> Vec<HeatmapCell> {
+        let mut cells = Vec::new();
+        for (weekday, hours) in self.counts.iter().enumerate() {
+            for (hour, &count) in hours.iter().enumerate() {
+                if count > 0 {
+                    cells.push(HeatmapCell {
+                        day: WEEKDAY_NAMES[weekday].to_string(),
+                        hour: hour as u32,
+                        count,
+                    });
+                }
+            }
+        }
+        cells
+    }
+}
+
+/// One hour of the week in an [`ActivityHeatmap`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeatmapCell {
+    /// Day of the week, e.g. "Tuesday"
+    pub day: String,
+    /// Hour of the day (0-23, UTC)
+    pub hour: u32,
+    /// Number of commits
+    pub count: u32,
+}
+
+/// Commit activity of a single author
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributorStat {
+    /// Author name
+    pub author: String,
+    /// Author email, if recorded
+    pub author_email: Option<String>,
+    /// Number of commits by this author
+    pub commit_count: u64,
+    /// ISO 8601 timestamp of the author's earliest commit
+    pub first_commit_at: String,
+    /// ISO 8601 timestamp of the author's latest commit
+    pub last_commit_at: String,
+    /// Files changed, summed over all of the author's commits
+    pub files_changed: u64,
+}
+
+/// How often a kind of Copilot context variable is attached to messages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableKindStats {
+    /// Variable kind (e.g. "file", "workspace", "promptFile")
+    pub kind: String,
+    /// Number of times a variable of this kind was attached
+    pub usage_count: u64,
+    /// Number of sessions that used this kind at least once
+    pub distinct_sessions: u64,
+}
+
+/// Change in how often a Copilot topic comes up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopicTrend {
+    /// Topic keyword
+    pub keyword: String,
+    /// Sessions mentioning the topic in the recent period
+    pub session_count: u64,
+    /// Recent session count divided by the prior period's count
+    pub trend: f64,
+}
+
+/// A code block found in a Copilot message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeBlockMatch {
+    /// Message the block was extracted from
+    pub message_id: String,
+    /// Session the message belongs to
+    pub session_id: String,
+    /// Workspace the session belongs to
+    pub workspace_id: String,
+    /// Message timestamp (RFC3339)
+    pub timestamp: String,
+    /// Fence info string language, if any
+    pub language: Option<String>,
+    /// Code inside the block
+    pub content: String,
+}
+
+/// A user question and the assistant's reply in a Copilot session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QAPair {
+    /// The user's message
+    pub question: CopilotMessageRecord,
+    /// The assistant's reply, if there was one
+    pub answer: Option<CopilotMessageRecord>,
+}
+
+/// A Copilot session with its full message history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionDetail {
+    /// Session ID (UUID)
+    pub id: String,
+    /// Original VS Code session ID
+    pub vscode_session_id: String,
+    /// ISO 8601 creation timestamp
+    pub created_at: String,
+    /// ISO 8601 last update timestamp
+    pub updated_at: String,
+    /// Model used for the session, if recorded
+    pub model: Option<String>,
+    /// Chat mode, if recorded
+    pub mode: Option<String>,
+    /// Messages in timestamp order
+    pub messages: Vec<MessageDetail>,
+}
+
+/// A single message of a [`SessionDetail`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageDetail {
+    /// Message role (user, assistant, system)
+    pub role: String,
+    /// Message content
+    pub content: String,
+    /// ISO 8601 timestamp
+    pub timestamp: String,
+    /// Attached variables (files, selections) as JSON
+    pub variables_json: Option<String>,
+}
+
+/// A Copilot session condensed to a single activity entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopilotSessionSummary {
+    /// Session ID (UUID)
+    pub session_id: String,
+    /// Workspace ID
+    pub workspace_id: String,
+    /// ISO 8601 timestamp of the first message
+    pub first_message: String,
+    /// ISO 8601 timestamp of the last message
+    pub last_message: String,
+    /// Number of messages in the session
+    pub message_count: u64,
+    /// Model used for the session, if recorded
+    pub model: Option<String>,
+    /// Text of the first user message, truncated to 100 characters
+    pub preview: Option<String>,
+}
+
 /// Get workspace ID from a workspace path
 ///
 /// The workspace filter can be either a workspace ID (UUID) or a filesystem path.
@@ -117,64 +553,68 @@ fn resolve_workspace_filter(conn: &Connection, filter: &str) -> Result<Option<St
 
 /// Query the timeline view for recent activity
 ///
+/// Events are returned most recent first. A `None` filter returns events
+/// from every workspace.
+///
 /// # Arguments
 ///
 /// * `conn` - Database connection
 /// * `limit` - Maximum number of events to return
-/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `timeline_filter` - Optional workspace, time range and event type filter
 ///
 /// # Errors
 ///
-/// Returns an error if the query fails.
+/// Returns an error if the time range ends before it starts or the query fails.
 pub fn get_timeline(
     conn: &Connection,
     limit: usize,
-    workspace_filter: Option<&str>,
+    timeline_filter: Option<&TimelineFilter>,
 ) -> Result<Vec<TimelineEvent>, QueryError> {
-    let mut events = Vec::new();
+    let default_filter = TimelineFilter::default();
+    let filter = timeline_filter.unwrap_or(&default_filter);
+
+    let TimeRange { start, end } = filter.time_range;
+    if let Some((start, end)) = start.zip(end).filter(|(start, end)| start > end) {
+        return Err(QueryError::InvalidParameter(format!(
+            "time range starts ({start}) after it ends ({end})"
+        )));
+    }
 
     // Resolve workspace filter (path or ID) to workspace ID
-    let resolved_workspace_id = match workspace_filter {
-        Some(filter) => resolve_workspace_filter(conn, filter)?,
+    let resolved_workspace_id = match filter.workspace_filter.as_deref() {
+        Some(workspace) => resolve_workspace_filter(conn, workspace)?,
         None => None,
     };
 
-    if let Some(workspace_id) = resolved_workspace_id {
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT event_type, event_id, workspace_id, event_timestamp, summary, details_json
-            FROM timeline
-            WHERE workspace_id = ?
-            ORDER BY event_timestamp DESC
-            LIMIT ?
-            "#,
-        )?;
-
-        let rows = stmt.query_map(params![workspace_id, limit as i64], |row| {
-            Ok(TimelineEvent {
-                event_type: row.get(0)?,
-                event_id: row.get(1)?,
-                workspace_id: row.get(2)?,
-                event_timestamp: row.get(3)?,
-                summary: row.get(4)?,
-                details_json: row.get(5)?,
-            })
-        })?;
-
-        for row in rows {
-            events.push(row?);
-        }
+    let event_types = if filter.event_types.is_empty() {
+        None
     } else {
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT event_type, event_id, workspace_id, event_timestamp, summary, details_json
-            FROM timeline
-            ORDER BY event_timestamp DESC
-            LIMIT ?
-            "#,
-        )?;
+        Some(serde_json::to_string(&filter.event_types).unwrap_or_default())
+    };
+
+    // Timestamps are stored as RFC 3339 in UTC, so string comparison
+    // matches time order
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT event_type, event_id, workspace_id, event_timestamp, summary, details_json
+        FROM timeline
+        WHERE (?1 IS NULL OR workspace_id = ?1)
+          AND event_timestamp BETWEEN COALESCE(?2, event_timestamp) AND COALESCE(?3, event_timestamp)
+          AND (?4 IS NULL OR event_type IN (SELECT value FROM json_each(?4)))
+        ORDER BY event_timestamp DESC
+        LIMIT ?5
+        "#,
+    )?;
 
-        let rows = stmt.query_map([limit as i64], |row| {
+    let rows = stmt.query_map(
+        params![
+            resolved_workspace_id,
+            start.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, false)),
+            end.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, false)),
+            event_types,
+            limit as i64
+        ],
+        |row| {
             Ok(TimelineEvent {
                 event_type: row.get(0)?,
                 event_id: row.get(1)?,
@@ -183,581 +623,4720 @@ pub fn get_timeline(
                 summary: row.get(4)?,
                 details_json: row.get(5)?,
             })
-        })?;
+        },
+    )?;
 
-        for row in rows {
-            events.push(row?);
-        }
+    let mut events = Vec::new();
+    for row in rows {
+        events.push(row?);
     }
 
     Ok(events)
 }
 
-/// Search commits using FTS5 full-text search
+/// Query the timeline view for activity on a single day
 ///
 /// # Arguments
 ///
 /// * `conn` - Database connection
-/// * `query` - Search query (FTS5 syntax)
-/// * `limit` - Maximum number of results
+/// * `date` - Day in `YYYY-MM-DD` format (UTC)
+/// * `limit` - Maximum number of events to return
+/// * `workspace_filter` - Optional workspace path or ID to filter by
 ///
 /// # Errors
 ///
-/// Returns an error if the query fails.
-pub fn search_commits(
+/// Returns an error if `date` is not a valid `YYYY-MM-DD` date or the query fails.
+pub fn get_timeline_on_date(
     conn: &Connection,
-    query: &str,
+    date: &str,
     limit: usize,
-) -> Result<Vec<SearchResult>, QueryError> {
-    if query.is_empty() {
-        return Err(QueryError::InvalidParameter("Query cannot be empty".into()));
-    }
+    workspace_filter: Option<&str>,
+) -> Result<Vec<TimelineEvent>, QueryError> {
+    validate_date(date)?;
 
-    let mut results = Vec::new();
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
 
-    // FTS5 uses rowid which matches the internal SQLite rowid of commits table
     let mut stmt = conn.prepare(
         r#"
-        SELECT
-            c.id,
-            snippet(commits_fts, 0, '<mark>', '</mark>', '...', 32) AS snippet,
-            commits_fts.rank,
-            c.timestamp
-        FROM commits_fts
-        JOIN commits c ON c.rowid = commits_fts.rowid
-        WHERE commits_fts MATCH ?
-        ORDER BY commits_fts.rank
-        LIMIT ?
+        SELECT event_type, event_id, workspace_id, event_timestamp, summary, details_json
+        FROM timeline
+        WHERE DATE(event_timestamp) = ?1
+          AND (?2 IS NULL OR workspace_id = ?2)
+        ORDER BY event_timestamp DESC
+        LIMIT ?3
         "#,
     )?;
 
-    let rows = stmt.query_map(params![query, limit as i64], |row| {
-        Ok(SearchResult {
-            result_type: "commit".to_string(),
-            id: row.get(0)?,
-            snippet: row.get(1)?,
-            rank: row.get(2)?,
-            timestamp: row.get(3)?,
+    let rows = stmt.query_map(params![date, resolved_workspace_id, limit as i64], |row| {
+        Ok(TimelineEvent {
+            event_type: row.get(0)?,
+            event_id: row.get(1)?,
+            workspace_id: row.get(2)?,
+            event_timestamp: row.get(3)?,
+            summary: row.get(4)?,
+            details_json: row.get(5)?,
         })
     })?;
 
+    let mut events = Vec::new();
     for row in rows {
-        results.push(row?);
+        events.push(row?);
     }
 
-    Ok(results)
+    Ok(events)
 }
 
-/// Search Copilot messages using FTS5 full-text search
+/// Get the most recently active Copilot sessions, one entry per session
+///
+/// Sessions without messages are left out.
 ///
 /// # Arguments
 ///
 /// * `conn` - Database connection
-/// * `query` - Search query (FTS5 syntax)
-/// * `limit` - Maximum number of results
+/// * `limit` - Maximum number of sessions to return
+/// * `workspace_filter` - Optional workspace path or ID to filter by
 ///
 /// # Errors
 ///
 /// Returns an error if the query fails.
-pub fn search_messages(
+pub fn get_recent_copilot_sessions(
     conn: &Connection,
-    query: &str,
     limit: usize,
-) -> Result<Vec<SearchResult>, QueryError> {
-    if query.is_empty() {
-        return Err(QueryError::InvalidParameter("Query cannot be empty".into()));
-    }
-
-    let mut results = Vec::new();
+    workspace_filter: Option<&str>,
+) -> Result<Vec<CopilotSessionSummary>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
 
-    // FTS5 uses rowid which matches the internal SQLite rowid of copilot_messages table
     let mut stmt = conn.prepare(
         r#"
-        SELECT
-            m.id,
-            snippet(copilot_messages_fts, 0, '<mark>', '</mark>', '...', 32) AS snippet,
-            copilot_messages_fts.rank,
-            m.timestamp
-        FROM copilot_messages_fts
-        JOIN copilot_messages m ON m.rowid = copilot_messages_fts.rowid
-        WHERE copilot_messages_fts MATCH ?
-        ORDER BY copilot_messages_fts.rank
-        LIMIT ?
+        SELECT cs.id, cs.workspace_id, MIN(cm.timestamp), MAX(cm.timestamp), COUNT(cm.id),
+               json_extract(cs.metadata_json, '$.model'),
+               (SELECT substr(f.content, 1, 100) FROM copilot_messages f
+                WHERE f.session_id = cs.id AND f.role = 'user'
+                ORDER BY f.timestamp LIMIT 1)
+        FROM copilot_sessions cs
+            JOIN copilot_messages cm ON cm.session_id = cs.id
+        WHERE ?1 IS NULL OR cs.workspace_id = ?1
+        GROUP BY cs.id
+        ORDER BY MAX(cm.timestamp) DESC
+        LIMIT ?2
         "#,
     )?;
 
-    let rows = stmt.query_map(params![query, limit as i64], |row| {
-        Ok(SearchResult {
-            result_type: "copilot_message".to_string(),
-            id: row.get(0)?,
-            snippet: row.get(1)?,
-            rank: row.get(2)?,
-            timestamp: row.get(3)?,
+    let rows = stmt.query_map(params![resolved_workspace_id, limit as i64], |row| {
+        Ok(CopilotSessionSummary {
+            session_id: row.get(0)?,
+            workspace_id: row.get(1)?,
+            first_message: row.get(2)?,
+            last_message: row.get(3)?,
+            message_count: row.get::<_, i64>(4)? as u64,
+            model: row.get(5)?,
+            preview: row.get(6)?,
         })
     })?;
 
+    let mut sessions = Vec::new();
     for row in rows {
-        results.push(row?);
+        sessions.push(row?);
     }
 
-    Ok(results)
+    Ok(sessions)
 }
 
-/// Combined search across commits and messages
+/// Get commits made within a time range, most recent first
+///
+/// Both bounds are inclusive.
 ///
 /// # Arguments
 ///
 /// * `conn` - Database connection
-/// * `query` - Search query (FTS5 syntax)
-/// * `limit` - Maximum number of results per type
+/// * `from` - Earliest commit time
+/// * `to` - Latest commit time
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `limit` - Maximum number of commits to return
 ///
 /// # Errors
 ///
 /// Returns an error if the query fails.
-pub fn search_all(
+pub fn get_commits_in_range(
     conn: &Connection,
-    query: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    workspace_filter: Option<&str>,
     limit: usize,
-) -> Result<Vec<SearchResult>, QueryError> {
-    let mut results = Vec::new();
+) -> Result<Vec<CommitRecord>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
 
-    // Search commits
-    results.extend(search_commits(conn, query, limit)?);
+    // Timestamps are stored as RFC 3339 in UTC, so string comparison
+    // matches time order and can use idx_commits_timestamp
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COMMIT_COLUMNS} FROM commits
+         WHERE timestamp BETWEEN ?1 AND ?2
+           AND (?3 IS NULL OR workspace_id = ?3)
+         ORDER BY timestamp DESC
+         LIMIT ?4"
+    ))?;
 
-    // Search messages
-    results.extend(search_messages(conn, query, limit)?);
+    let rows = stmt.query_map(
+        params![
+            from.to_rfc3339_opts(SecondsFormat::Secs, false),
+            to.to_rfc3339_opts(SecondsFormat::Secs, false),
+            resolved_workspace_id,
+            limit as i64
+        ],
+        commit_from_row,
+    )?;
 
-    // Sort by rank (lower is better)
-    results.sort_by(|a, b| {
-        a.rank
-            .partial_cmp(&b.rank)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    let mut commits = Vec::new();
+    for row in rows {
+        commits.push(row?);
+    }
 
-    // Limit total results
-    results.truncate(limit);
+    Ok(commits)
+}
 
-    Ok(results)
+/// Get all commits made on a day, most recent first
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `date` - Day in `YYYY-MM-DD` format (UTC)
+///
+/// # Errors
+///
+/// Returns an error if `date` is not a valid `YYYY-MM-DD` date or the query fails.
+pub fn get_commits_on_date(conn: &Connection, date: &str) -> Result<Vec<CommitRecord>, QueryError> {
+    validate_date(date)?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COMMIT_COLUMNS} FROM commits
+         WHERE DATE(timestamp) = ?1
+         ORDER BY timestamp DESC"
+    ))?;
+
+    let rows = stmt.query_map([date], commit_from_row)?;
+
+    let mut commits = Vec::new();
+    for row in rows {
+        commits.push(row?);
+    }
+
+    Ok(commits)
 }
 
-/// Get failing tests from the failing_tests view
+/// Check that a date is in `YYYY-MM-DD` format
+fn validate_date(date: &str) -> Result<(), QueryError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| {
+            QueryError::InvalidParameter(format!("Invalid date '{date}', expected YYYY-MM-DD"))
+        })
+}
+
+/// Search commits using FTS5 full-text search
+///
+/// The query is normalized with [`validate_fts5_query`] first.
 ///
 /// # Arguments
 ///
 /// * `conn` - Database connection
+/// * `query` - Search query (FTS5 syntax)
 /// * `limit` - Maximum number of results
-/// * `workspace_filter` - Optional workspace path or ID to filter by (via test_runs)
-/// * `commit_filter` - Optional commit SHA (full or partial) to filter by
 ///
 /// # Errors
 ///
-/// Returns an error if the query fails.
-pub fn get_failing_tests(
+/// Returns `QueryError::InvalidParameter` if the query is empty or
+/// malformed, or an error if the query fails.
+pub fn search_commits(
     conn: &Connection,
+    query: &str,
     limit: usize,
-    workspace_filter: Option<&str>,
-    commit_filter: Option<&str>,
-) -> Result<Vec<FailingTest>, QueryError> {
-    let mut tests = Vec::new();
+) -> Result<Vec<SearchResult>, QueryError> {
+    search_commits_matching(conn, &SearchQuery::raw(query)?, limit)
+}
 
-    // Resolve workspace filter (path or ID) to workspace ID
-    let resolved_workspace_id = match workspace_filter {
-        Some(filter) => resolve_workspace_filter(conn, filter)?,
-        None => None,
-    };
+/// Search commits with a query built by [`SearchQuery`]
+///
+/// # Errors
+///
+/// Returns `QueryError::InvalidParameter` if the query is empty, or an
+/// error if the query fails.
+pub fn search_commits_matching(
+    conn: &Connection,
+    query: &SearchQuery,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    match_commits(conn, query.non_empty_fts5()?, limit)
+}
 
-    // Build query based on filters
-    // The failing_tests view columns are:
-    // test_name (from tr.id), suite_name, full_name (from tr.test_name),
-    // duration_ms, output_json, run_id, commit_sha, started_at
-    match (resolved_workspace_id, commit_filter) {
-        (Some(workspace_id), Some(commit)) => {
-            // Filter by both workspace and commit
-            let commit_pattern = format!("{}%", commit);
-            let mut stmt = conn.prepare(
-                r#"
-                SELECT ft.test_name, ft.suite_name, ft.full_name, ft.duration_ms,
-                       ft.output_json, ft.run_id, ft.commit_sha, ft.started_at
-                FROM failing_tests ft
-                JOIN test_runs tr ON tr.id = ft.run_id
-                WHERE tr.workspace_id = ? AND ft.commit_sha LIKE ?
-                ORDER BY ft.started_at DESC
-                LIMIT ?
-                "#,
-            )?;
+/// Search commits for an exact phrase
+///
+/// Unlike [`search_commits`], the words must appear together and in order,
+/// so `hello world` does not match "world, hello".
+///
+/// # Errors
+///
+/// Returns `QueryError::InvalidParameter` if the phrase is empty, or an
+/// error if the query fails.
+pub fn search_commits_phrase(
+    conn: &Connection,
+    phrase: &str,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    let phrase = phrase.trim();
+    if phrase.is_empty() {
+        return Err(QueryError::InvalidParameter(
+            "Phrase cannot be empty".into(),
+        ));
+    }
+    match_commits(conn, &quote_fts5(phrase), limit)
+}
 
-            let rows =
-                stmt.query_map(params![workspace_id, commit_pattern, limit as i64], |row| {
-                    Ok(FailingTest {
-                        test_name: row.get(0)?,
-                        suite_name: row.get(1)?,
-                        full_name: row.get(2)?,
-                        duration_ms: row.get(3)?,
-                        output_json: row.get(4)?,
-                        run_id: row.get(5)?,
-                        commit_sha: row.get(6)?,
-                        started_at: row.get(7)?,
-                    })
-                })?;
+/// Run an FTS5 `MATCH` expression against commit messages
+fn match_commits(
+    conn: &Connection,
+    fts_query: &str,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    let mut results = Vec::new();
 
-            for row in rows {
-                tests.push(row?);
-            }
-        }
-        (Some(workspace_id), None) => {
-            // Filter by workspace only
-            let mut stmt = conn.prepare(
-                r#"
-                SELECT ft.test_name, ft.suite_name, ft.full_name, ft.duration_ms,
-                       ft.output_json, ft.run_id, ft.commit_sha, ft.started_at
-                FROM failing_tests ft
-                JOIN test_runs tr ON tr.id = ft.run_id
-                WHERE tr.workspace_id = ?
-                ORDER BY ft.started_at DESC
-                LIMIT ?
-                "#,
-            )?;
+    // FTS5 uses rowid which matches the internal SQLite rowid of commits table
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            c.id,
+            snippet(commits_fts, 0, '<mark>', '</mark>', '...', 32) AS snippet,
+            commits_fts.rank,
+            c.timestamp,
+            c.workspace_id,
+            c.sha
+        FROM commits_fts
+        JOIN commits c ON c.rowid = commits_fts.rowid
+        WHERE commits_fts MATCH ?
+        ORDER BY commits_fts.rank
+        LIMIT ?
+        "#,
+    )?;
 
-            let rows = stmt.query_map(params![workspace_id, limit as i64], |row| {
-                Ok(FailingTest {
-                    test_name: row.get(0)?,
-                    suite_name: row.get(1)?,
-                    full_name: row.get(2)?,
-                    duration_ms: row.get(3)?,
-                    output_json: row.get(4)?,
-                    run_id: row.get(5)?,
-                    commit_sha: row.get(6)?,
-                    started_at: row.get(7)?,
-                })
-            })?;
+    let rows = stmt.query_map(params![fts_query, limit as i64], |row| {
+        Ok(SearchResult {
+            result_type: "commit".to_string(),
+            id: row.get(0)?,
+            snippet: row.get(1)?,
+            rank: row.get(2)?,
+            timestamp: row.get(3)?,
+            workspace_id: row.get(4)?,
+            source_info: row.get(5)?,
+        })
+    })?;
 
-            for row in rows {
-                tests.push(row?);
-            }
-        }
-        (None, Some(commit)) => {
-            // Filter by commit only
-            let commit_pattern = format!("{}%", commit);
-            let mut stmt = conn.prepare(
-                r#"
-                SELECT test_name, suite_name, full_name, duration_ms,
-                       output_json, run_id, commit_sha, started_at
-                FROM failing_tests
-                WHERE commit_sha LIKE ?
-                ORDER BY started_at DESC
-                LIMIT ?
-                "#,
-            )?;
+    for row in rows {
+        results.push(row?);
+    }
 
-            let rows = stmt.query_map(params![commit_pattern, limit as i64], |row| {
-                Ok(FailingTest {
-                    test_name: row.get(0)?,
-                    suite_name: row.get(1)?,
-                    full_name: row.get(2)?,
-                    duration_ms: row.get(3)?,
-                    output_json: row.get(4)?,
-                    run_id: row.get(5)?,
-                    commit_sha: row.get(6)?,
-                    started_at: row.get(7)?,
-                })
-            })?;
+    Ok(results)
+}
 
-            for row in rows {
-                tests.push(row?);
+/// FTS5 boolean operators, which must appear between two terms
+const FTS5_OPERATORS: [&str; 3] = ["AND", "OR", "NOT"];
+
+/// What the previous token of an FTS5 query was, for operator placement checks
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Fts5Token {
+    Start,
+    Term,
+    Operator,
+    Open,
+}
+
+/// Normalize a search query into valid FTS5 syntax
+///
+/// Quoted phrases, parentheses, `AND`/`OR`/`NOT` and trailing `*` prefix
+/// markers pass through. Any other word containing characters FTS5 does not
+/// accept unquoted, including a stray double quote, is quoted so it is
+/// searched literally; an unterminated phrase is closed at the end of the
+/// query. Space-separated words remain an implicit AND.
+///
+/// # Errors
+///
+/// Returns `QueryError::InvalidParameter` if the query is empty, has
+/// unbalanced or empty parentheses, or has an operator without a term on
+/// each side.
+pub fn validate_fts5_query(query: &str) -> Result<String, QueryError> {
+    let invalid =
+        |reason: String| QueryError::InvalidParameter(format!("Invalid search query: {reason}"));
+
+    let mut tokens: Vec<String> = Vec::new();
+    let mut previous = Fts5Token::Start;
+    let mut depth = 0usize;
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => {
+                depth += 1;
+                previous = Fts5Token::Open;
+                tokens.push("(".to_string());
+            }
+            ')' => {
+                match previous {
+                    _ if depth == 0 => return Err(invalid("unbalanced parentheses".into())),
+                    Fts5Token::Open => return Err(invalid("empty parentheses".into())),
+                    Fts5Token::Operator => {
+                        return Err(invalid("operator before ')' has no right-hand term".into()));
+                    }
+                    _ => {}
+                }
+                depth -= 1;
+                previous = Fts5Token::Term;
+                tokens.push(")".to_string());
+            }
+            '"' => {
+                let mut phrase = String::new();
+                while let Some(c) = chars.next() {
+                    if c == '"' {
+                        if chars.peek() == Some(&'"') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    phrase.push(c);
+                }
+                if !phrase.trim().is_empty() {
+                    previous = Fts5Token::Term;
+                    tokens.push(quote_fts5(&phrase));
+                }
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+
+                if FTS5_OPERATORS.contains(&word.as_str()) {
+                    if previous != Fts5Token::Term {
+                        return Err(invalid(format!("'{word}' must follow a search term")));
+                    }
+                    previous = Fts5Token::Operator;
+                    tokens.push(word);
+                } else {
+                    previous = Fts5Token::Term;
+                    tokens.push(if is_fts5_bareword(&word) {
+                        word
+                    } else {
+                        quote_fts5(&word)
+                    });
+                }
             }
         }
-        (None, None) => {
-            // No filters
-            let mut stmt = conn.prepare(
-                r#"
-                SELECT test_name, suite_name, full_name, duration_ms,
-                       output_json, run_id, commit_sha, started_at
-                FROM failing_tests
-                ORDER BY started_at DESC
-                LIMIT ?
-                "#,
-            )?;
+    }
 
-            let rows = stmt.query_map([limit as i64], |row| {
-                Ok(FailingTest {
-                    test_name: row.get(0)?,
-                    suite_name: row.get(1)?,
-                    full_name: row.get(2)?,
-                    duration_ms: row.get(3)?,
-                    output_json: row.get(4)?,
-                    run_id: row.get(5)?,
-                    commit_sha: row.get(6)?,
-                    started_at: row.get(7)?,
-                })
-            })?;
+    if depth != 0 {
+        return Err(invalid("unbalanced parentheses".into()));
+    }
+    if previous == Fts5Token::Operator {
+        let operator = tokens.last().cloned().unwrap_or_default();
+        return Err(invalid(format!(
+            "'{operator}' must be followed by a search term"
+        )));
+    }
+    if !tokens.iter().any(|t| t != "(" && t != ")") {
+        return Err(QueryError::InvalidParameter("Query cannot be empty".into()));
+    }
 
-            for row in rows {
-                tests.push(row?);
-            }
+    let mut normalized = String::new();
+    for token in &tokens {
+        if !normalized.is_empty() && !normalized.ends_with('(') && token != ")" {
+            normalized.push(' ');
         }
+        normalized.push_str(token);
     }
+    Ok(normalized)
+}
 
-    Ok(tests)
+/// Check whether FTS5 accepts a word unquoted, allowing a trailing `*`
+fn is_fts5_bareword(word: &str) -> bool {
+    let stem = word.strip_suffix('*').unwrap_or(word);
+    !stem.is_empty()
+        && stem
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii())
 }
 
-/// Get recent activity summary
+/// Wrap text in FTS5 double quotes, escaping any quotes inside it
+fn quote_fts5(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// A full-text search query built without writing FTS5 syntax by hand
 ///
-/// # Arguments
+/// Text passed to [`SearchQuery::phrase`] and [`SearchQuery::prefix`] is
+/// quoted, so FTS5 operators and punctuation inside it are searched
+/// literally. Queries combine with [`SearchQuery::and`] and
+/// [`SearchQuery::or`]; combining with an empty query returns the other one.
 ///
-/// * `conn` - Database connection
-/// * `days` - Number of days to look back
+/// # Example
 ///
-/// # Errors
+/// ```
+/// use hindsight_mcp::queries::SearchQuery;
 ///
-/// Returns an error if the query fails.
-pub fn get_activity_summary(conn: &Connection, days: u32) -> Result<ActivitySummary, QueryError> {
-    let since = format!("-{} days", days);
+/// let query = SearchQuery::phrase("login timeout").or(SearchQuery::prefix("auth"));
+/// assert_eq!(query.as_fts5(), r#"("login timeout") OR ("auth"*)"#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    fts5: String,
+}
 
-    let commit_count: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM commits WHERE timestamp >= datetime('now', ?)",
-        [&since],
-        |row| row.get(0),
-    )?;
+impl SearchQuery {
+    /// Use a query written in FTS5 syntax, normalized by [`validate_fts5_query`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `QueryError::InvalidParameter` if the query is empty or malformed.
+    pub fn raw(query: &str) -> Result<Self, QueryError> {
+        Ok(Self {
+            fts5: validate_fts5_query(query)?,
+        })
+    }
 
-    // test_runs uses started_at, not timestamp
-    let test_run_count: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM test_runs WHERE started_at >= datetime('now', ?)",
-        [&since],
-        |row| row.get(0),
-    )?;
+    /// Match the words of `text` together and in order
+    #[must_use]
+    pub fn phrase(text: &str) -> Self {
+        let text = text.trim();
+        if text.is_empty() {
+            return Self::default();
+        }
+        Self {
+            fts5: quote_fts5(text),
+        }
+    }
 
-    // copilot_sessions uses created_at, not start_time
-    let session_count: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM copilot_sessions WHERE created_at >= datetime('now', ?)",
-        [&since],
-        |row| row.get(0),
-    )?;
+    /// Match words starting with `text`
+    ///
+    /// A trailing `*` is accepted, so `"auth"` and `"auth*"` both match
+    /// "authentication" but not "unauth".
+    #[must_use]
+    pub fn prefix(text: &str) -> Self {
+        let text = text.trim();
+        let text = text.strip_suffix('*').unwrap_or(text).trim_end();
+        if text.is_empty() {
+            return Self::default();
+        }
+        Self {
+            fts5: format!("{}*", quote_fts5(text)),
+        }
+    }
 
-    // test_results uses outcome, not status
-    let failing_test_count: i64 = conn.query_row(
+    /// Match only what both queries match
+    #[must_use]
+    pub fn and(self, other: SearchQuery) -> Self {
+        self.combine("AND", other)
+    }
+
+    /// Match what either query matches
+    #[must_use]
+    pub fn or(self, other: SearchQuery) -> Self {
+        self.combine("OR", other)
+    }
+
+    fn combine(self, operator: &str, other: SearchQuery) -> Self {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+        Self {
+            fts5: format!("({}) {operator} ({})", self.fts5, other.fts5),
+        }
+    }
+
+    /// Check whether the query has no terms
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fts5.is_empty()
+    }
+
+    /// The FTS5 `MATCH` expression for this query
+    #[must_use]
+    pub fn as_fts5(&self) -> &str {
+        &self.fts5
+    }
+
+    /// The FTS5 expression, or an error for an empty query
+    fn non_empty_fts5(&self) -> Result<&str, QueryError> {
+        if self.is_empty() {
+            return Err(QueryError::InvalidParameter("Query cannot be empty".into()));
+        }
+        Ok(&self.fts5)
+    }
+}
+
+/// Search Copilot messages using FTS5 full-text search
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `query` - Search query (FTS5 syntax)
+/// * `limit` - Maximum number of results
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn search_messages(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    search_messages_matching(conn, &SearchQuery::raw(query)?, limit)
+}
+
+/// Search Copilot messages with a query built by [`SearchQuery`]
+///
+/// # Errors
+///
+/// Returns `QueryError::InvalidParameter` if the query is empty, or an
+/// error if the query fails.
+pub fn search_messages_matching(
+    conn: &Connection,
+    query: &SearchQuery,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    let query = query.non_empty_fts5()?;
+
+    let mut results = Vec::new();
+
+    // FTS5 uses rowid which matches the internal SQLite rowid of copilot_messages table
+    let mut stmt = conn.prepare(
         r#"
-        SELECT COUNT(*)
-        FROM test_results tr
-        JOIN test_runs r ON r.id = tr.run_id
-        WHERE r.started_at >= datetime('now', ?)
-        AND tr.outcome IN ('failed', 'timedout')
+        SELECT
+            m.id,
+            snippet(copilot_messages_fts, 0, '<mark>', '</mark>', '...', 32) AS snippet,
+            copilot_messages_fts.rank,
+            m.timestamp,
+            s.workspace_id,
+            m.session_id
+        FROM copilot_messages_fts
+        JOIN copilot_messages m ON m.rowid = copilot_messages_fts.rowid
+        JOIN copilot_sessions s ON s.id = m.session_id
+        WHERE copilot_messages_fts MATCH ?
+        ORDER BY copilot_messages_fts.rank
+        LIMIT ?
         "#,
-        [&since],
-        |row| row.get(0),
     )?;
 
-    Ok(ActivitySummary {
-        days,
-        commits: commit_count as u64,
-        test_runs: test_run_count as u64,
-        copilot_sessions: session_count as u64,
-        failing_tests: failing_test_count as u64,
-    })
-}
+    let rows = stmt.query_map(params![query, limit as i64], |row| {
+        Ok(SearchResult {
+            result_type: "copilot_message".to_string(),
+            id: row.get(0)?,
+            snippet: row.get(1)?,
+            rank: row.get(2)?,
+            timestamp: row.get(3)?,
+            workspace_id: row.get(4)?,
+            source_info: row.get(5)?,
+        })
+    })?;
 
-/// Summary of recent activity
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ActivitySummary {
-    /// Number of days covered
-    pub days: u32,
-    /// Number of commits
-    pub commits: u64,
-    /// Number of test runs
-    pub test_runs: u64,
-    /// Number of Copilot sessions
-    pub copilot_sessions: u64,
-    /// Number of failing tests
-    pub failing_tests: u64,
+    for row in rows {
+        results.push(row?);
+    }
+
+    Ok(results)
 }
 
-/// Get commits with their associated test results
+/// Search git notes using FTS5 full-text search
 ///
 /// # Arguments
 ///
 /// * `conn` - Database connection
-/// * `commit_sha` - Git commit SHA (or prefix)
+/// * `query` - Search query (FTS5 syntax)
+/// * `limit` - Maximum number of results
 ///
 /// # Errors
 ///
 /// Returns an error if the query fails.
-pub fn get_commit_with_tests(
+pub fn search_notes(
     conn: &Connection,
-    commit_sha: &str,
-) -> Result<Option<CommitWithTests>, QueryError> {
-    // Find the commit - schema uses diff_json for file changes
-    let commit: Option<(String, String, String, String, String, Option<String>)> = conn
-        .query_row(
-            r#"
-            SELECT id, sha, message, author, timestamp, diff_json
-            FROM commits
-            WHERE sha LIKE ? || '%'
-            LIMIT 1
-            "#,
-            [commit_sha],
-            |row| {
-                Ok((
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                ))
-            },
-        )
-        .optional()?;
-
-    let Some((id, sha, message, author, timestamp, diff_json)) = commit else {
-        return Ok(None);
-    };
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    let query = validate_fts5_query(query)?;
 
-    // Parse diff JSON to extract file paths
-    let files: Vec<String> = match diff_json {
-        Some(ref json) => {
-            // Try to parse as object with "files" key, or as array of file paths
-            serde_json::from_str::<serde_json::Value>(json)
-                .ok()
-                .and_then(|v| {
-                    // Try {"files": [...]} format
-                    if let Some(files) = v.get("files") {
-                        files.as_array().map(|arr| {
-                            arr.iter()
-                                .filter_map(|f| f.get("path").and_then(|p| p.as_str()))
-                                .map(String::from)
-                                .collect()
-                        })
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or_default()
-        }
-        None => Vec::new(),
-    };
+    let mut results = Vec::new();
 
-    // Get associated test runs - schema uses started_at, passed_count, failed_count, ignored_count
     let mut stmt = conn.prepare(
         r#"
-        SELECT r.id, r.started_at, r.passed_count, r.failed_count, r.ignored_count
-        FROM test_runs r
-        WHERE r.commit_sha = ?
-        ORDER BY r.started_at DESC
+        SELECT
+            n.id,
+            snippet(notes_fts, 0, '<mark>', '</mark>', '...', 32) AS snippet,
+            notes_fts.rank,
+            c.timestamp,
+            c.workspace_id,
+            c.sha
+        FROM notes_fts
+        JOIN notes n ON n.rowid = notes_fts.rowid
+        JOIN commits c ON c.id = n.commit_id
+        WHERE notes_fts MATCH ?
+        ORDER BY notes_fts.rank
+        LIMIT ?
         "#,
     )?;
 
-    let test_runs: Vec<TestRunSummary> = stmt
-        .query_map([&sha], |row| {
-            Ok(TestRunSummary {
-                id: row.get(0)?,
-                timestamp: row.get(1)?,
-                passed: row.get(2)?,
-                failed: row.get(3)?,
-                skipped: row.get(4)?,
-            })
-        })?
-        .filter_map(Result::ok)
-        .collect();
+    let rows = stmt.query_map(params![query, limit as i64], |row| {
+        Ok(SearchResult {
+            result_type: "note".to_string(),
+            id: row.get(0)?,
+            snippet: row.get(1)?,
+            rank: row.get(2)?,
+            timestamp: row.get(3)?,
+            workspace_id: row.get(4)?,
+            source_info: row.get(5)?,
+        })
+    })?;
 
-    Ok(Some(CommitWithTests {
-        id,
-        sha,
-        message,
-        author,
-        timestamp,
-        files,
-        test_runs,
-    }))
+    for row in rows {
+        results.push(row?);
+    }
+
+    Ok(results)
+}
+
+/// Combined search across commits and messages
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `query` - Search query (FTS5 syntax)
+/// * `limit` - Maximum number of results per type
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn search_all(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    search_all_matching(conn, &SearchQuery::raw(query)?, limit)
+}
+
+/// Combined search across commits and messages with a [`SearchQuery`]
+///
+/// # Errors
+///
+/// Returns `QueryError::InvalidParameter` if the query is empty, or an
+/// error if the query fails.
+pub fn search_all_matching(
+    conn: &Connection,
+    query: &SearchQuery,
+    limit: usize,
+) -> Result<Vec<SearchResult>, QueryError> {
+    let mut results = Vec::new();
+
+    // Search commits
+    results.extend(search_commits_matching(conn, query, limit)?);
+
+    // Search messages
+    results.extend(search_messages_matching(conn, query, limit)?);
+
+    // Sort by rank (lower is better)
+    results.sort_by(|a, b| {
+        a.rank
+            .partial_cmp(&b.rank)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // Limit total results
+    results.truncate(limit);
+
+    Ok(results)
+}
+
+/// Number of terms returned by [`suggest_search_queries`]
+const SUGGESTION_LIMIT: usize = 20;
+
+/// Characters treated as word boundaries when splitting commit messages
+const WORD_SEPARATORS: &[char] = &[
+    '\n', '\r', '\t', '.', ',', ':', ';', '!', '?', '(', ')', '[', ']', '{', '}', '<', '>', '"',
+    '\'', '`', '/', '\\', '#', '*', '=', '|',
+];
+
+/// Number of keywords recorded per Copilot session by [`extract_keywords`]
+pub(crate) const SESSION_TOPIC_LIMIT: usize = 5;
+
+/// Common words that say nothing about a commit's topic
+const STOP_WORDS: &[&str] = &[
+    "about",
+    "add",
+    "added",
+    "adds",
+    "after",
+    "all",
+    "also",
+    "and",
+    "any",
+    "are",
+    "before",
+    "been",
+    "but",
+    "can",
+    "co-authored-by",
+    "each",
+    "for",
+    "from",
+    "has",
+    "have",
+    "instead",
+    "into",
+    "its",
+    "make",
+    "more",
+    "new",
+    "not",
+    "now",
+    "only",
+    "other",
+    "our",
+    "out",
+    "over",
+    "per",
+    "signed-off-by",
+    "some",
+    "than",
+    "that",
+    "the",
+    "them",
+    "then",
+    "they",
+    "this",
+    "under",
+    "update",
+    "updated",
+    "use",
+    "via",
+    "was",
+    "were",
+    "what",
+    "when",
+    "which",
+    "will",
+    "with",
+    "you",
+    "your",
+];
+
+/// Pick the most frequent significant words from some text
+///
+/// Words are lowercased and split on [`WORD_SEPARATORS`] and whitespace, and
+/// short words, stop words and tokens without letters are skipped. Ties are
+/// broken alphabetically.
+pub(crate) fn extract_keywords<'a>(
+    texts: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for text in texts {
+        let lower = text.to_lowercase();
+        for word in lower.split(|c: char| c.is_whitespace() || WORD_SEPARATORS.contains(&c)) {
+            if word.chars().count() < 3
+                || STOP_WORDS.contains(&word)
+                || !word.chars().any(char::is_alphabetic)
+            {
+                continue;
+            }
+            *counts.entry(word.to_string()).or_default() += 1;
+        }
+    }
+
+    let mut words: Vec<(String, usize)> = counts.into_iter().collect();
+    words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    words
+        .into_iter()
+        .take(limit)
+        .map(|(word, _)| word)
+        .collect()
 }
 
-/// A commit with associated test information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CommitWithTests {
-    /// Database ID (UUID)
-    pub id: String,
-    /// Git commit SHA
-    pub sha: String,
-    /// Commit message
-    pub message: String,
-    /// Author name
-    pub author: String,
-    /// ISO 8601 timestamp
-    pub timestamp: String,
-    /// Changed files
-    pub files: Vec<String>,
-    /// Associated test runs
-    pub test_runs: Vec<TestRunSummary>,
-}
+/// Suggest search terms that are likely to match commits
+///
+/// Returns the most frequent significant words across commit messages, most
+/// common first, as picked by [`extract_keywords`]. Words shorter than three
+/// characters, stop words and tokens without letters (numbers, version
+/// strings) are left out.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn suggest_search_queries(
+    conn: &Connection,
+    workspace_filter: Option<&str>,
+) -> Result<Vec<String>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt =
+        conn.prepare("SELECT message FROM commits WHERE ?1 IS NULL OR workspace_id = ?1")?;
+    let rows = stmt.query_map(params![resolved_workspace_id], |row| {
+        row.get::<_, String>(0)
+    })?;
+
+    let mut messages = Vec::new();
+    for row in rows {
+        messages.push(row?);
+    }
+
+    Ok(extract_keywords(
+        messages.iter().map(String::as_str),
+        SUGGESTION_LIMIT,
+    ))
+}
+
+/// Get failing tests from the failing_tests view
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `limit` - Maximum number of results
+/// * `workspace_filter` - Optional workspace path or ID to filter by (via test_runs)
+/// * `commit_filter` - Optional commit SHA (full or partial) to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_failing_tests(
+    conn: &Connection,
+    limit: usize,
+    workspace_filter: Option<&str>,
+    commit_filter: Option<&str>,
+) -> Result<Vec<FailingTest>, QueryError> {
+    let mut tests = Vec::new();
+
+    // Resolve workspace filter (path or ID) to workspace ID
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    // Build query based on filters
+    // The failing_tests view columns are:
+    // test_name (from tr.id), suite_name, full_name (from tr.test_name),
+    // duration_ms, output_json, run_id, commit_sha, started_at
+    match (resolved_workspace_id, commit_filter) {
+        (Some(workspace_id), Some(commit)) => {
+            // Filter by both workspace and commit
+            let commit_pattern = format!("{}%", commit);
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT ft.test_name, ft.suite_name, ft.full_name, ft.duration_ms,
+                       ft.output_json, ft.run_id, ft.commit_sha, ft.started_at
+                FROM failing_tests ft
+                JOIN test_runs tr ON tr.id = ft.run_id
+                WHERE tr.workspace_id = ? AND ft.commit_sha LIKE ?
+                ORDER BY ft.started_at DESC
+                LIMIT ?
+                "#,
+            )?;
+
+            let rows =
+                stmt.query_map(params![workspace_id, commit_pattern, limit as i64], |row| {
+                    Ok(FailingTest {
+                        test_name: row.get(0)?,
+                        suite_name: row.get(1)?,
+                        full_name: row.get(2)?,
+                        duration_ms: row.get(3)?,
+                        output_json: row.get(4)?,
+                        run_id: row.get(5)?,
+                        commit_sha: row.get(6)?,
+                        started_at: row.get(7)?,
+                    })
+                })?;
+
+            for row in rows {
+                tests.push(row?);
+            }
+        }
+        (Some(workspace_id), None) => {
+            // Filter by workspace only
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT ft.test_name, ft.suite_name, ft.full_name, ft.duration_ms,
+                       ft.output_json, ft.run_id, ft.commit_sha, ft.started_at
+                FROM failing_tests ft
+                JOIN test_runs tr ON tr.id = ft.run_id
+                WHERE tr.workspace_id = ?
+                ORDER BY ft.started_at DESC
+                LIMIT ?
+                "#,
+            )?;
+
+            let rows = stmt.query_map(params![workspace_id, limit as i64], |row| {
+                Ok(FailingTest {
+                    test_name: row.get(0)?,
+                    suite_name: row.get(1)?,
+                    full_name: row.get(2)?,
+                    duration_ms: row.get(3)?,
+                    output_json: row.get(4)?,
+                    run_id: row.get(5)?,
+                    commit_sha: row.get(6)?,
+                    started_at: row.get(7)?,
+                })
+            })?;
+
+            for row in rows {
+                tests.push(row?);
+            }
+        }
+        (None, Some(commit)) => {
+            // Filter by commit only
+            let commit_pattern = format!("{}%", commit);
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT test_name, suite_name, full_name, duration_ms,
+                       output_json, run_id, commit_sha, started_at
+                FROM failing_tests
+                WHERE commit_sha LIKE ?
+                ORDER BY started_at DESC
+                LIMIT ?
+                "#,
+            )?;
+
+            let rows = stmt.query_map(params![commit_pattern, limit as i64], |row| {
+                Ok(FailingTest {
+                    test_name: row.get(0)?,
+                    suite_name: row.get(1)?,
+                    full_name: row.get(2)?,
+                    duration_ms: row.get(3)?,
+                    output_json: row.get(4)?,
+                    run_id: row.get(5)?,
+                    commit_sha: row.get(6)?,
+                    started_at: row.get(7)?,
+                })
+            })?;
+
+            for row in rows {
+                tests.push(row?);
+            }
+        }
+        (None, None) => {
+            // No filters
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT test_name, suite_name, full_name, duration_ms,
+                       output_json, run_id, commit_sha, started_at
+                FROM failing_tests
+                ORDER BY started_at DESC
+                LIMIT ?
+                "#,
+            )?;
+
+            let rows = stmt.query_map([limit as i64], |row| {
+                Ok(FailingTest {
+                    test_name: row.get(0)?,
+                    suite_name: row.get(1)?,
+                    full_name: row.get(2)?,
+                    duration_ms: row.get(3)?,
+                    output_json: row.get(4)?,
+                    run_id: row.get(5)?,
+                    commit_sha: row.get(6)?,
+                    started_at: row.get(7)?,
+                })
+            })?;
+
+            for row in rows {
+                tests.push(row?);
+            }
+        }
+    }
+
+    Ok(tests)
+}
+
+/// Get test results from runs tagged with a given environment
+///
+/// Matches runs whose `tags_json` has the given `env` value (e.g., `"ci"` or
+/// `"local"`), most recent runs first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `env` - Environment tag value to match
+/// * `limit` - Maximum number of results to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_test_results_by_env(
+    conn: &Connection,
+    env: &str,
+    limit: usize,
+) -> Result<Vec<TestResultRecord>, QueryError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT tr.id, tr.run_id, tr.suite_name, tr.test_name, tr.outcome,
+               tr.duration_ms, tr.output_json, tr.started_seq, tr.finished_seq, tr.created_at
+        FROM test_results tr
+        JOIN test_runs r ON r.id = tr.run_id
+        WHERE json_extract(r.tags_json, '$.env') = ?
+        ORDER BY r.started_at DESC, tr.suite_name, tr.test_name
+        LIMIT ?
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![env, limit as i64], |row| {
+        Ok(TestResultRecord {
+            id: row.get(0)?,
+            run_id: row.get(1)?,
+            suite_name: row.get(2)?,
+            test_name: row.get(3)?,
+            outcome: row.get(4)?,
+            duration_ms: row.get(5)?,
+            output_json: row.get(6)?,
+            started_seq: row.get(7)?,
+            finished_seq: row.get(8)?,
+            created_at: timestamp_at(row, 9)?,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(results)
+}
+
+/// Get the parsed JSON output of each test in a run
+///
+/// Only results stored with [`crate::db::OutputFormat::Json`] are included.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `run_id` - Test run ID
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_test_json_outputs(
+    conn: &Connection,
+    run_id: &str,
+) -> Result<Vec<(String, serde_json::Value)>, QueryError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT test_name, output_json -> '$.json'
+        FROM test_results
+        WHERE run_id = ?
+          AND json_valid(output_json)
+          AND json_extract(output_json, '$.format') = 'json'
+        ORDER BY suite_name, test_name
+        "#,
+    )?;
+
+    let rows = stmt.query_map([run_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut outputs = Vec::new();
+    for row in rows {
+        let (test_name, json) = row?;
+        if let Ok(value) = serde_json::from_str(&json) {
+            outputs.push((test_name, value));
+        }
+    }
+    Ok(outputs)
+}
+
+/// Find tests that regressed from passing to failing
+///
+/// A regression is a `(suite_name, test_name)` pair whose most recent
+/// pass-to-failure transition falls within its `lookback_runs` most recent
+/// runs. Ignored results are skipped when comparing consecutive runs, and
+/// timed-out tests count as failures.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `lookback_runs` - Number of recent runs per test to inspect for failures
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_test_regressions(
+    conn: &Connection,
+    lookback_runs: u32,
+    workspace_filter: Option<&str>,
+) -> Result<Vec<Regression>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt = conn.prepare(
+        r#"
+        WITH history AS (
+            SELECT
+                r.suite_name,
+                r.test_name,
+                r.outcome,
+                tr.started_at,
+                tr.commit_sha,
+                LAG(r.outcome) OVER w AS prev_outcome,
+                LAG(tr.started_at) OVER w AS prev_started_at,
+                ROW_NUMBER() OVER (
+                    PARTITION BY r.suite_name, r.test_name
+                    ORDER BY tr.started_at DESC
+                ) AS recency
+            FROM test_results r
+            JOIN test_runs tr ON tr.id = r.run_id
+            WHERE r.outcome != 'ignored'
+              AND (?1 IS NULL OR tr.workspace_id = ?1)
+            WINDOW w AS (PARTITION BY r.suite_name, r.test_name ORDER BY tr.started_at)
+        ),
+        transitions AS (
+            SELECT
+                suite_name,
+                test_name,
+                prev_started_at,
+                started_at,
+                commit_sha,
+                ROW_NUMBER() OVER (
+                    PARTITION BY suite_name, test_name
+                    ORDER BY started_at DESC
+                ) AS latest
+            FROM history
+            WHERE prev_outcome = 'passed'
+              AND outcome IN ('failed', 'timed_out')
+              AND recency <= ?2
+        )
+        SELECT suite_name, test_name, prev_started_at, started_at, commit_sha
+        FROM transitions
+        WHERE latest = 1
+        ORDER BY started_at DESC, suite_name, test_name
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![resolved_workspace_id, lookback_runs], |row| {
+        Ok(Regression {
+            suite_name: row.get(0)?,
+            test_name: row.get(1)?,
+            last_pass_at: row.get(2)?,
+            first_failure_at: row.get(3)?,
+            commit_sha: row.get(4)?,
+        })
+    })?;
+
+    let mut regressions = Vec::new();
+    for row in rows {
+        regressions.push(row?);
+    }
+
+    Ok(regressions)
+}
+
+/// Find tests that passed early in a time window and failed later in it
+///
+/// The last `window_days` days are split in half. A test is reported when
+/// it ran in the first half and passed every time, then failed or timed out
+/// at least once in the second half. Ignored results are skipped. Results
+/// are ordered by first failure, most recent first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `window_days` - Length of the window, in days
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `limit` - Maximum number of regressions to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_regression_detection(
+    conn: &Connection,
+    window_days: u32,
+    workspace_filter: Option<&str>,
+    limit: usize,
+) -> Result<Vec<TestRegression>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+    let window_secs = u64::from(window_days) * 86_400;
+    let window_start = format!("-{} seconds", window_secs);
+    let midpoint = format!("-{} seconds", window_secs / 2);
+
+    let mut stmt = conn.prepare(
+        r#"
+        WITH windowed AS (
+            SELECT tr.suite_name, tr.test_name, tr.outcome, tr.run_id, r.started_at,
+                   datetime(r.started_at) >= datetime('now', ?3) AS is_recent
+            FROM test_results tr
+            JOIN test_runs r ON r.id = tr.run_id
+            WHERE tr.outcome != 'ignored'
+              AND datetime(r.started_at) >= datetime('now', ?2)
+              AND (?1 IS NULL OR r.workspace_id = ?1)
+        ),
+        earlier AS (
+            SELECT suite_name, test_name
+            FROM windowed
+            WHERE NOT is_recent
+            GROUP BY suite_name, test_name
+            HAVING SUM(outcome != 'passed') = 0
+        ),
+        recent_failures AS (
+            SELECT suite_name, test_name, run_id, started_at,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY suite_name, test_name
+                       ORDER BY datetime(started_at), run_id
+                   ) AS nth
+            FROM windowed
+            WHERE is_recent AND outcome IN ('failed', 'timed_out')
+        )
+        SELECT f.suite_name, f.test_name, f.started_at,
+               (SELECT w.started_at FROM windowed w
+                WHERE w.suite_name = f.suite_name
+                  AND w.test_name = f.test_name
+                  AND w.outcome = 'passed'
+                  AND datetime(w.started_at) < datetime(f.started_at)
+                ORDER BY datetime(w.started_at) DESC
+                LIMIT 1) AS last_pass,
+               f.run_id
+        FROM recent_failures f
+        JOIN earlier e ON e.suite_name = f.suite_name AND e.test_name = f.test_name
+        WHERE f.nth = 1
+        ORDER BY datetime(f.started_at) DESC, f.suite_name, f.test_name
+        LIMIT ?4
+        "#,
+    )?;
+
+    let rows = stmt.query_map(
+        params![resolved_workspace_id, window_start, midpoint, limit as i64],
+        |row| {
+            Ok(TestRegression {
+                suite_name: row.get(0)?,
+                test_name: row.get(1)?,
+                first_failure_at: row.get(2)?,
+                last_pass_before_failure: row.get(3)?,
+                failure_run_id: row.get(4)?,
+            })
+        },
+    )?;
+
+    let mut regressions = Vec::new();
+    for row in rows {
+        regressions.push(row?);
+    }
+
+    Ok(regressions)
+}
+
+/// Find tests that have failed in at least `min_streak` consecutive runs
+///
+/// A streak counts back from a test's most recent run and stops at the first
+/// run where it passed, so a test whose latest run passed has no streak.
+/// Ignored results are skipped and timed-out tests count as failures.
+/// Results are ordered by streak length, longest first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `min_streak` - Minimum number of consecutive failures to report
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_failure_streaks(
+    conn: &Connection,
+    min_streak: u32,
+    workspace_filter: Option<&str>,
+) -> Result<Vec<FailureStreak>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt = conn.prepare(
+        r#"
+        WITH history AS (
+            SELECT
+                r.suite_name,
+                r.test_name,
+                r.outcome,
+                tr.started_at,
+                tr.commit_sha,
+                ROW_NUMBER() OVER (
+                    PARTITION BY r.suite_name, r.test_name
+                    ORDER BY tr.started_at DESC
+                ) AS recency
+            FROM test_results r
+            JOIN test_runs tr ON tr.id = r.run_id
+            WHERE r.outcome != 'ignored'
+              AND (?1 IS NULL OR tr.workspace_id = ?1)
+        ),
+        streaks AS (
+            SELECT
+                suite_name,
+                test_name,
+                COALESCE(
+                    MIN(CASE WHEN outcome NOT IN ('failed', 'timed_out') THEN recency END) - 1,
+                    MAX(recency)
+                ) AS streak_length
+            FROM history
+            GROUP BY suite_name, test_name
+        )
+        SELECT s.suite_name, s.test_name, s.streak_length, latest.started_at, oldest.commit_sha
+        FROM streaks s
+        JOIN history latest
+          ON latest.suite_name = s.suite_name
+         AND latest.test_name = s.test_name
+         AND latest.recency = 1
+        JOIN history oldest
+          ON oldest.suite_name = s.suite_name
+         AND oldest.test_name = s.test_name
+         AND oldest.recency = s.streak_length
+        WHERE s.streak_length >= MAX(?2, 1)
+        ORDER BY s.streak_length DESC, latest.started_at DESC, s.suite_name, s.test_name
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![resolved_workspace_id, min_streak], |row| {
+        Ok(FailureStreak {
+            suite_name: row.get(0)?,
+            test_name: row.get(1)?,
+            streak_length: row.get(2)?,
+            last_failed_at: row.get(3)?,
+            first_failure_commit: row.get(4)?,
+        })
+    })?;
+
+    let mut streaks = Vec::new();
+    for row in rows {
+        streaks.push(row?);
+    }
+
+    Ok(streaks)
+}
+
+/// Get the tests that failed most often over a time period
+///
+/// Ignored results are not counted as runs. Tests that never failed are left
+/// out.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Number of days to look back
+/// * `limit` - Maximum number of tests to return
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_most_failing_tests(
+    conn: &Connection,
+    days: u32,
+    limit: usize,
+    workspace_filter: Option<&str>,
+) -> Result<Vec<FlakyTestStat>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+    let since = format!("-{} days", days);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT tr.suite_name,
+               tr.test_name,
+               SUM(tr.outcome IN ('failed', 'timed_out')) AS failure_count,
+               COUNT(*) AS total_runs,
+               MAX(CASE WHEN tr.outcome IN ('failed', 'timed_out') THEN r.started_at END)
+                   AS last_failed_at
+        FROM test_results tr
+        JOIN test_runs r ON r.id = tr.run_id
+        WHERE tr.outcome != 'ignored'
+          AND datetime(r.started_at) >= datetime('now', ?1)
+          AND (?2 IS NULL OR r.workspace_id = ?2)
+        GROUP BY tr.suite_name, tr.test_name
+        HAVING failure_count > 0
+        ORDER BY failure_count DESC, last_failed_at DESC, tr.suite_name, tr.test_name
+        LIMIT ?3
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![since, resolved_workspace_id, limit as i64], |row| {
+        let failure_count = row.get::<_, i64>(2)?.max(0) as u64;
+        let total_runs = row.get::<_, i64>(3)?.max(0) as u64;
+        Ok(FlakyTestStat {
+            suite_name: row.get(0)?,
+            test_name: row.get(1)?,
+            failure_count,
+            total_runs,
+            failure_rate_pct: failure_count as f64 * 100.0 / total_runs.max(1) as f64,
+            last_failed_at: row.get(4)?,
+        })
+    })?;
+
+    let mut stats = Vec::new();
+    for row in rows {
+        stats.push(row?);
+    }
+
+    Ok(stats)
+}
+
+/// Get the longest test runs by wall-clock time
+///
+/// Runs without a finish time are left out.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `limit` - Maximum number of runs to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_test_run_durations(
+    conn: &Connection,
+    workspace_filter: Option<&str>,
+    limit: usize,
+) -> Result<Vec<TestRunDuration>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, commit_sha, started_at, wall_time_secs
+        FROM test_runs
+        WHERE wall_time_secs IS NOT NULL
+          AND (?1 IS NULL OR workspace_id = ?1)
+        ORDER BY wall_time_secs DESC, started_at DESC
+        LIMIT ?2
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![resolved_workspace_id, limit as i64], |row| {
+        Ok(TestRunDuration {
+            run_id: row.get(0)?,
+            commit_sha: row.get(1)?,
+            started_at: row.get(2)?,
+            wall_time_secs: row.get(3)?,
+        })
+    })?;
+
+    let mut durations = Vec::new();
+    for row in rows {
+        durations.push(row?);
+    }
+
+    Ok(durations)
+}
+
+/// Get a single test's outcome across its most recent runs
+///
+/// Useful for spotting flaky tests. Entries are ordered by run start time,
+/// most recent first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `suite_name` - Suite/binary the test belongs to
+/// * `test_name` - Full test name
+/// * `limit` - Maximum number of runs to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_test_history_for_test(
+    conn: &Connection,
+    suite_name: &str,
+    test_name: &str,
+    limit: usize,
+) -> Result<Vec<TestHistoryEntry>, QueryError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT tr.run_id, tr.outcome, tr.duration_ms, r.started_at, r.commit_sha
+        FROM test_results tr
+        JOIN test_runs r ON r.id = tr.run_id
+        WHERE tr.suite_name = ?1 AND tr.test_name = ?2
+        ORDER BY r.started_at DESC
+        LIMIT ?3
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![suite_name, test_name, limit as i64], |row| {
+        Ok(TestHistoryEntry {
+            run_id: row.get(0)?,
+            outcome: row.get(1)?,
+            duration_ms: row.get(2)?,
+            started_at: row.get(3)?,
+            commit_sha: row.get(4)?,
+        })
+    })?;
+
+    let mut history = Vec::new();
+    for row in rows {
+        history.push(row?);
+    }
+
+    Ok(history)
+}
+
+/// Runs per test considered by [`get_test_duration_percentiles`]
+pub const DURATION_SAMPLE_RUNS: usize = 50;
+
+/// Get duration percentiles for each test, slowest first
+///
+/// Looks at the durations of each test's most recent
+/// [`DURATION_SAMPLE_RUNS`] results and computes nearest-rank percentiles in
+/// Rust, since SQLite has no percentile function. Results without a duration
+/// are skipped. Tests are ordered by `p90_ms`, highest first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `suite_name` - Optional suite to restrict the results to
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_test_duration_percentiles(
+    conn: &Connection,
+    suite_name: Option<&str>,
+    workspace_filter: Option<&str>,
+) -> Result<Vec<TestDurationStat>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT suite_name, test_name, duration_ms
+        FROM (
+            SELECT tr.suite_name, tr.test_name, tr.duration_ms,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY tr.suite_name, tr.test_name
+                       ORDER BY r.started_at DESC
+                   ) AS recency
+            FROM test_results tr
+            JOIN test_runs r ON r.id = tr.run_id
+            WHERE tr.duration_ms IS NOT NULL
+              AND (?1 IS NULL OR tr.suite_name = ?1)
+              AND (?2 IS NULL OR r.workspace_id = ?2)
+        )
+        WHERE recency <= ?3
+        ORDER BY suite_name, test_name
+        "#,
+    )?;
+
+    let rows = stmt.query_map(
+        params![
+            suite_name,
+            resolved_workspace_id,
+            DURATION_SAMPLE_RUNS as i64
+        ],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        },
+    )?;
+
+    // Rows arrive grouped by test, so each group is contiguous
+    let mut groups: Vec<(String, String, Vec<i64>)> = Vec::new();
+    for row in rows {
+        let (suite, test, duration) = row?;
+        match groups.last_mut() {
+            Some((s, t, durations)) if *s == suite && *t == test => durations.push(duration),
+            _ => groups.push((suite, test, vec![duration])),
+        }
+    }
+
+    let mut stats: Vec<TestDurationStat> = groups
+        .into_iter()
+        .map(|(suite_name, test_name, mut durations)| {
+            durations.sort_unstable();
+            TestDurationStat {
+                suite_name,
+                test_name,
+                p50_ms: percentile(&durations, 50),
+                p90_ms: percentile(&durations, 90),
+                p99_ms: percentile(&durations, 99),
+                max_ms: durations.last().copied().unwrap_or(0),
+                sample_count: durations.len() as u64,
+            }
+        })
+        .collect();
+
+    stats.sort_by(|a, b| {
+        b.p90_ms
+            .cmp(&a.p90_ms)
+            .then_with(|| a.suite_name.cmp(&b.suite_name))
+            .then_with(|| a.test_name.cmp(&b.test_name))
+    });
+
+    Ok(stats)
+}
+
+/// Nearest-rank percentile of an ascending, non-empty slice
+fn percentile(sorted: &[i64], pct: usize) -> i64 {
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// Infer which test suites executed in parallel during a run
+///
+/// Each test's window spans its `started` and result events in the run
+/// output. Tests with overlapping windows ran concurrently; connected runs of
+/// overlapping windows form a [`ParallelGroup`]. Results ingested without
+/// event windows are ignored, and an unknown run yields no groups.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `run_id` - Test run ID
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_inferred_suite_parallelism(
+    conn: &Connection,
+    run_id: &str,
+) -> Result<Vec<ParallelGroup>, QueryError> {
+    if run_id.is_empty() {
+        return Err(QueryError::InvalidParameter(
+            "Run ID cannot be empty".into(),
+        ));
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT suite_name, started_seq, finished_seq
+        FROM test_results
+        WHERE run_id = ? AND started_seq IS NOT NULL AND finished_seq IS NOT NULL
+        ORDER BY started_seq, finished_seq
+        "#,
+    )?;
+
+    let rows = stmt.query_map([run_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+
+    // Split windows into connected groups of overlapping intervals
+    let mut groups: Vec<Vec<(String, i64, i64)>> = Vec::new();
+    let mut group_end = i64::MIN;
+    for row in rows {
+        let (suite, started, finished) = row?;
+        match groups.last_mut() {
+            Some(group) if started < group_end => group.push((suite, started, finished)),
+            _ => groups.push(vec![(suite, started, finished)]),
+        }
+        group_end = group_end.max(finished);
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|windows| {
+            // Sweep start/finish events; starts sort first so zero-width windows count
+            let mut events: Vec<(i64, i32)> = windows
+                .iter()
+                .flat_map(|(_, started, finished)| [(*started, -1), (*finished, 1)])
+                .collect();
+            events.sort_unstable();
+
+            let mut running = 0usize;
+            let mut max_parallelism = 0usize;
+            for (_, delta) in events {
+                if delta < 0 {
+                    running += 1;
+                    max_parallelism = max_parallelism.max(running);
+                } else {
+                    running -= 1;
+                }
+            }
+
+            let mut suite_names: Vec<String> =
+                windows.into_iter().map(|(suite, _, _)| suite).collect();
+            suite_names.sort();
+            suite_names.dedup();
+
+            ParallelGroup {
+                suite_names,
+                max_parallelism,
+            }
+        })
+        .collect())
+}
+
+/// Get recent activity summary
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Number of days to look back
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_activity_summary(conn: &Connection, days: u32) -> Result<ActivitySummary, QueryError> {
+    let since = format!("-{} days", days);
+
+    let commit_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM commits WHERE timestamp >= datetime('now', ?)",
+        [&since],
+        |row| row.get(0),
+    )?;
+
+    // test_runs uses started_at, not timestamp
+    let test_run_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM test_runs WHERE started_at >= datetime('now', ?)",
+        [&since],
+        |row| row.get(0),
+    )?;
+
+    // copilot_sessions uses created_at, not start_time
+    let session_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM copilot_sessions WHERE created_at >= datetime('now', ?)",
+        [&since],
+        |row| row.get(0),
+    )?;
+
+    // test_results uses outcome, not status
+    let failing_test_count: i64 = conn.query_row(
+        r#"
+        SELECT COUNT(*)
+        FROM test_results tr
+        JOIN test_runs r ON r.id = tr.run_id
+        WHERE r.started_at >= datetime('now', ?)
+        AND tr.outcome IN ('failed', 'timedout')
+        "#,
+        [&since],
+        |row| row.get(0),
+    )?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT author, COUNT(*) AS commit_count
+        FROM commits
+        WHERE timestamp >= datetime('now', ?1)
+        GROUP BY author
+        ORDER BY commit_count DESC, author
+        LIMIT ?2
+        "#,
+    )?;
+    let rows = stmt.query_map(params![since, TOP_CONTRIBUTOR_LIMIT as i64], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?.max(0) as u64,
+        ))
+    })?;
+    let mut top_contributors = Vec::new();
+    for row in rows {
+        top_contributors.push(row?);
+    }
+
+    Ok(ActivitySummary {
+        days,
+        commits: commit_count as u64,
+        test_runs: test_run_count as u64,
+        copilot_sessions: session_count as u64,
+        failing_tests: failing_test_count as u64,
+        top_contributors,
+    })
+}
+
+/// Get the most recent commits whose dominant language matches
+///
+/// The language is recorded during ingestion from the diff (see
+/// [`hindsight_git::dominant_language`]) and matched case-insensitively.
+/// Commits ingested without diffs have no language and never match.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `language` - Language name, e.g. "Rust"
+/// * `limit` - Maximum number of commits to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_commits_by_language(
+    conn: &Connection,
+    language: &str,
+    limit: usize,
+) -> Result<Vec<CommitRecord>, QueryError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COMMIT_COLUMNS} FROM commits
+         WHERE language_hint = ?1 COLLATE NOCASE
+         ORDER BY timestamp DESC
+         LIMIT ?2"
+    ))?;
+
+    let rows = stmt.query_map(params![language, limit as i64], commit_from_row)?;
+
+    let mut commits = Vec::new();
+    for row in rows {
+        commits.push(row?);
+    }
+
+    Ok(commits)
+}
+
+/// Get the most recent commits created by `git revert`
+///
+/// Revert status is detected from the commit message during ingestion.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `limit` - Maximum number of commits to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_revert_commits(
+    conn: &Connection,
+    workspace_filter: Option<&str>,
+    limit: usize,
+) -> Result<Vec<CommitRecord>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COMMIT_COLUMNS} FROM commits
+         WHERE is_revert = 1
+           AND (?1 IS NULL OR workspace_id = ?1)
+         ORDER BY timestamp DESC
+         LIMIT ?2"
+    ))?;
+
+    let rows = stmt.query_map(
+        params![resolved_workspace_id, limit as i64],
+        commit_from_row,
+    )?;
+
+    let mut commits = Vec::new();
+    for row in rows {
+        commits.push(row?);
+    }
+
+    Ok(commits)
+}
+
+/// Get Copilot sessions lasting at least `min_minutes`, longest first
+///
+/// Duration is measured from the first to the last message, so sessions
+/// ingested before message timestamps were recorded are never returned.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `min_minutes` - Minimum session duration in minutes
+/// * `limit` - Maximum number of sessions to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_long_sessions(
+    conn: &Connection,
+    min_minutes: f64,
+    limit: usize,
+) -> Result<Vec<CopilotSessionRecord>, QueryError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SESSION_COLUMNS}
+         FROM copilot_sessions
+         WHERE duration_minutes >= ?1
+         ORDER BY duration_minutes DESC
+         LIMIT ?2"
+    ))?;
+
+    let rows = stmt.query_map(params![min_minutes, limit as i64], session_from_row)?;
+
+    let mut sessions = Vec::new();
+    for row in rows {
+        sessions.push(row?);
+    }
+
+    Ok(sessions)
+}
+
+/// Find Copilot topics whose session count changed between two periods
+///
+/// Keywords are read from each session's `topics_json`. The last `days` days
+/// are compared with the `days` days before them; `trend` is the recent count
+/// divided by the prior count (or by one when the topic is new). Topics seen
+/// in fewer than `min_sessions` sessions across both periods are left out.
+/// Results are ordered by trend, strongest rise first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Length of each comparison period in days
+/// * `min_sessions` - Minimum sessions mentioning a topic across both periods
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_trending_topics(
+    conn: &Connection,
+    days: u32,
+    min_sessions: u32,
+) -> Result<Vec<TopicTrend>, QueryError> {
+    let recent_since = format!("-{} days", days);
+    let prior_since = format!("-{} days", u64::from(days) * 2);
+
+    let mut stmt = conn.prepare(
+        r#"
+        WITH topics AS (
+            SELECT DISTINCT s.id, topic.value AS keyword, datetime(s.created_at) AS created_at
+            FROM copilot_sessions s, json_each(s.topics_json) topic
+            WHERE s.topics_json IS NOT NULL
+              AND datetime(s.created_at) >= datetime('now', ?2)
+        )
+        SELECT keyword,
+               SUM(created_at >= datetime('now', ?1)) AS recent,
+               SUM(created_at < datetime('now', ?1)) AS prior
+        FROM topics
+        GROUP BY keyword
+        HAVING COUNT(*) >= ?3
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![recent_since, prior_since, min_sessions], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+
+    let mut trends = Vec::new();
+    for row in rows {
+        let (keyword, recent, prior) = row?;
+        trends.push(TopicTrend {
+            keyword,
+            session_count: recent.max(0) as u64,
+            trend: recent as f64 / prior.max(1) as f64,
+        });
+    }
+
+    trends.sort_by(|a, b| {
+        b.trend
+            .total_cmp(&a.trend)
+            .then_with(|| b.session_count.cmp(&a.session_count))
+            .then_with(|| a.keyword.cmp(&b.keyword))
+    });
+
+    Ok(trends)
+}
+
+/// Split a Copilot session into question-answer turns
+///
+/// Messages are ordered by timestamp. Each user message starts a new pair and
+/// the first assistant message after it becomes the answer. A user message
+/// followed by another user message, or ending the session, has no answer.
+/// System messages and assistant messages with no preceding question are
+/// left out.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `session_id` - Session ID or original VS Code session ID
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_qa_pairs(conn: &Connection, session_id: &str) -> Result<Vec<QAPair>, QueryError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS}
+         FROM copilot_messages m
+         JOIN copilot_sessions s ON s.id = m.session_id
+         WHERE s.id = ?1 OR s.vscode_session_id = ?1
+         ORDER BY m.timestamp, m.rowid"
+    ))?;
+
+    let rows = stmt.query_map([session_id], message_from_row)?;
+    let messages = rows.collect::<Result<Vec<_>, _>>()?;
+
+    Ok(pair_messages(messages))
+}
+
+/// Pair up messages that are already in timestamp order
+///
+/// See [`get_qa_pairs`] for how questions and answers are matched.
+#[must_use]
+pub fn pair_messages(messages: Vec<CopilotMessageRecord>) -> Vec<QAPair> {
+    let mut pairs = Vec::new();
+    let mut pending: Option<CopilotMessageRecord> = None;
+    for message in messages {
+        match message.role.as_str() {
+            "user" => {
+                if let Some(question) = pending.replace(message) {
+                    pairs.push(QAPair {
+                        question,
+                        answer: None,
+                    });
+                }
+            }
+            "assistant" => {
+                if let Some(question) = pending.take() {
+                    pairs.push(QAPair {
+                        question,
+                        answer: Some(message),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(question) = pending {
+        pairs.push(QAPair {
+            question,
+            answer: None,
+        });
+    }
+
+    pairs
+}
+
+/// Get a Copilot session and all of its messages, oldest first
+///
+/// `session_id` may be the session ID or the original VS Code session ID;
+/// if several workspaces share a VS Code ID, the newest session is used.
+/// Returns `None` if the session does not exist.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `session_id` - Session ID or original VS Code session ID
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_copilot_session_with_messages(
+    conn: &Connection,
+    session_id: &str,
+) -> Result<Option<SessionDetail>, QueryError> {
+    let Some((session, messages)) = copilot_session_with_messages(conn, session_id)? else {
+        return Ok(None);
+    };
+
+    let metadata = session
+        .metadata_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok());
+    let metadata_field = |key: &str| {
+        metadata
+            .as_ref()
+            .and_then(|m| m.get(key))
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+    };
+
+    Ok(Some(SessionDetail {
+        id: session.id,
+        vscode_session_id: session.vscode_session_id,
+        created_at: session.created_at.to_rfc3339(),
+        updated_at: session.updated_at.to_rfc3339(),
+        model: metadata_field("model"),
+        mode: metadata_field("mode"),
+        messages: messages
+            .into_iter()
+            .map(|m| MessageDetail {
+                role: m.role,
+                content: m.content,
+                timestamp: m.timestamp.to_rfc3339(),
+                variables_json: m.variables_json,
+            })
+            .collect(),
+    }))
+}
+
+/// Get Copilot messages addressed to a chat participant, newest first
+///
+/// `agent_name` may be given with or without its leading `@`.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `agent_name` - Participant name, e.g. `@workspace`
+/// * `limit` - Maximum number of messages to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_messages_by_agent(
+    conn: &Connection,
+    agent_name: &str,
+    limit: usize,
+) -> Result<Vec<CopilotMessageRecord>, QueryError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS}
+         FROM copilot_messages m
+         WHERE m.agent = ?1
+         ORDER BY m.timestamp DESC, m.rowid DESC
+         LIMIT ?2"
+    ))?;
+
+    let rows = stmt.query_map(
+        params![agent_name.trim_start_matches('@'), limit as i64],
+        message_from_row,
+    )?;
+
+    let mut messages = Vec::new();
+    for row in rows {
+        messages.push(row?);
+    }
+
+    Ok(messages)
+}
+
+/// Search the code blocks extracted from Copilot messages
+///
+/// Unnests each message's `code_blocks_json` array. `language` matches the
+/// fence language case-insensitively and `query` is a case-insensitive
+/// substring match on the block content; at least one must be given.
+/// Results are most recent first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `language` - Optional language to filter by (e.g. "rust")
+/// * `query` - Text the block content must contain; empty matches any
+/// * `limit` - Maximum number of blocks to return
+///
+/// # Errors
+///
+/// Returns `QueryError::InvalidParameter` if neither a language nor a query
+/// is given, or an error if the query fails.
+pub fn search_code_blocks(
+    conn: &Connection,
+    language: Option<&str>,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<CodeBlockMatch>, QueryError> {
+    let language = language.map(str::trim).filter(|l| !l.is_empty());
+    let query = query.trim();
+    if language.is_none() && query.is_empty() {
+        return Err(QueryError::InvalidParameter(
+            "Either a language or a search query is required".to_string(),
+        ));
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.session_id, s.workspace_id, m.timestamp,
+                json_extract(b.value, '$.language'), json_extract(b.value, '$.content')
+         FROM copilot_messages m
+         JOIN copilot_sessions s ON s.id = m.session_id
+         JOIN json_each(m.code_blocks_json) b
+         WHERE m.code_blocks_json IS NOT NULL
+           AND (?1 IS NULL OR lower(json_extract(b.value, '$.language')) = lower(?1))
+           AND instr(lower(json_extract(b.value, '$.content')), lower(?2)) > 0
+         ORDER BY m.timestamp DESC, m.rowid DESC, b.key
+         LIMIT ?3",
+    )?;
+
+    let rows = stmt.query_map(params![language, query, limit as i64], |row| {
+        Ok(CodeBlockMatch {
+            message_id: row.get(0)?,
+            session_id: row.get(1)?,
+            workspace_id: row.get(2)?,
+            timestamp: row.get(3)?,
+            language: row.get(4)?,
+            content: row.get(5)?,
+        })
+    })?;
+
+    let mut blocks = Vec::new();
+    for row in rows {
+        blocks.push(row?);
+    }
+
+    Ok(blocks)
+}
+
+/// Count the kinds of context variables attached to Copilot messages
+///
+/// Unnests each message's `variables_json` array and groups the entries by
+/// their `kind`, most used first. Messages without variables are ignored.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Number of days to look back
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_variable_kind_stats(
+    conn: &Connection,
+    days: u32,
+    workspace_filter: Option<&str>,
+) -> Result<Vec<VariableKindStats>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+    let since = format!("-{} days", days);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT json_extract(v.value, '$.kind') AS kind,
+               COUNT(*),
+               COUNT(DISTINCT m.session_id)
+        FROM copilot_messages m
+        JOIN copilot_sessions s ON s.id = m.session_id,
+             json_each(m.variables_json) v
+        WHERE json_valid(m.variables_json)
+          AND datetime(m.timestamp) >= datetime('now', ?1)
+          AND (?2 IS NULL OR s.workspace_id = ?2)
+          AND kind IS NOT NULL
+        GROUP BY kind
+        ORDER BY COUNT(*) DESC, kind
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![since, resolved_workspace_id], |row| {
+        Ok(VariableKindStats {
+            kind: row.get(0)?,
+            usage_count: row.get::<_, i64>(1)?.max(0) as u64,
+            distinct_sessions: row.get::<_, i64>(2)?.max(0) as u64,
+        })
+    })?;
+
+    let mut stats = Vec::new();
+    for row in rows {
+        stats.push(row?);
+    }
+
+    Ok(stats)
+}
+
+/// Sum lines added per file extension over a time period
+///
+/// Uses the `extension_stats` recorded in each commit's diff JSON during
+/// ingestion; commits ingested without diffs contribute nothing.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Number of days to look back
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_language_activity_stats(
+    conn: &Connection,
+    days: u32,
+) -> Result<HashMap<String, i64>, QueryError> {
+    let since = format!("-{} days", days);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT ext.key, SUM(json_extract(ext.value, '$.insertions'))
+        FROM commits c, json_each(c.diff_json, '$.extension_stats') ext
+        WHERE c.timestamp >= datetime('now', ?)
+          AND json_valid(c.diff_json)
+        GROUP BY ext.key
+        "#,
+    )?;
+
+    let rows = stmt.query_map([&since], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut stats = HashMap::new();
+    for row in rows {
+        let (ext, insertions) = row?;
+        stats.insert(ext, insertions);
+    }
+
+    Ok(stats)
+}
+
+/// Get the files changed most often over a time period
+///
+/// Aggregates the per-file entries of each commit's diff JSON. Files are
+/// ranked by the number of commits touching them, then by lines changed.
+/// Commits ingested without diffs contribute nothing.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `days` - Number of days to look back
+/// * `limit` - Maximum number of files to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_most_active_files(
+    conn: &Connection,
+    workspace_filter: Option<&str>,
+    days: u32,
+    limit: usize,
+) -> Result<Vec<ActiveFileStat>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+    let since = format!("-{} days", days);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT json_extract(f.value, '$.path') AS file_path,
+               COUNT(DISTINCT c.id) AS commit_count,
+               COALESCE(SUM(json_extract(f.value, '$.insertions')), 0) AS additions,
+               COALESCE(SUM(json_extract(f.value, '$.deletions')), 0) AS deletions
+        FROM commits c, json_each(c.diff_json, '$.files') f
+        WHERE datetime(c.timestamp) >= datetime('now', ?1)
+          AND (?2 IS NULL OR c.workspace_id = ?2)
+          AND json_valid(c.diff_json)
+        GROUP BY file_path
+        ORDER BY commit_count DESC, additions + deletions DESC, file_path
+        LIMIT ?3
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![since, resolved_workspace_id, limit as i64], |row| {
+        let path: String = row.get(0)?;
+        Ok(ActiveFileStat {
+            extension: hindsight_git::file_extension(&path),
+            path,
+            commit_count: row.get::<_, i64>(1)?.max(0) as u64,
+            additions: row.get::<_, i64>(2)?.max(0) as u64,
+            deletions: row.get::<_, i64>(3)?.max(0) as u64,
+        })
+    })?;
+
+    let mut files = Vec::new();
+    for row in rows {
+        files.push(row?);
+    }
+
+    Ok(files)
+}
+
+/// Get the number of commits per day over a recent period
+///
+/// Returns one entry per day, oldest first, ending today (UTC). Days without
+/// commits are included with zero counts.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Number of days to include (including today)
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_daily_commit_volume(
+    conn: &Connection,
+    days: u32,
+    workspace_filter: Option<&str>,
+) -> Result<Vec<DailyCommitVolume>, QueryError> {
+    if days == 0 {
+        return Ok(Vec::new());
+    }
+
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+    let since = format!("-{} days", days - 1);
+
+    let mut stmt = conn.prepare(
+        r#"
+        WITH RECURSIVE dates(day) AS (
+            SELECT date('now', ?1)
+            UNION ALL
+            SELECT date(day, '+1 day') FROM dates WHERE day < date('now')
+        )
+        SELECT d.day, COUNT(c.id), COUNT(DISTINCT c.author)
+        FROM dates d
+        LEFT JOIN commits c
+          ON strftime('%Y-%m-%d', c.timestamp) = d.day
+         AND (?2 IS NULL OR c.workspace_id = ?2)
+        GROUP BY d.day
+        ORDER BY d.day
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![since, resolved_workspace_id], |row| {
+        Ok(DailyCommitVolume {
+            date: row.get(0)?,
+            commit_count: row.get::<_, i64>(1)?.max(0) as u64,
+            distinct_authors: row.get::<_, i64>(2)?.max(0) as u64,
+        })
+    })?;
+
+    let mut volume = Vec::new();
+    for row in rows {
+        volume.push(row?);
+    }
+
+    Ok(volume)
+}
+
+/// Get the authors with the most commits
+///
+/// Authors are grouped by name and email. `files_changed` is read from each
+/// commit's stored stats, or its stored diff for commits without stats, in
+/// Rust rather than with SQLite JSON functions, which are not compiled into
+/// every build.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `limit` - Maximum number of authors to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_contributors(
+    conn: &Connection,
+    workspace_filter: Option<&str>,
+    limit: usize,
+) -> Result<Vec<ContributorStat>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT author, author_email, COUNT(*) AS commit_count, MIN(timestamp), MAX(timestamp)
+        FROM commits
+        WHERE (?1 IS NULL OR workspace_id = ?1)
+        GROUP BY author, author_email
+        ORDER BY commit_count DESC, MAX(timestamp) DESC, author
+        LIMIT ?2
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![resolved_workspace_id, limit as i64], |row| {
+        Ok(ContributorStat {
+            author: row.get(0)?,
+            author_email: row.get(1)?,
+            commit_count: row.get::<_, i64>(2)?.max(0) as u64,
+            first_commit_at: row.get(3)?,
+            last_commit_at: row.get(4)?,
+            files_changed: 0,
+        })
+    })?;
+
+    let mut contributors = Vec::new();
+    for row in rows {
+        contributors.push(row?);
+    }
+
+    // Stats are stored for every commit but diffs only with include_diffs
+    let mut diff_stmt = conn.prepare(
+        r#"
+        SELECT COALESCE(stats_json, diff_json)
+        FROM commits
+        WHERE author = ?1
+          AND author_email IS ?2
+          AND (stats_json IS NOT NULL OR diff_json IS NOT NULL)
+          AND (?3 IS NULL OR workspace_id = ?3)
+        "#,
+    )?;
+
+    for contributor in &mut contributors {
+        let summaries = diff_stmt.query_map(
+            params![
+                contributor.author,
+                contributor.author_email,
+                resolved_workspace_id
+            ],
+            |row| row.get::<_, String>(0),
+        )?;
+        for summary in summaries {
+            contributor.files_changed += diff_files_changed(&summary?);
+        }
+    }
+
+    Ok(contributors)
+}
+
+/// Count commits by day of week and hour of day over a time period
+///
+/// Commit timestamps are bucketed in UTC. Commits whose timestamp SQLite
+/// cannot parse are ignored.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `author_filter` - Optional author name or email to match exactly; all
+///   authors when `None`
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `days` - Number of days to look back
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_author_activity_heatmap(
+    conn: &Connection,
+    author_filter: Option<&str>,
+    workspace_filter: Option<&str>,
+    days: u32,
+) -> Result<ActivityHeatmap, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+    let since = format!("-{} days", days);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT CAST(strftime('%w', timestamp) AS INTEGER) AS weekday,
+               CAST(strftime('%H', timestamp) AS INTEGER) AS hour,
+               COUNT(*)
+        FROM commits
+        WHERE datetime(timestamp) >= datetime('now', ?1)
+          AND (?2 IS NULL OR workspace_id = ?2)
+          AND (?3 IS NULL OR author = ?3 OR author_email = ?3)
+        GROUP BY weekday, hour
+        "#,
+    )?;
+
+    let rows = stmt.query_map(
+        params![since, resolved_workspace_id, author_filter],
+        |row| {
+            Ok((
+                row.get::<_, Option<i64>>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        },
+    )?;
+
+    let mut heatmap = ActivityHeatmap::default();
+    for row in rows {
+        let (Some(weekday), Some(hour), count) = row? else {
+            continue;
+        };
+        if let Some(cell) = heatmap
+            .counts
+            .get_mut(weekday as usize)
+            .and_then(|hours| hours.get_mut(hour as usize))
+        {
+            *cell = count.max(0) as u32;
+        }
+    }
+
+    Ok(heatmap)
+}
+
+/// Number of files changed in stored commit stats or a stored diff summary
+///
+/// Unparseable (for example truncated) diffs count as zero files.
+fn diff_files_changed(diff_json: &str) -> u64 {
+    let Ok(diff) = serde_json::from_str::<serde_json::Value>(diff_json) else {
+        return 0;
+    };
+    diff.get("files_changed")
+        .and_then(serde_json::Value::as_u64)
+        .or_else(|| {
+            diff.get("files")
+                .and_then(serde_json::Value::as_array)
+                .map(|files| files.len() as u64)
+        })
+        .unwrap_or(0)
+}
+
+/// Summary of recent activity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivitySummary {
+    /// Number of days covered
+    pub days: u32,
+    /// Number of commits
+    pub commits: u64,
+    /// Number of test runs
+    pub test_runs: u64,
+    /// Number of Copilot sessions
+    pub copilot_sessions: u64,
+    /// Number of failing tests
+    pub failing_tests: u64,
+    /// Authors with the most commits, as `(author, commit_count)` pairs
+    pub top_contributors: Vec<(String, u64)>,
+}
+
+/// Maximum number of authors in [`ActivitySummary::top_contributors`]
+pub const TOP_CONTRIBUTOR_LIMIT: usize = 5;
+
+/// Default API cost per 1,000 tokens (USD) used for token usage estimates
+pub const DEFAULT_COST_PER_1K_TOKENS: f64 = 0.01;
+
+/// Get estimated Copilot token usage over a time period
+///
+/// Sums the `token_estimate` recorded for each session ingested in the last
+/// `days` days, grouped by model. Costs are estimated at
+/// [`DEFAULT_COST_PER_1K_TOKENS`]; use [`TokenUsageStats::with_cost_rate`] to
+/// apply a different rate.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Number of days to look back
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_total_token_usage(conn: &Connection, days: u32) -> Result<TokenUsageStats, QueryError> {
+    let since = format!("-{} days", days);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT COALESCE(json_extract(metadata_json, '$.model'), 'unknown') AS model,
+               SUM(COALESCE(token_estimate, 0))
+        FROM copilot_sessions
+        WHERE created_at >= datetime('now', ?)
+        GROUP BY model
+        "#,
+    )?;
+
+    let rows = stmt.query_map([&since], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut by_model = HashMap::new();
+    for row in rows {
+        let (model, tokens) = row?;
+        by_model.insert(model, tokens.max(0) as u64);
+    }
+
+    let total_tokens = by_model.values().sum();
+
+    Ok(TokenUsageStats {
+        days,
+        total_tokens,
+        estimated_cost_usd: 0.0,
+        by_model,
+    }
+    .with_cost_rate(DEFAULT_COST_PER_1K_TOKENS))
+}
+
+/// Estimated Copilot token usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenUsageStats {
+    /// Number of days covered
+    pub days: u32,
+    /// Total estimated tokens across all sessions
+    pub total_tokens: u64,
+    /// Estimated cost in US dollars
+    pub estimated_cost_usd: f64,
+    /// Estimated tokens per model ("unknown" when no model was recorded)
+    pub by_model: HashMap<String, u64>,
+}
+
+impl TokenUsageStats {
+    /// Recompute the estimated cost using the given rate per 1,000 tokens
+    #[must_use]
+    pub fn with_cost_rate(mut self, cost_per_1k_tokens: f64) -> Self {
+        self.estimated_cost_usd = self.total_tokens as f64 / 1000.0 * cost_per_1k_tokens;
+        self
+    }
+}
+
+/// Get the CI annotations recorded for a test run, in log order
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_ci_annotations_for_run(
+    conn: &Connection,
+    run_id: &str,
+) -> Result<Vec<CiAnnotation>, QueryError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT level, file, line, message
+        FROM ci_annotations
+        WHERE run_id = ?1
+        ORDER BY rowid
+        "#,
+    )?;
+
+    let rows = stmt.query_map([run_id], |row| {
+        let level: String = row.get(0)?;
+        Ok(CiAnnotation {
+            level: level.parse().unwrap_or(AnnotationLevel::Error),
+            file: row.get(1)?,
+            line: row.get(2)?,
+            message: row.get(3)?,
+        })
+    })?;
+
+    let mut annotations = Vec::new();
+    for row in rows {
+        annotations.push(row?);
+    }
+
+    Ok(annotations)
+}
+
+/// Get commits with their associated test results
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `commit_sha` - Git commit SHA (or prefix)
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_commit_with_tests(
+    conn: &Connection,
+    commit_sha: &str,
+) -> Result<Option<CommitWithTests>, QueryError> {
+    // Find the commit - schema uses diff_json for file changes
+    let commit = conn
+        .query_row(
+            r#"
+            SELECT id, sha, message, author, timestamp, diff_json, stats_json
+            FROM commits
+            WHERE sha LIKE ? || '%'
+            LIMIT 1
+            "#,
+            [commit_sha],
+            |row| {
+                let stats_json: Option<String> = row.get(6)?;
+                let commit = CommitWithTests {
+                    id: row.get(0)?,
+                    sha: row.get(1)?,
+                    message: row.get(2)?,
+                    author: row.get(3)?,
+                    timestamp: row.get(4)?,
+                    files: Vec::new(),
+                    stats: stats_json.and_then(|json| serde_json::from_str(&json).ok()),
+                    test_runs: Vec::new(),
+                };
+                Ok((commit, row.get::<_, Option<String>>(5)?))
+            },
+        )
+        .optional()?;
+
+    let Some((mut commit, diff_json)) = commit else {
+        return Ok(None);
+    };
+
+    // Parse diff JSON to extract file paths
+    commit.files = match diff_json {
+        Some(ref json) => {
+            // Try to parse as object with "files" key, or as array of file paths
+            serde_json::from_str::<serde_json::Value>(json)
+                .ok()
+                .and_then(|v| {
+                    // Try {"files": [...]} format
+                    if let Some(files) = v.get("files") {
+                        files.as_array().map(|arr| {
+                            arr.iter()
+                                .filter_map(|f| f.get("path").and_then(|p| p.as_str()))
+                                .map(String::from)
+                                .collect()
+                        })
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    // Get associated test runs - schema uses started_at, passed_count, failed_count, ignored_count
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT r.id, r.started_at, r.passed_count, r.failed_count, r.ignored_count
+        FROM test_runs r
+        WHERE r.commit_sha = ?
+        ORDER BY r.started_at DESC
+        "#,
+    )?;
+
+    commit.test_runs = stmt
+        .query_map([&commit.sha], |row| {
+            Ok(TestRunSummary {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                passed: row.get(2)?,
+                failed: row.get(3)?,
+                skipped: row.get(4)?,
+                annotations: Vec::new(),
+            })
+        })?
+        .filter_map(Result::ok)
+        .collect();
+
+    for run in &mut commit.test_runs {
+        run.annotations = get_ci_annotations_for_run(conn, &run.id)?;
+    }
+
+    Ok(Some(commit))
+}
+
+/// Get the workspaces with activity in the last `days` days
+///
+/// Counts commits, test runs and Copilot sessions per workspace within the
+/// window. Workspaces with no activity are left out; the rest are ordered by
+/// their most recent event, newest first.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `days` - Number of days to look back
+/// * `limit` - Maximum number of workspaces to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_active_workspaces(
+    conn: &Connection,
+    days: u32,
+    limit: usize,
+) -> Result<Vec<WorkspaceActivity>, QueryError> {
+    let since = format!("-{} days", days);
+
+    // The subqueries only expose workspace_id, last_at and events, so the
+    // workspace columns need no table prefix
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {WORKSPACE_COLUMNS},
+               c.last_at, r.last_at, s.last_at,
+               COALESCE(c.events, 0) + COALESCE(r.events, 0) + COALESCE(s.events, 0) AS total
+        FROM workspaces w
+        LEFT JOIN (
+            SELECT workspace_id, MAX(timestamp) AS last_at, COUNT(*) AS events
+            FROM commits
+            WHERE datetime(timestamp) >= datetime('now', ?1)
+            GROUP BY workspace_id
+        ) c ON c.workspace_id = w.id
+        LEFT JOIN (
+            SELECT workspace_id, MAX(started_at) AS last_at, COUNT(*) AS events
+            FROM test_runs
+            WHERE datetime(started_at) >= datetime('now', ?1)
+            GROUP BY workspace_id
+        ) r ON r.workspace_id = w.id
+        LEFT JOIN (
+            SELECT workspace_id, MAX(updated_at) AS last_at, COUNT(*) AS events
+            FROM copilot_sessions
+            WHERE datetime(updated_at) >= datetime('now', ?1)
+            GROUP BY workspace_id
+        ) s ON s.workspace_id = w.id
+        WHERE total > 0
+        ORDER BY MAX(
+            COALESCE(datetime(c.last_at), ''),
+            COALESCE(datetime(r.last_at), ''),
+            COALESCE(datetime(s.last_at), '')
+        ) DESC, w.name
+        LIMIT ?2
+        "#
+    ))?;
+
+    let rows = stmt.query_map(params![since, limit as i64], |row| {
+        Ok(WorkspaceActivity {
+            workspace: workspace_from_row(row)?,
+            last_commit_at: row.get(9)?,
+            last_test_run_at: row.get(10)?,
+            last_copilot_session_at: row.get(11)?,
+            total_events_in_window: row.get::<_, i64>(12)?.max(0) as u64,
+        })
+    })?;
+
+    let mut workspaces = Vec::new();
+    for row in rows {
+        workspaces.push(row?);
+    }
+
+    Ok(workspaces)
+}
+
+/// Get git tags, most recent first
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `workspace_filter` - Optional workspace path or ID to filter by
+/// * `limit` - Maximum number of tags to return
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_tags(
+    conn: &Connection,
+    workspace_filter: Option<&str>,
+    limit: usize,
+) -> Result<Vec<GitTag>, QueryError> {
+    let resolved_workspace_id = match workspace_filter {
+        Some(filter) => resolve_workspace_filter(conn, filter)?,
+        None => None,
+    };
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.name, t.sha, t.tagged_sha, t.tagger, t.message, t.is_annotated,
+               t.timestamp, c.message
+        FROM tags t
+        JOIN commits c ON c.workspace_id = t.workspace_id AND c.sha = t.tagged_sha
+        WHERE ?1 IS NULL OR t.workspace_id = ?1
+        ORDER BY t.timestamp DESC, t.name DESC
+        LIMIT ?2
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![resolved_workspace_id, limit as i64], |row| {
+        let commit_message: String = row.get(7)?;
+        Ok(GitTag {
+            name: row.get(0)?,
+            sha: row.get(1)?,
+            tagged_sha: row.get(2)?,
+            tagger: row.get(3)?,
+            message: row.get(4)?,
+            is_annotated: row.get(5)?,
+            timestamp: row.get(6)?,
+            commit_subject: commit_message.lines().next().unwrap_or("").to_string(),
+        })
+    })?;
+
+    let mut tags = Vec::new();
+    for row in rows {
+        tags.push(row?);
+    }
+
+    Ok(tags)
+}
+
+/// Changes made to one file by a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiffEntry {
+    /// Path to the file after the change
+    pub path: String,
+    /// Previous path, for renamed and copied files
+    pub old_path: Option<String>,
+    /// Lines added
+    pub additions: usize,
+    /// Lines deleted
+    pub deletions: usize,
+    /// Unified diff text, present when the repository could be read
+    pub patch: Option<String>,
+}
+
+/// The full diff of a single commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitDiff {
+    /// Git commit SHA
+    pub sha: String,
+    /// Commit message
+    pub message: String,
+    /// Path of the workspace the commit was ingested from
+    pub workspace_path: String,
+    /// Changed files
+    pub files: Vec<FileDiffEntry>,
+    /// Whether `files` came from a diff stored at ingest time
+    #[serde(skip)]
+    pub diff_stored: bool,
+}
+
+/// Get the stored diff of a commit by SHA or SHA prefix
+///
+/// Only per-file line counts are stored, so every `patch` is `None`;
+/// callers can fill them in from the repository at `workspace_path`.
+/// Commits ingested without diffs have no files and `diff_stored` unset.
+/// Returns `None` if no commit matches.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection
+/// * `commit_sha` - Full SHA or SHA prefix
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn get_commit_diff(
+    conn: &Connection,
+    commit_sha: &str,
+) -> Result<Option<CommitDiff>, QueryError> {
+    let commit = conn
+        .query_row(
+            r#"
+            SELECT c.sha, c.message, w.path, c.diff_json
+            FROM commits c
+            JOIN workspaces w ON w.id = c.workspace_id
+            WHERE c.sha LIKE ? || '%'
+            LIMIT 1
+            "#,
+            [commit_sha],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            },
+        )
+        .optional()?;
+
+    let Some((sha, message, workspace_path, diff_json)) = commit else {
+        return Ok(None);
+    };
+
+    let stored = diff_json.and_then(|json| hindsight_git::DiffSummary::from_json(&json).ok());
+    let diff_stored = stored.is_some();
+    let files = stored
+        .map(|diff| {
+            diff.files
+                .into_iter()
+                .map(|file| FileDiffEntry {
+                    path: file.path,
+                    old_path: None,
+                    additions: file.insertions,
+                    deletions: file.deletions,
+                    patch: None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(CommitDiff {
+        sha,
+        message,
+        workspace_path,
+        files,
+        diff_stored,
+    }))
+}
+
+/// A commit with associated test information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitWithTests {
+    /// Database ID (UUID)
+    pub id: String,
+    /// Git commit SHA
+    pub sha: String,
+    /// Commit message
+    pub message: String,
+    /// Author name
+    pub author: String,
+    /// ISO 8601 timestamp
+    pub timestamp: String,
+    /// Changed files
+    pub files: Vec<String>,
+    /// Line statistics, present even when the diff was not stored
+    pub stats: Option<hindsight_git::CommitStats>,
+    /// Associated test runs
+    pub test_runs: Vec<TestRunSummary>,
+}
+
+/// Summary of a test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRunSummary {
+    /// Database ID (UUID)
+    pub id: String,
+    /// ISO 8601 timestamp
+    pub timestamp: String,
+    /// Number of passed tests
+    pub passed: i32,
+    /// Number of failed tests
+    pub failed: i32,
+    /// Number of skipped tests
+    pub skipped: i32,
+    /// CI annotations recorded for the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<CiAnnotation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrations::migrate(&conn).expect("migrate");
+        conn
+    }
+
+    #[test]
+    fn test_get_timeline_empty() {
+        let conn = setup_db();
+        let events = get_timeline(&conn, 10, None).expect("timeline");
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_search_commits_empty_query() {
+        let conn = setup_db();
+        let result = search_commits(&conn, "", 10);
+        assert!(matches!(result, Err(QueryError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_validate_fts5_query_passthrough() {
+        for query in [
+            "parser",
+            "fix parser",
+            "\"hello world\"",
+            "auth* OR login",
+            "(bug OR fix) NOT docs",
+            "日本語",
+        ] {
+            assert_eq!(validate_fts5_query(query).expect("valid"), query);
+        }
+    }
+
+    #[test]
+    fn test_validate_fts5_query_quotes_special_words() {
+        assert_eq!(
+            validate_fts5_query("  foo-bar  v1.2 ").expect("valid"),
+            "\"foo-bar\" \"v1.2\""
+        );
+        assert_eq!(
+            validate_fts5_query("say\"hi").expect("valid"),
+            "\"say\"\"hi\""
+        );
+        assert_eq!(
+            validate_fts5_query("\"unterminated phrase").expect("valid"),
+            "\"unterminated phrase\""
+        );
+    }
+
+    #[test]
+    fn test_validate_fts5_query_rejects_syntax_errors() {
+        for query in [
+            "",
+            "   ",
+            "\"\"",
+            "(fix",
+            "fix)",
+            "()",
+            "AND fix",
+            "fix OR",
+            "fix AND OR bug",
+            "(fix NOT)",
+        ] {
+            assert!(
+                matches!(
+                    validate_fts5_query(query),
+                    Err(QueryError::InvalidParameter(_))
+                ),
+                "{query:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_commits_phrase_matches_exact_phrase() {
+        let conn = setup_db();
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+        conn.execute_batch(
+            r#"
+            INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+            VALUES ('c-1', 'ws-1', 'aaaa', 'Print hello world on start', 'A', datetime('now'), datetime('now'));
+            INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+            VALUES ('c-2', 'ws-1', 'bbbb', 'Say hello to the new world', 'A', datetime('now'), datetime('now'));
+            "#,
+        )
+        .expect("insert commits");
+
+        let both = search_commits(&conn, "hello world", 10).expect("search");
+        assert_eq!(both.len(), 2);
+
+        let phrase = search_commits_phrase(&conn, "hello world", 10).expect("search");
+        assert_eq!(phrase.len(), 1);
+        assert_eq!(phrase[0].id, "c-1");
+
+        let quoted = search_commits(&conn, "\"hello world\"", 10).expect("search");
+        assert_eq!(quoted.len(), 1);
+        assert_eq!(quoted[0].id, "c-1");
+
+        assert!(matches!(
+            search_commits_phrase(&conn, "  ", 10),
+            Err(QueryError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_query_builder() {
+        assert_eq!(
+            SearchQuery::phrase("  a \"b\" c ").as_fts5(),
+            "\"a \"\"b\"\" c\""
+        );
+        assert_eq!(SearchQuery::prefix("auth*").as_fts5(), "\"auth\"*");
+        assert_eq!(SearchQuery::prefix("auth"), SearchQuery::prefix("auth*"));
+        assert_eq!(
+            SearchQuery::and(SearchQuery::phrase("a"), SearchQuery::prefix("b")).as_fts5(),
+            "(\"a\") AND (\"b\"*)"
+        );
+        assert_eq!(
+            SearchQuery::phrase("a").or(SearchQuery::phrase("  ")),
+            SearchQuery::phrase("a")
+        );
+        assert!(SearchQuery::prefix("*").is_empty());
+        assert_eq!(
+            SearchQuery::raw("fix AND").map_err(|e| e.to_string()),
+            Err(
+                "Invalid parameter: Invalid search query: 'AND' must be followed by a search term"
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_search_matching_phrase_and_prefix() {
+        let conn = setup_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+            INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+            VALUES ('c-1', 'ws-1', 'aaaa', 'Fix login timeout on slow networks', 'A', datetime('now'), datetime('now')),
+                   ('c-2', 'ws-1', 'bbbb', 'Raise timeout for login retries', 'A', datetime('now'), datetime('now')),
+                   ('c-3', 'ws-1', 'cccc', 'Add authentication middleware', 'A', datetime('now'), datetime('now')),
+                   ('c-4', 'ws-1', 'dddd', 'Reject unauth requests (OR NOT "quoted")', 'A', datetime('now'), datetime('now'));
+            INSERT INTO copilot_sessions (id, workspace_id, vscode_session_id, created_at, updated_at)
+            VALUES ('s-1', 'ws-1', 'vscode-1', datetime('now'), datetime('now'));
+            INSERT INTO copilot_messages (id, session_id, request_id, role, content, timestamp, created_at)
+            VALUES ('m-1', 's-1', 'req-1', 'user', 'Why does the login timeout fire?', datetime('now'), datetime('now'));
+            "#,
+        )
+        .expect("insert data");
+
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            let mut ids: Vec<String> = results.into_iter().map(|r| r.id).collect();
+            ids.sort();
+            ids
+        };
+
+        // Both commits mention login and timeout, only one as a phrase
+        let words = SearchQuery::raw("login timeout").expect("query");
+        assert_eq!(
+            ids(search_commits_matching(&conn, &words, 10).expect("search")),
+            vec!["c-1", "c-2"]
+        );
+        let phrase = SearchQuery::phrase("login timeout");
+        assert_eq!(
+            ids(search_commits_matching(&conn, &phrase, 10).expect("search")),
+            vec!["c-1"]
+        );
+        assert_eq!(
+            ids(search_all_matching(&conn, &phrase, 10).expect("search")),
+            vec!["c-1", "m-1"]
+        );
+
+        let prefix = SearchQuery::prefix("auth*");
+        assert_eq!(
+            ids(search_commits_matching(&conn, &prefix, 10).expect("search")),
+            vec!["c-3"]
+        );
+
+        // Operators and quotes inside a phrase are searched literally
+        let literal = SearchQuery::phrase("OR NOT \"quoted\"");
+        assert_eq!(
+            ids(search_commits_matching(&conn, &literal, 10).expect("search")),
+            vec!["c-4"]
+        );
+
+        let either = SearchQuery::or(phrase, prefix.and(SearchQuery::phrase("middleware")));
+        assert_eq!(
+            ids(search_commits_matching(&conn, &either, 10).expect("search")),
+            vec!["c-1", "c-3"]
+        );
+
+        assert!(matches!(
+            search_messages_matching(&conn, &SearchQuery::phrase(""), 10),
+            Err(QueryError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_messages_empty_query() {
+        let conn = setup_db();
+        let result = search_messages(&conn, "", 10);
+        assert!(matches!(result, Err(QueryError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_search_notes_empty_query() {
+        let conn = setup_db();
+        let result = search_notes(&conn, "", 10);
+        assert!(matches!(result, Err(QueryError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_get_failing_tests_empty() {
+        let conn = setup_db();
+        let tests = get_failing_tests(&conn, 10, None, None).expect("failing tests");
+        assert!(tests.is_empty());
+    }
+
+    #[test]
+    fn test_get_activity_summary() {
+        let conn = setup_db();
+        let summary = get_activity_summary(&conn, 7).expect("activity summary");
+        assert_eq!(summary.days, 7);
+        assert_eq!(summary.commits, 0);
+        assert_eq!(summary.test_runs, 0);
+        assert_eq!(summary.copilot_sessions, 0);
+        assert_eq!(summary.failing_tests, 0);
+        assert!(summary.top_contributors.is_empty());
+    }
+
+    #[test]
+    fn test_get_commit_with_tests_not_found() {
+        let conn = setup_db();
+        let result = get_commit_with_tests(&conn, "nonexistent").expect("query");
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_get_active_workspaces() {
+        let conn = setup_db();
+        let days_ago = |days: i64| (Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-a', 'alpha', '/alpha', strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+                   ('ws-b', 'beta', '/beta', strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+                   ('ws-c', 'gamma', '/gamma', strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), strftime('%Y-%m-%dT%H:%M:%SZ', 'now'));
+            "#,
+        )
+        .expect("insert workspaces");
+        let insert_commit = |id: &str, ws: &str, timestamp: &str| {
+            conn.execute(
+                "INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+                 VALUES (?1, ?2, ?1, 'msg', 'Dev', ?3, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+                params![id, ws, timestamp],
+            )
+            .expect("insert commit");
+        };
+        insert_commit("c-1", "ws-a", &days_ago(3));
+        let latest_commit = days_ago(2);
+        insert_commit("c-2", "ws-a", &latest_commit);
+        insert_commit("c-3", "ws-c", &days_ago(30));
+        conn.execute(
+            "INSERT INTO test_runs (id, workspace_id, started_at) VALUES ('run-1', 'ws-b', ?1)",
+            [days_ago(1)],
+        )
+        .expect("insert run");
+        conn.execute(
+            "INSERT INTO copilot_sessions (id, workspace_id, vscode_session_id, created_at, updated_at)
+             VALUES ('s-1', 'ws-a', 'chat', ?1, ?1)",
+            [days_ago(4)],
+        )
+        .expect("insert session");
+
+        let active = get_active_workspaces(&conn, 7, 10).expect("active");
+        let names: Vec<&str> = active.iter().map(|a| a.workspace.name.as_str()).collect();
+        assert_eq!(names, vec!["beta", "alpha"], "gamma is outside the window");
+
+        let alpha = &active[1];
+        assert_eq!(alpha.total_events_in_window, 3);
+        assert_eq!(
+            alpha.last_commit_at.as_deref(),
+            Some(latest_commit.as_str())
+        );
+        assert!(alpha.last_test_run_at.is_none());
+        assert!(alpha.last_copilot_session_at.is_some());
+        assert_eq!(active[0].total_events_in_window, 1);
+
+        assert_eq!(get_active_workspaces(&conn, 7, 1).expect("active").len(), 1);
+        assert_eq!(
+            get_active_workspaces(&conn, 60, 10).expect("active").len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_get_tags() {
+        let conn = setup_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'app', '/app', datetime('now'), datetime('now')),
+                   ('ws-2', 'lib', '/lib', datetime('now'), datetime('now'));
+            INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+            VALUES ('c1', 'ws-1', 'aaa111', 'Release 1.0', 'Dev', '2026-01-10T10:00:00Z', datetime('now')),
+                   ('c2', 'ws-1', 'bbb222', 'Release 1.1' || char(10) || char(10) || 'Notes', 'Dev', '2026-01-17T10:00:00Z', datetime('now')),
+                   ('c3', 'ws-2', 'ccc333', 'Lib release', 'Dev', '2026-01-12T10:00:00Z', datetime('now'));
+            INSERT INTO tags (id, workspace_id, name, sha, tagged_sha, tagger, message, is_annotated, timestamp, created_at)
+            VALUES ('t1', 'ws-1', 'v1.0.0', 'tag111', 'aaa111', 'Releaser', 'Version 1.0.0', 1, '2026-01-10T11:00:00Z', datetime('now')),
+                   ('t2', 'ws-1', 'v1.1.0', 'bbb222', 'bbb222', NULL, NULL, 0, '2026-01-17T10:00:00Z', datetime('now')),
+                   ('t3', 'ws-2', 'lib-v0.1', 'ccc333', 'ccc333', NULL, NULL, 0, '2026-01-12T10:00:00Z', datetime('now'));
+            "#,
+        )
+        .expect("insert tags");
+
+        let all = get_tags(&conn, None, 10).expect("tags");
+        let names: Vec<&str> = all.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["v1.1.0", "lib-v0.1", "v1.0.0"]);
+        assert_eq!(all[0].commit_subject, "Release 1.1");
+        assert!(!all[0].is_annotated);
+        assert!(all[2].is_annotated);
+        assert_eq!(all[2].tagger.as_deref(), Some("Releaser"));
+
+        let app = get_tags(&conn, Some("/app"), 1).expect("tags");
+        assert_eq!(app.len(), 1);
+        assert_eq!(app[0].name, "v1.1.0");
+    }
+
+    #[test]
+    fn test_get_commit_diff() {
+        let conn = setup_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+            INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, diff_json, created_at)
+            VALUES ('c1', 'ws-1', 'abc123def456', 'Fix parser', 'Dev', '2026-01-17T10:00:00Z',
+                    '{"files_changed":1,"insertions":3,"deletions":1,"files":[{"path":"src/lib.rs","status":"modified","insertions":3,"deletions":1}]}',
+                    datetime('now'));
+            "#,
+        )
+        .expect("insert commit");
+
+        let diff = get_commit_diff(&conn, "abc123")
+            .expect("query")
+            .expect("commit");
+        assert_eq!(diff.sha, "abc123def456");
+        assert_eq!(diff.message, "Fix parser");
+        assert_eq!(diff.workspace_path, "/test");
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].path, "src/lib.rs");
+        assert_eq!((diff.files[0].additions, diff.files[0].deletions), (3, 1));
+        assert!(diff.files[0].patch.is_none());
+        assert!(diff.diff_stored);
+
+        assert!(get_commit_diff(&conn, "fff999").expect("query").is_none());
+    }
+
+    #[test]
+    fn test_get_test_results_by_env() {
+        let conn = setup_db();
+
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+        conn.execute_batch(
+            r#"
+            INSERT INTO test_runs (id, workspace_id, started_at, tags_json)
+            VALUES ('run-ci', 'ws-1', '2026-01-17T10:00:00Z', '{"env": "ci"}'),
+                   ('run-local', 'ws-1', '2026-01-17T11:00:00Z', '{"env": "local"}'),
+                   ('run-untagged', 'ws-1', '2026-01-17T12:00:00Z', NULL);
+            INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, created_at)
+            VALUES ('tr-1', 'run-ci', 'crate', 'flaky_test', 'failed', '2026-01-17T10:00:01Z'),
+                   ('tr-2', 'run-local', 'crate', 'flaky_test', 'passed', '2026-01-17T11:00:01Z'),
+                   ('tr-3', 'run-untagged', 'crate', 'flaky_test', 'passed', '2026-01-17T12:00:01Z');
+            "#,
+        )
+        .expect("insert runs");
+
+        let ci = get_test_results_by_env(&conn, "ci", 10).expect("ci results");
+        assert_eq!(ci.len(), 1);
+        assert_eq!(ci[0].run_id, "run-ci");
+        assert_eq!(ci[0].outcome, "failed");
+
+        let local = get_test_results_by_env(&conn, "local", 10).expect("local results");
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].run_id, "run-local");
+
+        assert!(
+            get_test_results_by_env(&conn, "staging", 10)
+                .expect("query")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_get_test_json_outputs() {
+        use crate::db::{OutputFormat, TestResultRecord};
+
+        let conn = setup_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+            INSERT INTO test_runs (id, workspace_id, started_at)
+            VALUES ('run-1', 'ws-1', '2026-01-17T10:00:00Z');
+            "#,
+        )
+        .expect("insert run");
+
+        let outputs = [
+            ("test_json", OutputFormat::Json, r#"{"items": [1, 2]}"#),
+            ("test_malformed", OutputFormat::Json, "{not json"),
+            ("test_raw", OutputFormat::Raw, "plain output"),
+        ];
+        for (name, format, content) in outputs {
+            let record = TestResultRecord::new(
+                "run-1".to_string(),
+                "suite".to_string(),
+                name.to_string(),
+                "passed".to_string(),
+                None,
+            )
+            .with_structured_output(format, content);
+            conn.execute(
+                "INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, output_json, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+                params![
+                    record.id,
+                    record.run_id,
+                    record.suite_name,
+                    record.test_name,
+                    record.outcome,
+                    record.output_json
+                ],
+            )
+            .expect("insert result");
+        }
+
+        let json = get_test_json_outputs(&conn, "run-1").expect("json outputs");
+        assert_eq!(
+            json,
+            vec![(
+                "test_json".to_string(),
+                serde_json::json!({"items": [1, 2]})
+            )]
+        );
+        assert!(
+            get_test_json_outputs(&conn, "run-2")
+                .expect("json outputs")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_get_total_token_usage_empty() {
+        let conn = setup_db();
+        let usage = get_total_token_usage(&conn, 7).expect("token usage");
+        assert_eq!(usage.total_tokens, 0);
+        assert!(usage.by_model.is_empty());
+        assert_eq!(usage.estimated_cost_usd, 0.0);
+    }
+
+    #[test]
+    fn test_get_total_token_usage_by_model() {
+        let conn = setup_db();
+
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+        conn.execute_batch(
+            r#"
+            INSERT INTO copilot_sessions (id, workspace_id, vscode_session_id, created_at, updated_at, metadata_json, token_estimate)
+            VALUES ('s-1', 'ws-1', 'v-1', datetime('now'), datetime('now'), '{"model": "gpt-4o", "mode": null}', 1500),
+                   ('s-2', 'ws-1', 'v-2', datetime('now'), datetime('now'), '{"model": "gpt-4o", "mode": null}', 500),
+                   ('s-3', 'ws-1', 'v-3', datetime('now'), datetime('now'), NULL, 1000),
+                   ('s-4', 'ws-1', 'v-4', datetime('now', '-30 days'), datetime('now'), NULL, 9999);
+            "#,
+        )
+        .expect("insert sessions");
+
+        let usage = get_total_token_usage(&conn, 7).expect("token usage");
+        assert_eq!(usage.total_tokens, 3000);
+        assert_eq!(usage.by_model.get("gpt-4o"), Some(&2000));
+        assert_eq!(usage.by_model.get("unknown"), Some(&1000));
+        assert!((usage.estimated_cost_usd - 0.03).abs() < 1e-9);
+
+        let usage = usage.with_cost_rate(1.0);
+        assert!((usage.estimated_cost_usd - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_get_language_activity_stats() {
+        let conn = setup_db();
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let diffs = [
+            r#"{"files":[],"extension_stats":{"rs":{"files":1,"insertions":10,"deletions":0},"md":{"files":1,"insertions":3,"deletions":1}}}"#,
+            r#"{"files":[],"extension_stats":{"rs":{"files":2,"insertions":5,"deletions":2}}}"#,
+        ];
+        for (i, diff) in diffs.iter().enumerate() {
+            conn.execute(
+                r#"
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, diff_json, created_at)
+                VALUES (?1, 'ws-1', ?1, 'msg', 'Author', datetime('now'), ?2, datetime('now'))
+                "#,
+                params![format!("c-{i}"), diff],
+            )
+            .expect("insert commit");
+        }
+
+        let stats = get_language_activity_stats(&conn, 7).expect("language stats");
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["rs"], 15);
+        assert_eq!(stats["md"], 3);
+    }
+
+    #[test]
+    fn test_get_most_active_files() {
+        let conn = setup_db();
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'app', '/app', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z'),
+                    ('ws-2', 'lib', '/lib', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z');",
+        )
+        .expect("insert workspaces");
+
+        let file = |path: &str, ins: u32, del: u32| {
+            format!(
+                r#"{{"path":"{path}","status":"modified","insertions":{ins},"deletions":{del}}}"#
+            )
+        };
+        let commits = [
+            (
+                "c-1",
+                "ws-1",
+                "-1 days",
+                vec![file("src/lib.rs", 10, 2), file("README.md", 3, 0)],
+            ),
+            (
+                "c-2",
+                "ws-1",
+                "-2 days",
+                vec![file("src/lib.rs", 4, 4), file("src/main.rs", 30, 0)],
+            ),
+            ("c-3", "ws-2", "-1 days", vec![file("src/lib.rs", 1, 0)]),
+            ("c-4", "ws-1", "-30 days", vec![file("old.rs", 100, 0)]),
+        ];
+        for (id, ws, age, files) in commits {
+            let diff = format!(r#"{{"files":[{}]}}"#, files.join(","));
+            conn.execute(
+                r#"
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, diff_json, created_at)
+                VALUES (?1, ?2, ?1, 'msg', 'Author', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?3), ?4, '2026-01-01T00:00:00Z')
+                "#,
+                params![id, ws, age, diff],
+            )
+            .expect("insert commit");
+        }
+
+        let all = get_most_active_files(&conn, None, 7, 10).expect("active files");
+        let paths: Vec<&str> = all.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "src/main.rs", "README.md"]);
+        assert_eq!(
+            all[0],
+            ActiveFileStat {
+                path: "src/lib.rs".to_string(),
+                extension: "rs".to_string(),
+                commit_count: 3,
+                additions: 15,
+                deletions: 6,
+            }
+        );
+        assert_eq!(all[2].extension, "md");
+
+        let app = get_most_active_files(&conn, Some("/app"), 7, 1).expect("active files");
+        assert_eq!(app.len(), 1);
+        assert_eq!((app[0].commit_count, app[0].additions), (2, 14));
+
+        let month = get_most_active_files(&conn, None, 60, 10).expect("active files");
+        assert_eq!(month.len(), 4);
+    }
+
+    fn insert_commits_over_days(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        // Two commits a day from 2026-04-01 to 2026-04-10
+        for day in 1..=10 {
+            for hour in [9, 17] {
+                let id = format!("c-{day:02}-{hour}");
+                conn.execute(
+                    r#"
+                    INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+                    VALUES (?1, 'ws-1', ?1, 'msg', 'Author', ?2, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+                    "#,
+                    params![id, format!("2026-04-{day:02}T{hour:02}:00:00+00:00")],
+                )
+                .expect("insert commit");
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_commits_in_range() {
+        let conn = setup_db();
+        insert_commits_over_days(&conn);
+
+        let from = "2026-04-03T17:00:00Z".parse().expect("from");
+        let to = "2026-04-05T09:00:00Z".parse().expect("to");
+        let commits = get_commits_in_range(&conn, from, to, None, 100).expect("commits");
+        let shas: Vec<&str> = commits.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas, vec!["c-05-9", "c-04-17", "c-04-9", "c-03-17"]);
+
+        let limited = get_commits_in_range(&conn, from, to, Some("/test"), 2).expect("commits");
+        assert_eq!(limited.len(), 2);
+    }
+
+    #[test]
+    fn test_get_timeline_with_filter() {
+        let conn = setup_db();
+        insert_commits_over_days(&conn);
+        conn.execute(
+            "INSERT INTO test_runs (id, workspace_id, started_at)
+             VALUES ('run-1', 'ws-1', '2026-04-04T12:00:00+00:00')",
+            [],
+        )
+        .expect("insert run");
+
+        let range = TimeRange::new(
+            Some("2026-04-03T17:00:00Z".parse().expect("start")),
+            Some("2026-04-05T09:00:00Z".parse().expect("end")),
+        );
+        let filter = TimelineFilter::new()
+            .with_workspace("/test")
+            .with_time_range(range);
+        let events = get_timeline(&conn, 50, Some(&filter)).expect("timeline");
+        let ids: Vec<&str> = events.iter().map(|e| e.event_id.as_str()).collect();
+        assert_eq!(ids, vec!["c-05-9", "c-04-17", "run-1", "c-04-9", "c-03-17"]);
+
+        let commits_only = filter.clone().with_event_types(["commit"]);
+        let events = get_timeline(&conn, 50, Some(&commits_only)).expect("timeline");
+        assert_eq!(events.len(), 4);
+        assert!(events.iter().all(|e| e.event_type == "commit"));
+
+        // Open-ended ranges only bound one side
+        let since = TimelineFilter::new().with_time_range(TimeRange::since(
+            "2026-04-10T00:00:00Z".parse().expect("start"),
+        ));
+        let events = get_timeline(&conn, 50, Some(&since)).expect("timeline");
+        assert_eq!(events.len(), 2);
+        assert_eq!(get_timeline(&conn, 50, None).expect("timeline").len(), 21);
+
+        let backwards =
+            TimelineFilter::new().with_time_range(TimeRange::new(range.end, range.start));
+        assert!(matches!(
+            get_timeline(&conn, 50, Some(&backwards)),
+            Err(QueryError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_commits_on_date() {
+        let conn = setup_db();
+        insert_commits_over_days(&conn);
+
+        let commits = get_commits_on_date(&conn, "2026-04-07").expect("commits");
+        let shas: Vec<&str> = commits.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas, vec!["c-07-17", "c-07-9"]);
+
+        assert!(
+            get_commits_on_date(&conn, "2026-04-11")
+                .expect("commits")
+                .is_empty()
+        );
+        assert!(matches!(
+            get_commits_on_date(&conn, "April 7"),
+            Err(QueryError::InvalidParameter(_))
+        ));
+
+        let events = get_timeline_on_date(&conn, "2026-04-07", 50, None).expect("timeline");
+        assert_eq!(events.len(), 2);
+        assert!(
+            events
+                .iter()
+                .all(|e| e.event_timestamp.starts_with("2026-04-07"))
+        );
+    }
+
+    #[test]
+    fn test_get_revert_commits_and_timeline() {
+        use crate::db::Database;
+
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+
+        let original_sha = "1945ab9c752534e733c38ba0109dc3b741f0a6eb";
+        let message = format!("Revert \"feat: add parser\"\n\nThis reverts commit {original_sha}.");
+        let commits = [
+            CommitRecord::new(
+                ws_id.clone(),
+                original_sha.to_string(),
+                "Dev".to_string(),
+                None,
+                "feat: add parser".to_string(),
+                "2026-04-01T09:00:00Z".parse().expect("ts"),
+            ),
+            CommitRecord::new(
+                ws_id.clone(),
+                "c460aeb7fb2d109c17e43de0ce681faec0b7374d".to_string(),
+                "Dev".to_string(),
+                None,
+                message.clone(),
+                "2026-04-02T09:00:00Z".parse().expect("ts"),
+            )
+            .with_revert(hindsight_git::commit::reverted_sha(&message)),
+        ];
+        for commit in &commits {
+            db.insert_commit(commit).expect("insert commit");
+        }
+
+        let reverts = get_revert_commits(db.connection(), Some("/test"), 10).expect("reverts");
+        assert_eq!(reverts.len(), 1);
+        assert!(reverts[0].is_revert);
+        assert_eq!(reverts[0].reverted_sha.as_deref(), Some(original_sha));
+
+        let timeline = get_timeline(db.connection(), 10, None).expect("timeline");
+        let details: Vec<serde_json::Value> = timeline
+            .iter()
+            .map(|e| {
+                serde_json::from_str(e.details_json.as_deref().expect("details")).expect("json")
+            })
+            .collect();
+        assert_eq!(details[0]["is_revert"], true);
+        assert_eq!(details[0]["reverted_sha"], original_sha);
+        assert!(details[1].get("is_revert").is_none());
+    }
+
+    #[test]
+    fn test_get_long_sessions() {
+        use crate::db::Database;
+        use chrono::Duration;
+
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+
+        let start: DateTime<Utc> = "2026-04-01T09:00:00Z".parse().expect("start");
+        let spans = [("long", 90), ("short", 5)];
+        for (name, minutes) in spans {
+            let record = CopilotSessionRecord::new(ws_id.clone(), name.to_string())
+                .with_message_span(start, start + Duration::minutes(minutes));
+            db.insert_copilot_session(&record).expect("insert session");
+        }
+        db.insert_copilot_session(&CopilotSessionRecord::new(
+            ws_id.clone(),
+            "no-messages".to_string(),
+        ))
+        .expect("insert session");
+
+        let sessions = get_long_sessions(db.connection(), 60.0, 10).expect("long sessions");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].vscode_session_id, "long");
+        assert_eq!(sessions[0].session_duration_minutes(), Some(90.0));
+
+        let duration: f64 = db
+            .connection()
+            .query_row(
+                "SELECT duration_minutes FROM copilot_sessions WHERE vscode_session_id = 'long'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("duration");
+        assert!((duration - 90.0).abs() < 1e-6);
+
+        let all = get_long_sessions(db.connection(), 0.0, 10).expect("long sessions");
+        let names: Vec<&str> = all.iter().map(|s| s.vscode_session_id.as_str()).collect();
+        assert_eq!(names, vec!["long", "short"]);
+    }
+
+    fn insert_conversation(db: &mut crate::db::Database, vscode_id: &str, turns: &[(&str, &str)]) {
+        use chrono::Duration;
+
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let session_id = db
+            .insert_copilot_session(&CopilotSessionRecord::new(ws_id, vscode_id.to_string()))
+            .expect("insert session");
+        let start: DateTime<Utc> = "2026-04-01T09:00:00Z".parse().expect("start");
+        let messages: Vec<_> = turns
+            .iter()
+            .enumerate()
+            .map(|(i, (role, content))| {
+                CopilotMessageRecord::new(
+                    session_id.clone(),
+                    role.to_string(),
+                    content.to_string(),
+                    start + Duration::seconds(i as i64),
+                )
+            })
+            .collect();
+        db.insert_copilot_messages_batch(&messages)
+            .expect("insert messages");
+    }
+
+    fn summarize_pairs(pairs: &[QAPair]) -> Vec<(&str, Option<&str>)> {
+        pairs
+            .iter()
+            .map(|p| {
+                (
+                    p.question.content.as_str(),
+                    p.answer.as_ref().map(|a| a.content.as_str()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_copilot_session_with_messages() {
+        use chrono::Duration;
+
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let session_id = db
+            .insert_copilot_session(
+                &CopilotSessionRecord::new(ws_id, "chat-7".to_string())
+                    .with_metadata(Some("gpt-4o"), Some("agent")),
+            )
+            .expect("insert session");
+        let start: DateTime<Utc> = "2026-04-01T09:00:00Z".parse().expect("start");
+        // Inserted out of order to check the messages come back sorted
+        let messages: Vec<_> = [
+            (2, "user", "thanks"),
+            (0, "user", "hi"),
+            (1, "assistant", "hello"),
+        ]
+        .iter()
+        .map(|(offset, role, content)| {
+            CopilotMessageRecord::new(
+                session_id.clone(),
+                role.to_string(),
+                content.to_string(),
+                start + Duration::seconds(*offset),
+            )
+        })
+        .collect();
+        db.insert_copilot_messages_batch(&messages)
+            .expect("insert messages");
+
+        let detail = get_copilot_session_with_messages(db.connection(), "chat-7")
+            .expect("query")
+            .expect("session");
+        assert_eq!(detail.id, session_id);
+        assert_eq!(detail.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(detail.mode.as_deref(), Some("agent"));
+        let turns: Vec<(&str, &str)> = detail
+            .messages
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![("user", "hi"), ("assistant", "hello"), ("user", "thanks")]
+        );
+
+        let by_id = get_copilot_session_with_messages(db.connection(), &session_id)
+            .expect("query")
+            .expect("session");
+        assert_eq!(by_id.vscode_session_id, "chat-7");
+
+        assert!(
+            get_copilot_session_with_messages(db.connection(), "missing")
+                .expect("query")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_get_qa_pairs_alternating() {
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        insert_conversation(
+            &mut db,
+            "chat",
+            &[
+                ("user", "q1"),
+                ("assistant", "a1"),
+                ("user", "q2"),
+                ("assistant", "a2"),
+            ],
+        );
+
+        let pairs = get_qa_pairs(db.connection(), "chat").expect("pairs");
+        assert_eq!(
+            summarize_pairs(&pairs),
+            vec![("q1", Some("a1")), ("q2", Some("a2"))]
+        );
+
+        // The internal session ID works too
+        let by_id = get_qa_pairs(db.connection(), &pairs[0].question.session_id).expect("pairs");
+        assert_eq!(by_id.len(), 2);
+    }
+
+    #[test]
+    fn test_get_variable_kind_stats() {
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let other_ws = db
+            .get_or_create_workspace("other", "/other")
+            .expect("workspace");
+        let first = db
+            .insert_copilot_session(&CopilotSessionRecord::new(ws_id.clone(), "a".to_string()))
+            .expect("insert session");
+        let second = db
+            .insert_copilot_session(&CopilotSessionRecord::new(ws_id, "b".to_string()))
+            .expect("insert session");
+        let elsewhere = db
+            .insert_copilot_session(&CopilotSessionRecord::new(other_ws, "c".to_string()))
+            .expect("insert session");
+
+        let now = Utc::now();
+        let message = |session: &str, variables: &str, age_days: i64| {
+            CopilotMessageRecord::new(
+                session.to_string(),
+                "user".to_string(),
+                "question".to_string(),
+                now - chrono::Duration::days(age_days),
+            )
+            .with_variables_json(variables.to_string())
+        };
+        let file = r#"{"kind":"file","name":"lib.rs","value":"src/lib.rs"}"#;
+        let workspace = r#"{"kind":"workspace","name":"workspace"}"#;
+        db.insert_copilot_messages_batch(&[
+            message(&first, &format!("[{file},{file}]"), 0),
+            message(&first, &format!("[{workspace}]"), 1),
+            message(&second, &format!("[{file}]"), 2),
+            message(&elsewhere, &format!("[{file},{workspace}]"), 0),
+            // Too old for the 7-day window
+            message(&second, &format!("[{workspace}]"), 30),
+            message(&second, "not json", 0),
+        ])
+        .expect("insert messages");
+
+        let stats = get_variable_kind_stats(db.connection(), 7, Some("/test")).expect("stats");
+        assert_eq!(
+            stats,
+            vec![
+                VariableKindStats {
+                    kind: "file".to_string(),
+                    usage_count: 3,
+                    distinct_sessions: 2,
+                },
+                VariableKindStats {
+                    kind: "workspace".to_string(),
+                    usage_count: 1,
+                    distinct_sessions: 1,
+                },
+            ]
+        );
+
+        let all = get_variable_kind_stats(db.connection(), 60, None).expect("stats");
+        let counts: Vec<(&str, u64)> = all
+            .iter()
+            .map(|s| (s.kind.as_str(), s.usage_count))
+            .collect();
+        assert_eq!(counts, vec![("file", 4), ("workspace", 3)]);
+    }
+
+    #[test]
+    fn test_get_messages_by_agent_and_fts() {
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let session_id = db
+            .insert_copilot_session(&CopilotSessionRecord::new(ws_id, "chat".to_string()))
+            .expect("insert session");
+
+        let start: DateTime<Utc> = "2026-04-01T09:00:00Z".parse().expect("start");
+        let message = |content: &str, offset: i64| {
+            CopilotMessageRecord::new(
+                session_id.clone(),
+                "user".to_string(),
+                content.to_string(),
+                start + chrono::Duration::seconds(offset),
+            )
+        };
+        db.insert_copilot_messages_batch(&[
+            message("where is the parser defined", 0).with_agent("@workspace"),
+            message("list the open ports", 1).with_agent("@terminal"),
+            message("explain lifetimes", 2),
+            message("find the config loader", 3).with_agent("workspace"),
+        ])
+        .expect("insert messages");
+
+        let by_agent = get_messages_by_agent(db.connection(), "@workspace", 10).expect("agent");
+        let contents: Vec<&str> = by_agent.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec!["find the config loader", "where is the parser defined"]
+        );
+        assert!(
+            by_agent
+                .iter()
+                .all(|m| m.agent.as_deref() == Some("workspace"))
+        );
+
+        let limited = get_messages_by_agent(db.connection(), "workspace", 1).expect("agent");
+        assert_eq!(limited.len(), 1);
+
+        // The agent column is indexed for full-text search
+        let hits = search_messages(db.connection(), "workspace", 10).expect("search");
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[test]
+    fn test_search_code_blocks() {
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let session_id = db
+            .insert_copilot_session(&CopilotSessionRecord::new(
+                ws_id.clone(),
+                "chat".to_string(),
+            ))
+            .expect("insert session");
+
+        let start: DateTime<Utc> = "2026-04-01T09:00:00Z".parse().expect("start");
+        let message = |content: &str, offset: i64| {
+            CopilotMessageRecord::new(
+                session_id.clone(),
+                "assistant".to_string(),
+                content.to_string(),
+                start + chrono::Duration::seconds(offset),
+            )
+        };
+        db.insert_copilot_messages_batch(&[
+            message("Try this", 0).with_code_blocks_json(
+                r#"[{"language":"rust","content":"fn main() {}"},{"content":"cargo run"}]"#
+                    .to_string(),
+            ),
+            message("Or in Python", 1).with_code_blocks_json(
+                r#"[{"language":"Python","content":"def main(): pass"}]"#.to_string(),
+            ),
+            message("No code here", 2),
+        ])
+        .expect("insert messages");
+
+        let rust = search_code_blocks(db.connection(), Some("RUST"), "", 10).expect("rust");
+        assert_eq!(rust.len(), 1);
+        assert_eq!(rust[0].content, "fn main() {}");
+        assert_eq!(rust[0].workspace_id, ws_id);
+
+        let mains = search_code_blocks(db.connection(), None, "MAIN", 10).expect("main");
+        let contents: Vec<&str> = mains.iter().map(|b| b.content.as_str()).collect();
+        assert_eq!(contents, vec!["def main(): pass", "fn main() {}"]);
+
+        let untagged = search_code_blocks(db.connection(), None, "cargo", 10).expect("cargo");
+        assert_eq!(untagged.len(), 1);
+        assert_eq!(untagged[0].language, None);
+
+        let limited = search_code_blocks(db.connection(), None, "main", 1).expect("limit");
+        assert_eq!(limited.len(), 1);
+
+        assert!(matches!(
+            search_code_blocks(db.connection(), Some(" "), "", 10),
+            Err(QueryError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_qa_pairs_unanswered_questions() {
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        insert_conversation(
+            &mut db,
+            "chat",
+            &[
+                ("system", "You are helpful"),
+                ("user", "q1"),
+                ("user", "q2"),
+                ("assistant", "a2"),
+                ("assistant", "stray"),
+                ("user", "q3"),
+            ],
+        );
+
+        let pairs = get_qa_pairs(db.connection(), "chat").expect("pairs");
+        assert_eq!(
+            summarize_pairs(&pairs),
+            vec![("q1", None), ("q2", Some("a2")), ("q3", None)]
+        );
+
+        assert!(
+            get_qa_pairs(db.connection(), "missing")
+                .expect("pairs")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_timeline_one_event_per_copilot_session() {
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let turns: Vec<(&str, &str)> = (0..10)
+            .flat_map(|_| [("user", "How do I fix this?"), ("assistant", "Try this.")])
+            .collect();
+        assert_eq!(turns.len(), 20);
+        insert_conversation(&mut db, "chat", &turns);
+
+        let events = get_timeline(db.connection(), 50, None).expect("timeline");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "copilot_session");
+        assert_eq!(events[0].summary, "How do I fix this?");
+        assert_eq!(events[0].event_timestamp, "2026-04-01T09:00:00+00:00");
+
+        let details: serde_json::Value =
+            serde_json::from_str(events[0].details_json.as_deref().expect("details"))
+                .expect("json");
+        assert_eq!(details["message_count"], 20);
+        assert_eq!(details["last_message"], "2026-04-01T09:00:19+00:00");
+    }
+
+    #[test]
+    fn test_get_recent_copilot_sessions() {
+        let mut db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        insert_conversation(
+            &mut db,
+            "chat",
+            &[
+                ("system", "You are helpful"),
+                ("user", "Explain lifetimes"),
+                ("assistant", "Lifetimes describe..."),
+            ],
+        );
+        // Sessions without messages are not listed
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        db.insert_copilot_session(&CopilotSessionRecord::new(ws_id, "empty".to_string()))
+            .expect("insert session");
+
+        let sessions = get_recent_copilot_sessions(db.connection(), 10, None).expect("sessions");
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.message_count, 3);
+        assert_eq!(session.preview.as_deref(), Some("Explain lifetimes"));
+        assert_eq!(session.first_message, "2026-04-01T09:00:00+00:00");
+        assert_eq!(session.last_message, "2026-04-01T09:00:02+00:00");
+        assert!(session.model.is_none());
+
+        db.get_or_create_workspace("other", "/other")
+            .expect("workspace");
+        let filtered =
+            get_recent_copilot_sessions(db.connection(), 10, Some("/other")).expect("sessions");
+        assert!(filtered.is_empty());
+    }
+
+    #[test]
+    fn test_extract_keywords() {
+        let keywords = extract_keywords(
+            [
+                "How do I fix the parser? The parser panics.",
+                "Parser error in the lexer",
+            ],
+            2,
+        );
+        assert_eq!(keywords, vec!["parser", "error"]);
+    }
+
+    #[test]
+    fn test_get_trending_topics() {
+        use crate::db::Database;
+        use chrono::Duration;
+
+        let db = Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+
+        // Prior period talks about databases, recent period about async
+        let now = Utc::now();
+        let sessions: [(&str, i64, &[&str]); 6] = [
+            ("old-1", 10, &["database", "migration"]),
+            ("old-2", 11, &["database"]),
+            ("old-3", 12, &["database", "async"]),
+            ("new-1", 1, &["async", "tokio"]),
+            ("new-2", 2, &["async"]),
+            ("new-3", 3, &["async", "database"]),
+        ];
+        for (name, days_ago, topics) in sessions {
+            let topics: Vec<String> = topics.iter().map(|t| t.to_string()).collect();
+            let mut record =
+                CopilotSessionRecord::new(ws_id.clone(), name.to_string()).with_topics(&topics);
+            record.created_at = now - Duration::days(days_ago);
+            db.insert_copilot_session(&record).expect("insert session");
+        }
+
+        // Outside both periods
+        let mut stale = CopilotSessionRecord::new(ws_id.clone(), "stale".to_string())
+            .with_topics(&["migration".to_string(), "migration".to_string()]);
+        stale.created_at = now - Duration::days(30);
+        db.insert_copilot_session(&stale).expect("insert session");
+
+        let trends = get_trending_topics(db.connection(), 7, 2).expect("trends");
+        let keywords: Vec<&str> = trends.iter().map(|t| t.keyword.as_str()).collect();
+        assert_eq!(keywords, vec!["async", "database"]);
 
-/// Summary of a test run
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TestRunSummary {
-    /// Database ID (UUID)
-    pub id: String,
-    /// ISO 8601 timestamp
-    pub timestamp: String,
-    /// Number of passed tests
-    pub passed: i32,
-    /// Number of failed tests
-    pub failed: i32,
-    /// Number of skipped tests
-    pub skipped: i32,
-}
+        assert_eq!(trends[0].session_count, 3);
+        assert!((trends[0].trend - 3.0).abs() < 1e-9, "async should rise");
+        assert_eq!(trends[1].session_count, 1);
+        assert!(trends[1].trend < 1.0, "database should fall");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::migrations;
+        let all = get_trending_topics(db.connection(), 7, 1).expect("trends");
+        let tokio = all.iter().find(|t| t.keyword == "tokio").expect("tokio");
+        assert_eq!(tokio.trend, 1.0);
+        let migration = all
+            .iter()
+            .find(|t| t.keyword == "migration")
+            .expect("migration");
+        assert_eq!(migration.session_count, 0);
+        assert_eq!(migration.trend, 0.0);
+    }
 
-    fn setup_db() -> Connection {
-        let conn = Connection::open_in_memory().expect("create db");
-        migrations::migrate(&conn).expect("migrate");
-        conn
+    #[test]
+    fn test_get_commits_by_language() {
+        let conn = setup_db();
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let commits = [
+            ("c-1", Some("Rust"), "-2 days"),
+            ("c-2", Some("Python"), "-1 days"),
+            ("c-3", Some("Rust"), "+0 days"),
+            ("c-4", None, "+0 days"),
+        ];
+        for (id, language, offset) in commits {
+            conn.execute(
+                r#"
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, language_hint, created_at)
+                VALUES (?1, 'ws-1', ?1, 'msg', 'Author', strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now', ?3), ?2, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+                "#,
+                params![id, language, offset],
+            )
+            .expect("insert commit");
+        }
+
+        let rust = get_commits_by_language(&conn, "rust", 10).expect("commits");
+        let shas: Vec<&str> = rust.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas, vec!["c-3", "c-1"]);
+        assert_eq!(rust[0].language_hint.as_deref(), Some("Rust"));
+
+        assert_eq!(
+            get_commits_by_language(&conn, "Rust", 1)
+                .expect("commits")
+                .len(),
+            1
+        );
+        assert!(
+            get_commits_by_language(&conn, "Go", 10)
+                .expect("commits")
+                .is_empty()
+        );
     }
 
     #[test]
-    fn test_get_timeline_empty() {
+    fn test_get_contributors() {
         let conn = setup_db();
-        let events = get_timeline(&conn, 10, None).expect("timeline");
-        assert!(events.is_empty());
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now')),
+                   ('ws-2', 'other', '/other', datetime('now'), datetime('now'));
+            INSERT INTO commits (id, workspace_id, sha, message, author, author_email, timestamp, diff_json, created_at)
+            VALUES ('c-1', 'ws-1', 'sha1', 'msg', 'Alice', 'alice@example.com', '2026-01-01T10:00:00+00:00',
+                    '{"files_changed":2,"insertions":3,"deletions":0,"files":[]}', datetime('now')),
+                   ('c-2', 'ws-1', 'sha2', 'msg', 'Alice', 'alice@example.com', '2026-01-03T10:00:00+00:00',
+                    '{"files":[{"path":"a.rs"},{"path":"b.rs"},{"path":"c.rs"}]}', datetime('now')),
+                   ('c-3', 'ws-1', 'sha3', 'msg', 'Alice', 'alice@example.com', '2026-01-04T10:00:00+00:00',
+                    '{"files_changed": 7, "truncated', datetime('now')),
+                   ('c-4', 'ws-1', 'sha4', 'msg', 'Alice', NULL, '2026-01-02T10:00:00+00:00', NULL, datetime('now')),
+                   ('c-5', 'ws-1', 'sha5', 'msg', 'Bob', NULL, '2026-01-05T10:00:00+00:00',
+                    '{"files_changed":1,"insertions":1,"deletions":1,"files":[]}', datetime('now')),
+                   ('c-6', 'ws-2', 'sha6', 'msg', 'Carol', NULL, '2026-01-06T10:00:00+00:00', NULL, datetime('now')),
+                   ('c-7', 'ws-2', 'sha7', 'msg', 'Carol', NULL, '2026-01-07T10:00:00+00:00', NULL, datetime('now'));
+            "#,
+        )
+        .expect("insert commits");
+
+        let contributors = get_contributors(&conn, Some("/test"), 10).expect("contributors");
+        let summary: Vec<(&str, Option<&str>, u64, u64)> = contributors
+            .iter()
+            .map(|c| {
+                (
+                    c.author.as_str(),
+                    c.author_email.as_deref(),
+                    c.commit_count,
+                    c.files_changed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Alice", Some("alice@example.com"), 3, 5),
+                ("Bob", None, 1, 1),
+                ("Alice", None, 1, 0),
+            ]
+        );
+        assert_eq!(contributors[0].first_commit_at, "2026-01-01T10:00:00+00:00");
+        assert_eq!(contributors[0].last_commit_at, "2026-01-04T10:00:00+00:00");
+
+        let contributors = get_contributors(&conn, None, 1).expect("contributors");
+        assert_eq!(contributors.len(), 1);
+        assert_eq!(contributors[0].author, "Alice");
     }
 
     #[test]
-    fn test_search_commits_empty_query() {
+    fn test_get_contributors_counts_files_from_stats() {
         let conn = setup_db();
-        let result = search_commits(&conn, "", 10);
-        assert!(matches!(result, Err(QueryError::InvalidParameter(_))));
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+            INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, stats_json, diff_json, created_at)
+            VALUES ('c-1', 'ws-1', 'sha1', 'msg', 'Alice', '2026-01-01T10:00:00+00:00',
+                    '{"files_changed":4,"insertions":10,"deletions":2}', NULL, datetime('now')),
+                   ('c-2', 'ws-1', 'sha2', 'msg', 'Alice', '2026-01-02T10:00:00+00:00',
+                    '{"files_changed":3,"insertions":1,"deletions":1}',
+                    '{"files_changed": 3, "truncated', datetime('now'));
+            "#,
+        )
+        .expect("insert commits");
+
+        let contributors = get_contributors(&conn, None, 10).expect("contributors");
+        assert_eq!(contributors.len(), 1);
+        assert_eq!(contributors[0].files_changed, 7);
     }
 
     #[test]
-    fn test_search_messages_empty_query() {
+    fn test_get_author_activity_heatmap() {
         let conn = setup_db();
-        let result = search_messages(&conn, "", 10);
-        assert!(matches!(result, Err(QueryError::InvalidParameter(_))));
+        conn.execute_batch(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at)
+             VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now')),
+                    ('ws-2', 'other', '/other', datetime('now'), datetime('now'));",
+        )
+        .expect("insert workspaces");
+
+        // Ages are whole weeks, so each commit keeps the current weekday and hour
+        let commits = [
+            ("c-1", "ws-1", "Alice", "alice@example.com", "-7 days"),
+            ("c-2", "ws-1", "Alice", "alice@example.com", "-14 days"),
+            ("c-3", "ws-1", "Bob", "bob@example.com", "-7 days"),
+            ("c-4", "ws-2", "Alice", "alice@example.com", "-7 days"),
+            ("c-5", "ws-1", "Alice", "alice@example.com", "-70 days"),
+        ];
+        for (id, ws, author, email, age) in commits {
+            conn.execute(
+                "INSERT INTO commits (id, workspace_id, sha, message, author, author_email, timestamp, created_at)
+                 VALUES (?1, ?2, ?1, 'msg', ?3, ?4, strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?5), datetime('now'))",
+                params![id, ws, author, email, age],
+            )
+            .expect("insert commit");
+        }
+        let (weekday, hour): (i64, i64) = conn
+            .query_row(
+                "SELECT CAST(strftime('%w', 'now') AS INTEGER), CAST(strftime('%H', 'now') AS INTEGER)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("now");
+        let (weekday, hour) = (weekday as usize, hour as usize);
+
+        let all = get_author_activity_heatmap(&conn, None, None, 30).expect("heatmap");
+        assert_eq!(all.total(), 4);
+        assert_eq!(all.counts[weekday][hour], 4);
+
+        let alice =
+            get_author_activity_heatmap(&conn, Some("Alice"), Some("/test"), 30).expect("heatmap");
+        assert_eq!(alice.total(), 2);
+        let by_email =
+            get_author_activity_heatmap(&conn, Some("bob@example.com"), None, 30).expect("heatmap");
+        assert_eq!(by_email.total(), 1);
+
+        let cells = get_author_activity_heatmap(&conn, None, None, 90)
+            .expect("heatmap")
+            .cells();
+        assert_eq!(
+            cells,
+            vec![HeatmapCell {
+                day: WEEKDAY_NAMES[weekday].to_string(),
+                hour: hour as u32,
+                count: 5,
+            }]
+        );
+        assert!(ActivityHeatmap::default().cells().is_empty());
     }
 
     #[test]
-    fn test_get_failing_tests_empty() {
+    fn test_get_daily_commit_volume_includes_empty_days() {
         let conn = setup_db();
-        let tests = get_failing_tests(&conn, 10, None, None).expect("failing tests");
-        assert!(tests.is_empty());
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let commits = [
+            ("c-1", "Alice", "+0 days"),
+            ("c-2", "Bob", "+0 days"),
+            ("c-3", "Alice", "+0 days"),
+            ("c-4", "Alice", "-2 days"),
+            ("c-5", "Alice", "-30 days"),
+        ];
+        for (id, author, offset) in commits {
+            conn.execute(
+                r#"
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+                VALUES (?1, 'ws-1', ?1, 'msg', ?2, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now', ?3), datetime('now'))
+                "#,
+                params![id, author, offset],
+            )
+            .expect("insert commit");
+        }
+
+        let volume = get_daily_commit_volume(&conn, 5, Some("/test")).expect("volume");
+        let counts: Vec<(u64, u64)> = volume
+            .iter()
+            .map(|d| (d.commit_count, d.distinct_authors))
+            .collect();
+        assert_eq!(counts, vec![(0, 0), (0, 0), (1, 1), (0, 0), (3, 2)]);
+        assert!(volume.windows(2).all(|w| w[0].date < w[1].date));
+
+        assert!(
+            get_daily_commit_volume(&conn, 0, None)
+                .expect("volume")
+                .is_empty()
+        );
     }
 
     #[test]
-    fn test_get_activity_summary() {
+    fn test_suggest_search_queries() {
         let conn = setup_db();
-        let summary = get_activity_summary(&conn, 7).expect("activity summary");
-        assert_eq!(summary.days, 7);
-        assert_eq!(summary.commits, 0);
-        assert_eq!(summary.test_runs, 0);
-        assert_eq!(summary.copilot_sessions, 0);
-        assert_eq!(summary.failing_tests, 0);
+        for (id, path) in [("ws-1", "/test"), ("ws-2", "/other")] {
+            conn.execute(
+                "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES (?1, 'test', ?2, datetime('now'), datetime('now'))",
+                params![id, path],
+            )
+            .expect("insert workspace");
+        }
+
+        let messages = [
+            ("ws-1", "Refactor parser tokenizer"),
+            ("ws-1", "Fix parser crash on empty input"),
+            ("ws-1", "Speed up the parser (again)"),
+            ("ws-1", "Add tokenizer benchmarks for v2.0"),
+            ("ws-2", "Rewrite scheduler"),
+        ];
+        for (i, (workspace, message)) in messages.iter().enumerate() {
+            conn.execute(
+                r#"
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+                VALUES (?1, ?2, ?1, ?3, 'Author', datetime('now'), datetime('now'))
+                "#,
+                params![format!("c-{i}"), workspace, message],
+            )
+            .expect("insert commit");
+        }
+
+        let suggestions = suggest_search_queries(&conn, Some("/test")).expect("suggestions");
+        assert_eq!(suggestions[..2], ["parser", "tokenizer"]);
+        assert!(suggestions.contains(&"benchmarks".to_string()));
+        assert!(!suggestions.contains(&"the".to_string()));
+        assert!(!suggestions.contains(&"add".to_string()));
+        assert!(!suggestions.contains(&"scheduler".to_string()));
+        assert!(!suggestions.iter().any(|w| w.contains('.')));
+
+        let all = suggest_search_queries(&conn, None).expect("suggestions");
+        assert!(all.contains(&"scheduler".to_string()));
     }
 
     #[test]
-    fn test_get_commit_with_tests_not_found() {
+    fn test_suggest_search_queries_matches_extract_keywords() {
         let conn = setup_db();
-        let result = get_commit_with_tests(&conn, "nonexistent").expect("query");
-        assert!(result.is_none());
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let messages = [
+            "Fix Parser crash\n\nThe parser\tpanicked on ÉTAT input",
+            "parser: handle état (again) in v2.0",
+            "Refactor tokenizer/parser boundary",
+        ];
+        for (i, message) in messages.iter().enumerate() {
+            conn.execute(
+                r#"
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, created_at)
+                VALUES (?1, 'ws-1', ?1, ?2, 'Author', datetime('now'), datetime('now'))
+                "#,
+                params![format!("c-{i}"), message],
+            )
+            .expect("insert commit");
+        }
+
+        let suggestions = suggest_search_queries(&conn, None).expect("suggestions");
+        assert_eq!(suggestions, extract_keywords(messages, SUGGESTION_LIMIT));
+        assert_eq!(suggestions[..2], ["parser", "état"]);
     }
 
     #[test]
@@ -785,6 +5364,43 @@ mod tests {
         let results = search_commits(&conn, "parser", 10).expect("search");
         assert_eq!(results.len(), 1);
         assert!(results[0].snippet.contains("parser"));
+        assert_eq!(results[0].workspace_id.as_deref(), Some("ws-1"));
+        assert_eq!(
+            results[0].source_info.as_deref(),
+            Some("abc123def456789012345678901234567890abcd")
+        );
+    }
+
+    #[test]
+    fn test_search_messages_includes_source() {
+        let conn = setup_db();
+
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+        conn.execute(
+            r#"
+            INSERT INTO copilot_sessions (id, workspace_id, vscode_session_id, created_at, updated_at)
+            VALUES ('s-1', 'ws-1', 'vscode-1', datetime('now'), datetime('now'))
+            "#,
+            [],
+        )
+        .expect("insert session");
+        conn.execute(
+            r#"
+            INSERT INTO copilot_messages (id, session_id, request_id, role, content, timestamp, created_at)
+            VALUES ('m-1', 's-1', 'req-1', 'user', 'How do I refactor the tokenizer?', datetime('now'), datetime('now'))
+            "#,
+            [],
+        )
+        .expect("insert message");
+
+        let results = search_messages(&conn, "tokenizer", 10).expect("search");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].workspace_id.as_deref(), Some("ws-1"));
+        assert_eq!(results[0].source_info.as_deref(), Some("s-1"));
     }
 
     #[test]
@@ -852,6 +5468,459 @@ mod tests {
         assert_eq!(tests[0].full_name, "test_something");
     }
 
+    fn insert_run_result(
+        conn: &Connection,
+        run: &str,
+        started_at: &str,
+        test: &str,
+        outcome: &str,
+    ) {
+        conn.execute(
+            "INSERT OR IGNORE INTO test_runs (id, workspace_id, commit_sha, started_at) VALUES (?1, 'ws-1', ?1, ?2)",
+            params![run, started_at],
+        )
+        .expect("insert test run");
+        conn.execute(
+            r#"
+            INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, created_at)
+            VALUES (?1 || '-' || ?2, ?1, 'hindsight-mcp', ?2, ?3, datetime('now'))
+            "#,
+            params![run, test, outcome],
+        )
+        .expect("insert test result");
+    }
+
+    #[test]
+    fn test_get_test_regressions() {
+        let conn = setup_db();
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let runs = [
+            ("run-1", "2026-01-01T00:00:00+00:00"),
+            ("run-2", "2026-01-02T00:00:00+00:00"),
+            ("run-3", "2026-01-03T00:00:00+00:00"),
+        ];
+        for (run, started_at) in runs {
+            insert_run_result(&conn, run, started_at, "test_stable", "passed");
+            insert_run_result(&conn, run, started_at, "test_always_failing", "failed");
+        }
+        insert_run_result(&conn, "run-1", runs[0].1, "test_regressed", "passed");
+        insert_run_result(&conn, "run-2", runs[1].1, "test_regressed", "passed");
+        insert_run_result(&conn, "run-3", runs[2].1, "test_regressed", "failed");
+
+        let regressions = get_test_regressions(&conn, 5, Some("/test")).expect("regressions");
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].test_name, "test_regressed");
+        assert_eq!(regressions[0].last_pass_at, runs[1].1);
+        assert_eq!(regressions[0].first_failure_at, runs[2].1);
+        assert_eq!(regressions[0].commit_sha.as_deref(), Some("run-3"));
+
+        // A transition older than the lookback window is not reported
+        insert_run_result(
+            &conn,
+            "run-4",
+            "2026-01-04T00:00:00+00:00",
+            "test_regressed",
+            "failed",
+        );
+        let regressions = get_test_regressions(&conn, 1, None).expect("regressions");
+        assert!(regressions.is_empty());
+    }
+
+    #[test]
+    fn test_get_regression_detection() {
+        let conn = setup_db();
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let days_ago = |days: i64| (Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+        // A 14-day window splits at 7 days ago; run-old falls outside it
+        let runs = [
+            ("run-old", days_ago(20)),
+            ("run-1", days_ago(12)),
+            ("run-2", days_ago(9)),
+            ("run-3", days_ago(5)),
+            ("run-4", days_ago(2)),
+        ];
+        let outcomes = [
+            (
+                "test_stable",
+                ["passed", "passed", "passed", "passed", "passed"],
+            ),
+            (
+                "test_regressed",
+                ["failed", "passed", "passed", "passed", "failed"],
+            ),
+            (
+                "test_twice",
+                ["passed", "passed", "passed", "failed", "timed_out"],
+            ),
+            (
+                "test_already_failing",
+                ["passed", "passed", "failed", "failed", "failed"],
+            ),
+            (
+                "test_new",
+                ["ignored", "ignored", "ignored", "failed", "failed"],
+            ),
+        ];
+        for (test, results) in outcomes {
+            for ((run, started_at), outcome) in runs.iter().zip(results) {
+                insert_run_result(&conn, run, started_at, test, outcome);
+            }
+        }
+
+        let regressions =
+            get_regression_detection(&conn, 14, Some("/test"), 10).expect("regressions");
+        let summary: Vec<(&str, &str, &str, &str)> = regressions
+            .iter()
+            .map(|r| {
+                (
+                    r.test_name.as_str(),
+                    r.failure_run_id.as_str(),
+                    r.first_failure_at.as_str(),
+                    r.last_pass_before_failure.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "test_regressed",
+                    "run-4",
+                    runs[4].1.as_str(),
+                    runs[3].1.as_str()
+                ),
+                (
+                    "test_twice",
+                    "run-3",
+                    runs[3].1.as_str(),
+                    runs[2].1.as_str()
+                ),
+            ]
+        );
+
+        let limited = get_regression_detection(&conn, 14, None, 1).expect("regressions");
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].test_name, "test_regressed");
+    }
+
+    #[test]
+    fn test_get_test_run_durations() {
+        let db = crate::db::Database::in_memory().expect("create db");
+        db.initialize().expect("init db");
+        let ws_id = db
+            .get_or_create_workspace("test", "/test")
+            .expect("workspace");
+        let other = db
+            .get_or_create_workspace("other", "/other")
+            .expect("workspace");
+
+        let start: DateTime<Utc> = "2026-04-01T10:00:00Z".parse().expect("start");
+        let run = |workspace: &str, secs: i64| {
+            crate::db::TestRunRecord::new(workspace.to_string())
+                .with_timing(start, start + chrono::Duration::seconds(secs))
+        };
+        let four = db.insert_test_run(&run(&ws_id, 4)).expect("insert run");
+        let ten = db.insert_test_run(&run(&ws_id, 10)).expect("insert run");
+        db.insert_test_run(&run(&other, 60)).expect("insert run");
+        db.insert_test_run(&crate::db::TestRunRecord::new(ws_id.clone()))
+            .expect("insert unfinished run");
+
+        let durations = get_test_run_durations(db.connection(), Some("/test"), 10).expect("query");
+        let summary: Vec<(&str, f64)> = durations
+            .iter()
+            .map(|d| (d.run_id.as_str(), d.wall_time_secs.round()))
+            .collect();
+        assert_eq!(summary, vec![(ten.as_str(), 10.0), (four.as_str(), 4.0)]);
+
+        let longest = get_test_run_durations(db.connection(), None, 1).expect("query");
+        assert_eq!(longest.len(), 1);
+        assert!((longest[0].wall_time_secs - 60.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_get_test_history_for_test() {
+        let conn = setup_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'));
+            INSERT INTO test_runs (id, workspace_id, commit_sha, started_at)
+            VALUES ('run-1', 'ws-1', 'aaa111', '2026-04-01T10:00:00Z'),
+                   ('run-2', 'ws-1', 'bbb222', '2026-04-02T10:00:00Z'),
+                   ('run-3', 'ws-1', NULL, '2026-04-03T10:00:00Z');
+            INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, duration_ms, created_at)
+            VALUES ('r-1', 'run-1', 'core', 'test_flaky', 'passed', 10, datetime('now')),
+                   ('r-2', 'run-2', 'core', 'test_flaky', 'failed', 12, datetime('now')),
+                   ('r-3', 'run-3', 'core', 'test_flaky', 'passed', 9, datetime('now')),
+                   ('r-4', 'run-3', 'other', 'test_flaky', 'failed', 1, datetime('now')),
+                   ('r-5', 'run-3', 'core', 'test_flaky_too', 'failed', 1, datetime('now'));
+            "#,
+        )
+        .expect("insert runs");
+
+        let history = get_test_history_for_test(&conn, "core", "test_flaky", 10).expect("history");
+        let summary: Vec<(&str, &str)> = history
+            .iter()
+            .map(|e| (e.run_id.as_str(), e.outcome.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("run-3", "passed"),
+                ("run-2", "failed"),
+                ("run-1", "passed")
+            ]
+        );
+        assert_eq!(history[1].commit_sha.as_deref(), Some("bbb222"));
+        assert_eq!(history[1].duration_ms, Some(12));
+        assert_eq!(history[0].commit_sha, None);
+
+        let latest = get_test_history_for_test(&conn, "core", "test_flaky", 1).expect("history");
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].run_id, "run-3");
+
+        assert!(
+            get_test_history_for_test(&conn, "core", "test_missing", 10)
+                .expect("history")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let durations: Vec<i64> = (1..=10).collect();
+        assert_eq!(percentile(&durations, 50), 5);
+        assert_eq!(percentile(&durations, 90), 9);
+        assert_eq!(percentile(&durations, 99), 10);
+        assert_eq!(percentile(&[7], 50), 7);
+    }
+
+    #[test]
+    fn test_get_test_duration_percentiles() {
+        let conn = setup_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now')),
+                   ('ws-2', 'other', '/other', datetime('now'), datetime('now'));
+            INSERT INTO test_runs (id, workspace_id, started_at)
+            VALUES ('run-other', 'ws-2', '2026-04-01T10:00:00Z');
+            INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, duration_ms, created_at)
+            VALUES ('r-other', 'run-other', 'core', 'test_other', 'passed', 9000, datetime('now'));
+            "#,
+        )
+        .expect("insert other workspace");
+
+        // 60 runs; only the latest 50 count towards the percentiles
+        for i in 0..60 {
+            conn.execute(
+                "INSERT INTO test_runs (id, workspace_id, started_at)
+                 VALUES (?1, 'ws-1', datetime('2026-04-01', ?2))",
+                params![format!("run-{i}"), format!("+{i} hours")],
+            )
+            .expect("insert run");
+            let slow = if i < 10 { 100_000 } else { 1000 + i };
+            conn.execute(
+                "INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, duration_ms, created_at)
+                 VALUES (?1, ?2, 'core', 'test_slow', 'passed', ?3, datetime('now')),
+                        (?4, ?2, 'util', 'test_fast', 'passed', 5, datetime('now')),
+                        (?5, ?2, 'util', 'test_ignored', 'ignored', NULL, datetime('now'))",
+                params![
+                    format!("slow-{i}"),
+                    format!("run-{i}"),
+                    slow,
+                    format!("fast-{i}"),
+                    format!("ignored-{i}")
+                ],
+            )
+            .expect("insert results");
+        }
+
+        let stats = get_test_duration_percentiles(&conn, None, Some("/test")).expect("stats");
+        let names: Vec<&str> = stats.iter().map(|s| s.test_name.as_str()).collect();
+        assert_eq!(names, vec!["test_slow", "test_fast"]);
+
+        let slow = &stats[0];
+        assert_eq!(slow.sample_count, 50);
+        assert_eq!(slow.p50_ms, 1034);
+        assert_eq!(slow.p90_ms, 1054);
+        assert_eq!(slow.p99_ms, 1059);
+        assert_eq!(slow.max_ms, 1059);
+        assert_eq!(stats[1].p90_ms, 5);
+
+        let util = get_test_duration_percentiles(&conn, Some("util"), Some("/test")).expect("util");
+        assert_eq!(util.len(), 1);
+        assert_eq!(util[0].suite_name, "util");
+
+        let all = get_test_duration_percentiles(&conn, None, None).expect("all");
+        assert_eq!(all[0].test_name, "test_other");
+    }
+
+    #[test]
+    fn test_get_most_failing_tests() {
+        let conn = setup_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO workspaces (id, name, path, created_at, updated_at)
+            VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now')),
+                   ('ws-2', 'other', '/other', datetime('now'), datetime('now'));
+            INSERT INTO test_runs (id, workspace_id, started_at)
+            VALUES ('run-1', 'ws-1', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-3 days')),
+                   ('run-2', 'ws-1', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-2 days')),
+                   ('run-3', 'ws-1', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-1 days')),
+                   ('run-old', 'ws-1', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-30 days')),
+                   ('run-other', 'ws-2', strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-1 days'));
+            INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, created_at)
+            VALUES ('r-1', 'run-1', 'core', 'test_flaky', 'failed', datetime('now')),
+                   ('r-2', 'run-2', 'core', 'test_flaky', 'passed', datetime('now')),
+                   ('r-3', 'run-3', 'core', 'test_flaky', 'timed_out', datetime('now')),
+                   ('r-4', 'run-1', 'core', 'test_once', 'passed', datetime('now')),
+                   ('r-5', 'run-2', 'core', 'test_once', 'ignored', datetime('now')),
+                   ('r-6', 'run-3', 'core', 'test_once', 'failed', datetime('now')),
+                   ('r-7', 'run-3', 'core', 'test_stable', 'passed', datetime('now')),
+                   ('r-8', 'run-old', 'core', 'test_stable', 'failed', datetime('now')),
+                   ('r-9', 'run-other', 'core', 'test_elsewhere', 'failed', datetime('now'));
+            "#,
+        )
+        .expect("insert runs");
+
+        let stats = get_most_failing_tests(&conn, 7, 10, Some("/test")).expect("query");
+        let summary: Vec<(&str, u64, u64)> = stats
+            .iter()
+            .map(|s| (s.test_name.as_str(), s.failure_count, s.total_runs))
+            .collect();
+        assert_eq!(summary, vec![("test_flaky", 2, 3), ("test_once", 1, 2)]);
+        assert!((stats[0].failure_rate_pct - 200.0 / 3.0).abs() < 1e-9);
+        assert!((stats[1].failure_rate_pct - 50.0).abs() < 1e-9);
+
+        // The failure 30 days ago falls inside a longer window
+        let stats = get_most_failing_tests(&conn, 60, 10, None).expect("query");
+        assert_eq!(stats.len(), 4);
+        assert_eq!(
+            get_most_failing_tests(&conn, 60, 1, None)
+                .expect("query")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_get_failure_streaks() {
+        let conn = setup_db();
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let runs: Vec<(String, String)> = (1..=6)
+            .map(|i| (format!("run-{i}"), format!("2026-01-0{i}T00:00:00+00:00")))
+            .collect();
+        for (i, (run, started_at)) in runs.iter().enumerate() {
+            // Passes once, then fails in the last five runs
+            let blocker = if i == 0 { "passed" } else { "failed" };
+            insert_run_result(&conn, run, started_at, "test_blocker", blocker);
+            // Fails only in the last two runs
+            let flaky = if i < 4 { "passed" } else { "failed" };
+            insert_run_result(&conn, run, started_at, "test_recent", flaky);
+            insert_run_result(&conn, run, started_at, "test_stable", "passed");
+        }
+
+        let streaks = get_failure_streaks(&conn, 3, Some("/test")).expect("streaks");
+        assert_eq!(streaks.len(), 1);
+        assert_eq!(streaks[0].test_name, "test_blocker");
+        assert_eq!(streaks[0].streak_length, 5);
+        assert_eq!(streaks[0].last_failed_at, runs[5].1);
+        assert_eq!(streaks[0].first_failure_commit.as_deref(), Some("run-2"));
+
+        let streaks = get_failure_streaks(&conn, 1, None).expect("streaks");
+        let lengths: Vec<(&str, u32)> = streaks
+            .iter()
+            .map(|s| (s.test_name.as_str(), s.streak_length))
+            .collect();
+        assert_eq!(lengths, vec![("test_blocker", 5), ("test_recent", 2)]);
+
+        // A passing run ends the streak
+        insert_run_result(
+            &conn,
+            "run-7",
+            "2026-01-07T00:00:00+00:00",
+            "test_blocker",
+            "passed",
+        );
+        let streaks = get_failure_streaks(&conn, 3, None).expect("streaks");
+        assert!(streaks.is_empty());
+    }
+
+    #[test]
+    fn test_get_inferred_suite_parallelism() {
+        let conn = setup_db();
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+        conn.execute(
+            "INSERT INTO test_runs (id, workspace_id, started_at) VALUES ('tr-1', 'ws-1', datetime('now'))",
+            [],
+        )
+        .expect("insert test run");
+
+        // core and db overlap (three tests at once at position 3), then cli runs alone
+        let windows = [
+            ("core", "a", 1, 4),
+            ("core", "b", 2, 6),
+            ("db", "c", 3, 5),
+            ("cli", "d", 7, 8),
+            ("cli", "e", 9, 9),
+        ];
+        for (suite, test, started, finished) in windows {
+            conn.execute(
+                r#"
+                INSERT INTO test_results (id, run_id, suite_name, test_name, outcome, started_seq, finished_seq, created_at)
+                VALUES (?1, 'tr-1', ?2, ?1, 'passed', ?3, ?4, datetime('now'))
+                "#,
+                params![test, suite, started, finished],
+            )
+            .expect("insert test result");
+        }
+
+        let groups = get_inferred_suite_parallelism(&conn, "tr-1").expect("parallelism");
+        assert_eq!(
+            groups,
+            vec![
+                ParallelGroup {
+                    suite_names: vec!["core".to_string(), "db".to_string()],
+                    max_parallelism: 3,
+                },
+                ParallelGroup {
+                    suite_names: vec!["cli".to_string()],
+                    max_parallelism: 1,
+                },
+                ParallelGroup {
+                    suite_names: vec!["cli".to_string()],
+                    max_parallelism: 1,
+                },
+            ]
+        );
+
+        assert!(
+            get_inferred_suite_parallelism(&conn, "missing")
+                .expect("parallelism")
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_resolve_workspace_filter_by_path() {
         let conn = setup_db();
@@ -915,7 +5984,12 @@ mod tests {
         .expect("insert commit");
 
         // Get timeline filtered by path (not ID) - this tests the bug fix
-        let events = get_timeline(&conn, 10, Some("/my/workspace")).expect("timeline");
+        let events = get_timeline(
+            &conn,
+            10,
+            Some(&TimelineFilter::new().with_workspace("/my/workspace")),
+        )
+        .expect("timeline");
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].event_type, "commit");
         assert_eq!(events[0].workspace_id, "ws-1");
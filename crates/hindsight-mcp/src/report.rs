@@ -0,0 +1,350 @@
+// Copyright (c) 2026 - present Nicholas D. Crosbie
+// SPDX-License-Identifier: MIT
+
+//! Development summary reports
+//!
+//! This module gathers commit, test and Copilot activity for a time period
+//! into a [`DevelopmentReport`], which can be rendered as Markdown or
+//! serialized as JSON by the `report` subcommand.
+
+use std::fmt::Write as _;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::queries::{self, QueryError};
+
+/// Number of entries shown in the author and file rankings
+const RANKING_LIMIT: usize = 5;
+
+/// Number of search terms shown in the report
+const SEARCH_TERM_LIMIT: usize = 10;
+
+// ============================================================================
+// Report Types
+// ============================================================================
+
+/// Development activity over a time period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevelopmentReport {
+    /// Number of days covered
+    pub days: u32,
+    /// Number of commits
+    pub commits: u64,
+    /// Authors with the most commits, most active first
+    pub top_authors: Vec<AuthorActivity>,
+    /// Test pass rate per day, oldest first (days without runs are omitted)
+    pub pass_rate_trend: Vec<DailyPassRate>,
+    /// Files changed in the most commits, most active first
+    pub active_files: Vec<FileActivity>,
+    /// Number of Copilot sessions
+    pub copilot_sessions: u64,
+    /// Most common topics across all commit messages
+    pub search_terms: Vec<String>,
+}
+
+/// Commit count for a single author
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorActivity {
+    /// Author name
+    pub author: String,
+    /// Number of commits
+    pub commits: u64,
+}
+
+/// Test outcomes for a single day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyPassRate {
+    /// Date in `YYYY-MM-DD` format (UTC)
+    pub date: String,
+    /// Passed tests across all runs that day
+    pub passed: u64,
+    /// Failed tests across all runs that day
+    pub failed: u64,
+    /// Percentage of passed tests among passed and failed
+    pub pass_rate: f64,
+}
+
+/// Number of commits that touched a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileActivity {
+    /// File path relative to the repository root
+    pub path: String,
+    /// Number of commits that changed the file
+    pub commits: u64,
+}
+
+// ============================================================================
+// Report Generation
+// ============================================================================
+
+/// Collect development activity for the last `days` days
+///
+/// # Errors
+///
+/// Returns an error if any of the underlying queries fail.
+pub fn build_report(conn: &Connection, days: u32) -> Result<DevelopmentReport, QueryError> {
+    let summary = queries::get_activity_summary(conn, days)?;
+    let mut search_terms = queries::suggest_search_queries(conn, None)?;
+    search_terms.truncate(SEARCH_TERM_LIMIT);
+
+    Ok(DevelopmentReport {
+        days,
+        commits: summary.commits,
+        top_authors: summary
+            .top_contributors
+            .into_iter()
+            .map(|(author, commits)| AuthorActivity { author, commits })
+            .collect(),
+        pass_rate_trend: pass_rate_trend(conn, days)?,
+        active_files: active_files(conn, days)?,
+        copilot_sessions: summary.copilot_sessions,
+        search_terms,
+    })
+}
+
+/// Generate a Markdown development summary for the last `days` days
+///
+/// # Errors
+///
+/// Returns an error if the report data cannot be queried.
+pub fn generate_report(conn: &Connection, days: u32) -> anyhow::Result<String> {
+    Ok(build_report(conn, days)?.to_markdown())
+}
+
+impl DevelopmentReport {
+    /// Render the report as a Markdown document
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Development Report\n");
+        let _ = writeln!(out, "_Last {} days_\n", self.days);
+
+        let _ = writeln!(out, "## Commits\n");
+        let _ = writeln!(out, "{} commits\n", self.commits);
+        if !self.top_authors.is_empty() {
+            let _ = writeln!(out, "| Author | Commits |\n|--------|---------|");
+            for author in &self.top_authors {
+                let _ = writeln!(out, "| {} | {} |", author.author, author.commits);
+            }
+            out.push('\n');
+        }
+
+        let _ = writeln!(out, "## Test Pass Rate\n");
+        if self.pass_rate_trend.is_empty() {
+            let _ = writeln!(out, "No test runs recorded.\n");
+        } else {
+            let _ = writeln!(
+                out,
+                "| Date | Passed | Failed | Pass Rate |\n|------|--------|--------|-----------|"
+            );
+            for day in &self.pass_rate_trend {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {:.1}% |",
+                    day.date, day.passed, day.failed, day.pass_rate
+                );
+            }
+            out.push('\n');
+        }
+
+        let _ = writeln!(out, "## Most Active Files\n");
+        if self.active_files.is_empty() {
+            let _ = writeln!(out, "No file changes recorded.\n");
+        } else {
+            for file in &self.active_files {
+                let _ = writeln!(out, "- `{}` ({} commits)", file.path, file.commits);
+            }
+            out.push('\n');
+        }
+
+        let _ = writeln!(out, "## Copilot\n");
+        let _ = writeln!(out, "{} sessions\n", self.copilot_sessions);
+
+        let _ = writeln!(out, "## Key Search Terms\n");
+        if self.search_terms.is_empty() {
+            let _ = writeln!(out, "No commit messages recorded.");
+        } else {
+            let terms: Vec<String> = self.search_terms.iter().map(|t| format!("`{t}`")).collect();
+            let _ = writeln!(out, "{}", terms.join(", "));
+        }
+
+        out
+    }
+}
+
+/// Sum passed and failed counts of test runs per day
+fn pass_rate_trend(conn: &Connection, days: u32) -> Result<Vec<DailyPassRate>, QueryError> {
+    let since = format!("-{} days", days);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT strftime('%Y-%m-%d', started_at) AS day, SUM(passed_count), SUM(failed_count)
+        FROM test_runs
+        WHERE started_at >= datetime('now', ?)
+        GROUP BY day
+        ORDER BY day
+        "#,
+    )?;
+
+    let rows = stmt.query_map([&since], |row| {
+        let passed = row.get::<_, i64>(1)?.max(0) as u64;
+        let failed = row.get::<_, i64>(2)?.max(0) as u64;
+        let total = passed + failed;
+        Ok(DailyPassRate {
+            date: row.get(0)?,
+            passed,
+            failed,
+            pass_rate: if total == 0 {
+                100.0
+            } else {
+                passed as f64 / total as f64 * 100.0
+            },
+        })
+    })?;
+
+    let mut trend = Vec::new();
+    for row in rows {
+        trend.push(row?);
+    }
+    Ok(trend)
+}
+
+/// Rank files by the number of commits whose diff touched them
+fn active_files(conn: &Connection, days: u32) -> Result<Vec<FileActivity>, QueryError> {
+    Ok(
+        queries::get_most_active_files(conn, None, days, RANKING_LIMIT)?
+            .into_iter()
+            .map(|file| FileActivity {
+                path: file.path,
+                commits: file.commit_count,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+    use rusqlite::params;
+    use similar_asserts::assert_eq;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().expect("create db");
+        migrations::migrate(&conn).expect("migrate");
+        conn
+    }
+
+    fn populate(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO workspaces (id, name, path, created_at, updated_at) VALUES ('ws-1', 'test', '/test', datetime('now'), datetime('now'))",
+            [],
+        )
+        .expect("insert workspace");
+
+        let commits = [
+            (
+                "Alice",
+                "Refactor parser",
+                r#"{"files":[{"path":"src/parser.rs"}]}"#,
+            ),
+            (
+                "Alice",
+                "Fix parser panic",
+                r#"{"files":[{"path":"src/parser.rs"},{"path":"src/lib.rs"}]}"#,
+            ),
+            (
+                "Bob",
+                "Document parser",
+                r#"{"files":[{"path":"README.md"}]}"#,
+            ),
+        ];
+        for (i, (author, message, diff)) in commits.iter().enumerate() {
+            conn.execute(
+                r#"
+                INSERT INTO commits (id, workspace_id, sha, message, author, timestamp, diff_json, created_at)
+                VALUES (?1, 'ws-1', ?1, ?2, ?3, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now'), ?4, datetime('now'))
+                "#,
+                params![format!("c-{i}"), message, author, diff],
+            )
+            .expect("insert commit");
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO test_runs (id, workspace_id, started_at, passed_count, failed_count, ignored_count)
+            VALUES ('run-1', 'ws-1', strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now'), 3, 1, 0)
+            "#,
+            [],
+        )
+        .expect("insert run");
+
+        conn.execute(
+            r#"
+            INSERT INTO copilot_sessions (id, workspace_id, vscode_session_id, created_at, updated_at)
+            VALUES ('s-1', 'ws-1', 'vs-1', datetime('now'), datetime('now'))
+            "#,
+            [],
+        )
+        .expect("insert session");
+    }
+
+    #[test]
+    fn test_build_report() {
+        let conn = setup_db();
+        populate(&conn);
+
+        let report = build_report(&conn, 7).expect("report");
+        assert_eq!(report.commits, 3);
+        assert_eq!(
+            report.top_authors,
+            vec![
+                AuthorActivity {
+                    author: "Alice".to_string(),
+                    commits: 2
+                },
+                AuthorActivity {
+                    author: "Bob".to_string(),
+                    commits: 1
+                },
+            ]
+        );
+        assert_eq!(report.active_files[0].path, "src/parser.rs");
+        assert_eq!(report.active_files[0].commits, 2);
+        assert_eq!(report.pass_rate_trend.len(), 1);
+        assert!((report.pass_rate_trend[0].pass_rate - 75.0).abs() < f64::EPSILON);
+        assert_eq!(report.copilot_sessions, 1);
+        assert_eq!(report.search_terms[0], "parser");
+    }
+
+    #[test]
+    fn test_generate_report_sections() {
+        let conn = setup_db();
+        populate(&conn);
+
+        let markdown = generate_report(&conn, 7).expect("report");
+        for heading in [
+            "# Development Report",
+            "## Commits",
+            "## Test Pass Rate",
+            "## Most Active Files",
+            "## Copilot",
+            "## Key Search Terms",
+        ] {
+            assert!(markdown.contains(heading), "missing {heading}");
+        }
+        assert!(markdown.contains("| Alice | 2 |"));
+        assert!(markdown.contains("75.0%"));
+        assert!(markdown.contains("`src/parser.rs` (2 commits)"));
+    }
+
+    #[test]
+    fn test_generate_report_empty_db() {
+        let conn = setup_db();
+
+        let markdown = generate_report(&conn, 7).expect("report");
+        assert!(markdown.contains("0 commits"));
+        assert!(markdown.contains("No test runs recorded."));
+    }
+}
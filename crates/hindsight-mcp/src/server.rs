@@ -8,21 +8,27 @@
 
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use rust_mcp_sdk::McpServer;
 use rust_mcp_sdk::mcp_server::ServerHandler;
 use rust_mcp_sdk::schema::{
-    CallToolRequestParams, CallToolResult, ListToolsResult, PaginatedRequestParams, RpcError,
-    TextContent, Tool, ToolInputSchema, schema_utils::CallToolError,
+    CallToolRequestParams, CallToolResult, ListToolsResult, PaginatedRequestParams,
+    ProgressNotificationParams, ProgressToken, RpcError, TextContent, Tool, ToolInputSchema,
+    schema_utils::CallToolError,
 };
 use serde_json::{Map, Value, json};
 use tokio::sync::Mutex;
 use tracing::{debug, error, trace};
 
-use crate::db::Database;
+use crate::db::{Database, DbError};
 use crate::handlers::{self, HandlerError};
+use crate::ingest::{ProgressCallback, ProgressEvent};
+
+/// How long cached workspace IDs are trusted before being re-queried
+pub const DEFAULT_WORKSPACE_CACHE_TTL: Duration = Duration::from_secs(60);
 
 /// Convert a JSON object into the properties format expected by ToolInputSchema.
 ///
@@ -51,6 +57,19 @@ pub struct HindsightServer {
     db_path: Option<PathBuf>,
     /// Default workspace path for queries
     workspace: Option<PathBuf>,
+    /// Cached workspace path to ID mappings
+    workspace_ids: RwLock<WorkspaceIdCache>,
+    /// How long `workspace_ids` stays valid
+    workspace_cache_ttl: Duration,
+}
+
+/// Workspace path to ID mappings, discarded as a whole once they expire
+#[derive(Debug, Default)]
+struct WorkspaceIdCache {
+    /// Workspace path to ID
+    ids: HashMap<String, String>,
+    /// When the first mapping was cached
+    fetched_at: Option<Instant>,
 }
 
 impl HindsightServer {
@@ -66,6 +85,8 @@ impl HindsightServer {
             db: Arc::new(Mutex::new(db)),
             db_path: None,
             workspace,
+            workspace_ids: RwLock::default(),
+            workspace_cache_ttl: DEFAULT_WORKSPACE_CACHE_TTL,
         }
     }
 
@@ -79,6 +100,79 @@ impl HindsightServer {
         self
     }
 
+    /// Set how long prefetched workspace IDs are cached
+    #[must_use]
+    pub fn with_workspace_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.workspace_cache_ttl = ttl;
+        self
+    }
+
+    /// Resolve a workspace path to its ID ahead of the first tool call
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the workspace lookup fails.
+    pub async fn prefetch_workspace(&self, path: &str) -> Result<(), DbError> {
+        let db = self.db.lock().await;
+        self.resolve_workspace_id(&db, path)?;
+        Ok(())
+    }
+
+    /// Resolve a workspace path to its ID, caching the mapping
+    ///
+    /// Cached mappings are reused until the cache TTL expires, after which
+    /// the whole cache is discarded and paths are looked up again. Paths
+    /// missing from the cache are looked up in `db`; paths without a
+    /// workspace record are not cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the workspace lookup fails.
+    pub fn resolve_workspace_id(
+        &self,
+        db: &Database,
+        path: &str,
+    ) -> Result<Option<String>, DbError> {
+        if let Some(id) = self.cached_workspace_id(path) {
+            return Ok(Some(id));
+        }
+
+        let id = db.get_workspace_id(path)?;
+        if let Some(id) = &id {
+            let mut cache = self
+                .workspace_ids
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            if !self.workspace_cache_fresh(&cache) {
+                *cache = WorkspaceIdCache::default();
+            }
+            debug!(path, id = %id, "Cached workspace ID");
+            cache.ids.insert(path.to_string(), id.clone());
+            cache.fetched_at.get_or_insert_with(Instant::now);
+        }
+        Ok(id)
+    }
+
+    /// Get the cached ID for a workspace path, if it hasn't expired
+    #[must_use]
+    pub fn cached_workspace_id(&self, path: &str) -> Option<String> {
+        let cache = self
+            .workspace_ids
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !self.workspace_cache_fresh(&cache) {
+            return None;
+        }
+        cache.ids.get(path).cloned()
+    }
+
+    /// Whether the workspace cache was populated within the TTL
+    fn workspace_cache_fresh(&self, cache: &WorkspaceIdCache) -> bool {
+        cache
+            .fetched_at
+            .is_some_and(|fetched| fetched.elapsed() < self.workspace_cache_ttl)
+    }
+
     /// Get access to the database (async, requires await)
     pub async fn db(&self) -> tokio::sync::MutexGuard<'_, Database> {
         self.db.lock().await
@@ -105,7 +199,26 @@ impl HindsightServer {
             Self::failing_tests_tool(),
             Self::activity_summary_tool(),
             Self::commit_details_tool(),
+            Self::diff_tool(),
             Self::ingest_tool(),
+            Self::copilot_stats_tool(),
+            Self::trending_topics_tool(),
+            Self::session_detail_tool(),
+            Self::regressions_tool(),
+            Self::failure_streaks_tool(),
+            Self::flaky_tests_tool(),
+            Self::slow_tests_tool(),
+            Self::suite_parallelism_tool(),
+            Self::commit_volume_tool(),
+            Self::authors_tool(),
+            Self::heatmap_tool(),
+            Self::tags_tool(),
+            Self::context_usage_tool(),
+            Self::annotate_tool(),
+            Self::query_tool(),
+            Self::suggest_queries_tool(),
+            Self::workspace_list_tool(),
+            Self::workspaces_tool(),
         ]
     }
 
@@ -114,7 +227,7 @@ impl HindsightServer {
             name: "hindsight_timeline".into(),
             description: Some(
                 "Get a chronological view of development activity including commits, \
-                 test runs, and Copilot sessions."
+                 test runs, and Copilot sessions (one event per session)."
                     .into(),
             ),
             input_schema: ToolInputSchema::new(
@@ -128,6 +241,10 @@ impl HindsightServer {
                     "workspace": {
                         "type": "string",
                         "description": "Filter by workspace path (optional)"
+                    },
+                    "date": {
+                        "type": "string",
+                        "description": "Only show events on this day, YYYY-MM-DD (optional)"
                     }
                 }))),
                 None,
@@ -214,7 +331,10 @@ impl HindsightServer {
     fn activity_summary_tool() -> Tool {
         Tool {
             name: "hindsight_activity_summary".into(),
-            description: Some("Get aggregate activity statistics for a time period.".into()),
+            description: Some(
+                "Get aggregate activity statistics for a time period, including the top contributors by commit count."
+                    .into(),
+            ),
             input_schema: ToolInputSchema::new(
                 vec![],
                 Some(make_properties(json!({
@@ -247,7 +367,7 @@ impl HindsightServer {
                 Some(make_properties(json!({
                     "sha": {
                         "type": "string",
-                        "description": "Full or partial commit SHA"
+                        "description": "Full or partial commit SHA (at least 4 characters)"
                     }
                 }))),
                 None,
@@ -261,12 +381,39 @@ impl HindsightServer {
         }
     }
 
+    fn diff_tool() -> Tool {
+        Tool {
+            name: "hindsight_diff".into(),
+            description: Some(
+                "Get the full diff of a specific commit, with the patch of every changed file."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec!["sha".into()],
+                Some(make_properties(json!({
+                    "sha": {
+                        "type": "string",
+                        "description": "Full or partial commit SHA (at least 4 characters)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Commit Diff".into()),
+        }
+    }
+
     fn ingest_tool() -> Tool {
         Tool {
             name: "hindsight_ingest".into(),
             description: Some(
-                "Trigger data ingestion from sources (git, copilot). \
-                 Note: Test ingestion requires nextest output and should be done via CLI."
+                "Trigger data ingestion from sources (git, copilot, tests). \
+                 Test ingestion reads nextest libtest-json output passed as nextest_json. \
+                 Progress is reported when the request includes a progress token."
                     .into(),
             ),
             input_schema: ToolInputSchema::new(
@@ -274,9 +421,9 @@ impl HindsightServer {
                 Some(make_properties(json!({
                     "source": {
                         "type": "string",
-                        "enum": ["git", "copilot", "all"],
+                        "enum": ["git", "copilot", "tests", "all"],
                         "default": "all",
-                        "description": "Source to ingest from (git, copilot, or all)"
+                        "description": "Source to ingest from (git, copilot, tests, or all)"
                     },
                     "workspace": {
                         "type": "string",
@@ -290,6 +437,15 @@ impl HindsightServer {
                     "limit": {
                         "type": "integer",
                         "description": "Max items to ingest (optional)"
+                    },
+                    "include_diffs": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Store per-file diff statistics with each commit"
+                    },
+                    "nextest_json": {
+                        "type": "string",
+                        "description": "Nextest libtest-json output (required for the tests source)"
                     }
                 }))),
                 None,
@@ -302,133 +458,1024 @@ impl HindsightServer {
             title: Some("Ingest Data".into()),
         }
     }
-}
 
-/// ServerHandler implementation for the MCP protocol
-#[async_trait]
-impl ServerHandler for HindsightServer {
-    /// Handle requests to list available tools
-    async fn handle_list_tools_request(
-        &self,
-        _params: Option<PaginatedRequestParams>,
-        _runtime: Arc<dyn McpServer>,
-    ) -> Result<ListToolsResult, RpcError> {
-        let tools = Self::build_tools();
-        debug!(tool_count = tools.len(), "Listing available tools");
-        trace!(tools = ?tools.iter().map(|t| &t.name).collect::<Vec<_>>(), "Tool names");
-        Ok(ListToolsResult {
-            tools,
+    fn copilot_stats_tool() -> Tool {
+        Tool {
+            name: "hindsight_copilot_stats".into(),
+            description: Some(
+                "Get estimated Copilot token usage and API cost for a time period, \
+                 broken down by model."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "days": {
+                        "type": "integer",
+                        "default": 7,
+                        "description": "Number of days to summarize"
+                    },
+                    "cost_per_1k_tokens": {
+                        "type": "number",
+                        "default": crate::queries::DEFAULT_COST_PER_1K_TOKENS,
+                        "description": "Cost per 1,000 tokens in USD used for the estimate"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
             meta: None,
-            next_cursor: None,
-        })
+            output_schema: None,
+            title: Some("Copilot Token Usage".into()),
+        }
     }
 
-    /// Handle requests to call a specific tool
-    async fn handle_call_tool_request(
-        &self,
-        params: CallToolRequestParams,
-        _runtime: Arc<dyn McpServer>,
-    ) -> Result<CallToolResult, CallToolError> {
-        debug!(tool = %params.name, "Calling tool");
-        trace!(tool = %params.name, args = ?params.arguments, "Tool arguments");
-
-        let args = params.arguments;
-
-        let result: Result<Value, HandlerError> = match params.name.as_str() {
-            "hindsight_timeline" => {
-                let db = self.db.lock().await;
-                let workspace = self.workspace.clone();
-                handlers::handle_timeline(&db, args, workspace.as_ref())
-                    .map(|events| serde_json::to_value(events).unwrap_or_default())
-            }
-            "hindsight_search" => {
-                let db = self.db.lock().await;
-                handlers::handle_search(&db, args)
-                    .map(|results| serde_json::to_value(results).unwrap_or_default())
-            }
-            "hindsight_failing_tests" => {
-                let db = self.db.lock().await;
-                let workspace = self.workspace.clone();
-                handlers::handle_failing_tests(&db, args, workspace.as_ref())
-                    .map(|tests| serde_json::to_value(tests).unwrap_or_default())
-            }
-            "hindsight_activity_summary" => {
-                let db = self.db.lock().await;
-                handlers::handle_activity_summary(&db, args)
-                    .map(|summary| serde_json::to_value(summary).unwrap_or_default())
-            }
-            "hindsight_commit_details" => {
-                let db = self.db.lock().await;
-                handlers::handle_commit_details(&db, args)
-                    .map(|commit| serde_json::to_value(commit).unwrap_or_default())
-            }
-            "hindsight_ingest" => {
-                // Ingest requires ownership of database, so we open a new connection
-                // This is safe because SQLite handles concurrent access
-                match self.open_db_for_ingest() {
-                    Some(db) => handlers::handle_ingest(db, args)
-                        .map(|response| serde_json::to_value(response).unwrap_or_default()),
-                    None => Err(HandlerError::InvalidInput(
-                        "Cannot perform ingestion: database path not configured. \
-                         Use --database flag when starting the server."
-                            .to_string(),
-                    )),
-                }
-            }
-            _ => return Err(CallToolError::unknown_tool(&params.name)),
-        };
-
-        match result {
-            Ok(value) => {
-                let json_str = serde_json::to_string_pretty(&value).unwrap_or_default();
-                debug!(tool = %params.name, response_len = json_str.len(), "Tool completed successfully");
-                trace!(tool = %params.name, response = %json_str, "Tool response");
-                Ok(CallToolResult::text_content(vec![TextContent::new(
-                    json_str, None, None,
-                )]))
-            }
-            Err(e) => {
-                error!(error = %e, tool = %params.name, "Tool handler error");
-                // Return error as content rather than failing the request
-                // This allows the LLM to see and potentially handle the error
-                Ok(CallToolResult::text_content(vec![TextContent::new(
-                    format!("Error: {}", e),
-                    None,
-                    None,
-                )]))
-            }
+    fn trending_topics_tool() -> Tool {
+        Tool {
+            name: "hindsight_trending_topics".into(),
+            description: Some(
+                "Find Copilot chat topics that are coming up more or less often, comparing \
+                 the last N days with the N days before. A trend above 1 means rising."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "days": {
+                        "type": "integer",
+                        "default": 7,
+                        "description": "Length of each comparison period in days"
+                    },
+                    "min_sessions": {
+                        "type": "integer",
+                        "default": 2,
+                        "description": "Minimum sessions mentioning a topic across both periods"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Trending Copilot Topics".into()),
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    fn create_test_server() -> HindsightServer {
-        let db = Database::in_memory().expect("Failed to create in-memory database");
-        HindsightServer::new(db, None)
+    fn session_detail_tool() -> Tool {
+        Tool {
+            name: "hindsight_session_detail".into(),
+            description: Some(
+                "Show a Copilot chat session as question-answer turns. Questions that got no \
+                 reply have a null answer. Set messages to get the full message history with \
+                 the session's model and mode instead."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec!["session_id".into()],
+                Some(make_properties(json!({
+                    "session_id": {
+                        "type": "string",
+                        "description": "Copilot session ID or original VS Code session ID"
+                    },
+                    "messages": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Return every message in order instead of question-answer turns"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Copilot Session Detail".into()),
+        }
     }
-
-    #[test]
-    fn test_server_creation() {
-        let server = create_test_server();
-        assert!(server.workspace().is_none());
+    fn regressions_tool() -> Tool {
+        Tool {
+            name: "hindsight_regressions".into(),
+            description: Some(
+                "Find tests that started failing after previously passing, with the last \
+                 passing run and the commit of the first failure. Set window_days to \
+                 instead report tests that passed throughout the first half of that many \
+                 days and failed in the second half."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "lookback_runs": {
+                        "type": "integer",
+                        "default": 5,
+                        "description": "Number of recent runs per test to inspect for failures"
+                    },
+                    "window_days": {
+                        "type": "integer",
+                        "description": "Compare the two halves of this many days instead (optional)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "default": 20,
+                        "description": "Maximum number of regressions to return with window_days"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Test Regressions".into()),
+        }
     }
 
-    #[test]
-    fn test_server_with_workspace() {
-        let db = Database::in_memory().expect("Failed to create in-memory database");
-        let workspace = PathBuf::from("/test/workspace");
-        let server = HindsightServer::new(db, Some(workspace.clone()));
-
-        assert_eq!(server.workspace(), Some(&workspace));
+    fn failure_streaks_tool() -> Tool {
+        Tool {
+            name: "hindsight_failure_streaks".into(),
+            description: Some(
+                "Find persistent blockers: tests that have failed in several consecutive \
+                 runs, with the commit where the streak began."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "min_streak": {
+                        "type": "integer",
+                        "default": 3,
+                        "description": "Minimum number of consecutive failing runs"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Failure Streaks".into()),
+        }
+    }
+
+    fn flaky_tests_tool() -> Tool {
+        Tool {
+            name: "hindsight_flaky_tests".into(),
+            description: Some(
+                "Find the tests that fail most often over a period, with their failure \
+                 count, number of runs, and failure rate."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "days": {
+                        "type": "integer",
+                        "default": 30,
+                        "description": "Number of days to look back"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "default": 20,
+                        "description": "Maximum number of tests to return"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Flaky Tests".into()),
+        }
+    }
+
+    fn slow_tests_tool() -> Tool {
+        Tool {
+            name: "hindsight_slow_tests".into(),
+            description: Some(
+                "Find the slowest tests by their 90th percentile duration over recent runs, \
+                 with median, 99th percentile and maximum durations."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "suite": {
+                        "type": "string",
+                        "description": "Only include tests from this suite (optional)"
+                    },
+                    "top_n": {
+                        "type": "integer",
+                        "default": 20,
+                        "description": "Maximum number of tests to return"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Slow Tests".into()),
+        }
+    }
+
+    fn context_usage_tool() -> Tool {
+        Tool {
+            name: "hindsight_context_usage".into(),
+            description: Some(
+                "See what context is attached to Copilot chats: counts of each variable kind \
+                 (files, workspace, prompt files) and how many sessions used it."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "days": {
+                        "type": "integer",
+                        "default": 30,
+                        "description": "Number of days to look back"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Context Usage".into()),
+        }
+    }
+
+    fn annotate_tool() -> Tool {
+        Tool {
+            name: "hindsight_annotate".into(),
+            description: Some(
+                "Record an external event (deploy, incident, customer feedback) on the \
+                 workspace timeline so it shows up next to commits and test runs."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec!["event_type".into(), "title".into()],
+                Some(make_properties(json!({
+                    "event_type": {
+                        "type": "string",
+                        "description": "Kind of event, e.g. 'deploy' or 'incident'"
+                    },
+                    "title": {
+                        "type": "string",
+                        "description": "Short title shown in the timeline"
+                    },
+                    "description": {
+                        "type": "string",
+                        "description": "Longer description (optional)"
+                    },
+                    "event_at": {
+                        "type": "string",
+                        "description": "When the event happened, RFC 3339 (default: now)"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Workspace path (default: the server's workspace)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Annotate Timeline".into()),
+        }
+    }
+
+    fn suite_parallelism_tool() -> Tool {
+        Tool {
+            name: "hindsight_suite_parallelism".into(),
+            description: Some(
+                "Infer which test suites ran concurrently during a test run, based on \
+                 when each test started and finished."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec!["run_id".into()],
+                Some(make_properties(json!({
+                    "run_id": {
+                        "type": "string",
+                        "description": "Test run ID"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Suite Parallelism".into()),
+        }
+    }
+
+    fn commit_volume_tool() -> Tool {
+        Tool {
+            name: "hindsight_commit_volume".into(),
+            description: Some(
+                "Get the number of commits per day, including days without commits, \
+                 with a sparkline of the volume over time."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "days": {
+                        "type": "integer",
+                        "default": 30,
+                        "description": "Number of days to include"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Commit Volume".into()),
+        }
+    }
+
+    fn authors_tool() -> Tool {
+        Tool {
+            name: "hindsight_authors".into(),
+            description: Some(
+                "List the contributors with the most commits, with their first and latest \
+                 commit times and the number of files they changed."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "default": 20,
+                        "description": "Maximum number of authors to return"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Authors".into()),
+        }
+    }
+
+    fn heatmap_tool() -> Tool {
+        Tool {
+            name: "hindsight_heatmap".into(),
+            description: Some(
+                "Count commits by day of week and hour of day (UTC), returned as day, hour \
+                 and count for each hour with commits. Use it to describe when someone \
+                 usually commits."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "author": {
+                        "type": "string",
+                        "description": "Author name or email (optional, defaults to all authors)"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    },
+                    "days": {
+                        "type": "integer",
+                        "default": 90,
+                        "description": "Number of days to look back"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Commit Heatmap".into()),
+        }
+    }
+
+    fn tags_tool() -> Tool {
+        Tool {
+            name: "hindsight_tags".into(),
+            description: Some(
+                "List git tags, most recent first, with the tagged commit and, for annotated \
+                 tags, the tagger and message. Useful for finding when versions were released."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "default": 20,
+                        "description": "Maximum number of tags to return"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Tags".into()),
+        }
+    }
+
+    fn query_tool() -> Tool {
+        Tool {
+            name: "hindsight_query".into(),
+            description: Some(
+                "Run a read-only SQL SELECT against the hindsight SQLite database. Only \
+                 SELECT statements are accepted; never attempt INSERT, UPDATE, DELETE, DROP \
+                 or other destructive SQL. Results are limited to 1000 rows and 5 seconds."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec!["sql".into()],
+                Some(make_properties(json!({
+                    "sql": {
+                        "type": "string",
+                        "description": "SELECT statement to run"
+                    },
+                    "params": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Positional parameters for ?1, ?2, ... (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("SQL Query".into()),
+        }
+    }
+
+    fn suggest_queries_tool() -> Tool {
+        Tool {
+            name: "hindsight_suggest_queries".into(),
+            description: Some(
+                "List the most common topics in the workspace's development history, taken \
+                 from commit messages. Use these terms as queries for hindsight_search."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "workspace": {
+                        "type": "string",
+                        "description": "Filter by workspace (optional)"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Suggest Queries".into()),
+        }
+    }
+
+    fn workspace_list_tool() -> Tool {
+        Tool {
+            name: "hindsight_workspace_list".into(),
+            description: Some(
+                "List all known workspaces with their IDs, paths, and commit and Copilot \
+                 session counts. Use a path or ID from here as the workspace filter of other tools."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(vec![], Some(HashMap::new()), None),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Workspace List".into()),
+        }
+    }
+
+    fn workspaces_tool() -> Tool {
+        Tool {
+            name: "hindsight_workspaces".into(),
+            description: Some(
+                "List the workspaces with recent commits, test runs or Copilot sessions, most \
+                 recently active first, with when each kind of activity last happened."
+                    .into(),
+            ),
+            input_schema: ToolInputSchema::new(
+                vec![],
+                Some(make_properties(json!({
+                    "days": {
+                        "type": "integer",
+                        "default": 7,
+                        "description": "Number of days to look back"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "default": 20,
+                        "description": "Maximum number of workspaces to return"
+                    }
+                }))),
+                None,
+            ),
+            annotations: None,
+            execution: None,
+            icons: vec![],
+            meta: None,
+            output_schema: None,
+            title: Some("Active Workspaces".into()),
+        }
+    }
+}
+
+/// ServerHandler implementation for the MCP protocol
+#[async_trait]
+impl ServerHandler for HindsightServer {
+    /// Handle requests to list available tools
+    async fn handle_list_tools_request(
+        &self,
+        _params: Option<PaginatedRequestParams>,
+        _runtime: Arc<dyn McpServer>,
+    ) -> Result<ListToolsResult, RpcError> {
+        let tools = Self::build_tools();
+        debug!(tool_count = tools.len(), "Listing available tools");
+        trace!(tools = ?tools.iter().map(|t| &t.name).collect::<Vec<_>>(), "Tool names");
+        Ok(ListToolsResult {
+            tools,
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    /// Handle requests to call a specific tool
+    async fn handle_call_tool_request(
+        &self,
+        params: CallToolRequestParams,
+        runtime: Arc<dyn McpServer>,
+    ) -> Result<CallToolResult, CallToolError> {
+        debug!(tool = %params.name, "Calling tool");
+        trace!(tool = %params.name, args = ?params.arguments, "Tool arguments");
+
+        let args = params.arguments;
+        let progress_token = params.meta.and_then(|meta| meta.progress_token);
+
+        let result: Result<Value, HandlerError> = match params.name.as_str() {
+            "hindsight_timeline" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_timeline(&db, args, workspace.as_ref())
+                    .map(|events| serde_json::to_value(events).unwrap_or_default())
+            }
+            "hindsight_search" => {
+                let db = self.db.lock().await;
+                handlers::handle_search(&db, args)
+                    .map(|results| serde_json::to_value(results).unwrap_or_default())
+            }
+            "hindsight_failing_tests" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_failing_tests(&db, args, workspace.as_ref())
+                    .map(|tests| serde_json::to_value(tests).unwrap_or_default())
+            }
+            "hindsight_activity_summary" => {
+                let db = self.db.lock().await;
+                handlers::handle_activity_summary(&db, args)
+                    .map(|summary| serde_json::to_value(summary).unwrap_or_default())
+            }
+            "hindsight_commit_details" => {
+                let db = self.db.lock().await;
+                handlers::handle_commit_details(&db, args)
+                    .map(|commit| serde_json::to_value(commit).unwrap_or_default())
+            }
+            "hindsight_diff" => {
+                let db = self.db.lock().await;
+                handlers::handle_diff(&db, args)
+                    .map(|diff| serde_json::to_value(diff).unwrap_or_default())
+            }
+            "hindsight_copilot_stats" => {
+                let db = self.db.lock().await;
+                handlers::handle_copilot_stats(&db, args)
+                    .map(|stats| serde_json::to_value(stats).unwrap_or_default())
+            }
+            "hindsight_trending_topics" => {
+                let db = self.db.lock().await;
+                handlers::handle_trending_topics(&db, args)
+                    .map(|trends| serde_json::to_value(trends).unwrap_or_default())
+            }
+            "hindsight_session_detail" => {
+                let db = self.db.lock().await;
+                handlers::handle_session_detail(&db, args)
+                    .map(|detail| serde_json::to_value(detail).unwrap_or_default())
+            }
+            "hindsight_regressions" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_regressions(&db, args, workspace.as_ref())
+                    .map(|regressions| serde_json::to_value(regressions).unwrap_or_default())
+            }
+            "hindsight_failure_streaks" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_failure_streaks(&db, args, workspace.as_ref())
+                    .map(|streaks| serde_json::to_value(streaks).unwrap_or_default())
+            }
+            "hindsight_flaky_tests" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_flaky_tests(&db, args, workspace.as_ref())
+                    .map(|stats| serde_json::to_value(stats).unwrap_or_default())
+            }
+            "hindsight_slow_tests" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_slow_tests(&db, args, workspace.as_ref())
+                    .map(|stats| serde_json::to_value(stats).unwrap_or_default())
+            }
+            "hindsight_context_usage" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_context_usage(&db, args, workspace.as_ref())
+                    .map(|stats| serde_json::to_value(stats).unwrap_or_default())
+            }
+            "hindsight_annotate" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_annotate(&db, args, workspace.as_ref(), |path| {
+                    self.resolve_workspace_id(&db, path)
+                })
+                .map(|response| serde_json::to_value(response).unwrap_or_default())
+            }
+            "hindsight_suite_parallelism" => {
+                let db = self.db.lock().await;
+                handlers::handle_suite_parallelism(&db, args)
+                    .map(|groups| serde_json::to_value(groups).unwrap_or_default())
+            }
+            "hindsight_commit_volume" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_commit_volume(&db, args, workspace.as_ref())
+                    .map(|response| serde_json::to_value(response).unwrap_or_default())
+            }
+            "hindsight_authors" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_authors(&db, args, workspace.as_ref())
+                    .map(|contributors| serde_json::to_value(contributors).unwrap_or_default())
+            }
+            "hindsight_heatmap" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_heatmap(&db, args, workspace.as_ref())
+                    .map(|cells| serde_json::to_value(cells).unwrap_or_default())
+            }
+            "hindsight_tags" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_tags(&db, args, workspace.as_ref())
+                    .map(|tags| serde_json::to_value(tags).unwrap_or_default())
+            }
+            "hindsight_query" => {
+                let db = self.db.lock().await;
+                handlers::handle_query(&db, args)
+                    .map(|rows| serde_json::to_value(rows).unwrap_or_default())
+            }
+            "hindsight_suggest_queries" => {
+                let db = self.db.lock().await;
+                let workspace = self.workspace.clone();
+                handlers::handle_suggest_queries(&db, args, workspace.as_ref())
+                    .map(|terms| serde_json::to_value(terms).unwrap_or_default())
+            }
+            "hindsight_workspace_list" => {
+                let db = self.db.lock().await;
+                handlers::handle_workspace_list(&db)
+            }
+            "hindsight_workspaces" => {
+                let db = self.db.lock().await;
+                handlers::handle_workspaces(&db, args)
+                    .map(|workspaces| serde_json::to_value(workspaces).unwrap_or_default())
+            }
+            "hindsight_ingest" => {
+                // Ingest requires ownership of database, so we open a new connection
+                // This is safe because SQLite handles concurrent access
+                match self.open_db_for_ingest() {
+                    Some(db) => run_ingest(db, args, progress_token, runtime)
+                        .await
+                        .map(|response| serde_json::to_value(response).unwrap_or_default()),
+                    None => Err(HandlerError::InvalidInput(
+                        "Cannot perform ingestion: database path not configured. \
+                         Use --database flag when starting the server."
+                            .to_string(),
+                    )),
+                }
+            }
+            _ => return Err(CallToolError::unknown_tool(&params.name)),
+        };
+
+        match result {
+            Ok(value) => {
+                let json_str = serde_json::to_string_pretty(&value).unwrap_or_default();
+                debug!(tool = %params.name, response_len = json_str.len(), "Tool completed successfully");
+                trace!(tool = %params.name, response = %json_str, "Tool response");
+                Ok(CallToolResult::text_content(vec![TextContent::new(
+                    json_str, None, None,
+                )]))
+            }
+            Err(e) => {
+                error!(error = %e, tool = %params.name, "Tool handler error");
+                // Return error as content rather than failing the request
+                // This allows the LLM to see and potentially handle the error
+                let body = serde_json::to_string_pretty(&e.to_mcp_error()).unwrap_or_default();
+                Ok(CallToolResult::text_content(vec![TextContent::new(
+                    body, None, None,
+                )]))
+            }
+        }
+    }
+}
+
+/// Run the ingest tool off the async runtime, forwarding progress to the client
+///
+/// Progress notifications are only sent when the caller supplied a progress
+/// token. Each ingestion event advances the progress count by one.
+async fn run_ingest(
+    db: Database,
+    args: Option<Map<String, Value>>,
+    progress_token: Option<ProgressToken>,
+    runtime: Arc<dyn McpServer>,
+) -> Result<handlers::IngestResponse, HandlerError> {
+    let Some(token) = progress_token else {
+        return spawn_ingest(db, args, None).await;
+    };
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<ProgressEvent>();
+    let forwarder = tokio::spawn(async move {
+        let mut count = 0.0;
+        while let Some(event) = rx.recv().await {
+            count += 1.0;
+            let params = ProgressNotificationParams {
+                message: Some(progress_message(&event)),
+                meta: None,
+                progress: count,
+                progress_token: token.clone(),
+                total: None,
+            };
+            if let Err(e) = runtime.notify_progress(params).await {
+                debug!(error = %e, "Failed to send progress notification");
+            }
+        }
+    });
+
+    let callback: ProgressCallback = Box::new(move |event| {
+        let _ = tx.send(event.clone());
+    });
+    let result = spawn_ingest(db, args, Some(callback)).await;
+    // The sender is dropped with the ingestor, so the forwarder drains and exits
+    let _ = forwarder.await;
+    result
+}
+
+/// Run the ingest handler on a blocking thread
+async fn spawn_ingest(
+    db: Database,
+    args: Option<Map<String, Value>>,
+    on_progress: Option<ProgressCallback>,
+) -> Result<handlers::IngestResponse, HandlerError> {
+    tokio::task::spawn_blocking(move || {
+        handlers::handle_ingest_with_progress(db, args, on_progress)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        Err(HandlerError::Internal(format!(
+            "Ingestion task failed: {e}"
+        )))
+    })
+}
+
+/// Describe an ingestion event for a progress notification
+fn progress_message(event: &ProgressEvent) -> String {
+    match event {
+        ProgressEvent::Started {
+            source,
+            total_items: Some(total),
+        } => format!("{source}: starting ({total} items)"),
+        ProgressEvent::Started { source, .. } => format!("{source}: starting"),
+        ProgressEvent::Progress {
+            source,
+            processed,
+            total: Some(total),
+        } => format!("{source}: {processed}/{total}"),
+        ProgressEvent::Progress {
+            source, processed, ..
+        } => format!("{source}: {processed} processed"),
+        ProgressEvent::Warning { source, message } => format!("{source}: {message}"),
+        ProgressEvent::Completed { source, stats } => {
+            format!("{source}: done ({} items)", stats.total_items())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_server() -> HindsightServer {
+        let db = Database::in_memory().expect("Failed to create in-memory database");
+        HindsightServer::new(db, None)
+    }
+
+    #[test]
+    fn test_server_creation() {
+        let server = create_test_server();
+        assert!(server.workspace().is_none());
+    }
+
+    #[test]
+    fn test_server_with_workspace() {
+        let db = Database::in_memory().expect("Failed to create in-memory database");
+        let workspace = PathBuf::from("/test/workspace");
+        let server = HindsightServer::new(db, Some(workspace.clone()));
+
+        assert_eq!(server.workspace(), Some(&workspace));
+    }
+
+    fn create_server_with_workspace(path: &str) -> HindsightServer {
+        let db = Database::in_memory().expect("Failed to create in-memory database");
+        db.initialize().expect("Failed to initialize database");
+        db.get_or_create_workspace("project", path)
+            .expect("Failed to create workspace");
+        HindsightServer::new(db, None)
+    }
+
+    /// When the server's workspace cache was last filled
+    fn cache_fetched_at(server: &HindsightServer) -> Option<Instant> {
+        server.workspace_ids.read().expect("cache lock").fetched_at
+    }
+
+    #[tokio::test]
+    async fn test_resolve_workspace_id_cache_hit() {
+        let server = create_server_with_workspace("/test/project");
+        assert!(server.cached_workspace_id("/test/project").is_none());
+
+        server
+            .prefetch_workspace("/test/project")
+            .await
+            .expect("prefetch");
+        let id = server
+            .cached_workspace_id("/test/project")
+            .expect("cached id");
+        let fetched_at = cache_fetched_at(&server);
+        assert!(fetched_at.is_some());
+
+        // A hit is answered from the cache without refreshing it
+        let db = server.db().await;
+        let resolved = server
+            .resolve_workspace_id(&db, "/test/project")
+            .expect("resolve");
+        assert_eq!(resolved.as_deref(), Some(id.as_str()));
+        assert_eq!(cache_fetched_at(&server), fetched_at);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_workspace_id_cache_miss() {
+        let server = create_server_with_workspace("/test/project");
+        server
+            .prefetch_workspace("/test/project")
+            .await
+            .expect("prefetch");
+
+        let db = server.db().await;
+        assert!(
+            server
+                .resolve_workspace_id(&db, "/unknown")
+                .expect("resolve")
+                .is_none()
+        );
+        assert!(server.cached_workspace_id("/unknown").is_none());
+
+        // A workspace created after the prefetch is looked up and cached
+        let other = db
+            .get_or_create_workspace("other", "/test/other")
+            .expect("workspace");
+        let resolved = server
+            .resolve_workspace_id(&db, "/test/other")
+            .expect("resolve");
+        assert_eq!(resolved.as_deref(), Some(other.as_str()));
+        assert_eq!(server.cached_workspace_id("/test/other"), Some(other));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_workspace_id_ttl_expiry() {
+        let server = create_server_with_workspace("/test/project")
+            .with_workspace_cache_ttl(Duration::from_millis(50));
+        let db = server.db().await;
+        db.get_or_create_workspace("other", "/test/other")
+            .expect("workspace");
+
+        server
+            .resolve_workspace_id(&db, "/test/project")
+            .expect("resolve");
+        server
+            .resolve_workspace_id(&db, "/test/other")
+            .expect("resolve");
+        let fetched_at = cache_fetched_at(&server).expect("cache filled");
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(server.cached_workspace_id("/test/project").is_none());
+
+        // The expired cache is replaced by a fresh one holding only the new lookup
+        let resolved = server
+            .resolve_workspace_id(&db, "/test/project")
+            .expect("resolve");
+        assert!(resolved.is_some());
+        assert!(cache_fetched_at(&server).expect("cache refilled") > fetched_at);
+        assert_eq!(server.cached_workspace_id("/test/project"), resolved);
+        assert!(server.cached_workspace_id("/test/other").is_none());
+    }
+
+    #[test]
+    fn test_progress_message() {
+        let started = ProgressEvent::Started {
+            source: "git".to_string(),
+            total_items: Some(10),
+        };
+        assert_eq!(progress_message(&started), "git: starting (10 items)");
+
+        let progress = ProgressEvent::Progress {
+            source: "copilot".to_string(),
+            processed: 3,
+            total: None,
+        };
+        assert_eq!(progress_message(&progress), "copilot: 3 processed");
     }
 
     #[test]
     fn test_build_tools() {
         let tools = HindsightServer::build_tools();
-        assert_eq!(tools.len(), 6);
+        assert_eq!(tools.len(), 25);
 
         let tool_names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
         assert!(tool_names.contains(&"hindsight_timeline"));
@@ -436,7 +1483,26 @@ mod tests {
         assert!(tool_names.contains(&"hindsight_failing_tests"));
         assert!(tool_names.contains(&"hindsight_activity_summary"));
         assert!(tool_names.contains(&"hindsight_commit_details"));
+        assert!(tool_names.contains(&"hindsight_diff"));
         assert!(tool_names.contains(&"hindsight_ingest"));
+        assert!(tool_names.contains(&"hindsight_copilot_stats"));
+        assert!(tool_names.contains(&"hindsight_trending_topics"));
+        assert!(tool_names.contains(&"hindsight_session_detail"));
+        assert!(tool_names.contains(&"hindsight_regressions"));
+        assert!(tool_names.contains(&"hindsight_failure_streaks"));
+        assert!(tool_names.contains(&"hindsight_flaky_tests"));
+        assert!(tool_names.contains(&"hindsight_slow_tests"));
+        assert!(tool_names.contains(&"hindsight_suite_parallelism"));
+        assert!(tool_names.contains(&"hindsight_commit_volume"));
+        assert!(tool_names.contains(&"hindsight_authors"));
+        assert!(tool_names.contains(&"hindsight_heatmap"));
+        assert!(tool_names.contains(&"hindsight_tags"));
+        assert!(tool_names.contains(&"hindsight_context_usage"));
+        assert!(tool_names.contains(&"hindsight_annotate"));
+        assert!(tool_names.contains(&"hindsight_query"));
+        assert!(tool_names.contains(&"hindsight_suggest_queries"));
+        assert!(tool_names.contains(&"hindsight_workspace_list"));
+        assert!(tool_names.contains(&"hindsight_workspaces"));
     }
 
     #[test]
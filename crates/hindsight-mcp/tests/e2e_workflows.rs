@@ -524,6 +524,10 @@ fn e2e_activity_summary_with_copilot() {
         created_at: hours_ago(now, 2),
         updated_at: hours_ago(now, 2),
         metadata_json: None,
+        token_estimate: None,
+        first_message_at: None,
+        last_message_at: None,
+        topics_json: None,
     };
 
     db.insert_copilot_session(&session1).unwrap();
@@ -650,6 +654,10 @@ fn e2e_multiple_copilot_sessions() {
         created_at: hours_ago(now, 1),
         updated_at: hours_ago(now, 1),
         metadata_json: None,
+        token_estimate: None,
+        first_message_at: None,
+        last_message_at: None,
+        topics_json: None,
     };
     db.insert_copilot_session(&session2).unwrap();
     db.insert_copilot_messages_batch(&[
@@ -706,7 +714,7 @@ fn e2e_timeline_with_copilot() {
     let commit = fixtures::sample_commit(&ws_id, "abc123", "Add feature", hours_ago(now, 2));
     db.insert_commit(&commit).unwrap();
 
-    // Create a copilot session with a message (messages appear in timeline, not sessions)
+    // Create a copilot session with a message (sessions with messages appear in the timeline)
     let session = sample_copilot_session(&ws_id, hours_ago(now, 1));
     db.insert_copilot_session(&session).unwrap();
     let message = sample_copilot_message(&session.id, "user", "How do I add a feature?", 0);
@@ -722,13 +730,13 @@ fn e2e_timeline_with_copilot() {
         .timeline(Some(10), None)
         .expect("timeline should succeed");
 
-    // Should have events for commit, copilot message, and test run
+    // Should have events for commit, copilot session, and test run
     assert!(timeline.len() >= 3);
 
     // Verify different event types exist
     let event_types: Vec<&str> = timeline.iter().map(|e| e.event_type.as_str()).collect();
     assert!(event_types.contains(&"commit"));
-    assert!(event_types.contains(&"copilot_message"));
+    assert!(event_types.contains(&"copilot_session"));
     assert!(event_types.contains(&"test_run"));
 }
 
@@ -246,6 +246,10 @@ pub fn sample_copilot_session(
         created_at,
         updated_at: created_at,
         metadata_json: Some(r#"{"version":3}"#.to_string()),
+        token_estimate: None,
+        first_message_at: None,
+        last_message_at: None,
+        topics_json: None,
     }
 }
 
@@ -5,16 +5,34 @@
 //!
 //! This module tests:
 //! - Valid nextest JSON input ingestion
+//! - `cargo test` JSON input ingestion
 //! - Edge cases (empty input, malformed JSON, etc.)
 //! - `--commit` flag behavior
 //! - Error handling and user-friendly messages
+//! - Git ingestion with excluded diff paths
+//! - Commit line statistics stored without diffs
+//! - Commit deduplication across clones of the same repository
+//! - Environment tagging of test runs
+//! - Per-suite totals in test run metadata
+//! - Live file history queries against git
+//! - Git notes ingestion and search
+//! - Git tag ingestion
+//! - CI annotation ingestion for test runs
+//! - Ingestion of every workspace in the database
+//! - Resuming interrupted git ingestion from a checkpoint
+//! - Full commit diffs read back through the diff tool
 
 mod fixtures;
 mod test_utils;
 
 use fixtures::test_database;
-use hindsight_mcp::ingest::{IngestError, IngestStats, Ingestor};
-use test_utils::{TempTestDir, sample_nextest_json};
+use hindsight_git::CommitStats;
+use hindsight_mcp::db::{Database, IngestSource, RetentionPolicy};
+use hindsight_mcp::handlers::handle_diff;
+use hindsight_mcp::ingest::{IngestError, IngestOptions, IngestStats, Ingestor};
+use hindsight_mcp::queries::get_commit_with_tests;
+use hindsight_tests::OutputFormat;
+use test_utils::{TempTestDir, TestGitRepo, sample_nextest_json};
 
 // ============================================================================
 // Valid Input Ingestion Tests
@@ -29,7 +47,7 @@ fn test_ingest_valid_nextest_json_all_passing() {
     let json = sample_nextest_json(5, 0, 0);
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1, "should insert 1 test run");
@@ -48,7 +66,7 @@ fn test_ingest_valid_nextest_json_with_failures() {
     let json = sample_nextest_json(3, 2, 0);
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -64,7 +82,7 @@ fn test_ingest_valid_nextest_json_with_ignored() {
     let json = sample_nextest_json(3, 1, 2);
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -81,7 +99,7 @@ fn test_ingest_creates_workspace_record() {
     let json = sample_nextest_json(2, 0, 0);
 
     let _stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     // Workspace should be created - verify via count
@@ -99,7 +117,7 @@ fn test_ingest_test_results_stored_correctly() {
     let json = sample_nextest_json(3, 1, 0);
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     // Verify results count from stats
@@ -125,14 +143,14 @@ fn test_ingest_multiple_runs_separate() {
 
     // First ingestion
     let stats1 = ingestor
-        .ingest_tests(temp.path(), &json1, None)
+        .ingest_tests(temp.path(), &json1, OutputFormat::Nextest, None)
         .expect("first ingestion");
     assert_eq!(stats1.test_runs_inserted, 1);
     assert_eq!(stats1.test_results_inserted, 2);
 
     // Second ingestion
     let stats2 = ingestor
-        .ingest_tests(temp.path(), &json2, None)
+        .ingest_tests(temp.path(), &json2, OutputFormat::Nextest, None)
         .expect("second ingestion");
     assert_eq!(stats2.test_runs_inserted, 1);
     assert_eq!(stats2.test_results_inserted, 3);
@@ -153,47 +171,77 @@ fn test_ingest_large_test_suite() {
     let json = sample_nextest_json(450, 30, 20);
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1);
     assert_eq!(stats.test_results_inserted, 500);
 }
 
+#[test]
+fn test_ingest_cargo_test_json() {
+    let mut ingestor = Ingestor::new(test_database());
+    let temp = TempTestDir::new("ingest_cargo_test");
+    let output = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "tests::adds" }
+{ "type": "test", "name": "tests::adds", "event": "ok", "exec_time": 0.01 }
+{ "type": "test", "event": "started", "name": "tests::divides" }
+{ "type": "test", "name": "tests::divides", "event": "failed", "exec_time": 0.02, "stdout": "attempt to divide by zero" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.03 }"#;
+
+    let stats = ingestor
+        .ingest_tests(temp.path(), output, OutputFormat::CargoTest, None)
+        .expect("ingestion should succeed");
+
+    assert_eq!(stats.test_runs_inserted, 1);
+    assert_eq!(stats.test_results_inserted, 2);
+
+    // Names are stored without nextest's binary prefix being stripped
+    let failing: (String, String) = ingestor
+        .database()
+        .connection()
+        .query_row(
+            "SELECT suite_name, test_name FROM test_results WHERE outcome = 'failed'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("failing result");
+    assert_eq!(failing, ("tests".to_string(), "divides".to_string()));
+}
+
 // ============================================================================
 // Edge Case Tests
 // ============================================================================
 
 #[test]
-fn test_ingest_empty_input_creates_empty_run() {
-    // The parser is lenient - empty input just produces an empty summary
+fn test_ingest_empty_input_is_skipped() {
     let db = test_database();
     let mut ingestor = Ingestor::new(db);
 
     let temp = TempTestDir::new("ingest_empty");
-    let result = ingestor.ingest_tests(temp.path(), "", None);
+    let result = ingestor.ingest_tests(temp.path(), "", OutputFormat::Nextest, None);
 
-    // Empty input is accepted but produces a run with no results
-    assert!(
-        result.is_ok(),
-        "empty input should succeed (lenient parser)"
-    );
+    // Empty input is logged and skipped rather than creating an empty run
+    assert!(result.is_ok(), "empty input should not be an error");
     let stats = result.unwrap();
-    assert_eq!(stats.test_runs_inserted, 1, "should create 1 test run");
+    assert_eq!(stats.test_runs_inserted, 0, "should create no test run");
     assert_eq!(stats.test_results_inserted, 0, "should insert 0 results");
 }
 
 #[test]
-fn test_ingest_whitespace_only_creates_empty_run() {
-    // Whitespace-only is also accepted by the lenient parser
+fn test_ingest_whitespace_only_is_skipped() {
     let db = test_database();
     let mut ingestor = Ingestor::new(db);
 
     let temp = TempTestDir::new("ingest_whitespace");
-    let result = ingestor.ingest_tests(temp.path(), "   \n\t\n   ", None);
+    let result = ingestor.ingest_tests(temp.path(), "   \n\t\n   ", OutputFormat::Nextest, None);
 
-    assert!(result.is_ok(), "whitespace-only input should succeed");
+    assert!(
+        result.is_ok(),
+        "whitespace-only input should not be an error"
+    );
     let stats = result.unwrap();
+    assert_eq!(stats.test_runs_inserted, 0, "should create no test run");
     assert_eq!(stats.test_results_inserted, 0, "should insert 0 results");
 }
 
@@ -205,7 +253,7 @@ fn test_ingest_malformed_json_fails() {
     let temp = TempTestDir::new("ingest_malformed");
     let malformed = r#"{"type": "not valid nextest", "garbage": true"#; // Missing closing brace
 
-    let result = ingestor.ingest_tests(temp.path(), malformed, None);
+    let result = ingestor.ingest_tests(temp.path(), malformed, OutputFormat::Nextest, None);
 
     assert!(result.is_err(), "malformed JSON should fail");
 }
@@ -219,7 +267,7 @@ fn test_ingest_invalid_json_structure_fails() {
     // Valid JSON but not nextest format
     let invalid = r#"{"foo": "bar", "baz": 123}"#;
 
-    let result = ingestor.ingest_tests(temp.path(), invalid, None);
+    let result = ingestor.ingest_tests(temp.path(), invalid, OutputFormat::Nextest, None);
 
     assert!(result.is_err(), "invalid structure should fail");
 }
@@ -233,7 +281,7 @@ fn test_ingest_json_array_instead_of_lines_fails() {
     // JSON array instead of line-delimited
     let invalid = r#"[{"type":"test","event":"ok","name":"test1"}]"#;
 
-    let result = ingestor.ingest_tests(temp.path(), invalid, None);
+    let result = ingestor.ingest_tests(temp.path(), invalid, OutputFormat::Nextest, None);
 
     // This should fail or produce 0 results since it's not line-delimited
     match result {
@@ -261,7 +309,7 @@ invalid line here
 {"type":"suite","event":"ok","passed":1,"failed":0,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.1}"#;
 
     // Parser may be lenient and skip invalid lines, or fail entirely
-    let result = ingestor.ingest_tests(temp.path(), partial, None);
+    let result = ingestor.ingest_tests(temp.path(), partial, OutputFormat::Nextest, None);
 
     // Accept either outcome - just shouldn't panic
     if let Ok(stats) = result {
@@ -280,7 +328,7 @@ fn test_ingest_zero_tests() {
     let json = sample_nextest_json(0, 0, 0);
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("zero tests should still succeed");
 
     // Should create a test run with 0 results
@@ -303,7 +351,7 @@ fn test_ingest_special_characters_in_test_names() {
 {"type":"suite","event":"ok","passed":2,"failed":0,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.1}"#;
 
     let stats = ingestor
-        .ingest_tests(temp.path(), json, None)
+        .ingest_tests(temp.path(), json, OutputFormat::Nextest, None)
         .expect("special characters should be handled");
 
     assert_eq!(stats.test_results_inserted, 2);
@@ -325,7 +373,7 @@ fn test_ingest_very_long_test_output() {
     );
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("long output should be handled");
 
     assert_eq!(stats.test_results_inserted, 1);
@@ -345,7 +393,7 @@ fn test_ingest_with_valid_commit_sha() {
     let commit_sha = "abc123def456789012345678901234567890abcd";
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, Some(commit_sha))
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, Some(commit_sha))
         .expect("ingestion with commit should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -372,7 +420,7 @@ fn test_ingest_with_short_commit_sha() {
 
     // Short SHA should be accepted (validation is caller's responsibility)
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, Some(short_sha))
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, Some(short_sha))
         .expect("short SHA should be accepted");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -396,7 +444,7 @@ fn test_ingest_with_no_commit_sha() {
     let json = sample_nextest_json(2, 0, 0);
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion without commit should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -422,7 +470,7 @@ fn test_ingest_with_full_40_char_sha() {
     let full_sha = "0123456789abcdef0123456789abcdef01234567";
 
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, Some(full_sha))
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, Some(full_sha))
         .expect("full SHA should work");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -448,7 +496,7 @@ fn test_ingest_commit_sha_with_uppercase() {
 
     // Should be accepted (case is preserved or normalized)
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, Some(upper_sha))
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, Some(upper_sha))
         .expect("uppercase SHA should be accepted");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -464,7 +512,7 @@ fn test_ingest_empty_commit_sha_treated_as_none() {
 
     // Empty string should be treated similarly to None or as empty
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, Some(""))
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, Some(""))
         .expect("empty SHA should not crash");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -494,12 +542,12 @@ fn test_ingest_multiple_runs_different_commits() {
 
     // First run with sha1
     ingestor
-        .ingest_tests(temp.path(), &json, Some(sha1))
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, Some(sha1))
         .expect("first commit");
 
     // Second run with sha2
     ingestor
-        .ingest_tests(temp.path(), &json, Some(sha2))
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, Some(sha2))
         .expect("second commit");
 
     // Query runs and verify different commits
@@ -575,6 +623,23 @@ fn test_ingest_error_display_tests() {
     assert!(msg.contains("Tests error"), "error message: {}", msg);
 }
 
+#[test]
+fn test_ingest_error_display_chain_nested() {
+    let git_err = hindsight_git::GitError::Git2(git2::Error::from_str("object not found"));
+    let ingest_err = IngestError::Git(git_err);
+
+    assert_eq!(ingest_err.display_chain(), "Git error → object not found");
+}
+
+#[test]
+fn test_ingest_error_display_chain_single_level() {
+    let ingest_err = IngestError::WorkspaceNotFound {
+        path: "/missing".to_string(),
+    };
+
+    assert_eq!(ingest_err.display_chain(), "Workspace not found: /missing");
+}
+
 #[test]
 fn test_ingest_database_preserved_after_error() {
     let db = test_database();
@@ -585,11 +650,11 @@ fn test_ingest_database_preserved_after_error() {
     // First, successful ingestion
     let json_good = sample_nextest_json(2, 0, 0);
     ingestor
-        .ingest_tests(temp.path(), &json_good, None)
+        .ingest_tests(temp.path(), &json_good, OutputFormat::Nextest, None)
         .expect("first should succeed");
 
     // Then, failed ingestion
-    let _result = ingestor.ingest_tests(temp.path(), "invalid json", None);
+    let _result = ingestor.ingest_tests(temp.path(), "invalid json", OutputFormat::Nextest, None);
     // Error is expected, ignore result
 
     // Original data should still be there
@@ -607,7 +672,7 @@ fn test_ingest_workspace_path_variations() {
 
     // Absolute path
     let temp1 = TempTestDir::new("ingest_abs");
-    let result1 = ingestor.ingest_tests(temp1.path(), &json, None);
+    let result1 = ingestor.ingest_tests(temp1.path(), &json, OutputFormat::Nextest, None);
     assert!(result1.is_ok(), "absolute path should work");
 
     // Path with trailing slash (create new ingestor to avoid duplicate workspace issues)
@@ -615,7 +680,7 @@ fn test_ingest_workspace_path_variations() {
     let mut ingestor2 = Ingestor::new(db2);
     let temp2 = TempTestDir::new("ingest_trailing");
     let path_with_slash = format!("{}/", temp2.path().display());
-    let result2 = ingestor2.ingest_tests(&path_with_slash, &json, None);
+    let result2 = ingestor2.ingest_tests(&path_with_slash, &json, OutputFormat::Nextest, None);
     // Should work or fail gracefully
     assert!(result2.is_ok() || result2.is_err());
 }
@@ -631,7 +696,7 @@ fn test_ingest_nonexistent_workspace_creates_record() {
 
     // The ingestor creates workspace record based on path string,
     // it doesn't validate that the path exists
-    let result = ingestor.ingest_tests(fake_path, &json, None);
+    let result = ingestor.ingest_tests(fake_path, &json, OutputFormat::Nextest, None);
 
     // This may succeed since workspace creation is by path string
     match result {
@@ -657,7 +722,7 @@ fn test_ingest_run_record_has_counts() {
     let json = sample_nextest_json(5, 3, 2);
 
     ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     // Verify the test run has correct counts stored
@@ -686,7 +751,7 @@ fn test_ingest_run_has_started_at_timestamp() {
     let json = sample_nextest_json(1, 0, 0);
 
     ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     let db_ref = ingestor.database();
@@ -712,7 +777,7 @@ fn test_ingest_result_records_have_outcome() {
     let json = sample_nextest_json(2, 1, 0);
 
     ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     let db_ref = ingestor.database();
@@ -748,7 +813,7 @@ fn test_ingest_result_records_linked_to_run() {
     let json = sample_nextest_json(3, 0, 0);
 
     ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
         .expect("ingestion should succeed");
 
     let db_ref = ingestor.database();
@@ -770,3 +835,1137 @@ fn test_ingest_result_records_linked_to_run() {
 
     assert_eq!(linked_count, 3, "all results should be linked to the run");
 }
+
+#[test]
+fn test_ingest_run_records_suite_metadata() {
+    let db = test_database();
+    let mut ingestor = Ingestor::new(db);
+
+    let temp = TempTestDir::new("ingest_suites");
+    let json = r#"{"type":"suite","event":"started","test_count":3}
+{"type":"test","event":"ok","name":"app::app$db::tests::test_open","exec_time":0.010}
+{"type":"test","event":"failed","name":"app::app$db::tests::test_close","exec_time":0.030}
+{"type":"test","event":"ok","name":"app::app$cli::tests::test_args","exec_time":0.002}
+{"type":"suite","event":"failed","passed":2,"failed":1,"ignored":0,"exec_time":0.042}"#;
+
+    ingestor
+        .ingest_tests(temp.path(), json, OutputFormat::Nextest, None)
+        .expect("ingestion should succeed");
+
+    let metadata: String = ingestor
+        .database()
+        .connection()
+        .query_row("SELECT metadata_json FROM test_runs LIMIT 1", [], |row| {
+            row.get(0)
+        })
+        .expect("query should work");
+    let metadata: serde_json::Value = serde_json::from_str(&metadata).expect("valid JSON");
+
+    assert_eq!(
+        metadata["suites"],
+        serde_json::json!([
+            {"name": "cli", "passed": 1, "failed": 0, "ignored": 0, "total_duration_ms": 2},
+            {"name": "db", "passed": 1, "failed": 1, "ignored": 0, "total_duration_ms": 40},
+        ])
+    );
+}
+
+// ============================================================================
+// Git Exclude Path Tests
+// ============================================================================
+
+/// Fetch the stored diff JSON and exclusion flag for a commit
+fn stored_diff(ingestor: &Ingestor, sha: &str) -> (Option<String>, bool) {
+    ingestor
+        .database()
+        .connection()
+        .query_row(
+            "SELECT diff_json, diff_excluded FROM commits WHERE sha = ?1",
+            [sha],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("commit should exist")
+}
+
+#[test]
+fn test_ingest_git_excluded_only_file_stores_null_diff() {
+    let mut repo = TestGitRepo::new("ingest_exclude_lock");
+    repo.init();
+    let src_sha = repo.create_and_commit("src/main.rs", "fn main() {}", "Add main.rs");
+    let lock_sha = repo.create_and_commit("Cargo.lock", "# lock", "Update lock file");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let options = IngestOptions::full().with_exclude_paths(vec!["Cargo.lock".to_string()]);
+    ingestor
+        .ingest_git(repo.path(), &options)
+        .expect("git ingestion should succeed");
+
+    let (lock_diff, lock_excluded) = stored_diff(&ingestor, &lock_sha);
+    assert!(lock_diff.is_none(), "fully excluded diff should be null");
+    assert!(lock_excluded, "fully excluded diff should be flagged");
+
+    let (src_diff, src_excluded) = stored_diff(&ingestor, &src_sha);
+    assert!(src_diff.expect("diff stored").contains("src/main.rs"));
+    assert!(!src_excluded);
+}
+
+#[test]
+fn test_ingest_git_excluded_paths_removed_from_mixed_diff() {
+    let mut repo = TestGitRepo::new("ingest_exclude_mixed");
+    repo.init();
+    repo.create_file("Cargo.lock", "# lock");
+    repo.create_file("src/lib.rs", "pub fn lib() {}");
+    let sha = repo.commit("Add library and lock file");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let options = IngestOptions::full().with_exclude_paths(vec!["*.lock".to_string()]);
+    ingestor
+        .ingest_git(repo.path(), &options)
+        .expect("git ingestion should succeed");
+
+    let (diff, excluded) = stored_diff(&ingestor, &sha);
+    let diff = diff.expect("diff stored");
+    assert!(!excluded);
+    assert!(diff.contains("src/lib.rs"));
+    assert!(!diff.contains("Cargo.lock"));
+}
+
+#[test]
+fn test_ingest_git_invalid_exclude_pattern() {
+    let mut repo = TestGitRepo::new("ingest_exclude_invalid");
+    repo.init();
+    repo.create_and_commit("README.md", "# Readme", "Initial commit");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let options = IngestOptions::full().with_exclude_paths(vec!["[".to_string()]);
+    let result = ingestor.ingest_git(repo.path(), &options);
+
+    assert!(matches!(result, Err(IngestError::InvalidPattern(_))));
+}
+
+#[test]
+fn test_ingest_git_not_a_repository() {
+    let dir = TempTestDir::new("ingest_not_a_repo");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let err = ingestor
+        .ingest_git(dir.path(), &IngestOptions::full())
+        .expect_err("ingesting a plain directory should fail");
+
+    assert!(matches!(err, IngestError::NotARepository { .. }));
+    assert!(err.to_string().contains("is not a git repository"));
+    assert!(err.to_string().contains(&dir.path().display().to_string()));
+    assert_eq!(
+        ingestor.database().count("workspaces").expect("count"),
+        0,
+        "no workspace should be created for a non-repository"
+    );
+}
+
+// ============================================================================
+// Commit Stats Tests
+// ============================================================================
+
+#[test]
+fn test_ingest_git_stores_stats_without_diffs() {
+    let mut repo = TestGitRepo::new("ingest_stats_no_diff");
+    repo.init();
+    repo.create_file("src/lib.rs", "pub fn a() {}\npub fn b() {}\n");
+    repo.create_file("README.md", "# Readme\n");
+    let sha = repo.commit("Add library");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let options = IngestOptions {
+        include_diffs: false,
+        ..IngestOptions::full()
+    };
+    ingestor
+        .ingest_git(repo.path(), &options)
+        .expect("git ingestion should succeed");
+
+    let (diff, _) = stored_diff(&ingestor, &sha);
+    assert!(diff.is_none(), "diff should not be stored");
+
+    let commit = get_commit_with_tests(ingestor.database().connection(), &sha)
+        .expect("query")
+        .expect("commit exists");
+    assert!(commit.files.is_empty());
+    assert_eq!(
+        commit.stats,
+        Some(CommitStats {
+            files_changed: 2,
+            insertions: 3,
+            deletions: 0,
+        })
+    );
+}
+
+// ============================================================================
+// Repository Fingerprint Tests
+// ============================================================================
+
+#[test]
+fn test_ingest_git_skips_commits_shared_with_clone() {
+    let mut repo = TestGitRepo::new("ingest_fingerprint_origin");
+    repo.init();
+    repo.create_commits(3);
+    let clone = repo.clone_to("ingest_fingerprint_clone");
+    let new_sha = clone.create_and_commit("clone.txt", "only in clone", "Clone-only commit");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let first = ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("origin ingestion should succeed");
+    assert_eq!(first.commits_inserted, 3);
+
+    let second = ingestor
+        .ingest_git(clone.path(), &IngestOptions::full())
+        .expect("clone ingestion should succeed");
+    assert_eq!(second.commits_inserted, 1, "only the new commit is stored");
+    assert_eq!(second.commits_skipped, 3);
+
+    let db = ingestor.database();
+    assert_eq!(db.count("commits").expect("count"), 4);
+    let workspaces = db.list_workspaces().expect("list");
+    assert_eq!(workspaces.len(), 2);
+    let fingerprint = workspaces[0].repo_fingerprint.clone();
+    assert!(fingerprint.is_some());
+    assert_eq!(workspaces[1].repo_fingerprint, fingerprint);
+
+    let clone_id = db
+        .get_workspace_id(&clone.path().display().to_string())
+        .expect("query")
+        .expect("clone workspace exists");
+    assert!(db.get_commit_by_sha(&clone_id, &new_sha).is_ok());
+}
+
+#[test]
+fn test_ingest_git_reuses_stored_fingerprint() {
+    let mut repo = TestGitRepo::new("ingest_fingerprint_reuse");
+    repo.init();
+    repo.create_commits(2);
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("first ingestion should succeed");
+
+    let workspace_id = ingestor
+        .database()
+        .get_workspace_id(&repo.path().display().to_string())
+        .expect("query")
+        .expect("workspace exists");
+    ingestor
+        .database()
+        .set_workspace_fingerprint(&workspace_id, "stored")
+        .expect("set fingerprint");
+
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("second ingestion should succeed");
+
+    // A recomputed fingerprint would have replaced the stored one
+    assert_eq!(
+        ingestor
+            .database()
+            .get_workspace_fingerprint(&workspace_id)
+            .expect("get")
+            .as_deref(),
+        Some("stored")
+    );
+}
+
+#[test]
+fn test_ingest_git_unrelated_repos_not_deduplicated() {
+    let mut first = TestGitRepo::new("ingest_fingerprint_first");
+    first.init();
+    first.create_commits(2);
+    let mut second = TestGitRepo::new("ingest_fingerprint_second");
+    second.init();
+    second.create_and_commit("other.txt", "Other", "Unrelated root");
+    second.create_and_commit("more.txt", "More", "Unrelated follow-up");
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(first.path(), &IngestOptions::full())
+        .expect("first ingestion should succeed");
+    let stats = ingestor
+        .ingest_git(second.path(), &IngestOptions::full())
+        .expect("second ingestion should succeed");
+
+    assert_eq!(stats.commits_inserted, 2);
+    assert_eq!(stats.commits_skipped, 0);
+}
+
+// ============================================================================
+// Tag-Bounded Ingestion Tests
+// ============================================================================
+
+#[test]
+fn test_walk_commits_since_tag_returns_post_tag_commits() {
+    let mut repo = TestGitRepo::new("walk_since_tag");
+    repo.init();
+    repo.create_commits(2);
+    repo.tag("v1.0.0");
+    let after: Vec<String> = vec![
+        repo.create_and_commit("a.txt", "a", "After tag one"),
+        repo.create_and_commit("b.txt", "b", "After tag two"),
+    ];
+
+    let git_repo = hindsight_git::GitRepo::open(repo.path()).expect("open repo");
+    let commits = git_repo
+        .walk_commits_since_tag("v1.0.0", true)
+        .expect("walk should succeed");
+
+    let shas: Vec<&str> = commits.iter().map(|c| c.commit.sha.as_str()).collect();
+    assert_eq!(shas, vec![after[1].as_str(), after[0].as_str()]);
+    assert!(commits.iter().all(|c| c.diff.is_some()));
+}
+
+#[test]
+fn test_ingest_git_since_tag() {
+    let mut repo = TestGitRepo::new("ingest_since_tag");
+    repo.init();
+    repo.create_commits(3);
+    repo.tag("v1.2.3");
+    let after = repo.create_and_commit("new.txt", "new", "After release");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let stats = ingestor
+        .ingest_git(repo.path(), &IngestOptions::full().with_since_tag("v1.2.3"))
+        .expect("ingestion should succeed");
+
+    assert_eq!(stats.commits_inserted, 1);
+    let db = ingestor.database();
+    assert_eq!(db.count("commits").expect("count"), 1);
+    let workspace_id = db
+        .get_workspace_id(&repo.path().display().to_string())
+        .expect("query")
+        .expect("workspace exists");
+    assert!(db.get_commit_by_sha(&workspace_id, &after).is_ok());
+}
+
+#[test]
+fn test_ingest_git_since_missing_tag_fails() {
+    let mut repo = TestGitRepo::new("ingest_since_missing_tag");
+    repo.init();
+    repo.create_commits(1);
+
+    let mut ingestor = Ingestor::new(test_database());
+    let result = ingestor.ingest_git(repo.path(), &IngestOptions::full().with_since_tag("v9.9.9"));
+    assert!(result.is_err(), "missing tag should be an error");
+}
+
+#[test]
+fn test_ingest_git_tags_allowlist() {
+    let mut repo = TestGitRepo::new("ingest_tags_allowlist");
+    repo.init();
+    let first = repo.create_and_commit("a.txt", "a", "First");
+    repo.tag("v1.0.0");
+    let second = repo.create_and_commit("b.txt", "b", "Second");
+    repo.tag("v1.1.0");
+    let unreleased = repo.create_and_commit("c.txt", "c", "Unreleased");
+
+    let ingest = |tags: &[&str]| {
+        let mut ingestor = Ingestor::new(test_database());
+        let options =
+            IngestOptions::full().with_tags_allowlist(tags.iter().map(|t| t.to_string()).collect());
+        ingestor
+            .ingest_git(repo.path(), &options)
+            .expect("ingestion should succeed");
+        let db = ingestor.into_database();
+        let workspace_id = db
+            .get_workspace_id(&repo.path().display().to_string())
+            .expect("query")
+            .expect("workspace exists");
+        let stored = |sha: &str| db.get_commit_by_sha(&workspace_id, sha).is_ok();
+        (stored(&first), stored(&second), stored(&unreleased))
+    };
+
+    assert_eq!(ingest(&["v1.0.0"]), (true, false, false));
+    assert_eq!(ingest(&["v1.0.0", "v1.1.0"]), (true, true, false));
+    assert_eq!(ingest(&[]), (true, true, true));
+
+    let mut ingestor = Ingestor::new(test_database());
+    let result = ingestor.ingest_git(
+        repo.path(),
+        &IngestOptions::full().with_tags_allowlist(vec!["v9.9.9".to_string()]),
+    );
+    assert!(result.is_err(), "missing tag should be an error");
+}
+
+// ============================================================================
+// Last Ingest Tracking Tests
+// ============================================================================
+
+fn find_workspace(
+    ingestor: &Ingestor,
+    path: &std::path::Path,
+) -> hindsight_mcp::db::WorkspaceRecord {
+    let path = path.display().to_string();
+    ingestor
+        .database()
+        .list_workspaces()
+        .expect("list")
+        .into_iter()
+        .find(|w| w.path == path)
+        .expect("workspace exists")
+}
+
+#[test]
+fn test_ingest_records_last_ingest_times() {
+    let mut repo = TestGitRepo::new("ingest_last_ingest");
+    repo.init();
+    repo.create_commits(1);
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("git ingestion should succeed");
+    let workspace = find_workspace(&ingestor, repo.path());
+    assert!(workspace.last_git_ingest_at.is_some());
+    assert!(workspace.last_test_ingest_at.is_none());
+
+    ingestor
+        .ingest_tests(
+            repo.path(),
+            &sample_nextest_json(2, 1, 0),
+            OutputFormat::Nextest,
+            None,
+        )
+        .expect("test ingestion should succeed");
+    let workspace = find_workspace(&ingestor, repo.path());
+    assert!(workspace.last_test_ingest_at.is_some());
+
+    // Copilot storage may not exist on this machine
+    if ingestor
+        .ingest_copilot(repo.path(), &IngestOptions::default(), None)
+        .is_ok()
+    {
+        let workspace = find_workspace(&ingestor, repo.path());
+        assert!(workspace.last_copilot_ingest_at.is_some());
+    }
+
+    let stale = ingestor
+        .database()
+        .get_stale_workspaces(1)
+        .expect("stale query");
+    assert!(stale.is_empty(), "a just-ingested workspace is not stale");
+}
+
+// ============================================================================
+// Live File History Tests
+// ============================================================================
+
+#[test]
+fn test_query_file_history_live_without_diffs() {
+    let mut repo = TestGitRepo::new("ingest_file_history");
+    repo.init();
+    let first = repo.create_and_commit("src/lib.rs", "pub fn a() {}", "Add lib.rs");
+    repo.create_and_commit("README.md", "# Readme", "Add readme");
+    let second = repo.create_and_commit("src/lib.rs", "pub fn b() {}", "Change lib.rs");
+
+    // Ingest without diffs, then forget the newest commit so it must come from git
+    let mut ingestor = Ingestor::new(test_database());
+    let options = IngestOptions {
+        include_diffs: false,
+        ..IngestOptions::full()
+    };
+    ingestor
+        .ingest_git(repo.path(), &options)
+        .expect("git ingestion should succeed");
+    ingestor
+        .database()
+        .connection()
+        .execute("DELETE FROM commits WHERE sha = ?1", [&second])
+        .expect("remove newest commit");
+
+    let history = ingestor
+        .query_file_history_live(repo.path(), "src/lib.rs", 10)
+        .expect("file history");
+    let shas: Vec<&str> = history.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, vec![second.as_str(), first.as_str()]);
+
+    // The ingested commit comes from the database, the other from git
+    let workspace_id = history[1].workspace_id.clone();
+    assert!(!workspace_id.is_empty());
+    assert_eq!(history[0].workspace_id, workspace_id);
+    assert_eq!(history[0].message.trim(), "Change lib.rs");
+
+    let limited = ingestor
+        .query_file_history_live(repo.path(), "src/lib.rs", 1)
+        .expect("file history");
+    assert_eq!(limited.len(), 1);
+}
+
+#[test]
+fn test_query_file_history_live_current_repo() {
+    let repo_root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .and_then(|p| p.parent())
+        .expect("workspace root");
+
+    let ingestor = Ingestor::new(test_database());
+    let history = ingestor
+        .query_file_history_live(repo_root, "Cargo.toml", 3)
+        .expect("file history");
+    assert!(!history.is_empty());
+    assert!(history.len() <= 3);
+    assert!(history.iter().all(|c| c.sha.len() == 40));
+}
+
+#[test]
+fn test_query_file_history_live_not_a_repository() {
+    let temp = TempTestDir::new("ingest_file_history_no_repo");
+    let ingestor = Ingestor::new(test_database());
+    let result = ingestor.query_file_history_live(temp.path(), "src/lib.rs", 10);
+    assert!(matches!(result, Err(IngestError::NotARepository { .. })));
+}
+
+// ============================================================================
+// Environment Tag Tests
+// ============================================================================
+
+#[test]
+fn test_ingest_tests_environment_tags_distinguish_runs() {
+    use hindsight_mcp::queries::get_test_results_by_env;
+
+    let mut ingestor = Ingestor::new(test_database());
+    let temp = TempTestDir::new("ingest_env_tags");
+
+    let ci = IngestOptions::default().with_environment_tag("env", "ci");
+    let local = IngestOptions::default().with_environment_tag("env", "local");
+    ingestor
+        .ingest_tests_with_options(temp.path(), &sample_nextest_json(0, 2, 0), None, &ci)
+        .expect("ci ingestion should succeed");
+    ingestor
+        .ingest_tests_with_options(temp.path(), &sample_nextest_json(3, 0, 0), None, &local)
+        .expect("local ingestion should succeed");
+
+    let conn = ingestor.database().connection();
+    let ci_results = get_test_results_by_env(conn, "ci", 100).expect("ci query");
+    let local_results = get_test_results_by_env(conn, "local", 100).expect("local query");
+
+    assert_eq!(ci_results.len(), 2);
+    assert!(ci_results.iter().all(|r| r.outcome == "failed"));
+    assert_eq!(local_results.len(), 3);
+    assert!(local_results.iter().all(|r| r.outcome == "passed"));
+}
+
+#[test]
+fn test_ingest_tests_records_wall_time_from_suite_exec_time() {
+    use hindsight_mcp::queries::get_test_run_durations;
+
+    let mut ingestor = Ingestor::new(test_database());
+    let temp = TempTestDir::new("ingest_wall_time");
+    let json = sample_nextest_json(2, 0, 0).replace(r#""exec_time":0.1}"#, r#""exec_time":4.0}"#);
+
+    ingestor
+        .ingest_tests(temp.path(), &json, OutputFormat::Nextest, None)
+        .expect("ingestion should succeed");
+
+    let durations =
+        get_test_run_durations(ingestor.database().connection(), None, 10).expect("durations");
+    assert_eq!(durations.len(), 1);
+    assert!(
+        (durations[0].wall_time_secs - 4.0).abs() < 0.01,
+        "got {}",
+        durations[0].wall_time_secs
+    );
+}
+
+#[test]
+fn test_ingest_tests_records_event_windows() {
+    let mut ingestor = Ingestor::new(test_database());
+    let temp = TempTestDir::new("ingest_event_windows");
+
+    ingestor
+        .ingest_tests(
+            temp.path(),
+            &sample_nextest_json(3, 1, 0),
+            OutputFormat::Nextest,
+            None,
+        )
+        .expect("ingestion should succeed");
+
+    let missing: i64 = ingestor
+        .database()
+        .connection()
+        .query_row(
+            "SELECT COUNT(*) FROM test_results WHERE started_seq IS NULL OR finished_seq < started_seq",
+            [],
+            |row| row.get(0),
+        )
+        .expect("query");
+    assert_eq!(missing, 0, "every result should have a valid event window");
+}
+
+#[test]
+fn test_ingest_tests_matches_event_windows_by_name() {
+    let mut ingestor = Ingestor::new(test_database());
+    let temp = TempTestDir::new("ingest_event_windows_by_name");
+
+    let output = r#"{"type":"suite","event":"started","test_count":2}
+{"type":"test","event":"started","name":"c::c$slow::test_a"}
+{"type":"test","event":"started","name":"c::c$fast::test_b"}
+{"type":"test","event":"ok","name":"c::c$fast::test_b","exec_time":0.1}
+{"type":"test","event":"ok","name":"c::c$slow::test_a","exec_time":0.5}
+{"type":"suite","event":"ok","passed":2,"failed":0,"ignored":0,"exec_time":0.5}"#;
+    ingestor
+        .ingest_tests(temp.path(), output, OutputFormat::Nextest, None)
+        .expect("ingestion should succeed");
+
+    let conn = ingestor.database().connection();
+    let mut stmt = conn
+        .prepare("SELECT test_name, started_seq, finished_seq FROM test_results ORDER BY test_name")
+        .expect("prepare");
+    let windows: Vec<(String, i64, i64)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .expect("query")
+        .collect::<Result<_, _>>()
+        .expect("rows");
+    assert_eq!(
+        windows,
+        vec![("test_a".to_string(), 1, 4), ("test_b".to_string(), 2, 3)]
+    );
+}
+
+#[test]
+fn test_ingest_tests_always_tags_env() {
+    let mut ingestor = Ingestor::new(test_database());
+    let temp = TempTestDir::new("ingest_env_default");
+
+    ingestor
+        .ingest_tests(
+            temp.path(),
+            &sample_nextest_json(1, 0, 0),
+            OutputFormat::Nextest,
+            None,
+        )
+        .expect("ingestion should succeed");
+
+    let env: String = ingestor
+        .database()
+        .connection()
+        .query_row(
+            "SELECT json_extract(tags_json, '$.env') FROM test_runs",
+            [],
+            |row| row.get(0),
+        )
+        .expect("run should be tagged");
+    assert!(env == "ci" || env == "local");
+}
+
+#[test]
+fn test_ingest_git_records_extension_stats() {
+    let mut repo = TestGitRepo::new("ingest_extension_stats");
+    repo.init();
+    repo.create_file("src/main.rs", "fn main() {}\n")
+        .create_file(".gitignore", "target\n/dist\n");
+    let sha = repo.commit("Add main and gitignore");
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("git ingestion should succeed");
+
+    let (rs, gitignore): (i64, i64) = ingestor
+        .database()
+        .connection()
+        .query_row(
+            "SELECT json_extract(diff_json, '$.extension_stats.rs.insertions'),
+                    json_extract(diff_json, '$.extension_stats.gitignore.insertions')
+             FROM commits WHERE sha = ?1",
+            [&sha],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("commit should have extension stats");
+    assert_eq!(rs, 1);
+    assert_eq!(gitignore, 2);
+}
+
+#[test]
+fn test_ingest_git_records_language_hint() {
+    use hindsight_mcp::queries::get_commits_by_language;
+
+    let mut repo = TestGitRepo::new("ingest_language_hint");
+    repo.init();
+    let rust_source: String = (0..100).map(|i| format!("// line {i}\n")).collect();
+    let python_source: String = (0..10).map(|i| format!("# line {i}\n")).collect();
+    repo.create_file("src/lib.rs", &rust_source)
+        .create_file("scripts/gen.py", &python_source);
+    let sha = repo.commit("Add library and generator script");
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("git ingestion should succeed");
+
+    let conn = ingestor.database().connection();
+    let commits = get_commits_by_language(conn, "Rust", 10).expect("query commits");
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].sha, sha);
+    assert!(
+        get_commits_by_language(conn, "Python", 10)
+            .expect("query commits")
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_ingest_git_oldest_first() {
+    let mut repo = TestGitRepo::new("ingest_git_oldest_first");
+    repo.init();
+    let shas = repo.create_commits(4);
+
+    // With a limit, oldest-first ingests the root commit and its child
+    let mut ingestor = Ingestor::new(test_database());
+    let stats = ingestor
+        .ingest_git(
+            repo.path(),
+            &IngestOptions::incremental().oldest_first().with_limit(2),
+        )
+        .expect("git ingestion should succeed");
+    assert_eq!(stats.commits_inserted, 2);
+
+    let stored: Vec<String> = {
+        let conn = ingestor.database().connection();
+        let mut stmt = conn
+            .prepare("SELECT sha FROM commits ORDER BY rowid")
+            .expect("prepare");
+        stmt.query_map([], |row| row.get(0))
+            .expect("query")
+            .collect::<Result<_, _>>()
+            .expect("rows")
+    };
+    assert_eq!(stored, shas[..2]);
+
+    // A later oldest-first pass picks up the remaining commits
+    let stats = ingestor
+        .ingest_git(repo.path(), &IngestOptions::incremental().oldest_first())
+        .expect("git ingestion should succeed");
+    assert_eq!(stats.commits_inserted, 2);
+    assert_eq!(stats.commits_skipped, 2);
+}
+
+#[test]
+fn test_ingest_test_list_records_inventory() {
+    let list_json = r#"{
+        "test-count": 3,
+        "rust-suites": {
+            "my-crate": {
+                "package-name": "my-crate",
+                "binary-id": "my-crate",
+                "binary-name": "my_crate",
+                "kind": "lib",
+                "testcases": {
+                    "tests::test_one": {"kind": "test", "ignored": false},
+                    "tests::test_slow": {"kind": "test", "ignored": true}
+                }
+            },
+            "my-crate::integration": {
+                "package-name": "my-crate",
+                "binary-id": "my-crate::integration",
+                "binary-name": "integration",
+                "kind": "test",
+                "testcases": {
+                    "test_end_to_end": {"kind": "test", "ignored": false}
+                }
+            }
+        }
+    }"#;
+
+    let temp_dir = TempTestDir::new("ingest_test_list");
+    let mut ingestor = Ingestor::new(test_database());
+    let stats = ingestor
+        .ingest_test_list(temp_dir.path(), list_json)
+        .expect("list ingestion should succeed");
+
+    assert_eq!(stats.tests_listed, 3);
+    assert_eq!(stats.test_runs_inserted, 0);
+    assert_eq!(
+        ingestor.database().count("test_inventory").expect("count"),
+        3
+    );
+
+    let result = ingestor.ingest_test_list(temp_dir.path(), "not json");
+    assert!(matches!(result, Err(IngestError::Tests(_))));
+}
+
+// ============================================================================
+// CI Annotation Ingestion Tests
+// ============================================================================
+
+const CI_LOG: &str = "\
+2026-03-01T12:00:00.0000000Z Running tests
+2026-03-01T12:00:01.0000000Z ::error file=src/lib.rs,line=42::assertion failed
+2026-03-01T12:00:02.0000000Z ::warning file=src/db.rs,line=7::unused variable
+2026-03-01T12:00:03.0000000Z ::error::process completed with exit code 101
+";
+
+#[test]
+fn test_ingest_ci_annotations_attach_to_test_run() {
+    use hindsight_mcp::queries::get_ci_annotations_for_run;
+    use hindsight_tests::AnnotationLevel;
+
+    let mut repo = TestGitRepo::new("ingest_ci_annotations");
+    repo.init();
+    repo.create_file("src/lib.rs", "pub fn a() {}\n");
+    let sha = repo.commit("Add library");
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("git ingestion should succeed");
+    ingestor
+        .ingest_tests(
+            repo.path(),
+            &sample_nextest_json(1, 1, 0),
+            OutputFormat::Nextest,
+            Some(&sha),
+        )
+        .expect("test ingestion should succeed");
+    let run_id = ingestor
+        .database()
+        .list_test_runs(None, Some(&sha), 1)
+        .expect("list runs")[0]
+        .id
+        .clone();
+
+    let inserted = ingestor
+        .ingest_ci_annotations(repo.path(), CI_LOG, &run_id)
+        .expect("annotation ingestion should succeed");
+    assert_eq!(inserted, 3);
+
+    let conn = ingestor.database().connection();
+    let annotations = get_ci_annotations_for_run(conn, &run_id).expect("annotation query");
+    assert_eq!(annotations.len(), 3);
+    assert_eq!(annotations[0].level, AnnotationLevel::Error);
+    assert_eq!(annotations[0].file.as_deref(), Some("src/lib.rs"));
+    assert_eq!(annotations[0].line, Some(42));
+    assert_eq!(annotations[1].level, AnnotationLevel::Warning);
+
+    let commit = get_commit_with_tests(conn, &sha)
+        .expect("query")
+        .expect("commit exists");
+    assert_eq!(commit.test_runs.len(), 1);
+    assert_eq!(commit.test_runs[0].annotations, annotations);
+}
+
+#[test]
+fn test_ingest_ci_annotations_unknown_workspace_or_run() {
+    let mut ingestor = Ingestor::new(test_database());
+    let temp = TempTestDir::new("ingest_ci_annotations_errors");
+
+    let result = ingestor.ingest_ci_annotations(temp.path(), CI_LOG, "no-such-run");
+    assert!(matches!(result, Err(IngestError::WorkspaceNotFound { .. })));
+
+    ingestor
+        .ingest_tests(
+            temp.path(),
+            &sample_nextest_json(1, 0, 0),
+            OutputFormat::Nextest,
+            None,
+        )
+        .expect("test ingestion should succeed");
+    let result = ingestor.ingest_ci_annotations(temp.path(), CI_LOG, "no-such-run");
+    assert!(matches!(result, Err(IngestError::Database(_))));
+}
+
+// ============================================================================
+// Git Notes Ingestion Tests
+// ============================================================================
+
+#[test]
+fn test_ingest_git_notes_searchable() {
+    use hindsight_mcp::queries::search_notes;
+
+    let mut repo = TestGitRepo::new("ingest_git_notes");
+    repo.init();
+    let sha = repo.create_and_commit("src/lib.rs", "pub fn f() {}", "Add lib");
+    repo.add_note(&sha, "ci", "pipeline flaky on windows runner");
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("git ingestion should succeed");
+    let stats = ingestor
+        .ingest_git_notes(repo.path(), Some("refs/notes/ci"))
+        .expect("notes ingestion should succeed");
+
+    assert_eq!(stats.notes_inserted, 1);
+
+    let results = search_notes(ingestor.database().connection(), "flaky", 10).expect("search");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].result_type, "note");
+    assert_eq!(results[0].source_info.as_deref(), Some(sha.as_str()));
+}
+
+#[test]
+fn test_ingest_git_records_tags() {
+    use hindsight_mcp::queries::get_tags;
+
+    let mut repo = TestGitRepo::new("ingest_git_tags");
+    repo.init();
+    repo.create_and_commit("src/lib.rs", "pub fn f() {}", "Add lib");
+    repo.tag("v0.1.0");
+    let head = repo.create_and_commit("src/lib.rs", "pub fn g() {}", "Rename f");
+    repo.tag("v0.2.0");
+
+    let mut ingestor = Ingestor::new(test_database());
+    let stats = ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("git ingestion should succeed");
+    assert_eq!(stats.tags_inserted, 2);
+
+    let tags = get_tags(ingestor.database().connection(), None, 10).expect("tags");
+    let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+    assert!(names.contains(&"v0.1.0") && names.contains(&"v0.2.0"));
+    let latest = tags.iter().find(|t| t.name == "v0.2.0").expect("v0.2.0");
+    assert_eq!(latest.tagged_sha, head);
+    assert_eq!(latest.commit_subject, "Rename f");
+
+    // Re-reading tags updates them in place
+    let stats = ingestor.ingest_tags(repo.path()).expect("tag ingestion");
+    assert_eq!(stats.tags_inserted, 2);
+    assert_eq!(ingestor.database().count("tags").expect("count"), 2);
+}
+
+#[test]
+fn test_ingest_git_notes_skips_unknown_commits() {
+    let mut repo = TestGitRepo::new("ingest_git_notes_skip");
+    repo.init();
+    let sha = repo.create_and_commit("README.md", "# readme", "Add readme");
+    repo.add_note(&sha, "commits", "reviewed");
+
+    // Notes are only attached to commits already in the database
+    let mut ingestor = Ingestor::new(test_database());
+    let stats = ingestor
+        .ingest_git_notes(repo.path(), None)
+        .expect("notes ingestion should succeed");
+
+    assert_eq!(stats.notes_inserted, 0);
+}
+
+// ============================================================================
+// All-Workspace Ingestion Tests
+// ============================================================================
+
+#[test]
+fn test_ingest_all_workspaces() {
+    let mut first = TestGitRepo::new("ingest_all_ws_first");
+    first.init();
+    first.create_file("a.txt", "a\n");
+    first.commit("First repo commit");
+
+    let mut second = TestGitRepo::new("ingest_all_ws_second");
+    second.init();
+    second.create_file("b.txt", "b\n");
+    second.commit("Second repo commit 1");
+    second.create_file("b.txt", "bb\n");
+    second.commit("Second repo commit 2");
+
+    let not_a_repo = TempTestDir::new("ingest_all_ws_not_a_repo");
+
+    let db = test_database();
+    let first_id = db
+        .get_or_create_workspace("first", &first.path().display().to_string())
+        .expect("workspace");
+    let second_id = db
+        .get_or_create_workspace("second", &second.path().display().to_string())
+        .expect("workspace");
+    let broken_id = db
+        .get_or_create_workspace("broken", &not_a_repo.path().display().to_string())
+        .expect("workspace");
+    let missing_id = db
+        .get_or_create_workspace("gone", "/nonexistent/path/for/hindsight")
+        .expect("workspace");
+
+    let mut ingestor = Ingestor::new(db);
+    let all_stats = ingestor
+        .ingest_all_workspaces(&IngestOptions::incremental())
+        .expect("ingestion should succeed");
+
+    assert_eq!(all_stats.len(), 3, "missing paths are skipped");
+    assert!(!all_stats.contains_key(&missing_id));
+    assert_eq!(all_stats[&first_id].commits_inserted, 1);
+    assert_eq!(all_stats[&second_id].commits_inserted, 2);
+
+    // Git failing for one workspace is recorded, not fatal
+    assert!(all_stats[&broken_id].warnings >= 1);
+    assert_eq!(all_stats[&broken_id].commits_inserted, 0);
+}
+
+#[test]
+fn test_ingest_all_applies_retention_policy() {
+    let mut repo = TestGitRepo::new("ingest_all_retention");
+    repo.init();
+    repo.create_commits(4);
+
+    let db = test_database();
+    let ws_id = db
+        .get_or_create_workspace("retention", &repo.path().display().to_string())
+        .expect("workspace");
+    db.set_retention_policy(
+        &ws_id,
+        IngestSource::Git,
+        RetentionPolicy::default().with_max_records(2),
+    )
+    .expect("set policy");
+
+    let mut ingestor = Ingestor::new(db);
+    let stats = ingestor
+        .ingest_all(repo.path(), &IngestOptions::incremental())
+        .expect("ingestion should succeed");
+    assert_eq!(stats.commits_inserted, 4);
+
+    // Commits made within the same second share a timestamp, so only the
+    // number kept is deterministic
+    let db = ingestor.into_database();
+    assert_eq!(db.count("commits").expect("count"), 2);
+}
+
+#[test]
+fn test_ingest_git_bundle() {
+    let mut repo = TestGitRepo::new("ingest_git_bundle_source");
+    repo.init();
+    let shas = repo.create_commits(3);
+
+    let out = TempTestDir::new("ingest_git_bundle_out");
+    let bundle = out.path().join("repo.bundle");
+    repo.create_bundle(&bundle);
+
+    let mut ingestor = Ingestor::new(test_database());
+    let stats = ingestor
+        .ingest_git_bundle(&bundle, "/ci/checkouts/project", &IngestOptions::default())
+        .expect("bundle ingestion should succeed");
+    assert_eq!(stats.commits_inserted, 3);
+
+    let db = ingestor.into_database();
+    let ws_id = db
+        .get_workspace_id("/ci/checkouts/project")
+        .expect("lookup")
+        .expect("workspace registered under the given path");
+    for sha in &shas {
+        assert!(db.get_commit_by_sha(&ws_id, sha).is_ok());
+    }
+}
+
+#[test]
+fn test_ingest_git_bundle_invalid() {
+    let out = TempTestDir::new("ingest_git_bundle_invalid");
+    let not_a_bundle = out.create_file("repo.bundle", "not a bundle\n");
+    let mut ingestor = Ingestor::new(test_database());
+
+    for path in [not_a_bundle, out.path().join("missing.bundle")] {
+        let result = ingestor.ingest_git_bundle(&path, "/ci/project", &IngestOptions::default());
+        assert!(
+            matches!(result, Err(IngestError::Bundle { .. })),
+            "{}: {result:?}",
+            path.display()
+        );
+    }
+    let db = ingestor.into_database();
+    assert_eq!(db.get_workspace_id("/ci/project").expect("lookup"), None);
+}
+
+// ============================================================================
+// Resumable Ingestion Tests
+// ============================================================================
+
+#[test]
+fn test_ingest_git_resumes_from_checkpoint() {
+    let mut repo = TestGitRepo::new("ingest_git_resume_checkpoint");
+    repo.init();
+    let shas = repo.create_commits(6);
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::incremental())
+        .expect("git ingestion should succeed");
+    let db = ingestor.into_database();
+    let path = repo.path().display().to_string();
+    let ws_id = db
+        .get_or_create_workspace("ignored", &path)
+        .expect("workspace");
+    assert_eq!(
+        db.get_checkpoint(&ws_id, IngestSource::Git).expect("get"),
+        None,
+        "a completed ingestion leaves no checkpoint"
+    );
+
+    // Simulate a newest-first ingestion killed after storing shas[3..]
+    db.connection()
+        .execute(
+            "DELETE FROM commits WHERE sha IN (?1, ?2, ?3)",
+            [&shas[0], &shas[1], &shas[2]],
+        )
+        .expect("delete");
+
+    // Without a checkpoint the older history is never revisited
+    let mut ingestor = Ingestor::new(db);
+    let stats = ingestor
+        .ingest_git(repo.path(), &IngestOptions::incremental())
+        .expect("git ingestion should succeed");
+    assert_eq!(stats.commits_inserted, 0);
+
+    let db = ingestor.into_database();
+    let tx = db.connection().unchecked_transaction().expect("tx");
+    Database::set_checkpoint_in_tx(&tx, &ws_id, IngestSource::Git, &shas[3]).expect("set");
+    tx.commit().expect("commit");
+
+    let mut ingestor = Ingestor::new(db);
+    let stats = ingestor
+        .ingest_git(repo.path(), &IngestOptions::incremental())
+        .expect("git ingestion should succeed");
+    assert_eq!(stats.commits_inserted, 3);
+    assert_eq!(ingestor.database().count("commits").expect("count"), 6);
+    assert_eq!(
+        ingestor
+            .database()
+            .get_checkpoint(&ws_id, IngestSource::Git)
+            .expect("get"),
+        None
+    );
+}
+
+// ============================================================================
+// Commit Diff Tests
+// ============================================================================
+
+#[test]
+fn test_diff_reads_patches_from_repository() {
+    let mut repo = TestGitRepo::new("diff_tool_patches");
+    repo.init();
+    repo.create_file("notes.txt", "first line\n");
+    repo.commit("Add notes");
+    repo.create_file("notes.txt", "first line\nsecond line\n");
+    let sha = repo.commit("Extend notes");
+
+    let mut ingestor = Ingestor::new(test_database());
+    ingestor
+        .ingest_git(repo.path(), &IngestOptions::full())
+        .expect("git ingestion should succeed");
+    let db = ingestor.into_database();
+
+    let args = serde_json::json!({ "sha": &sha[..12] });
+    let diff = handle_diff(&db, args.as_object().cloned()).expect("diff");
+    assert_eq!(diff.sha, sha);
+    assert_eq!(diff.message.trim(), "Extend notes");
+    assert_eq!(diff.files.len(), 1);
+    let file = &diff.files[0];
+    assert_eq!(file.path, "notes.txt");
+    assert_eq!((file.additions, file.deletions), (1, 0));
+    let patch = file
+        .patch
+        .as_deref()
+        .expect("patch from the live repository");
+    assert!(patch.contains("+second line"), "{patch}");
+
+    // Once the checkout is gone, only the stored line counts remain
+    drop(repo);
+    let diff = handle_diff(&db, args.as_object().cloned()).expect("stored diff");
+    assert_eq!(diff.files.len(), 1);
+    assert_eq!(diff.files[0].additions, 1);
+    assert!(diff.files[0].patch.is_none());
+}
@@ -431,7 +431,12 @@ fn test_ingest_tests_with_sample_output() {
         .parent()
         .unwrap();
 
-    let result = ingestor.ingest_tests(workspace_path, nextest_output, None);
+    let result = ingestor.ingest_tests(
+        workspace_path,
+        nextest_output,
+        hindsight_tests::OutputFormat::Nextest,
+        None,
+    );
 
     // Note: This may fail if the JSON doesn't match nextest's exact format
     // The test documents the expected behavior
@@ -926,7 +931,7 @@ fn test_e2e_full_pipeline_validation() {
         .expect("Git ingestion failed");
 
     // Try to ingest Copilot sessions (may not find any)
-    let copilot_stats = ingestor.ingest_copilot(repo_path);
+    let copilot_stats = ingestor.ingest_copilot(repo_path, &IngestOptions::default(), None);
     let copilot_sessions = match copilot_stats {
         Ok(stats) => stats.sessions_inserted,
         Err(_) => 0, // Expected if no Copilot sessions available
@@ -1114,7 +1119,7 @@ fn test_handler_commit_details_not_found() {
     let db = Database::in_memory().expect("Failed to create database");
     db.initialize().expect("Failed to initialize");
 
-    let args = serde_json::json!({ "sha": "nonexistent123" });
+    let args = serde_json::json!({ "sha": "deadbeef123" });
     let args_map: serde_json::Map<String, serde_json::Value> =
         serde_json::from_value(args).expect("parse args");
 
@@ -1123,7 +1128,7 @@ fn test_handler_commit_details_not_found() {
     assert!(result.is_err());
     match result {
         Err(HandlerError::NotFound(msg)) => {
-            assert!(msg.contains("nonexistent123"));
+            assert!(msg.contains("deadbeef123"));
         }
         Err(e) => panic!("Expected NotFound error, got: {:?}", e),
         Ok(_) => panic!("Expected error for nonexistent commit"),
@@ -1476,6 +1481,78 @@ fn test_subcommand_stdin_dry_run() {
     assert!(stdout.contains("Test Summary"));
 }
 
+/// Test that --progress-json writes ingestion progress as JSON lines on stderr
+#[test]
+fn test_subcommand_stdin_progress_json() {
+    use std::fs;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let nextest_json = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "crate::test_one" }
+{ "type": "test", "name": "crate::test_one", "event": "ok", "exec_time": 0.001 }
+{ "type": "test", "event": "started", "name": "crate::test_two" }
+{ "type": "test", "name": "crate::test_two", "event": "ok", "exec_time": 0.002 }
+{ "type": "suite", "event": "ok", "passed": 2, "failed": 0, "ignored": 0 }
+"#;
+
+    let temp_dir = std::env::temp_dir().join("hindsight_test_progress_json");
+    let _ = fs::create_dir_all(&temp_dir);
+    let db_path = temp_dir.join("progress.db");
+    let _ = fs::remove_file(&db_path);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hindsight-mcp"))
+        .args([
+            "--database",
+            db_path.to_str().unwrap(),
+            "test",
+            "--stdin",
+            "--no-commit",
+            "--progress-json",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn hindsight-mcp");
+
+    {
+        let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+        stdin
+            .write_all(nextest_json.as_bytes())
+            .expect("Failed to write to stdin");
+    }
+
+    let output = child.wait_with_output().expect("Failed to wait on child");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        output.status.success(),
+        "Command failed. stderr: {}",
+        stderr
+    );
+
+    // Progress events are the only output on stderr
+    let events: Vec<serde_json::Value> = stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("progress line should be valid JSON"))
+        .collect();
+
+    assert_eq!(events.first().expect("started event")["type"], "started");
+    assert_eq!(events[0]["source"], "tests");
+    assert_eq!(events[0]["total_items"], 2);
+
+    let completed = events.last().expect("completed event");
+    assert_eq!(completed["type"], "completed");
+    assert_eq!(completed["source"], "tests");
+    assert_eq!(completed["stats"]["test_results_inserted"], 2);
+    assert!(stdout.contains("Test results: +2 in 1 runs"));
+
+    let _ = fs::remove_file(&db_path);
+    let _ = fs::remove_dir(&temp_dir);
+}
+
 /// Test that nextest check is performed (skip if nextest not installed)
 #[test]
 fn test_subcommand_nextest_check() {
@@ -361,7 +361,7 @@ mod tests {
     #[test]
     fn test_harness_commit_details_not_found() {
         let harness = test_harness();
-        let result = harness.commit_details("nonexistent");
+        let result = harness.commit_details("deadbeef");
         assert!(result.is_err());
     }
 
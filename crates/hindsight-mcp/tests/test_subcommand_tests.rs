@@ -18,7 +18,7 @@ mod test_utils;
 
 use clap::Parser;
 use fixtures::test_database;
-use hindsight_mcp::config::{Command, Config};
+use hindsight_mcp::config::{Command, Config, OutputFormat};
 use hindsight_mcp::ingest::Ingestor;
 use test_utils::{TempTestDir, sample_nextest_json};
 
@@ -45,6 +45,8 @@ fn test_subcommand_test_default_values() {
             no_commit,
             commit,
             show_output,
+            progress_json,
+            format,
             nextest_args,
         }) => {
             assert!(package.is_empty(), "package should be empty by default");
@@ -55,6 +57,12 @@ fn test_subcommand_test_default_values() {
             assert!(!no_commit, "no_commit should be false by default");
             assert!(commit.is_none(), "commit should be None by default");
             assert!(!show_output, "show_output should be false by default");
+            assert!(!progress_json, "progress_json should be false by default");
+            assert_eq!(
+                format,
+                OutputFormat::Text,
+                "format should be text by default"
+            );
             assert!(nextest_args.is_empty(), "nextest_args should be empty");
         }
         _ => panic!("expected Test command"),
@@ -611,6 +619,8 @@ fn test_subcommand_all_flags_combined() {
             no_commit,
             commit,
             show_output,
+            progress_json,
+            format,
             nextest_args,
         }) => {
             assert_eq!(package, vec!["pkg1".to_string(), "pkg2".to_string()]);
@@ -621,6 +631,8 @@ fn test_subcommand_all_flags_combined() {
             assert!(!no_commit);
             assert_eq!(commit, Some("abc123".to_string()));
             assert!(show_output);
+            assert!(!progress_json);
+            assert_eq!(format, OutputFormat::Text);
             assert_eq!(nextest_args, vec!["--retries".to_string(), "3".to_string()]);
         }
         _ => panic!("expected Test command"),
@@ -671,7 +683,12 @@ fn test_subcommand_stdin_mode_ingests_correctly() {
 
     // This simulates the ingestion that would happen in stdin mode
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, Some("abc123"))
+        .ingest_tests(
+            temp.path(),
+            &json,
+            hindsight_tests::OutputFormat::Nextest,
+            Some("abc123"),
+        )
         .expect("ingestion should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1);
@@ -699,7 +716,12 @@ fn test_subcommand_stdin_mode_no_commit_variant() {
 
     // When --no-commit is set, commit_sha is None
     let stats = ingestor
-        .ingest_tests(temp.path(), &json, None)
+        .ingest_tests(
+            temp.path(),
+            &json,
+            hindsight_tests::OutputFormat::Nextest,
+            None,
+        )
         .expect("ingestion should succeed");
 
     assert_eq!(stats.test_runs_inserted, 1);
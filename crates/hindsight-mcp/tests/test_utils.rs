@@ -170,6 +170,49 @@ impl TestGitRepo {
         self.commit(message)
     }
 
+    /// Attach a git note to a commit under `refs/notes/<namespace>`
+    #[allow(dead_code)]
+    pub fn add_note(&self, sha: &str, namespace: &str, message: &str) -> &Self {
+        run_git(
+            self.temp_dir.path(),
+            &["notes", "--ref", namespace, "add", "-m", message, sha],
+        );
+        self
+    }
+
+    /// Create a lightweight tag pointing at HEAD
+    #[allow(dead_code)]
+    pub fn tag(&self, name: &str) -> &Self {
+        run_git(self.temp_dir.path(), &["tag", name]);
+        self
+    }
+
+    /// Write every ref of this repository to a `git bundle` file
+    #[allow(dead_code)]
+    pub fn create_bundle(&self, dest: &Path) -> &Self {
+        let dest = dest.display().to_string();
+        run_git(
+            self.path(),
+            &["bundle", "create", "--quiet", &dest, "--all"],
+        );
+        self
+    }
+
+    /// Clone this repository into a new temporary directory
+    #[allow(dead_code)]
+    pub fn clone_to(&self, test_name: &str) -> TestGitRepo {
+        let clone = TestGitRepo::new(test_name);
+        let source = self.path().display().to_string();
+        let target = clone.path().display().to_string();
+        run_git(self.path(), &["clone", "--quiet", &source, &target]);
+        run_git(clone.path(), &["config", "user.email", "test@example.com"]);
+        run_git(clone.path(), &["config", "user.name", "Test Author"]);
+        TestGitRepo {
+            initialized: true,
+            ..clone
+        }
+    }
+
     /// Get the SHA of HEAD
     pub fn get_head_sha(&self) -> String {
         let output = Command::new("git")
@@ -145,6 +145,43 @@ fn test_activity_summary_counts_commits() {
     assert!(summary.commits >= 5, "Should count at least 5 commits");
 }
 
+#[test]
+fn test_activity_summary_top_contributors_ranked() {
+    let db = test_database();
+
+    let workspace = WorkspaceRecord::new("team".to_string(), "/tmp/team".to_string());
+    db.insert_workspace(&workspace).expect("insert workspace");
+
+    let base = now();
+    let authors = [("Alice", 3), ("Bob", 1), ("Carol", 2)];
+    let mut n = 0;
+    for (author, count) in authors {
+        for _ in 0..count {
+            let mut commit = sample_commit(
+                &workspace.id,
+                &format!("{n:040x}"),
+                "Team commit",
+                hours_ago(base, n + 1),
+            );
+            commit.author = author.to_string();
+            db.insert_commit(&commit).expect("insert commit");
+            n += 1;
+        }
+    }
+
+    let harness = McpTestHarness::new(db);
+    let summary = harness.activity_summary(Some(7)).expect("summary");
+
+    assert_eq!(
+        summary.top_contributors,
+        vec![
+            ("Alice".to_string(), 3),
+            ("Carol".to_string(), 2),
+            ("Bob".to_string(), 1),
+        ]
+    );
+}
+
 #[test]
 fn test_activity_summary_counts_test_runs() {
     let harness = harness_with_populated_db();
@@ -0,0 +1,286 @@
+// Copyright (c) 2026 - present Nicholas D. Crosbie
+// SPDX-License-Identifier: MIT
+
+//! Tests for MCP tool error responses
+//!
+//! Every tool handler error is reported as
+//! `{"error": {"code", "message", "details"}}`. These tests call each tool
+//! with invalid parameters and check the structure and code of the response.
+
+mod fixtures;
+mod test_utils;
+
+use fixtures::test_database;
+use serde_json::{Map, Value, json};
+use test_utils::TempTestDir;
+
+use hindsight_mcp::db::Database;
+use hindsight_mcp::handlers::{
+    self, ERR_DATABASE_ERROR, ERR_EMPTY_RESULT, ERR_INVALID_PARAMETER, ERR_WORKSPACE_NOT_FOUND,
+    HandlerError,
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn to_map(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => panic!("Expected JSON object"),
+    }
+}
+
+/// Call a read-only tool by name with the given arguments
+fn call_tool(db: &Database, tool: &str, args: Value) -> Result<Value, HandlerError> {
+    let args = Some(to_map(args));
+    match tool {
+        "hindsight_timeline" => {
+            handlers::handle_timeline(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_search" => {
+            handlers::handle_search(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_failing_tests" => {
+            handlers::handle_failing_tests(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_activity_summary" => {
+            handlers::handle_activity_summary(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_commit_details" => {
+            handlers::handle_commit_details(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_diff" => {
+            handlers::handle_diff(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_copilot_stats" => {
+            handlers::handle_copilot_stats(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_trending_topics" => {
+            handlers::handle_trending_topics(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_session_detail" => {
+            handlers::handle_session_detail(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_regressions" => {
+            handlers::handle_regressions(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_failure_streaks" => handlers::handle_failure_streaks(db, args, None)
+            .map(|v| serde_json::to_value(v).unwrap()),
+        "hindsight_flaky_tests" => {
+            handlers::handle_flaky_tests(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_slow_tests" => {
+            handlers::handle_slow_tests(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_suite_parallelism" => {
+            handlers::handle_suite_parallelism(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_commit_volume" => {
+            handlers::handle_commit_volume(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_authors" => {
+            handlers::handle_authors(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_heatmap" => {
+            handlers::handle_heatmap(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_tags" => {
+            handlers::handle_tags(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_context_usage" => {
+            handlers::handle_context_usage(db, args, None).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_annotate" => {
+            handlers::handle_annotate(db, args, None, |path| db.get_workspace_id(path))
+                .map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_query" => {
+            handlers::handle_query(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        "hindsight_suggest_queries" => handlers::handle_suggest_queries(db, args, None)
+            .map(|v| serde_json::to_value(v).unwrap()),
+        "hindsight_workspaces" => {
+            handlers::handle_workspaces(db, args).map(|v| serde_json::to_value(v).unwrap())
+        }
+        _ => panic!("Unknown tool: {tool}"),
+    }
+}
+
+/// Assert that a result failed with a well-formed error response using `code`
+fn assert_error_response(result: Result<Value, HandlerError>, code: &str, context: &str) {
+    let err = match result {
+        Ok(value) => panic!("{context}: expected an error, got {value}"),
+        Err(err) => err,
+    };
+    let response = err.to_mcp_error();
+
+    let body = response
+        .as_object()
+        .unwrap_or_else(|| panic!("{context}: response should be an object"));
+    assert_eq!(body.len(), 1, "{context}: only the error key is expected");
+
+    let error = response["error"]
+        .as_object()
+        .unwrap_or_else(|| panic!("{context}: error should be an object"));
+    assert_eq!(error["code"], code, "{context}: {response}");
+    assert!(
+        error["message"].as_str().is_some_and(|m| !m.is_empty()),
+        "{context}: message should be a non-empty string"
+    );
+    assert!(
+        error["details"].is_null() || error["details"].is_string(),
+        "{context}: details should be a string or null"
+    );
+}
+
+// ============================================================================
+// Response Structure Tests
+// ============================================================================
+
+#[test]
+fn test_format_mcp_error_structure() {
+    let response = handlers::format_mcp_error(ERR_EMPTY_RESULT, "Nothing here", Some("why"));
+
+    assert_eq!(
+        response,
+        json!({
+            "error": {
+                "code": "EMPTY_RESULT",
+                "message": "Nothing here",
+                "details": "why"
+            }
+        })
+    );
+}
+
+#[test]
+fn test_format_mcp_error_without_details() {
+    let response = handlers::format_mcp_error(ERR_INVALID_PARAMETER, "Bad input", None);
+
+    assert!(response["error"]["details"].is_null());
+}
+
+// ============================================================================
+// Invalid Parameter Tests
+// ============================================================================
+
+#[test]
+fn test_invalid_parameters_report_invalid_parameter_code() {
+    let db = test_database();
+
+    let cases = [
+        ("hindsight_timeline", json!({"limit": "many"})),
+        ("hindsight_timeline", json!({"date": "yesterday"})),
+        ("hindsight_search", json!({})),
+        ("hindsight_search", json!({"query": ""})),
+        ("hindsight_search", json!({"query": "fix AND"})),
+        ("hindsight_failing_tests", json!({"limit": -1})),
+        ("hindsight_activity_summary", json!({"days": "week"})),
+        ("hindsight_commit_details", json!({"sha": ""})),
+        ("hindsight_commit_details", json!({"sha": "ab"})),
+        ("hindsight_diff", json!({"sha": ""})),
+        (
+            "hindsight_copilot_stats",
+            json!({"cost_per_1k_tokens": -1.0}),
+        ),
+        ("hindsight_trending_topics", json!({"days": 0})),
+        ("hindsight_session_detail", json!({"session_id": ""})),
+        ("hindsight_regressions", json!({"lookback_runs": 0})),
+        ("hindsight_regressions", json!({"window_days": 0})),
+        ("hindsight_failure_streaks", json!({"min_streak": 0})),
+        ("hindsight_flaky_tests", json!({"days": 0})),
+        ("hindsight_flaky_tests", json!({"limit": -1})),
+        ("hindsight_slow_tests", json!({"top_n": -1})),
+        ("hindsight_suite_parallelism", json!({"run_id": ""})),
+        ("hindsight_commit_volume", json!({"days": 0})),
+        ("hindsight_authors", json!({"limit": -1})),
+        ("hindsight_heatmap", json!({"days": 0})),
+        ("hindsight_tags", json!({"limit": -1})),
+        ("hindsight_context_usage", json!({"days": 0})),
+        ("hindsight_annotate", json!({"event_type": "deploy"})),
+        (
+            "hindsight_annotate",
+            json!({"event_type": "deploy", "title": "x", "event_at": "soon"}),
+        ),
+        ("hindsight_query", json!({"sql": " "})),
+        ("hindsight_query", json!({"sql": "DELETE FROM commits"})),
+        ("hindsight_suggest_queries", json!({"workspace": 5})),
+        ("hindsight_workspaces", json!({"days": 0})),
+    ];
+
+    for (tool, args) in cases {
+        let context = format!("{tool} {args}");
+        assert_error_response(call_tool(&db, tool, args), ERR_INVALID_PARAMETER, &context);
+    }
+}
+
+#[test]
+fn test_missing_records_report_empty_result_code() {
+    let db = test_database();
+
+    let cases = [
+        ("hindsight_commit_details", json!({"sha": "deadbeef"})),
+        ("hindsight_diff", json!({"sha": "deadbeef"})),
+        (
+            "hindsight_session_detail",
+            json!({"session_id": "no-such-session"}),
+        ),
+    ];
+
+    for (tool, args) in cases {
+        let context = format!("{tool} {args}");
+        assert_error_response(call_tool(&db, tool, args), ERR_EMPTY_RESULT, &context);
+    }
+}
+
+#[test]
+fn test_bad_sql_reports_database_error_code() {
+    let db = test_database();
+
+    let result = call_tool(
+        &db,
+        "hindsight_query",
+        json!({"sql": "SELECT * FROM no_such_table"}),
+    );
+
+    assert_error_response(result, ERR_DATABASE_ERROR, "unknown table");
+}
+
+// ============================================================================
+// Ingest Error Tests
+// ============================================================================
+
+#[test]
+fn test_ingest_missing_workspace_reports_workspace_not_found_code() {
+    let db = test_database();
+
+    let args = to_map(json!({"workspace": "/nonexistent/path/for/hindsight"}));
+    let result = handlers::handle_ingest(db, Some(args)).map(|v| serde_json::to_value(v).unwrap());
+
+    assert_error_response(result, ERR_WORKSPACE_NOT_FOUND, "missing workspace");
+}
+
+#[test]
+fn test_ingest_non_repository_reports_workspace_not_found_code() {
+    let db = test_database();
+    let dir = TempTestDir::new("tool_error_not_a_repo");
+
+    let args = to_map(json!({
+        "workspace": dir.path().to_string_lossy(),
+        "source": "git"
+    }));
+    let result = handlers::handle_ingest(db, Some(args)).map(|v| serde_json::to_value(v).unwrap());
+
+    assert_error_response(result, ERR_WORKSPACE_NOT_FOUND, "not a repository");
+}
+
+#[test]
+fn test_ingest_missing_workspace_argument_reports_invalid_parameter_code() {
+    let db = test_database();
+
+    let result =
+        handlers::handle_ingest(db, Some(Map::new())).map(|v| serde_json::to_value(v).unwrap());
+
+    assert_error_response(result, ERR_INVALID_PARAMETER, "no workspace");
+}
@@ -8,13 +8,19 @@
 
 mod fixtures;
 mod mcp_harness;
+mod test_utils;
 
 use fixtures::test_database;
 use serde_json::{Map, Value, json};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
+use hindsight_copilot::CopilotError;
+use hindsight_mcp::db::Database;
 use hindsight_mcp::handlers::{self, HandlerError};
+use hindsight_mcp::ingest::{IngestError, ProgressEvent};
+use test_utils::{TempTestDir, TestGitRepo, sample_nextest_json};
 
 // ============================================================================
 // Helper Functions
@@ -645,6 +651,191 @@ fn test_ingest_sql_injection_attempt_in_workspace() {
     assert!(matches!(result, Err(HandlerError::WorkspaceNotFound(_))));
 }
 
+// ============================================================================
+// Source Selection Stats Tests
+// ============================================================================
+
+/// Create an initialized database file that outlives the handler call
+fn file_database(dir: &TempTestDir) -> (Database, PathBuf) {
+    let path = dir.path().join("hindsight.db");
+    let db = Database::open(&path).expect("open db");
+    db.initialize().expect("initialize db");
+    (db, path)
+}
+
+fn reopen_count(path: &Path, table: &str) -> usize {
+    Database::open(path)
+        .expect("reopen db")
+        .count(table)
+        .expect("count") as usize
+}
+
+#[test]
+fn test_ingest_git_stats_match_database() {
+    let dir = TempTestDir::new("tool_ingest_git_stats");
+    let (db, db_path) = file_database(&dir);
+    let mut repo = TestGitRepo::new("tool_ingest_git_stats_repo");
+    repo.init();
+    repo.create_commits(3);
+
+    let args = to_map(json!({
+        "workspace": repo.path().to_str().unwrap(),
+        "source": "git",
+        "include_diffs": true
+    }));
+    let result = handlers::handle_ingest(db, Some(args)).expect("ingest should succeed");
+
+    assert_eq!(result.stats.commits_inserted, 3);
+    assert_eq!(reopen_count(&db_path, "commits"), 3);
+
+    let db = Database::open(&db_path).expect("reopen db");
+    let with_diffs: i64 = db
+        .connection()
+        .query_row(
+            "SELECT COUNT(*) FROM commits WHERE diff_json IS NOT NULL",
+            [],
+            |row| row.get(0),
+        )
+        .expect("query");
+    assert_eq!(with_diffs, 3);
+}
+
+#[test]
+fn test_ingest_tests_stats_match_database() {
+    let dir = TempTestDir::new("tool_ingest_tests_stats");
+    let (db, db_path) = file_database(&dir);
+    let workspace = create_temp_workspace("tests_stats");
+
+    let args = to_map(json!({
+        "workspace": workspace.to_str().unwrap(),
+        "source": "tests",
+        "nextest_json": sample_nextest_json(3, 1, 1)
+    }));
+    let result = handlers::handle_ingest(db, Some(args)).expect("ingest should succeed");
+
+    assert_eq!(result.source, "tests");
+    assert_eq!(result.stats.test_runs_inserted, 1);
+    assert_eq!(result.stats.test_results_inserted, 5);
+    assert_eq!(reopen_count(&db_path, "test_runs"), 1);
+    assert_eq!(reopen_count(&db_path, "test_results"), 5);
+}
+
+#[test]
+fn test_ingest_tests_requires_nextest_json() {
+    let db = test_database();
+    let workspace = create_temp_workspace("tests_no_json");
+
+    let args = to_map(json!({
+        "workspace": workspace.to_str().unwrap(),
+        "source": "tests"
+    }));
+    let result = handlers::handle_ingest(db, Some(args));
+
+    assert!(matches!(result, Err(HandlerError::InvalidInput(msg)) if msg.contains("nextest_json")));
+}
+
+#[test]
+fn test_ingest_copilot_stats_match_database() {
+    let dir = TempTestDir::new("tool_ingest_copilot_stats");
+    let (db, db_path) = file_database(&dir);
+    let workspace = create_temp_workspace("copilot_stats");
+
+    let args = to_map(json!({
+        "workspace": workspace.to_str().unwrap(),
+        "source": "copilot"
+    }));
+
+    match handlers::handle_ingest(db, Some(args)) {
+        Ok(result) => {
+            assert_eq!(
+                result.stats.sessions_inserted,
+                reopen_count(&db_path, "copilot_sessions")
+            );
+            assert_eq!(
+                result.stats.messages_inserted,
+                reopen_count(&db_path, "copilot_messages")
+            );
+        }
+        // Machines without VS Code have no Copilot storage to read
+        Err(HandlerError::Ingest(IngestError::Copilot(
+            CopilotError::WorkspaceStorageNotFound { .. },
+        ))) => {}
+        Err(e) => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn test_ingest_all_includes_nextest_json() {
+    let dir = TempTestDir::new("tool_ingest_all_stats");
+    let (db, db_path) = file_database(&dir);
+    let mut repo = TestGitRepo::new("tool_ingest_all_stats_repo");
+    repo.init();
+    repo.create_commits(2);
+
+    let args = to_map(json!({
+        "workspace": repo.path().to_str().unwrap(),
+        "source": "all",
+        "nextest_json": sample_nextest_json(2, 0, 0)
+    }));
+    let result = handlers::handle_ingest(db, Some(args)).expect("ingest should succeed");
+
+    assert_eq!(result.stats.commits_inserted, 2);
+    assert_eq!(result.stats.test_runs_inserted, 1);
+    assert_eq!(reopen_count(&db_path, "commits"), 2);
+    assert_eq!(reopen_count(&db_path, "test_runs"), 1);
+}
+
+#[test]
+fn test_ingest_all_rejects_malformed_nextest_json() {
+    let db = test_database();
+    let mut repo = TestGitRepo::new("tool_ingest_all_bad_json_repo");
+    repo.init();
+    repo.create_commits(1);
+
+    let args = to_map(json!({
+        "workspace": repo.path().to_str().unwrap(),
+        "source": "all",
+        "nextest_json": "not nextest output"
+    }));
+    let result = handlers::handle_ingest(db, Some(args));
+
+    assert!(matches!(result, Err(HandlerError::Ingest(_))), "{result:?}");
+}
+
+#[test]
+fn test_ingest_reports_progress_events() {
+    let dir = TempTestDir::new("tool_ingest_progress");
+    let (db, _) = file_database(&dir);
+    let mut repo = TestGitRepo::new("tool_ingest_progress_repo");
+    repo.init();
+    repo.create_commits(2);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let args = to_map(json!({
+        "workspace": repo.path().to_str().unwrap(),
+        "source": "git"
+    }));
+    handlers::handle_ingest_with_progress(
+        db,
+        Some(args),
+        Some(Box::new(move |event: &ProgressEvent| {
+            sink.lock().unwrap().push(event.clone());
+        })),
+    )
+    .expect("ingest should succeed");
+
+    let events = events.lock().unwrap();
+    assert!(matches!(
+        events.first(),
+        Some(ProgressEvent::Started { .. })
+    ));
+    assert!(matches!(
+        events.last(),
+        Some(ProgressEvent::Completed { .. })
+    ));
+}
+
 // ============================================================================
 // Note: Ingest tool takes ownership of database
 // ============================================================================
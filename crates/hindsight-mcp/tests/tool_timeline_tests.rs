@@ -215,19 +215,23 @@ fn test_timeline_contains_commits() {
 }
 
 #[test]
-fn test_timeline_contains_copilot_messages() {
+fn test_timeline_contains_copilot_sessions() {
     let harness = harness_with_populated_db();
     let events = harness
         .timeline(None, None)
         .expect("timeline should succeed");
 
-    // The populated database has copilot messages
-    // Event type is "copilot_message" (not "copilot" or "copilot_session")
-    let has_copilot = events.iter().any(|e| e.event_type == "copilot_message");
-    assert!(
-        has_copilot,
-        "Timeline should contain copilot_message events"
+    // The populated database has one copilot session with several messages,
+    // which appears as a single "copilot_session" event
+    let copilot_events = events
+        .iter()
+        .filter(|e| e.event_type == "copilot_session")
+        .count();
+    assert_eq!(
+        copilot_events, 1,
+        "Timeline should contain one copilot_session event"
     );
+    assert!(!events.iter().any(|e| e.event_type == "copilot_message"));
 }
 
 #[test]
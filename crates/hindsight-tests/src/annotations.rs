@@ -0,0 +1,219 @@
+// Copyright (c) 2026 - present Nicholas D. Crosbie
+// SPDX-License-Identifier: MIT
+
+//! GitHub Actions annotation parsing
+//!
+//! Workflow commands such as `::error file=src/lib.rs,line=42::message` mark
+//! failures in CI logs. This module extracts `error` and `warning` commands
+//! so they can be stored alongside the test run they belong to.
+//!
+//! # Example
+//!
+//! ```
+//! use hindsight_tests::annotations::{AnnotationLevel, parse_github_annotations};
+//!
+//! let log = "::error file=src/lib.rs,line=42::assertion failed";
+//! let annotations = parse_github_annotations(log);
+//! assert_eq!(annotations[0].level, AnnotationLevel::Error);
+//! assert_eq!(annotations[0].line, Some(42));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Severity of a CI annotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationLevel {
+    /// `::error` command
+    Error,
+    /// `::warning` command
+    Warning,
+}
+
+impl AnnotationLevel {
+    /// Get the workflow command name for this level
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+impl fmt::Display for AnnotationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AnnotationLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "warning" => Ok(Self::Warning),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An error or warning annotation from a CI log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CiAnnotation {
+    /// Annotation severity
+    pub level: AnnotationLevel,
+    /// File the annotation points at, if given
+    pub file: Option<String>,
+    /// Line number in `file`, if given
+    pub line: Option<u32>,
+    /// Annotation message
+    pub message: String,
+}
+
+/// Extract `::error` and `::warning` annotations from a GitHub Actions log
+///
+/// Lines may carry the timestamp prefix that GitHub adds to downloaded logs.
+/// Other workflow commands, malformed commands and ordinary output are
+/// skipped. Escaped characters (`%25`, `%0D`, `%0A`, and `%3A`/`%2C` in
+/// properties) are decoded.
+#[must_use]
+pub fn parse_github_annotations(log: &str) -> Vec<CiAnnotation> {
+    log.lines().filter_map(parse_annotation_line).collect()
+}
+
+/// Parse a single log line as an annotation command
+fn parse_annotation_line(line: &str) -> Option<CiAnnotation> {
+    let command = strip_timestamp(line.trim_start()).strip_prefix("::")?;
+    let (head, message) = command.split_once("::")?;
+    let (name, properties) = match head.split_once(' ') {
+        Some((name, properties)) => (name, properties),
+        None => (head, ""),
+    };
+    let level = name.parse().ok()?;
+
+    let mut file = None;
+    let mut line_number = None;
+    for property in properties.split(',') {
+        match property.trim().split_once('=') {
+            Some(("file", value)) => file = Some(unescape_property(value)),
+            Some(("line", value)) => line_number = value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+
+    Some(CiAnnotation {
+        level,
+        file,
+        line: line_number,
+        message: unescape_data(message.trim_end()),
+    })
+}
+
+/// Drop a leading `2026-01-01T00:00:00.0000000Z ` timestamp, if present
+fn strip_timestamp(line: &str) -> &str {
+    match line.split_once(' ') {
+        Some((first, rest))
+            if first.starts_with(|c: char| c.is_ascii_digit()) && first.contains('T') =>
+        {
+            rest.trim_start()
+        }
+        _ => line,
+    }
+}
+
+/// Decode an escaped annotation message
+fn unescape_data(value: &str) -> String {
+    value
+        .replace("%0D", "\r")
+        .replace("%0A", "\n")
+        .replace("%25", "%")
+}
+
+/// Decode an escaped annotation property value
+fn unescape_property(value: &str) -> String {
+    value
+        .replace("%0D", "\r")
+        .replace("%0A", "\n")
+        .replace("%3A", ":")
+        .replace("%2C", ",")
+        .replace("%25", "%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn test_parse_multiple_annotations() {
+        let log = "\
+Running tests
+::error file=src/lib.rs,line=42,col=5::assertion `left == right` failed
+some ordinary output
+::warning file=src/db.rs,line=7::unused variable: `x`
+::error::process completed with exit code 101
+::group::Test output
+";
+        let annotations = parse_github_annotations(log);
+
+        assert_eq!(
+            annotations,
+            vec![
+                CiAnnotation {
+                    level: AnnotationLevel::Error,
+                    file: Some("src/lib.rs".to_string()),
+                    line: Some(42),
+                    message: "assertion `left == right` failed".to_string(),
+                },
+                CiAnnotation {
+                    level: AnnotationLevel::Warning,
+                    file: Some("src/db.rs".to_string()),
+                    line: Some(7),
+                    message: "unused variable: `x`".to_string(),
+                },
+                CiAnnotation {
+                    level: AnnotationLevel::Error,
+                    file: None,
+                    line: None,
+                    message: "process completed with exit code 101".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamped_and_escaped_annotation() {
+        let log =
+            "2026-03-01T12:00:00.1234567Z ::error file=src/a%2Cb.rs,line=3::first%0Asecond 100%25";
+        let annotations = parse_github_annotations(log);
+
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].file.as_deref(), Some("src/a,b.rs"));
+        assert_eq!(annotations[0].message, "first\nsecond 100%");
+    }
+
+    #[test]
+    fn test_rust_paths_are_not_annotations() {
+        let log = "error[E0277]: `std::error::Error` is not implemented\n  --> ::warning::";
+        assert!(parse_github_annotations(log).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_line_number_is_dropped() {
+        let annotations = parse_github_annotations("::warning file=x.rs,line=abc::careful");
+        assert_eq!(annotations[0].file.as_deref(), Some("x.rs"));
+        assert_eq!(annotations[0].line, None);
+    }
+
+    #[test]
+    fn test_annotation_level_round_trip() {
+        for level in [AnnotationLevel::Error, AnnotationLevel::Warning] {
+            assert_eq!(level.as_str().parse::<AnnotationLevel>(), Ok(level));
+        }
+        assert!("notice".parse::<AnnotationLevel>().is_err());
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (c) 2026 - present Nicholas D. Crosbie
+// SPDX-License-Identifier: MIT
+
+//! `cargo test` JSON output parsing
+//!
+//! On nightly, `cargo test -- -Z unstable-options --format json` prints
+//! libtest's own JSON events. They have the same shape as the events nextest
+//! emits, with a few differences:
+//!
+//! - Test names are not prefixed with the test binary
+//! - Every test binary reports its own `suite` events, so one run holds
+//!   several suites
+//! - Other event types, such as `bench`, may appear and are skipped
+//!
+//! # Example
+//!
+//! ```
+//! use hindsight_tests::cargo_test::parse_cargo_test_output;
+//!
+//! let output = r#"{"type":"suite","event":"started","test_count":1}
+//! {"type":"test","event":"started","name":"tests::it_works"}
+//! {"type":"test","name":"tests::it_works","event":"ok","exec_time":0.001}
+//! {"type":"suite","event":"ok","passed":1,"failed":0,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.002}"#;
+//!
+//! let summary = parse_cargo_test_output(output).unwrap();
+//! assert_eq!(summary.passed, 1);
+//! assert_eq!(summary.results[0].name, "tests::it_works");
+//! ```
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::TestsError;
+use crate::nextest::{LibtestEvent, TestRunSummary, malformed_json, parse_run_output};
+
+/// Test runner that produced a JSON test output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// `cargo nextest run --message-format libtest-json`
+    #[default]
+    Nextest,
+    /// `cargo test -- -Z unstable-options --format json`
+    CargoTest,
+}
+
+impl OutputFormat {
+    /// Parse test run output written in this format
+    ///
+    /// # Errors
+    ///
+    /// Returns `TestsError::EmptyInput` if the output is empty or
+    /// whitespace-only, or `TestsError::MalformedJson` if a line is invalid.
+    pub fn parse(self, output: &str) -> Result<TestRunSummary, TestsError> {
+        match self {
+            Self::Nextest => parse_run_output(output),
+            Self::CargoTest => parse_cargo_test_output(output),
+        }
+    }
+}
+
+/// Parse `cargo test -- -Z unstable-options --format json` output
+///
+/// The output is newline-delimited JSON. Suite totals and execution times
+/// are summed across test binaries, and test names are kept as reported.
+///
+/// # Errors
+///
+/// Returns `TestsError::EmptyInput` if the output is empty or whitespace-only,
+/// or `TestsError::MalformedJson` if a line is invalid JSON or a malformed
+/// `suite` or `test` event.
+pub fn parse_cargo_test_output(output: &str) -> Result<TestRunSummary, TestsError> {
+    if output.trim().is_empty() {
+        return Err(TestsError::EmptyInput);
+    }
+
+    let mut summary = TestRunSummary::empty();
+    let now = Utc::now();
+
+    for (index, line) in output.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let value: Value =
+            serde_json::from_str(line).map_err(|e| malformed_json(index + 1, line, &e))?;
+        if !matches!(
+            value.get("type").and_then(Value::as_str),
+            Some("suite" | "test")
+        ) {
+            continue;
+        }
+        let event: LibtestEvent =
+            serde_json::from_value(value).map_err(|e| malformed_json(index + 1, line, &e))?;
+
+        match event {
+            LibtestEvent::SuiteStarted { test_count } => {
+                summary.total += test_count as usize;
+            }
+            LibtestEvent::SuiteFinished {
+                passed,
+                failed,
+                ignored,
+                exec_time,
+            } => {
+                summary.passed += passed as usize;
+                summary.failed += failed as usize;
+                summary.ignored += ignored as usize;
+                summary.exec_time_secs += exec_time.unwrap_or(0.0);
+            }
+            LibtestEvent::TestStarted { .. } => {}
+            event => summary.results.extend(event.into_unnormalized_result(now)),
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::TestOutcome;
+    use similar_asserts::assert_eq;
+
+    /// Two test binaries, one with a failure, plus a benchmark event
+    const TWO_SUITES: &str = r#"
+{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "parser::tests::parses" }
+{ "type": "test", "name": "parser::tests::parses", "event": "ok", "exec_time": 0.25 }
+{ "type": "test", "event": "started", "name": "parser::tests::rejects" }
+{ "type": "test", "name": "parser::tests::rejects", "event": "failed", "exec_time": 0.5, "stdout": "thread 'parser::tests::rejects' panicked" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.75 }
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "event": "started", "name": "slow" }
+{ "type": "test", "event": "ignored", "name": "slow" }
+{ "type": "bench", "name": "bench_parse", "median": 120, "deviation": 4 }
+{ "type": "suite", "event": "ok", "passed": 0, "failed": 0, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.25 }
+"#;
+
+    #[test]
+    fn test_parse_cargo_test_output_sums_suites() {
+        let summary = parse_cargo_test_output(TWO_SUITES).expect("parse");
+
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.passed, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.ignored, 1);
+        assert!((summary.exec_time_secs - 1.0).abs() < f64::EPSILON);
+
+        let names: Vec<&str> = summary.results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["parser::tests::parses", "parser::tests::rejects", "slow"]
+        );
+    }
+
+    #[test]
+    fn test_parse_cargo_test_output_failure_details() {
+        let summary = parse_cargo_test_output(TWO_SUITES).expect("parse");
+        let failing = summary.failing_tests();
+
+        assert_eq!(failing.len(), 1);
+        assert_eq!(failing[0].outcome, TestOutcome::Failed);
+        assert_eq!(failing[0].duration_ms, 500);
+        assert_eq!(
+            failing[0].output.as_deref(),
+            Some("thread 'parser::tests::rejects' panicked")
+        );
+    }
+
+    #[test]
+    fn test_parse_cargo_test_output_errors() {
+        assert!(matches!(
+            parse_cargo_test_output("  \n"),
+            Err(TestsError::EmptyInput)
+        ));
+        assert!(matches!(
+            parse_cargo_test_output("{\"type\":\"suite\"\nnot json"),
+            Err(TestsError::MalformedJson { line: 1, .. })
+        ));
+        // A test event without a name is malformed, not skipped
+        assert!(matches!(
+            parse_cargo_test_output(r#"{"type":"test","event":"ok"}"#),
+            Err(TestsError::MalformedJson { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_output_format_selects_parser() {
+        let line = r#"{"type":"test","name":"bin::tests::a","event":"ok","exec_time":0.0}"#;
+
+        let nextest = OutputFormat::Nextest.parse(line).expect("parse");
+        assert_eq!(nextest.results[0].name, "tests::a");
+
+        let cargo = OutputFormat::CargoTest.parse(line).expect("parse");
+        assert_eq!(cargo.results[0].name, "bin::tests::a");
+
+        assert_eq!(OutputFormat::default(), OutputFormat::Nextest);
+        assert_eq!(
+            serde_json::to_string(&OutputFormat::CargoTest).expect("serialize"),
+            "\"cargo_test\""
+        );
+    }
+}
@@ -22,4 +22,54 @@ pub enum TestsError {
         /// Description of the format error
         message: String,
     },
+
+    /// Test output was empty or contained only whitespace
+    #[error("Empty test output")]
+    EmptyInput,
+
+    /// A line of test output was not valid JSON
+    #[error("Malformed JSON on line {line}: {context}")]
+    MalformedJson {
+        /// 1-based line number of the offending line
+        line: usize,
+        /// The offending line, truncated, followed by the parser's message
+        context: String,
+    },
+}
+
+impl TestsError {
+    /// Format this error and every underlying cause, separated by `" → "`
+    #[must_use]
+    pub fn display_chain(&self) -> String {
+        hindsight_common::display_chain(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn test_display_chain_nested() {
+        let err = TestsError::Io(std::io::Error::other("permission denied"));
+        assert_eq!(err.display_chain(), "IO error → permission denied");
+    }
+
+    #[test]
+    fn test_display_chain_json() {
+        let json_err = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let expected = format!("JSON parse error → {json_err}");
+        let err = TestsError::JsonParse(json_err);
+        assert_eq!(err.display_chain(), expected);
+    }
+
+    #[test]
+    fn test_malformed_json_display() {
+        let err = TestsError::MalformedJson {
+            line: 3,
+            context: "not json".to_string(),
+        };
+        assert_eq!(err.to_string(), "Malformed JSON on line 3: not json");
+    }
 }
@@ -4,7 +4,8 @@
 //! hindsight-tests: Test log processing for hindsight-mcp
 //!
 //! This library crate provides functionality to parse and process test results
-//! (particularly from cargo-nextest) for consumption by the hindsight-mcp server.
+//! (particularly from cargo-nextest, or `cargo test` JSON output on nightly) for
+//! consumption by the hindsight-mcp server.
 
 #![warn(missing_docs)]
 
@@ -22,13 +23,18 @@
 //! parser.process_line(output).unwrap();
 //! ```
 
+pub mod annotations;
+pub mod cargo_test;
 pub mod error;
 pub mod nextest;
 pub mod result;
 
+pub use annotations::{AnnotationLevel, CiAnnotation, parse_github_annotations};
+pub use cargo_test::{OutputFormat, parse_cargo_test_output};
 pub use error::TestsError;
 pub use nextest::{
-    LibtestEvent, StreamingParser, TestList, TestRunSummary, TestSuite, parse_list_output,
+    EventWindow, LibtestEvent, StreamingParser, TestList, TestRunSummary, TestSuite,
+    TestSuiteSummary, parse_event_windows, parse_libtest_event, parse_list_output,
     parse_run_output,
 };
 pub use result::{TestOutcome, TestResult};
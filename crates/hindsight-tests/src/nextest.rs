@@ -91,6 +91,15 @@ impl TestList {
             .unwrap_or_default()
     }
 
+    /// Count tests in each suite, keyed by binary ID
+    #[must_use]
+    pub fn count_by_suite(&self) -> HashMap<String, usize> {
+        self.rust_suites
+            .iter()
+            .map(|(suite_id, suite)| (suite_id.clone(), suite.testcases.len()))
+            .collect()
+    }
+
     /// Count ignored tests
     #[must_use]
     pub fn ignored_count(&self) -> usize {
@@ -107,43 +116,229 @@ impl TestList {
 // ============================================================================
 
 /// A single event from libtest JSON output
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "lowercase")]
+///
+/// On the wire each event is an object tagged by `type` (`suite` or `test`)
+/// and `event`. Unrecognised test events are read as failures, matching how
+/// libtest reports unexpected outcomes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawLibtestEvent", into = "RawLibtestEvent")]
 pub enum LibtestEvent {
-    /// Suite started event
-    Suite(SuiteEvent),
-    /// Test event (started, ok, failed, ignored)
-    Test(TestEvent),
+    /// A test binary started running
+    SuiteStarted {
+        /// Number of tests the binary will run
+        test_count: u32,
+    },
+    /// A test binary finished running
+    SuiteFinished {
+        /// Number of passed tests
+        passed: u32,
+        /// Number of failed tests
+        failed: u32,
+        /// Number of ignored tests
+        ignored: u32,
+        /// Total execution time in seconds, if reported
+        exec_time: Option<f64>,
+    },
+    /// A test started running
+    TestStarted {
+        /// Full test name including binary
+        name: String,
+    },
+    /// A test passed
+    TestPassed {
+        /// Full test name including binary
+        name: String,
+        /// Execution time in seconds
+        exec_time: f64,
+    },
+    /// A test failed
+    TestFailed {
+        /// Full test name including binary
+        name: String,
+        /// Execution time in seconds
+        exec_time: f64,
+        /// Captured stdout, if any
+        stdout: Option<String>,
+        /// Captured stderr, if any
+        stderr: Option<String>,
+    },
+    /// A test was ignored
+    TestIgnored {
+        /// Full test name including binary
+        name: String,
+    },
+    /// A test exceeded its time limit
+    TestTimeout {
+        /// Full test name including binary
+        name: String,
+    },
 }
 
-/// Suite-level event
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SuiteEvent {
-    /// Event type: "started" or "ok"/"failed"
-    pub event: String,
-    /// Number of tests (only in "started" event)
-    pub test_count: Option<usize>,
-    /// Number of passed tests (in final event)
-    pub passed: Option<usize>,
-    /// Number of failed tests (in final event)
-    pub failed: Option<usize>,
-    /// Number of ignored tests (in final event)
-    pub ignored: Option<usize>,
-    /// Execution time (in final event)
-    pub exec_time: Option<f64>,
+impl LibtestEvent {
+    /// Full test name for test events, `None` for suite events
+    #[must_use]
+    pub fn test_name(&self) -> Option<&str> {
+        match self {
+            Self::TestStarted { name }
+            | Self::TestPassed { name, .. }
+            | Self::TestFailed { name, .. }
+            | Self::TestIgnored { name }
+            | Self::TestTimeout { name } => Some(name),
+            Self::SuiteStarted { .. } | Self::SuiteFinished { .. } => None,
+        }
+    }
+
+    /// Convert a finished-test event into a [`TestResult`]
+    ///
+    /// Returns `None` for suite events and `TestStarted`.
+    fn into_result(self, timestamp: chrono::DateTime<Utc>) -> Option<TestResult> {
+        let mut result = self.into_unnormalized_result(timestamp)?;
+        result.name = normalize_test_name(&result.name);
+        Some(result)
+    }
+
+    /// Convert a finished-test event into a [`TestResult`], keeping the name as is
+    ///
+    /// Returns `None` for suite events and `TestStarted`.
+    pub(crate) fn into_unnormalized_result(
+        self,
+        timestamp: chrono::DateTime<Utc>,
+    ) -> Option<TestResult> {
+        let (name, outcome, exec_time, output) = match self {
+            Self::TestPassed { name, exec_time } => (name, TestOutcome::Passed, exec_time, None),
+            Self::TestFailed {
+                name,
+                exec_time,
+                stdout,
+                stderr,
+            } => (name, TestOutcome::Failed, exec_time, stdout.or(stderr)),
+            Self::TestIgnored { name } => (name, TestOutcome::Ignored, 0.0, None),
+            Self::TestTimeout { name } => (name, TestOutcome::TimedOut, 0.0, None),
+            Self::SuiteStarted { .. } | Self::SuiteFinished { .. } | Self::TestStarted { .. } => {
+                return None;
+            }
+        };
+
+        Some(TestResult {
+            name,
+            outcome,
+            duration_ms: (exec_time * 1000.0) as u64,
+            timestamp,
+            output,
+        })
+    }
 }
 
-/// Test-level event
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TestEvent {
-    /// Event type: "started", "ok", "failed", "ignored"
-    pub event: String,
-    /// Full test name including binary
-    pub name: String,
-    /// Execution time in seconds (only in finished events)
-    pub exec_time: Option<f64>,
-    /// Stdout output (only in failed events)
-    pub stdout: Option<String>,
+/// Wire representation of a libtest JSON event
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawLibtestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    test_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    passed: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failed: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ignored: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exec_time: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stdout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stderr: Option<String>,
+}
+
+impl TryFrom<RawLibtestEvent> for LibtestEvent {
+    type Error = String;
+
+    fn try_from(raw: RawLibtestEvent) -> Result<Self, Self::Error> {
+        match raw.kind.as_str() {
+            "suite" if raw.event == "started" => Ok(Self::SuiteStarted {
+                test_count: raw.test_count.unwrap_or(0),
+            }),
+            "suite" => Ok(Self::SuiteFinished {
+                passed: raw.passed.unwrap_or(0),
+                failed: raw.failed.unwrap_or(0),
+                ignored: raw.ignored.unwrap_or(0),
+                exec_time: raw.exec_time,
+            }),
+            "test" => {
+                let name = raw.name.ok_or("test event is missing `name`")?;
+                let exec_time = raw.exec_time.unwrap_or(0.0);
+                Ok(match raw.event.as_str() {
+                    "started" => Self::TestStarted { name },
+                    "ok" => Self::TestPassed { name, exec_time },
+                    "ignored" => Self::TestIgnored { name },
+                    "timeout" => Self::TestTimeout { name },
+                    _ => Self::TestFailed {
+                        name,
+                        exec_time,
+                        stdout: raw.stdout,
+                        stderr: raw.stderr,
+                    },
+                })
+            }
+            other => Err(format!("unknown event type `{other}`")),
+        }
+    }
+}
+
+impl From<LibtestEvent> for RawLibtestEvent {
+    fn from(event: LibtestEvent) -> Self {
+        let suite = |event: &str| Self {
+            kind: "suite".to_string(),
+            event: event.to_string(),
+            ..Self::default()
+        };
+        let test = |event: &str, name: String| Self {
+            kind: "test".to_string(),
+            event: event.to_string(),
+            name: Some(name),
+            ..Self::default()
+        };
+
+        match event {
+            LibtestEvent::SuiteStarted { test_count } => Self {
+                test_count: Some(test_count),
+                ..suite("started")
+            },
+            LibtestEvent::SuiteFinished {
+                passed,
+                failed,
+                ignored,
+                exec_time,
+            } => Self {
+                passed: Some(passed),
+                failed: Some(failed),
+                ignored: Some(ignored),
+                exec_time,
+                ..suite(if failed > 0 { "failed" } else { "ok" })
+            },
+            LibtestEvent::TestStarted { name } => test("started", name),
+            LibtestEvent::TestPassed { name, exec_time } => Self {
+                exec_time: Some(exec_time),
+                ..test("ok", name)
+            },
+            LibtestEvent::TestFailed {
+                name,
+                exec_time,
+                stdout,
+                stderr,
+            } => Self {
+                exec_time: Some(exec_time),
+                stdout,
+                stderr,
+                ..test("failed", name)
+            },
+            LibtestEvent::TestIgnored { name } => test("ignored", name),
+            LibtestEvent::TestTimeout { name } => test("timeout", name),
+        }
+    }
 }
 
 /// Aggregated results from a test run
@@ -188,6 +383,48 @@ impl TestRunSummary {
     pub fn failing_tests(&self) -> Vec<&TestResult> {
         self.results.iter().filter(|r| r.failed()).collect()
     }
+
+    /// Aggregate results per suite, keyed by suite name
+    ///
+    /// The suite is the part of a test name before the first `::`; tests
+    /// without one are grouped under an empty name. Timed out tests count as
+    /// failed.
+    #[must_use]
+    pub fn suites(&self) -> HashMap<&str, TestSuiteSummary> {
+        let mut suites: HashMap<&str, TestSuiteSummary> = HashMap::new();
+        for result in &self.results {
+            let name = result.name.split_once("::").map_or("", |(suite, _)| suite);
+            let suite = suites.entry(name).or_insert_with(|| TestSuiteSummary {
+                name: name.to_string(),
+                ..TestSuiteSummary::default()
+            });
+            match result.outcome {
+                TestOutcome::Passed => suite.passed += 1,
+                TestOutcome::Failed | TestOutcome::TimedOut => suite.failed += 1,
+                TestOutcome::Ignored => suite.ignored += 1,
+            }
+            suite.total_duration_ms += result.duration_ms;
+        }
+        suites
+    }
+}
+
+/// Per-suite totals for a test run
+///
+/// Named to avoid clashing with [`TestSuite`], which describes a test binary
+/// in `nextest list` output.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestSuiteSummary {
+    /// Suite name
+    pub name: String,
+    /// Tests passed
+    pub passed: u32,
+    /// Tests failed or timed out
+    pub failed: u32,
+    /// Tests ignored
+    pub ignored: u32,
+    /// Sum of test durations in milliseconds
+    pub total_duration_ms: u64,
 }
 
 // ============================================================================
@@ -209,70 +446,50 @@ pub fn parse_list_output(json: &str) -> Result<TestList, TestsError> {
 ///
 /// # Errors
 ///
-/// Returns `TestsError::JsonParse` if any line is invalid JSON.
+/// Returns `TestsError::EmptyInput` if the output is empty or whitespace-only,
+/// or `TestsError::MalformedJson` if any line is invalid JSON.
 pub fn parse_run_output(output: &str) -> Result<TestRunSummary, TestsError> {
+    if output.trim().is_empty() {
+        return Err(TestsError::EmptyInput);
+    }
+
     let mut summary = TestRunSummary::empty();
     let mut pending_tests: HashMap<String, chrono::DateTime<Utc>> = HashMap::new();
     let now = Utc::now();
 
-    for line in output.lines() {
+    for (index, line) in output.lines().enumerate() {
         let line = line.trim();
         if line.is_empty() {
             continue;
         }
 
-        let event: LibtestEvent = serde_json::from_str(line)?;
+        let event: LibtestEvent =
+            serde_json::from_str(line).map_err(|e| malformed_json(index + 1, line, &e))?;
 
         match event {
-            LibtestEvent::Suite(suite) => {
-                if suite.event == "started" {
-                    if let Some(count) = suite.test_count {
-                        summary.total = count;
-                    }
-                } else {
-                    // Final summary event
-                    if let Some(passed) = suite.passed {
-                        summary.passed = passed;
-                    }
-                    if let Some(failed) = suite.failed {
-                        summary.failed = failed;
-                    }
-                    if let Some(ignored) = suite.ignored {
-                        summary.ignored = ignored;
-                    }
-                    if let Some(exec_time) = suite.exec_time {
-                        summary.exec_time_secs = exec_time;
-                    }
-                }
+            // Nextest emits one suite per test binary, so totals add up
+            LibtestEvent::SuiteStarted { test_count } => {
+                summary.total += test_count as usize;
+            }
+            LibtestEvent::SuiteFinished {
+                passed,
+                failed,
+                ignored,
+                exec_time,
+            } => {
+                summary.passed += passed as usize;
+                summary.failed += failed as usize;
+                summary.ignored += ignored as usize;
+                summary.exec_time_secs += exec_time.unwrap_or(0.0);
+            }
+            LibtestEvent::TestStarted { name } => {
+                pending_tests.insert(name, now);
             }
-            LibtestEvent::Test(test) => {
-                if test.event == "started" {
-                    pending_tests.insert(test.name.clone(), now);
-                } else {
-                    // Test finished
-                    let outcome = match test.event.as_str() {
-                        "ok" => TestOutcome::Passed,
-                        "failed" => TestOutcome::Failed,
-                        "ignored" => TestOutcome::Ignored,
-                        _ => TestOutcome::Failed,
-                    };
-
-                    let duration_ms = test.exec_time.map(|t| (t * 1000.0) as u64).unwrap_or(0);
-
-                    // Parse the name - nextest format: "binary-id::binary_name$test::path"
-                    let test_name = normalize_test_name(&test.name);
-
-                    let result = TestResult {
-                        name: test_name,
-                        outcome,
-                        duration_ms,
-                        timestamp: now,
-                        output: test.stdout,
-                    };
-
-                    summary.results.push(result);
-                    pending_tests.remove(&test.name);
+            event => {
+                if let Some(name) = event.test_name() {
+                    pending_tests.remove(name);
                 }
+                summary.results.extend(event.into_result(now));
             }
         }
     }
@@ -280,6 +497,71 @@ pub fn parse_run_output(output: &str) -> Result<TestRunSummary, TestsError> {
     Ok(summary)
 }
 
+/// Maximum characters of an offending line quoted in `TestsError::MalformedJson`
+const MALFORMED_CONTEXT_CHARS: usize = 80;
+
+/// Build a `TestsError::MalformedJson` quoting the start of the offending line
+pub(crate) fn malformed_json(line: usize, content: &str, error: &serde_json::Error) -> TestsError {
+    let mut snippet: String = content.chars().take(MALFORMED_CONTEXT_CHARS).collect();
+    if snippet.len() < content.len() {
+        snippet.push_str("...");
+    }
+    TestsError::MalformedJson {
+        line,
+        context: format!("{snippet} ({error})"),
+    }
+}
+
+/// A test's span in the event stream, measured in event positions
+///
+/// libtest JSON carries no wall-clock timestamps, but nextest emits a test's
+/// `started` event when it is spawned and its result event when it finishes.
+/// Two tests whose windows overlap therefore ran concurrently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventWindow {
+    /// Normalized test name (as in [`TestResult::name`])
+    pub name: String,
+    /// Position of the `started` event
+    pub started: usize,
+    /// Position of the result event
+    pub finished: usize,
+}
+
+/// Extract each test's event window from libtest JSON output
+///
+/// Positions count non-empty lines. A test with no `started` event gets a
+/// zero-width window at its result event.
+///
+/// # Errors
+///
+/// Returns `TestsError::JsonParse` if any line is invalid JSON.
+pub fn parse_event_windows(output: &str) -> Result<Vec<EventWindow>, TestsError> {
+    let mut windows = Vec::new();
+    let mut pending: HashMap<String, usize> = HashMap::new();
+
+    let lines = output.lines().map(str::trim).filter(|l| !l.is_empty());
+    for (position, line) in lines.enumerate() {
+        let event: LibtestEvent = serde_json::from_str(line)?;
+        match event {
+            LibtestEvent::TestStarted { name } => {
+                pending.insert(name, position);
+            }
+            event => {
+                if let Some(name) = event.test_name() {
+                    let started = pending.remove(name).unwrap_or(position);
+                    windows.push(EventWindow {
+                        name: normalize_test_name(name),
+                        started,
+                        finished: position,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(windows)
+}
+
 /// Normalize a nextest test name to a clean format
 ///
 /// Input: "hindsight-tests::hindsight_tests$result::tests::test_name"
@@ -296,13 +578,14 @@ fn normalize_test_name(name: &str) -> String {
     }
 }
 
-/// Parse a single libtest JSON event
+/// Parse a single line of libtest JSON output into an event
 ///
 /// # Errors
 ///
-/// Returns `TestsError::JsonParse` if the JSON is invalid.
-pub fn parse_event(json: &str) -> Result<LibtestEvent, TestsError> {
-    serde_json::from_str(json).map_err(TestsError::from)
+/// Returns `TestsError::JsonParse` if the JSON is invalid or is not a
+/// recognised libtest event.
+pub fn parse_libtest_event(line: &str) -> Result<LibtestEvent, TestsError> {
+    serde_json::from_str(line).map_err(TestsError::from)
 }
 
 // ============================================================================
@@ -310,10 +593,15 @@ pub fn parse_event(json: &str) -> Result<LibtestEvent, TestsError> {
 // ============================================================================
 
 /// A streaming parser for libtest JSON output
+///
+/// One parser can handle several runs in turn: take the summary with
+/// [`StreamingParser::summary`] once [`StreamingParser::is_run_complete`]
+/// returns `true`, then call [`StreamingParser::reset`] before the next run.
 pub struct StreamingParser {
     pending_tests: HashMap<String, chrono::DateTime<Utc>>,
     results: Vec<TestResult>,
     total: usize,
+    run_complete: bool,
 }
 
 impl StreamingParser {
@@ -324,9 +612,27 @@ impl StreamingParser {
             pending_tests: HashMap::new(),
             results: Vec::new(),
             total: 0,
+            run_complete: false,
         }
     }
 
+    /// Clear all state so the parser can process another run
+    ///
+    /// Keeps the allocated buffers, so reusing a parser is cheaper than
+    /// creating a new one.
+    pub fn reset(&mut self) {
+        self.pending_tests.clear();
+        self.results.clear();
+        self.total = 0;
+        self.run_complete = false;
+    }
+
+    /// Check whether the `suite finished` event has been processed
+    #[must_use]
+    pub fn is_run_complete(&self) -> bool {
+        self.run_complete
+    }
+
     /// Process a single line of output
     ///
     /// # Errors
@@ -342,42 +648,27 @@ impl StreamingParser {
         let now = Utc::now();
 
         match event {
-            LibtestEvent::Suite(suite) => {
-                if suite.event == "started"
-                    && let Some(count) = suite.test_count
-                {
-                    self.total = count;
-                }
+            LibtestEvent::SuiteStarted { test_count } => {
+                self.total = test_count as usize;
                 Ok(None)
             }
-            LibtestEvent::Test(test) => {
-                if test.event == "started" {
-                    self.pending_tests.insert(test.name.clone(), now);
-                    Ok(None)
-                } else {
-                    let outcome = match test.event.as_str() {
-                        "ok" => TestOutcome::Passed,
-                        "failed" => TestOutcome::Failed,
-                        "ignored" => TestOutcome::Ignored,
-                        _ => TestOutcome::Failed,
-                    };
-
-                    let duration_ms = test.exec_time.map(|t| (t * 1000.0) as u64).unwrap_or(0);
-
-                    let test_name = normalize_test_name(&test.name);
-
-                    let result = TestResult {
-                        name: test_name,
-                        outcome,
-                        duration_ms,
-                        timestamp: now,
-                        output: test.stdout,
-                    };
-
+            LibtestEvent::SuiteFinished { .. } => {
+                self.run_complete = true;
+                Ok(None)
+            }
+            LibtestEvent::TestStarted { name } => {
+                self.pending_tests.insert(name, now);
+                Ok(None)
+            }
+            event => {
+                if let Some(name) = event.test_name() {
+                    self.pending_tests.remove(name);
+                }
+                let result = event.into_result(now);
+                if let Some(result) = &result {
                     self.results.push(result.clone());
-                    self.pending_tests.remove(&test.name);
-                    Ok(Some(result))
                 }
+                Ok(result)
             }
         }
     }
@@ -388,27 +679,39 @@ impl StreamingParser {
         &self.results
     }
 
+    /// Build a summary of the current run without consuming the parser
+    #[must_use]
+    pub fn summary(&self) -> TestRunSummary {
+        build_summary(self.total, self.results.clone())
+    }
+
     /// Finalize and return summary
     #[must_use]
     pub fn into_summary(self) -> TestRunSummary {
-        let passed = self.results.iter().filter(|r| r.passed()).count();
-        let failed = self.results.iter().filter(|r| r.failed()).count();
-        let ignored = self
-            .results
-            .iter()
-            .filter(|r| r.outcome == TestOutcome::Ignored)
-            .count();
-        let exec_time_secs =
-            self.results.iter().map(|r| r.duration_ms).sum::<u64>() as f64 / 1000.0;
-
-        TestRunSummary {
-            total: self.total,
-            passed,
-            failed,
-            ignored,
-            exec_time_secs,
-            results: self.results,
-        }
+        build_summary(self.total, self.results)
+    }
+}
+
+/// Count outcomes and total the execution time of a run's results
+fn build_summary(total: usize, results: Vec<TestResult>) -> TestRunSummary {
+    let passed = results.iter().filter(|r| r.passed()).count();
+    let failed = results
+        .iter()
+        .filter(|r| matches!(r.outcome, TestOutcome::Failed | TestOutcome::TimedOut))
+        .count();
+    let ignored = results
+        .iter()
+        .filter(|r| r.outcome == TestOutcome::Ignored)
+        .count();
+    let exec_time_secs = results.iter().map(|r| r.duration_ms).sum::<u64>() as f64 / 1000.0;
+
+    TestRunSummary {
+        total,
+        passed,
+        failed,
+        ignored,
+        exec_time_secs,
+        results,
     }
 }
 
@@ -447,6 +750,105 @@ mod tests {
         assert_eq!(list.ignored_count(), 1);
     }
 
+    #[test]
+    fn test_parse_list_output_count_by_suite() {
+        let json = r#"{
+            "test-count": 5,
+            "rust-suites": {
+                "my-crate": {
+                    "package-name": "my-crate",
+                    "binary-id": "my-crate",
+                    "binary-name": "my_crate",
+                    "kind": "lib",
+                    "testcases": {
+                        "tests::r#type": {"kind": "test", "ignored": false},
+                        "tests::handles_ünïcode": {"kind": "test", "ignored": false},
+                        "proptest::prop_roundtrip::{closure}": {"kind": "test", "ignored": true}
+                    }
+                },
+                "my-crate::integration tests": {
+                    "package-name": "my-crate",
+                    "binary-id": "my-crate::integration tests",
+                    "binary-name": "integration tests",
+                    "kind": "test",
+                    "testcases": {
+                        "quoted \"name\"": {"kind": "test", "ignored": false, "filter-match": {"status": "matches"}},
+                        "path/with/slashes": {"kind": "test", "ignored": false}
+                    }
+                },
+                "my-crate::bin/empty": {
+                    "package-name": "my-crate",
+                    "binary-id": "my-crate::bin/empty",
+                    "binary-name": "empty",
+                    "kind": "bin",
+                    "testcases": {}
+                }
+            }
+        }"#;
+
+        let list = parse_list_output(json).expect("Should parse");
+        let counts = list.count_by_suite();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts["my-crate"], 3);
+        assert_eq!(counts["my-crate::integration tests"], 2);
+        assert_eq!(counts["my-crate::bin/empty"], 0);
+        assert_eq!(counts.values().sum::<usize>(), list.test_count);
+        assert!(
+            list.tests_in_suite("my-crate::integration tests")
+                .contains(&"quoted \"name\"")
+        );
+    }
+
+    #[test]
+    fn test_run_summary_suites() {
+        let output = r#"{"type":"suite","event":"started","test_count":4}
+{"type":"test","event":"ok","name":"my-crate::my_crate$parser::tests::test_one","exec_time":0.010}
+{"type":"test","event":"failed","name":"my-crate::my_crate$parser::tests::test_two","exec_time":0.020}
+{"type":"test","event":"ignored","name":"my-crate::my_crate$parser::tests::test_three"}
+{"type":"test","event":"ok","name":"my-crate::my_crate$lexer::tests::test_four","exec_time":0.005}
+{"type":"suite","event":"failed","passed":2,"failed":1,"ignored":1,"exec_time":0.035}"#;
+
+        let summary = parse_run_output(output).expect("Should parse");
+        let suites = summary.suites();
+        assert_eq!(suites.len(), 2);
+        assert_eq!(
+            suites["parser"],
+            TestSuiteSummary {
+                name: "parser".to_string(),
+                passed: 1,
+                failed: 1,
+                ignored: 1,
+                total_duration_ms: 30,
+            }
+        );
+        assert_eq!(
+            suites["lexer"],
+            TestSuiteSummary {
+                name: "lexer".to_string(),
+                passed: 1,
+                failed: 0,
+                ignored: 0,
+                total_duration_ms: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_run_output_multiple_suites() {
+        let output = r#"{"type":"suite","event":"started","test_count":2}
+{"type":"test","event":"ok","name":"my-crate::my_crate$tests::test_one","exec_time":0.25}
+{"type":"test","event":"failed","name":"my-crate::my_crate$tests::test_two","exec_time":0.5}
+{"type":"suite","event":"failed","passed":1,"failed":1,"ignored":0,"exec_time":0.75}
+{"type":"suite","event":"started","test_count":1}
+{"type":"test","event":"ok","name":"my-crate::integration$test_three","exec_time":1.25}
+{"type":"suite","event":"ok","passed":1,"failed":0,"ignored":0,"exec_time":1.25}"#;
+
+        let summary = parse_run_output(output).expect("Should parse");
+        assert_eq!(summary.total, 3);
+        assert_eq!((summary.passed, summary.failed, summary.ignored), (2, 1, 0));
+        assert!((summary.exec_time_secs - 2.0).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn test_parse_run_output_single_test() {
         let output = r#"{"type":"suite","event":"started","test_count":1}
@@ -496,6 +898,41 @@ mod tests {
         assert_eq!(summary.results.len(), 3);
     }
 
+    #[test]
+    fn test_parse_event_windows_overlapping() {
+        let output = r#"{"type":"suite","event":"started","test_count":3}
+{"type":"test","event":"started","name":"c::b$test_a"}
+{"type":"test","event":"started","name":"c::b$test_b"}
+{"type":"test","event":"ok","name":"c::b$test_a","exec_time":0.001}
+{"type":"test","event":"started","name":"c::b$test_c"}
+{"type":"test","event":"ok","name":"c::b$test_b","exec_time":0.002}
+
+{"type":"test","event":"ignored","name":"c::b$test_c"}
+{"type":"suite","event":"ok","passed":2,"failed":0,"ignored":1,"exec_time":0.003}"#;
+
+        let windows = parse_event_windows(output).expect("Should parse");
+        assert_eq!(
+            windows,
+            vec![
+                EventWindow {
+                    name: "test_a".to_string(),
+                    started: 1,
+                    finished: 3
+                },
+                EventWindow {
+                    name: "test_b".to_string(),
+                    started: 2,
+                    finished: 5
+                },
+                EventWindow {
+                    name: "test_c".to_string(),
+                    started: 4,
+                    finished: 6
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_normalize_test_name() {
         assert_eq!(
@@ -533,6 +970,52 @@ mod tests {
         assert_eq!(summary.passed, 1);
     }
 
+    #[test]
+    fn test_streaming_parser_reuse() {
+        let first_run = [
+            r#"{"type":"suite","event":"started","test_count":2}"#,
+            r#"{"type":"test","event":"started","name":"c::b$a"}"#,
+            r#"{"type":"test","event":"ok","name":"c::b$a","exec_time":0.01}"#,
+            r#"{"type":"test","event":"started","name":"c::b$b"}"#,
+            r#"{"type":"test","event":"failed","name":"c::b$b","exec_time":0.02}"#,
+            r#"{"type":"suite","event":"failed","passed":1,"failed":1,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.03}"#,
+        ];
+        let second_run = [
+            r#"{"type":"suite","event":"started","test_count":1}"#,
+            r#"{"type":"test","event":"started","name":"c::b$c"}"#,
+            r#"{"type":"test","event":"ok","name":"c::b$c","exec_time":0.5}"#,
+            r#"{"type":"suite","event":"ok","passed":1,"failed":0,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.5}"#,
+        ];
+
+        let mut parser = StreamingParser::new();
+        assert!(!parser.is_run_complete());
+
+        for line in first_run {
+            parser.process_line(line).expect("Should parse");
+        }
+        assert!(parser.is_run_complete());
+        let first = parser.summary();
+
+        parser.reset();
+        assert!(!parser.is_run_complete());
+        assert!(parser.results().is_empty());
+
+        for line in second_run {
+            parser.process_line(line).expect("Should parse");
+        }
+        assert!(parser.is_run_complete());
+        let second = parser.into_summary();
+
+        assert_eq!((first.total, first.passed, first.failed), (2, 1, 1));
+        let first_names: Vec<&str> = first.results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(first_names, vec!["a", "b"]);
+
+        assert_eq!((second.total, second.passed, second.failed), (1, 1, 0));
+        assert_eq!(second.results.len(), 1);
+        assert_eq!(second.results[0].name, "c");
+        assert_eq!(second.exec_time_secs, 0.5);
+    }
+
     #[test]
     fn test_test_run_summary_helpers() {
         let summary = TestRunSummary {
@@ -603,14 +1086,113 @@ mod tests {
 
     #[test]
     fn test_parse_empty_output() {
-        let summary = parse_run_output("").expect("Should parse empty");
-        assert_eq!(summary.total, 0);
+        let result = parse_run_output("");
+        assert!(matches!(result, Err(TestsError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_parse_whitespace_only_output() {
+        let result = parse_run_output("  \n\t\n  ");
+        assert!(matches!(result, Err(TestsError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_parse_valid_output_is_ok() {
+        let output = r#"{"type":"suite","event":"started","test_count":0}"#;
+        let summary = parse_run_output(output).expect("Should parse");
         assert_eq!(summary.results.len(), 0);
     }
 
     #[test]
     fn test_parse_invalid_json() {
         let result = parse_run_output("not json");
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(TestsError::MalformedJson { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_malformed_json_reports_line() {
+        let output = "{\"type\":\"suite\",\"event\":\"started\",\"test_count\":1}\n\n{broken";
+        match parse_run_output(output) {
+            Err(TestsError::MalformedJson { line, context }) => {
+                assert_eq!(line, 3);
+                assert!(context.starts_with("{broken"));
+            }
+            other => panic!("expected MalformedJson, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_libtest_event_variants_roundtrip() {
+        let events = vec![
+            LibtestEvent::SuiteStarted { test_count: 3 },
+            LibtestEvent::SuiteFinished {
+                passed: 1,
+                failed: 1,
+                ignored: 1,
+                exec_time: Some(0.5),
+            },
+            LibtestEvent::TestStarted {
+                name: "c::b$test_a".to_string(),
+            },
+            LibtestEvent::TestPassed {
+                name: "c::b$test_a".to_string(),
+                exec_time: 0.25,
+            },
+            LibtestEvent::TestFailed {
+                name: "c::b$test_b".to_string(),
+                exec_time: 0.125,
+                stdout: Some("assertion failed".to_string()),
+                stderr: Some("backtrace".to_string()),
+            },
+            LibtestEvent::TestIgnored {
+                name: "c::b$test_c".to_string(),
+            },
+            LibtestEvent::TestTimeout {
+                name: "c::b$test_d".to_string(),
+            },
+        ];
+
+        for event in events {
+            let json = serde_json::to_string(&event).expect("Should serialize");
+            let parsed = parse_libtest_event(&json).expect("Should parse");
+            assert_eq!(parsed, event, "roundtrip of {json}");
+        }
+    }
+
+    #[test]
+    fn test_parse_libtest_event_wire_format() {
+        let event = parse_libtest_event(
+            r#"{"type":"suite","event":"failed","passed":2,"failed":1,"ignored":0}"#,
+        )
+        .expect("Should parse");
+        assert_eq!(
+            event,
+            LibtestEvent::SuiteFinished {
+                passed: 2,
+                failed: 1,
+                ignored: 0,
+                exec_time: None,
+            }
+        );
+
+        let event = parse_libtest_event(r#"{"type":"test","event":"timeout","name":"c::b$slow"}"#)
+            .expect("Should parse");
+        assert_eq!(event.test_name(), Some("c::b$slow"));
+
+        assert!(parse_libtest_event(r#"{"type":"bench","event":"ok"}"#).is_err());
+        assert!(parse_libtest_event(r#"{"type":"test","event":"ok"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_run_output_timeout() {
+        let output = r#"{"type":"test","event":"started","name":"c::b$slow"}
+{"type":"test","event":"timeout","name":"c::b$slow"}"#;
+
+        let summary = parse_run_output(output).expect("Should parse");
+        assert_eq!(summary.results.len(), 1);
+        assert_eq!(summary.results[0].outcome, TestOutcome::TimedOut);
     }
 }